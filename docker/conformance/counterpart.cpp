@@ -0,0 +1,30 @@
+// roscpp counterpart node for roslibrust's conformance tests, see roslibrust/tests/conformance.rs
+#include <ros/ros.h>
+#include <std_msgs/String.h>
+#include <std_srvs/SetBool.h>
+
+bool setBool(std_srvs::SetBool::Request& request, std_srvs::SetBool::Response& response)
+{
+  response.success = request.data;
+  response.message = "roscpp";
+  return true;
+}
+
+int main(int argc, char** argv)
+{
+  ros::init(argc, argv, "conformance_roscpp");
+  ros::NodeHandle nh("/conformance/roscpp");
+
+  ros::Publisher echo = nh.advertise<std_msgs::String>("echo_out", 10);
+  ros::Subscriber echo_in = nh.subscribe<std_msgs::String>(
+      "echo_in", 10, [&echo](const std_msgs::String::ConstPtr& msg) { echo.publish(*msg); });
+
+  ros::Publisher latched = nh.advertise<std_msgs::String>("latched", 1, true);
+  std_msgs::String msg;
+  msg.data = "latched by roscpp";
+  latched.publish(msg);
+
+  ros::ServiceServer set_bool = nh.advertiseService("set_bool", setBool);
+  ros::spin();
+  return 0;
+}
@@ -0,0 +1,27 @@
+#!/usr/bin/env python3
+# rospy counterpart node for roslibrust's conformance tests, see roslibrust/tests/conformance.rs
+import rospy
+from std_msgs.msg import String
+from std_srvs.srv import SetBool, SetBoolResponse
+
+
+def main():
+    rospy.init_node("conformance_rospy")
+    namespace = "/conformance/rospy"
+
+    echo = rospy.Publisher(namespace + "/echo_out", String, queue_size=10)
+    rospy.Subscriber(namespace + "/echo_in", String, echo.publish)
+
+    latched = rospy.Publisher(namespace + "/latched", String, queue_size=1, latch=True)
+    latched.publish(String(data="latched by rospy"))
+
+    rospy.Service(
+        namespace + "/set_bool",
+        SetBool,
+        lambda request: SetBoolResponse(success=request.data, message="rospy"),
+    )
+    rospy.spin()
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,8 @@
+#!/bin/bash
+# Runs a master along with every counterpart node, exiting if any of them do
+source /opt/ros/noetic/setup.bash
+roscore &
+until rostopic list > /dev/null 2>&1; do sleep 0.5; done
+python3 /conformance/counterpart.py &
+/conformance/counterpart_roscpp &
+wait -n
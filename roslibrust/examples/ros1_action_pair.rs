@@ -0,0 +1,92 @@
+roslibrust_codegen_macro::find_and_generate_ros_messages!("assets/ros1_common_interfaces");
+
+/// This example shows an action server and an ActionClient sending it goals natively over TCPROS.
+/// roslibrust does not provide an action server, so this one is written by hand on the action's topics,
+/// answering each nav_msgs/GetMap goal with an empty map.
+/// To run this example a roscore should be running:
+/// `cargo run --example ros1_action_pair --features ros1`
+#[cfg(feature = "ros1")]
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use roslibrust::NodeHandle;
+    use std::time::Duration;
+
+    simple_logger::SimpleLogger::new()
+        .with_level(log::LevelFilter::Debug)
+        .without_timestamps()
+        .init()
+        .unwrap();
+
+    // The server side, following the actionlib protocol on the topics beneath /make_map
+    let server = NodeHandle::new("http://localhost:11311", "map_server_rs").await?;
+    let status = server
+        .advertise::<actionlib_msgs::GoalStatusArray>("/make_map/status", 1)
+        .await?;
+    let result = server
+        .advertise::<nav_msgs::GetMapActionResult>("/make_map/result", 1)
+        .await?;
+    let goals = server
+        .subscribe::<nav_msgs::GetMapActionGoal>("/make_map/goal", 1)
+        .await?;
+    let _cancels = server
+        .subscribe::<actionlib_msgs::GoalID>("/make_map/cancel", 1)
+        .await?;
+    server.spawn(async move {
+        use futures::StreamExt;
+        let mut goals = Box::pin(goals.into_stream());
+        // Action servers publish their status periodically, which is how clients find them
+        let mut heartbeat = tokio::time::interval(Duration::from_millis(200));
+        loop {
+            tokio::select! {
+                _ = heartbeat.tick() => {
+                    let _ = status.publish(&Default::default()).await;
+                }
+                Some(goal) = goals.next() => {
+                    log::info!("Server received goal {}", goal.goal_id.id);
+                    let mut map = nav_msgs::OccupancyGrid::default();
+                    map.info.width = 4;
+                    map.info.height = 4;
+                    map.data = vec![0; 16];
+                    let _ = result
+                        .publish(&nav_msgs::GetMapActionResult {
+                            header: Default::default(),
+                            status: actionlib_msgs::GoalStatus {
+                                goal_id: goal.goal_id,
+                                status: actionlib_msgs::GoalStatus::SUCCEEDED,
+                                text: "Map ready".to_owned(),
+                            },
+                            result: nav_msgs::GetMapResult { map },
+                        })
+                        .await;
+                }
+            }
+        }
+    });
+
+    // The client side
+    let nh = NodeHandle::new("http://localhost:11311", "map_client_rs").await?;
+    let client = nh
+        .action_client::<nav_msgs::GetMapAction>("/make_map")
+        .await?;
+    client.wait_for_server(Some(Duration::from_secs(5))).await?;
+
+    for _ in 0..3 {
+        let mut goal = client.send_goal(nav_msgs::GetMapGoal {}).await?;
+        let outcome = goal.result().await?;
+        log::info!(
+            "Goal {} finished {:?} ({}) with a map {}x{}",
+            goal.goal_id(),
+            outcome.state,
+            outcome.text,
+            outcome.result.map.info.width,
+            outcome.result.map.info.height
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "ros1"))]
+fn main() {
+    eprintln!("This example does nothing without compiling with the feature 'ros1'");
+}
@@ -0,0 +1,86 @@
+/**
+ * Command line front end for the bag utilities provided by roslibrust
+ *
+ * Usage:
+ *   ros1_bag_tool filter <input> <output> [--topic <topic>]... [--start <secs>] [--end <secs>]
+ *   ros1_bag_tool merge <output> <input>...
+ *   ros1_bag_tool split <input> <output_prefix> <duration_secs>
+ *   ros1_bag_tool reindex <input> <output>
+ */
+#[cfg(feature = "ros1")]
+fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use roslibrust::BagFilter;
+    use roslibrust_codegen::Time;
+
+    simple_logger::SimpleLogger::new()
+        .with_level(log::LevelFilter::Info)
+        .without_timestamps() // required for running wsl2
+        .init()
+        .unwrap();
+
+    fn parse_time(secs: &str) -> Result<Time, Box<dyn std::error::Error + Send + Sync>> {
+        let secs: f64 = secs.parse()?;
+        Ok(Time {
+            secs: secs.trunc() as u32,
+            nsecs: (secs.fract() * 1e9) as u32,
+        })
+    }
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["filter", input, output, options @ ..] => {
+            let mut filter = BagFilter::new();
+            let mut topics = vec![];
+            let mut options = options.iter();
+            while let Some(option) = options.next() {
+                let value = options
+                    .next()
+                    .ok_or(format!("Missing value for {option}"))?;
+                match *option {
+                    "--topic" => topics.push(value.to_string()),
+                    "--start" => filter = filter.start_time(parse_time(value)?),
+                    "--end" => filter = filter.end_time(parse_time(value)?),
+                    _ => return Err(format!("Unknown option {option}").into()),
+                }
+            }
+            if !topics.is_empty() {
+                filter = filter.topics(topics);
+            }
+            let count = filter.apply(input, output)?;
+            log::info!("Wrote {count} messages to {output}");
+        }
+        ["merge", output, inputs @ ..] if !inputs.is_empty() => {
+            let count = roslibrust::merge_bags(inputs, output)?;
+            log::info!("Merged {count} messages into {output}");
+        }
+        ["split", input, output_prefix, duration] => {
+            let duration = std::time::Duration::from_secs_f64(duration.parse()?);
+            for path in roslibrust::split_bag(input, output_prefix, duration)? {
+                log::info!("Wrote {}", path.display());
+            }
+        }
+        ["reindex", input, output] => {
+            let count = roslibrust::reindex_bag(input, output)?;
+            log::info!("Recovered {count} messages into {output}");
+        }
+        _ => {
+            eprintln!("Usage:");
+            eprintln!("  ros1_bag_tool filter <input> <output> [--topic <topic>]... [--start <secs>] [--end <secs>]");
+            eprintln!("  ros1_bag_tool merge <output> <input>...");
+            eprintln!("  ros1_bag_tool split <input> <output_prefix> <duration_secs>");
+            eprintln!("  ros1_bag_tool reindex <input> <output>");
+            std::process::exit(1);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "ros1"))]
+fn main() {
+    // Provide a dummy main for this example when ros1 is disabled
+}
@@ -0,0 +1,55 @@
+/// This example is a `rostopic echo` for topics of any type, without generating any messages.
+/// Messages are received left serialized, with their type and definition taken from the publisher's connection header.
+/// To run this example a roscore and a publisher should be running, e.g. the ros1_talker example:
+/// `cargo run --example ros1_dynamic_echo --features ros1 -- /chatter`
+#[cfg(feature = "ros1")]
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use roslibrust::NodeHandle;
+
+    simple_logger::SimpleLogger::new()
+        .with_level(log::LevelFilter::Info)
+        .without_timestamps()
+        .init()
+        .unwrap();
+
+    let topic = std::env::args().nth(1).unwrap_or("/chatter".to_owned());
+    let nh = NodeHandle::new("http://localhost:11311", "dynamic_echo_rs").await?;
+    let mut subscriber = nh.subscribe_any(&topic, 10).await?;
+
+    let mut known_type = None;
+    loop {
+        let msg = match subscriber.next().await {
+            Ok(msg) => msg,
+            Err(err) => {
+                log::info!("Stopped echoing {topic}: {err}");
+                break;
+            }
+        };
+        let header = &msg.publisher;
+        // Publishers of a topic normally agree on its type, print the definition whenever it changes
+        if known_type.as_ref() != Some(&header.md5sum) {
+            log::info!(
+                "{topic} is a {} [{}], defined as:\n{}",
+                header.topic_type,
+                header.md5sum,
+                header.msg_definition
+            );
+            known_type = Some(header.md5sum.clone());
+        }
+        log::info!(
+            "{} bytes from {}{}: {:02x?}",
+            msg.data.len(),
+            header.caller_id,
+            if msg.latched { " (latched)" } else { "" },
+            &msg.data[..msg.data.len().min(32)]
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "ros1"))]
+fn main() {
+    eprintln!("This example does nothing without compiling with the feature 'ros1'");
+}
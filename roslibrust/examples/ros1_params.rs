@@ -0,0 +1,60 @@
+/// This example shows using the parameter server natively, without rosbridge.
+/// To run this example a roscore should be running.
+/// Parameters are set, read back, searched for and watched for changes.
+#[cfg(feature = "ros1")]
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use roslibrust::NodeHandle;
+
+    /// Any type serde can represent works as a parameter, structs are stored as dictionaries
+    #[derive(serde::Serialize, serde::Deserialize, Debug)]
+    struct Gains {
+        p: f64,
+        i: f64,
+        d: f64,
+    }
+
+    simple_logger::SimpleLogger::new()
+        .with_level(log::LevelFilter::Debug)
+        .without_timestamps()
+        .init()
+        .unwrap();
+
+    let nh = NodeHandle::new("http://localhost:11311", "params_rs").await?;
+
+    let gains = Gains {
+        p: 1.0,
+        i: 0.1,
+        d: 0.01,
+    };
+    nh.set_param("/params_rs/gains", &gains).await?;
+    let gains: Gains = nh.get_param("/params_rs/gains").await?;
+    log::info!("Got gains: {gains:?}");
+
+    // Each member of a dictionary is a parameter in its own right
+    let p: f64 = nh.get_param("/params_rs/gains/p").await?;
+    log::info!("Got proportional gain: {p}");
+
+    // Searching looks in our namespace first, then its parents
+    if let Some(key) = nh.search_param("gains").await? {
+        log::info!("Found gains at {key}");
+    }
+
+    // Watching a parameter gives its current value, followed by a value for every change.
+    // Try changing it from the command line with `rosparam set /params_rs/gains/p 2.0`
+    let mut watcher = nh.subscribe_param::<Gains>("~gains").await?;
+    log::info!("Watching {}", watcher.key());
+    log::info!("Current value: {:?}", watcher.next().await?);
+    nh.set_param("/params_rs/gains/p", &2.0).await?;
+    log::info!("Updated value: {:?}", watcher.next().await?);
+    nh.delete_param("/params_rs/gains").await?;
+    log::info!("Deleted value: {:?}", watcher.next().await?);
+
+    nh.shutdown().await;
+    Ok(())
+}
+
+#[cfg(not(feature = "ros1"))]
+fn main() {
+    eprintln!("This example does nothing without compiling with the feature 'ros1'");
+}
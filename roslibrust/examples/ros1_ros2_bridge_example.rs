@@ -19,6 +19,12 @@ mod ros2 {
     );
 }
 
+// Generates `From` conversions between the ros1 and ros2 versions of each message
+roslibrust_codegen_macro::generate_ros1_ros2_conversions! {
+    crate::ros1: "assets/ros1_common_interfaces/std_msgs";
+    crate::ros2: "assets/ros2_common_interfaces/std_msgs";
+}
+
 /// The goal of this example is to create a "baby bridge" that will listen to a ros1 message,
 /// and re-publish it to ros2.
 ///
@@ -86,11 +92,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 info!("Got a message from ros1 {msg:?}");
 
                 // We got a new message!
-                // Covert it to the ros2 format
-                let converted_msg = ros2::std_msgs::Header {
-                    stamp: msg.stamp,
-                    frame_id: msg.frame_id,
-                };
+                // Covert it to the ros2 format, ros2 headers have no seq so it is dropped
+                let converted_msg: ros2::std_msgs::Header = msg.into();
 
                 // and re-publish it!
                 publisher.publish(converted_msg).await?;
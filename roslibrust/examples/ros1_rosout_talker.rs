@@ -0,0 +1,50 @@
+roslibrust_codegen_macro::find_and_generate_ros_messages!("assets/ros1_common_interfaces/std_msgs");
+
+/// This example shows a native talker making use of the node's supporting subsystems:
+/// its logs are published to /rosout, it publishes on a timer driven by the node's Clock,
+/// and it shuts down gracefully on ctrl-c or when another node takes its name.
+/// To run this example a roscore should be running, try watching its logs with rqt_console.
+/// Setting `rosparam set /use_sim_time true` before starting it, then running `rosbag play --clock <bag>`,
+/// makes it publish according to the bag's time.
+#[cfg(feature = "ros1")]
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use roslibrust::{NodeHandle, RosoutLogger};
+
+    let nh = NodeHandle::new("http://localhost:11311", "rosout_talker_rs").await?;
+    RosoutLogger::new(&nh)
+        .await?
+        .with_fallback(simple_logger::SimpleLogger::new().without_timestamps())
+        .init(log::LevelFilter::Info)?;
+    if nh.clock().is_sim_time() {
+        log::info!("Following simulated time from /clock");
+    }
+
+    let publisher = nh.advertise::<std_msgs::String>("chatter", 1).await?;
+    let mut interval = nh.clock().interval(std::time::Duration::from_millis(500));
+    loop {
+        tokio::select! {
+            stamp = interval.tick() => {
+                let data = format!("hello world from rust at {}.{:09}", stamp.secs, stamp.nsecs);
+                publisher.publish(&std_msgs::String { data: data.clone() }).await?;
+                log::info!("Published: {data}");
+            }
+            _ = tokio::signal::ctrl_c() => {
+                log::info!("Interrupted, unregistering from the master");
+                nh.shutdown().await;
+                break;
+            }
+            reason = nh.wait_for_shutdown() => {
+                log::error!("Node shut down: {reason}");
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "ros1"))]
+fn main() {
+    eprintln!("This example does nothing without compiling with the feature 'ros1'");
+}
@@ -0,0 +1,34 @@
+roslibrust_codegen_macro::find_and_generate_ros_messages!("assets/ros1_common_interfaces");
+
+/// This example shows calling a service natively over TCPROS, without rosbridge
+/// To run this example a roscore and the rosapi node should be running
+/// This node calls a service on the rosapi node to get the current ros time.
+#[cfg(feature = "ros1")]
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use roslibrust::NodeHandle;
+
+    simple_logger::SimpleLogger::new()
+        .with_level(log::LevelFilter::Debug)
+        .without_timestamps()
+        .init()
+        .unwrap();
+
+    let nh = NodeHandle::new("http://localhost:11311", "service_client_rs").await?;
+    let client = nh.service_client::<rosapi::GetTime>("/rosapi/get_time", true);
+
+    let header = client.probe().await?;
+    log::info!("Service is provided by {}", header.caller_id);
+
+    for _ in 0..3 {
+        let response = client.call(&rosapi::GetTimeRequest {}).await?;
+        log::info!("Got time: {:?}", response.time);
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "ros1"))]
+fn main() {
+    eprintln!("This example does nothing without compiling with the feature 'ros1'");
+}
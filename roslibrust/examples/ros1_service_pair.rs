@@ -0,0 +1,68 @@
+roslibrust_codegen_macro::find_and_generate_ros_messages!("assets/ros1_common_interfaces");
+
+/// This example shows a service server and a client calling it natively over TCPROS.
+/// roslibrust does not host ROS1 services natively yet, so the server is advertised through rosbridge,
+/// which registers it with the master where the native client finds it like any other service.
+/// To run this example a roscore and a rosbridge_server with websockets at port 9090 should be running:
+/// `cargo run --example ros1_service_pair --features ros1`
+#[cfg(all(feature = "ros1", feature = "rosbridge"))]
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use roslibrust::{ClientHandle, NodeHandle};
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    simple_logger::SimpleLogger::new()
+        .with_level(log::LevelFilter::Debug)
+        .without_timestamps()
+        .init()
+        .unwrap();
+
+    // The server side, holding a bool the client sets
+    let bridge = ClientHandle::new("ws://localhost:9090").await?;
+    let state = Arc::new(AtomicBool::new(false));
+    let server_state = state.clone();
+    // Dropping the handle unadvertises the service
+    let _service = bridge
+        .advertise_service::<std_srvs::SetBool, _>(
+            "/set_enabled",
+            move |request: std_srvs::SetBoolRequest| -> Result<
+                std_srvs::SetBoolResponse,
+                Box<dyn std::error::Error + Send + Sync>,
+            > {
+                let previous = server_state.swap(request.data, Ordering::SeqCst);
+                Ok(std_srvs::SetBoolResponse {
+                    success: previous != request.data,
+                    message: format!("Enabled was {previous}, now {}", request.data),
+                })
+            },
+        )
+        .await?;
+
+    // The client side, calling the service directly over TCPROS
+    let nh = NodeHandle::new("http://localhost:11311", "service_pair_rs").await?;
+    nh.wait_for_service("/set_enabled", Some(std::time::Duration::from_secs(5)))
+        .await?;
+    let client = nh.service_client::<std_srvs::SetBool>("/set_enabled", true);
+
+    for data in [true, true, false] {
+        let response = client.call(&std_srvs::SetBoolRequest { data }).await?;
+        log::info!(
+            "Set enabled to {data}: changed={}, {}",
+            response.success,
+            response.message
+        );
+    }
+    log::info!("Final state: {}", state.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[cfg(not(all(feature = "ros1", feature = "rosbridge")))]
+fn main() {
+    eprintln!(
+        "This example does nothing without compiling with the features 'ros1' and 'rosbridge'"
+    );
+}
@@ -0,0 +1,397 @@
+//! Configuration files for creating a [crate::ClientHandle] or ROS1 native [crate::NodeHandle],
+//! so deployments can tune behavior without recompiling.
+//!
+//! A configuration file is TOML with a `[rosbridge]` section, a `[ros1]` section, or both.
+//! Every field other than `url` and `name` is optional:
+//! ```toml
+//! [rosbridge]
+//! url = "ws://localhost:9090"
+//! timeout_secs = 5.0
+//! encoding = "json"   # or "bson" with the bson feature
+//! cached_services = { "/rosapi/topic_type" = 10.0 }
+//!
+//! [rosbridge.reconnect]
+//! initial_delay_secs = 0.2
+//! max_delay_secs = 5.0
+//! max_attempts = 10
+//!
+//! [ros1]
+//! name = "/talker"
+//! master_uri = "http://localhost:11311"   # defaults to ROS_MASTER_URI, then http://localhost:11311
+//! anonymous = false
+//! bind_host = "192.168.1.10"   # takes precedence over ROS_IP and ROS_HOSTNAME
+//! default_queue_size = 10
+//! remappings = { chatter = "/robot/chatter" }
+//!
+//! [ros1.topics."/camera/image"]
+//! queue_size = 1
+//! tcp_nodelay = true
+//! ```
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! let config = roslibrust::config::Config::from_file("robot.toml")?;
+//! let client = roslibrust::ClientHandle::new_with_options(config.rosbridge_options()?).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{reconnect::DEFAULT_RECONNECT_DELAY, ClientHandleOptions, Encoding, ReconnectPolicy};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path, time::Duration};
+
+const DEFAULT_MASTER_URI: &str = "http://localhost:11311";
+
+/// Errors which can occur while loading a configuration file
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read configuration file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to parse configuration: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Invalid configuration value for {field}: {reason}")]
+    Invalid { field: String, reason: String },
+    #[error("Configuration has no [{0}] section")]
+    MissingSection(&'static str),
+}
+
+/// The contents of a configuration file, see the [module documentation](self) for the format
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub rosbridge: Option<RosbridgeConfig>,
+    pub ros1: Option<Ros1Config>,
+}
+
+/// Configuration of a [crate::ClientHandle]
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RosbridgeConfig {
+    /// Fully described websocket url, e.g. `ws://localhost:9090`
+    pub url: String,
+    /// See [ClientHandleOptions::timeout]
+    pub timeout_secs: Option<f64>,
+    #[serde(default)]
+    pub encoding: Encoding,
+    /// Services whose responses are cached, with the time to cache them for in seconds,
+    /// see [ClientHandleOptions::cache_service_responses]
+    #[serde(default)]
+    pub cached_services: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub reconnect: ReconnectConfig,
+}
+
+/// Configuration of a [ReconnectPolicy]
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ReconnectConfig {
+    /// Delay before the first retry, defaults to 0.2 seconds
+    pub initial_delay_secs: Option<f64>,
+    /// The delay doubles after each failed attempt up to this, defaults to the initial delay
+    pub max_delay_secs: Option<f64>,
+    /// Gives up after this many failed attempts in a row, retries forever if not set
+    pub max_attempts: Option<u32>,
+}
+
+/// Configuration of a ROS1 native [crate::NodeHandle]
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Ros1Config {
+    pub name: String,
+    pub master_uri: Option<String>,
+    #[serde(default)]
+    pub anonymous: bool,
+    pub bind_host: Option<String>,
+    pub default_queue_size: Option<usize>,
+    /// Remappings from one name to another, in addition to those on the command line
+    #[serde(default)]
+    pub remappings: BTreeMap<String, String>,
+    /// Options for individual topics, keyed by topic name
+    #[serde(default)]
+    pub topics: BTreeMap<String, TopicConfig>,
+}
+
+/// Configuration of a single topic of a ROS1 native node
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TopicConfig {
+    pub queue_size: Option<usize>,
+    pub tcp_nodelay: Option<bool>,
+    pub latched: Option<bool>,
+}
+
+impl Config {
+    /// Parses and validates a configuration
+    pub fn from_toml(toml: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(toml)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reads, parses and validates a configuration file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Checks every value is usable, which is done by [Config::from_toml] and [Config::from_file]
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(rosbridge) = &self.rosbridge {
+            rosbridge.validate()?;
+        }
+        if let Some(ros1) = &self.ros1 {
+            ros1.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Options for creating a [crate::ClientHandle] from the `[rosbridge]` section
+    pub fn rosbridge_options(&self) -> Result<ClientHandleOptions, ConfigError> {
+        let rosbridge = self
+            .rosbridge
+            .as_ref()
+            .ok_or(ConfigError::MissingSection("rosbridge"))?;
+        rosbridge.validate()?;
+        Ok(rosbridge.options())
+    }
+
+    /// Options for creating a ROS1 native [crate::NodeHandle] from the `[ros1]` section
+    #[cfg(feature = "ros1")]
+    pub fn node_options(&self) -> Result<crate::NodeHandleOptions, ConfigError> {
+        let ros1 = self
+            .ros1
+            .as_ref()
+            .ok_or(ConfigError::MissingSection("ros1"))?;
+        ros1.validate()?;
+        Ok(ros1.options())
+    }
+}
+
+impl RosbridgeConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if !(self.url.starts_with("ws://") || self.url.starts_with("wss://")) {
+            return Err(invalid(
+                "rosbridge.url",
+                "must be a websocket url starting with ws:// or wss://",
+            ));
+        }
+        if let Some(timeout) = self.timeout_secs {
+            duration("rosbridge.timeout_secs", timeout)?;
+        }
+        for (service, ttl) in &self.cached_services {
+            duration(&format!("rosbridge.cached_services.{service}"), *ttl)?;
+        }
+        self.reconnect.policy()?;
+        Ok(())
+    }
+
+    // Only valid once validated
+    fn options(&self) -> ClientHandleOptions {
+        let mut options = ClientHandleOptions::new(self.url.as_str())
+            .encoding(self.encoding)
+            .reconnect_policy(self.reconnect.policy().unwrap_or_default());
+        if let Some(timeout) = self.timeout_secs {
+            options = options.timeout(Duration::from_secs_f64(timeout));
+        }
+        for (service, ttl) in &self.cached_services {
+            options = options.cache_service_responses(service, Duration::from_secs_f64(*ttl));
+        }
+        options
+    }
+}
+
+impl ReconnectConfig {
+    /// The policy described by this configuration, or an error if any of its values are invalid
+    pub fn policy(&self) -> Result<ReconnectPolicy, ConfigError> {
+        let initial_delay = match self.initial_delay_secs {
+            Some(delay) => duration("rosbridge.reconnect.initial_delay_secs", delay)?,
+            None => DEFAULT_RECONNECT_DELAY,
+        };
+        let max_delay = match self.max_delay_secs {
+            Some(delay) => duration("rosbridge.reconnect.max_delay_secs", delay)?,
+            None => initial_delay,
+        };
+        if max_delay < initial_delay {
+            return Err(invalid(
+                "rosbridge.reconnect.max_delay_secs",
+                "must not be less than initial_delay_secs",
+            ));
+        }
+        let policy = ReconnectPolicy::exponential(initial_delay, max_delay);
+        match self.max_attempts {
+            Some(0) => Err(invalid(
+                "rosbridge.reconnect.max_attempts",
+                "must be at least 1",
+            )),
+            Some(attempts) => Ok(policy.max_attempts(attempts)),
+            None => Ok(policy),
+        }
+    }
+}
+
+impl Ros1Config {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.name.trim_start_matches('/').is_empty() {
+            return Err(invalid("ros1.name", "must not be empty"));
+        }
+        if let Some(master_uri) = &self.master_uri {
+            if !master_uri.starts_with("http://") {
+                return Err(invalid("ros1.master_uri", "must start with http://"));
+            }
+        }
+        if self.default_queue_size == Some(0) {
+            return Err(invalid("ros1.default_queue_size", "must be at least 1"));
+        }
+        for (topic, options) in &self.topics {
+            if options.queue_size == Some(0) {
+                return Err(invalid(
+                    &format!("ros1.topics.\"{topic}\".queue_size"),
+                    "must be at least 1",
+                ));
+            }
+        }
+        // Names are checked when the node is created, as they are resolved against the node's name
+        Ok(())
+    }
+
+    /// The master uri to connect to, which falls back to `ROS_MASTER_URI` and then the default master
+    pub fn master_uri(&self) -> String {
+        self.master_uri
+            .clone()
+            .or_else(|| std::env::var("ROS_MASTER_URI").ok())
+            .unwrap_or_else(|| DEFAULT_MASTER_URI.to_owned())
+    }
+
+    // Only valid once validated
+    #[cfg(feature = "ros1")]
+    fn options(&self) -> crate::NodeHandleOptions {
+        let remappings = self.remappings.iter().fold(
+            crate::Remappings::from_env_args(),
+            |remappings, (from, to)| remappings.remap(from, to),
+        );
+        let mut options = crate::NodeHandleOptions::new(self.master_uri(), self.name.as_str())
+            .anonymous(self.anonymous)
+            .remappings(remappings);
+        if let Some(host) = &self.bind_host {
+            options = options.bind_host(host);
+        }
+        if let Some(queue_size) = self.default_queue_size {
+            options = options.default_queue_size(queue_size);
+        }
+        for (topic, config) in &self.topics {
+            let mut topic_options = crate::TopicOptions::new();
+            if let Some(queue_size) = config.queue_size {
+                topic_options = topic_options.queue_size(queue_size);
+            }
+            if let Some(tcp_nodelay) = config.tcp_nodelay {
+                topic_options = topic_options.tcp_nodelay(tcp_nodelay);
+            }
+            if let Some(latched) = config.latched {
+                topic_options = topic_options.latched(latched);
+            }
+            options = options.topic(topic, topic_options);
+        }
+        options
+    }
+}
+
+fn invalid(field: &str, reason: &str) -> ConfigError {
+    ConfigError::Invalid {
+        field: field.to_owned(),
+        reason: reason.to_owned(),
+    }
+}
+
+fn duration(field: &str, secs: f64) -> Result<Duration, ConfigError> {
+    Duration::try_from_secs_f64(secs)
+        .map_err(|_| invalid(field, "must be a non-negative number of seconds"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Config, ConfigError};
+    use crate::{Encoding, ReconnectPolicy};
+    use std::time::Duration;
+
+    #[test]
+    fn parses_and_validates_config() {
+        let config = Config::from_toml(
+            r#"
+            [rosbridge]
+            url = "ws://localhost:9090"
+            cached_services = { "/rosapi/topic_type" = 10.0 }
+
+            [rosbridge.reconnect]
+            initial_delay_secs = 0.5
+            max_delay_secs = 4.0
+            max_attempts = 3
+
+            [ros1]
+            name = "talker"
+            default_queue_size = 5
+
+            [ros1.topics."/camera/image"]
+            queue_size = 1
+            tcp_nodelay = true
+            "#,
+        )
+        .unwrap();
+
+        let rosbridge = config.rosbridge.as_ref().unwrap();
+        assert_eq!(rosbridge.encoding, Encoding::Json);
+        assert_eq!(
+            rosbridge.reconnect.policy().unwrap(),
+            ReconnectPolicy::exponential(Duration::from_millis(500), Duration::from_secs(4))
+                .max_attempts(3)
+        );
+        let ros1 = config.ros1.as_ref().unwrap();
+        assert_eq!(ros1.topics["/camera/image"].tcp_nodelay, Some(true));
+        assert!(config.rosbridge_options().is_ok());
+
+        // Typos are reported rather than silently ignored
+        let err = Config::from_toml("[ros1]\nname = \"talker\"\nqueue_size = 5").unwrap_err();
+        assert!(matches!(err, ConfigError::Parse(_)));
+
+        let err = Config::from_toml("[rosbridge]\nurl = \"http://localhost:9090\"").unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { .. }));
+        let err = Config::from_toml(
+            "[rosbridge]\nurl = \"ws://localhost:9090\"\n[rosbridge.reconnect]\ninitial_delay_secs = -1.0",
+        )
+        .unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { .. }));
+
+        let rosbridge_only =
+            Config::from_toml("[rosbridge]\nurl = \"ws://localhost:9090\"").unwrap();
+        #[cfg(feature = "ros1")]
+        assert!(matches!(
+            rosbridge_only.node_options(),
+            Err(ConfigError::MissingSection("ros1"))
+        ));
+        assert!(rosbridge_only.ros1.is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "bson")]
+    fn parses_bson_encoding() {
+        let config =
+            Config::from_toml("[rosbridge]\nurl = \"ws://localhost:9090\"\nencoding = \"bson\"")
+                .unwrap();
+        assert_eq!(config.rosbridge.unwrap().encoding, Encoding::Bson);
+    }
+
+    #[test]
+    fn reconnect_delay_backs_off() {
+        let policy = ReconnectPolicy::exponential(Duration::from_secs(1), Duration::from_secs(5))
+            .max_attempts(5);
+        let delays: Vec<_> = (1..=5).map(|attempt| policy.delay(attempt)).collect();
+        assert_eq!(
+            delays,
+            vec![
+                Some(Duration::from_secs(1)),
+                Some(Duration::from_secs(2)),
+                Some(Duration::from_secs(4)),
+                Some(Duration::from_secs(5)),
+                None
+            ]
+        );
+    }
+}
@@ -0,0 +1,470 @@
+//! This module contains downsampling of large sensor messages, applied before republishing them or bridging them
+//! through rosbridge, to make viewing them remotely feasible over constrained links:
+//! [voxel_grid] for `sensor_msgs/PointCloud2`, [resize] for `sensor_msgs/Image` and [decimate] for
+//! `sensor_msgs/LaserScan`.
+//!
+//! As with [crate::Stamped], the helpers work with generated messages through traits implemented for them:
+//! ```ignore
+//! impl roslibrust::downsample::PointCloud for sensor_msgs::PointCloud2 {
+//!     fn point_step(&self) -> usize { self.point_step as usize }
+//!     fn is_bigendian(&self) -> bool { self.is_bigendian }
+//!     fn field(&self, name: &str) -> Option<(usize, u8)> {
+//!         let field = self.fields.iter().find(|field| field.name == name)?;
+//!         Some((field.offset as usize, field.datatype))
+//!     }
+//!     fn data(&self) -> &[u8] { &self.data }
+//!     fn set_points(&mut self, data: Vec<u8>, width: usize) {
+//!         self.height = 1;
+//!         self.width = width as u32;
+//!         self.row_step = data.len() as u32;
+//!         self.data = data;
+//!     }
+//! }
+//! impl roslibrust::downsample::Image for sensor_msgs::Image {
+//!     fn width(&self) -> u32 { self.width }
+//!     fn height(&self) -> u32 { self.height }
+//!     fn encoding(&self) -> &str { &self.encoding }
+//!     fn step(&self) -> u32 { self.step }
+//!     fn data(&self) -> &[u8] { &self.data }
+//!     fn set_pixels(&mut self, data: Vec<u8>, width: u32, height: u32, step: u32) {
+//!         (self.data, self.width, self.height, self.step) = (data, width, height, step);
+//!     }
+//! }
+//! impl roslibrust::downsample::LaserScan for sensor_msgs::LaserScan {
+//!     fn angle_min(&self) -> f32 { self.angle_min }
+//!     fn angle_increment(&self) -> f32 { self.angle_increment }
+//!     fn time_increment(&self) -> f32 { self.time_increment }
+//!     fn ranges(&self) -> &[f32] { &self.ranges }
+//!     fn intensities(&self) -> &[f32] { &self.intensities }
+//!     fn set_readings(&mut self, readings: roslibrust::downsample::ScanReadings) {
+//!         self.angle_max = readings.angle_max;
+//!         self.angle_increment = readings.angle_increment;
+//!         self.time_increment = readings.time_increment;
+//!         self.ranges = readings.ranges;
+//!         self.intensities = readings.intensities;
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+/// The `sensor_msgs/PointField` datatypes of coordinates [voxel_grid] supports
+const FLOAT32: u8 = 7;
+const FLOAT64: u8 = 8;
+
+/// A `sensor_msgs/PointCloud2`, see the [module docs](self) for implementing it
+pub trait PointCloud: Clone {
+    fn point_step(&self) -> usize;
+    fn is_bigendian(&self) -> bool;
+    /// The offset within each point and the `sensor_msgs/PointField` datatype of the field called `name`
+    fn field(&self, name: &str) -> Option<(usize, u8)>;
+    fn data(&self) -> &[u8];
+    /// Replaces the points with the `width` points in `data`, as an unorganized cloud with a height of 1
+    fn set_points(&mut self, data: Vec<u8>, width: usize);
+}
+
+/// A `sensor_msgs/Image`, see the [module docs](self) for implementing it
+pub trait Image: Clone {
+    fn width(&self) -> u32;
+    fn height(&self) -> u32;
+    fn encoding(&self) -> &str;
+    fn step(&self) -> u32;
+    fn data(&self) -> &[u8];
+    fn set_pixels(&mut self, data: Vec<u8>, width: u32, height: u32, step: u32);
+}
+
+/// A `sensor_msgs/LaserScan`, see the [module docs](self) for implementing it
+pub trait LaserScan: Clone {
+    fn angle_min(&self) -> f32;
+    fn angle_increment(&self) -> f32;
+    fn time_increment(&self) -> f32;
+    fn ranges(&self) -> &[f32];
+    fn intensities(&self) -> &[f32];
+    fn set_readings(&mut self, readings: ScanReadings);
+}
+
+/// The fields of a `sensor_msgs/LaserScan` which change when it is decimated
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanReadings {
+    pub angle_max: f32,
+    pub angle_increment: f32,
+    pub time_increment: f32,
+    pub ranges: Vec<f32>,
+    pub intensities: Vec<f32>,
+}
+
+/// Reasons a message can't be downsampled
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum DownsampleError {
+    #[error("{0} must be greater than zero")]
+    InvalidParameter(&'static str),
+    #[error("Point cloud has no {0} field")]
+    MissingField(&'static str),
+    #[error(
+        "Point cloud field {field} has datatype {datatype}, only FLOAT32 and FLOAT64 are supported"
+    )]
+    UnsupportedField { field: &'static str, datatype: u8 },
+    #[error("Image encoding {0} is not supported")]
+    UnsupportedEncoding(String),
+    #[error("Message holds less data than its dimensions describe")]
+    Truncated,
+}
+
+/// Downsamples `cloud` by dividing space into cubes `leaf_size` wide and keeping one point per occupied cube,
+/// positioned at the centroid of the points in it. The other fields of each point are taken from the first point
+/// in its cube. Points with coordinates which are not finite are dropped. The result is an unorganized cloud.
+pub fn voxel_grid<C: PointCloud>(cloud: &C, leaf_size: f64) -> Result<C, DownsampleError> {
+    if leaf_size.is_nan() || leaf_size <= 0.0 {
+        return Err(DownsampleError::InvalidParameter("leaf_size"));
+    }
+    let point_step = cloud.point_step();
+    if point_step == 0 {
+        return Err(DownsampleError::InvalidParameter("point_step"));
+    }
+    let big_endian = cloud.is_bigendian();
+    let mut coordinates = vec![];
+    for name in ["x", "y", "z"] {
+        let (offset, datatype) = cloud
+            .field(name)
+            .ok_or(DownsampleError::MissingField(name))?;
+        let size = match datatype {
+            FLOAT32 => 4,
+            FLOAT64 => 8,
+            _ => {
+                return Err(DownsampleError::UnsupportedField {
+                    field: name,
+                    datatype,
+                })
+            }
+        };
+        if offset + size > point_step {
+            return Err(DownsampleError::Truncated);
+        }
+        coordinates.push(Coordinate {
+            offset,
+            datatype,
+            big_endian,
+        });
+    }
+
+    // Voxels are kept in the order they're first occupied, so the output follows the input's order
+    let mut voxels: Vec<(&[u8], [f64; 3], usize)> = vec![];
+    let mut voxel_index: HashMap<[i64; 3], usize> = HashMap::new();
+    for point in cloud.data().chunks_exact(point_step) {
+        let position = [0, 1, 2].map(|axis| coordinates[axis].read(point));
+        if !position.iter().all(|value| value.is_finite()) {
+            continue;
+        }
+        let key = position.map(|value| (value / leaf_size).floor() as i64);
+        match voxel_index.get(&key) {
+            Some(&index) => {
+                let (_, sum, count) = &mut voxels[index];
+                for (sum, value) in sum.iter_mut().zip(position) {
+                    *sum += value;
+                }
+                *count += 1;
+            }
+            None => {
+                voxel_index.insert(key, voxels.len());
+                voxels.push((point, position, 1));
+            }
+        }
+    }
+
+    let mut data = Vec::with_capacity(voxels.len() * point_step);
+    for (first, sum, count) in &voxels {
+        let start = data.len();
+        data.extend_from_slice(first);
+        for (coordinate, sum) in coordinates.iter().zip(sum) {
+            coordinate.write(&mut data[start..], sum / *count as f64);
+        }
+    }
+    let mut downsampled = cloud.clone();
+    downsampled.set_points(data, voxels.len());
+    Ok(downsampled)
+}
+
+// Where and how one coordinate is stored within each point of a cloud
+struct Coordinate {
+    offset: usize,
+    datatype: u8,
+    big_endian: bool,
+}
+
+impl Coordinate {
+    fn read(&self, point: &[u8]) -> f64 {
+        let bytes = &point[self.offset..];
+        match (self.datatype, self.big_endian) {
+            (FLOAT32, false) => f32::from_le_bytes(bytes[..4].try_into().unwrap()) as f64,
+            (FLOAT32, true) => f32::from_be_bytes(bytes[..4].try_into().unwrap()) as f64,
+            (_, false) => f64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            (_, true) => f64::from_be_bytes(bytes[..8].try_into().unwrap()),
+        }
+    }
+
+    fn write(&self, point: &mut [u8], value: f64) {
+        let bytes = &mut point[self.offset..];
+        match (self.datatype, self.big_endian) {
+            (FLOAT32, false) => bytes[..4].copy_from_slice(&(value as f32).to_le_bytes()),
+            (FLOAT32, true) => bytes[..4].copy_from_slice(&(value as f32).to_be_bytes()),
+            (_, false) => bytes[..8].copy_from_slice(&value.to_le_bytes()),
+            (_, true) => bytes[..8].copy_from_slice(&value.to_be_bytes()),
+        }
+    }
+}
+
+/// Resizes `image` to `width` by `height` pixels with nearest neighbour sampling, which keeps pixel values exact
+/// so works for depth and label images as well as color.
+/// Bayer encodings are not supported as sampling them would break up their color pattern.
+pub fn resize<I: Image>(image: &I, width: u32, height: u32) -> Result<I, DownsampleError> {
+    if width == 0 || height == 0 {
+        return Err(DownsampleError::InvalidParameter("Image size"));
+    }
+    let pixel_size = pixel_size(image.encoding())
+        .ok_or_else(|| DownsampleError::UnsupportedEncoding(image.encoding().to_owned()))?;
+    let (source_width, source_height) = (image.width() as usize, image.height() as usize);
+    let source_step = image.step() as usize;
+    if source_width == 0 || source_height == 0 {
+        return Err(DownsampleError::InvalidParameter("Source image size"));
+    }
+    if source_step < source_width * pixel_size || image.data().len() < source_step * source_height {
+        return Err(DownsampleError::Truncated);
+    }
+
+    let step = width as usize * pixel_size;
+    let mut data = Vec::with_capacity(step * height as usize);
+    for row in 0..height as usize {
+        let source_row = row * source_height / height as usize;
+        let source = &image.data()[source_row * source_step..];
+        for column in 0..width as usize {
+            let start = column * source_width / width as usize * pixel_size;
+            data.extend_from_slice(&source[start..start + pixel_size]);
+        }
+    }
+    let mut resized = image.clone();
+    resized.set_pixels(data, width, height, step as u32);
+    Ok(resized)
+}
+
+// The bytes per pixel of the encodings in sensor_msgs/image_encodings.h, except bayer encodings
+fn pixel_size(encoding: &str) -> Option<usize> {
+    match encoding {
+        "mono8" => return Some(1),
+        "mono16" => return Some(2),
+        "rgb8" | "bgr8" => return Some(3),
+        "rgba8" | "bgra8" => return Some(4),
+        "rgb16" | "bgr16" => return Some(6),
+        "rgba16" | "bgra16" => return Some(8),
+        _ => {}
+    }
+    // e.g. 32FC1, a single channel of 32 bit floats
+    let (depth, channels) = encoding.split_once('C')?;
+    let bits = depth
+        .strip_suffix(['U', 'S', 'F'])?
+        .parse::<usize>()
+        .ok()
+        .filter(|bits| [8, 16, 32, 64].contains(bits))?;
+    let channels = channels
+        .parse::<usize>()
+        .ok()
+        .filter(|channels| *channels > 0)?;
+    Some(bits / 8 * channels)
+}
+
+/// Keeps every `factor`th reading of `scan`, starting with the first, adjusting its angles and timing to match.
+pub fn decimate<S: LaserScan>(scan: &S, factor: usize) -> Result<S, DownsampleError> {
+    if factor == 0 {
+        return Err(DownsampleError::InvalidParameter("factor"));
+    }
+    let ranges: Vec<f32> = scan.ranges().iter().copied().step_by(factor).collect();
+    let intensities = scan.intensities().iter().copied().step_by(factor).collect();
+    let angle_increment = scan.angle_increment() * factor as f32;
+    let mut decimated = scan.clone();
+    decimated.set_readings(ScanReadings {
+        angle_max: scan.angle_min() + angle_increment * ranges.len().saturating_sub(1) as f32,
+        angle_increment,
+        time_increment: scan.time_increment() * factor as f32,
+        ranges,
+        intensities,
+    });
+    Ok(decimated)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Debug)]
+    struct Cloud {
+        data: Vec<u8>,
+        width: usize,
+    }
+
+    // Points of x, y and z as little endian FLOAT32 followed by a one byte label
+    impl PointCloud for Cloud {
+        fn point_step(&self) -> usize {
+            13
+        }
+        fn is_bigendian(&self) -> bool {
+            false
+        }
+        fn field(&self, name: &str) -> Option<(usize, u8)> {
+            let offset = ["x", "y", "z"].iter().position(|axis| *axis == name)?;
+            Some((offset * 4, FLOAT32))
+        }
+        fn data(&self) -> &[u8] {
+            &self.data
+        }
+        fn set_points(&mut self, data: Vec<u8>, width: usize) {
+            self.data = data;
+            self.width = width;
+        }
+    }
+
+    fn cloud(points: &[([f32; 3], u8)]) -> Cloud {
+        let mut data = vec![];
+        for (position, label) in points {
+            for value in position {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+            data.push(*label);
+        }
+        Cloud {
+            data,
+            width: points.len(),
+        }
+    }
+
+    #[test]
+    fn voxel_grid_keeps_a_centroid_per_voxel() {
+        let input = cloud(&[
+            ([0.25, 0.25, 0.25], 1),
+            ([5.0, 5.0, 5.0], 2),
+            ([0.75, 0.5, 0.25], 3),
+            ([f32::NAN, 0.0, 0.0], 4),
+            ([-0.5, 0.1, 0.1], 5),
+        ]);
+        let output = voxel_grid(&input, 1.0).unwrap();
+        assert_eq!(
+            output.data,
+            cloud(&[
+                ([0.5, 0.375, 0.25], 1),
+                ([5.0, 5.0, 5.0], 2),
+                ([-0.5, 0.1, 0.1], 5)
+            ])
+            .data
+        );
+        assert_eq!(output.width, 3);
+
+        assert_eq!(
+            voxel_grid(&input, 0.0).unwrap_err(),
+            DownsampleError::InvalidParameter("leaf_size")
+        );
+    }
+
+    #[derive(Clone, Debug)]
+    struct Mono8 {
+        data: Vec<u8>,
+        width: u32,
+        height: u32,
+        step: u32,
+        encoding: String,
+    }
+
+    impl Image for Mono8 {
+        fn width(&self) -> u32 {
+            self.width
+        }
+        fn height(&self) -> u32 {
+            self.height
+        }
+        fn encoding(&self) -> &str {
+            &self.encoding
+        }
+        fn step(&self) -> u32 {
+            self.step
+        }
+        fn data(&self) -> &[u8] {
+            &self.data
+        }
+        fn set_pixels(&mut self, data: Vec<u8>, width: u32, height: u32, step: u32) {
+            (self.data, self.width, self.height, self.step) = (data, width, height, step);
+        }
+    }
+
+    #[test]
+    fn resize_samples_nearest_pixels() {
+        // 4x2 pixels with a padding byte at the end of each row
+        let image = Mono8 {
+            data: vec![1, 2, 3, 4, 0, 5, 6, 7, 8, 0],
+            width: 4,
+            height: 2,
+            step: 5,
+            encoding: "mono8".to_owned(),
+        };
+        let resized = resize(&image, 2, 1).unwrap();
+        assert_eq!(resized.data, vec![1, 3]);
+        assert_eq!((resized.width, resized.height, resized.step), (2, 1, 2));
+
+        assert_eq!(pixel_size("32FC1"), Some(4));
+        assert_eq!(pixel_size("16UC3"), Some(6));
+        let bayer = Mono8 {
+            encoding: "bayer_rggb8".to_owned(),
+            ..image
+        };
+        assert!(matches!(
+            resize(&bayer, 2, 1),
+            Err(DownsampleError::UnsupportedEncoding(_))
+        ));
+    }
+
+    #[derive(Clone, Debug)]
+    struct Scan {
+        readings: ScanReadings,
+    }
+
+    impl LaserScan for Scan {
+        fn angle_min(&self) -> f32 {
+            -1.0
+        }
+        fn angle_increment(&self) -> f32 {
+            self.readings.angle_increment
+        }
+        fn time_increment(&self) -> f32 {
+            self.readings.time_increment
+        }
+        fn ranges(&self) -> &[f32] {
+            &self.readings.ranges
+        }
+        fn intensities(&self) -> &[f32] {
+            &self.readings.intensities
+        }
+        fn set_readings(&mut self, readings: ScanReadings) {
+            self.readings = readings;
+        }
+    }
+
+    #[test]
+    fn decimate_keeps_every_nth_reading() {
+        let scan = Scan {
+            readings: ScanReadings {
+                angle_max: 1.0,
+                angle_increment: 0.5,
+                time_increment: 0.25,
+                ranges: vec![1.0, 2.0, 3.0, 4.0, 5.0],
+                intensities: vec![],
+            },
+        };
+        let decimated = decimate(&scan, 2).unwrap();
+        assert_eq!(
+            decimated.readings,
+            ScanReadings {
+                angle_max: 1.0,
+                angle_increment: 1.0,
+                time_increment: 0.5,
+                ranges: vec![1.0, 3.0, 5.0],
+                intensities: vec![],
+            }
+        );
+    }
+}
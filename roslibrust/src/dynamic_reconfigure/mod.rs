@@ -0,0 +1,591 @@
+//! A [dynamic_reconfigure](http://wiki.ros.org/dynamic_reconfigure) server, letting tools such as
+//! rqt_reconfigure change the configuration of a running node through rosbridge.
+//!
+//! The parameters of a configuration are described with a [ConfigDescriptor], the equivalent of a `.cfg` file,
+//! while their values are held in any type which serializes to a struct with a field of the same name per parameter:
+//! ```no_run
+//! use roslibrust::dynamic_reconfigure::{ConfigDescriptor, Parameter, ReconfigureServer};
+//!
+//! #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+//! struct DriverConfig {
+//!     rate: i32,
+//!     gain: f64,
+//!     enabled: bool,
+//!     frame_id: String,
+//! }
+//!
+//! # async fn run(client: roslibrust::ClientHandle) -> roslibrust::RosLibRustResult<()> {
+//! let descriptor = ConfigDescriptor::new()
+//!     .parameter(Parameter::int("rate", 10, 1, 100).description("Publish rate in Hz"))
+//!     .parameter(Parameter::double("gain", 0.5, 0.0, 1.0).level(1))
+//!     .parameter(Parameter::bool("enabled", true))
+//!     .parameter(Parameter::str("frame_id", "base_link"));
+//! let server = ReconfigureServer::new(
+//!     &client,
+//!     "/driver",
+//!     descriptor,
+//!     |config: &mut DriverConfig, level| log::info!("Reconfigured to {config:?} at level {level}"),
+//! )
+//! .await?;
+//! let rate = server.config().rate;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Like roscpp's `dynamic_reconfigure::Server` the server advertises the `set_parameters` service and the
+//! latched `parameter_descriptions` and `parameter_updates` topics, under the namespace it is given
+//! rather than a node's private namespace.
+
+use crate::{ClientHandle, RosLibRustError, RosLibRustResult, ServiceHandle};
+use abort_on_drop::ChildTask;
+use anyhow::anyhow;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+pub mod msgs;
+
+/// The value of a [Parameter]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParameterValue {
+    Bool(bool),
+    Int(i32),
+    Double(f64),
+    Str(String),
+}
+
+impl ParameterValue {
+    // The name dynamic_reconfigure gives the type in a ParamDescription
+    fn type_name(&self) -> &'static str {
+        match self {
+            ParameterValue::Bool(_) => "bool",
+            ParameterValue::Int(_) => "int",
+            ParameterValue::Double(_) => "double",
+            ParameterValue::Str(_) => "str",
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            ParameterValue::Bool(value) => Value::from(*value),
+            ParameterValue::Int(value) => Value::from(*value),
+            ParameterValue::Double(value) => Value::from(*value),
+            ParameterValue::Str(value) => Value::from(value.as_str()),
+        }
+    }
+
+    // Reads `value` as a parameter of the same type as self
+    fn parse(&self, value: &Value) -> Option<ParameterValue> {
+        Some(match self {
+            ParameterValue::Bool(_) => ParameterValue::Bool(value.as_bool()?),
+            ParameterValue::Int(_) => ParameterValue::Int(i32::try_from(value.as_i64()?).ok()?),
+            ParameterValue::Double(_) => ParameterValue::Double(value.as_f64()?),
+            ParameterValue::Str(_) => ParameterValue::Str(value.as_str()?.to_owned()),
+        })
+    }
+
+    fn clamp(self, min: &ParameterValue, max: &ParameterValue) -> ParameterValue {
+        match (self, min, max) {
+            (ParameterValue::Int(value), ParameterValue::Int(min), ParameterValue::Int(max)) => {
+                ParameterValue::Int(value.max(*min).min(*max))
+            }
+            (
+                ParameterValue::Double(value),
+                ParameterValue::Double(min),
+                ParameterValue::Double(max),
+            ) => ParameterValue::Double(value.max(*min).min(*max)),
+            (value, _, _) => value,
+        }
+    }
+}
+
+/// A parameter of a [ConfigDescriptor], the equivalent of a `gen.add` line in a `.cfg` file
+#[derive(Clone, Debug)]
+pub struct Parameter {
+    name: String,
+    description: String,
+    level: u32,
+    default: ParameterValue,
+    min: ParameterValue,
+    max: ParameterValue,
+}
+
+impl Parameter {
+    fn new(name: &str, default: ParameterValue, min: ParameterValue, max: ParameterValue) -> Self {
+        Self {
+            name: name.to_owned(),
+            description: String::new(),
+            level: 0,
+            default,
+            min,
+            max,
+        }
+    }
+
+    pub fn bool(name: &str, default: bool) -> Self {
+        Self::new(
+            name,
+            ParameterValue::Bool(default),
+            ParameterValue::Bool(false),
+            ParameterValue::Bool(true),
+        )
+    }
+
+    /// An integer parameter, with values set outside of `min..=max` clamped into it
+    pub fn int(name: &str, default: i32, min: i32, max: i32) -> Self {
+        Self::new(
+            name,
+            ParameterValue::Int(default),
+            ParameterValue::Int(min),
+            ParameterValue::Int(max),
+        )
+    }
+
+    /// A floating point parameter, with values set outside of `min..=max` clamped into it.
+    /// The bounds must be finite, as rosbridge's JSON has no representation of infinity.
+    pub fn double(name: &str, default: f64, min: f64, max: f64) -> Self {
+        Self::new(
+            name,
+            ParameterValue::Double(default),
+            ParameterValue::Double(min),
+            ParameterValue::Double(max),
+        )
+    }
+
+    pub fn str(name: &str, default: &str) -> Self {
+        Self::new(
+            name,
+            ParameterValue::Str(default.to_owned()),
+            ParameterValue::Str(String::new()),
+            ParameterValue::Str(String::new()),
+        )
+    }
+
+    /// Sets the description shown alongside the parameter by rqt_reconfigure
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_owned();
+        self
+    }
+
+    /// Sets the bits passed to the server's callback when the parameter changes,
+    /// combined with a bitwise or with those of any other parameters changed at the same time
+    pub fn level(mut self, level: u32) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn default_value(&self) -> &ParameterValue {
+        &self.default
+    }
+}
+
+/// Describes the parameters of a configuration served by a [ReconfigureServer], the equivalent of a `.cfg` file.
+/// All parameters are placed in the single default group.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigDescriptor {
+    parameters: Vec<Parameter>,
+}
+
+impl ConfigDescriptor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parameter(mut self, parameter: Parameter) -> Self {
+        self.parameters.push(parameter);
+        self
+    }
+
+    pub fn parameters(&self) -> &[Parameter] {
+        &self.parameters
+    }
+
+    /// The description published on `parameter_descriptions`
+    pub fn to_description(&self) -> msgs::ConfigDescription {
+        let values = |value: fn(&Parameter) -> &ParameterValue| {
+            self.to_message(
+                self.parameters
+                    .iter()
+                    .map(value)
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            )
+        };
+        msgs::ConfigDescription {
+            groups: vec![msgs::Group {
+                name: "Default".to_owned(),
+                r#type: String::new(),
+                parameters: self
+                    .parameters
+                    .iter()
+                    .map(|parameter| msgs::ParamDescription {
+                        name: parameter.name.clone(),
+                        r#type: parameter.default.type_name().to_owned(),
+                        level: parameter.level,
+                        description: parameter.description.clone(),
+                        edit_method: String::new(),
+                    })
+                    .collect(),
+                parent: 0,
+                id: 0,
+            }],
+            max: values(|parameter| &parameter.max),
+            min: values(|parameter| &parameter.min),
+            dflt: values(|parameter| &parameter.default),
+        }
+    }
+
+    // Values are kept in the order of the descriptor's parameters
+    fn defaults(&self) -> Vec<ParameterValue> {
+        self.parameters
+            .iter()
+            .map(|parameter| parameter.default.clone())
+            .collect()
+    }
+
+    fn to_message(&self, values: Vec<ParameterValue>) -> msgs::Config {
+        let mut config = msgs::Config {
+            groups: vec![msgs::GroupState {
+                name: "Default".to_owned(),
+                state: true,
+                id: 0,
+                parent: 0,
+            }],
+            ..Default::default()
+        };
+        for (parameter, value) in self.parameters.iter().zip(values) {
+            let name = parameter.name.clone();
+            match value {
+                ParameterValue::Bool(value) => {
+                    config.bools.push(msgs::BoolParameter { name, value })
+                }
+                ParameterValue::Int(value) => config.ints.push(msgs::IntParameter { name, value }),
+                ParameterValue::Double(value) => {
+                    config.doubles.push(msgs::DoubleParameter { name, value })
+                }
+                ParameterValue::Str(value) => config.strs.push(msgs::StrParameter { name, value }),
+            }
+        }
+        config
+    }
+
+    // Applies the parameters set in `config` to `values`, clamped to their bounds.
+    // Like roscpp parameters missing from `config`, or given with the wrong type, are left as they were.
+    fn merge(&self, values: &[ParameterValue], config: &msgs::Config) -> Vec<ParameterValue> {
+        self.parameters
+            .iter()
+            .zip(values)
+            .map(|(parameter, value)| {
+                let name = &parameter.name;
+                let requested = match parameter.default {
+                    ParameterValue::Bool(_) => config
+                        .bools
+                        .iter()
+                        .find(|set| &set.name == name)
+                        .map(|set| ParameterValue::Bool(set.value)),
+                    ParameterValue::Int(_) => config
+                        .ints
+                        .iter()
+                        .find(|set| &set.name == name)
+                        .map(|set| ParameterValue::Int(set.value)),
+                    ParameterValue::Double(_) => config
+                        .doubles
+                        .iter()
+                        .find(|set| &set.name == name)
+                        .map(|set| ParameterValue::Double(set.value)),
+                    ParameterValue::Str(_) => config
+                        .strs
+                        .iter()
+                        .find(|set| &set.name == name)
+                        .map(|set| ParameterValue::Str(set.value.clone())),
+                };
+                match requested {
+                    Some(requested) => requested.clamp(&parameter.min, &parameter.max),
+                    None => value.clone(),
+                }
+            })
+            .collect()
+    }
+
+    // The bitwise or of the levels of the parameters which differ between `old` and `new`
+    fn level(&self, old: &[ParameterValue], new: &[ParameterValue]) -> u32 {
+        self.parameters
+            .iter()
+            .zip(old.iter().zip(new))
+            .filter(|(_, (old, new))| old != new)
+            .fold(0, |level, (parameter, _)| level | parameter.level)
+    }
+
+    fn to_config<T: DeserializeOwned>(&self, values: &[ParameterValue]) -> RosLibRustResult<T> {
+        let fields = self
+            .parameters
+            .iter()
+            .zip(values)
+            .map(|(parameter, value)| (parameter.name.clone(), value.to_json()))
+            .collect();
+        Ok(serde_json::from_value(Value::Object(fields))?)
+    }
+
+    fn values_of<T: Serialize>(&self, config: &T) -> RosLibRustResult<Vec<ParameterValue>> {
+        let Value::Object(fields) = serde_json::to_value(config)? else {
+            return Err(RosLibRustError::Unexpected(anyhow!(
+                "A dynamic_reconfigure configuration must serialize to a struct"
+            )));
+        };
+        self.parameters
+            .iter()
+            .map(|parameter| {
+                fields
+                    .get(&parameter.name)
+                    .and_then(|value| parameter.default.parse(value))
+                    .ok_or_else(|| {
+                        RosLibRustError::Unexpected(anyhow!(
+                            "The configuration has no {} field named {}",
+                            parameter.default.type_name(),
+                            parameter.name
+                        ))
+                    })
+            })
+            .collect()
+    }
+}
+
+// The configuration as last set, with its values in the order of the descriptor's parameters
+struct State<T> {
+    values: Vec<ParameterValue>,
+    config: T,
+}
+
+// Shared by the server and its set_parameters service
+struct Shared<T> {
+    descriptor: ConfigDescriptor,
+    state: Mutex<State<T>>,
+    // Each change is published on parameter_updates by the server's task
+    updates: watch::Sender<msgs::Config>,
+}
+
+impl<T: Serialize + DeserializeOwned> Shared<T> {
+    fn reconfigure(
+        &self,
+        request: &msgs::Config,
+        callback: &impl Fn(&mut T, u32),
+    ) -> RosLibRustResult<msgs::Config> {
+        let mut state = self.state.lock().unwrap();
+        let values = self.descriptor.merge(&state.values, request);
+        let level = self.descriptor.level(&state.values, &values);
+        let mut config = self.descriptor.to_config(&values)?;
+        callback(&mut config, level);
+        self.set(&mut state, config)
+    }
+
+    fn set(&self, state: &mut State<T>, config: T) -> RosLibRustResult<msgs::Config> {
+        let values = self.descriptor.values_of(&config)?;
+        let update = self.descriptor.to_message(values.clone());
+        *state = State { values, config };
+        self.updates.send_replace(update.clone());
+        Ok(update)
+    }
+}
+
+/// Serves a configuration through dynamic_reconfigure, see the [module documentation](self).
+///
+/// The callback is run each time a client such as rqt_reconfigure sets parameters, with the new configuration
+/// and the bitwise or of the levels of the parameters which changed. As with roscpp it may adjust the
+/// configuration, which is then reported back to the client and published on `parameter_updates`.
+///
+/// The service and topics are unadvertised when the server is dropped.
+pub struct ReconfigureServer<T> {
+    shared: Arc<Shared<T>>,
+    _service: ServiceHandle,
+    _task: ChildTask<()>,
+}
+
+impl<T> ReconfigureServer<T>
+where
+    T: Serialize + DeserializeOwned + Clone + Send + 'static,
+{
+    /// Starts serving the configuration described by `descriptor` under `namespace`.
+    ///
+    /// Following roscpp the callback is first run with the default configuration and every bit of the level set,
+    /// before the server is advertised.
+    /// Fails if `T` doesn't have a field of the right type for each of the parameters.
+    pub async fn new<F>(
+        client: &ClientHandle,
+        namespace: &str,
+        descriptor: ConfigDescriptor,
+        callback: F,
+    ) -> RosLibRustResult<Self>
+    where
+        F: Fn(&mut T, u32) + Send + Sync + 'static,
+    {
+        let namespace = namespace.trim_end_matches('/');
+        let mut config = descriptor.to_config(&descriptor.defaults())?;
+        callback(&mut config, !0);
+        let values = descriptor.values_of(&config)?;
+        let update = descriptor.to_message(values.clone());
+
+        let descriptions = client
+            .advertise_latched(&format!("{namespace}/parameter_descriptions"))
+            .await?;
+        descriptions.publish(descriptor.to_description()).await?;
+        let updates_publisher = client
+            .advertise_latched(&format!("{namespace}/parameter_updates"))
+            .await?;
+        updates_publisher.publish(update.clone()).await?;
+
+        let (updates, mut receiver) = watch::channel(update);
+        let task_name = format!("dynamic_reconfigure updates of {namespace}");
+        let task = crate::tasks::spawn(&task_name, async move {
+            // Held so the descriptions stay advertised for clients which connect later
+            let _descriptions = descriptions;
+            while receiver.changed().await.is_ok() {
+                let update = receiver.borrow_and_update().clone();
+                if let Err(err) = updates_publisher.publish(update).await {
+                    log::error!("Failed to publish a dynamic_reconfigure update: {err}");
+                }
+            }
+        });
+
+        let shared = Arc::new(Shared {
+            descriptor,
+            state: Mutex::new(State { values, config }),
+            updates,
+        });
+        let service_shared = shared.clone();
+        let service = client
+            .advertise_service::<msgs::Reconfigure, _>(
+                &format!("{namespace}/set_parameters"),
+                move |request| {
+                    let config = service_shared.reconfigure(&request.config, &callback)?;
+                    Ok(msgs::ReconfigureResponse { config })
+                },
+            )
+            .await?;
+
+        Ok(Self {
+            shared,
+            _service: service,
+            _task: task.into(),
+        })
+    }
+
+    /// The configuration as last set
+    pub fn config(&self) -> T {
+        self.shared.state.lock().unwrap().config.clone()
+    }
+
+    /// Replaces the configuration from within the node, publishing it so clients see the change.
+    /// Like roscpp's `updateConfig` the callback is not run.
+    pub fn update_config(&self, config: T) -> RosLibRustResult<()> {
+        let mut state = self.shared.state.lock().unwrap();
+        self.shared.set(&mut state, config)?;
+        Ok(())
+    }
+
+    pub fn descriptor(&self) -> &ConfigDescriptor {
+        &self.shared.descriptor
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{msgs, ConfigDescriptor, Parameter, ParameterValue};
+
+    #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+    struct DriverConfig {
+        rate: i32,
+        gain: f64,
+        enabled: bool,
+        frame_id: String,
+    }
+
+    fn descriptor() -> ConfigDescriptor {
+        ConfigDescriptor::new()
+            .parameter(Parameter::int("rate", 10, 1, 100).level(1))
+            .parameter(Parameter::double("gain", 0.5, 0.0, 1.0).level(2))
+            .parameter(Parameter::bool("enabled", true).level(4))
+            .parameter(Parameter::str("frame_id", "base_link").description("Frame of the output"))
+    }
+
+    #[test]
+    fn configs_convert_to_and_from_values() {
+        let descriptor = descriptor();
+        let defaults = descriptor.defaults();
+        let config: DriverConfig = descriptor.to_config(&defaults).unwrap();
+        assert_eq!(
+            config,
+            DriverConfig {
+                rate: 10,
+                gain: 0.5,
+                enabled: true,
+                frame_id: "base_link".to_owned(),
+            }
+        );
+        assert_eq!(descriptor.values_of(&config).unwrap(), defaults);
+
+        // Every parameter needs a field of its type
+        #[derive(serde::Serialize)]
+        struct Incomplete {
+            rate: i32,
+        }
+        assert!(descriptor.values_of(&Incomplete { rate: 1 }).is_err());
+        assert!(descriptor.values_of(&"not a struct").is_err());
+    }
+
+    #[test]
+    fn requests_are_merged_clamped_and_levelled() {
+        let descriptor = descriptor();
+        let defaults = descriptor.defaults();
+        let request = msgs::Config {
+            ints: vec![msgs::IntParameter {
+                name: "rate".to_owned(),
+                value: 1000,
+            }],
+            strs: vec![msgs::StrParameter {
+                name: "frame_id".to_owned(),
+                value: "map".to_owned(),
+            }],
+            // Given with the wrong type, so ignored
+            doubles: vec![msgs::DoubleParameter {
+                name: "enabled".to_owned(),
+                value: 0.0,
+            }],
+            ..Default::default()
+        };
+        let values = descriptor.merge(&defaults, &request);
+        assert_eq!(
+            values,
+            vec![
+                ParameterValue::Int(100),
+                ParameterValue::Double(0.5),
+                ParameterValue::Bool(true),
+                ParameterValue::Str("map".to_owned()),
+            ]
+        );
+        assert_eq!(descriptor.level(&defaults, &values), 1);
+        assert_eq!(descriptor.level(&defaults, &defaults), 0);
+
+        let message = descriptor.to_message(values);
+        assert_eq!(message.ints[0].value, 100);
+        assert_eq!(message.doubles[0].name, "gain");
+        assert_eq!(message.groups[0].name, "Default");
+    }
+
+    #[test]
+    fn descriptions_list_parameters_and_bounds() {
+        let description = descriptor().to_description();
+        let parameters = &description.groups[0].parameters;
+        let types: Vec<_> = parameters.iter().map(|p| p.r#type.as_str()).collect();
+        assert_eq!(types, ["int", "double", "bool", "str"]);
+        assert_eq!(parameters[3].description, "Frame of the output");
+        assert_eq!(description.min.ints[0].value, 1);
+        assert_eq!(description.max.doubles[0].value, 1.0);
+        assert_eq!(description.dflt.strs[0].value, "base_link");
+        assert!(description.max.bools[0].value);
+    }
+}
@@ -0,0 +1,296 @@
+//! Hand written equivalents of the messages and service of the dynamic_reconfigure package,
+//! so serving configuration does not depend on generated messages.
+
+use roslibrust_codegen::{RosMessageType, RosServiceType};
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub struct BoolParameter {
+    pub name: String,
+    pub value: bool,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub struct IntParameter {
+    pub name: String,
+    pub value: i32,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub struct StrParameter {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub struct DoubleParameter {
+    pub name: String,
+    pub value: f64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub struct GroupState {
+    pub name: String,
+    pub state: bool,
+    pub id: i32,
+    pub parent: i32,
+}
+
+/// The values of a configuration, as published on `parameter_updates` and exchanged with `set_parameters`
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub struct Config {
+    pub bools: Vec<BoolParameter>,
+    pub ints: Vec<IntParameter>,
+    pub strs: Vec<StrParameter>,
+    pub doubles: Vec<DoubleParameter>,
+    pub groups: Vec<GroupState>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub struct ParamDescription {
+    pub name: String,
+    pub r#type: String,
+    pub level: u32,
+    pub description: String,
+    pub edit_method: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub struct Group {
+    pub name: String,
+    pub r#type: String,
+    pub parameters: Vec<ParamDescription>,
+    pub parent: i32,
+    pub id: i32,
+}
+
+/// The parameters of a configuration and their bounds, as published on `parameter_descriptions`
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub struct ConfigDescription {
+    pub groups: Vec<Group>,
+    pub max: Config,
+    pub min: Config,
+    pub dflt: Config,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub struct ReconfigureRequest {
+    pub config: Config,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub struct ReconfigureResponse {
+    pub config: Config,
+}
+
+/// The `set_parameters` service, dynamic_reconfigure/Reconfigure
+pub struct Reconfigure;
+
+impl RosMessageType for BoolParameter {
+    const ROS_TYPE_NAME: &'static str = "dynamic_reconfigure/BoolParameter";
+    const MD5SUM: &'static str = "23f05028c1a699fb83e22401228c3a9e";
+    const DEFINITION: &'static str = "string name\nbool value\n";
+}
+
+impl RosMessageType for IntParameter {
+    const ROS_TYPE_NAME: &'static str = "dynamic_reconfigure/IntParameter";
+    const MD5SUM: &'static str = "65fedc7a0cbfb8db035e46194a350bf1";
+    const DEFINITION: &'static str = "string name\nint32 value\n";
+}
+
+impl RosMessageType for StrParameter {
+    const ROS_TYPE_NAME: &'static str = "dynamic_reconfigure/StrParameter";
+    const MD5SUM: &'static str = "bc6ccc4a57f61779c8eaae61e9f422e0";
+    const DEFINITION: &'static str = "string name\nstring value\n";
+}
+
+impl RosMessageType for DoubleParameter {
+    const ROS_TYPE_NAME: &'static str = "dynamic_reconfigure/DoubleParameter";
+    const MD5SUM: &'static str = "d8512f27253c0f65f928a67c329cd658";
+    const DEFINITION: &'static str = "string name\nfloat64 value\n";
+}
+
+impl RosMessageType for GroupState {
+    const ROS_TYPE_NAME: &'static str = "dynamic_reconfigure/GroupState";
+    const MD5SUM: &'static str = "a2d87f51dc22930325041a2f8b1571f8";
+    const DEFINITION: &'static str = "string name\nbool state\nint32 id\nint32 parent\n";
+}
+
+impl RosMessageType for Config {
+    const ROS_TYPE_NAME: &'static str = "dynamic_reconfigure/Config";
+    const MD5SUM: &'static str = "958f16a05573709014982821e6822580";
+    const DEFINITION: &'static str = r#"BoolParameter[] bools
+IntParameter[] ints
+StrParameter[] strs
+DoubleParameter[] doubles
+GroupState[] groups
+
+================================================================================
+MSG: dynamic_reconfigure/BoolParameter
+string name
+bool value
+
+================================================================================
+MSG: dynamic_reconfigure/IntParameter
+string name
+int32 value
+
+================================================================================
+MSG: dynamic_reconfigure/StrParameter
+string name
+string value
+
+================================================================================
+MSG: dynamic_reconfigure/DoubleParameter
+string name
+float64 value
+
+================================================================================
+MSG: dynamic_reconfigure/GroupState
+string name
+bool state
+int32 id
+int32 parent
+"#;
+}
+
+impl RosMessageType for ParamDescription {
+    const ROS_TYPE_NAME: &'static str = "dynamic_reconfigure/ParamDescription";
+    const MD5SUM: &'static str = "7434fcb9348c13054e0c3b267c8cb34d";
+    const DEFINITION: &'static str =
+        "string name\nstring type\nuint32 level\nstring description\nstring edit_method\n";
+}
+
+impl RosMessageType for Group {
+    const ROS_TYPE_NAME: &'static str = "dynamic_reconfigure/Group";
+    const MD5SUM: &'static str = "9e8cd9e9423c94823db3614dd8b1cf7a";
+    const DEFINITION: &'static str = r#"string name
+string type
+ParamDescription[] parameters
+int32 parent
+int32 id
+
+================================================================================
+MSG: dynamic_reconfigure/ParamDescription
+string name
+string type
+uint32 level
+string description
+string edit_method
+"#;
+}
+
+impl RosMessageType for ConfigDescription {
+    const ROS_TYPE_NAME: &'static str = "dynamic_reconfigure/ConfigDescription";
+    const MD5SUM: &'static str = "757ce9d44ba8ddd801bb30bc456f946f";
+    const DEFINITION: &'static str = r#"Group[] groups
+Config max
+Config min
+Config dflt
+
+================================================================================
+MSG: dynamic_reconfigure/Group
+string name
+string type
+ParamDescription[] parameters
+int32 parent
+int32 id
+
+================================================================================
+MSG: dynamic_reconfigure/ParamDescription
+string name
+string type
+uint32 level
+string description
+string edit_method
+
+================================================================================
+MSG: dynamic_reconfigure/Config
+BoolParameter[] bools
+IntParameter[] ints
+StrParameter[] strs
+DoubleParameter[] doubles
+GroupState[] groups
+
+================================================================================
+MSG: dynamic_reconfigure/BoolParameter
+string name
+bool value
+
+================================================================================
+MSG: dynamic_reconfigure/IntParameter
+string name
+int32 value
+
+================================================================================
+MSG: dynamic_reconfigure/StrParameter
+string name
+string value
+
+================================================================================
+MSG: dynamic_reconfigure/DoubleParameter
+string name
+float64 value
+
+================================================================================
+MSG: dynamic_reconfigure/GroupState
+string name
+bool state
+int32 id
+int32 parent
+"#;
+}
+
+impl RosMessageType for ReconfigureRequest {
+    const ROS_TYPE_NAME: &'static str = "dynamic_reconfigure/ReconfigureRequest";
+    const MD5SUM: &'static str = "ac41a77620a4a0348b7001641796a8a1";
+    const DEFINITION: &'static str = r#"Config config
+
+================================================================================
+MSG: dynamic_reconfigure/Config
+BoolParameter[] bools
+IntParameter[] ints
+StrParameter[] strs
+DoubleParameter[] doubles
+GroupState[] groups
+
+================================================================================
+MSG: dynamic_reconfigure/BoolParameter
+string name
+bool value
+
+================================================================================
+MSG: dynamic_reconfigure/IntParameter
+string name
+int32 value
+
+================================================================================
+MSG: dynamic_reconfigure/StrParameter
+string name
+string value
+
+================================================================================
+MSG: dynamic_reconfigure/DoubleParameter
+string name
+float64 value
+
+================================================================================
+MSG: dynamic_reconfigure/GroupState
+string name
+bool state
+int32 id
+int32 parent
+"#;
+}
+
+impl RosMessageType for ReconfigureResponse {
+    const ROS_TYPE_NAME: &'static str = "dynamic_reconfigure/ReconfigureResponse";
+    const MD5SUM: &'static str = "ac41a77620a4a0348b7001641796a8a1";
+    const DEFINITION: &'static str = ReconfigureRequest::DEFINITION;
+}
+
+impl RosServiceType for Reconfigure {
+    const ROS_SERVICE_NAME: &'static str = "dynamic_reconfigure/Reconfigure";
+    const MD5SUM: &'static str = "bb125d226a21982a4a98760418dc2672";
+    type Request = ReconfigureRequest;
+    type Response = ReconfigureResponse;
+}
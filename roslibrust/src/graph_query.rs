@@ -0,0 +1,253 @@
+//! This module provides queries of the ROS graph which work the same with either backend,
+//! so tooling like command line tools and dashboards can be written once, see [GraphQuery].
+
+use async_trait::async_trait;
+
+type GraphQueryResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// The topics and services of a node, see [GraphQuery::node_details]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeDetails {
+    /// Topics the node publishes
+    pub publishing: Vec<String>,
+    /// Topics the node subscribes to
+    pub subscribing: Vec<String>,
+    /// Services the node provides
+    pub services: Vec<String>,
+}
+
+/// Queries of the topics, nodes and services making up the ROS graph.
+///
+/// Implemented for [crate::ClientHandle] with the `rosapi` feature, answered by the rosapi node through rosbridge,
+/// and for [crate::MasterClient] and [crate::NodeHandle] with the `ros1` feature, answered by the master's xmlrpc API.
+/// Code written against this trait works with either backend:
+/// ```no_run
+/// use roslibrust::GraphQuery;
+///
+/// async fn print_topics(graph: &impl GraphQuery) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+///     for (topic, topic_type) in graph.topics().await? {
+///         println!("{topic} [{topic_type}]");
+///     }
+///     Ok(())
+/// }
+/// ```
+/// Names are fully resolved e.g. "/rosout", and lists are in the order the backend gives them.
+#[async_trait]
+pub trait GraphQuery {
+    /// Every published topic along with its type, as (topic, type)
+    async fn topics(&self) -> GraphQueryResult<Vec<(String, String)>>;
+
+    /// The type of a topic, or None if the topic is unknown
+    async fn topic_type(&self, topic: &str) -> GraphQueryResult<Option<String>>;
+
+    /// The topics of a given type e.g. "rosgraph_msgs/Log"
+    async fn topics_for_type(&self, topic_type: &str) -> GraphQueryResult<Vec<String>>;
+
+    /// The names of every node in the graph
+    async fn nodes(&self) -> GraphQueryResult<Vec<String>>;
+
+    /// The topics published and subscribed to, and services provided by a given node
+    async fn node_details(&self, node: &str) -> GraphQueryResult<NodeDetails>;
+
+    /// The nodes publishing a given topic
+    async fn publishers(&self, topic: &str) -> GraphQueryResult<Vec<String>>;
+
+    /// The nodes subscribed to a given topic
+    async fn subscribers(&self, topic: &str) -> GraphQueryResult<Vec<String>>;
+
+    /// The names of every service in the graph
+    async fn services(&self) -> GraphQueryResult<Vec<String>>;
+
+    /// The node providing a given service, or None if the service is unknown
+    async fn service_node(&self, service: &str) -> GraphQueryResult<Option<String>>;
+}
+
+#[cfg(feature = "ros1")]
+mod native {
+    use super::{GraphQuery, GraphQueryResult, NodeDetails};
+    use crate::{MasterClient, NodeHandle, StateEntry};
+    use async_trait::async_trait;
+
+    // The nodes listed for `name`
+    pub(super) fn nodes_of(entries: &[StateEntry], name: &str) -> Vec<String> {
+        entries
+            .iter()
+            .find(|entry| entry.topic == name)
+            .map(|entry| entry.nodes.clone())
+            .unwrap_or_default()
+    }
+
+    // The names `node` is listed for
+    pub(super) fn names_of(entries: &[StateEntry], node: &str) -> Vec<String> {
+        entries
+            .iter()
+            .filter(|entry| entry.nodes.iter().any(|name| name == node))
+            .map(|entry| entry.topic.clone())
+            .collect()
+    }
+
+    // Every node listed, sorted and without duplicates
+    pub(super) fn all_nodes(entries: &[&[StateEntry]]) -> Vec<String> {
+        let mut nodes: Vec<String> = entries
+            .iter()
+            .flat_map(|entries| entries.iter())
+            .flat_map(|entry| entry.nodes.iter().cloned())
+            .collect();
+        nodes.sort();
+        nodes.dedup();
+        nodes
+    }
+
+    #[async_trait]
+    impl GraphQuery for MasterClient {
+        async fn topics(&self) -> GraphQueryResult<Vec<(String, String)>> {
+            Ok(self.get_published_topics("").await?)
+        }
+
+        async fn topic_type(&self, topic: &str) -> GraphQueryResult<Option<String>> {
+            // Unlike getPublishedTopics this includes topics which are only subscribed to
+            Ok(self
+                .get_topic_types()
+                .await?
+                .into_iter()
+                .find(|(name, _)| name == topic)
+                .map(|(_, topic_type)| topic_type))
+        }
+
+        async fn topics_for_type(&self, topic_type: &str) -> GraphQueryResult<Vec<String>> {
+            Ok(self
+                .get_topic_types()
+                .await?
+                .into_iter()
+                .filter(|(_, name)| name == topic_type)
+                .map(|(topic, _)| topic)
+                .collect())
+        }
+
+        async fn nodes(&self) -> GraphQueryResult<Vec<String>> {
+            let state = self.get_system_state().await?;
+            Ok(all_nodes(&[
+                state.publishers(),
+                state.subscribers(),
+                state.service_providers(),
+            ]))
+        }
+
+        async fn node_details(&self, node: &str) -> GraphQueryResult<NodeDetails> {
+            let state = self.get_system_state().await?;
+            Ok(NodeDetails {
+                publishing: names_of(state.publishers(), node),
+                subscribing: names_of(state.subscribers(), node),
+                services: names_of(state.service_providers(), node),
+            })
+        }
+
+        async fn publishers(&self, topic: &str) -> GraphQueryResult<Vec<String>> {
+            Ok(nodes_of(self.get_system_state().await?.publishers(), topic))
+        }
+
+        async fn subscribers(&self, topic: &str) -> GraphQueryResult<Vec<String>> {
+            Ok(nodes_of(
+                self.get_system_state().await?.subscribers(),
+                topic,
+            ))
+        }
+
+        async fn services(&self) -> GraphQueryResult<Vec<String>> {
+            Ok(self
+                .get_system_state()
+                .await?
+                .service_providers()
+                .iter()
+                .map(|entry| entry.topic.clone())
+                .collect())
+        }
+
+        async fn service_node(&self, service: &str) -> GraphQueryResult<Option<String>> {
+            // Only one node can provide a service
+            Ok(
+                nodes_of(self.get_system_state().await?.service_providers(), service)
+                    .into_iter()
+                    .next(),
+            )
+        }
+    }
+
+    #[async_trait]
+    impl GraphQuery for NodeHandle {
+        async fn topics(&self) -> GraphQueryResult<Vec<(String, String)>> {
+            self.master_client().await?.topics().await
+        }
+
+        async fn topic_type(&self, topic: &str) -> GraphQueryResult<Option<String>> {
+            self.master_client().await?.topic_type(topic).await
+        }
+
+        async fn topics_for_type(&self, topic_type: &str) -> GraphQueryResult<Vec<String>> {
+            self.master_client()
+                .await?
+                .topics_for_type(topic_type)
+                .await
+        }
+
+        async fn nodes(&self) -> GraphQueryResult<Vec<String>> {
+            self.master_client().await?.nodes().await
+        }
+
+        async fn node_details(&self, node: &str) -> GraphQueryResult<NodeDetails> {
+            self.master_client().await?.node_details(node).await
+        }
+
+        async fn publishers(&self, topic: &str) -> GraphQueryResult<Vec<String>> {
+            self.master_client().await?.publishers(topic).await
+        }
+
+        async fn subscribers(&self, topic: &str) -> GraphQueryResult<Vec<String>> {
+            self.master_client().await?.subscribers(topic).await
+        }
+
+        async fn services(&self) -> GraphQueryResult<Vec<String>> {
+            self.master_client().await?.services().await
+        }
+
+        async fn service_node(&self, service: &str) -> GraphQueryResult<Option<String>> {
+            self.master_client().await?.service_node(service).await
+        }
+    }
+}
+
+#[cfg(feature = "ros1")]
+#[cfg(test)]
+mod test {
+    use super::native::{all_nodes, names_of, nodes_of};
+    use crate::StateEntry;
+
+    fn entry(topic: &str, nodes: &[&str]) -> StateEntry {
+        StateEntry {
+            topic: topic.to_owned(),
+            nodes: nodes.iter().map(|node| node.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn system_state_answers_graph_queries() {
+        let publishers = [
+            entry("/rosout_agg", &["/rosout"]),
+            entry("/chatter", &["/talker", "/relay"]),
+        ];
+        let subscribers = [
+            entry("/rosout", &["/rosout"]),
+            entry("/chatter", &["/listener"]),
+        ];
+        let services = [entry("/rosout/get_loggers", &["/rosout"])];
+
+        assert_eq!(nodes_of(&publishers, "/chatter"), ["/talker", "/relay"]);
+        assert!(nodes_of(&subscribers, "/unknown").is_empty());
+        assert_eq!(names_of(&publishers, "/relay"), ["/chatter"]);
+        assert_eq!(names_of(&subscribers, "/rosout"), ["/rosout"]);
+        assert_eq!(
+            all_nodes(&[&publishers, &subscribers, &services]),
+            ["/listener", "/relay", "/rosout", "/talker"]
+        );
+    }
+}
@@ -98,12 +98,62 @@
 //! Specifically, roslibrust attempts to follow "good" ros error handling convention and be as compatible as possible
 //! with various error types; however, due to the async nature of the crate `Box<dyn Error + Send + Sync>` is needed.
 
+#[cfg(feature = "rosbridge")]
 mod rosbridge;
+#[cfg(feature = "rosbridge")]
 pub use rosbridge::*;
 
+mod reconnect;
+pub use reconnect::ReconnectPolicy;
+
+pub mod prelude;
+
+#[cfg(any(feature = "rosbridge", feature = "ros1"))]
+mod response_cache;
+
+#[cfg(any(feature = "rosbridge", feature = "ros1"))]
+mod tasks;
+
+/// Type erasure of callback for a service
+/// Internally this will covert the input string to the Request type
+/// Send that converted type into the user's callback
+/// Get the result of the user's callback and then serialize that so it can be transmitted
+// TODO reconsider use of serde_json::Value here vs. tungstenite::Message vs. &str
+// Not quite sure what type we want to erase to?
+// I can make a good argument for &str because that should be generic even if we switch
+// backends - Carter 2022-10-6
+#[cfg(any(feature = "rosbridge", feature = "ros1"))]
+pub(crate) type ServiceCallback = Box<
+    dyn Fn(&str) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>
+        + Send
+        + Sync,
+>;
+
+mod ordered_merge;
+pub use ordered_merge::{OrderedMerge, Stamped};
+
+pub mod trajectory;
+
 #[cfg(feature = "rosapi")]
 pub mod rosapi;
 
+#[cfg(any(feature = "rosapi", feature = "ros1"))]
+mod graph_query;
+#[cfg(any(feature = "rosapi", feature = "ros1"))]
+pub use graph_query::{GraphQuery, NodeDetails};
+
+#[cfg(feature = "config")]
+pub mod config;
+
+#[cfg(feature = "dynamic_reconfigure")]
+pub mod dynamic_reconfigure;
+
+#[cfg(feature = "urdf")]
+pub mod urdf;
+
+#[cfg(feature = "downsample")]
+pub mod downsample;
+
 #[cfg(feature = "ros1")]
 mod ros1;
 #[cfg(feature = "ros1")]
@@ -0,0 +1,281 @@
+//! This module contains OrderedMerge which combines several streams of stamped messages into one ordered by stamp.
+
+use futures::{stream::SelectAll, Stream, StreamExt};
+use roslibrust_codegen::Time;
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+/// Messages which carry the time they refer to, usually the `stamp` of their `std_msgs/Header`.
+/// Implement this for generated messages to merge them with [OrderedMerge::source]:
+/// ```ignore
+/// impl roslibrust::Stamped for sensor_msgs::Imu {
+///     fn stamp(&self) -> roslibrust_codegen::Time {
+///         self.header.stamp.clone()
+///     }
+/// }
+/// ```
+pub trait Stamped {
+    fn stamp(&self) -> Time;
+}
+
+type Source<T> = Pin<Box<dyn Stream<Item = (Duration, T)> + Send>>;
+
+/// Merges several streams of messages, e.g. subscriptions to different sensors or topics read from bags,
+/// into a single stream ordered by stamp.
+///
+/// Messages are held back for a reordering window before being released, so messages arriving late by up to the window
+/// are still delivered in order. A message is released once a message stamped at least the window later has arrived,
+/// or once it has been held for the window, so quiet sources do not hold up the others.
+/// Messages arriving after a later stamped message has already been released are dropped, see [OrderedMerge::dropped].
+/// A longer window tolerates more delay between sources at the cost of latency.
+///
+/// Streams of different message types can be merged by mapping each into a common type, e.g. an enum:
+/// ```ignore
+/// enum Sensor {
+///     Imu(sensor_msgs::Imu),
+///     Odom(nav_msgs::Odometry),
+/// }
+/// let mut merged = roslibrust::OrderedMerge::new(Duration::from_millis(100))
+///     .source_with(imu.into_stream().map(Sensor::Imu), |msg| match msg {
+///         Sensor::Imu(imu) => imu.header.stamp.clone(),
+///         Sensor::Odom(odom) => odom.header.stamp.clone(),
+///     })
+///     .source_with(odom.into_stream().map(Sensor::Odom), |msg| ...);
+/// while let Some(msg) = merged.next().await {
+///     // Messages of both topics in stamp order
+/// }
+/// ```
+pub struct OrderedMerge<T> {
+    window: Duration,
+    sources: SelectAll<Source<T>>,
+    pending: BinaryHeap<Pending<T>>,
+    // Latest stamp received and the stamp of the last message released
+    latest: Option<Duration>,
+    released: Option<Duration>,
+    // Orders messages with the same stamp by arrival
+    received: u64,
+    dropped: u64,
+}
+
+impl<T: Send + 'static> OrderedMerge<T> {
+    /// Creates a merge with no sources which reorders messages arriving up to `window` apart
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            sources: SelectAll::new(),
+            pending: BinaryHeap::new(),
+            latest: None,
+            released: None,
+            received: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Adds a source of messages which implement [Stamped]
+    pub fn source(self, source: impl Stream<Item = T> + Send + 'static) -> Self
+    where
+        T: Stamped,
+    {
+        self.source_with(source, T::stamp)
+    }
+
+    /// Adds a source of messages, ordering them by the time `stamp` returns for them
+    pub fn source_with(
+        mut self,
+        source: impl Stream<Item = T> + Send + 'static,
+        stamp: impl Fn(&T) -> Time + Send + 'static,
+    ) -> Self {
+        self.sources.push(Box::pin(
+            source.map(move |msg| (to_duration(&stamp(&msg)), msg)),
+        ));
+        self
+    }
+
+    /// The number of messages dropped because they arrived after a later stamped message was released
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Waits for the next message in stamp order.
+    /// Returns None once every source has ended and all remaining messages have been released.
+    pub async fn next(&mut self) -> Option<T> {
+        loop {
+            let sources_ended = self.sources.is_empty();
+            if let Some(msg) = self.pop_ready(sources_ended) {
+                return Some(msg);
+            }
+            if sources_ended {
+                return None;
+            }
+            // Wakes up when the longest held message has waited for the window
+            let deadline = self
+                .pending
+                .iter()
+                .map(|pending| pending.arrived)
+                .min()
+                .map(|arrived| arrived + self.window);
+            let window_passed = async move {
+                match deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                    None => futures::future::pending().await,
+                }
+            };
+            tokio::select! {
+                received = self.sources.next() => {
+                    if let Some((stamp, msg)) = received {
+                        self.push(stamp, msg);
+                    }
+                }
+                _ = window_passed => {}
+            }
+        }
+    }
+
+    fn push(&mut self, stamp: Duration, msg: T) {
+        if self.released.is_some_and(|released| stamp < released) {
+            log::debug!("Dropping message which arrived after its reordering window had passed");
+            self.dropped += 1;
+            return;
+        }
+        self.latest = self.latest.max(Some(stamp));
+        self.pending.push(Pending {
+            stamp,
+            order: self.received,
+            arrived: Instant::now(),
+            msg,
+        });
+        self.received += 1;
+    }
+
+    // Releases the earliest stamped message if it has waited long enough, or if no more messages can arrive
+    fn pop_ready(&mut self, flush: bool) -> Option<T> {
+        let earliest = self.pending.peek()?;
+        let now = Instant::now();
+        let ready = flush
+            || self
+                .latest
+                .is_some_and(|latest| latest.saturating_sub(earliest.stamp) >= self.window)
+            || self
+                .pending
+                .iter()
+                .any(|pending| now.duration_since(pending.arrived) >= self.window);
+        if !ready {
+            return None;
+        }
+        let released = self.pending.pop()?;
+        self.released = Some(released.stamp);
+        Some(released.msg)
+    }
+}
+
+struct Pending<T> {
+    stamp: Duration,
+    order: u64,
+    arrived: Instant,
+    msg: T,
+}
+
+// BinaryHeap is a max heap, so the ordering is reversed to pop the earliest stamp first
+impl<T> Ord for Pending<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.stamp, other.order).cmp(&(self.stamp, self.order))
+    }
+}
+
+impl<T> PartialOrd for Pending<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for Pending<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Pending<T> {}
+
+pub(crate) fn to_duration(time: &Time) -> Duration {
+    Duration::new(time.secs.into(), time.nsecs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{OrderedMerge, Stamped};
+    use roslibrust_codegen::Time;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    #[derive(Debug, PartialEq)]
+    struct Reading(u32);
+
+    impl Stamped for Reading {
+        fn stamp(&self) -> Time {
+            Time {
+                secs: self.0,
+                nsecs: 0,
+            }
+        }
+    }
+
+    fn channel_source() -> (
+        mpsc::UnboundedSender<Reading>,
+        impl futures::Stream<Item = Reading> + Send,
+    ) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let source = futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|msg| (msg, receiver))
+        });
+        (sender, source)
+    }
+
+    #[tokio::test]
+    async fn merges_sources_in_stamp_order() {
+        let (a, source_a) = channel_source();
+        let (b, source_b) = channel_source();
+        let mut merged = OrderedMerge::new(Duration::from_secs(2))
+            .source(source_a)
+            .source(source_b);
+
+        a.send(Reading(1)).unwrap();
+        b.send(Reading(2)).unwrap();
+        a.send(Reading(5)).unwrap();
+        assert_eq!(merged.next().await, Some(Reading(1)));
+        assert_eq!(merged.next().await, Some(Reading(2)));
+
+        // Late, but still within the window of the latest message
+        b.send(Reading(3)).unwrap();
+        assert_eq!(merged.next().await, Some(Reading(3)));
+
+        // Too late, as a later message has already been released
+        a.send(Reading(0)).unwrap();
+        b.send(Reading(8)).unwrap();
+        assert_eq!(merged.next().await, Some(Reading(5)));
+        assert_eq!(merged.dropped(), 1);
+
+        // Everything left is released once the sources end
+        drop((a, b));
+        assert_eq!(merged.next().await, Some(Reading(8)));
+        assert_eq!(merged.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn quiet_sources_do_not_hold_up_messages() {
+        let (sender, source) = channel_source();
+        let mut merged = OrderedMerge::new(Duration::from_millis(100)).source(source);
+        sender.send(Reading(1)).unwrap();
+        sender.send(Reading(0)).unwrap();
+
+        // Released once they have been held for the window, even though no later messages arrive
+        let start = std::time::Instant::now();
+        assert_eq!(merged.next().await, Some(Reading(0)));
+        assert_eq!(merged.next().await, Some(Reading(1)));
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert_eq!(merged.dropped(), 0);
+    }
+}
@@ -0,0 +1,26 @@
+//! The stable, supported surface of roslibrust, for glob importing with `use roslibrust::prelude::*;`
+//!
+//! Everything re-exported here follows semver: it is only changed incompatibly in a breaking release.
+//! Items outside of the prelude, e.g. the experimental topic provider traits, may change in any release.
+//! The surface of the prelude is recorded in `tests/public_api.txt` and checked by the `public_api` test,
+//! so changes to it are deliberate.
+//!
+//! Each backend's items are only present with its feature enabled. As both backends have a `Publisher` and
+//! `Subscriber`, only rosbridge's are exported by name, the native ros1 ones are reached through
+//! [NodeHandle::advertise](crate::NodeHandle::advertise) and [NodeHandle::subscribe](crate::NodeHandle::subscribe).
+
+pub use crate::{OrderedMerge, ReconnectPolicy, Stamped};
+pub use roslibrust_codegen::{RosMessageType, RosServiceType, Time};
+
+#[cfg(feature = "rosbridge")]
+pub use crate::rosbridge::{
+    ClientHandle, ClientHandleOptions, Encoding, Publisher, RosLibRustError, RosLibRustResult,
+    ServiceHandle, Subscriber,
+};
+
+#[cfg(feature = "ros1")]
+pub use crate::ros1::{
+    CallbackPanic, Clock, InvalidNameError, MessageInfo, NodeHandle, NodeHandleOptions,
+    NodeShutdownError, Remappings, RosMasterError, ServiceClient, ServiceHandshakeError,
+    TopicOptions,
+};
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+/// How a connection is retried, both when first connecting and after the connection is lost.
+/// Used by the rosbridge [crate::ClientHandle] for its connection to rosbridge, and by ROS1 native nodes
+/// for their connections to publishers.
+///
+/// The delay between attempts starts at the initial delay and doubles after each failed attempt, up to the max delay.
+/// The default retries forever every 200ms. If a maximum number of attempts is set and reached while reconnecting,
+/// the connection is given up on; a rosbridge client stays disconnected and its operations fail with
+/// `RosLibRustError::Disconnected`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    initial_delay: Duration,
+    max_delay: Duration,
+    max_attempts: Option<u32>,
+}
+
+pub(crate) const DEFAULT_RECONNECT_DELAY: Duration = Duration::from_millis(200);
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::fixed(DEFAULT_RECONNECT_DELAY)
+    }
+}
+
+impl ReconnectPolicy {
+    /// Retries with the same `delay` between every attempt
+    pub fn fixed(delay: Duration) -> Self {
+        Self::exponential(delay, delay)
+    }
+
+    /// Retries after `initial_delay`, doubling the delay after each failed attempt up to `max_delay`
+    pub fn exponential(initial_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            initial_delay,
+            max_delay: max_delay.max(initial_delay),
+            max_attempts: None,
+        }
+    }
+
+    /// Gives up after `attempts` failed attempts in a row
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+
+    /// The delay before retrying after `failed_attempts` attempts in a row have failed,
+    /// or None if no further attempts should be made.
+    pub(crate) fn delay(&self, failed_attempts: u32) -> Option<Duration> {
+        if self
+            .max_attempts
+            .is_some_and(|max_attempts| failed_attempts >= max_attempts)
+        {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(failed_attempts.saturating_sub(1));
+        Some(
+            self.initial_delay
+                .saturating_mul(factor)
+                .min(self.max_delay),
+        )
+    }
+}
@@ -0,0 +1,79 @@
+//! This module contains the ResponseCache shared by the service clients of both backends.
+
+use serde::Serialize;
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+// Each response is stored alongside when it was inserted
+type Entries = HashMap<Vec<u8>, (Instant, Box<dyn Any + Send + Sync>)>;
+
+/// A time limited cache of the responses of a single service, keyed by the serialized request.
+/// Only suitable for idempotent services, where calling again with the same request gives the same response.
+/// Clones share the same entries.
+#[derive(Clone)]
+pub(crate) struct ResponseCache {
+    ttl: Duration,
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the key a request is cached under, or None if the request cannot be serialized.
+    pub fn key<Req: Serialize>(request: &Req) -> Option<Vec<u8>> {
+        serde_json::to_vec(request).ok()
+    }
+
+    /// Returns the cached response to the request with `key` if there is one that has not expired.
+    /// A response cached with a different type is treated as missing.
+    pub fn get<Res: Clone + 'static>(&self, key: &[u8]) -> Option<Res> {
+        let entries = self.entries.lock().unwrap();
+        let (inserted, response) = entries.get(key)?;
+        if inserted.elapsed() > self.ttl {
+            return None;
+        }
+        response.downcast_ref::<Res>().cloned()
+    }
+
+    pub fn insert<Res: Clone + Send + Sync + 'static>(&self, key: Vec<u8>, response: &Res) {
+        let mut entries = self.entries.lock().unwrap();
+        // Expired entries are only removed here, which bounds the cache to the requests made within one ttl
+        entries.retain(|_, (inserted, _)| inserted.elapsed() <= self.ttl);
+        entries.insert(key, (Instant::now(), Box::new(response.clone())));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ResponseCache;
+    use std::time::Duration;
+
+    #[test]
+    fn cached_responses_expire() {
+        let cache = ResponseCache::new(Duration::from_millis(50));
+        let key = ResponseCache::key(&"/topic").unwrap();
+        assert_eq!(cache.get::<String>(&key), None);
+
+        cache.insert(key.clone(), &"std_msgs/String".to_owned());
+        assert_eq!(
+            cache.get::<String>(&key).as_deref(),
+            Some("std_msgs/String")
+        );
+        // Looking up with the wrong type misses rather than failing
+        assert_eq!(cache.get::<u32>(&key), None);
+        let other_key = ResponseCache::key(&"/other_topic").unwrap();
+        assert_eq!(cache.get::<String>(&other_key), None);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get::<String>(&key), None);
+    }
+}
@@ -0,0 +1,832 @@
+//! This module contains the ActionClient which sends goals to ROS1 action servers natively, like actionlib's SimpleActionClient.
+
+use super::{node::ShutdownReason, publisher::Publisher, rosout::Header, Clock, NodeHandle};
+use abort_on_drop::ChildTask;
+use futures::StreamExt;
+use roslibrust_codegen::{RosActionType, RosMessageType, Time};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::sync::{mpsc, watch};
+
+// Matches the default queue sizes of actionlib's clients
+const PUBLISHER_QUEUE_SIZE: usize = 10;
+const SUBSCRIBER_QUEUE_SIZE: usize = 100;
+// Feedback beyond this which has not been read from a goal's handle is dropped
+const FEEDBACK_QUEUE_SIZE: usize = 100;
+const WAIT_FOR_SERVER_POLL_PERIOD: Duration = Duration::from_millis(50);
+
+// Hand written equivalents of actionlib_msgs/GoalID and actionlib_msgs/GoalStatus(Array),
+// so the client does not depend on generated messages
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+struct GoalId {
+    stamp: Time,
+    id: String,
+}
+
+impl RosMessageType for GoalId {
+    const ROS_TYPE_NAME: &'static str = "actionlib_msgs/GoalID";
+    const MD5SUM: &'static str = "302881f31927c1df708a2dbab0e80ee8";
+    const DEFINITION: &'static str = r#"# The stamp should store the time at which this goal was requested.
+# It is used by an action server when it tries to preempt all
+# goals that were requested before a certain time
+time stamp
+
+# The id provides a way to associate feedback and
+# result message with specific goal requests. The id
+# specified must be unique.
+string id
+
+"#;
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+struct GoalStatus {
+    goal_id: GoalId,
+    status: u8,
+    text: String,
+}
+
+impl GoalStatus {
+    const PENDING: u8 = 0;
+    const ACTIVE: u8 = 1;
+    const PREEMPTED: u8 = 2;
+    const SUCCEEDED: u8 = 3;
+    const ABORTED: u8 = 4;
+    const REJECTED: u8 = 5;
+    const PREEMPTING: u8 = 6;
+    const RECALLING: u8 = 7;
+    const RECALLED: u8 = 8;
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+struct GoalStatusArray {
+    header: Header,
+    status_list: Vec<GoalStatus>,
+}
+
+impl RosMessageType for GoalStatusArray {
+    const ROS_TYPE_NAME: &'static str = "actionlib_msgs/GoalStatusArray";
+    const MD5SUM: &'static str = "8b2b82f13216d0a8ea88bd3af735e619";
+    const DEFINITION: &'static str = r#"# Stores the statuses for goals that are currently being tracked
+# by an action server
+Header header
+GoalStatus[] status_list
+
+
+================================================================================
+MSG: std_msgs/Header
+# Standard metadata for higher-level stamped data types.
+# This is generally used to communicate timestamped data
+# in a particular coordinate frame.
+#
+# sequence ID: consecutively increasing ID
+uint32 seq
+#Two-integer timestamp that is expressed as:
+# * stamp.sec: seconds (stamp_secs) since epoch (in Python the variable is called 'secs')
+# * stamp.nsec: nanoseconds since stamp_secs (in Python the variable is called 'nsecs')
+# time-handling sugar is provided by the client library
+time stamp
+#Frame this data is associated with
+string frame_id
+
+================================================================================
+MSG: actionlib_msgs/GoalStatus
+GoalID goal_id
+uint8 status
+uint8 PENDING         = 0   # The goal has yet to be processed by the action server
+uint8 ACTIVE          = 1   # The goal is currently being processed by the action server
+uint8 PREEMPTED       = 2   # The goal received a cancel request after it started executing
+                            #   and has since completed its execution (Terminal State)
+uint8 SUCCEEDED       = 3   # The goal was achieved successfully by the action server (Terminal State)
+uint8 ABORTED         = 4   # The goal was aborted during execution by the action server due
+                            #    to some failure (Terminal State)
+uint8 REJECTED        = 5   # The goal was rejected by the action server without being processed,
+                            #    because the goal was unattainable or invalid (Terminal State)
+uint8 PREEMPTING      = 6   # The goal received a cancel request after it started executing
+                            #    and has not yet completed execution
+uint8 RECALLING       = 7   # The goal received a cancel request before it started executing,
+                            #    but the action server has not yet confirmed that the goal is canceled
+uint8 RECALLED        = 8   # The goal received a cancel request before it started executing
+                            #    and was successfully cancelled (Terminal State)
+uint8 LOST            = 9   # An action client can determine that a goal is LOST. This should not be
+                            #    sent over the wire by an action server
+
+#Allow for the user to associate a string with GoalStatus for debugging
+string text
+
+
+================================================================================
+MSG: actionlib_msgs/GoalID
+# The stamp should store the time at which this goal was requested.
+# It is used by an action server when it tries to preempt all
+# goals that were requested before a certain time
+time stamp
+
+# The id provides a way to associate feedback and
+# result message with specific goal requests. The id
+# specified must be unique.
+string id
+
+"#;
+}
+
+// The messages an action expands into, generic over the action so any generated action can be used.
+// They serialize the same as the action's generated ActionGoal, ActionResult and ActionFeedback messages,
+// and take their type names and md5sums from them.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(bound = "")]
+struct ActionGoal<A: RosActionType> {
+    header: Header,
+    goal_id: GoalId,
+    goal: A::Goal,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(bound = "")]
+struct ActionResult<A: RosActionType> {
+    header: Header,
+    status: GoalStatus,
+    result: A::Result,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(bound = "")]
+struct ActionFeedback<A: RosActionType> {
+    header: Header,
+    status: GoalStatus,
+    feedback: A::Feedback,
+}
+
+// Deriving these would require the action's marker type to implement them too
+impl<A: RosActionType> Clone for ActionGoal<A> {
+    fn clone(&self) -> Self {
+        Self {
+            header: self.header.clone(),
+            goal_id: self.goal_id.clone(),
+            goal: self.goal.clone(),
+        }
+    }
+}
+
+impl<A: RosActionType> std::fmt::Debug for ActionGoal<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActionGoal")
+            .field("header", &self.header)
+            .field("goal_id", &self.goal_id)
+            .field("goal", &self.goal)
+            .finish()
+    }
+}
+
+impl<A: RosActionType> Clone for ActionResult<A> {
+    fn clone(&self) -> Self {
+        Self {
+            header: self.header.clone(),
+            status: self.status.clone(),
+            result: self.result.clone(),
+        }
+    }
+}
+
+impl<A: RosActionType> std::fmt::Debug for ActionResult<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActionResult")
+            .field("header", &self.header)
+            .field("status", &self.status)
+            .field("result", &self.result)
+            .finish()
+    }
+}
+
+impl<A: RosActionType> Clone for ActionFeedback<A> {
+    fn clone(&self) -> Self {
+        Self {
+            header: self.header.clone(),
+            status: self.status.clone(),
+            feedback: self.feedback.clone(),
+        }
+    }
+}
+
+impl<A: RosActionType> std::fmt::Debug for ActionFeedback<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActionFeedback")
+            .field("header", &self.header)
+            .field("status", &self.status)
+            .field("feedback", &self.feedback)
+            .finish()
+    }
+}
+
+impl<A: RosActionType + 'static> RosMessageType for ActionGoal<A> {
+    const ROS_TYPE_NAME: &'static str = A::ActionGoal::ROS_TYPE_NAME;
+    const MD5SUM: &'static str = A::ActionGoal::MD5SUM;
+    const DEFINITION: &'static str = A::ActionGoal::DEFINITION;
+}
+
+impl<A: RosActionType + 'static> RosMessageType for ActionResult<A> {
+    const ROS_TYPE_NAME: &'static str = A::ActionResult::ROS_TYPE_NAME;
+    const MD5SUM: &'static str = A::ActionResult::MD5SUM;
+    const DEFINITION: &'static str = A::ActionResult::DEFINITION;
+}
+
+impl<A: RosActionType + 'static> RosMessageType for ActionFeedback<A> {
+    const ROS_TYPE_NAME: &'static str = A::ActionFeedback::ROS_TYPE_NAME;
+    const MD5SUM: &'static str = A::ActionFeedback::MD5SUM;
+    const DEFINITION: &'static str = A::ActionFeedback::DEFINITION;
+}
+
+/// Where a goal is in its exchange with the action server, following actionlib's client goal state machine.
+/// Goals only ever move forward through these states, in the order they are declared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CommState {
+    /// The goal was sent, but the server has not reported it yet
+    WaitingForGoalAck,
+    /// The server has accepted the goal, but not started working on it
+    Pending,
+    /// The server is working on the goal
+    Active,
+    /// The goal was canceled, but the server has not reported the cancellation yet
+    WaitingForCancelAck,
+    /// The server is canceling the goal before it started working on it
+    Recalling,
+    /// The server is canceling the goal while working on it
+    Preempting,
+    /// The server has finished with the goal, but its result has not been received yet
+    WaitingForResult,
+    /// The result of the goal has been received
+    Done,
+}
+
+impl CommState {
+    // The state a status reported by the server moves a goal to
+    fn from_status(status: u8) -> Option<Self> {
+        match status {
+            GoalStatus::PENDING => Some(Self::Pending),
+            GoalStatus::ACTIVE => Some(Self::Active),
+            GoalStatus::RECALLING => Some(Self::Recalling),
+            GoalStatus::PREEMPTING => Some(Self::Preempting),
+            GoalStatus::PREEMPTED
+            | GoalStatus::SUCCEEDED
+            | GoalStatus::ABORTED
+            | GoalStatus::REJECTED
+            | GoalStatus::RECALLED => Some(Self::WaitingForResult),
+            // LOST is only ever determined by clients
+            _ => None,
+        }
+    }
+}
+
+/// How the action server finished with a goal, as reported along with its result
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TerminalState {
+    /// The goal was canceled after the server started working on it
+    Preempted,
+    /// The server achieved the goal
+    Succeeded,
+    /// The server failed to achieve the goal
+    Aborted,
+    /// The server refused to work on the goal
+    Rejected,
+    /// The goal was canceled before the server started working on it
+    Recalled,
+    /// The server reported a result without a terminal status
+    Lost,
+}
+
+impl TerminalState {
+    fn from_status(status: u8) -> Self {
+        match status {
+            GoalStatus::PREEMPTED => Self::Preempted,
+            GoalStatus::SUCCEEDED => Self::Succeeded,
+            GoalStatus::ABORTED => Self::Aborted,
+            GoalStatus::REJECTED => Self::Rejected,
+            GoalStatus::RECALLED => Self::Recalled,
+            _ => Self::Lost,
+        }
+    }
+}
+
+/// The outcome of a goal, returned by [GoalHandle::result]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GoalResult<R> {
+    /// How the server finished with the goal
+    pub state: TerminalState,
+    /// The text the server gave along with the final status of the goal
+    pub text: String,
+    /// The result the server sent, only meaningful if the goal [succeeded](GoalResult::succeeded) unless the action says otherwise
+    pub result: R,
+}
+
+impl<R> GoalResult<R> {
+    pub fn succeeded(&self) -> bool {
+        self.state == TerminalState::Succeeded
+    }
+}
+
+// The progress of a goal as seen by its handle
+struct Progress<R> {
+    state: CommState,
+    result: Option<GoalResult<R>>,
+}
+
+impl<R> Progress<R> {
+    // Moves the goal to `state` unless it is already past it
+    fn advance(&mut self, state: CommState) -> bool {
+        if state > self.state {
+            self.state = state;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn finish(&mut self, status: &GoalStatus, result: R) {
+        self.state = CommState::Done;
+        self.result = Some(GoalResult {
+            state: TerminalState::from_status(status.status),
+            text: status.text.clone(),
+            result,
+        });
+    }
+}
+
+// Where the updates received for a goal are routed
+struct GoalEntry<A: RosActionType> {
+    progress: watch::Sender<Progress<A::Result>>,
+    feedback: mpsc::Sender<A::Feedback>,
+}
+
+// State shared between the client, its goal handles and the task receiving from the server
+struct Shared<A: RosActionType + 'static> {
+    action_namespace: String,
+    node_name: String,
+    clock: Clock,
+    shutdown_reason: ShutdownReason,
+    goal_publisher: Publisher<ActionGoal<A>>,
+    cancel_publisher: Publisher<GoalId>,
+    goals: Mutex<HashMap<String, GoalEntry<A>>>,
+    next_goal: AtomicU64,
+    // Set once the server has published its status
+    server_seen: watch::Sender<bool>,
+}
+
+impl<A: RosActionType + 'static> Shared<A> {
+    // Follows actionlib's goal ids, which include the client's node name to make them unique
+    fn next_goal_id(&self, stamp: &Time) -> String {
+        let count = self.next_goal.fetch_add(1, Ordering::Relaxed) + 1;
+        format!(
+            "{}-{count}-{}.{:09}",
+            self.node_name, stamp.secs, stamp.nsecs
+        )
+    }
+
+    fn advance(&self, goal_id: &str, state: CommState) {
+        if let Some(entry) = self.goals.lock().unwrap().get(goal_id) {
+            entry
+                .progress
+                .send_if_modified(|progress| progress.advance(state));
+        }
+    }
+
+    fn handle_status(&self, statuses: &GoalStatusArray) {
+        self.server_seen.send_replace(true);
+        for status in &statuses.status_list {
+            if let Some(state) = CommState::from_status(status.status) {
+                self.advance(&status.goal_id.id, state);
+            }
+        }
+    }
+
+    fn handle_feedback(&self, feedback: ActionFeedback<A>) {
+        let goals = self.goals.lock().unwrap();
+        let Some(entry) = goals.get(&feedback.status.goal_id.id) else {
+            return;
+        };
+        if let Some(state) = CommState::from_status(feedback.status.status) {
+            entry
+                .progress
+                .send_if_modified(|progress| progress.advance(state));
+        }
+        if entry.feedback.try_send(feedback.feedback).is_err() {
+            log::debug!(
+                "Dropping feedback for goal {} of {}, none has been read recently",
+                feedback.status.goal_id.id,
+                self.action_namespace
+            );
+        }
+    }
+
+    fn handle_result(&self, result: ActionResult<A>) {
+        // Goals are done once their result arrives, which also ends their feedback
+        let Some(entry) = self.goals.lock().unwrap().remove(&result.status.goal_id.id) else {
+            return;
+        };
+        entry
+            .progress
+            .send_modify(|progress| progress.finish(&result.status, result.result));
+    }
+}
+
+/// A client for a ROS1 action server, created with [NodeHandle::action_client].
+///
+/// Goals are published on the `goal` topic beneath the action's namespace, and followed through the server's
+/// `status`, `feedback` and `result` topics by a [GoalHandle].
+/// The client keeps receiving from the server as long as it or any of its goal handles are alive.
+/// ```no_run
+/// # roslibrust_codegen_macro::find_and_generate_ros_messages!(
+/// #    "assets/ros1_common_interfaces"
+/// # );
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/map_client").await?;
+/// let client = nh.action_client::<nav_msgs::GetMapAction>("/static_map").await?;
+/// client.wait_for_server(None).await?;
+/// let mut goal = client.send_goal(nav_msgs::GetMapGoal {}).await?;
+/// let result = goal.result().await?;
+/// if result.succeeded() {
+///     println!("Received a map {} cells wide", result.result.map.info.width);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct ActionClient<A: RosActionType + 'static> {
+    shared: Arc<Shared<A>>,
+    receive_task: Arc<ChildTask<()>>,
+}
+
+impl<A: RosActionType + 'static> ActionClient<A> {
+    pub(crate) async fn new(
+        nh: &NodeHandle,
+        action_namespace: &str,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let action_namespace = action_namespace.trim_end_matches('/').to_owned();
+        let topic = |name: &str| format!("{action_namespace}/{name}");
+        let goal_publisher = nh
+            .advertise::<ActionGoal<A>>(&topic("goal"), PUBLISHER_QUEUE_SIZE)
+            .await?;
+        let cancel_publisher = nh
+            .advertise::<GoalId>(&topic("cancel"), PUBLISHER_QUEUE_SIZE)
+            .await?;
+        let status = nh
+            .subscribe::<GoalStatusArray>(&topic("status"), SUBSCRIBER_QUEUE_SIZE)
+            .await?;
+        let feedback = nh
+            .subscribe::<ActionFeedback<A>>(&topic("feedback"), SUBSCRIBER_QUEUE_SIZE)
+            .await?;
+        let result = nh
+            .subscribe::<ActionResult<A>>(&topic("result"), SUBSCRIBER_QUEUE_SIZE)
+            .await?;
+        let node_name = format!("/{}", nh.get_node_name().await?.trim_start_matches('/'));
+
+        let shared = Arc::new(Shared {
+            node_name,
+            clock: nh.clock().clone(),
+            shutdown_reason: nh.inner.shutdown_reason().clone(),
+            goal_publisher,
+            cancel_publisher,
+            goals: Mutex::new(HashMap::new()),
+            next_goal: AtomicU64::new(0),
+            server_seen: watch::channel(false).0,
+            action_namespace,
+        });
+        let receive_task = crate::tasks::spawn(
+            &format!("action client of {}", shared.action_namespace),
+            Self::receive(
+                shared.clone(),
+                status.into_stream(),
+                feedback.into_stream(),
+                result.into_stream(),
+            ),
+        );
+        Ok(Self {
+            shared,
+            receive_task: Arc::new(receive_task.into()),
+        })
+    }
+
+    async fn receive(
+        shared: Arc<Shared<A>>,
+        status: impl futures::Stream<Item = GoalStatusArray>,
+        feedback: impl futures::Stream<Item = ActionFeedback<A>>,
+        result: impl futures::Stream<Item = ActionResult<A>>,
+    ) {
+        receive_all(
+            status,
+            feedback,
+            result,
+            |status| shared.handle_status(&status),
+            |feedback| shared.handle_feedback(feedback),
+            |result| shared.handle_result(result),
+        )
+        .await;
+        // Dropping the goals wakes their handles, which then report the shutdown
+        shared.goals.lock().unwrap().clear();
+    }
+
+    /// The resolved namespace of the action's topics
+    pub fn action_namespace(&self) -> &str {
+        &self.shared.action_namespace
+    }
+
+    /// Waits until the action server has published its status and subscribed to the client's goals and cancellations,
+    /// after which goals sent are received by the server.
+    /// Waits indefinitely if `timeout` is None, otherwise fails with [std::io::ErrorKind::TimedOut] once it passes.
+    /// Also fails if the node shuts down while waiting.
+    pub async fn wait_for_server(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let wait = async {
+            let mut server_seen = self.shared.server_seen.subscribe();
+            loop {
+                if *server_seen.borrow_and_update()
+                    && self.shared.goal_publisher.get_num_subscribers() > 0
+                    && self.shared.cancel_publisher.get_num_subscribers() > 0
+                {
+                    return Ok(());
+                }
+                if self.receive_task.is_finished() {
+                    return Err(self.shared.shutdown_reason.error().into());
+                }
+                tokio::time::sleep(WAIT_FOR_SERVER_POLL_PERIOD).await;
+            }
+        };
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, wait).await.map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!(
+                        "Timed out waiting for action server {}",
+                        self.shared.action_namespace
+                    ),
+                )
+            })?,
+            None => wait.await,
+        }
+    }
+
+    /// Sends `goal` to the action server, returning a handle for following it to its result.
+    /// Goals sent before the server is connected are lost, see [ActionClient::wait_for_server].
+    pub async fn send_goal(
+        &self,
+        goal: A::Goal,
+    ) -> Result<GoalHandle<A>, Box<dyn std::error::Error + Send + Sync>> {
+        let stamp = self.shared.clock.now();
+        let goal_id = GoalId {
+            id: self.shared.next_goal_id(&stamp),
+            stamp: stamp.clone(),
+        };
+        let (progress_sender, progress) = watch::channel(Progress {
+            state: CommState::WaitingForGoalAck,
+            result: None,
+        });
+        let (feedback_sender, feedback) = mpsc::channel(FEEDBACK_QUEUE_SIZE);
+        // Registered first so no update from the server can be missed
+        self.shared.goals.lock().unwrap().insert(
+            goal_id.id.clone(),
+            GoalEntry {
+                progress: progress_sender,
+                feedback: feedback_sender,
+            },
+        );
+        let handle = GoalHandle {
+            goal_id: goal_id.id.clone(),
+            progress,
+            feedback,
+            shared: self.shared.clone(),
+            _receive_task: self.receive_task.clone(),
+        };
+        self.shared
+            .goal_publisher
+            .publish(&ActionGoal {
+                header: Header {
+                    seq: 0,
+                    stamp,
+                    frame_id: String::new(),
+                },
+                goal_id,
+                goal,
+            })
+            .await?;
+        Ok(handle)
+    }
+
+    /// Sends `goal` to the action server and waits for its result, discarding any feedback.
+    pub async fn send_goal_and_wait(
+        &self,
+        goal: A::Goal,
+    ) -> Result<GoalResult<A::Result>, Box<dyn std::error::Error + Send + Sync>> {
+        self.send_goal(goal).await?.result().await
+    }
+
+    /// Asks the action server to cancel every goal it is working on, including those sent by other clients.
+    pub async fn cancel_all_goals(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // An empty id with a zero stamp matches every goal
+        self.shared
+            .cancel_publisher
+            .publish(&GoalId::default())
+            .await
+    }
+}
+
+// Hands the items of each stream to its handler as they arrive, until all three have ended.
+// The streams only end when the node shuts down, but may do so one at a time with the others still
+// holding items, so each is fused to never be polled again once it has ended.
+async fn receive_all<S, F, R>(
+    status: impl futures::Stream<Item = S>,
+    feedback: impl futures::Stream<Item = F>,
+    result: impl futures::Stream<Item = R>,
+    mut on_status: impl FnMut(S),
+    mut on_feedback: impl FnMut(F),
+    mut on_result: impl FnMut(R),
+) {
+    let (status, feedback, result) = (status.fuse(), feedback.fuse(), result.fuse());
+    futures::pin_mut!(status, feedback, result);
+    loop {
+        tokio::select! {
+            Some(status) = status.next() => on_status(status),
+            Some(feedback) = feedback.next() => on_feedback(feedback),
+            Some(result) = result.next() => on_result(result),
+            else => break,
+        }
+    }
+}
+
+/// A goal sent with [ActionClient::send_goal], for following the goal's progress and receiving its result.
+/// Dropping the handle stops following the goal, but does not cancel it.
+pub struct GoalHandle<A: RosActionType + 'static> {
+    goal_id: String,
+    progress: watch::Receiver<Progress<A::Result>>,
+    feedback: mpsc::Receiver<A::Feedback>,
+    shared: Arc<Shared<A>>,
+    _receive_task: Arc<ChildTask<()>>,
+}
+
+impl<A: RosActionType + 'static> GoalHandle<A> {
+    /// The id the goal was sent with
+    pub fn goal_id(&self) -> &str {
+        &self.goal_id
+    }
+
+    /// Where the goal currently is in its exchange with the server
+    pub fn comm_state(&self) -> CommState {
+        self.progress.borrow().state
+    }
+
+    /// Receives the next feedback the server sent for the goal.
+    /// Returns None once the goal is done and all of its feedback has been received.
+    pub async fn feedback(&mut self) -> Option<A::Feedback> {
+        self.feedback.recv().await
+    }
+
+    /// Waits for the server to finish with the goal, returning its result.
+    /// Fails if the node shuts down first.
+    pub async fn result(
+        &mut self,
+    ) -> Result<GoalResult<A::Result>, Box<dyn std::error::Error + Send + Sync>> {
+        loop {
+            if let Some(result) = &self.progress.borrow_and_update().result {
+                return Ok(result.clone());
+            }
+            if self.progress.changed().await.is_err() {
+                // The result is set before the goal is dropped, so it may have arrived along with the close
+                return match &self.progress.borrow().result {
+                    Some(result) => Ok(result.clone()),
+                    None => Err(self.shared.shutdown_reason.error().into()),
+                };
+            }
+        }
+    }
+
+    /// Asks the server to cancel the goal. The goal is done once its result arrives, as for any other goal.
+    pub async fn cancel(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.shared
+            .advance(&self.goal_id, CommState::WaitingForCancelAck);
+        self.shared
+            .cancel_publisher
+            .publish(&GoalId {
+                stamp: Time::default(),
+                id: self.goal_id.clone(),
+            })
+            .await
+    }
+}
+
+impl<A: RosActionType + 'static> Drop for GoalHandle<A> {
+    fn drop(&mut self) {
+        self.shared.goals.lock().unwrap().remove(&self.goal_id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn status(id: &str, status: u8) -> GoalStatus {
+        GoalStatus {
+            goal_id: GoalId {
+                stamp: Time::default(),
+                id: id.to_owned(),
+            },
+            status,
+            text: String::new(),
+        }
+    }
+
+    // A stream which panics if polled again after ending, like those of subscribers
+    fn stream_of(items: Vec<u8>) -> impl futures::Stream<Item = u8> {
+        futures::stream::unfold(items.into_iter(), |mut items| async move {
+            items.next().map(|item| (item, items))
+        })
+    }
+
+    #[tokio::test]
+    async fn streams_ending_early_are_not_polled_again() {
+        let received = Mutex::new(vec![]);
+        // The status stream ends first while feedback and results are still buffered
+        receive_all(
+            stream_of(vec![]),
+            stream_of(vec![1, 2, 3]),
+            stream_of(vec![4]),
+            |status| received.lock().unwrap().push(status),
+            |feedback| received.lock().unwrap().push(feedback),
+            |result| received.lock().unwrap().push(result),
+        )
+        .await;
+        let mut received = received.into_inner().unwrap();
+        received.sort();
+        assert_eq!(received, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn goals_only_move_forward() {
+        let mut progress = Progress::<()> {
+            state: CommState::WaitingForGoalAck,
+            result: None,
+        };
+        assert!(progress.advance(CommState::Active));
+        // A stale PENDING status arriving after ACTIVE is ignored
+        assert!(!progress.advance(CommState::Pending));
+        assert_eq!(progress.state, CommState::Active);
+        assert!(progress.advance(CommState::WaitingForCancelAck));
+        assert!(progress.advance(CommState::Preempting));
+        assert!(!progress.advance(CommState::WaitingForCancelAck));
+        assert_eq!(progress.state, CommState::Preempting);
+    }
+
+    #[test]
+    fn statuses_map_to_states() {
+        assert_eq!(
+            CommState::from_status(GoalStatus::PENDING),
+            Some(CommState::Pending)
+        );
+        assert_eq!(
+            CommState::from_status(GoalStatus::RECALLING),
+            Some(CommState::Recalling)
+        );
+        for terminal in [
+            GoalStatus::PREEMPTED,
+            GoalStatus::SUCCEEDED,
+            GoalStatus::ABORTED,
+            GoalStatus::REJECTED,
+            GoalStatus::RECALLED,
+        ] {
+            assert_eq!(
+                CommState::from_status(terminal),
+                Some(CommState::WaitingForResult)
+            );
+        }
+        assert_eq!(CommState::from_status(9), None);
+    }
+
+    #[test]
+    fn results_finish_goals() {
+        let mut progress = Progress {
+            state: CommState::Active,
+            result: None,
+        };
+        let mut aborted = status("/client-1-0.000000000", GoalStatus::ABORTED);
+        aborted.text = "Out of range".to_owned();
+        progress.finish(&aborted, 7u8);
+        assert_eq!(progress.state, CommState::Done);
+        assert_eq!(
+            progress.result,
+            Some(GoalResult {
+                state: TerminalState::Aborted,
+                text: "Out of range".to_owned(),
+                result: 7,
+            })
+        );
+        assert!(!progress.advance(CommState::Active));
+
+        progress.finish(&status("", GoalStatus::ACTIVE), 7);
+        assert_eq!(progress.result.unwrap().state, TerminalState::Lost);
+    }
+}
@@ -0,0 +1,129 @@
+//! This module contains the handling of the addresses nodes listen on and advertise over both IPv4 and IPv6.
+//! Nodes listening on every interface bind dual-stack sockets to `::`, accepting IPv4 peers as well as IPv6 ones,
+//! and fall back to `0.0.0.0` on hosts without IPv6.
+
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// The address nodes listen on to accept peers on every interface, over IPv4 and IPv6 where the host supports both
+pub(crate) const UNSPECIFIED: IpAddr = IpAddr::V6(Ipv6Addr::UNSPECIFIED);
+
+// Connections the operating system holds for a listener before refusing more, as std and tokio use
+const BACKLOG: i32 = 1024;
+
+/// Binds a TCP listener to a port the operating system picks on `addr`
+pub(crate) fn bind_tcp(addr: IpAddr) -> std::io::Result<std::net::TcpListener> {
+    let socket = bind(addr, Type::STREAM, Protocol::TCP)?;
+    socket.listen(BACKLOG)?;
+    Ok(socket.into())
+}
+
+/// Binds a UDP socket to a port the operating system picks on `addr`
+pub(crate) fn bind_udp(addr: IpAddr) -> std::io::Result<std::net::UdpSocket> {
+    Ok(bind(addr, Type::DGRAM, Protocol::UDP)?.into())
+}
+
+fn bind(addr: IpAddr, ty: Type, protocol: Protocol) -> std::io::Result<Socket> {
+    match bind_socket(addr, ty, protocol) {
+        Err(err) if addr == UNSPECIFIED => {
+            log::debug!("Failed to bind a dual-stack socket ({err}), listening on IPv4 only");
+            bind_socket(Ipv4Addr::UNSPECIFIED.into(), ty, protocol)
+        }
+        result => result,
+    }
+}
+
+fn bind_socket(addr: IpAddr, ty: Type, protocol: Protocol) -> std::io::Result<Socket> {
+    let addr = SocketAddr::new(addr, 0);
+    let socket = Socket::new(Domain::for_address(addr), ty, Some(protocol))?;
+    // Platforms disagree on whether IPv6 sockets also accept IPv4 by default, Windows and the BSDs don't
+    if addr == SocketAddr::new(UNSPECIFIED, 0) {
+        socket.set_only_v6(false)?;
+    }
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    Ok(socket)
+}
+
+/// Where a socket bound to `local` sends to reach `peer`, None if it can't reach the peer's address family.
+/// Dual-stack sockets reach IPv4 peers through their IPv4-mapped IPv6 addresses.
+pub(crate) fn route(local: SocketAddr, peer: SocketAddr) -> Option<SocketAddr> {
+    match (local.ip(), peer.ip()) {
+        (IpAddr::V4(_), IpAddr::V4(_)) | (IpAddr::V6(_), IpAddr::V6(_)) => Some(peer),
+        (local, IpAddr::V4(ip)) if local == UNSPECIFIED => {
+            Some(SocketAddr::from((ip.to_ipv6_mapped(), peer.port())))
+        }
+        _ => None,
+    }
+}
+
+/// The address of a peer as it is known over IPv4, where it reached a dual-stack socket from an IPv4-mapped address
+pub(crate) fn canonical(addr: SocketAddr) -> SocketAddr {
+    match addr {
+        SocketAddr::V6(v6) => match v6.ip().to_ipv4_mapped() {
+            Some(ip) => SocketAddr::from((ip, v6.port())),
+            None => addr,
+        },
+        SocketAddr::V4(_) => addr,
+    }
+}
+
+/// Joins `host` and `port` as in a URI or endpoint, bracketing IPv6 addresses, e.g. `[fd00::2]:11311`
+pub(crate) fn host_port(host: &str, port: u16) -> String {
+    match host.parse::<Ipv6Addr>() {
+        Ok(_) => format!("[{host}]:{port}"),
+        Err(_) => format!("{host}:{port}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{bind_tcp, canonical, host_port, route, UNSPECIFIED};
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+    use tokio::net::{TcpListener, TcpStream};
+
+    #[test]
+    fn ipv6_hosts_are_bracketed() {
+        assert_eq!(host_port("fd00::2", 11311), "[fd00::2]:11311");
+        assert_eq!(host_port("10.0.0.2", 11311), "10.0.0.2:11311");
+        assert_eq!(host_port("robot.local", 11311), "robot.local:11311");
+    }
+
+    #[test]
+    fn ipv4_peers_are_reached_through_dual_stack_sockets() {
+        let peer = SocketAddr::from((Ipv4Addr::new(10, 0, 0, 2), 4321));
+        let mapped = SocketAddr::from((Ipv4Addr::new(10, 0, 0, 2).to_ipv6_mapped(), 4321));
+        assert_eq!(route(SocketAddr::new(UNSPECIFIED, 1), peer), Some(mapped));
+        assert_eq!(
+            route(SocketAddr::from((Ipv4Addr::LOCALHOST, 1)), peer),
+            Some(peer)
+        );
+        let loopback = SocketAddr::from((Ipv6Addr::LOCALHOST, 1));
+        assert_eq!(route(loopback, peer), None);
+        assert_eq!(canonical(mapped), peer);
+        assert_eq!(canonical(loopback), loopback);
+    }
+
+    #[tokio::test]
+    async fn dual_stack_listeners_accept_ipv4_and_ipv6() {
+        let listener = TcpListener::from_std(bind_tcp(UNSPECIFIED).unwrap()).unwrap();
+        let local = listener.local_addr().unwrap();
+        let accept = tokio::spawn(async move {
+            let (_, first) = listener.accept().await.unwrap();
+            let (_, second) = listener.accept().await.unwrap();
+            (canonical(first), canonical(second))
+        });
+        let v4 = TcpStream::connect((Ipv4Addr::LOCALHOST, local.port()))
+            .await
+            .unwrap();
+        // Hosts without IPv6 fall back to listening on IPv4 alone
+        let second = match local.is_ipv6() {
+            true => TcpStream::connect((Ipv6Addr::LOCALHOST, local.port())).await,
+            false => TcpStream::connect((Ipv4Addr::LOCALHOST, local.port())).await,
+        }
+        .unwrap();
+        let (first, accepted) = accept.await.unwrap();
+        assert_eq!(first, v4.local_addr().unwrap());
+        assert_eq!(accepted, second.local_addr().unwrap());
+    }
+}
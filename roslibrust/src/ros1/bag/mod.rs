@@ -0,0 +1,257 @@
+//! Minimal support for reading and writing ROS1 bag files (format version 2.0)
+//! Implementation is based off of the format description here: http://wiki.ros.org/Bags/Format/2.0
+//! Only uncompressed chunks are supported.
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use roslibrust_codegen::{RosMessageType, Time};
+use std::io::{Cursor, Read, Write};
+
+// Reader is a transparent module, we directly expose internal types
+// Module exists only to organize source code.
+mod reader;
+pub use reader::*;
+
+// Writer is a transparent module, we directly expose internal types
+// Module exists only to organize source code.
+mod writer;
+pub use writer::*;
+
+// Tools is a transparent module, we directly expose internal types
+// Module exists only to organize source code.
+mod tools;
+pub use tools::*;
+
+const BAG_VERSION_LINE: &[u8] = b"#ROSBAG V2.0\n";
+// The bag header record is padded out to this length so it can be re-written in place when the bag is closed
+const BAG_HEADER_RECORD_LENGTH: usize = 4096;
+
+const OP_MSG_DATA: u8 = 0x02;
+const OP_BAG_HEADER: u8 = 0x03;
+const OP_INDEX_DATA: u8 = 0x04;
+const OP_CHUNK: u8 = 0x05;
+const OP_CHUNK_INFO: u8 = 0x06;
+const OP_CONNECTION: u8 = 0x07;
+
+/// Describes a single topic connection stored within a bag file
+#[derive(Clone, Debug, PartialEq)]
+pub struct BagConnection {
+    pub topic: String,
+    pub topic_type: String,
+    pub md5sum: String,
+    pub msg_definition: String,
+}
+
+/// A single message read out of a bag file
+#[derive(Clone, Debug)]
+pub struct BagMessage {
+    /// Id of the connection within the bag the message was recorded on
+    pub connection_id: u32,
+    /// Time the message was recorded at
+    pub time: Time,
+    /// The serialized message body, without the leading TCPROS length prefix
+    pub data: Vec<u8>,
+}
+
+impl BagMessage {
+    /// Attempts to deserialize the message as the given type.
+    /// No checking is performed that `T` matches the type of the connection the message was recorded on.
+    pub fn decode<T: RosMessageType>(&self) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+        // serde_rosmsg expects the length prefix that is stripped when messages are stored in a bag
+        let mut buffer = Vec::with_capacity(self.data.len() + 4);
+        buffer.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+        buffer.extend_from_slice(&self.data);
+        // Gotta do some funny error mapping here as serde_rosmsg's error type is not sync
+        serde_rosmsg::from_slice(&buffer).map_err(|err| format!("{err:?}").into())
+    }
+}
+
+/// Converts a Time into a single value which can be used for ordering comparisons
+fn time_key(time: &Time) -> u64 {
+    ((time.secs as u64) << 32) | time.nsecs as u64
+}
+
+type Fields = Vec<(String, Vec<u8>)>;
+
+fn field_u8(name: &str, value: u8) -> (String, Vec<u8>) {
+    (name.to_owned(), vec![value])
+}
+
+fn field_u32(name: &str, value: u32) -> (String, Vec<u8>) {
+    (name.to_owned(), value.to_le_bytes().to_vec())
+}
+
+fn field_u64(name: &str, value: u64) -> (String, Vec<u8>) {
+    (name.to_owned(), value.to_le_bytes().to_vec())
+}
+
+fn field_time(name: &str, time: &Time) -> (String, Vec<u8>) {
+    let mut value = time.secs.to_le_bytes().to_vec();
+    value.extend_from_slice(&time.nsecs.to_le_bytes());
+    (name.to_owned(), value)
+}
+
+fn field_str(name: &str, value: &str) -> (String, Vec<u8>) {
+    (name.to_owned(), value.as_bytes().to_vec())
+}
+
+/// Serializes a set of `name=value` fields in the same format used by TCPROS connection headers
+fn encode_fields(fields: &[(String, Vec<u8>)]) -> std::io::Result<Vec<u8>> {
+    let mut encoded = vec![];
+    for (name, value) in fields {
+        encoded.write_u32::<LittleEndian>((name.len() + 1 + value.len()) as u32)?;
+        encoded.write_all(name.as_bytes())?;
+        encoded.write_all(b"=")?;
+        encoded.write_all(value)?;
+    }
+    Ok(encoded)
+}
+
+/// Inverse of [encode_fields]
+fn decode_fields(data: &[u8]) -> std::io::Result<Fields> {
+    let mut cursor = Cursor::new(data);
+    let mut fields = vec![];
+    while (cursor.position() as usize) < data.len() {
+        let field_length = cursor.read_u32::<LittleEndian>()? as usize;
+        let mut field = vec![0u8; field_length];
+        cursor.read_exact(&mut field)?;
+        let Some(equals_pos) = field.iter().position(|byte| *byte == b'=') else {
+            return Err(invalid_data("Bag record header field is missing '='"));
+        };
+        let name = String::from_utf8_lossy(&field[..equals_pos]).into_owned();
+        fields.push((name, field[equals_pos + 1..].to_vec()));
+    }
+    Ok(fields)
+}
+
+fn get_field<'a>(fields: &'a Fields, name: &str) -> std::io::Result<&'a [u8]> {
+    fields
+        .iter()
+        .find(|(field_name, _)| field_name == name)
+        .map(|(_, value)| value.as_slice())
+        .ok_or_else(|| invalid_data(format!("Bag record is missing required field {name}")))
+}
+
+fn get_field_u8(fields: &Fields, name: &str) -> std::io::Result<u8> {
+    get_field(fields, name)?
+        .first()
+        .copied()
+        .ok_or_else(|| invalid_data(format!("Bag record field {name} is empty")))
+}
+
+fn get_field_u32(fields: &Fields, name: &str) -> std::io::Result<u32> {
+    Cursor::new(get_field(fields, name)?).read_u32::<LittleEndian>()
+}
+
+fn get_field_u64(fields: &Fields, name: &str) -> std::io::Result<u64> {
+    Cursor::new(get_field(fields, name)?).read_u64::<LittleEndian>()
+}
+
+fn get_field_time(fields: &Fields, name: &str) -> std::io::Result<Time> {
+    let mut cursor = Cursor::new(get_field(fields, name)?);
+    Ok(Time {
+        secs: cursor.read_u32::<LittleEndian>()?,
+        nsecs: cursor.read_u32::<LittleEndian>()?,
+    })
+}
+
+fn get_field_str(fields: &Fields, name: &str) -> std::io::Result<String> {
+    Ok(String::from_utf8_lossy(get_field(fields, name)?).into_owned())
+}
+
+fn invalid_data(msg: impl Into<String>) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg.into())
+}
+
+fn write_record(
+    writer: &mut impl Write,
+    header: &[(String, Vec<u8>)],
+    data: &[u8],
+) -> std::io::Result<()> {
+    let header = encode_fields(header)?;
+    writer.write_u32::<LittleEndian>(header.len() as u32)?;
+    writer.write_all(&header)?;
+    writer.write_u32::<LittleEndian>(data.len() as u32)?;
+    writer.write_all(data)
+}
+
+/// Reads a single record, returning the decoded header fields and the record's data
+fn read_record(reader: &mut impl Read) -> std::io::Result<(Fields, Vec<u8>)> {
+    let header_length = reader.read_u32::<LittleEndian>()? as usize;
+    let mut header = vec![0u8; header_length];
+    reader.read_exact(&mut header)?;
+    let data_length = reader.read_u32::<LittleEndian>()? as usize;
+    let mut data = vec![0u8; data_length];
+    reader.read_exact(&mut data)?;
+    Ok((decode_fields(&header)?, data))
+}
+
+fn connection_record_data(connection: &BagConnection) -> std::io::Result<Vec<u8>> {
+    encode_fields(&[
+        field_str("topic", &connection.topic),
+        field_str("type", &connection.topic_type),
+        field_str("md5sum", &connection.md5sum),
+        field_str("message_definition", &connection.msg_definition),
+    ])
+}
+
+fn parse_connection_record(header: &Fields, data: &[u8]) -> std::io::Result<(u32, BagConnection)> {
+    let id = get_field_u32(header, "conn")?;
+    let data = decode_fields(data)?;
+    Ok((
+        id,
+        BagConnection {
+            topic: get_field_str(header, "topic")?,
+            topic_type: get_field_str(&data, "type")?,
+            md5sum: get_field_str(&data, "md5sum")?,
+            // Not strictly required by the format
+            msg_definition: get_field_str(&data, "message_definition").unwrap_or_default(),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_connection(topic: &str) -> BagConnection {
+        BagConnection {
+            topic: topic.to_owned(),
+            topic_type: "std_msgs/String".to_owned(),
+            md5sum: "992ce8a1687cec8c8bd883ec73ca41d1".to_owned(),
+            msg_definition: "string data".to_owned(),
+        }
+    }
+
+    #[test]
+    fn bag_round_trip() {
+        let path = std::env::temp_dir().join("roslibrust_bag_round_trip.bag");
+        let mut bag = BagWriter::create(&path).unwrap();
+        // Force multiple chunks to be written
+        bag.set_chunk_threshold(64);
+        let chatter = bag.add_connection(test_connection("/chatter"));
+        let other = bag.add_connection(test_connection("/other"));
+        for i in 0..10u32 {
+            let conn = if i % 2 == 0 { chatter } else { other };
+            bag.write_message(conn, &Time { secs: i, nsecs: 0 }, &[1, 0, 0, 0, i as u8])
+                .unwrap();
+        }
+        bag.finish().unwrap();
+
+        let mut reader = BagReader::open(&path).unwrap();
+        assert_eq!(reader.connections().len(), 2);
+        assert!(reader.chunk_count() > 1);
+        let messages = reader.messages().collect::<Result<Vec<_>, _>>().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(messages.len(), 10);
+        for (i, msg) in messages.iter().enumerate() {
+            assert_eq!(msg.time.secs, i as u32);
+            assert_eq!(msg.data, vec![1, 0, 0, 0, i as u8]);
+            let expected_topic = if i % 2 == 0 { "/chatter" } else { "/other" };
+            assert_eq!(
+                reader.connections()[&msg.connection_id].topic,
+                expected_topic
+            );
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Reading side of the bag subsystem, see [BagReader]
+
+use super::*;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs::File,
+    io::{BufReader, Seek, SeekFrom},
+    path::Path,
+};
+
+/// Reads messages out of a ROS1 bag file.
+///
+/// Messages are read by scanning the bag's chunks in file order, the index is only used to discover connections.
+/// This means bags which were never finished (e.g. the recording process crashed) can still be read up to the
+/// last complete record, which is what [reindex_bag] relies on.
+pub struct BagReader {
+    file: BufReader<File>,
+    connections: BTreeMap<u32, BagConnection>,
+    chunk_count: u32,
+    // Position of the index section, zero if the bag was never finished
+    index_position: u64,
+    // Position of the first record after the bag header
+    data_position: u64,
+    // Messages from the most recently read chunk which have not been returned yet
+    pending: VecDeque<BagMessage>,
+    done: bool,
+}
+
+impl BagReader {
+    /// Opens the bag file at the given path and reads its header and connection index
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+        let mut version_line = vec![0u8; BAG_VERSION_LINE.len()];
+        file.read_exact(&mut version_line)?;
+        if version_line != BAG_VERSION_LINE {
+            return Err(invalid_data(format!(
+                "Unsupported bag version: {}",
+                String::from_utf8_lossy(&version_line).trim_end()
+            )));
+        }
+
+        let (header, _padding) = read_record(&mut file)?;
+        if get_field_u8(&header, "op")? != OP_BAG_HEADER {
+            return Err(invalid_data("First record in bag is not a bag header"));
+        }
+        let index_position = get_field_u64(&header, "index_pos")?;
+        let chunk_count = get_field_u32(&header, "chunk_count")?;
+        let data_position = file.stream_position()?;
+
+        let mut connections = BTreeMap::new();
+        if index_position != 0 {
+            file.seek(SeekFrom::Start(index_position))?;
+            loop {
+                let (header, data) = match read_record(&mut file) {
+                    Ok(record) => record,
+                    Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                    Err(err) => return Err(err),
+                };
+                if get_field_u8(&header, "op")? == OP_CONNECTION {
+                    let (id, connection) = parse_connection_record(&header, &data)?;
+                    connections.insert(id, connection);
+                }
+            }
+            file.seek(SeekFrom::Start(data_position))?;
+        }
+
+        Ok(Self {
+            file,
+            connections,
+            chunk_count,
+            index_position,
+            data_position,
+            pending: VecDeque::new(),
+            done: false,
+        })
+    }
+
+    /// The connections within the bag, keyed by connection id.
+    /// For bags without an index this is populated as connection records are encountered while reading messages.
+    pub fn connections(&self) -> &BTreeMap<u32, BagConnection> {
+        &self.connections
+    }
+
+    /// The number of chunks the bag's header claims it holds, zero if the bag was never finished
+    pub fn chunk_count(&self) -> u32 {
+        self.chunk_count
+    }
+
+    /// Whether the bag has an index, bags without one should be passed through [reindex_bag]
+    pub fn is_indexed(&self) -> bool {
+        self.index_position != 0
+    }
+
+    /// Returns to the first message in the bag
+    pub fn rewind(&mut self) -> std::io::Result<()> {
+        self.file.seek(SeekFrom::Start(self.data_position))?;
+        self.pending.clear();
+        self.done = false;
+        Ok(())
+    }
+
+    /// Reads the next message from the bag, returns `None` once all messages have been read.
+    pub fn read_next(&mut self) -> std::io::Result<Option<BagMessage>> {
+        loop {
+            if let Some(message) = self.pending.pop_front() {
+                return Ok(Some(message));
+            }
+            if self.done {
+                return Ok(None);
+            }
+            if self.index_position != 0 && self.file.stream_position()? >= self.index_position {
+                self.done = true;
+                continue;
+            }
+            let (header, data) = match read_record(&mut self.file) {
+                Ok(record) => record,
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    // Either the end of an unindexed bag or a record that was only partially written
+                    self.done = true;
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            match get_field_u8(&header, "op")? {
+                OP_CHUNK => self.read_chunk(&header, &data)?,
+                op => self.handle_record(op, &header, data)?,
+            }
+        }
+    }
+
+    /// Returns an iterator over the remaining messages in the bag
+    pub fn messages(&mut self) -> impl Iterator<Item = std::io::Result<BagMessage>> + '_ {
+        std::iter::from_fn(move || self.read_next().transpose())
+    }
+
+    fn read_chunk(&mut self, header: &Fields, data: &[u8]) -> std::io::Result<()> {
+        let compression = get_field_str(header, "compression")?;
+        if compression != "none" {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("Bag chunks compressed with {compression} are not supported"),
+            ));
+        }
+        let mut cursor = Cursor::new(data);
+        while (cursor.position() as usize) < data.len() {
+            let (header, data) = read_record(&mut cursor)?;
+            let op = get_field_u8(&header, "op")?;
+            self.handle_record(op, &header, data)?;
+        }
+        Ok(())
+    }
+
+    fn handle_record(&mut self, op: u8, header: &Fields, data: Vec<u8>) -> std::io::Result<()> {
+        match op {
+            OP_MSG_DATA => {
+                self.pending.push_back(BagMessage {
+                    connection_id: get_field_u32(header, "conn")?,
+                    time: get_field_time(header, "time")?,
+                    data,
+                });
+            }
+            OP_CONNECTION => {
+                let (id, connection) = parse_connection_record(header, &data)?;
+                self.connections.insert(id, connection);
+            }
+            // Index records are not needed as we scan every chunk
+            OP_INDEX_DATA | OP_CHUNK_INFO => {}
+            op => {
+                log::debug!("Skipping unexpected bag record with op {op}");
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,390 @@
+//! Offline operations on bag files: filtering, merging, splitting and reindexing.
+
+use super::*;
+use std::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+type MessagePredicate = Box<dyn Fn(&BagConnection, &BagMessage) -> bool + Send + Sync>;
+
+/// Selects a subset of the messages within a bag and writes them to a new bag.
+///
+/// All configured conditions must pass for a message to be kept.
+/// ```no_run
+/// # roslibrust_codegen_macro::find_and_generate_ros_messages!(
+/// #    "assets/ros1_common_interfaces/std_msgs"
+/// # );
+/// let kept = roslibrust::BagFilter::new()
+///     .topics(["/chatter", "/rosout"])
+///     .typed_predicate::<std_msgs::String>("/chatter", |msg| msg.data.contains("hello"))
+///     .apply("input.bag", "output.bag")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Default)]
+pub struct BagFilter {
+    topics: Option<BTreeSet<String>>,
+    start_time: Option<Time>,
+    end_time: Option<Time>,
+    predicates: Vec<MessagePredicate>,
+}
+
+impl BagFilter {
+    /// Creates a filter which keeps every message
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only keep messages on the given topics
+    pub fn topics<S: Into<String>>(mut self, topics: impl IntoIterator<Item = S>) -> Self {
+        self.topics = Some(topics.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Only keep messages recorded at or after `time`
+    pub fn start_time(mut self, time: Time) -> Self {
+        self.start_time = Some(time);
+        self
+    }
+
+    /// Only keep messages recorded at or before `time`
+    pub fn end_time(mut self, time: Time) -> Self {
+        self.end_time = Some(time);
+        self
+    }
+
+    /// Only keep messages for which `predicate` returns true.
+    /// The predicate is given the raw message, see [BagFilter::typed_predicate] to operate on decoded messages.
+    pub fn predicate(
+        mut self,
+        predicate: impl Fn(&BagConnection, &BagMessage) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.predicates.push(Box::new(predicate));
+        self
+    }
+
+    /// Decodes messages on `topic` as `T` and only keeps those for which `predicate` returns true.
+    /// Messages on other topics are unaffected, messages on `topic` which fail to decode as `T` are dropped.
+    pub fn typed_predicate<T: RosMessageType>(
+        self,
+        topic: &str,
+        predicate: impl Fn(&T) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        let topic = topic.to_owned();
+        self.predicate(move |connection, message| {
+            if connection.topic != topic {
+                return true;
+            }
+            match message.decode::<T>() {
+                Ok(msg) => predicate(&msg),
+                Err(err) => {
+                    log::warn!(
+                        "Failed to decode message on {topic} as {}, dropping it: {err:?}",
+                        T::ROS_TYPE_NAME
+                    );
+                    false
+                }
+            }
+        })
+    }
+
+    /// Checks whether a single message passes the filter
+    pub fn matches(&self, connection: &BagConnection, message: &BagMessage) -> bool {
+        if let Some(topics) = &self.topics {
+            if !topics.contains(&connection.topic) {
+                return false;
+            }
+        }
+        if let Some(start_time) = &self.start_time {
+            if time_key(&message.time) < time_key(start_time) {
+                return false;
+            }
+        }
+        if let Some(end_time) = &self.end_time {
+            if time_key(&message.time) > time_key(end_time) {
+                return false;
+            }
+        }
+        self.predicates
+            .iter()
+            .all(|predicate| predicate(connection, message))
+    }
+
+    /// Writes every message in `input` which passes the filter to a new bag at `output`.
+    /// Returns the number of messages written.
+    pub fn apply(
+        &self,
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+    ) -> std::io::Result<usize> {
+        let mut reader = BagReader::open(input)?;
+        let mut writer = BagWriter::create(output)?;
+        let mut connection_ids = BTreeMap::new();
+        let mut count = 0;
+        while let Some(message) = reader.read_next()? {
+            let connection = lookup_connection(&reader, message.connection_id)?;
+            if !self.matches(connection, &message) {
+                continue;
+            }
+            let id = map_connection(
+                &mut connection_ids,
+                &mut writer,
+                connection,
+                message.connection_id,
+            );
+            writer.write_message(id, &message.time, &message.data)?;
+            count += 1;
+        }
+        writer.finish()?;
+        Ok(count)
+    }
+}
+
+/// Combines several bags into a single bag at `output`, ordering messages by their recorded time.
+/// Connections which are identical between the input bags are merged together.
+/// Returns the number of messages written.
+pub fn merge_bags(inputs: &[impl AsRef<Path>], output: impl AsRef<Path>) -> std::io::Result<usize> {
+    let mut readers = inputs
+        .iter()
+        .map(BagReader::open)
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let mut heads = readers
+        .iter_mut()
+        .map(|reader| reader.read_next())
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let mut connection_ids = vec![BTreeMap::new(); readers.len()];
+    let mut writer = BagWriter::create(output)?;
+    let mut count = 0;
+    loop {
+        // Pick the earliest pending message across all inputs, ties go to the earlier input
+        let next = heads
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, head)| head.as_ref().map(|msg| (time_key(&msg.time), idx)))
+            .min();
+        let Some((_, idx)) = next else {
+            break;
+        };
+        let message = std::mem::replace(&mut heads[idx], readers[idx].read_next()?)
+            .expect("Selected input must have a pending message");
+        let connection = lookup_connection(&readers[idx], message.connection_id)?;
+        let id = map_connection(
+            &mut connection_ids[idx],
+            &mut writer,
+            connection,
+            message.connection_id,
+        );
+        writer.write_message(id, &message.time, &message.data)?;
+        count += 1;
+    }
+    writer.finish()?;
+    Ok(count)
+}
+
+/// Splits the bag at `input` into multiple bags which each span at most `duration` of recorded time.
+/// Output bags are written next to `output_prefix` and named `<output_prefix>_<n>.bag`.
+/// Spans which contain no messages do not produce an output bag.
+/// Returns the paths of the bags written, in time order.
+pub fn split_bag(
+    input: impl AsRef<Path>,
+    output_prefix: impl AsRef<Path>,
+    duration: Duration,
+) -> std::io::Result<Vec<PathBuf>> {
+    let span = duration.as_nanos() as u64;
+    if span == 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Cannot split a bag into spans of zero duration",
+        ));
+    }
+
+    let mut reader = BagReader::open(input)?;
+    // Messages are not guaranteed to be stored in time order so first find the start of the bag
+    let mut start = None;
+    while let Some(message) = reader.read_next()? {
+        let nanos = time_nanos(&message.time);
+        start = Some(start.map_or(nanos, |start: u64| start.min(nanos)));
+    }
+    let Some(start) = start else {
+        return Ok(vec![]);
+    };
+    reader.rewind()?;
+
+    let output_prefix = output_prefix.as_ref().as_os_str().to_owned();
+    let mut outputs: BTreeMap<u64, (PathBuf, BagWriter, BTreeMap<u32, u32>)> = BTreeMap::new();
+    while let Some(message) = reader.read_next()? {
+        let bucket = (time_nanos(&message.time) - start) / span;
+        let (_, writer, connection_ids) = match outputs.entry(bucket) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut path = output_prefix.clone();
+                path.push(format!("_{bucket}.bag"));
+                let path = PathBuf::from(path);
+                let writer = BagWriter::create(&path)?;
+                entry.insert((path, writer, BTreeMap::new()))
+            }
+        };
+        let connection = lookup_connection(&reader, message.connection_id)?;
+        let id = map_connection(connection_ids, writer, connection, message.connection_id);
+        writer.write_message(id, &message.time, &message.data)?;
+    }
+
+    let mut paths = Vec::with_capacity(outputs.len());
+    for (path, writer, _) in outputs.into_values() {
+        writer.finish()?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Rebuilds the index of the bag at `input`, writing the result to `output`.
+/// This recovers bags which were never finished, e.g. because the recording process crashed,
+/// any partially written trailing record is discarded.
+/// Returns the number of messages recovered.
+pub fn reindex_bag(input: impl AsRef<Path>, output: impl AsRef<Path>) -> std::io::Result<usize> {
+    BagFilter::new().apply(input, output)
+}
+
+fn time_nanos(time: &Time) -> u64 {
+    time.secs as u64 * 1_000_000_000 + time.nsecs as u64
+}
+
+fn lookup_connection(reader: &BagReader, connection_id: u32) -> std::io::Result<&BagConnection> {
+    reader.connections().get(&connection_id).ok_or_else(|| {
+        invalid_data(format!(
+            "Bag message references unknown connection {connection_id}"
+        ))
+    })
+}
+
+/// Translates a connection id from an input bag into the id used for the same connection in `writer`
+fn map_connection(
+    connection_ids: &mut BTreeMap<u32, u32>,
+    writer: &mut BagWriter,
+    connection: &BagConnection,
+    connection_id: u32,
+) -> u32 {
+    *connection_ids
+        .entry(connection_id)
+        .or_insert_with(|| writer.add_connection(connection.clone()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write_test_bag(path: &Path, topic: &str, times: &[u32]) {
+        let mut bag = BagWriter::create(path).unwrap();
+        let conn = bag.add_connection(BagConnection {
+            topic: topic.to_owned(),
+            topic_type: "std_msgs/UInt8".to_owned(),
+            md5sum: "7c8164229e7d2c17eb95e9231617fdee".to_owned(),
+            msg_definition: "uint8 data".to_owned(),
+        });
+        for secs in times {
+            bag.write_message(
+                conn,
+                &Time {
+                    secs: *secs,
+                    nsecs: 0,
+                },
+                &[*secs as u8],
+            )
+            .unwrap();
+        }
+        bag.finish().unwrap();
+    }
+
+    fn read_times(path: &Path) -> Vec<(String, u32)> {
+        let mut reader = BagReader::open(path).unwrap();
+        let mut times = vec![];
+        while let Some(msg) = reader.read_next().unwrap() {
+            let topic = reader.connections()[&msg.connection_id].topic.clone();
+            times.push((topic, msg.time.secs));
+        }
+        times
+    }
+
+    #[test]
+    fn filter_merge_and_split() {
+        let dir = std::env::temp_dir();
+        let a = dir.join("roslibrust_bag_tools_a.bag");
+        let b = dir.join("roslibrust_bag_tools_b.bag");
+        let merged = dir.join("roslibrust_bag_tools_merged.bag");
+        let filtered = dir.join("roslibrust_bag_tools_filtered.bag");
+        write_test_bag(&a, "/a", &[1, 3, 5]);
+        write_test_bag(&b, "/b", &[2, 4, 6]);
+
+        assert_eq!(merge_bags(&[&a, &b], &merged).unwrap(), 6);
+        assert_eq!(
+            read_times(&merged),
+            vec![
+                ("/a".to_owned(), 1),
+                ("/b".to_owned(), 2),
+                ("/a".to_owned(), 3),
+                ("/b".to_owned(), 4),
+                ("/a".to_owned(), 5),
+                ("/b".to_owned(), 6)
+            ]
+        );
+
+        let kept = BagFilter::new()
+            .topics(["/b"])
+            .start_time(Time { secs: 3, nsecs: 0 })
+            .predicate(|_, msg| msg.data != [6])
+            .apply(&merged, &filtered)
+            .unwrap();
+        assert_eq!(kept, 1);
+        assert_eq!(read_times(&filtered), vec![("/b".to_owned(), 4)]);
+
+        let splits = split_bag(
+            &merged,
+            dir.join("roslibrust_bag_tools_split"),
+            Duration::from_secs(2),
+        )
+        .unwrap();
+        assert_eq!(splits.len(), 3);
+        assert_eq!(
+            read_times(&splits[1]),
+            vec![("/a".to_owned(), 3), ("/b".to_owned(), 4)]
+        );
+
+        for path in [a, b, merged, filtered].into_iter().chain(splits) {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn reindex_recovers_unfinished_bag() {
+        let dir = std::env::temp_dir();
+        let unfinished = dir.join("roslibrust_bag_tools_unfinished.bag");
+        let recovered = dir.join("roslibrust_bag_tools_recovered.bag");
+        {
+            let mut bag = BagWriter::create(&unfinished).unwrap();
+            bag.set_chunk_threshold(1);
+            let conn = bag.add_connection(BagConnection {
+                topic: "/a".to_owned(),
+                topic_type: "std_msgs/UInt8".to_owned(),
+                md5sum: "7c8164229e7d2c17eb95e9231617fdee".to_owned(),
+                msg_definition: "uint8 data".to_owned(),
+            });
+            for secs in 0..3 {
+                bag.write_message(conn, &Time { secs, nsecs: 0 }, &[0])
+                    .unwrap();
+            }
+            // Dropped without calling finish
+        }
+        // Simulate a partially written trailing record
+        let mut contents = std::fs::read(&unfinished).unwrap();
+        contents.extend_from_slice(&[200, 0, 0, 0, 1, 2]);
+        std::fs::write(&unfinished, contents).unwrap();
+
+        assert!(!BagReader::open(&unfinished).unwrap().is_indexed());
+        assert_eq!(reindex_bag(&unfinished, &recovered).unwrap(), 3);
+        assert!(BagReader::open(&recovered).unwrap().is_indexed());
+
+        std::fs::remove_file(unfinished).unwrap();
+        std::fs::remove_file(recovered).unwrap();
+    }
+}
@@ -0,0 +1,286 @@
+//! Writing side of the bag subsystem, see [BagWriter]
+
+use super::*;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom},
+    path::Path,
+};
+
+// Matches the default chunk threshold used by rosbag
+const DEFAULT_CHUNK_THRESHOLD: usize = 768 * 1024;
+
+struct ChunkInfo {
+    position: u64,
+    start_time: Time,
+    end_time: Time,
+    message_counts: BTreeMap<u32, u32>,
+}
+
+/// Writes raw serialized ROS1 messages into a bag file.
+///
+/// Messages are buffered into uncompressed chunks which are written out once they exceed the chunk threshold.
+/// [BagWriter::finish] must be called to write out the index, bags which are not finished cannot be read by
+/// rosbag without first being reindexed.
+pub struct BagWriter {
+    file: BufWriter<File>,
+    connections: Vec<BagConnection>,
+    // Records within the chunk currently being accumulated
+    chunk_buffer: Vec<u8>,
+    // Per connection list of (time, offset into chunk data) for the chunk currently being accumulated
+    chunk_index: BTreeMap<u32, Vec<(Time, u32)>>,
+    chunk_times: Option<(Time, Time)>,
+    chunk_threshold: usize,
+    chunk_infos: Vec<ChunkInfo>,
+    // Connections for which a connection record has already been written into a chunk
+    written_connections: Vec<bool>,
+}
+
+impl BagWriter {
+    /// Creates a new bag file at the given path, truncating any existing file
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(BAG_VERSION_LINE)?;
+        // Write a placeholder bag header which is overwritten once we know where the index is
+        write_bag_header(&mut file, 0, 0, 0)?;
+        Ok(Self {
+            file,
+            connections: vec![],
+            chunk_buffer: Vec::with_capacity(DEFAULT_CHUNK_THRESHOLD),
+            chunk_index: BTreeMap::new(),
+            chunk_times: None,
+            chunk_threshold: DEFAULT_CHUNK_THRESHOLD,
+            chunk_infos: vec![],
+            written_connections: vec![],
+        })
+    }
+
+    /// Sets the size in bytes a chunk may grow to before it is written to disk
+    pub fn set_chunk_threshold(&mut self, chunk_threshold: usize) {
+        self.chunk_threshold = chunk_threshold;
+    }
+
+    /// Registers a new connection within the bag and returns the id to use when writing messages for it
+    pub fn add_connection(&mut self, connection: BagConnection) -> u32 {
+        if let Some(id) = self.connections.iter().position(|conn| *conn == connection) {
+            return id as u32;
+        }
+        self.connections.push(connection);
+        self.written_connections.push(false);
+        (self.connections.len() - 1) as u32
+    }
+
+    /// Writes a single message to the bag
+    /// * `connection_id` -- Id returned by [BagWriter::add_connection]
+    /// * `time` -- Time at which the message was received
+    /// * `data` -- The serialized message body, without the leading TCPROS length prefix
+    pub fn write_message(
+        &mut self,
+        connection_id: u32,
+        time: &Time,
+        data: &[u8],
+    ) -> std::io::Result<()> {
+        let Some(connection) = self.connections.get(connection_id as usize) else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("No connection with id {connection_id} was added to the bag"),
+            ));
+        };
+        if !self.written_connections[connection_id as usize] {
+            write_connection_record(&mut self.chunk_buffer, connection_id, connection)?;
+            self.written_connections[connection_id as usize] = true;
+        }
+
+        let offset = self.chunk_buffer.len() as u32;
+        let header = [
+            field_u8("op", OP_MSG_DATA),
+            field_u32("conn", connection_id),
+            field_time("time", time),
+        ];
+        write_record(&mut self.chunk_buffer, &header, data)?;
+
+        self.chunk_index
+            .entry(connection_id)
+            .or_default()
+            .push((time.clone(), offset));
+        self.chunk_times = match self.chunk_times.take() {
+            Some((start, end)) => {
+                Some((min_time(start, time.clone()), max_time(end, time.clone())))
+            }
+            None => Some((time.clone(), time.clone())),
+        };
+
+        if self.chunk_buffer.len() >= self.chunk_threshold {
+            self.flush_chunk()?;
+        }
+        Ok(())
+    }
+
+    /// Writes out any buffered messages along with the bag's index and closes the file
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.flush_chunk()?;
+        let index_position = self.file.stream_position()?;
+        for (id, connection) in self.connections.iter().enumerate() {
+            write_connection_record(&mut self.file, id as u32, connection)?;
+        }
+        for chunk_info in &self.chunk_infos {
+            let header = [
+                field_u8("op", OP_CHUNK_INFO),
+                field_u32("ver", 1),
+                field_u64("chunk_pos", chunk_info.position),
+                field_time("start_time", &chunk_info.start_time),
+                field_time("end_time", &chunk_info.end_time),
+                field_u32("count", chunk_info.message_counts.len() as u32),
+            ];
+            let mut data = Vec::with_capacity(chunk_info.message_counts.len() * 8);
+            for (conn, count) in &chunk_info.message_counts {
+                data.write_u32::<LittleEndian>(*conn)?;
+                data.write_u32::<LittleEndian>(*count)?;
+            }
+            write_record(&mut self.file, &header, &data)?;
+        }
+
+        self.file
+            .seek(SeekFrom::Start(BAG_VERSION_LINE.len() as u64))?;
+        write_bag_header(
+            &mut self.file,
+            index_position,
+            self.connections.len() as u32,
+            self.chunk_infos.len() as u32,
+        )?;
+        self.file.flush()
+    }
+
+    fn flush_chunk(&mut self) -> std::io::Result<()> {
+        let Some((start_time, end_time)) = self.chunk_times.take() else {
+            // Nothing has been written into this chunk
+            return Ok(());
+        };
+        let position = self.file.stream_position()?;
+        let header = [
+            field_u8("op", OP_CHUNK),
+            field_str("compression", "none"),
+            field_u32("size", self.chunk_buffer.len() as u32),
+        ];
+        write_record(&mut self.file, &header, &self.chunk_buffer)?;
+
+        let mut message_counts = BTreeMap::new();
+        for (conn, entries) in std::mem::take(&mut self.chunk_index) {
+            let header = [
+                field_u8("op", OP_INDEX_DATA),
+                field_u32("ver", 1),
+                field_u32("conn", conn),
+                field_u32("count", entries.len() as u32),
+            ];
+            let mut data = Vec::with_capacity(entries.len() * 12);
+            for (time, offset) in &entries {
+                data.write_u32::<LittleEndian>(time.secs)?;
+                data.write_u32::<LittleEndian>(time.nsecs)?;
+                data.write_u32::<LittleEndian>(*offset)?;
+            }
+            write_record(&mut self.file, &header, &data)?;
+            message_counts.insert(conn, entries.len() as u32);
+        }
+
+        self.chunk_infos.push(ChunkInfo {
+            position,
+            start_time,
+            end_time,
+            message_counts,
+        });
+        self.chunk_buffer.clear();
+        Ok(())
+    }
+}
+
+fn min_time(a: Time, b: Time) -> Time {
+    if time_key(&a) <= time_key(&b) {
+        a
+    } else {
+        b
+    }
+}
+
+fn max_time(a: Time, b: Time) -> Time {
+    if time_key(&a) >= time_key(&b) {
+        a
+    } else {
+        b
+    }
+}
+
+fn write_connection_record(
+    writer: &mut impl Write,
+    connection_id: u32,
+    connection: &BagConnection,
+) -> std::io::Result<()> {
+    let header = [
+        field_u8("op", OP_CONNECTION),
+        field_u32("conn", connection_id),
+        field_str("topic", &connection.topic),
+    ];
+    write_record(writer, &header, &connection_record_data(connection)?)
+}
+
+fn write_bag_header(
+    writer: &mut impl Write,
+    index_position: u64,
+    connection_count: u32,
+    chunk_count: u32,
+) -> std::io::Result<()> {
+    let header = encode_fields(&[
+        field_u8("op", OP_BAG_HEADER),
+        field_u64("index_pos", index_position),
+        field_u32("conn_count", connection_count),
+        field_u32("chunk_count", chunk_count),
+    ])?;
+    let padding = BAG_HEADER_RECORD_LENGTH - header.len() - 8;
+    writer.write_u32::<LittleEndian>(header.len() as u32)?;
+    writer.write_all(&header)?;
+    writer.write_u32::<LittleEndian>(padding as u32)?;
+    writer.write_all(&vec![b' '; padding])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bag_header_points_at_index() {
+        let path = std::env::temp_dir().join("roslibrust_bag_header_points_at_index.bag");
+        let mut bag = BagWriter::create(&path).unwrap();
+        let conn = bag.add_connection(BagConnection {
+            topic: "/chatter".to_owned(),
+            topic_type: "std_msgs/String".to_owned(),
+            md5sum: "992ce8a1687cec8c8bd883ec73ca41d1".to_owned(),
+            msg_definition: "string data".to_owned(),
+        });
+        let time = Time { secs: 1, nsecs: 2 };
+        bag.write_message(conn, &time, &[5, 0, 0, 0, b'h', b'e', b'l', b'l', b'o'])
+            .unwrap();
+        bag.finish().unwrap();
+
+        let contents = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(contents.starts_with(BAG_VERSION_LINE));
+
+        let mut cursor = Cursor::new(&contents[BAG_VERSION_LINE.len()..]);
+        let header_len = cursor.read_u32::<LittleEndian>().unwrap() as usize;
+        let mut header = vec![0; header_len];
+        cursor.read_exact(&mut header).unwrap();
+        let data_len = cursor.read_u32::<LittleEndian>().unwrap() as usize;
+        assert_eq!(header_len + data_len + 8, BAG_HEADER_RECORD_LENGTH);
+
+        // index_pos is the second field after the op field
+        let index_field = b"index_pos=";
+        let start = header
+            .windows(index_field.len())
+            .position(|window| window == index_field)
+            .unwrap()
+            + index_field.len();
+        let index_pos = u64::from_le_bytes(header[start..start + 8].try_into().unwrap()) as usize;
+        assert!(index_pos > BAG_VERSION_LINE.len() + BAG_HEADER_RECORD_LENGTH);
+        assert!(index_pos < contents.len());
+    }
+}
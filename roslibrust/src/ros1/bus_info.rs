@@ -0,0 +1,307 @@
+//! This module contains the bookkeeping of the node's TCPROS and UDPROS connections reported by the slave API's
+//! getBusStats and getBusInfo, which tools like `rosnode info` use to show who a node is talking to.
+
+use serde_xmlrpc::Value;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, Weak,
+    },
+    time::Instant,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Data received by one of our subscriptions
+    Inbound,
+    /// Data sent by one of our publications
+    Outbound,
+}
+
+impl Direction {
+    fn code(&self) -> &'static str {
+        match self {
+            Direction::Inbound => "i",
+            Direction::Outbound => "o",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transport {
+    Tcpros,
+    Udpros,
+    Unixros,
+    Intraprocess,
+}
+
+impl Transport {
+    fn name(&self) -> &'static str {
+        match self {
+            Transport::Tcpros => "TCPROS",
+            Transport::Udpros => "UDPROS",
+            Transport::Unixros => "UNIXROS",
+            Transport::Intraprocess => "INTRAPROCESS",
+        }
+    }
+}
+
+/// A single connection of one of the node's publications or subscriptions.
+/// The task driving the connection holds it and records the traffic it sees.
+#[derive(Debug)]
+pub struct BusConnection {
+    id: i32,
+    // Caller id of the node at the other end
+    peer: String,
+    direction: Direction,
+    transport: Transport,
+    topic: String,
+    // Human readable description of the socket, shown by rosnode info
+    transport_info: String,
+    opened: Instant,
+    bytes: AtomicU64,
+    messages: AtomicU64,
+}
+
+impl BusConnection {
+    /// Records one message of `bytes` bytes passing over the connection
+    pub fn record(&self, bytes: usize) {
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Identifies the connection within the node, also used as the UDPROS connection id
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Caller id of the node at the other end
+    pub fn peer(&self) -> &str {
+        &self.peer
+    }
+
+    /// When the connection was opened
+    pub fn opened(&self) -> Instant {
+        self.opened
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    fn bytes(&self) -> i32 {
+        // xmlrpc only has 32 bit integers
+        i32::try_from(self.bytes.load(Ordering::Relaxed)).unwrap_or(i32::MAX)
+    }
+
+    /// The number of messages which passed over the connection
+    pub fn messages(&self) -> i32 {
+        i32::try_from(self.messages.load(Ordering::Relaxed)).unwrap_or(i32::MAX)
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    next_id: i32,
+    connections: Vec<Weak<BusConnection>>,
+}
+
+/// Every open connection of a node. A connection is forgotten once the task driving it drops it.
+#[derive(Clone, Default)]
+pub(crate) struct BusConnections(Arc<Mutex<Registry>>);
+
+impl BusConnections {
+    pub fn add(
+        &self,
+        peer: &str,
+        direction: Direction,
+        transport: Transport,
+        topic: &str,
+        transport_info: String,
+    ) -> Arc<BusConnection> {
+        let mut registry = self.0.lock().unwrap();
+        let connection = Arc::new(BusConnection {
+            id: registry.next_id,
+            peer: peer.to_owned(),
+            direction,
+            transport,
+            topic: topic.to_owned(),
+            transport_info,
+            opened: Instant::now(),
+            bytes: AtomicU64::new(0),
+            messages: AtomicU64::new(0),
+        });
+        registry.next_id = registry.next_id.wrapping_add(1);
+        registry.connections.push(Arc::downgrade(&connection));
+        connection
+    }
+
+    /// The connections which are still open, in the order they were made
+    pub fn current(&self) -> Vec<Arc<BusConnection>> {
+        let mut registry = self.0.lock().unwrap();
+        registry
+            .connections
+            .retain(|connection| connection.strong_count() > 0);
+        registry
+            .connections
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect()
+    }
+}
+
+/// Formats connections as the response to getBusInfo:
+/// `[[connectionId, destinationId, direction, transport, topic, connected, transportInfo], ...]`
+pub(crate) fn bus_info(connections: &[Arc<BusConnection>]) -> Value {
+    Value::Array(
+        connections
+            .iter()
+            .map(|connection| {
+                Value::Array(vec![
+                    connection.id.into(),
+                    connection.peer.as_str().into(),
+                    connection.direction.code().into(),
+                    connection.transport.name().into(),
+                    connection.topic.as_str().into(),
+                    true.into(),
+                    connection.transport_info.as_str().into(),
+                ])
+            })
+            .collect(),
+    )
+}
+
+/// Formats connections as the response to getBusStats: `[publishStats, subscribeStats, serviceStats]`.
+/// Every publication and subscription is listed, including those without any connections.
+pub(crate) fn bus_stats(
+    publications: &[(String, String)],
+    subscriptions: &[(String, String)],
+    connections: &[Arc<BusConnection>],
+) -> Value {
+    let connections_of = |topic: &str, direction| {
+        connections
+            .iter()
+            .filter(move |connection| {
+                connection.topic() == topic && connection.direction() == direction
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // [topicName, messageDataSent, [[connectionId, bytesSent, numSent, connected], ...]]
+    let publish_stats = publications
+        .iter()
+        .map(|(topic, _)| {
+            let connections = connections_of(topic, Direction::Outbound);
+            let bytes_sent = connections.iter().fold(0i32, |total, connection| {
+                total.saturating_add(connection.bytes())
+            });
+            let connection_data = connections
+                .iter()
+                .map(|connection| {
+                    Value::Array(vec![
+                        connection.id.into(),
+                        connection.bytes().into(),
+                        connection.messages().into(),
+                        true.into(),
+                    ])
+                })
+                .collect();
+            Value::Array(vec![
+                topic.as_str().into(),
+                bytes_sent.into(),
+                Value::Array(connection_data),
+            ])
+        })
+        .collect();
+
+    // [topicName, [[connectionId, bytesReceived, numReceived, dropEstimate, connected], ...]]
+    let subscribe_stats = subscriptions
+        .iter()
+        .map(|(topic, _)| {
+            let connection_data = connections_of(topic, Direction::Inbound)
+                .iter()
+                .map(|connection| {
+                    Value::Array(vec![
+                        connection.id.into(),
+                        connection.bytes().into(),
+                        connection.messages().into(),
+                        // Drops happen in the broadcast to subscribers rather than per connection, so are not estimated
+                        (-1).into(),
+                        true.into(),
+                    ])
+                })
+                .collect();
+            Value::Array(vec![topic.as_str().into(), Value::Array(connection_data)])
+        })
+        .collect();
+
+    // Services are not hosted natively, so there are no service stats to report
+    Value::Array(vec![
+        Value::Array(publish_stats),
+        Value::Array(subscribe_stats),
+        Value::Array(vec![]),
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::{bus_info, bus_stats, BusConnections, Direction, Transport};
+
+    #[test]
+    fn reports_open_connections() {
+        let registry = BusConnections::default();
+        let outbound = registry.add(
+            "/listener",
+            Direction::Outbound,
+            Transport::Tcpros,
+            "/chatter",
+            "TCPROS connection on port 4000 to [127.0.0.1:5000]".to_owned(),
+        );
+        let inbound = registry.add(
+            "/talker",
+            Direction::Inbound,
+            Transport::Udpros,
+            "/chatter",
+            "UDPROS connection to [127.0.0.1:4000]".to_owned(),
+        );
+        outbound.record(10);
+        outbound.record(20);
+        inbound.record(30);
+
+        let connections = registry.current();
+        let chatter = vec![("/chatter".to_owned(), "std_msgs/String".to_owned())];
+        let info: Vec<(i32, String, String, String, String, bool, String)> =
+            serde_xmlrpc::from_value(bus_info(&connections)).unwrap();
+        assert_eq!(info.len(), 2);
+        assert_eq!(info[0].1, "/listener");
+        assert_eq!(info[0].2, "o");
+        assert_eq!(info[1].1, "/talker");
+        assert_eq!(info[1].2, "i");
+        assert_eq!(info[0].3, "TCPROS");
+        assert_eq!(info[1].3, "UDPROS");
+        assert_ne!(info[0].0, info[1].0);
+
+        type PublishStats = Vec<(String, i32, Vec<(i32, i32, i32, bool)>)>;
+        type SubscribeStats = Vec<(String, Vec<(i32, i32, i32, i32, bool)>)>;
+        let (publish_stats, subscribe_stats, service_stats): (
+            PublishStats,
+            SubscribeStats,
+            Vec<i32>,
+        ) = serde_xmlrpc::from_value(bus_stats(&chatter, &chatter, &connections)).unwrap();
+        assert_eq!(publish_stats[0].1, 30);
+        assert_eq!(publish_stats[0].2[0].1, 30);
+        assert_eq!(publish_stats[0].2[0].2, 2);
+        assert_eq!(subscribe_stats[0].1[0].1, 30);
+        assert_eq!(subscribe_stats[0].1[0].2, 1);
+        assert!(service_stats.is_empty());
+
+        // Closed connections are no longer reported
+        drop(connections);
+        drop(outbound);
+        assert_eq!(registry.current().len(), 1);
+    }
+}
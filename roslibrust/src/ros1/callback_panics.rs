@@ -0,0 +1,138 @@
+//! This module contains the isolation of panics in user callbacks, so a callback which panics on one message
+//! is reported through [super::NodeHandle::callback_panics] instead of taking down the node.
+
+use futures::FutureExt;
+use std::{
+    any::Any,
+    future::Future,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+use tokio::sync::broadcast;
+
+// Panics are rare, so only the most recent are kept for receivers which fall behind
+const PANIC_CHANNEL_SIZE: usize = 16;
+
+/// A panic caught in a user callback, received from [super::NodeHandle::callback_panics]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallbackPanic {
+    /// The callback which panicked, e.g. `parallel subscriber of /chatter`
+    pub source: String,
+    /// The message the callback panicked with
+    pub message: String,
+}
+
+/// Runs the user callbacks of a node, catching their panics.
+/// Clones report to the same receivers.
+#[derive(Clone, Debug)]
+pub(crate) struct CallbackGuard {
+    panics: broadcast::Sender<CallbackPanic>,
+    abort_on_panic: bool,
+}
+
+impl Default for CallbackGuard {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl CallbackGuard {
+    /// With `abort_on_panic` set a panicking callback aborts the process once reported, rather than the
+    /// panic being contained
+    pub fn new(abort_on_panic: bool) -> Self {
+        let (panics, _) = broadcast::channel(PANIC_CHANNEL_SIZE);
+        Self {
+            panics,
+            abort_on_panic,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<CallbackPanic> {
+        self.panics.subscribe()
+    }
+
+    /// Wraps `callback` so each call is run by [CallbackGuard::run]
+    pub fn wrap<T>(
+        &self,
+        source: String,
+        callback: impl Fn(T) + Send + Sync + 'static,
+    ) -> impl Fn(T) + Send + Sync + 'static {
+        let guard = self.clone();
+        move |arg| {
+            guard.run(&source, || callback(arg));
+        }
+    }
+
+    /// Runs `callback`, returning None if it panicked.
+    /// The panic is logged and sent to receivers, naming `source` as the callback which panicked.
+    pub fn run<R>(&self, source: &str, callback: impl FnOnce() -> R) -> Option<R> {
+        match catch_unwind(AssertUnwindSafe(callback)) {
+            Ok(result) => Some(result),
+            Err(payload) => {
+                self.report(source, payload);
+                None
+            }
+        }
+    }
+
+    /// Like [CallbackGuard::run] for asynchronous callbacks, catching panics while `callback` is polled
+    pub async fn run_async<R>(&self, source: &str, callback: impl Future<Output = R>) -> Option<R> {
+        match AssertUnwindSafe(callback).catch_unwind().await {
+            Ok(result) => Some(result),
+            Err(payload) => {
+                self.report(source, payload);
+                None
+            }
+        }
+    }
+
+    fn report(&self, source: &str, payload: Box<dyn Any + Send>) {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_owned());
+        log::error!("Callback of {source} panicked: {message}");
+        // Nobody listening is fine, the panic has been logged
+        let _ = self.panics.send(CallbackPanic {
+            source: source.to_owned(),
+            message,
+        });
+        if self.abort_on_panic {
+            log::error!("Aborting after a callback panicked, as configured");
+            std::process::abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CallbackGuard, CallbackPanic};
+
+    #[test]
+    fn panics_are_caught_and_reported() {
+        let guard = CallbackGuard::default();
+        let mut panics = guard.subscribe();
+        assert_eq!(guard.run("test", || 1), Some(1));
+        assert_eq!(
+            guard.run("subscriber of /chatter", || -> u32 {
+                panic!("bad message {}", 7)
+            }),
+            None
+        );
+        assert_eq!(
+            panics.try_recv().unwrap(),
+            CallbackPanic {
+                source: "subscriber of /chatter".to_owned(),
+                message: "bad message 7".to_owned(),
+            }
+        );
+        // Guards keep working after a panic
+        assert_eq!(guard.clone().run("test", || 2), Some(2));
+        assert!(panics.try_recv().is_err());
+
+        // Panics while polling asynchronous callbacks are caught too
+        let run = guard.run_async("timer callback", async { panic!("bad tick") });
+        assert_eq!(futures::executor::block_on(run), None::<()>);
+        assert_eq!(panics.try_recv().unwrap().message, "bad tick");
+    }
+}
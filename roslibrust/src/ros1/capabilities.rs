@@ -0,0 +1,188 @@
+//! This module contains the registry of capabilities nodes advertise on the parameter server, letting clients find
+//! the service, action or topic providing e.g. grasp planning rather than hardcoding its name.
+//!
+//! Each node advertising capabilities keeps them in a single parameter beneath [CAPABILITIES_NAMESPACE],
+//! which is deleted when the node shuts down.
+
+use super::param_subscriber::ParamSubscriber;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The namespace on the parameter server holding every node's capabilities
+pub const CAPABILITIES_NAMESPACE: &str = "/capabilities";
+
+/// A capability a node provides: a tag naming what it does, e.g. `grasp_planning`,
+/// and the service, action or topic providing it, e.g. `/planner/plan_grasp`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Capability {
+    pub tag: String,
+    pub resource: String,
+}
+
+/// A capability found through discovery, along with the node advertising it
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CapabilityProvider {
+    /// The name of the node advertising the capability
+    pub node: String,
+    pub tag: String,
+    /// The fully resolved name of the service, action or topic providing the capability
+    pub resource: String,
+}
+
+/// What each node stores beneath [CAPABILITIES_NAMESPACE]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Advertisement {
+    node: String,
+    capabilities: Vec<Capability>,
+}
+
+/// Every node's advertisement, keyed by the parameter holding it
+pub(crate) type Advertisements = HashMap<String, Advertisement>;
+
+impl Advertisement {
+    pub fn new(node: &str, capabilities: &[Capability]) -> Self {
+        Self {
+            node: node.to_owned(),
+            capabilities: capabilities.to_vec(),
+        }
+    }
+}
+
+/// The parameter holding the capabilities of `node_name`.
+/// Nodes are kept in a flat namespace, so the advertisement of a node can't be nested within that of another.
+pub(crate) fn param_key(node_name: &str) -> String {
+    format!(
+        "{CAPABILITIES_NAMESPACE}/{}",
+        node_name.trim_start_matches('/').replace('/', "__")
+    )
+}
+
+/// The providers of `tag` among `advertisements`, or of every capability if `tag` is None, sorted by tag and then node
+pub(crate) fn providers(
+    advertisements: &Advertisements,
+    tag: Option<&str>,
+) -> Vec<CapabilityProvider> {
+    let mut providers: Vec<_> = advertisements
+        .values()
+        .flat_map(|advertisement| {
+            advertisement
+                .capabilities
+                .iter()
+                .filter(move |capability| match tag {
+                    Some(tag) => capability.tag == tag,
+                    None => true,
+                })
+                .map(|capability| CapabilityProvider {
+                    node: advertisement.node.clone(),
+                    tag: capability.tag.clone(),
+                    resource: capability.resource.clone(),
+                })
+        })
+        .collect();
+    providers.sort_by(|a, b| (&a.tag, &a.node, &a.resource).cmp(&(&b.tag, &b.node, &b.resource)));
+    providers
+}
+
+/// Watches for changes to the providers of a capability, created with [super::NodeHandle::watch_capability].
+///
+/// The first call to [CapabilityWatch::next] returns the providers at the time of watching,
+/// each call after that waits until a node starts or stops providing the capability.
+pub struct CapabilityWatch {
+    tag: String,
+    subscriber: ParamSubscriber<Advertisements>,
+    // The providers last returned, None until the first call to next
+    providers: Option<Vec<CapabilityProvider>>,
+}
+
+impl CapabilityWatch {
+    pub(crate) fn new(tag: &str, subscriber: ParamSubscriber<Advertisements>) -> Self {
+        Self {
+            tag: tag.to_owned(),
+            subscriber,
+            providers: None,
+        }
+    }
+
+    /// The tag of the capability being watched
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Waits for the providers of the capability to change, returning all of its current providers.
+    /// An error is returned if the node has shut down.
+    pub async fn next(
+        &mut self,
+    ) -> Result<Vec<CapabilityProvider>, Box<dyn std::error::Error + Send + Sync>> {
+        loop {
+            // Nothing set beneath the namespace means nothing is advertised
+            let advertisements = self.subscriber.next().await?.unwrap_or_default();
+            let providers = providers(&advertisements, Some(&self.tag));
+            if self.providers.as_ref() != Some(&providers) {
+                self.providers = Some(providers.clone());
+                return Ok(providers);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        param_key, providers, Advertisement, Advertisements, Capability, CapabilityProvider,
+    };
+    use std::collections::HashMap;
+
+    fn capability(tag: &str, resource: &str) -> Capability {
+        Capability {
+            tag: tag.to_owned(),
+            resource: resource.to_owned(),
+        }
+    }
+
+    #[test]
+    fn providers_are_found_by_tag() {
+        assert_eq!(param_key("/ns/planner"), "/capabilities/ns__planner");
+
+        let advertisements = HashMap::from([
+            (
+                param_key("/ns/planner"),
+                Advertisement::new(
+                    "/ns/planner",
+                    &[
+                        capability("grasp_planning", "/ns/planner/plan_grasp"),
+                        capability("motion_planning", "/ns/planner/plan_motion"),
+                    ],
+                ),
+            ),
+            (
+                param_key("/backup_planner"),
+                Advertisement::new(
+                    "/backup_planner",
+                    &[capability("grasp_planning", "/backup_planner/plan")],
+                ),
+            ),
+        ]);
+        assert_eq!(
+            providers(&advertisements, Some("grasp_planning")),
+            vec![
+                CapabilityProvider {
+                    node: "/backup_planner".to_owned(),
+                    tag: "grasp_planning".to_owned(),
+                    resource: "/backup_planner/plan".to_owned(),
+                },
+                CapabilityProvider {
+                    node: "/ns/planner".to_owned(),
+                    tag: "grasp_planning".to_owned(),
+                    resource: "/ns/planner/plan_grasp".to_owned(),
+                },
+            ]
+        );
+        assert!(providers(&advertisements, Some("navigation")).is_empty());
+        assert_eq!(providers(&advertisements, None).len(), 3);
+
+        // Advertisements are read back from the parameter server as they were written
+        let value = serde_xmlrpc::to_value(&advertisements).unwrap();
+        let read: Advertisements = serde_xmlrpc::from_value(value).unwrap();
+        assert_eq!(read, advertisements);
+    }
+}
@@ -0,0 +1,129 @@
+//! This module contains the optional CRC32 checksums roslibrust peers append to topic messages on unreliable links.
+//!
+//! A subscriber asks for checksums with `checksum=crc32` in its connection header, and a publisher which
+//! supports them answers with the same field. Each message is then sent with its length prefix covering
+//! a trailing CRC32 of the message's data. Peers which don't know the field ignore it, so messages from
+//! other ROS client libraries arrive unchecked.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+/// Value of the connection header field `checksum` asking for and agreeing to CRC32 checksums
+pub(crate) const CRC32: &str = "crc32";
+
+// Table of the reflected CRC-32 (IEEE 802.3) polynomial, as used by zlib and ethernet
+const TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, byte| {
+        TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Appends the checksum of a serialized message's data, extending its length prefix to cover it
+pub(crate) fn append(message: &[u8]) -> Vec<u8> {
+    let data = &message[4.min(message.len())..];
+    let mut checked = Vec::with_capacity(message.len() + 4);
+    checked.extend_from_slice(&(data.len() as u32 + 4).to_le_bytes());
+    checked.extend_from_slice(data);
+    checked.extend_from_slice(&crc32(data).to_le_bytes());
+    checked
+}
+
+/// Verifies and removes the checksum of a message made with [append],
+/// returning the message as it was serialized or None if it was corrupted
+pub(crate) fn verify(checked: &[u8]) -> Option<Vec<u8>> {
+    let length = u32::from_le_bytes(checked.get(..4)?.try_into().unwrap()) as usize;
+    if length < 4 || checked.len() != length + 4 {
+        return None;
+    }
+    let (data, crc) = checked[4..].split_at(length - 4);
+    if crc32(data).to_le_bytes() != crc {
+        return None;
+    }
+    let mut message = Vec::with_capacity(length);
+    message.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    message.extend_from_slice(data);
+    Some(message)
+}
+
+/// Counts of the messages a node dropped because their checksum did not match, retrieved with
+/// [super::NodeHandle::checksum_failures].
+///
+/// Checksums are only verified on topics subscribed to with [super::TopicOptions::checksum],
+/// for messages from publishers which agreed to send them. A corrupted message is dropped before
+/// deserialization rather than handed to subscribers.
+///
+/// Clones share the same counts.
+#[derive(Clone, Debug, Default)]
+pub struct ChecksumFailures(Arc<Mutex<BTreeMap<String, u64>>>);
+
+impl ChecksumFailures {
+    /// The messages dropped from `topic`, a resolved name, because of a checksum mismatch
+    pub fn topic_failures(&self, topic: &str) -> u64 {
+        self.0.lock().unwrap().get(topic).copied().unwrap_or(0)
+    }
+
+    /// The messages dropped because of a checksum mismatch by topic, listing only topics which had any
+    pub fn failures(&self) -> BTreeMap<String, u64> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub(crate) fn record(&self, topic: &str) {
+        *self.0.lock().unwrap().entry(topic.to_owned()).or_default() += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{append, crc32, verify, ChecksumFailures};
+
+    #[test]
+    fn matches_reference_crc() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn checksums_round_trip() {
+        let message = b"\x05\0\0\0hello".to_vec();
+        let checked = append(&message);
+        assert_eq!(&checked[..4], &9u32.to_le_bytes());
+        assert_eq!(verify(&checked), Some(message));
+
+        let mut corrupted = checked.clone();
+        corrupted[6] ^= 0x20;
+        assert_eq!(verify(&corrupted), None);
+        // Truncated messages and those without room for a checksum are rejected as well
+        assert_eq!(verify(&checked[..checked.len() - 1]), None);
+        assert_eq!(verify(b"\x02\0\0\0hi"), None);
+        assert_eq!(verify(b"\x01\0"), None);
+
+        let failures = ChecksumFailures::default();
+        failures.record("/chatter");
+        failures.record("/chatter");
+        assert_eq!(failures.topic_failures("/chatter"), 2);
+        assert_eq!(failures.topic_failures("/other"), 0);
+        assert_eq!(failures.failures().len(), 1);
+    }
+}
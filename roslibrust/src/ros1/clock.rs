@@ -0,0 +1,382 @@
+//! This module contains the Clock which provides the node's view of the current time, following
+//! simulated time published on /clock when `/use_sim_time` is set.
+
+use super::{node::NodeServerHandle, subscription_queue::QueueReceiver};
+use abort_on_drop::ChildTask;
+use roslibrust_codegen::{RosMessageType, Time};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::{broadcast, watch};
+
+const CLOCK_TOPIC: &str = "/clock";
+const USE_SIM_TIME_PARAM: &str = "/use_sim_time";
+
+// Hand written equivalent of rosgraph_msgs/Clock, so the clock does not depend on generated messages
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+struct ClockMsg {
+    clock: Time,
+}
+
+impl RosMessageType for ClockMsg {
+    const ROS_TYPE_NAME: &'static str = "rosgraph_msgs/Clock";
+    const MD5SUM: &'static str = "a9c97c1d230cfc112e270351a944ee47";
+    const DEFINITION: &'static str = r#"# roslib/Clock is used for publishing simulated time in ROS.
+# This message simply communicates the current time.
+# For more information, see http://www.ros.org/wiki/Clock
+time clock
+"#;
+}
+
+/// The source of time for a node, retrieved with [super::NodeHandle::clock].
+///
+/// When the `/use_sim_time` parameter is true at the time the node is created, the clock follows the
+/// time published on `/clock`, e.g. by a simulator or `rosbag play --clock`. Until the first message is
+/// received simulated time reads as zero, matching roscpp and rospy.
+/// Otherwise the clock reads the system time.
+///
+/// Clones share the same underlying subscription to `/clock`.
+#[derive(Clone)]
+pub struct Clock {
+    // None when following system time
+    sim_time: Option<watch::Receiver<Time>>,
+    _clock_task: Option<Arc<ChildTask<()>>>,
+}
+
+impl Clock {
+    /// A clock which always reads the system time
+    pub fn wall() -> Self {
+        Self {
+            sim_time: None,
+            _clock_task: None,
+        }
+    }
+
+    /// Checks `/use_sim_time` and, if set, subscribes to `/clock` via the node.
+    pub(crate) async fn new(
+        node: &NodeServerHandle,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let use_sim_time = match node.get_param(USE_SIM_TIME_PARAM).await {
+            Ok(value) => serde_xmlrpc::from_value::<bool>(value).unwrap_or_else(|err| {
+                log::warn!("Ignoring {USE_SIM_TIME_PARAM} as it is not a bool: {err}");
+                false
+            }),
+            // Not set
+            Err(_) => false,
+        };
+        if !use_sim_time {
+            return Ok(Self::wall());
+        }
+
+        let receiver = node.register_subscriber::<ClockMsg>(CLOCK_TOPIC, 1).await?;
+        let (sender, time) = watch::channel(Time::default());
+        let clock_task = crate::tasks::spawn(
+            &format!("clock following {CLOCK_TOPIC}"),
+            Self::follow_clock(receiver, sender),
+        );
+        Ok(Self::simulated(time, Some(clock_task.into())))
+    }
+
+    pub(crate) fn simulated(
+        time: watch::Receiver<Time>,
+        clock_task: Option<ChildTask<()>>,
+    ) -> Self {
+        Self {
+            sim_time: Some(time),
+            _clock_task: clock_task.map(Arc::new),
+        }
+    }
+
+    /// Whether this clock follows simulated time from `/clock`
+    pub fn is_sim_time(&self) -> bool {
+        self.sim_time.is_some()
+    }
+
+    /// The current time
+    pub fn now(&self) -> Time {
+        match &self.sim_time {
+            Some(time) => time.borrow().clone(),
+            None => std::time::SystemTime::now().into(),
+        }
+    }
+
+    /// Waits until `duration` has passed according to this clock.
+    pub async fn sleep(&self, duration: Duration) {
+        let target = to_duration(&self.now()) + duration;
+        self.sleep_until(&from_duration(target)).await
+    }
+
+    /// Waits until this clock reads at least `deadline`.
+    /// With simulated time this returns early if the node shuts down, as time would otherwise never advance.
+    pub async fn sleep_until(&self, deadline: &Time) {
+        let target = to_duration(deadline);
+        match &self.sim_time {
+            Some(time) => {
+                let mut time = time.clone();
+                loop {
+                    let now = to_duration(&time.borrow_and_update());
+                    if now >= target || time.changed().await.is_err() {
+                        return;
+                    }
+                }
+            }
+            None => {
+                let now = to_duration(&std::time::SystemTime::now().into());
+                tokio::time::sleep(target.saturating_sub(now)).await
+            }
+        }
+    }
+
+    /// Creates a timer which ticks every `period` according to this clock, see [Interval].
+    pub fn interval(&self, period: Duration) -> Interval {
+        Interval {
+            clock: self.clone(),
+            period,
+            next: self.now(),
+        }
+    }
+
+    /// Creates a [Rate] for running a loop `frequency` times a second according to this clock, like roscpp's `ros::Rate`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `frequency` is not a positive, finite number.
+    pub fn rate(&self, frequency: f64) -> Rate {
+        assert!(
+            frequency.is_finite() && frequency > 0.0,
+            "Rate frequency must be positive, got {frequency}"
+        );
+        Rate {
+            clock: self.clone(),
+            expected_cycle_time: Duration::from_secs_f64(1.0 / frequency),
+            actual_cycle_time: Duration::ZERO,
+            start: to_duration(&self.now()),
+        }
+    }
+
+    async fn follow_clock(mut receiver: QueueReceiver, sender: watch::Sender<Time>) {
+        loop {
+            match receiver.recv().await {
+                Ok(message) => match message.decode::<ClockMsg>() {
+                    Ok(msg) => {
+                        if sender.send(msg.clock).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) => log::warn!("Failed to deserialize message on {CLOCK_TOPIC}: {err}"),
+                },
+                // Only the latest time matters
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => {
+                    log::debug!("Subscription to {CLOCK_TOPIC} closed, simulated time has stopped");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// A timer driven by a [Clock], created with [Clock::interval].
+///
+/// The first tick completes immediately. If ticks are missed, e.g. because the caller was busy or simulated
+/// time jumped forward, the next tick completes immediately and the schedule restarts from the current time
+/// rather than firing once for every missed period.
+pub struct Interval {
+    clock: Clock,
+    period: Duration,
+    next: Time,
+}
+
+impl Interval {
+    /// Waits for the next tick, returning the time it was scheduled for.
+    pub async fn tick(&mut self) -> Time {
+        self.clock.sleep_until(&self.next).await;
+        let scheduled = self.next.clone();
+        let mut next = to_duration(&scheduled) + self.period;
+        let now = to_duration(&self.clock.now());
+        if next <= now {
+            next = now + self.period;
+        }
+        self.next = from_duration(next);
+        scheduled
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
+
+/// Keeps a loop running at a fixed frequency according to a [Clock], created with [Clock::rate].
+///
+/// Behaves like roscpp's `ros::Rate`: each call to [Rate::sleep] waits out the rest of the current cycle,
+/// so the work done in the loop doesn't slow it down as long as it fits within a cycle.
+/// ```no_run
+/// # async fn run(nh: roslibrust::NodeHandle) {
+/// let mut rate = nh.clock().rate(10.0);
+/// loop {
+///     // Control loop body
+///     if !rate.sleep().await {
+///         log::warn!("Control loop overran, took {:?}", rate.cycle_time());
+///     }
+/// }
+/// # }
+/// ```
+pub struct Rate {
+    clock: Clock,
+    expected_cycle_time: Duration,
+    // How long the last cycle took before sleep was called
+    actual_cycle_time: Duration,
+    // When the current cycle started, as a duration since the epoch of the clock
+    start: Duration,
+}
+
+impl Rate {
+    /// Waits until the end of the current cycle, returning false if the cycle had already overrun.
+    ///
+    /// A cycle overrunning by more than a whole cycle restarts the schedule from now rather than
+    /// returning immediately on the following calls to catch up, as does time jumping backwards,
+    /// e.g. when a bag being played with `--clock` loops.
+    pub async fn sleep(&mut self) -> bool {
+        let now = to_duration(&self.clock.now());
+        let expected_end = match now < self.start {
+            true => now + self.expected_cycle_time,
+            false => self.start + self.expected_cycle_time,
+        };
+        self.actual_cycle_time = now.saturating_sub(self.start);
+        self.start = expected_end;
+        if now >= expected_end {
+            if now > expected_end + self.expected_cycle_time {
+                self.start = now;
+            }
+            return false;
+        }
+        self.clock.sleep_until(&from_duration(expected_end)).await;
+        true
+    }
+
+    /// Restarts the current cycle from now, e.g. after a pause in the loop which shouldn't count as an overrun
+    pub fn reset(&mut self) {
+        self.start = to_duration(&self.clock.now());
+    }
+
+    /// How much of the current cycle is left, zero if it has overrun
+    pub fn remaining(&self) -> Duration {
+        let elapsed = to_duration(&self.clock.now()).saturating_sub(self.start);
+        self.expected_cycle_time.saturating_sub(elapsed)
+    }
+
+    /// How long the last cycle took before [Rate::sleep] was called, i.e. the time spent on the work in the loop
+    pub fn cycle_time(&self) -> Duration {
+        self.actual_cycle_time
+    }
+
+    /// The length of each cycle, the inverse of the frequency
+    pub fn expected_cycle_time(&self) -> Duration {
+        self.expected_cycle_time
+    }
+}
+
+fn to_duration(time: &Time) -> Duration {
+    Duration::new(time.secs.into(), time.nsecs)
+}
+
+fn from_duration(duration: Duration) -> Time {
+    Time {
+        secs: u32::try_from(duration.as_secs()).unwrap_or(u32::MAX),
+        nsecs: duration.subsec_nanos(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Clock;
+    use roslibrust_codegen::Time;
+    use std::time::Duration;
+    use tokio::sync::watch;
+
+    #[tokio::test]
+    async fn sim_time_drives_sleep_and_interval() {
+        let (sender, time) = watch::channel(Time::default());
+        let clock = Clock::simulated(time, None);
+        assert!(clock.is_sim_time());
+        assert_eq!(clock.now(), Time::default());
+
+        let mut interval = clock.interval(Duration::from_secs(1));
+        assert_eq!(interval.tick().await, Time { secs: 0, nsecs: 0 });
+
+        let sleeper = tokio::spawn({
+            let clock = clock.clone();
+            async move { clock.sleep(Duration::from_millis(1500)).await }
+        });
+        let ticker = tokio::spawn(async move { interval.tick().await });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!sleeper.is_finished());
+        assert!(!ticker.is_finished());
+
+        // Wall time passing has no effect, only /clock advances the clock
+        sender.send(Time { secs: 1, nsecs: 0 }).unwrap();
+        assert_eq!(ticker.await.unwrap(), Time { secs: 1, nsecs: 0 });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!sleeper.is_finished());
+
+        sender
+            .send(Time {
+                secs: 1,
+                nsecs: 500_000_000,
+            })
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(1), sleeper)
+            .await
+            .expect("Sleep should end once simulated time passes its deadline")
+            .unwrap();
+        assert_eq!(
+            clock.now(),
+            Time {
+                secs: 1,
+                nsecs: 500_000_000
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_keeps_to_sim_time() {
+        let secs = |secs: f64| {
+            let duration = Duration::from_secs_f64(secs);
+            Time {
+                secs: duration.as_secs() as u32,
+                nsecs: duration.subsec_nanos(),
+            }
+        };
+        let (sender, time) = watch::channel(secs(10.0));
+        let clock = Clock::simulated(time, None);
+        let mut rate = clock.rate(2.0);
+        assert_eq!(rate.expected_cycle_time(), Duration::from_millis(500));
+
+        // Work taking part of the cycle leaves the rest to sleep through
+        sender.send(secs(10.2)).unwrap();
+        assert_eq!(rate.remaining(), Duration::from_millis(300));
+        let sleeper = tokio::spawn(async move {
+            let met = rate.sleep().await;
+            (rate, met)
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!sleeper.is_finished());
+        sender.send(secs(10.5)).unwrap();
+        let (mut rate, met) = tokio::time::timeout(Duration::from_secs(1), sleeper)
+            .await
+            .expect("Sleep should end with the cycle")
+            .unwrap();
+        assert!(met);
+        assert_eq!(rate.cycle_time(), Duration::from_millis(200));
+
+        // Overrunning a cycle doesn't sleep, and the next cycle keeps to the original schedule
+        sender.send(secs(11.1)).unwrap();
+        assert_eq!(rate.remaining(), Duration::ZERO);
+        assert!(!rate.sleep().await);
+        assert_eq!(rate.cycle_time(), Duration::from_millis(600));
+        assert_eq!(rate.remaining(), Duration::from_millis(400));
+
+        // Overrunning by more than a cycle restarts the schedule from now
+        sender.send(secs(13.0)).unwrap();
+        assert!(!rate.sleep().await);
+        assert_eq!(rate.remaining(), Duration::from_millis(500));
+    }
+}
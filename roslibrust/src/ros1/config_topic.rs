@@ -0,0 +1,200 @@
+//! This module contains ConfigTopic and ConfigSubscriber for sharing configuration over latched topics,
+//! created with [super::NodeHandle::advertise_config] and [super::NodeHandle::subscribe_config].
+
+use super::{
+    node::{NodeShutdownError, ShutdownReason},
+    publisher::Publisher,
+    subscriber::Subscriber,
+};
+use abort_on_drop::ChildTask;
+use roslibrust_codegen::RosMessageType;
+use tokio::sync::{watch, Mutex};
+
+/// Publishes the value of a piece of configuration on a latched topic,
+/// so every [ConfigSubscriber] receives the current value as soon as it connects.
+/// Setting the value it already has publishes nothing.
+pub struct ConfigTopic<T> {
+    publisher: Publisher<T>,
+    // Held while publishing so values are published in the order they are set
+    value: Mutex<Option<T>>,
+}
+
+impl<T: RosMessageType + PartialEq> ConfigTopic<T> {
+    pub(crate) fn new(publisher: Publisher<T>) -> Self {
+        Self {
+            publisher,
+            value: Mutex::new(None),
+        }
+    }
+
+    /// Publishes `value` if it differs from the current value, returning whether it was published
+    pub async fn set(&self, value: T) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let mut current = self.value.lock().await;
+        if current.as_ref() == Some(&value) {
+            return Ok(false);
+        }
+        self.publisher.publish(&value).await?;
+        *current = Some(value);
+        Ok(true)
+    }
+
+    /// The value last set, None until a value is set
+    pub async fn get(&self) -> Option<T> {
+        self.value.lock().await.clone()
+    }
+}
+
+/// Follows the value of a piece of configuration published by a [ConfigTopic], or any latched topic.
+/// Updates which repeat the current value are ignored.
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// # roslibrust_codegen_macro::find_and_generate_ros_messages!("assets/ros1_common_interfaces/std_msgs");
+/// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/camera_driver").await?;
+/// let mut mode = nh.subscribe_config::<std_msgs::String>("/camera/mode").await?;
+/// println!("Starting in mode {}", mode.current().await?.data);
+/// loop {
+///     println!("Switching to mode {}", mode.changed().await?.data);
+/// }
+/// # }
+/// ```
+pub struct ConfigSubscriber<T> {
+    receiver: watch::Receiver<Option<T>>,
+    shutdown_reason: ShutdownReason,
+    _update_task: Option<ChildTask<()>>,
+}
+
+impl<T: RosMessageType + PartialEq> ConfigSubscriber<T> {
+    pub(crate) fn new(
+        topic_name: &str,
+        mut subscriber: Subscriber<T>,
+        shutdown_reason: ShutdownReason,
+    ) -> Self {
+        let (sender, receiver) = watch::channel(None);
+        let task_name = format!("config subscriber {topic_name}");
+        let update_task = crate::tasks::spawn(&task_name, async move {
+            loop {
+                match subscriber.next().await {
+                    Ok(value) => {
+                        update(&sender, value);
+                    }
+                    Err(err) if err.is::<NodeShutdownError>() => break,
+                    Err(err) => log::warn!("Skipping configuration update: {err}"),
+                }
+            }
+        });
+        Self {
+            receiver,
+            shutdown_reason,
+            _update_task: Some(update_task.into()),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> ConfigSubscriber<T> {
+    /// The current value, None until one has been received
+    pub fn get(&self) -> Option<T> {
+        self.receiver.borrow().clone()
+    }
+
+    /// The current value, waiting for one if none has been received yet
+    pub async fn current(&mut self) -> Result<T, NodeShutdownError> {
+        loop {
+            if let Some(value) = self.receiver.borrow_and_update().clone() {
+                return Ok(value);
+            }
+            self.changed_value().await?;
+        }
+    }
+
+    /// Waits for the value to change from the value last returned by this subscriber, returning the new value.
+    /// Values which change more than once before this is called are only returned at their latest.
+    /// Fails once the node shuts down.
+    pub async fn changed(&mut self) -> Result<T, NodeShutdownError> {
+        self.changed_value().await
+    }
+
+    /// Turns the subscriber into a [futures::Stream] of the current value, if there is one, followed by each change.
+    /// The stream ends when the node shuts down.
+    pub fn into_stream(mut self) -> impl futures::Stream<Item = T> + Send {
+        use futures::StreamExt;
+        let current = self.receiver.borrow_and_update().clone();
+        futures::stream::iter(current)
+            .chain(futures::stream::unfold(self, |mut config| async move {
+                config.changed().await.ok().map(|value| (value, config))
+            }))
+    }
+
+    async fn changed_value(&mut self) -> Result<T, NodeShutdownError> {
+        loop {
+            // The update task only stops when the node shuts down
+            self.receiver
+                .changed()
+                .await
+                .map_err(|_| self.shutdown_reason.error())?;
+            if let Some(value) = self.receiver.borrow_and_update().clone() {
+                return Ok(value);
+            }
+        }
+    }
+}
+
+// Stores `value` unless it is already the current value, returning whether it was stored
+fn update<T: PartialEq>(sender: &watch::Sender<Option<T>>, value: T) -> bool {
+    sender.send_if_modified(|current| {
+        if current.as_ref() == Some(&value) {
+            return false;
+        }
+        *current = Some(value);
+        true
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{update, ConfigSubscriber};
+    use crate::ros1::node::ShutdownReason;
+    use futures::StreamExt;
+    use tokio::sync::watch;
+
+    fn config() -> (watch::Sender<Option<u32>>, ConfigSubscriber<u32>) {
+        let (sender, receiver) = watch::channel(None);
+        let subscriber = ConfigSubscriber {
+            receiver,
+            shutdown_reason: ShutdownReason::default(),
+            _update_task: None,
+        };
+        (sender, subscriber)
+    }
+
+    #[tokio::test]
+    async fn repeated_values_are_not_changes() {
+        let (sender, mut config) = config();
+        assert_eq!(config.get(), None);
+        assert!(update(&sender, 1));
+        assert_eq!(config.current().await.unwrap(), 1);
+
+        assert!(!update(&sender, 1));
+        assert!(update(&sender, 2));
+        assert_eq!(config.changed().await.unwrap(), 2);
+        assert!(!update(&sender, 2));
+        assert!(update(&sender, 3));
+        assert_eq!(config.changed().await.unwrap(), 3);
+        assert_eq!(config.get(), Some(3));
+
+        // Shutting the node down closes the channel
+        drop(sender);
+        assert!(config.changed().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn streams_start_with_the_current_value() {
+        let (sender, config) = config();
+        update(&sender, 1);
+        let stream = config.into_stream();
+        update(&sender, 1);
+        update(&sender, 2);
+        drop(sender);
+        assert_eq!(stream.collect::<Vec<_>>().await, vec![1, 2]);
+    }
+}
@@ -0,0 +1,178 @@
+//! This module contains the DataTap which records topics the application is already subscribed to
+//! into a bag file, with recording toggled on and off at runtime.
+
+use super::{
+    bag::{BagConnection, BagWriter},
+    node::NodeHandle,
+    subscription_queue::QueueReceiver,
+    Clock,
+};
+use abort_on_drop::ChildTask;
+use roslibrust_codegen::Time;
+use std::path::PathBuf;
+use tokio::sync::{broadcast, mpsc, Mutex};
+
+struct Recording {
+    path: PathBuf,
+    // One task per tapped topic forwarding raw messages to the writer
+    forward_tasks: Vec<ChildTask<()>>,
+    // Task owning the bag file, completes once all forwarding tasks have been dropped
+    writer_task: tokio::task::JoinHandle<std::io::Result<usize>>,
+}
+
+/// Records the raw messages of existing subscriptions into a bag file on demand.
+///
+/// The tap shares the byte stream of subscriptions created through the node (e.g. via [NodeHandle::subscribe]),
+/// no additional network connections are made and topics the node is not subscribed to cannot be recorded.
+/// ```no_run
+/// # roslibrust_codegen_macro::find_and_generate_ros_messages!(
+/// #    "assets/ros1_common_interfaces/std_msgs"
+/// # );
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "tap_node").await?;
+/// let _subscriber = nh.subscribe::<std_msgs::String>("/chatter", 10).await?;
+/// let tap = roslibrust::DataTap::new(&nh);
+/// tap.start_recording("/tmp/chatter.bag", &["/chatter"]).await?;
+/// // ... later
+/// let count = tap.stop_recording().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct DataTap {
+    node: NodeHandle,
+    recording: Mutex<Option<Recording>>,
+}
+
+impl DataTap {
+    pub fn new(node: &NodeHandle) -> Self {
+        Self {
+            node: node.clone(),
+            recording: Mutex::new(None),
+        }
+    }
+
+    /// Begins recording every message received on `topics` into a new bag file at `path`.
+    /// Fails if a recording is already in progress or if the node is not subscribed to one of the topics.
+    pub async fn start_recording(
+        &self,
+        path: impl Into<PathBuf>,
+        topics: &[&str],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut recording = self.recording.lock().await;
+        if let Some(current) = recording.as_ref() {
+            return Err(format!(
+                "Already recording to {}, stop the current recording first",
+                current.path.display()
+            )
+            .into());
+        }
+
+        // Tap every topic before touching the filesystem so a bad topic doesn't leave an empty bag behind
+        let mut taps = Vec::with_capacity(topics.len());
+        for topic in topics {
+            let (header, receiver) = self.node.inner.tap_subscription(topic).await?;
+            let connection = BagConnection {
+                topic: header.topic,
+                topic_type: header.topic_type,
+                md5sum: header.md5sum,
+                msg_definition: header.msg_definition,
+            };
+            taps.push((connection, receiver));
+        }
+
+        let path = path.into();
+        let mut bag = BagWriter::create(&path)?;
+        let (sender, mut message_receiver) = mpsc::unbounded_channel();
+        let mut forward_tasks = Vec::with_capacity(taps.len());
+        for (connection, receiver) in taps {
+            let topic = connection.topic.clone();
+            let connection_id = bag.add_connection(connection);
+            let handle = crate::tasks::spawn(
+                &format!("data tap {topic}"),
+                Self::forward_topic(
+                    topic,
+                    connection_id,
+                    receiver,
+                    sender.clone(),
+                    self.node.clock().clone(),
+                ),
+            );
+            forward_tasks.push(handle.into());
+        }
+
+        let writer_path = path.clone();
+        let writer_name = format!("data tap writing {}", path.display());
+        let writer_task = crate::tasks::spawn_blocking(&writer_name, move || {
+            let mut count = 0;
+            while let Some((connection_id, time, data)) = message_receiver.blocking_recv() {
+                bag.write_message(connection_id, &time, &data)?;
+                count += 1;
+            }
+            bag.finish()?;
+            log::info!("Recorded {count} messages to {}", writer_path.display());
+            Ok(count)
+        });
+
+        log::info!("Started recording {topics:?} to {}", path.display());
+        *recording = Some(Recording {
+            path,
+            forward_tasks,
+            writer_task,
+        });
+        Ok(())
+    }
+
+    /// Stops the current recording and finishes writing its bag file.
+    /// Returns the number of messages recorded, or an error if no recording was in progress.
+    pub async fn stop_recording(&self) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(recording) = self.recording.lock().await.take() else {
+            return Err("No recording is in progress".into());
+        };
+        // Dropping the forwarding tasks closes the channel to the writer, which then finishes the bag
+        drop(recording.forward_tasks);
+        Ok(recording.writer_task.await??)
+    }
+
+    /// Whether a recording is currently in progress
+    pub async fn is_recording(&self) -> bool {
+        self.recording.lock().await.is_some()
+    }
+
+    async fn forward_topic(
+        topic: String,
+        connection_id: u32,
+        mut receiver: QueueReceiver,
+        sender: mpsc::UnboundedSender<(u32, Time, Vec<u8>)>,
+        clock: Clock,
+    ) {
+        loop {
+            match receiver.recv().await {
+                Ok(message) => {
+                    let time = clock.now();
+                    let mut data = match message.into_data() {
+                        Ok(data) => data,
+                        Err(err) => {
+                            log::warn!("Data tap failed to serialize a message on {topic}: {err}");
+                            continue;
+                        }
+                    };
+                    // Raw messages carry their TCPROS length prefix which is not stored in the bag
+                    data.drain(..4.min(data.len()));
+                    if sender.send((connection_id, time, data)).is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(count)) => {
+                    log::warn!(
+                        "Data tap fell behind on topic {topic}, {count} messages were not recorded"
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    log::debug!("Subscription to {topic} closed, data tap no longer recording it");
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! This module contains the log of a node's interactions with the master, kept for diagnosing registration problems
+//! after the fact, see [super::NodeHandle::graph_events].
+
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    io::Write,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The number of events a node keeps unless set with [super::NodeHandleOptions::graph_event_capacity]
+pub(crate) const DEFAULT_GRAPH_EVENT_CAPACITY: usize = 256;
+
+/// A call between a node and the master, recorded in the node's [GraphEventLog]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphEvent {
+    /// When the call completed, or was received for calls made by the master
+    pub time: SystemTime,
+    /// The xmlrpc method called, e.g. `registerSubscriber` on the master, or `publisherUpdate` and `shutdown`
+    /// on the node by the master
+    pub method: String,
+    /// The topic, service or node the call concerned
+    pub name: String,
+    /// What came of the call, e.g. the publishers the master listed for a topic, or why the call failed
+    pub outcome: Result<String, String>,
+}
+
+impl Display for GraphEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let time = self.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        write!(
+            f,
+            "[{}.{:09}] {} {}",
+            time.as_secs(),
+            time.subsec_nanos(),
+            self.method,
+            self.name
+        )?;
+        match &self.outcome {
+            Ok(outcome) if outcome.is_empty() => Ok(()),
+            Ok(outcome) => write!(f, ": {outcome}"),
+            Err(err) => write!(f, " failed: {err}"),
+        }
+    }
+}
+
+/// The most recent registrations, lookups and updates a node exchanged with the master, oldest first,
+/// retrieved with [super::NodeHandle::graph_events].
+/// Useful for working out why a node dropped out of the graph or missed a publisher, once it has happened:
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/my_node").await?;
+/// for event in nh.graph_events().events() {
+///     println!("{event}");
+/// }
+/// nh.graph_events().dump("/tmp/my_node_graph_events.log")?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Clones share the same log.
+#[derive(Clone, Debug)]
+pub struct GraphEventLog {
+    events: Arc<Mutex<VecDeque<GraphEvent>>>,
+    capacity: usize,
+}
+
+impl GraphEventLog {
+    /// A log keeping the last `capacity` events, which records nothing if `capacity` is 0
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            events: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Records a call, dropping the oldest event once the log is full
+    pub(crate) fn record(&self, method: &str, name: &str, outcome: Result<String, String>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut events = self.events.lock().unwrap();
+        if events.len() == self.capacity {
+            events.pop_front();
+        }
+        events.push_back(GraphEvent {
+            time: SystemTime::now(),
+            method: method.to_owned(),
+            name: name.to_owned(),
+            outcome,
+        });
+    }
+
+    /// The events in the log, oldest first
+    pub fn events(&self) -> Vec<GraphEvent> {
+        self.events.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Writes the events in the log to the file at `path`, one per line, replacing the file if it exists.
+    /// See [super::NodeHandleOptions::graph_event_dump] for writing them when the node shuts down.
+    pub fn dump(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        for event in self.events() {
+            writeln!(file, "{event}")?;
+        }
+        file.flush()
+    }
+}
+
+/// Describes the peers the master listed for a topic, e.g. `publishers http://talker:38421/`
+pub(crate) fn listed(peers: &str, uris: &[String]) -> String {
+    if uris.is_empty() {
+        format!("no {peers}")
+    } else {
+        format!("{peers} {}", uris.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GraphEventLog;
+
+    #[test]
+    fn oldest_events_are_dropped() {
+        let log = GraphEventLog::new(2);
+        log.record(
+            "registerSubscriber",
+            "/chatter",
+            Ok("no publishers".to_owned()),
+        );
+        log.record(
+            "publisherUpdate",
+            "/chatter",
+            Ok("http://talker:1234/".to_owned()),
+        );
+        log.record("lookupNode", "/talker", Err("unknown node".to_owned()));
+        let events = log.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].method, "publisherUpdate");
+        assert!(events[1]
+            .to_string()
+            .ends_with("] lookupNode /talker failed: unknown node"));
+
+        let disabled = GraphEventLog::new(0);
+        disabled.record("registerSubscriber", "/chatter", Ok(String::new()));
+        assert!(disabled.events().is_empty());
+    }
+}
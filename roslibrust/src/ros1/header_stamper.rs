@@ -0,0 +1,92 @@
+//! This module contains the stamping of the `std_msgs/Header` of published messages, see [super::Publisher::stamp_headers].
+
+use super::clock::Clock;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Whether the first field of the message defined by `definition` is a `std_msgs/Header`
+pub(crate) fn has_header(definition: &str) -> bool {
+    definition
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        // Constants may come before the first field
+        .find(|line| !line.is_empty() && !line.contains('='))
+        .and_then(|field| field.split_whitespace().next())
+        .is_some_and(|field_type| field_type == "Header" || field_type == "std_msgs/Header")
+}
+
+/// Fills in the header at the start of serialized messages, which begins with
+/// its `uint32 seq` and the `secs` and `nsecs` of its `time stamp`
+pub(crate) struct HeaderStamper {
+    clock: Clock,
+    seq: AtomicU32,
+}
+
+impl HeaderStamper {
+    pub fn new(clock: Clock) -> Self {
+        Self {
+            clock,
+            seq: AtomicU32::new(0),
+        }
+    }
+
+    /// Stamps a message serialized with its length prefix, setting the next sequence number
+    /// and, if the stamp was left at zero, the current time
+    pub fn stamp(&self, message: &mut [u8]) {
+        let Some(header) = message.get_mut(4..16) else {
+            log::warn!("Message is too short to contain a header, not stamping it");
+            return;
+        };
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        header[..4].copy_from_slice(&seq.to_le_bytes());
+        if header[4..].iter().all(|byte| *byte == 0) {
+            let now = self.clock.now();
+            header[4..8].copy_from_slice(&now.secs.to_le_bytes());
+            header[8..].copy_from_slice(&now.nsecs.to_le_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{has_header, HeaderStamper};
+    use crate::ros1::clock::Clock;
+    use roslibrust_codegen::Time;
+    use tokio::sync::watch;
+
+    #[test]
+    fn finds_headers() {
+        assert!(has_header("Header header\nstring child_frame_id\n"));
+        assert!(has_header(
+            "# A comment\nuint8 DEBUG=1 # Constants come first\n\nstd_msgs/Header  header\nint32 data\n"
+        ));
+        assert!(!has_header("string data\n"));
+        assert!(!has_header("time stamp\nHeader header\n"));
+        assert!(!has_header(""));
+    }
+
+    #[test]
+    fn stamps_headers() {
+        let (_sender, time) = watch::channel(Time {
+            secs: 12,
+            nsecs: 34,
+        });
+        let stamper = HeaderStamper::new(Clock::simulated(time, None));
+
+        // Length, seq, stamp and an empty frame_id
+        let mut message = vec![16, 0, 0, 0, 9, 9, 9, 9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        stamper.stamp(&mut message);
+        assert_eq!(&message[4..8], &0u32.to_le_bytes());
+        assert_eq!(&message[8..12], &12u32.to_le_bytes());
+        assert_eq!(&message[12..16], &34u32.to_le_bytes());
+
+        // Stamps which were set are kept, while the sequence number keeps counting
+        message[8..16].copy_from_slice(&[1, 0, 0, 0, 2, 0, 0, 0]);
+        stamper.stamp(&mut message);
+        assert_eq!(&message[4..8], &1u32.to_le_bytes());
+        assert_eq!(&message[8..16], &[1, 0, 0, 0, 2, 0, 0, 0]);
+
+        let mut short = vec![0, 0, 0, 0, 1];
+        stamper.stamp(&mut short);
+        assert_eq!(short, vec![0, 0, 0, 0, 1]);
+    }
+}
@@ -0,0 +1,132 @@
+//! This module contains the delivery of messages published by a node to its own subscriptions of the same topic,
+//! handing subscribers the published message itself rather than serializing it over a TCPROS connection to the node,
+//! like roscpp's intra-process transport used by nodelets. See [super::TopicOptions::intra_process].
+
+use super::{
+    bus_info::BusConnection, memory_budgets::TopicAccount, subscriber::RawMessage,
+    subscription_queue::PublisherLink, tcpros::ConnectionHeader,
+};
+use bytes::Bytes;
+use roslibrust_codegen::RosMessageType;
+use std::{
+    any::Any,
+    sync::{Arc, Mutex},
+};
+
+type Serialize = fn(&(dyn Any + Send + Sync)) -> Result<Vec<u8>, serde_rosmsg::Error>;
+
+/// A message published in-process, shared by every subscriber receiving it
+#[derive(Clone)]
+pub(crate) struct LocalMessage {
+    message: Arc<dyn Any + Send + Sync>,
+    // Serializes the message for receivers which need its bytes, e.g. to record it to a bag
+    serialize: Serialize,
+}
+
+impl LocalMessage {
+    pub fn new<T: RosMessageType>(message: T) -> Self {
+        Self {
+            message: Arc::new(message),
+            serialize: |message| {
+                let message = message
+                    .downcast_ref::<T>()
+                    .expect("LocalMessage holds the type it was created with");
+                serde_rosmsg::to_vec(message)
+            },
+        }
+    }
+
+    /// The message, if it is a `T`
+    pub fn downcast<T: 'static>(&self) -> Option<&T> {
+        self.message.downcast_ref()
+    }
+
+    /// Serializes the message as it would be sent over TCPROS, starting with its length
+    pub fn serialize(&self) -> Result<Vec<u8>, serde_rosmsg::Error> {
+        (self.serialize)(self.message.as_ref())
+    }
+}
+
+impl std::fmt::Debug for LocalMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalMessage").finish_non_exhaustive()
+    }
+}
+
+/// The node's own subscription of a publication's topic, if it receives the topic in-process.
+/// Shared by the publication and every publisher of it, clones hand messages to the same subscription.
+#[derive(Clone, Default)]
+pub(crate) struct LocalSubscription(Arc<Mutex<Option<LocalLink>>>);
+
+struct LocalLink {
+    link: PublisherLink,
+    // Where the subscription charges the messages it holds
+    account: Arc<TopicAccount>,
+    // The header of the publication, given to subscribers as that of the publisher of each message
+    publisher: Arc<ConnectionHeader>,
+    // Both ends of the link, reported by getBusInfo until it is disconnected
+    connections: [Arc<BusConnection>; 2],
+}
+
+impl LocalSubscription {
+    pub fn connect(
+        &self,
+        link: PublisherLink,
+        account: Arc<TopicAccount>,
+        publisher: ConnectionHeader,
+        connections: [Arc<BusConnection>; 2],
+    ) {
+        *self.0.lock().unwrap() = Some(LocalLink {
+            link,
+            account,
+            publisher: Arc::new(publisher),
+            connections,
+        });
+    }
+
+    pub fn disconnect(&self) {
+        self.0.lock().unwrap().take();
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.0.lock().unwrap().is_some()
+    }
+
+    /// Hands a message to the subscription, either `local` as published or `data` already serialized.
+    /// Messages are dropped if the subscription's memory budget refuses them, or if it has gone.
+    pub async fn send(&self, data: Bytes, local: Option<LocalMessage>, latched: bool) {
+        let Some(account) = self
+            .0
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|link| link.account.clone())
+        else {
+            return;
+        };
+        // The size of a message held in-process isn't known without serializing it, which is what handing it
+        // over as published avoids, so only serialized bytes are charged and recorded, but a budget which is
+        // already exceeded still applies. This exemption is documented on TopicOptions::intra_process.
+        let Some(charge) = account.charge_incoming(data.len()).await else {
+            return;
+        };
+        let link = self.0.lock().unwrap();
+        let Some(link) = link.as_ref() else {
+            return;
+        };
+        for connection in &link.connections {
+            connection.record(data.len());
+        }
+        let message = RawMessage {
+            data,
+            local,
+            latched,
+            publisher: link.publisher.clone(),
+            _charge: Arc::new(charge),
+        };
+        let topic_name = link.publisher.topic.as_str();
+        if !link.link.send(message, topic_name) {
+            log::debug!("No subscribers left on {topic_name}, dropping a message");
+        }
+    }
+}
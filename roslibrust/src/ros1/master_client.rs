@@ -1,5 +1,6 @@
 //! This module is concerned with direct communication over xmlprc between the master
 
+use super::graph_events::{listed, GraphEventLog};
 use log::*;
 
 #[derive(thiserror::Error, Debug)]
@@ -14,13 +15,109 @@ pub enum RosMasterError {
     MasterError(String),
     #[error("Failure running xmlrpc server: {0}")]
     HostIoError(#[from] hyper::Error),
-    #[error("Failed to determine a valid ip address on which to host the nodes xmlrpc server, check that one of ROS_IP, ROS_HOSTNAME or the computer's hostname resolve to a valid Ipv4 address {0}")]
+    #[error("Failed to determine a valid ip address on which to host the nodes xmlrpc server, check that one of ROS_IP, ROS_HOSTNAME or the computer's hostname resolve to a valid IP address {0}")]
     HostIpResolutionFailure(String),
 }
 
-/// A client that exposes the API hosted by the [rosmaster](http://wiki.ros.org/ROS/Master_API)
-// TODO consider exposing this type publicly
-pub(crate) struct MasterClient {
+/// Wrapper allowing a parameter of any type to be deserialized from a master response
+#[derive(Debug)]
+pub(crate) struct ParamValue(pub serde_xmlrpc::Value);
+
+impl<'de> serde::Deserialize<'de> for ParamValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{MapAccess, SeqAccess, Visitor};
+        use serde_xmlrpc::Value;
+
+        struct ParamVisitor;
+        impl<'de> Visitor<'de> for ParamVisitor {
+            type Value = ParamValue;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("any xmlrpc value")
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<ParamValue, E> {
+                Ok(ParamValue(Value::Bool(v)))
+            }
+
+            fn visit_i32<E>(self, v: i32) -> Result<ParamValue, E> {
+                Ok(ParamValue(Value::Int(v)))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<ParamValue, E> {
+                // Plain xmlrpc ints are also handed to us as i64
+                Ok(ParamValue(match i32::try_from(v) {
+                    Ok(v) => Value::Int(v),
+                    Err(_) => Value::Int64(v),
+                }))
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<ParamValue, E> {
+                Ok(ParamValue(Value::Double(v)))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<ParamValue, E> {
+                Ok(ParamValue(Value::String(v.to_owned())))
+            }
+
+            fn visit_string<E>(self, v: String) -> Result<ParamValue, E> {
+                Ok(ParamValue(Value::String(v)))
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<ParamValue, E> {
+                Ok(ParamValue(Value::Base64(v.to_vec())))
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<ParamValue, E> {
+                Ok(ParamValue(Value::Base64(v)))
+            }
+
+            fn visit_unit<E>(self) -> Result<ParamValue, E> {
+                Ok(ParamValue(Value::Nil))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ParamValue, A::Error> {
+                let mut values = vec![];
+                while let Some(ParamValue(value)) = seq.next_element()? {
+                    values.push(value);
+                }
+                Ok(ParamValue(Value::Array(values)))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ParamValue, A::Error> {
+                let mut values = std::collections::BTreeMap::new();
+                while let Some((key, ParamValue(value))) = map.next_entry::<String, ParamValue>()? {
+                    values.insert(key, value);
+                }
+                Ok(ParamValue(Value::Struct(values)))
+            }
+        }
+
+        deserializer.deserialize_any(ParamVisitor)
+    }
+}
+
+/// A client that exposes the API hosted by the [rosmaster](http://wiki.ros.org/ROS/Master_API),
+/// for introspecting the ROS graph the way `rosnode` and `rostopic` do:
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), roslibrust::RosMasterError> {
+/// // The client uri is only needed for registering with the master, not for queries
+/// let master = roslibrust::MasterClient::new("http://localhost:11311", "", "/graph_inspector").await?;
+/// for (topic, topic_type) in master.get_published_topics("").await? {
+///     println!("{topic} [{topic_type}]");
+/// }
+/// let state = master.get_system_state().await?;
+/// for entry in state.subscribers() {
+///     println!("{} is subscribed to by {:?}", entry.topic, entry.nodes);
+/// }
+/// println!("rosout is at {}", master.lookup_node("/rosout").await?);
+/// # Ok(())
+/// # }
+/// ```
+/// Nodes created with [crate::NodeHandle] register their publishers, subscribers and services themselves,
+/// the registration calls here are for implementing nodes by hand.
+pub struct MasterClient {
     client: reqwest::Client,
     // Address at which the rosmaster should be found
     master_uri: String,
@@ -29,17 +126,21 @@ pub(crate) struct MasterClient {
     client_uri: String,
     // An id for this node
     id: String,
+    // Where registrations and lookups are recorded, for clients belonging to a node
+    graph_events: Option<GraphEventLog>,
 }
 
 /// Format of data returned by rosmaster's getSystemState
-#[derive(Debug)]
-struct StateEntry {
-    topic: String,
-    nodes: Vec<String>,
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateEntry {
+    /// Name of the topic or service
+    pub topic: String,
+    /// Names of the nodes publishing, subscribing to or providing it
+    pub nodes: Vec<String>,
 }
 
 /// The complete list of publishers, subscribers, and service hosts know to the master
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct SystemState {
     publishers: Vec<StateEntry>,
     subscribers: Vec<StateEntry>,
@@ -47,6 +148,21 @@ pub struct SystemState {
 }
 
 impl SystemState {
+    /// Every published topic along with the nodes publishing it
+    pub fn publishers(&self) -> &[StateEntry] {
+        &self.publishers
+    }
+
+    /// Every subscribed topic along with the nodes subscribed to it
+    pub fn subscribers(&self) -> &[StateEntry] {
+        &self.subscribers
+    }
+
+    /// Every service along with the nodes providing it
+    pub fn service_providers(&self) -> &[StateEntry] {
+        &self.service_providers
+    }
+
     /// Helper function for checking if a node is registered as a publisher of a given topic.
     /// Returns true iff the node is a publisher of that topic
     pub fn is_publishing(&self, topic: &str, node: &str) -> bool {
@@ -106,6 +222,7 @@ impl MasterClient {
             master_uri: master_uri.into(),
             client_uri: client_uri.into(),
             id: id.into(),
+            graph_events: None,
         };
 
         match client.get_uri().await {
@@ -114,6 +231,26 @@ impl MasterClient {
         }
     }
 
+    /// Records the registrations and lookups made with this client in `graph_events`
+    pub(crate) fn with_graph_events(mut self, graph_events: GraphEventLog) -> Self {
+        self.graph_events = Some(graph_events);
+        self
+    }
+
+    // Records a call concerning `name` in the graph event log, if the client has one
+    fn record<T>(
+        &self,
+        method: &str,
+        name: &str,
+        result: &Result<T, RosMasterError>,
+        outcome: impl FnOnce(&T) -> String,
+    ) {
+        if let Some(graph_events) = &self.graph_events {
+            let outcome = result.as_ref().map(outcome).map_err(|err| err.to_string());
+            graph_events.record(method, name, outcome);
+        }
+    }
+
     async fn post<T: serde::de::DeserializeOwned + std::fmt::Debug>(
         &self,
         request: String,
@@ -166,18 +303,20 @@ impl MasterClient {
         service: impl Into<String>,
         service_uri: impl Into<String>,
     ) -> Result<(), RosMasterError> {
+        let service = service.into();
         let body = serde_xmlrpc::request_to_string(
             "registerService",
             vec![
                 self.id.clone().into(),
-                service.into().into(),
+                service.clone().into(),
                 service_uri.into().into(),
                 self.client_uri.clone().into(),
             ],
         )?;
         // Little conversion here to ignore third response parameter
-        let _: u8 = self.post(body).await?;
-        Ok(())
+        let result = self.post::<u8>(body).await.map(|_| ());
+        self.record("registerService", &service, &result, |_| String::new());
+        result
     }
 
     /// Hits the master's xmlrpc endpoint "unregisterService", returns true if the service was
@@ -188,16 +327,18 @@ impl MasterClient {
         service: impl Into<String>,
         service_uri: impl Into<String>,
     ) -> Result<bool, RosMasterError> {
+        let service = service.into();
         let body = serde_xmlrpc::request_to_string(
             "unregisterService",
             vec![
                 self.id.clone().into(),
-                service.into().into(),
+                service.clone().into(),
                 service_uri.into().into(),
             ],
         )?;
-        let x: u8 = self.post(body).await?;
-        Ok(x.eq(&1))
+        let result = self.post::<u8>(body).await.map(|x| x.eq(&1));
+        self.record("unregisterService", &service, &result, unregistered);
+        result
     }
 
     /// Hits the master's xmlrpc endpoint "registerSubscriber", returns the list of publisher node
@@ -207,16 +348,21 @@ impl MasterClient {
         topic: impl Into<String>,
         topic_type: impl Into<String>,
     ) -> Result<Vec<String>, RosMasterError> {
+        let topic = topic.into();
         let body = serde_xmlrpc::request_to_string(
             "registerSubscriber",
             vec![
                 self.id.clone().into(),
-                topic.into().into(),
+                topic.clone().into(),
                 topic_type.into().into(),
                 self.client_uri.clone().into(),
             ],
         )?;
-        self.post(body).await
+        let result = self.post::<Vec<String>>(body).await;
+        self.record("registerSubscriber", &topic, &result, |uris| {
+            listed("publishers", uris)
+        });
+        result
     }
 
     /// Hits the master's xmlrpc endpoint "unregisterSubscriber", returns true if the subscriber
@@ -225,17 +371,19 @@ impl MasterClient {
         &self,
         topic: impl Into<String>,
     ) -> Result<bool, RosMasterError> {
+        let topic = topic.into();
         let body = serde_xmlrpc::request_to_string(
             "unregisterSubscriber",
             vec![
                 self.id.clone().into(),
-                topic.into().into(),
+                topic.clone().into(),
                 self.client_uri.clone().into(),
             ],
         )?;
         // little hack to convert response to bool
-        let x: u8 = self.post(body).await?;
-        Ok(x.eq(&1))
+        let result = self.post::<u8>(body).await.map(|x| x.eq(&1));
+        self.record("unregisterSubscriber", &topic, &result, unregistered);
+        result
     }
 
     /// Hits the master's xmlrpc endpoint "registerPublisher", returns the list of current
@@ -245,17 +393,22 @@ impl MasterClient {
         topic: impl Into<String>,
         topic_type: impl Into<String>,
     ) -> Result<Vec<String>, RosMasterError> {
+        let topic = topic.into();
         let body = serde_xmlrpc::request_to_string(
             "registerPublisher",
             vec![
                 self.id.clone().into(),
-                topic.into().into(),
+                topic.clone().into(),
                 topic_type.into().into(),
                 self.client_uri.clone().into(),
             ],
         )?;
         log::trace!("Posting {body:?} to register publisher");
-        self.post(body).await
+        let result = self.post::<Vec<String>>(body).await;
+        self.record("registerPublisher", &topic, &result, |uris| {
+            listed("subscribers", uris)
+        });
+        result
     }
 
     /// Hits the master's xmlrpc endpoint "unregisterPublisher", returns true if the subscriber was
@@ -264,17 +417,19 @@ impl MasterClient {
         &self,
         topic: impl Into<String>,
     ) -> Result<bool, RosMasterError> {
+        let topic = topic.into();
         let body = serde_xmlrpc::request_to_string(
             "unregisterPublisher",
             vec![
                 self.id.clone().into(),
-                topic.into().into(),
+                topic.clone().into(),
                 self.client_uri.clone().into(),
             ],
         )?;
         // little hack to convert response to bool
-        let x: u8 = self.post(body).await?;
-        Ok(x.eq(&1))
+        let result = self.post::<u8>(body).await.map(|x| x.eq(&1));
+        self.record("unregisterPublisher", &topic, &result, unregistered);
+        result
     }
 
     /// Hits the master's xmlrpc endpoint "lookupNode" and returns the uri associated with the
@@ -283,24 +438,30 @@ impl MasterClient {
         &self,
         node_name: impl Into<String>,
     ) -> Result<String, RosMasterError> {
+        let node_name = node_name.into();
         let body = serde_xmlrpc::request_to_string(
             "lookupNode",
-            vec![self.id.clone().into(), node_name.into().into()],
+            vec![self.id.clone().into(), node_name.clone().into()],
         )?;
-        self.post(body).await
+        let result = self.post::<String>(body).await;
+        self.record("lookupNode", &node_name, &result, String::clone);
+        result
     }
 
-    /// Hits the master's xmlrpc endpoint "lookup_service" and returns the uri associated with the
+    /// Hits the master's xmlrpc endpoint "lookupService" and returns the rosrpc uri associated with the
     /// service
     pub async fn lookup_service(
         &self,
         service_name: impl Into<String>,
     ) -> Result<String, RosMasterError> {
+        let service_name = service_name.into();
         let body = serde_xmlrpc::request_to_string(
             "lookupService",
-            vec![self.id.clone().into(), service_name.into().into()],
+            vec![self.id.clone().into(), service_name.clone().into()],
         )?;
-        self.post(body).await
+        let result = self.post::<String>(body).await;
+        self.record("lookupService", &service_name, &result, String::clone);
+        result
     }
 
     /// Hits the master's xmlrpc endpoint "getPublishedTopics" and returns the corresponding list.
@@ -327,6 +488,113 @@ impl MasterClient {
         &self.client_uri
     }
 
+    /// Hits the master's xmlrpc endpoint "getParam" and returns the value of the parameter.
+    /// Returns an error if the parameter is not set.
+    pub async fn get_param(
+        &self,
+        key: impl Into<String>,
+    ) -> Result<serde_xmlrpc::Value, RosMasterError> {
+        let body = serde_xmlrpc::request_to_string(
+            "getParam",
+            vec![self.id.clone().into(), key.into().into()],
+        )?;
+        let ParamValue(value) = self.post(body).await?;
+        Ok(value)
+    }
+
+    /// Hits the master's xmlrpc endpoint "setParam", creating or replacing the parameter.
+    /// Setting a dictionary value replaces the whole namespace beneath the key.
+    pub async fn set_param(
+        &self,
+        key: impl Into<String>,
+        value: serde_xmlrpc::Value,
+    ) -> Result<(), RosMasterError> {
+        let body = serde_xmlrpc::request_to_string(
+            "setParam",
+            vec![self.id.clone().into(), key.into().into(), value],
+        )?;
+        // Response contains an ignorable integer
+        let _: i32 = self.post(body).await?;
+        Ok(())
+    }
+
+    /// Hits the master's xmlrpc endpoint "deleteParam".
+    /// Returns an error if the parameter is not set.
+    pub async fn delete_param(&self, key: impl Into<String>) -> Result<(), RosMasterError> {
+        let body = serde_xmlrpc::request_to_string(
+            "deleteParam",
+            vec![self.id.clone().into(), key.into().into()],
+        )?;
+        // Response contains an ignorable integer
+        let _: i32 = self.post(body).await?;
+        Ok(())
+    }
+
+    /// Hits the master's xmlrpc endpoint "hasParam" and returns whether the parameter is set.
+    pub async fn has_param(&self, key: impl Into<String>) -> Result<bool, RosMasterError> {
+        let body = serde_xmlrpc::request_to_string(
+            "hasParam",
+            vec![self.id.clone().into(), key.into().into()],
+        )?;
+        self.post(body).await
+    }
+
+    /// Hits the master's xmlrpc endpoint "searchParam", which looks for `key` in this node's namespace
+    /// and then in each parent namespace, returning the full name of the closest parameter found.
+    /// Returns None if no parameter was found.
+    pub async fn search_param(
+        &self,
+        key: impl Into<String>,
+    ) -> Result<Option<String>, RosMasterError> {
+        let body = serde_xmlrpc::request_to_string(
+            "searchParam",
+            vec![self.id.clone().into(), key.into().into()],
+        )?;
+        match self.post(body).await {
+            Ok(found) => Ok(Some(found)),
+            // The master reports an unsuccessful search with an error status
+            Err(RosMasterError::MasterError(msg)) => {
+                debug!("searchParam found nothing: {msg}");
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Hits the master's xmlrpc endpoint "subscribeParam", after which the master calls "paramUpdate"
+    /// on this node's xmlrpc server whenever the parameter changes.
+    /// Returns the current value of the parameter, which is an empty dictionary if it is not set.
+    pub async fn subscribe_param(
+        &self,
+        key: impl Into<String>,
+    ) -> Result<serde_xmlrpc::Value, RosMasterError> {
+        let body = serde_xmlrpc::request_to_string(
+            "subscribeParam",
+            vec![
+                self.id.clone().into(),
+                self.client_uri.clone().into(),
+                key.into().into(),
+            ],
+        )?;
+        let ParamValue(value) = self.post(body).await?;
+        Ok(value)
+    }
+
+    /// Hits the master's xmlrpc endpoint "unsubscribeParam", returns true if this node was subscribed
+    /// to the parameter and false if the server reported this operation as a no-op.
+    pub async fn unsubscribe_param(&self, key: impl Into<String>) -> Result<bool, RosMasterError> {
+        let body = serde_xmlrpc::request_to_string(
+            "unsubscribeParam",
+            vec![
+                self.id.clone().into(),
+                self.client_uri.clone().into(),
+                key.into().into(),
+            ],
+        )?;
+        let unsubscribed: i32 = self.post(body).await?;
+        Ok(unsubscribed == 1)
+    }
+
     /// Hits the master's xmlrpc endpoint "getSystemState" and returns the response
     pub async fn get_system_state(&self) -> Result<SystemState, RosMasterError> {
         // Comes in order of Publishers, Subscribers, Services
@@ -357,6 +625,15 @@ impl MasterClient {
     }
 }
 
+// Describes the answer to an unregistration
+fn unregistered(unregistered: &bool) -> String {
+    if *unregistered {
+        "unregistered".to_owned()
+    } else {
+        "was not registered".to_owned()
+    }
+}
+
 #[cfg(feature = "ros1_test")]
 #[cfg(test)]
 mod test {
@@ -376,7 +653,13 @@ mod test {
 
     #[test_log::test(tokio::test)]
     async fn get_system_state() -> Result<(), RosMasterError> {
-        let _state = test_client().await?.get_system_state().await?;
+        let state = test_client().await?.get_system_state().await?;
+        // The rosout node started alongside the master publishes /rosout_agg
+        assert!(state
+            .publishers()
+            .iter()
+            .any(|entry| entry.topic == "/rosout_agg"
+                && entry.nodes.iter().any(|node| node == "/rosout")));
         Ok(())
     }
 
@@ -398,6 +681,36 @@ mod test {
         assert!(!topic_types.is_empty());
     }
 
+    #[test_log::test(tokio::test)]
+    async fn test_set_get_and_delete_param() {
+        let client = test_client().await.unwrap();
+        let key = "/native_ros1_test/param_test/gain";
+
+        client
+            .set_param(key, serde_xmlrpc::Value::Double(1.5))
+            .await
+            .unwrap();
+        assert!(client.has_param(key).await.unwrap());
+        assert_eq!(
+            client.get_param(key).await.unwrap(),
+            serde_xmlrpc::Value::Double(1.5)
+        );
+        // The test node lives in the root namespace so only top level names can be found
+        assert_eq!(client.search_param("gain").await.unwrap(), None);
+        assert_eq!(
+            client
+                .search_param("native_ros1_test")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("/native_ros1_test")
+        );
+
+        client.delete_param(key).await.unwrap();
+        assert!(!client.has_param(key).await.unwrap());
+        assert!(client.get_param(key).await.is_err());
+    }
+
     #[test_log::test(tokio::test)]
     async fn test_register_and_unregister_service() {
         let client = test_client().await.unwrap();
@@ -476,4 +789,37 @@ mod test {
         let topics = client.get_published_topics(subgraph).await.unwrap();
         assert!(!topics.is_empty());
     }
+
+    #[test_log::test(tokio::test)]
+    async fn test_graph_query() {
+        use crate::GraphQuery;
+        let client = test_client().await.unwrap();
+        assert_eq!(
+            client.topic_type("/rosout_agg").await.unwrap(),
+            Some("rosgraph_msgs/Log".to_owned())
+        );
+        assert!(client
+            .topics_for_type("rosgraph_msgs/Log")
+            .await
+            .unwrap()
+            .iter()
+            .any(|topic| topic == "/rosout_agg"));
+        assert!(client
+            .nodes()
+            .await
+            .unwrap()
+            .iter()
+            .any(|node| node == "/rosout"));
+        let details = client.node_details("/rosout").await.unwrap();
+        assert!(details
+            .publishing
+            .iter()
+            .any(|topic| topic == "/rosout_agg"));
+        assert!(details.subscribing.iter().any(|topic| topic == "/rosout"));
+        assert_eq!(
+            client.service_node("/rosout/get_loggers").await.unwrap(),
+            Some("/rosout".to_owned())
+        );
+        assert_eq!(client.service_node("/not_a_service").await.unwrap(), None);
+    }
 }
@@ -0,0 +1,375 @@
+//! This module contains the accounting of the bytes a node holds in its publisher queues and subscription buffers,
+//! and the budgets bounding them so a stalled consumer can't exhaust the memory of the robot.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::sync::Notify;
+
+/// What happens to a message which would take a topic or the node over its [MemoryBudget]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BudgetPolicy {
+    /// Publishers discard the oldest messages waiting in their queue to make room for new ones.
+    /// Messages a subscription has handed to the node's subscribers can't be taken back,
+    /// so subscriptions discard the incoming message instead, as if it was lost on the way.
+    #[default]
+    DropOldest,
+    /// Waits for room: publishing waits for queued messages to be sent, while subscriptions stop reading
+    /// from their publishers until the node's subscribers catch up, letting TCP push back on the publishers.
+    Backpressure,
+    /// Publishing fails with [std::io::ErrorKind::OutOfMemory], while subscriptions discard the incoming
+    /// message and log an error.
+    Error,
+}
+
+/// A limit on the bytes held in the queues and buffers of a topic, see [super::TopicOptions::memory_budget],
+/// or of every topic of a node, see [super::NodeHandleOptions::memory_budget].
+///
+/// A single message larger than the whole budget is still let through while nothing else is held,
+/// so it isn't refused forever.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryBudget {
+    pub bytes: usize,
+    pub policy: BudgetPolicy,
+}
+
+impl MemoryBudget {
+    pub fn new(bytes: usize, policy: BudgetPolicy) -> Self {
+        Self { bytes, policy }
+    }
+}
+
+/// The bytes held for a topic or the whole node, see [MemoryBudgets]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Bytes of the messages currently waiting in publisher queues or to be received by subscribers
+    pub bytes: usize,
+    /// The most bytes held at once
+    pub peak_bytes: usize,
+    /// The number of messages discarded or refused to stay within budget
+    pub dropped: u64,
+    pub budget: Option<MemoryBudget>,
+}
+
+/// The memory accounting of a node, retrieved with [super::NodeHandle::memory_budgets].
+///
+/// Every message a publisher has queued but not yet sent, and every message a subscription has received
+/// but not yet handed to all of the node's subscribers of the topic, is counted against its topic
+/// and the node as a whole. Budgets set with [super::TopicOptions::memory_budget] and
+/// [super::NodeHandleOptions::memory_budget] bound the bytes held, so a subscriber which stops reading
+/// or a publisher whose subscribers stall can't grow the node without limit.
+/// ```no_run
+/// # async fn run(nh: roslibrust::NodeHandle) {
+/// use roslibrust::{BudgetPolicy, MemoryBudget};
+/// nh.memory_budgets()
+///     .set_node_budget(Some(MemoryBudget::new(64 * 1024 * 1024, BudgetPolicy::DropOldest)));
+/// for (topic, usage) in nh.memory_budgets().usage() {
+///     println!("{topic} holds {} bytes, dropped {} messages", usage.bytes, usage.dropped);
+/// }
+/// # }
+/// ```
+///
+/// Clones share the same accounting.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryBudgets {
+    node: Arc<NodeAccount>,
+    // Accounts are kept once created, so the usage of topics the node no longer has is still reported
+    topics: Arc<Mutex<BTreeMap<String, Arc<TopicAccount>>>>,
+}
+
+impl MemoryBudgets {
+    pub(crate) fn new(node_budget: Option<MemoryBudget>) -> Self {
+        let budgets = Self::default();
+        budgets.set_node_budget(node_budget);
+        budgets
+    }
+
+    /// Replaces the budget of the node as a whole, None leaving it unbounded
+    pub fn set_node_budget(&self, budget: Option<MemoryBudget>) {
+        *self.node.budget.lock().unwrap() = budget;
+        // Publishers and subscriptions waiting for room may have some now
+        self.node.released.notify_waiters();
+    }
+
+    /// The bytes held across every topic of the node
+    pub fn node_usage(&self) -> MemoryUsage {
+        self.node.usage.snapshot(*self.node.budget.lock().unwrap())
+    }
+
+    /// The bytes held for the resolved name `topic`, None if the node never advertised or subscribed to it
+    pub fn topic_usage(&self, topic: &str) -> Option<MemoryUsage> {
+        let topics = self.topics.lock().unwrap();
+        topics.get(topic).map(|account| account.usage())
+    }
+
+    /// The bytes held for each topic the node has advertised or subscribed to, keyed by resolved name
+    pub fn usage(&self) -> BTreeMap<String, MemoryUsage> {
+        let topics = self.topics.lock().unwrap();
+        topics
+            .iter()
+            .map(|(topic, account)| (topic.clone(), account.usage()))
+            .collect()
+    }
+
+    /// The account of `topic`, shared by its publication and subscription.
+    /// A budget replaces the one the topic had, while None leaves it as it was.
+    pub(crate) fn account(&self, topic: &str, budget: Option<MemoryBudget>) -> Arc<TopicAccount> {
+        let mut topics = self.topics.lock().unwrap();
+        let account = topics.entry(topic.to_owned()).or_insert_with(|| {
+            Arc::new(TopicAccount {
+                topic: topic.to_owned(),
+                usage: Usage::default(),
+                budget: Mutex::default(),
+                node: self.node.clone(),
+            })
+        });
+        if budget.is_some() {
+            *account.budget.lock().unwrap() = budget;
+            self.node.released.notify_waiters();
+        }
+        account.clone()
+    }
+}
+
+#[derive(Debug, Default)]
+struct NodeAccount {
+    usage: Usage,
+    budget: Mutex<Option<MemoryBudget>>,
+    // Notified whenever bytes are released or budgets change, waking those applying backpressure
+    released: Notify,
+}
+
+#[derive(Debug, Default)]
+struct Usage {
+    bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+    dropped: AtomicU64,
+}
+
+impl Usage {
+    fn add(&self, bytes: usize) {
+        let held = self.bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.peak_bytes.fetch_max(held, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self, budget: Option<MemoryBudget>) -> MemoryUsage {
+        MemoryUsage {
+            bytes: self.bytes.load(Ordering::Relaxed),
+            peak_bytes: self.peak_bytes.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            budget,
+        }
+    }
+
+    // The policy of `budget` if holding `bytes` more would exceed it
+    fn exceeded(&self, budget: Option<MemoryBudget>, bytes: usize) -> Option<BudgetPolicy> {
+        let budget = budget?;
+        let held = self.bytes.load(Ordering::Relaxed);
+        (held > 0 && held + bytes > budget.bytes).then_some(budget.policy)
+    }
+
+    // Whether more than `budget` is held under DropOldest, besides the `bytes` of the message being checked
+    fn overrun(&self, budget: Option<MemoryBudget>, bytes: usize) -> bool {
+        let held = self.bytes.load(Ordering::Relaxed);
+        match budget {
+            Some(MemoryBudget {
+                bytes: limit,
+                policy: BudgetPolicy::DropOldest,
+            }) => held > limit && held > bytes,
+            _ => false,
+        }
+    }
+}
+
+/// The bytes held for a single topic, counted towards its node's total as well
+#[derive(Debug)]
+pub(crate) struct TopicAccount {
+    topic: String,
+    usage: Usage,
+    budget: Mutex<Option<MemoryBudget>>,
+    node: Arc<NodeAccount>,
+}
+
+impl TopicAccount {
+    fn usage(&self) -> MemoryUsage {
+        self.usage.snapshot(*self.budget.lock().unwrap())
+    }
+
+    // The policy of the first budget holding `bytes` more would exceed, the topic's before the node's
+    fn exceeded(&self, bytes: usize) -> Option<BudgetPolicy> {
+        let topic_budget = *self.budget.lock().unwrap();
+        let node_budget = *self.node.budget.lock().unwrap();
+        self.usage
+            .exceeded(topic_budget, bytes)
+            .or_else(|| self.node.usage.exceeded(node_budget, bytes))
+    }
+
+    fn charge(self: &Arc<Self>, bytes: usize) -> Charge {
+        self.usage.add(bytes);
+        self.node.usage.add(bytes);
+        Charge {
+            account: self.clone(),
+            bytes,
+        }
+    }
+
+    fn count_dropped(&self) {
+        self.usage.dropped.fetch_add(1, Ordering::Relaxed);
+        self.node.usage.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Charges a message a publisher is about to queue, waiting for room under [BudgetPolicy::Backpressure].
+    /// Under [BudgetPolicy::DropOldest] the message is always queued, the publication instead discarding
+    /// the oldest queued messages as told by [Charge::evict].
+    pub async fn charge_outgoing(self: &Arc<Self>, bytes: usize) -> std::io::Result<Charge> {
+        loop {
+            // Created before checking, so a release in between isn't missed
+            let released = self.node.released.notified();
+            match self.exceeded(bytes) {
+                None | Some(BudgetPolicy::DropOldest) => return Ok(self.charge(bytes)),
+                Some(BudgetPolicy::Backpressure) => released.await,
+                Some(BudgetPolicy::Error) => {
+                    self.count_dropped();
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::OutOfMemory,
+                        format!(
+                            "Queueing {bytes} bytes on {} would exceed its memory budget",
+                            self.topic
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Charges a message a subscription received, waiting for room under [BudgetPolicy::Backpressure].
+    /// Returns None if the message is to be discarded.
+    pub async fn charge_incoming(self: &Arc<Self>, bytes: usize) -> Option<Charge> {
+        loop {
+            let released = self.node.released.notified();
+            match self.exceeded(bytes) {
+                None => return Some(self.charge(bytes)),
+                Some(BudgetPolicy::Backpressure) => released.await,
+                Some(BudgetPolicy::DropOldest) => {
+                    self.count_dropped();
+                    log::debug!(
+                        "Dropping a message received on {} to stay within its memory budget",
+                        self.topic
+                    );
+                    return None;
+                }
+                Some(BudgetPolicy::Error) => {
+                    self.count_dropped();
+                    log::error!(
+                        "Dropping a message received on {} as it would exceed the memory budget",
+                        self.topic
+                    );
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// The bytes of a message charged to its topic and node, released when dropped
+#[derive(Debug)]
+pub(crate) struct Charge {
+    account: Arc<TopicAccount>,
+    bytes: usize,
+}
+
+impl Charge {
+    /// Whether the message should be discarded to make room for newer ones, as more than a
+    /// [BudgetPolicy::DropOldest] budget is held besides it. Discarded messages are counted as dropped.
+    pub fn evict(&self) -> bool {
+        let account = &self.account;
+        let topic_budget = *account.budget.lock().unwrap();
+        let node_budget = *account.node.budget.lock().unwrap();
+        let evict = account.usage.overrun(topic_budget, self.bytes)
+            || account.node.usage.overrun(node_budget, self.bytes);
+        if evict {
+            account.count_dropped();
+        }
+        evict
+    }
+}
+
+impl Drop for Charge {
+    fn drop(&mut self) {
+        self.account
+            .usage
+            .bytes
+            .fetch_sub(self.bytes, Ordering::Relaxed);
+        self.account
+            .node
+            .usage
+            .bytes
+            .fetch_sub(self.bytes, Ordering::Relaxed);
+        self.account.node.released.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BudgetPolicy, MemoryBudget, MemoryBudgets};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn budgets_bound_the_bytes_held() {
+        let budgets = MemoryBudgets::new(Some(MemoryBudget::new(1000, BudgetPolicy::Error)));
+        let chatter = budgets.account(
+            "/chatter",
+            Some(MemoryBudget::new(100, BudgetPolicy::DropOldest)),
+        );
+        let images = budgets.account(
+            "/image",
+            Some(MemoryBudget::new(800, BudgetPolicy::Backpressure)),
+        );
+
+        // Charges are counted against the topic and node until dropped
+        let first = chatter.charge_outgoing(60).await.unwrap();
+        let second = chatter.charge_outgoing(60).await.unwrap();
+        assert_eq!(budgets.topic_usage("/chatter").unwrap().bytes, 120);
+        assert_eq!(budgets.node_usage().bytes, 120);
+        // Over a DropOldest budget the oldest message is evicted, after which the newest fits
+        assert!(first.evict());
+        drop(first);
+        assert!(!second.evict());
+        // Subscriptions can't evict what they handed out, so drop incoming messages instead
+        assert!(chatter.charge_incoming(60).await.is_none());
+        assert_eq!(budgets.topic_usage("/chatter").unwrap().dropped, 2);
+        drop(second);
+
+        // A message larger than the whole budget still passes when nothing else is held
+        let image = images.charge_incoming(900).await.unwrap();
+        let waiting = tokio::spawn({
+            let images = images.clone();
+            async move { images.charge_incoming(500).await.map(|_| ()) }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+        drop(image);
+        tokio::time::timeout(Duration::from_secs(1), waiting)
+            .await
+            .expect("Backpressure should end once bytes are released")
+            .unwrap()
+            .expect("Message should be admitted");
+
+        // The node's budget applies across topics
+        let image = images.charge_outgoing(700).await.unwrap();
+        let err = chatter.charge_outgoing(400).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::OutOfMemory);
+        drop(image);
+
+        let usage = budgets.usage();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage["/image"].bytes, 0);
+        assert_eq!(usage["/image"].peak_bytes, 900);
+        assert_eq!(budgets.node_usage().dropped, 3);
+        assert_eq!(budgets.topic_usage("/unknown"), None);
+    }
+}
@@ -8,12 +8,139 @@ pub use master_client::*;
 mod xmlrpc_server;
 pub(crate) use xmlrpc_server::*;
 
+/// [xmlrpc_client] module contains the http client used to call the xmlrpc APIs of other nodes
+mod xmlrpc_client;
+
+/// [names] module contains validation, resolution and remapping of graph names
 mod names;
+pub use names::{InvalidNameError, Remappings};
 
 /// [node] module contains the central Node and NodeHandle APIs
 mod node;
 pub use node::*;
 
+/// [topic_defaults] module contains the per topic options applied when advertising and subscribing
+mod topic_defaults;
+pub use topic_defaults::{TopicDefaults, TopicOptions};
+
+/// [topic_endpoints] module contains the cache of the endpoints publishers give in answer to requestTopic
+mod topic_endpoints;
+pub use topic_endpoints::{TopicEndpoint, TopicEndpoints};
+
+/// [memory_budgets] module contains the accounting and budgets of the bytes held in a node's queues
+mod memory_budgets;
+pub use memory_budgets::{BudgetPolicy, MemoryBudget, MemoryBudgets, MemoryUsage};
+
+/// [callback_panics] module contains the isolation of panics in user callbacks
+mod callback_panics;
+pub use callback_panics::CallbackPanic;
+
+/// [capabilities] module contains the registry of capabilities nodes advertise for discovery by clients
+mod capabilities;
+pub use capabilities::{Capability, CapabilityProvider, CapabilityWatch, CAPABILITIES_NAMESPACE};
+
+/// [checksum] module contains the optional checksums of topic messages exchanged between roslibrust peers
+mod checksum;
+pub use checksum::ChecksumFailures;
+
+/// [graph_events] module contains the log of a node's interactions with the master
+mod graph_events;
+pub use graph_events::{GraphEvent, GraphEventLog};
+
+/// [peer_errors] module contains the reporting of protocol errors in exchanges with other nodes
+mod peer_errors;
+pub use peer_errors::{PeerError, PeerErrorKind};
+
+/// [header_stamper] module contains the stamping of message headers by publishers
+mod header_stamper;
+
+/// [intra_process] module contains the delivery of messages between a node's own publishers and subscribers
+mod intra_process;
+
+mod bus_info;
 mod publisher;
+pub use publisher::SubscriberInfo;
 mod subscriber;
+pub use subscriber::{AnyMessage, AnySubscriber, DeadLetter, DeserializeErrorPolicy, MessageInfo};
+
+/// [subscription_queue] module contains the queue subscriptions hand messages to subscribers through
+mod subscription_queue;
+pub use subscription_queue::{QueueOverflowPolicy, SubscriberStats};
+
+/// [parallel_subscriber] module contains the ParallelSubscriber for handling messages on a pool of workers
+mod parallel_subscriber;
+pub use parallel_subscriber::ParallelSubscriber;
 mod tcpros;
+pub use tcpros::{ConnectionHeader, HeaderValidation};
+mod tls;
+#[cfg(feature = "ros1_tls")]
+pub use tls::TcprosTls;
+
+/// [udpros] module contains the framing of topics carried over UDP
+mod udpros;
+
+/// [addrs] module contains the binding of dual-stack sockets and the formatting of IPv6 hosts
+mod addrs;
+
+/// [unixros] module contains the negotiation of topics carried over Unix domain sockets between nodes on the same host
+mod unixros;
+
+/// [service_client] module contains the ServiceClient for calling services natively over TCPROS
+mod service_client;
+pub use service_client::*;
+
+/// [action_client] module contains the ActionClient for sending goals to action servers natively
+mod action_client;
+pub use action_client::{ActionClient, CommState, GoalHandle, GoalResult, TerminalState};
+
+/// [move_base] module contains the MoveBaseClient for sending navigation goals to move_base natively
+mod move_base;
+pub use move_base::{MoveBaseAction, MoveBaseClient, NavigationGoal};
+
+/// [clock] module contains the Clock which follows simulated time when `/use_sim_time` is set
+mod clock;
+pub use clock::{Clock, Interval, Rate};
+
+/// [timer] module contains the Timer which runs a callback periodically according to the node's clock
+mod timer;
+pub use timer::{Timer, TimerEvent};
+
+/// [config_topic] module contains ConfigTopic and ConfigSubscriber for sharing configuration on latched topics
+mod config_topic;
+pub use config_topic::{ConfigSubscriber, ConfigTopic};
+
+/// [tf_static] module contains the StaticTransformBroadcaster and StaticTransforms for sharing static transforms on /tf_static
+mod tf_static;
+pub use tf_static::{StaticTransform, StaticTransformBroadcaster, StaticTransforms};
+
+/// [topic_pair] module contains the TopicPair for request and response style exchanges over topics
+mod topic_pair;
+pub use topic_pair::TopicPair;
+
+/// [runtime_placement] module contains the choice of runtime a node runs its networking on
+mod runtime_placement;
+pub use runtime_placement::RuntimePlacement;
+
+/// [param_subscriber] module contains the ParamSubscriber for watching parameters for changes
+mod param_subscriber;
+pub use param_subscriber::ParamSubscriber;
+
+/// [primitives] module contains StdMsg, the std_msgs messages wrapping a single primitive
+mod primitives;
+pub use primitives::{Primitive, StdMsg};
+
+/// [rosout] module contains the RosoutLogger for publishing log records to /rosout
+mod rosout;
+pub use rosout::RosoutLogger;
+
+/// [bag] module contains a minimal reader, writer and offline tools for the ROS1 bag format
+mod bag;
+pub use bag::*;
+
+/// [snapshotter] module contains the Snapshotter for capturing recent messages on demand
+mod snapshotter;
+pub use snapshotter::*;
+
+/// [data_tap] module contains the DataTap for recording existing subscriptions at runtime
+mod data_tap;
+pub use data_tap::*;
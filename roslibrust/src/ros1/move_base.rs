@@ -0,0 +1,232 @@
+//! This module contains the MoveBaseClient, an [ActionClient] for sending navigation goals to move_base,
+//! created with [super::NodeHandle::move_base_client].
+//!
+//! As roslibrust does not ship move_base_msgs, the client works with the generated action through a trait
+//! implemented for it:
+//! ```ignore
+//! impl roslibrust::MoveBaseAction for move_base_msgs::MoveBaseAction {
+//!     type PoseStamped = geometry_msgs::PoseStamped;
+//!     fn goal(target_pose: Self::PoseStamped) -> Self::Goal {
+//!         move_base_msgs::MoveBaseGoal { target_pose }
+//!     }
+//!     fn base_position(feedback: &Self::Feedback) -> &Self::PoseStamped {
+//!         &feedback.base_position
+//!     }
+//! }
+//! ```
+//!
+//! ROS2's `nav2_msgs/NavigateToPose` is not covered here, as the rosbridge client does not support actions yet.
+
+use super::{ActionClient, CommState, GoalHandle, GoalResult};
+use roslibrust_codegen::{RosActionType, RosMessageType};
+use std::time::Duration;
+
+/// A `move_base_msgs/MoveBaseAction`, see the [module docs](self) for implementing it
+pub trait MoveBaseAction: RosActionType + 'static {
+    /// `geometry_msgs/PoseStamped`
+    type PoseStamped: RosMessageType;
+    /// The goal of reaching `target_pose`
+    fn goal(target_pose: Self::PoseStamped) -> Self::Goal;
+    /// Where the robot was when the server sent `feedback`
+    fn base_position(feedback: &Self::Feedback) -> &Self::PoseStamped;
+}
+
+/// Sends poses for move_base to navigate to, following the robot's position as it goes.
+/// ```ignore
+/// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/navigator").await?;
+/// let move_base = nh.move_base_client::<move_base_msgs::MoveBaseAction>("/move_base").await?;
+/// move_base.wait_for_server(Some(Duration::from_secs(5))).await?;
+/// let mut goal = move_base.send_goal(target_pose).await?;
+/// while let Some(position) = goal.base_position().await {
+///     log::info!("Robot is at {:?}", position.pose.position);
+/// }
+/// if !goal.result().await?.succeeded() {
+///     log::warn!("Failed to reach the target");
+/// }
+/// ```
+pub struct MoveBaseClient<A: MoveBaseAction> {
+    client: ActionClient<A>,
+}
+
+impl<A: MoveBaseAction> MoveBaseClient<A> {
+    pub(crate) fn new(client: ActionClient<A>) -> Self {
+        Self { client }
+    }
+
+    /// The action client the goals are sent with
+    pub fn action_client(&self) -> &ActionClient<A> {
+        &self.client
+    }
+
+    /// See [ActionClient::wait_for_server]
+    pub async fn wait_for_server(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.client.wait_for_server(timeout).await
+    }
+
+    /// Sends move_base the goal of reaching `target_pose`, returning a handle for following the robot there.
+    /// A goal sent while another is active preempts it.
+    pub async fn send_goal(
+        &self,
+        target_pose: A::PoseStamped,
+    ) -> Result<NavigationGoal<A>, Box<dyn std::error::Error + Send + Sync>> {
+        let handle = self.client.send_goal(goal_to::<A>(target_pose)).await?;
+        Ok(NavigationGoal { handle })
+    }
+
+    /// Sends move_base the goal of reaching `target_pose` and waits until it finishes, successfully or not.
+    pub async fn go_to(
+        &self,
+        target_pose: A::PoseStamped,
+    ) -> Result<GoalResult<A::Result>, Box<dyn std::error::Error + Send + Sync>> {
+        self.client
+            .send_goal_and_wait(goal_to::<A>(target_pose))
+            .await
+    }
+
+    /// Stops the robot, canceling every goal move_base is working on
+    pub async fn cancel_all_goals(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.client.cancel_all_goals().await
+    }
+}
+
+/// A goal sent with [MoveBaseClient::send_goal]. Dropping it stops following the goal, but does not cancel it.
+pub struct NavigationGoal<A: MoveBaseAction> {
+    handle: GoalHandle<A>,
+}
+
+impl<A: MoveBaseAction> NavigationGoal<A> {
+    /// See [GoalHandle::goal_id]
+    pub fn goal_id(&self) -> &str {
+        self.handle.goal_id()
+    }
+
+    /// See [GoalHandle::comm_state]
+    pub fn comm_state(&self) -> CommState {
+        self.handle.comm_state()
+    }
+
+    /// Receives the next position of the robot move_base reported while working on the goal.
+    /// Returns None once the goal is done and every position has been received.
+    pub async fn base_position(&mut self) -> Option<A::PoseStamped> {
+        position_of::<A>(self.handle.feedback().await)
+    }
+
+    /// See [GoalHandle::result]
+    pub async fn result(
+        &mut self,
+    ) -> Result<GoalResult<A::Result>, Box<dyn std::error::Error + Send + Sync>> {
+        self.handle.result().await
+    }
+
+    /// Asks move_base to stop working towards the goal, see [GoalHandle::cancel]
+    pub async fn cancel(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.handle.cancel().await
+    }
+
+    /// The action's goal handle, for the raw feedback
+    pub fn goal_handle(&mut self) -> &mut GoalHandle<A> {
+        &mut self.handle
+    }
+}
+
+// The goal sent to move_base for reaching `target_pose`
+fn goal_to<A: MoveBaseAction>(target_pose: A::PoseStamped) -> A::Goal {
+    A::goal(target_pose)
+}
+
+// The robot's position reported in the next feedback of a goal, None once its feedback has ended
+fn position_of<A: MoveBaseAction>(feedback: Option<A::Feedback>) -> Option<A::PoseStamped> {
+    feedback.map(|feedback| A::base_position(&feedback).clone())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    // Stand-ins for the generated geometry_msgs and move_base_msgs types
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    struct PoseStamped {
+        frame_id: String,
+        x: f64,
+        y: f64,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    struct MoveBaseGoal {
+        target_pose: PoseStamped,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    struct MoveBaseFeedback {
+        base_position: PoseStamped,
+    }
+
+    impl RosMessageType for PoseStamped {
+        const ROS_TYPE_NAME: &'static str = "geometry_msgs/PoseStamped";
+    }
+
+    impl RosMessageType for MoveBaseGoal {
+        const ROS_TYPE_NAME: &'static str = "move_base_msgs/MoveBaseGoal";
+    }
+
+    impl RosMessageType for MoveBaseFeedback {
+        const ROS_TYPE_NAME: &'static str = "move_base_msgs/MoveBaseFeedback";
+    }
+
+    struct MoveBase;
+
+    impl RosActionType for MoveBase {
+        const ROS_ACTION_NAME: &'static str = "move_base_msgs/MoveBase";
+        type Goal = MoveBaseGoal;
+        type Result = PoseStamped;
+        type Feedback = MoveBaseFeedback;
+        type ActionGoal = MoveBaseGoal;
+        type ActionResult = PoseStamped;
+        type ActionFeedback = MoveBaseFeedback;
+    }
+
+    impl MoveBaseAction for MoveBase {
+        type PoseStamped = PoseStamped;
+        fn goal(target_pose: Self::PoseStamped) -> Self::Goal {
+            MoveBaseGoal { target_pose }
+        }
+        fn base_position(feedback: &Self::Feedback) -> &Self::PoseStamped {
+            &feedback.base_position
+        }
+    }
+
+    fn pose(x: f64, y: f64) -> PoseStamped {
+        PoseStamped {
+            frame_id: "map".to_owned(),
+            x,
+            y,
+        }
+    }
+
+    #[test]
+    fn goals_target_the_pose() {
+        assert_eq!(
+            goal_to::<MoveBase>(pose(1.5, -2.0)),
+            MoveBaseGoal {
+                target_pose: pose(1.5, -2.0)
+            }
+        );
+    }
+
+    #[test]
+    fn feedback_maps_to_base_position() {
+        let feedback = MoveBaseFeedback {
+            base_position: pose(0.25, 0.5),
+        };
+        assert_eq!(
+            position_of::<MoveBase>(Some(feedback)),
+            Some(pose(0.25, 0.5))
+        );
+        // Positions end along with the goal's feedback
+        assert_eq!(position_of::<MoveBase>(None), None);
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 lazy_static::lazy_static! {
     static ref GRAPH_NAME_REGEX: regex::Regex = regex::Regex::new(r"^([/~a-zA-Z]){1}([a-zA-Z0-9_/])*([A-z0-9_])$").unwrap();
 }
@@ -50,10 +52,214 @@ impl Name {
     }
 }
 
+impl std::fmt::Display for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.inner)
+    }
+}
+
 fn is_valid(name: &str) -> bool {
     GRAPH_NAME_REGEX.is_match(name)
 }
 
+/// Remappings of graph names, following ROS's [remapping arguments](http://wiki.ros.org/Remapping%20Arguments).
+///
+/// A remapping `from:=to` makes the node use `to` wherever it is asked for `from`, for the topics it publishes and
+/// subscribes to, the services it calls and the parameters it uses. Both names are resolved relative to the node before being compared,
+/// so `chatter:=/robot/chatter` also applies to `/chatter` for a node in the root namespace.
+/// The special remappings `__name:=` and `__ns:=` replace the node's name and namespace.
+///
+/// [crate::NodeHandle::new] applies the remappings given on the command line, as launch files do;
+/// use [crate::NodeHandle::new_with_remappings] to provide them programmatically.
+/// ```
+/// let remappings = roslibrust::Remappings::from_args(["image:=/camera/image_raw", "__ns:=/robot"]);
+/// let remappings = remappings.remap("cmd_vel", "/base/cmd_vel");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Remappings {
+    names: Vec<(String, String)>,
+    node_name: Option<String>,
+    namespace: Option<String>,
+}
+
+impl Remappings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the remappings within `args`, ignoring any arguments which are not remappings.
+    /// Special arguments other than `__name` and `__ns`, e.g. `__log`, are also ignored.
+    pub fn from_args<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut remappings = Self::new();
+        for arg in args {
+            let Some((from, to)) = arg.as_ref().split_once(":=") else {
+                continue;
+            };
+            remappings = match from {
+                "__name" => remappings.node_name(to),
+                "__ns" => remappings.namespace(to),
+                from if from.starts_with('_') => {
+                    log::debug!("Ignoring unsupported argument {from}:={to}");
+                    remappings
+                }
+                from => remappings.remap(from, to),
+            };
+        }
+        remappings
+    }
+
+    /// Parses the remappings within the process's command line arguments, see [Remappings::from_args].
+    pub fn from_env_args() -> Self {
+        Self::from_args(std::env::args().skip(1))
+    }
+
+    /// Parses a remap table read from the parameter server, either a list of remapping arguments,
+    /// e.g. `["chatter:=/robot/chatter"]`, or a dictionary from names to their replacements, e.g. `{chatter: /robot/chatter}`.
+    /// `__name` and `__ns` are ignored with a warning, as the node's name is needed to read the table.
+    pub(crate) fn from_param(value: serde_xmlrpc::Value) -> Result<Self, String> {
+        let args = match value {
+            serde_xmlrpc::Value::Array(args) => args
+                .into_iter()
+                .map(|arg| match arg {
+                    serde_xmlrpc::Value::String(arg) => Ok(arg),
+                    other => Err(format!("expected a remapping argument, got {other:?}")),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            serde_xmlrpc::Value::Struct(names) => names
+                .into_iter()
+                .map(|(from, to)| match to {
+                    serde_xmlrpc::Value::String(to) => Ok(format!("{from}:={to}")),
+                    other => Err(format!("expected a name to remap {from} to, got {other:?}")),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            other => {
+                return Err(format!(
+                    "expected a list or dictionary of remappings, got {other:?}"
+                ))
+            }
+        };
+        let mut remappings = Self::from_args(args);
+        if let Some(name) = remappings.node_name.take() {
+            log::warn!(
+                "Ignoring __name:={name} in remap table, it can only be given on the command line"
+            );
+        }
+        if let Some(namespace) = remappings.namespace.take() {
+            log::warn!("Ignoring __ns:={namespace} in remap table, it can only be given on the command line");
+        }
+        Ok(remappings)
+    }
+
+    /// Adds the remappings of `table` beneath these ones, so these take precedence for names remapped by both
+    pub(crate) fn with_table(mut self, table: Remappings) -> Self {
+        self.names.splice(0..0, table.names);
+        self
+    }
+
+    /// Uses `to` in place of `from`. Invalid names are ignored with a warning.
+    pub fn remap(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        let (from, to) = (from.into(), to.into());
+        if is_valid(&from) && is_valid(&to) {
+            self.names.push((from, to));
+        } else {
+            log::warn!("Ignoring invalid remapping {from}:={to}");
+        }
+        self
+    }
+
+    /// Replaces the node's name, keeping its namespace, equivalent to `__name:=`
+    pub fn node_name(mut self, name: impl Into<String>) -> Self {
+        self.node_name = Some(name.into());
+        self
+    }
+
+    /// Moves the node into `namespace`, equivalent to `__ns:=`
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Moves the node into `namespace` unless `__ns` is given, as `ROS_NAMESPACE` does
+    pub(crate) fn default_namespace(mut self, namespace: Option<String>) -> Self {
+        if self.namespace.is_none() {
+            self.namespace = namespace;
+        }
+        self
+    }
+
+    /// Applies `__name` and `__ns` to the name a node was created with
+    pub(crate) fn apply_to_node_name(&self, name: &str) -> String {
+        let name = match &self.node_name {
+            // Only the last component is the name, anything before it is the namespace
+            Some(base_name) => match name.rsplit_once('/') {
+                Some((namespace, _)) => format!("{namespace}/{base_name}"),
+                None => base_name.clone(),
+            },
+            None => name.to_owned(),
+        };
+        match &self.namespace {
+            Some(namespace) => {
+                let base_name = name.rsplit('/').next().unwrap_or_default();
+                format!("/{}/{base_name}", namespace.trim_matches('/')).replace("//", "/")
+            }
+            None => name,
+        }
+    }
+
+    /// Creates a resolver for the node named `node_name`, resolving the remappings relative to it
+    pub(crate) fn resolver(&self, node_name: &Name) -> NameResolver {
+        let names = self
+            .names
+            .iter()
+            .filter_map(|(from, to)| {
+                let from = Name::new(from.as_str())?.resolve_to_global(node_name);
+                let to = Name::new(to.as_str())?.resolve_to_global(node_name);
+                Some((from.to_string(), to.to_string()))
+            })
+            .collect();
+        NameResolver {
+            node_name: node_name.clone(),
+            remappings: names,
+        }
+    }
+}
+
+/// Returned when a name given to the node is not a valid [graph resource name](http://wiki.ros.org/Names)
+#[derive(thiserror::Error, Debug)]
+#[error("{0} is not a valid ROS graph name")]
+pub struct InvalidNameError(pub String);
+
+/// Resolves names the way roscpp's `resolveName` does for a node, with its [Remappings] applied
+#[derive(Debug)]
+pub(crate) struct NameResolver {
+    node_name: Name,
+    // Keyed by the resolved name being remapped
+    remappings: HashMap<String, String>,
+}
+
+impl NameResolver {
+    /// Resolves `name` to a global name:
+    /// - global names, e.g. `/chatter`, are used as is
+    /// - relative names, e.g. `chatter`, are placed in the node's namespace
+    /// - private names, e.g. `~chatter`, are placed beneath the node's name
+    ///
+    /// and then replaced by the node's remapping of the resolved name, if it has one.
+    pub fn resolve(&self, name: &str) -> Result<String, InvalidNameError> {
+        let resolved = Name::new(name)
+            .ok_or_else(|| InvalidNameError(name.to_owned()))?
+            .resolve_to_global(&self.node_name)
+            .to_string();
+        Ok(match self.remappings.get(&resolved) {
+            Some(remapped) => remapped.clone(),
+            None => resolved,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,6 +277,83 @@ mod tests {
         assert!(!is_valid("_leading"));
     }
 
+    #[test]
+    fn remap_names() {
+        let remappings = Remappings::from_args([
+            "--verbose",
+            "chatter:=/robot/chatter",
+            "~image:=camera/image",
+            "__log:=/tmp/log",
+            "__ns:=/wg",
+        ]);
+        assert_eq!(remappings.apply_to_node_name("talker"), "/wg/talker");
+        assert_eq!(remappings.apply_to_node_name("/ns/talker"), "/wg/talker");
+        let renamed = remappings.clone().node_name("listener");
+        assert_eq!(renamed.apply_to_node_name("/ns/talker"), "/wg/listener");
+        assert_eq!(
+            Remappings::new()
+                .node_name("listener")
+                .apply_to_node_name("/ns/talker"),
+            "/ns/listener"
+        );
+
+        // __ns takes precedence over ROS_NAMESPACE
+        let env_namespace = Some("/robot".to_owned());
+        assert_eq!(
+            remappings
+                .clone()
+                .default_namespace(env_namespace.clone())
+                .apply_to_node_name("talker"),
+            "/wg/talker"
+        );
+        assert_eq!(
+            Remappings::new()
+                .default_namespace(env_namespace)
+                .apply_to_node_name("/talker"),
+            "/robot/talker"
+        );
+
+        let resolver = remappings.resolver(&Name::new("/wg/talker").unwrap());
+        assert_eq!(resolver.resolve("chatter").unwrap(), "/robot/chatter");
+        assert_eq!(resolver.resolve("/wg/chatter").unwrap(), "/robot/chatter");
+        assert_eq!(resolver.resolve("/chatter").unwrap(), "/chatter");
+        assert_eq!(resolver.resolve("~image").unwrap(), "/wg/camera/image");
+        // Names which are not remapped are still resolved
+        assert_eq!(resolver.resolve("image").unwrap(), "/wg/image");
+        assert_eq!(resolver.resolve("~gain").unwrap(), "/wg/talker/gain");
+        assert!(resolver.resolve("bad name").is_err());
+    }
+
+    #[test]
+    fn remap_tables() {
+        use serde_xmlrpc::Value;
+
+        let listed = Remappings::from_param(Value::Array(vec![
+            "chatter:=/robot/chatter".into(),
+            "__ns:=/ignored".into(),
+        ]))
+        .unwrap();
+        assert_eq!(listed.apply_to_node_name("/talker"), "/talker");
+        let table = Remappings::from_param(Value::Struct(
+            [
+                ("chatter".to_owned(), "/table/chatter".into()),
+                ("image".to_owned(), "/camera/image".into()),
+            ]
+            .into_iter()
+            .collect(),
+        ))
+        .unwrap();
+        assert!(Remappings::from_param(Value::Array(vec![Value::Int(1)])).is_err());
+        assert!(Remappings::from_param("chatter:=/robot/chatter".into()).is_err());
+
+        // Remappings given directly take precedence over those of the table
+        let resolver = listed
+            .with_table(table)
+            .resolver(&Name::new("/talker").unwrap());
+        assert_eq!(resolver.resolve("chatter").unwrap(), "/robot/chatter");
+        assert_eq!(resolver.resolve("image").unwrap(), "/camera/image");
+    }
+
     // Examples pulled from http://wiki.ros.org/Names
     #[test]
     fn resolve_name() {
@@ -1,69 +1,289 @@
 //! This module contains the top level Node and NodeHandle classes.
 //! These wrap the lower level management of a ROS Node connection into a higher level and thread safe API.
 
+#[cfg(feature = "ros1_tls")]
+use super::TcprosTls;
 use super::{
-    names::Name,
-    publisher::{Publication, Publisher},
-    subscriber::{Subscriber, Subscription},
+    action_client::ActionClient,
+    addrs,
+    bus_info::{BusConnection, BusConnections},
+    callback_panics::{CallbackGuard, CallbackPanic},
+    capabilities::{
+        self, Advertisement, Advertisements, Capability, CapabilityProvider, CapabilityWatch,
+    },
+    checksum::ChecksumFailures,
+    clock::Clock,
+    config_topic::{ConfigSubscriber, ConfigTopic},
+    graph_events::{GraphEventLog, DEFAULT_GRAPH_EVENT_CAPACITY},
+    memory_budgets::{MemoryBudget, MemoryBudgets},
+    move_base::{MoveBaseAction, MoveBaseClient},
+    names::{InvalidNameError, Name, NameResolver, Remappings},
+    parallel_subscriber::{ParallelSubscriber, WorkerPool},
+    param_subscriber::{ParamSubscriber, ParamSubscription, ParamUpdateReceiver},
+    peer_errors::{PeerError, PeerErrors, PEER_ERROR_QUEUE_SIZE},
+    primitives::{Primitive, PrimitivePublishers, StdMsg},
+    publisher::{Publication, PublicationHandle, PublicationOptions, Publisher, SlowSubscribers},
+    runtime_placement::{DedicatedRuntime, RuntimePlacement},
+    service_client::ServiceClient,
+    subscriber::{AnySubscriber, Subscriber, Subscription},
+    subscription_queue::QueueReceiver,
+    tcpros::{md5sums_match, ConnectionHeader, WILDCARD},
+    tf_static::{StaticTransformBroadcaster, StaticTransforms, TfMessage, TF_STATIC_QUEUE_SIZE},
+    timer::{Timer, TimerEvent},
+    tls::TcprosSecurity,
+    topic_defaults::{TopicDefaults, TopicOptions},
+    topic_endpoints::TopicEndpoints,
+    topic_pair::TopicPair,
+    udpros::{UdprosRequest, UdprosResponse},
+    unixros::{UnixrosRequest, UnixrosResponse},
+    xmlrpc_client::XmlRpcClient,
+};
+use crate::{
+    MasterClient, ReconnectPolicy, RosMasterError, ServiceCallback, XmlRpcServer,
+    XmlRpcServerHandle,
 };
-use crate::{MasterClient, RosMasterError, ServiceCallback, XmlRpcServer, XmlRpcServerHandle};
 use abort_on_drop::ChildTask;
 use roslibrust_codegen::RosMessageType;
 use std::{
     collections::HashMap,
-    net::{IpAddr, Ipv4Addr},
-    sync::Arc,
+    future::Future,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+    time::Duration,
 };
 use tokio::sync::{broadcast, mpsc, oneshot};
 
+// Bounds of the backoff between checks of the master in NodeHandle::wait_for_service
+const WAIT_FOR_SERVICE_INITIAL_DELAY: Duration = Duration::from_millis(50);
+const WAIT_FOR_SERVICE_MAX_DELAY: Duration = Duration::from_secs(1);
+// Where tf2_ros shares static transforms
+const TF_STATIC_TOPIC: &str = "/tf_static";
+// Queue size of both topics of a TopicPair
+const TOPIC_PAIR_QUEUE_SIZE: usize = 10;
+// Queue size of the topics advertised by NodeHandle::publish_primitive
+const PRIMITIVE_QUEUE_SIZE: usize = 10;
+// Where roscpp and rospy look for the master when ROS_MASTER_URI isn't set
+const DEFAULT_MASTER_URI: &str = "http://localhost:11311";
+
+// Default policy for reconnecting subscribers to publishers, see NodeHandleOptions::publisher_reconnect
+const PUBLISHER_RECONNECT_INITIAL_DELAY: Duration = Duration::from_millis(100);
+const PUBLISHER_RECONNECT_MAX_DELAY: Duration = Duration::from_secs(5);
+const PUBLISHER_RECONNECT_MAX_ATTEMPTS: u32 = 10;
+
 #[derive(Debug)]
-pub struct ProtocolParams {
+pub(crate) struct ProtocolParams {
     pub hostname: String,
     pub protocol: String,
     pub port: u16,
+    // Set when the subscriber is answered with UDPROS rather than TCPROS
+    pub udpros: Option<UdprosResponse>,
+    // Set when the subscriber is answered with UNIXROS
+    pub unixros: Option<UnixrosResponse>,
 }
 
+/// A protocol a subscriber asked for in its requestTopic call
 #[derive(Debug)]
-pub enum NodeMsg {
+pub(crate) enum TopicProtocol {
+    Tcpros,
+    Udpros(Box<UdprosRequest>),
+    Unixros(UnixrosRequest),
+}
+
+#[derive(Debug)]
+pub(crate) enum NodeMsg {
     GetMasterUri {
         reply: oneshot::Sender<String>,
     },
     GetClientUri {
         reply: oneshot::Sender<String>,
     },
+    GetNodeName {
+        reply: oneshot::Sender<String>,
+    },
     GetSubscriptions {
         reply: oneshot::Sender<Vec<(String, String)>>,
     },
     GetPublications {
         reply: oneshot::Sender<Vec<(String, String)>>,
     },
+    GetBusConnections {
+        reply: oneshot::Sender<Vec<Arc<BusConnection>>>,
+    },
     SetPeerPublishers {
         topic: String,
         publishers: Vec<String>,
     },
-    Shutdown,
+    Shutdown {
+        reason: NodeShutdownError,
+    },
     RegisterPublisher {
-        reply: oneshot::Sender<Result<mpsc::Sender<Vec<u8>>, String>>,
+        reply: oneshot::Sender<Result<PublicationHandle, String>>,
         topic: String,
         topic_type: String,
         queue_size: usize,
+        latching: bool,
+        keep_latest: bool,
         msg_definition: String,
         md5sum: String,
     },
     RegisterSubscriber {
-        reply: oneshot::Sender<Result<broadcast::Receiver<Vec<u8>>, String>>,
+        reply: oneshot::Sender<Result<QueueReceiver, String>>,
         topic: String,
         topic_type: String,
         queue_size: usize,
         msg_definition: String,
         md5sum: String,
     },
+    UnregisterSubscriber {
+        topic: String,
+    },
+    UnregisterPublisher {
+        topic: String,
+    },
+    TapSubscription {
+        reply: oneshot::Sender<Result<(ConnectionHeader, QueueReceiver), String>>,
+        topic: String,
+    },
+    LookupService {
+        reply: oneshot::Sender<Result<String, String>>,
+        service: String,
+    },
+    GetParam {
+        reply: oneshot::Sender<Result<serde_xmlrpc::Value, String>>,
+        key: String,
+    },
+    SetParam {
+        reply: oneshot::Sender<Result<(), String>>,
+        key: String,
+        value: serde_xmlrpc::Value,
+    },
+    DeleteParam {
+        reply: oneshot::Sender<Result<(), String>>,
+        key: String,
+    },
+    HasParam {
+        reply: oneshot::Sender<Result<bool, String>>,
+        key: String,
+    },
+    SearchParam {
+        reply: oneshot::Sender<Result<Option<String>, String>>,
+        key: String,
+    },
+    SubscribeParam {
+        reply: oneshot::Sender<Result<(String, serde_xmlrpc::Value, ParamUpdateReceiver), String>>,
+        key: String,
+    },
+    UnsubscribeParam {
+        key: String,
+    },
+    ParamUpdate {
+        key: String,
+        value: serde_xmlrpc::Value,
+    },
     RequestTopic {
         reply: oneshot::Sender<Result<ProtocolParams, String>>,
         caller_id: String,
         topic: String,
-        protocols: Vec<String>,
+        protocols: Vec<TopicProtocol>,
+    },
+    AdvertiseCapability {
+        reply: oneshot::Sender<Result<(), String>>,
+        capability: Capability,
+    },
+    WithdrawCapability {
+        reply: oneshot::Sender<Result<(), String>>,
+        capability: Capability,
     },
+    ReapStaleConnections,
+    // Answered once every message sent before it has been handled
+    Barrier {
+        reply: oneshot::Sender<()>,
+    },
+}
+
+/// Returned by node operations once the node has shut down.
+/// Operations which were still waiting on the node when it shut down also resolve with this error.
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum NodeShutdownError {
+    /// [NodeHandle::shutdown] was called, another node requested a shutdown, or every [NodeHandle] was dropped
+    #[error("Node has shut down")]
+    ShutDown,
+    /// The master shut the node down because another node registered with the same name.
+    /// Only one node may hold a name, so the master hands it to the newest node.
+    #[error(
+        "Node {name} was shut down by the master as another node registered with the same name"
+    )]
+    SupersededByNewNode { name: String },
+}
+
+// The message rosmaster sends with its shutdown request when a node is replaced
+pub(crate) const NAME_CONFLICT_REASON: &str = "new node registered with same name";
+
+/// Records why a node shut down, shared with everything which reports a [NodeShutdownError]
+#[derive(Clone, Default)]
+pub(crate) struct ShutdownReason(Arc<OnceLock<NodeShutdownError>>);
+
+impl ShutdownReason {
+    /// Only the first reason is kept
+    pub fn set(&self, reason: NodeShutdownError) {
+        let _ = self.0.set(reason);
+    }
+
+    pub fn error(&self) -> NodeShutdownError {
+        self.0.get().cloned().unwrap_or(NodeShutdownError::ShutDown)
+    }
+}
+
+// Owns the node's task. Dropping this requests a graceful shutdown rather than aborting the task,
+// so the node can still unregister from the master.
+struct NodeTask {
+    node_server_sender: mpsc::UnboundedSender<NodeMsg>,
+    _task: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for NodeTask {
+    fn drop(&mut self) {
+        // Fails only if the node has already shut down
+        let _ = self.node_server_sender.send(NodeMsg::Shutdown {
+            reason: NodeShutdownError::ShutDown,
+        });
+    }
+}
+
+/// Held by the publishers and subscribers the node hands out, asking the node to unregister their topic
+/// once they are dropped. The node only does so once no other publishers or subscribers of the topic are left.
+pub(crate) struct TopicRegistration {
+    node_server_sender: mpsc::UnboundedSender<NodeMsg>,
+    unregister: Option<NodeMsg>,
+}
+
+impl Drop for TopicRegistration {
+    fn drop(&mut self) {
+        if let Some(unregister) = self.unregister.take() {
+            // Fails once the node has shut down, which unregisters everything anyway
+            let _ = self.node_server_sender.send(unregister);
+        }
+    }
+}
+
+impl TopicRegistration {
+    /// Asks the node to unregister the topic as on drop, returning once the node has done so
+    pub async fn release(self) {
+        let node_server_sender = self.node_server_sender.clone();
+        drop(self);
+        let (reply, done) = oneshot::channel();
+        // Fails once the node has shut down, which unregisters everything anyway
+        if node_server_sender.send(NodeMsg::Barrier { reply }).is_ok() {
+            let _ = done.await;
+        }
+    }
+}
+
+impl std::fmt::Debug for TopicRegistration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TopicRegistration").finish_non_exhaustive()
+    }
 }
 
 #[derive(Clone)]
@@ -72,29 +292,80 @@ pub(crate) struct NodeServerHandle {
     // If this handle should keep the underlying node task alive it will hold an
     // Arc to the underlying node task. This is an option because internal handles
     // within the node shouldn't keep it alive (e.g. what we hand to xml server)
-    _node_task: Option<Arc<ChildTask<()>>>,
+    _node_task: Option<Arc<NodeTask>>,
+    shutdown_reason: ShutdownReason,
+    resolver: Arc<NameResolver>,
+    topic_defaults: TopicDefaults,
+    topic_endpoints: TopicEndpoints,
+    callback_guard: CallbackGuard,
+    memory_budgets: MemoryBudgets,
+    checksum_failures: ChecksumFailures,
+    graph_events: GraphEventLog,
+    peer_errors: PeerErrors,
 }
 
 impl NodeServerHandle {
+    fn send(&self, msg: NodeMsg) -> Result<(), NodeShutdownError> {
+        self.node_server_sender
+            .send(msg)
+            .map_err(|_| self.shutdown_reason.error())
+    }
+
+    /// Returns a handle to the same node which does not keep the node alive,
+    /// for long lived internals such as loggers which should not prevent a shutdown.
+    pub fn without_ownership(&self) -> NodeServerHandle {
+        NodeServerHandle {
+            node_server_sender: self.node_server_sender.clone(),
+            _node_task: None,
+            shutdown_reason: self.shutdown_reason.clone(),
+            resolver: self.resolver.clone(),
+            topic_defaults: self.topic_defaults.clone(),
+            topic_endpoints: self.topic_endpoints.clone(),
+            callback_guard: self.callback_guard.clone(),
+            memory_budgets: self.memory_budgets.clone(),
+            checksum_failures: self.checksum_failures.clone(),
+            graph_events: self.graph_events.clone(),
+            peer_errors: self.peer_errors.clone(),
+        }
+    }
+
+    /// The log of the node's interactions with the master
+    pub fn graph_events(&self) -> &GraphEventLog {
+        &self.graph_events
+    }
+
+    /// Where protocol errors in the node's exchanges with other nodes are reported
+    pub fn peer_errors(&self) -> &PeerErrors {
+        &self.peer_errors
+    }
+
+    /// Resolves a topic, service or parameter name relative to the node, applying the node's remappings
+    pub fn resolve_name(&self, name: &str) -> Result<String, InvalidNameError> {
+        self.resolver.resolve(name)
+    }
+
     /// Get the URI of the master node.
-    pub async fn get_master_uri(&self) -> Result<String, Box<dyn std::error::Error>> {
+    pub async fn get_master_uri(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
         let (sender, receiver) = oneshot::channel();
-        match self
-            .node_server_sender
-            .send(NodeMsg::GetMasterUri { reply: sender })
-        {
-            Ok(()) => Ok(receiver.await.map_err(|err| Box::new(err))?),
+        match self.send(NodeMsg::GetMasterUri { reply: sender }) {
+            Ok(()) => Ok(receiver.await.map_err(|_| self.shutdown_reason.error())?),
             Err(e) => Err(Box::new(e)),
         }
     }
 
     pub async fn get_client_uri(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
         let (sender, receiver) = oneshot::channel();
-        match self
-            .node_server_sender
-            .send(NodeMsg::GetClientUri { reply: sender })
-        {
-            Ok(()) => Ok(receiver.await.map_err(|err| Box::new(err))?),
+        match self.send(NodeMsg::GetClientUri { reply: sender }) {
+            Ok(()) => Ok(receiver.await.map_err(|_| self.shutdown_reason.error())?),
+            Err(e) => Err(Box::new(e)),
+        }
+    }
+
+    /// Gets the name the node registered with the master under, used as the caller id for connections.
+    pub async fn get_node_name(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let (sender, receiver) = oneshot::channel();
+        match self.send(NodeMsg::GetNodeName { reply: sender }) {
+            Ok(()) => Ok(receiver.await.map_err(|_| self.shutdown_reason.error())?),
             Err(e) => Err(Box::new(e)),
         }
     }
@@ -105,11 +376,8 @@ impl NodeServerHandle {
         &self,
     ) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
         let (sender, receiver) = oneshot::channel();
-        match self
-            .node_server_sender
-            .send(NodeMsg::GetSubscriptions { reply: sender })
-        {
-            Ok(()) => Ok(receiver.await.map_err(|err| Box::new(err))?),
+        match self.send(NodeMsg::GetSubscriptions { reply: sender }) {
+            Ok(()) => Ok(receiver.await.map_err(|_| self.shutdown_reason.error())?),
             Err(e) => Err(Box::new(e)),
         }
     }
@@ -120,11 +388,19 @@ impl NodeServerHandle {
         &self,
     ) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
         let (sender, receiver) = oneshot::channel();
-        match self
-            .node_server_sender
-            .send(NodeMsg::GetPublications { reply: sender })
-        {
-            Ok(()) => Ok(receiver.await.map_err(|err| Box::new(err))?),
+        match self.send(NodeMsg::GetPublications { reply: sender }) {
+            Ok(()) => Ok(receiver.await.map_err(|_| self.shutdown_reason.error())?),
+            Err(e) => Err(Box::new(e)),
+        }
+    }
+
+    /// Gets the open connections of the node's publishers and subscribers, as reported by getBusStats and getBusInfo.
+    pub(crate) async fn get_bus_connections(
+        &self,
+    ) -> Result<Vec<Arc<BusConnection>>, Box<dyn std::error::Error>> {
+        let (sender, receiver) = oneshot::channel();
+        match self.send(NodeMsg::GetBusConnections { reply: sender }) {
+            Ok(()) => Ok(receiver.await.map_err(|_| self.shutdown_reason.error())?),
             Err(e) => Err(Box::new(e)),
         }
     }
@@ -137,38 +413,110 @@ impl NodeServerHandle {
         publishers: Vec<String>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         Ok(self
-            .node_server_sender
             .send(NodeMsg::SetPeerPublishers { topic, publishers })
             .map_err(|err| Box::new(err))?)
     }
 
-    pub fn shutdown(&self) -> Result<(), Box<dyn std::error::Error>> {
-        self.node_server_sender
-            .send(NodeMsg::Shutdown)
-            .map_err(|err| Box::new(err))?;
+    /// Subscribes the node to changes of a parameter, returning the resolved name of the parameter,
+    /// its current value and a receiver of future updates.
+    pub async fn subscribe_param(
+        &self,
+        key: &str,
+    ) -> Result<
+        (String, serde_xmlrpc::Value, ParamUpdateReceiver),
+        Box<dyn std::error::Error + Send + Sync>,
+    > {
+        let (sender, receiver) = oneshot::channel();
+        self.send(NodeMsg::SubscribeParam {
+            reply: sender,
+            key: self.resolve_name(key)?,
+        })?;
+        Ok(receiver.await.map_err(|_| self.shutdown_reason.error())??)
+    }
+
+    /// Releases one subscriber of a parameter, the node unsubscribes once none are left.
+    pub fn unsubscribe_param(&self, key: String) -> Result<(), Box<dyn std::error::Error>> {
+        self.send(NodeMsg::UnsubscribeParam { key })?;
+        Ok(())
+    }
+
+    pub fn param_update(
+        &self,
+        key: String,
+        value: serde_xmlrpc::Value,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.send(NodeMsg::ParamUpdate { key, value })?;
+        Ok(())
+    }
+
+    /// Requests the node shut down without waiting for it to finish.
+    pub fn shutdown(&self, reason: NodeShutdownError) -> Result<(), Box<dyn std::error::Error>> {
+        self.send(NodeMsg::Shutdown { reason })?;
         Ok(())
     }
 
+    /// Shuts the node down, returning once it has unregistered from the master and closed its connections.
+    /// Returns immediately if the node has already shut down.
+    pub async fn shutdown_and_wait(&self) {
+        // Fails if a shutdown is already underway, in which case we wait on that one
+        let _ = self.send(NodeMsg::Shutdown {
+            reason: NodeShutdownError::ShutDown,
+        });
+        self.node_server_sender.closed().await
+    }
+
+    /// Waits until the node has shut down for any reason, returning why.
+    pub async fn wait_for_shutdown(&self) -> NodeShutdownError {
+        self.node_server_sender.closed().await;
+        self.shutdown_reason.error()
+    }
+
+    pub fn is_shut_down(&self) -> bool {
+        self.node_server_sender.is_closed()
+    }
+
+    /// Resolves once the node has shut down like [NodeServerHandle::wait_for_shutdown],
+    /// without borrowing the handle or keeping the node alive while waiting
+    pub fn shut_down(&self) -> impl Future<Output = ()> + Send + 'static {
+        let sender = self.node_server_sender.clone();
+        async move { sender.closed().await }
+    }
+
+    pub fn shutdown_reason(&self) -> &ShutdownReason {
+        &self.shutdown_reason
+    }
+
     pub async fn register_publisher<T: RosMessageType>(
         &self,
         topic: &str,
         topic_type: &str,
         queue_size: usize,
-    ) -> Result<mpsc::Sender<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>> {
+        latching: bool,
+        keep_latest: bool,
+    ) -> Result<PublicationHandle, Box<dyn std::error::Error + Send + Sync>> {
         let (sender, receiver) = oneshot::channel();
-        match self.node_server_sender.send(NodeMsg::RegisterPublisher {
+        let topic = self.resolve_name(topic)?;
+        match self.send(NodeMsg::RegisterPublisher {
             reply: sender,
-            topic: topic.to_owned(),
+            topic: topic.clone(),
             topic_type: topic_type.to_owned(),
             queue_size,
+            latching,
+            keep_latest,
             msg_definition: T::DEFINITION.to_owned(),
             md5sum: T::MD5SUM.to_owned(),
         }) {
             Ok(()) => {
-                let received = receiver.await.map_err(|err| Box::new(err))?;
-                Ok(received.map_err(|_err| {
-                    Box::new(std::io::Error::from(std::io::ErrorKind::ConnectionAborted))
-                })?)
+                let received = receiver.await.map_err(|_| self.shutdown_reason.error())?;
+                let (queue, subscribers) = received.map_err(|err| {
+                    log::error!("Failed to register publisher: {err}");
+                    Box::new(std::io::Error::new(
+                        std::io::ErrorKind::ConnectionAborted,
+                        err,
+                    ))
+                })?;
+                let registration = self.topic_registration(NodeMsg::UnregisterPublisher { topic });
+                Ok((queue.unregister_on_drop(registration), subscribers))
             }
             Err(err) => Err(Box::new(err)),
         }
@@ -178,42 +526,215 @@ impl NodeServerHandle {
         &self,
         topic: &str,
         queue_size: usize,
-    ) -> Result<broadcast::Receiver<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<QueueReceiver, Box<dyn std::error::Error + Send + Sync>> {
+        self.register_subscriber_of_type(
+            topic,
+            T::ROS_TYPE_NAME,
+            T::DEFINITION,
+            T::MD5SUM,
+            queue_size,
+        )
+        .await
+    }
+
+    /// Subscribes to a topic of any type, see [NodeHandle::subscribe_any]
+    pub async fn register_any_subscriber(
+        &self,
+        topic: &str,
+        queue_size: usize,
+    ) -> Result<QueueReceiver, Box<dyn std::error::Error + Send + Sync>> {
+        self.register_subscriber_of_type(topic, WILDCARD, "", WILDCARD, queue_size)
+            .await
+    }
+
+    async fn register_subscriber_of_type(
+        &self,
+        topic: &str,
+        topic_type: &str,
+        msg_definition: &str,
+        md5sum: &str,
+        queue_size: usize,
+    ) -> Result<QueueReceiver, Box<dyn std::error::Error + Send + Sync>> {
         let (sender, receiver) = oneshot::channel();
-        match self.node_server_sender.send(NodeMsg::RegisterSubscriber {
+        let topic = self.resolve_name(topic)?;
+        match self.send(NodeMsg::RegisterSubscriber {
             reply: sender,
-            topic: topic.to_owned(),
-            topic_type: T::ROS_TYPE_NAME.to_owned(),
+            topic: topic.clone(),
+            topic_type: topic_type.to_owned(),
             queue_size,
-            msg_definition: T::DEFINITION.to_owned(),
-            md5sum: T::MD5SUM.to_owned(),
+            msg_definition: msg_definition.to_owned(),
+            md5sum: md5sum.to_owned(),
         }) {
             Ok(()) => {
-                let received = receiver.await.map_err(|err| Box::new(err))?;
-                Ok(received.map_err(|err| {
+                let received = receiver.await.map_err(|_| self.shutdown_reason.error())?;
+                let receiver = received.map_err(|err| {
                     log::error!("Failed to register subscriber: {err}");
-                    Box::new(std::io::Error::from(std::io::ErrorKind::ConnectionAborted))
+                    Box::new(std::io::Error::new(
+                        std::io::ErrorKind::ConnectionAborted,
+                        err,
+                    ))
+                })?;
+                let registration = self.topic_registration(NodeMsg::UnregisterSubscriber { topic });
+                Ok(receiver.unregister_on_drop(registration))
+            }
+            Err(err) => Err(Box::new(err)),
+        }
+    }
+
+    // Sends `unregister` to the node once dropped
+    fn topic_registration(&self, unregister: NodeMsg) -> TopicRegistration {
+        TopicRegistration {
+            node_server_sender: self.node_server_sender.clone(),
+            unregister: Some(unregister),
+        }
+    }
+
+    /// Gets a receiver of the raw messages for a topic this node is already subscribed to.
+    /// Unlike [NodeServerHandle::register_subscriber] this never creates a new subscription.
+    /// The returned connection header describes the subscription's message type.
+    pub async fn tap_subscription(
+        &self,
+        topic: &str,
+    ) -> Result<(ConnectionHeader, QueueReceiver), Box<dyn std::error::Error + Send + Sync>> {
+        let (sender, receiver) = oneshot::channel();
+        match self.send(NodeMsg::TapSubscription {
+            reply: sender,
+            topic: self.resolve_name(topic)?,
+        }) {
+            Ok(()) => {
+                let received = receiver.await.map_err(|_| self.shutdown_reason.error())?;
+                Ok(received.map_err(|err| {
+                    log::error!("Failed to tap subscription: {err}");
+                    Box::new(std::io::Error::from(std::io::ErrorKind::NotFound))
                 })?)
             }
             Err(err) => Err(Box::new(err)),
         }
     }
 
+    /// Asks the master for the rosrpc URI of the node currently providing `service`.
+    pub async fn lookup_service(
+        &self,
+        service: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let (sender, receiver) = oneshot::channel();
+        match self.send(NodeMsg::LookupService {
+            reply: sender,
+            service: service.to_owned(),
+        }) {
+            Ok(()) => {
+                let received = receiver.await.map_err(|_| self.shutdown_reason.error())?;
+                Ok(received.map_err(|err| {
+                    // Not an error in itself, e.g. while waiting for a service to come up
+                    log::debug!("Failed to look up service {service}: {err}");
+                    Box::new(std::io::Error::from(std::io::ErrorKind::NotFound))
+                })?)
+            }
+            Err(err) => Err(Box::new(err)),
+        }
+    }
+
+    pub async fn get_param(
+        &self,
+        key: &str,
+    ) -> Result<serde_xmlrpc::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send(NodeMsg::GetParam {
+            reply: sender,
+            key: self.resolve_name(key)?,
+        })?;
+        Ok(receiver.await.map_err(|_| self.shutdown_reason.error())??)
+    }
+
+    pub async fn set_param(
+        &self,
+        key: &str,
+        value: serde_xmlrpc::Value,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send(NodeMsg::SetParam {
+            reply: sender,
+            key: self.resolve_name(key)?,
+            value,
+        })?;
+        Ok(receiver.await.map_err(|_| self.shutdown_reason.error())??)
+    }
+
+    pub async fn delete_param(
+        &self,
+        key: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send(NodeMsg::DeleteParam {
+            reply: sender,
+            key: self.resolve_name(key)?,
+        })?;
+        Ok(receiver.await.map_err(|_| self.shutdown_reason.error())??)
+    }
+
+    pub async fn has_param(
+        &self,
+        key: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send(NodeMsg::HasParam {
+            reply: sender,
+            key: self.resolve_name(key)?,
+        })?;
+        Ok(receiver.await.map_err(|_| self.shutdown_reason.error())??)
+    }
+
+    pub async fn search_param(
+        &self,
+        key: &str,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send(NodeMsg::SearchParam {
+            reply: sender,
+            key: key.to_owned(),
+        })?;
+        Ok(receiver.await.map_err(|_| self.shutdown_reason.error())??)
+    }
+
+    pub async fn advertise_capability(
+        &self,
+        capability: Capability,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send(NodeMsg::AdvertiseCapability {
+            reply: sender,
+            capability,
+        })?;
+        Ok(receiver.await.map_err(|_| self.shutdown_reason.error())??)
+    }
+
+    pub async fn withdraw_capability(
+        &self,
+        capability: Capability,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send(NodeMsg::WithdrawCapability {
+            reply: sender,
+            capability,
+        })?;
+        Ok(receiver.await.map_err(|_| self.shutdown_reason.error())??)
+    }
+
     pub async fn request_topic(
         &self,
         caller_id: &str,
         topic: &str,
-        protocols: &[String],
+        protocols: Vec<TopicProtocol>,
     ) -> Result<ProtocolParams, Box<dyn std::error::Error>> {
         let (sender, receiver) = oneshot::channel();
-        match self.node_server_sender.send(NodeMsg::RequestTopic {
+        match self.send(NodeMsg::RequestTopic {
             caller_id: caller_id.to_owned(),
             topic: topic.to_owned(),
-            protocols: protocols.into(),
+            protocols,
             reply: sender,
         }) {
             Ok(()) => {
-                let received = receiver.await.map_err(|err| Box::new(err))?;
+                let received = receiver.await.map_err(|_| self.shutdown_reason.error())?;
                 Ok(received.map_err(|err| {
                     log::error!(
                         "Fail to coordinate channel between publisher and subscriber: {err}"
@@ -228,7 +749,7 @@ impl NodeServerHandle {
 
 /// Represents a single "real" node, typically only one of these is expected per process
 /// but nothing should specifically prevent that.
-pub struct Node {
+pub(crate) struct Node {
     // The xmlrpc client this node uses to make requests to master
     client: MasterClient,
     // Server which handles updates from the rosmaster and other ROS nodes
@@ -241,37 +762,114 @@ pub struct Node {
     subscriptions: HashMap<String, Subscription>,
     // Record of what services this node is serving
     services: HashMap<String, ServiceCallback>,
+    // Record of parameters this node is watching for changes
+    param_subscriptions: HashMap<String, ParamSubscription>,
+    // Capabilities this node advertises on the parameter server, see NodeHandle::advertise_capability
+    capabilities: Vec<Capability>,
+    // Why the node shut down, shared with the node's handles, publishers and subscribers
+    shutdown_reason: ShutdownReason,
+    // Every open connection of the node's publishers and subscribers, reported by getBusStats and getBusInfo
+    bus_connections: BusConnections,
+    // Overrides of the queue sizes requested by callers, see NodeHandleOptions
+    default_queue_size: Option<usize>,
+    // Options of individual topics, shared with the node's handles
+    topic_defaults: TopicDefaults,
+    // How subscriptions retry connections to publishers which fail or close
+    publisher_reconnect: ReconnectPolicy,
+    // Shared by subscriptions for requesting topics from publishers
+    xmlrpc_client: XmlRpcClient,
+    // Endpoints publishers gave for their topics, shared by subscriptions and with the node's handles
+    topic_endpoints: TopicEndpoints,
+    // Runs user callbacks, shared with the node's handles
+    callback_guard: CallbackGuard,
+    // Accounts for the bytes held by publications and subscriptions, shared with the node's handles
+    memory_budgets: MemoryBudgets,
+    // Counts the messages subscriptions dropped for failing their checksum, shared with the node's handles
+    checksum_failures: ChecksumFailures,
+    // Where protocol errors of the node's peers are reported, shared with the node's handles
+    peer_errors: PeerErrors,
+    // Whether the TCPROS connections of publications and subscriptions are wrapped in TLS
+    tcpros_security: TcprosSecurity,
+    // Where the graph events the master client records are written when the node shuts down, if anywhere
+    graph_event_dump: Option<(GraphEventLog, PathBuf)>,
+    // Periodically asks the node to close stale connections, if enabled
+    _reaper_task: Option<ChildTask<()>>,
+    // The runtime the node runs on, if it has one of its own
+    _runtime: Option<DedicatedRuntime>,
     // TODO need signal to shutdown xmlrpc server when node is dropped
-    host_addr: Ipv4Addr,
+    host_addr: IpAddr,
     hostname: String,
     node_name: String,
 }
 
 impl Node {
     async fn new(
-        master_uri: &str,
         hostname: &str,
         node_name: &str,
-        addr: Ipv4Addr,
+        addr: IpAddr,
+        options: &NodeHandleOptions,
+        runtime: Option<DedicatedRuntime>,
     ) -> Result<NodeServerHandle, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(global_name) = Name::new(node_name) else {
+            log::error!("Node name {node_name} is not valid");
+            return Err(Box::new(InvalidNameError(node_name.to_owned())));
+        };
+        let resolver = Arc::new(options.remappings.resolver(&global_name));
+        let topic_defaults = TopicDefaults::default();
+        for (topic, topic_options) in &options.topics {
+            topic_defaults.set(&resolver.resolve(topic)?, topic_options.clone())?;
+        }
+
+        let topic_endpoints = TopicEndpoints::default();
+        let callback_guard = CallbackGuard::new(options.abort_on_callback_panic);
+        let memory_budgets = MemoryBudgets::new(options.memory_budget);
+        let checksum_failures = ChecksumFailures::default();
+        let graph_events = GraphEventLog::new(options.graph_event_capacity);
+        let peer_errors = PeerErrors::default();
+
         let (node_sender, node_receiver) = mpsc::unbounded_channel();
+        let shutdown_reason = ShutdownReason::default();
         let xml_server_handle = NodeServerHandle {
             node_server_sender: node_sender.clone(),
             // None here because this handle should not keep task alive
             _node_task: None,
+            shutdown_reason: shutdown_reason.clone(),
+            resolver: resolver.clone(),
+            topic_defaults: topic_defaults.clone(),
+            topic_endpoints: topic_endpoints.clone(),
+            callback_guard: callback_guard.clone(),
+            memory_budgets: memory_budgets.clone(),
+            checksum_failures: checksum_failures.clone(),
+            graph_events: graph_events.clone(),
+            peer_errors: peer_errors.clone(),
         };
         // Create our xmlrpc server and bind our socket so we know our port and can determine our local URI
         let xmlrpc_server = XmlRpcServer::new(addr, xml_server_handle)?;
-        let client_uri = format!("http://{hostname}:{}", xmlrpc_server.port());
-
-        if let None = Name::new(node_name) {
-            log::error!("Node name {node_name} is not valid");
-            return Err(Box::new(std::io::Error::from(
-                std::io::ErrorKind::InvalidInput,
-            )));
-        }
+        let client_uri = format!(
+            "http://{}",
+            addrs::host_port(hostname, xmlrpc_server.port())
+        );
 
-        let rosmaster_client = MasterClient::new(master_uri, client_uri, node_name).await?;
+        let rosmaster_client = MasterClient::new(&options.master_uri, client_uri, node_name)
+            .await?
+            .with_graph_events(graph_events.clone());
+        let reaper_task: Option<ChildTask<()>> = options.stale_connection_reaping.map(|period| {
+            let node_sender = node_sender.clone();
+            let task_name = format!("stale connection reaper of {node_name}");
+            crate::tasks::spawn(&task_name, async move {
+                let mut interval = tokio::time::interval(period);
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                // The first tick completes immediately
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    if node_sender.send(NodeMsg::ReapStaleConnections).is_err() {
+                        break;
+                    }
+                }
+            })
+            .into()
+        });
         let mut node = Self {
             client: rosmaster_client,
             _xmlrpc_server: xmlrpc_server,
@@ -279,34 +877,69 @@ impl Node {
             publishers: std::collections::HashMap::new(),
             subscriptions: std::collections::HashMap::new(),
             services: std::collections::HashMap::new(),
+            param_subscriptions: std::collections::HashMap::new(),
+            capabilities: vec![],
+            shutdown_reason: shutdown_reason.clone(),
+            bus_connections: BusConnections::default(),
+            default_queue_size: options.default_queue_size,
+            topic_defaults: topic_defaults.clone(),
+            publisher_reconnect: options.publisher_reconnect.clone(),
+            xmlrpc_client: XmlRpcClient::default(),
+            topic_endpoints: topic_endpoints.clone(),
+            callback_guard: callback_guard.clone(),
+            memory_budgets: memory_budgets.clone(),
+            checksum_failures: checksum_failures.clone(),
+            peer_errors: peer_errors.clone(),
+            #[cfg(feature = "ros1_tls")]
+            tcpros_security: TcprosSecurity::new(options.tcpros_tls.clone()),
+            #[cfg(not(feature = "ros1_tls"))]
+            tcpros_security: TcprosSecurity::default(),
+            graph_event_dump: options
+                .graph_event_dump
+                .clone()
+                .map(|path| (graph_events.clone(), path)),
+            _reaper_task: reaper_task,
+            _runtime: runtime,
             host_addr: addr,
             hostname: hostname.to_owned(),
             node_name: node_name.to_owned(),
         };
 
-        let t = Arc::new(
-            tokio::spawn(async move {
-                loop {
-                    match node.node_msg_rx.recv().await {
-                        Some(NodeMsg::Shutdown) => {
-                            log::info!("Shutdown requested, shutting down node");
-                            break;
-                        }
-                        Some(node_msg) => {
-                            node.handle_msg(node_msg).await;
-                        }
-                        None => {
-                            break;
-                        }
+        let task = crate::tasks::spawn(&format!("node {node_name}"), async move {
+            loop {
+                match node.node_msg_rx.recv().await {
+                    Some(NodeMsg::Shutdown { reason }) => {
+                        log::info!("Shutdown requested, shutting down node");
+                        node.shutdown(reason).await;
+                        break;
+                    }
+                    Some(node_msg) => {
+                        node.handle_msg(node_msg).await;
+                    }
+                    None => {
+                        break;
                     }
                 }
-            })
-            .into(),
-        );
+            }
+            // Dropping the node drops any requests still queued, which resolves them with a NodeShutdownError
+        });
+        let t = Arc::new(NodeTask {
+            node_server_sender: node_sender.clone(),
+            _task: task,
+        });
 
         let node_server_handle = NodeServerHandle {
             node_server_sender: node_sender,
             _node_task: Some(t),
+            shutdown_reason,
+            resolver,
+            topic_defaults,
+            topic_endpoints,
+            callback_guard,
+            memory_budgets,
+            checksum_failures,
+            graph_events,
+            peer_errors,
         };
         Ok(node_server_handle)
     }
@@ -319,6 +952,9 @@ impl Node {
             NodeMsg::GetClientUri { reply } => {
                 let _ = reply.send(self.client.client_uri().to_owned());
             }
+            NodeMsg::GetNodeName { reply } => {
+                let _ = reply.send(self.node_name.clone());
+            }
             NodeMsg::GetSubscriptions { reply } => {
                 let _ = reply.send(
                     self.subscriptions
@@ -337,14 +973,13 @@ impl Node {
                         .collect(),
                 );
             }
+            NodeMsg::GetBusConnections { reply } => {
+                let _ = reply.send(self.bus_connections.current());
+            }
             NodeMsg::SetPeerPublishers { topic, publishers } => {
                 if let Some(subscription) = self.subscriptions.get_mut(&topic) {
-                    for publisher_uri in publishers {
-                        if let Err(err) = subscription.add_publisher_source(&publisher_uri).await {
-                            log::error!(
-                                "Unable to create subscribe stream for topic {topic}: {err}"
-                            );
-                        }
+                    if let Err(err) = subscription.set_publishers(&publishers).await {
+                        log::error!("Unable to create subscribe stream for topic {topic}: {err}");
                     }
                 } else {
                     log::warn!(
@@ -357,11 +992,21 @@ impl Node {
                 topic,
                 topic_type,
                 queue_size,
+                latching,
+                keep_latest,
                 msg_definition,
                 md5sum,
             } => {
                 let res = self
-                    .register_publisher(topic, &topic_type, queue_size, msg_definition, md5sum)
+                    .register_publisher(
+                        topic,
+                        &topic_type,
+                        queue_size,
+                        latching,
+                        keep_latest,
+                        msg_definition,
+                        md5sum,
+                    )
                     .await;
                 match res {
                     Ok(handle) => reply.send(Ok(handle)),
@@ -389,171 +1034,1168 @@ impl Node {
                     .map_err(|err| err.to_string()),
                 );
             }
-            NodeMsg::RequestTopic {
-                reply,
-                topic,
-                protocols,
-                ..
-            } => {
-                // TODO: Should move the actual implementation similar to RegisterPublisher
-                if protocols
-                    .iter()
-                    .find(|proto| proto.as_str() == "TCPROS")
-                    .is_some()
-                {
-                    if let Some((_key, publishing_channel)) =
-                        self.publishers.iter().find(|(key, _pub)| *key == &topic)
-                    {
-                        let protocol_params = ProtocolParams {
-                            hostname: self.hostname.clone(),
-                            protocol: String::from("TCPROS"), // Hardcoded as the only option for now
-                            port: publishing_channel.port(),
-                        };
-                        let _ = reply.send(Ok(protocol_params));
-                    } else {
-                        let err_str = format!("Got request for topic {topic} from subscriber which this node does not publish");
-                        log::warn!("{err_str}");
-                        let _ = reply.send(Err(err_str));
+            NodeMsg::UnregisterSubscriber { topic } => {
+                let Some(subscription) = self.subscriptions.get(&topic) else {
+                    return;
+                };
+                if !subscription.has_receivers() {
+                    // Dropping the subscription closes its connections to the topic's publishers
+                    self.subscriptions.remove(&topic);
+                    if let Some(publication) = self.publishers.get(&topic) {
+                        publication.disconnect_local();
+                    }
+                    if let Err(err) = self.client.unregister_subscriber(&topic).await {
+                        log::warn!("Failed to unregister subscriber of {topic}: {err}");
                     }
-                } else {
-                    let err_str = format!(
-                        "No supported protocols in the request from the subscriber: {protocols:?}"
-                    );
-                    log::error!("{err_str}");
-                    let _ = reply.send(Err(err_str));
                 }
             }
-            NodeMsg::Shutdown => {
-                unreachable!("This node msg is handled in the wrapping handling code");
+            NodeMsg::UnregisterPublisher { topic } => {
+                let Some(publication) = self.publishers.get(&topic) else {
+                    return;
+                };
+                if !publication.has_handles() {
+                    // Dropping the publication closes its connections to the topic's subscribers
+                    self.publishers.remove(&topic);
+                    if let Err(err) = self.client.unregister_publisher(&topic).await {
+                        log::warn!("Failed to unregister publisher of {topic}: {err}");
+                    }
+                }
             }
-        }
-    }
-
-    async fn register_subscriber(
-        &mut self,
-        topic: &str,
-        topic_type: &str,
-        queue_size: usize,
-        msg_definition: &str,
-        md5sum: &str,
-    ) -> Result<broadcast::Receiver<Vec<u8>>, Box<dyn std::error::Error>> {
-        match self.subscriptions.iter().find(|(key, _)| *key == topic) {
-            Some((_topic, subscription)) => Ok(subscription.get_receiver()),
-            None => {
-                let mut subscription = Subscription::new(
+            NodeMsg::TapSubscription { reply, topic } => {
+                let _ = reply.send(match self.subscriptions.get(&topic) {
+                    Some(subscription) => Ok((
+                        subscription.connection_header().clone(),
+                        subscription.get_receiver(),
+                    )),
+                    None => Err(format!("Node is not subscribed to {topic}")),
+                });
+            }
+            NodeMsg::LookupService { reply, service } => {
+                let _ = reply.send(
+                    self.client
+                        .lookup_service(service)
+                        .await
+                        .map_err(|err| err.to_string()),
+                );
+            }
+            NodeMsg::GetParam { reply, key } => {
+                let _ = reply.send(
+                    self.client
+                        .get_param(key)
+                        .await
+                        .map_err(|err| err.to_string()),
+                );
+            }
+            NodeMsg::SetParam { reply, key, value } => {
+                let _ = reply.send(
+                    self.client
+                        .set_param(key, value)
+                        .await
+                        .map_err(|err| err.to_string()),
+                );
+            }
+            NodeMsg::DeleteParam { reply, key } => {
+                let _ = reply.send(
+                    self.client
+                        .delete_param(key)
+                        .await
+                        .map_err(|err| err.to_string()),
+                );
+            }
+            NodeMsg::HasParam { reply, key } => {
+                let _ = reply.send(
+                    self.client
+                        .has_param(key)
+                        .await
+                        .map_err(|err| err.to_string()),
+                );
+            }
+            NodeMsg::SearchParam { reply, key } => {
+                let _ = reply.send(
+                    self.client
+                        .search_param(key)
+                        .await
+                        .map_err(|err| err.to_string()),
+                );
+            }
+            NodeMsg::SubscribeParam { reply, key } => {
+                let _ = reply.send(
+                    self.subscribe_param(&key)
+                        .await
+                        .map_err(|err| err.to_string()),
+                );
+            }
+            NodeMsg::UnsubscribeParam { key } => {
+                let Some(subscription) = self.param_subscriptions.get_mut(&key) else {
+                    return;
+                };
+                if subscription.remove_subscriber() == 0 {
+                    self.param_subscriptions.remove(&key);
+                    if let Err(err) = self.client.unsubscribe_param(&key).await {
+                        log::warn!("Failed to unsubscribe from parameter {key}: {err}");
+                    }
+                }
+            }
+            NodeMsg::ParamUpdate { key, value } => {
+                // The master sends keys with a trailing slash, and also reports changes beneath a watched namespace
+                let key = key.trim_end_matches('/');
+                for (watched_key, subscription) in &self.param_subscriptions {
+                    if key == watched_key
+                        || key
+                            .strip_prefix(watched_key.as_str())
+                            .is_some_and(|rest| rest.starts_with('/'))
+                    {
+                        subscription.update(key, value.clone());
+                    }
+                }
+            }
+            NodeMsg::RequestTopic {
+                reply,
+                caller_id,
+                topic,
+                protocols,
+            } => {
+                // TODO: Should move the actual implementation similar to RegisterPublisher
+                let Some(publishing_channel) = self.publishers.get_mut(&topic) else {
+                    let err_str = format!("Got request for topic {topic} from {caller_id} which this node does not publish");
+                    log::warn!("{err_str}");
+                    let _ = reply.send(Err(err_str));
+                    return;
+                };
+                // Subscribers list protocols in order of preference, we take the first we can serve
+                for protocol in &protocols {
+                    match protocol {
+                        TopicProtocol::Tcpros => {
+                            let protocol_params = ProtocolParams {
+                                hostname: self.hostname.clone(),
+                                protocol: String::from("TCPROS"),
+                                port: publishing_channel.port(),
+                                udpros: None,
+                                unixros: None,
+                            };
+                            let _ = reply.send(Ok(protocol_params));
+                            return;
+                        }
+                        TopicProtocol::Udpros(request) => {
+                            match publishing_channel
+                                .add_udp_subscriber(request, &self.hostname)
+                                .await
+                            {
+                                Ok(response) => {
+                                    let protocol_params = ProtocolParams {
+                                        hostname: self.hostname.clone(),
+                                        protocol: String::from("UDPROS"),
+                                        port: response.port,
+                                        udpros: Some(response),
+                                        unixros: None,
+                                    };
+                                    let _ = reply.send(Ok(protocol_params));
+                                    return;
+                                }
+                                Err(err) => {
+                                    log::warn!(
+                                        "Unable to publish {topic} over UDPROS to {}: {err}",
+                                        request.header.caller_id
+                                    );
+                                }
+                            }
+                        }
+                        // Only subscribers on our host can reach our sockets
+                        TopicProtocol::Unixros(request) if request.host == self.hostname => {
+                            match publishing_channel.unix_socket_path() {
+                                Ok(path) => {
+                                    let protocol_params = ProtocolParams {
+                                        hostname: self.hostname.clone(),
+                                        protocol: String::from("UNIXROS"),
+                                        port: 0,
+                                        udpros: None,
+                                        unixros: Some(UnixrosResponse { path }),
+                                    };
+                                    let _ = reply.send(Ok(protocol_params));
+                                    return;
+                                }
+                                Err(err) => {
+                                    log::warn!("Unable to publish {topic} over UNIXROS to {caller_id}: {err}");
+                                }
+                            }
+                        }
+                        TopicProtocol::Unixros(_) => {}
+                    }
+                }
+                let err_str = format!(
+                    "No supported protocols in the request from the subscriber: {protocols:?}"
+                );
+                log::error!("{err_str}");
+                let _ = reply.send(Err(err_str));
+            }
+            NodeMsg::AdvertiseCapability { reply, capability } => {
+                if self.capabilities.contains(&capability) {
+                    let _ = reply.send(Ok(()));
+                    return;
+                }
+                self.capabilities.push(capability);
+                let _ = reply.send(self.publish_capabilities().await);
+            }
+            NodeMsg::WithdrawCapability { reply, capability } => {
+                let Some(index) = self.capabilities.iter().position(|c| c == &capability) else {
+                    let _ = reply.send(Ok(()));
+                    return;
+                };
+                self.capabilities.remove(index);
+                let _ = reply.send(self.publish_capabilities().await);
+            }
+            NodeMsg::ReapStaleConnections => {
+                self.reap_stale_connections().await;
+            }
+            NodeMsg::Barrier { reply } => {
+                let _ = reply.send(());
+            }
+            NodeMsg::Shutdown { .. } => {
+                unreachable!("This node msg is handled in the wrapping handling code");
+            }
+        }
+    }
+
+    // Closes the connections of peers the master no longer lists for the topic, whose leaving was never reported,
+    // see NodeHandleOptions::reap_stale_connections
+    async fn reap_stale_connections(&mut self) {
+        let listed_at = std::time::Instant::now();
+        let state = match self.client.get_system_state().await {
+            Ok(state) => state,
+            Err(err) => {
+                log::warn!(
+                    "Unable to check for stale connections, failed to get the system state: {err}"
+                );
+                return;
+            }
+        };
+        let mut reaped = 0;
+        for (topic, publication) in &self.publishers {
+            let subscribers = state
+                .subscribers()
+                .iter()
+                .find(|entry| &entry.topic == topic)
+                .map(|entry| entry.nodes.as_slice())
+                .unwrap_or_default();
+            reaped += publication.reap_subscribers(subscribers, listed_at).await;
+        }
+
+        // Subscriptions know their publishers by xmlrpc uri rather than by name
+        let mut uris: HashMap<String, Option<String>> = HashMap::new();
+        'subscriptions: for (topic, subscription) in &mut self.subscriptions {
+            let publishers = state
+                .publishers()
+                .iter()
+                .find(|entry| &entry.topic == topic)
+                .map(|entry| entry.nodes.as_slice())
+                .unwrap_or_default();
+            let mut publisher_uris = vec![];
+            for publisher in publishers {
+                if !uris.contains_key(publisher) {
+                    let uri = match self.client.lookup_node(publisher).await {
+                        Ok(uri) => Some(uri),
+                        // The publisher has unregistered since the system state was retrieved
+                        Err(RosMasterError::MasterError(_)) => None,
+                        Err(err) => {
+                            log::warn!("Unable to check {topic} for stale connections, failed to look up {publisher}: {err}");
+                            continue 'subscriptions;
+                        }
+                    };
+                    uris.insert(publisher.clone(), uri);
+                }
+                publisher_uris.extend(uris[publisher].clone());
+            }
+            reaped += subscription.reap_publishers(&publisher_uris);
+        }
+        if reaped > 0 {
+            log::info!("Closed {reaped} stale connections the master no longer lists");
+        }
+        log::debug!(
+            "Node has {} open connections after checking for stale connections",
+            self.bus_connections.current().len()
+        );
+    }
+
+    // Replaces this node's advertisement on the parameter server with its current capabilities
+    async fn publish_capabilities(&self) -> Result<(), String> {
+        let key = capabilities::param_key(&self.node_name);
+        if self.capabilities.is_empty() {
+            return self
+                .client
+                .delete_param(key)
+                .await
+                .map_err(|err| err.to_string());
+        }
+        let advertisement = Advertisement::new(&self.node_name, &self.capabilities);
+        let value = serde_xmlrpc::to_value(&advertisement).map_err(|err| err.to_string())?;
+        self.client
+            .set_param(key, value)
+            .await
+            .map_err(|err| err.to_string())
+    }
+
+    async fn shutdown(&mut self, reason: NodeShutdownError) {
+        let replaced = matches!(reason, NodeShutdownError::SupersededByNewNode { .. });
+        if replaced {
+            log::error!("{reason}");
+        }
+        self.shutdown_reason.set(reason);
+        // When replaced, the master has already handed our name and registrations to the new node
+        if !replaced {
+            self.unregister_all().await;
+        }
+        // Dropping the publications and subscriptions stops their tasks, closing their TCPROS sockets
+        self.publishers.clear();
+        self.subscriptions.clear();
+        self.param_subscriptions.clear();
+        if let Some((graph_events, path)) = &self.graph_event_dump {
+            if let Err(err) = graph_events.dump(path) {
+                log::error!("Failed to write graph events to {}: {err}", path.display());
+            }
+        }
+    }
+
+    // Unregisters everything from the master before closing connections,
+    // so peers learn we are gone from the master rather than from a dropped connection
+    async fn unregister_all(&self) {
+        for topic in self.publishers.keys() {
+            if let Err(err) = self.client.unregister_publisher(topic).await {
+                log::warn!("Failed to unregister publisher of {topic}: {err}");
+            }
+        }
+        for topic in self.subscriptions.keys() {
+            if let Err(err) = self.client.unregister_subscriber(topic).await {
+                log::warn!("Failed to unregister subscriber of {topic}: {err}");
+            }
+        }
+        for key in self.param_subscriptions.keys() {
+            if let Err(err) = self.client.unsubscribe_param(key).await {
+                log::warn!("Failed to unsubscribe from parameter {key}: {err}");
+            }
+        }
+        if !self.capabilities.is_empty() {
+            let key = capabilities::param_key(&self.node_name);
+            if let Err(err) = self.client.delete_param(&key).await {
+                log::warn!("Failed to withdraw capabilities from {key}: {err}");
+            }
+        }
+        // Nothing registers services with the master yet, so there are none to unregister
+    }
+
+    async fn subscribe_param(
+        &mut self,
+        key: &str,
+    ) -> Result<(String, serde_xmlrpc::Value, ParamUpdateReceiver), Box<dyn std::error::Error>>
+    {
+        // Updates from the master carry the resolved name, which is what we are given, so subscriptions are tracked by it
+        let key = key.to_owned();
+
+        // Subscribing again is harmless for the master, and is how we get the current value
+        let value = self.client.subscribe_param(&key).await?;
+        let receiver = self
+            .param_subscriptions
+            .entry(key.clone())
+            .or_insert_with(ParamSubscription::new)
+            .add_subscriber();
+        Ok((key, value, receiver))
+    }
+
+    // The queue size to use for a topic, which the node's options may override
+    fn queue_size(&self, topic: &str, requested: usize) -> usize {
+        self.topic_defaults
+            .get(topic)
+            .queue_size
+            .or(self.default_queue_size)
+            .unwrap_or(requested)
+    }
+
+    async fn register_subscriber(
+        &mut self,
+        topic: &str,
+        topic_type: &str,
+        queue_size: usize,
+        msg_definition: &str,
+        md5sum: &str,
+    ) -> Result<QueueReceiver, Box<dyn std::error::Error>> {
+        match self.subscriptions.get(topic) {
+            // Subscribers of any type share whatever subscription there is
+            Some(subscription) if md5sum == WILDCARD => Ok(subscription.get_receiver()),
+            Some(subscription) => {
+                check_topic_type(
+                    topic,
+                    (
+                        subscription.topic_type(),
+                        &subscription.connection_header().md5sum,
+                    ),
+                    (topic_type, md5sum),
+                )?;
+                Ok(subscription.get_receiver())
+            }
+            None => {
+                let options = self.topic_defaults.get(topic);
+                let mut subscription = Subscription::new(
                     &self.node_name,
                     &topic,
                     &topic_type,
-                    queue_size,
+                    self.queue_size(topic, queue_size),
                     msg_definition.to_owned(),
                     md5sum.to_owned(),
-                );
+                    self.bus_connections.clone(),
+                )
+                .socket_options(options.socket_options())
+                .reconnect_policy(self.publisher_reconnect.clone())
+                .xmlrpc_client(self.xmlrpc_client.clone())
+                .topic_endpoints(self.topic_endpoints.clone())
+                .memory_account(self.memory_budgets.account(topic, options.memory_budget))
+                .queue_overflow(options.queue_overflow.unwrap_or_default())
+                .header_validation(options.header_validation.unwrap_or_default())
+                .header_fields(options.header_fields.clone())
+                .peer_errors(self.peer_errors.clone())
+                .tcpros_security(self.tcpros_security.clone());
+                if let Some(max_message_size) = options.max_message_size {
+                    subscription = subscription.max_message_size(max_message_size);
+                }
+                if let Some(timeout) = options.idle_timeout {
+                    subscription = subscription.idle_timeout(timeout);
+                }
+                if options.udp.unwrap_or(false) {
+                    subscription = subscription.udpros(&self.hostname, self.host_addr);
+                }
+                if cfg!(unix) && options.unix_socket.unwrap_or(false) {
+                    subscription = subscription.unix_socket(&self.hostname);
+                }
+                if options.intra_process.unwrap_or(false) {
+                    subscription = subscription.intra_process(self.client.client_uri());
+                }
+                if options.checksum.unwrap_or(false) {
+                    subscription = subscription.checksum(self.checksum_failures.clone());
+                }
+                // Taken before connecting to publishers, so nothing they send is dropped for want of a receiver
+                let receiver = subscription.get_receiver();
                 let current_publishers = self.client.register_subscriber(topic, topic_type).await?;
+                // Endpoints cached by an earlier subscription of publishers which have since gone are stale
+                self.topic_endpoints
+                    .retain_publishers(topic, &current_publishers);
                 for publisher in current_publishers {
                     if let Err(err) = subscription.add_publisher_source(&publisher).await {
                         log::error!("Unable to create subscriber connection to {publisher} for {topic}: {err}");
                     }
                 }
-                let receiver = subscription.get_receiver();
                 self.subscriptions.insert(topic.to_owned(), subscription);
+                self.connect_intra_process(topic).await;
                 Ok(receiver)
             }
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn register_publisher(
         &mut self,
         topic: String,
         topic_type: &str,
         queue_size: usize,
+        latching: bool,
+        keep_latest: bool,
         msg_definition: String,
         md5sum: String,
-    ) -> Result<mpsc::Sender<Vec<u8>>, Box<dyn std::error::Error>> {
-        let existing_entry = {
-            self.publishers.iter().find_map(|(key, value)| {
-                if key.as_str() == &topic {
-                    if value.topic_type() == topic_type {
-                        Some(Ok(value.get_sender()))
-                    } else {
-                        Some(Err(Box::new(std::io::Error::from(
-                            std::io::ErrorKind::AddrInUse,
-                        ))))
-                    }
-                } else {
-                    None
-                }
-            })
-        };
-
-        if let Some(handle) = existing_entry {
-            Ok(handle?)
+    ) -> Result<PublicationHandle, Box<dyn std::error::Error>> {
+        if let Some(publication) = self.publishers.get(&topic) {
+            check_topic_type(
+                &topic,
+                (publication.topic_type(), publication.md5sum()),
+                (topic_type, &md5sum),
+            )?;
+            Ok(publication.get_handle())
         } else {
+            let options = self.topic_defaults.get(&topic);
             let channel = Publication::new(
                 &self.node_name,
-                false,
                 &topic,
-                self.host_addr,
-                queue_size,
+                topic_type,
+                self.queue_size(&topic, queue_size),
                 &msg_definition,
                 &md5sum,
-                topic_type,
+                PublicationOptions::default()
+                    .latching(options.latched.unwrap_or(latching))
+                    .slow_subscribers(SlowSubscribers {
+                        keep_latest: options.keep_latest.unwrap_or(keep_latest),
+                        send_timeout: options.send_timeout,
+                    })
+                    .socket_options(options.socket_options())
+                    .header_validation(options.header_validation.unwrap_or_default())
+                    .header_fields(options.header_fields.clone())
+                    .bind_addr(self.host_addr)
+                    .bus_connections(self.bus_connections.clone())
+                    .callback_guard(self.callback_guard.clone())
+                    .memory_account(self.memory_budgets.account(&topic, options.memory_budget))
+                    .peer_errors(self.peer_errors.clone())
+                    .tcpros_security(self.tcpros_security.clone()),
             )
             .await
             .map_err(|err| {
                 log::error!("Failed to create publishing channel: {err:?}");
                 err
             })?;
-            let handle = channel.get_sender();
+            let handle = channel.get_handle();
             self.publishers.insert(topic.clone(), channel);
+            self.connect_intra_process(&topic).await;
             let _current_subscribers = self.client.register_publisher(&topic, topic_type).await?;
             Ok(handle)
         }
     }
+
+    // Hands the messages of our publication of `topic` to our subscription of it in-process,
+    // if there are both and the subscription asked for it
+    async fn connect_intra_process(&self, topic: &str) {
+        let (Some(publication), Some(subscription)) =
+            (self.publishers.get(topic), self.subscriptions.get(topic))
+        else {
+            return;
+        };
+        let Some((queue, account)) = subscription.intra_process_queue() else {
+            return;
+        };
+        if !md5sums_match(
+            &subscription.connection_header().md5sum,
+            publication.md5sum(),
+        ) {
+            log::warn!(
+                "Not receiving {topic} in-process, as the node publishes it as {} but subscribes to it as {}",
+                publication.topic_type(),
+                subscription.topic_type()
+            );
+            return;
+        }
+        publication.connect_local(queue, account).await;
+    }
+}
+
+// Advertising or subscribing to a topic the node already has a publication or subscription for shares it,
+// which only works if both use the same message type
+fn check_topic_type(
+    topic: &str,
+    (existing_type, existing_md5sum): (&str, &str),
+    (topic_type, md5sum): (&str, &str),
+) -> Result<(), std::io::Error> {
+    if existing_type == topic_type && existing_md5sum == md5sum {
+        return Ok(());
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::AddrInUse,
+        format!("{topic} is already in use by this node with type {existing_type} ({existing_md5sum}), not {topic_type} ({md5sum})"),
+    ))
+}
+
+/// Reads the remap table in the parameter `param`, which is resolved relative to the node named `node_name`
+async fn load_remap_table(
+    master_uri: &str,
+    remappings: &Remappings,
+    node_name: &str,
+    param: &str,
+) -> Result<Remappings, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(global_name) = Name::new(node_name) else {
+        return Err(Box::new(InvalidNameError(node_name.to_owned())));
+    };
+    let param = remappings.resolver(&global_name).resolve(param)?;
+    // The node isn't serving yet, so the table is read by a client without a URI of its own
+    let master = MasterClient::new(master_uri, "", node_name).await?;
+    match master.get_param(&param).await {
+        Ok(value) => {
+            let table = Remappings::from_param(value)
+                .map_err(|err| format!("Remap table {param} is not valid: {err}"))?;
+            log::debug!("Applying remap table {param}: {table:?}");
+            Ok(table)
+        }
+        Err(_) => {
+            log::debug!("Remap table {param} is not set");
+            Ok(Remappings::new())
+        }
+    }
+}
+
+/// Builder options for creating a [NodeHandle], see [NodeHandle::new_with_options]
+#[derive(Clone, Debug)]
+pub struct NodeHandleOptions {
+    master_uri: String,
+    name: String,
+    anonymous: bool,
+    remappings: Remappings,
+    namespace: Option<String>,
+    bind_host: Option<String>,
+    default_queue_size: Option<usize>,
+    topics: HashMap<String, TopicOptions>,
+    publisher_reconnect: ReconnectPolicy,
+    abort_on_callback_panic: bool,
+    memory_budget: Option<MemoryBudget>,
+    remap_table: Option<String>,
+    stale_connection_reaping: Option<Duration>,
+    runtime: RuntimePlacement,
+    graph_event_capacity: usize,
+    graph_event_dump: Option<PathBuf>,
+    #[cfg(feature = "ros1_tls")]
+    tcpros_tls: Option<TcprosTls>,
+}
+
+impl NodeHandleOptions {
+    /// Options matching [NodeHandle::new], which applies the remappings given on the command line
+    /// and the namespace given by `ROS_NAMESPACE`
+    pub fn new(master_uri: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            master_uri: master_uri.into(),
+            name: name.into(),
+            anonymous: false,
+            remappings: Remappings::from_env_args(),
+            namespace: env_var("ROS_NAMESPACE"),
+            bind_host: None,
+            default_queue_size: None,
+            topics: HashMap::new(),
+            publisher_reconnect: ReconnectPolicy::exponential(
+                PUBLISHER_RECONNECT_INITIAL_DELAY,
+                PUBLISHER_RECONNECT_MAX_DELAY,
+            )
+            .max_attempts(PUBLISHER_RECONNECT_MAX_ATTEMPTS),
+            abort_on_callback_panic: false,
+            memory_budget: None,
+            remap_table: None,
+            stale_connection_reaping: None,
+            runtime: RuntimePlacement::default(),
+            graph_event_capacity: DEFAULT_GRAPH_EVENT_CAPACITY,
+            graph_event_dump: None,
+            #[cfg(feature = "ros1_tls")]
+            tcpros_tls: None,
+        }
+    }
+
+    /// Options like [NodeHandleOptions::new] for the master given by `ROS_MASTER_URI`, or the master on
+    /// localhost if it isn't set, as roscpp and rospy find their master
+    pub fn from_env(name: impl Into<String>) -> Self {
+        let master_uri = env_var("ROS_MASTER_URI").unwrap_or_else(|| DEFAULT_MASTER_URI.to_owned());
+        Self::new(master_uri, name)
+    }
+
+    /// Appends a random suffix to the node's name to make it unique, see [NodeHandle::new_anonymous]
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.anonymous = anonymous;
+        self
+    }
+
+    /// Uses `remappings` instead of those on the command line
+    pub fn remappings(mut self, remappings: Remappings) -> Self {
+        self.remappings = remappings;
+        self
+    }
+
+    /// Reads further remappings from the parameter `param` when the node starts, e.g. `~remappings` or `/remappings`,
+    /// so a deployment can rewire nodes through the parameter server rather than relaunching them with new arguments.
+    /// The parameter holds either a list of remapping arguments, e.g. `["chatter:=/robot/chatter"]`,
+    /// or a dictionary from names to their replacements, e.g. `{chatter: /robot/chatter}`.
+    ///
+    /// The table applies to every name the node resolves, like any other remapping, with remappings given on the command line
+    /// or through [NodeHandleOptions::remappings] taking precedence. The node starts without it if the parameter is not set.
+    pub fn remap_table(mut self, param: impl Into<String>) -> Self {
+        self.remap_table = Some(param.into());
+        self
+    }
+
+    /// Moves the node into `namespace`, replacing any namespace in its name, so relative names are resolved within it.
+    /// Defaults to `ROS_NAMESPACE`, as set by roslaunch for nodes in a namespaced group; use `"/"` for the root namespace.
+    /// A `__ns:=` remapping takes precedence, as in roscpp and rospy.
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// The hostname or IPv4 or IPv6 address the node listens on and advertises to other nodes,
+    /// taking precedence over `ROS_HOSTNAME` and `ROS_IP`.
+    pub fn bind_host(mut self, host: impl Into<String>) -> Self {
+        self.bind_host = Some(host.into());
+        self
+    }
+
+    /// Used in place of the queue size given when advertising or subscribing, for topics without their own queue size
+    pub fn default_queue_size(mut self, queue_size: usize) -> Self {
+        self.default_queue_size = Some(queue_size);
+        self
+    }
+
+    /// Applies `options` to `topic`, which is resolved relative to the node like any other topic name
+    pub fn topic(mut self, topic: impl Into<String>, options: TopicOptions) -> Self {
+        self.topics.insert(topic.into(), options);
+        self
+    }
+
+    /// How subscribers retry connecting to a publisher after failing to connect or losing the connection,
+    /// e.g. while the publisher restarts. Once the policy gives up, the publisher is only connected to again
+    /// when the master next lists it for the topic.
+    /// Defaults to backing off from 100ms to 5s, giving up after 10 failed attempts in a row.
+    pub fn publisher_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.publisher_reconnect = policy;
+        self
+    }
+
+    /// Aborts the process when a user callback panics, once the panic has been logged, rather than
+    /// containing it and carrying on, see [NodeHandle::callback_panics].
+    /// Suits processes restarted by a supervisor, which would rather restart than run with a callback failing.
+    pub fn abort_on_callback_panic(mut self, abort: bool) -> Self {
+        self.abort_on_callback_panic = abort;
+        self
+    }
+
+    /// Checks the node's connections against the master every `period`, closing those to peers the master no longer lists
+    /// for the topic, e.g. a publisher whose publisherUpdate was lost or a subscriber which unregistered without closing its socket.
+    /// Disabled by default, as each check queries the master for the whole system state.
+    pub fn reap_stale_connections(mut self, period: Duration) -> Self {
+        self.stale_connection_reaping = Some(period);
+        self
+    }
+
+    /// Runs the node's networking on the given runtime rather than the one the node is created from,
+    /// see [RuntimePlacement]
+    pub fn runtime(mut self, placement: RuntimePlacement) -> Self {
+        self.runtime = placement;
+        self
+    }
+
+    /// Keeps the last `capacity` of the node's interactions with the master in [NodeHandle::graph_events],
+    /// rather than the last 256. A capacity of 0 disables the log.
+    pub fn graph_event_capacity(mut self, capacity: usize) -> Self {
+        self.graph_event_capacity = capacity;
+        self
+    }
+
+    /// Writes the node's [GraphEventLog] to the file at `path` when the node shuts down,
+    /// so the events leading up to it can be read after the process has exited
+    pub fn graph_event_dump(mut self, path: impl Into<PathBuf>) -> Self {
+        self.graph_event_dump = Some(path.into());
+        self
+    }
+
+    /// Bounds the bytes held across all of the node's publisher queues and subscription buffers,
+    /// on top of any budgets of individual topics, see [MemoryBudgets]. Unbounded by default.
+    pub fn memory_budget(mut self, budget: MemoryBudget) -> Self {
+        self.memory_budget = Some(budget);
+        self
+    }
+
+    /// Wraps the TCPROS connections of the node's publishers and subscribers in TLS, see [TcprosTls].
+    /// Every node the node exchanges messages with must be configured the same way, as roscpp and rospy nodes can't connect.
+    #[cfg(feature = "ros1_tls")]
+    pub fn tcpros_tls(mut self, tls: TcprosTls) -> Self {
+        self.tcpros_tls = Some(tls);
+        self
+    }
 }
 
 /// Represents a handle to an underlying [Node]. NodeHandle's can be freely cloned, moved, copied, etc.
 /// This class provides the user facing API for interacting with ROS.
+///
+/// Each node talks to the master it was created with, independently of `ROS_MASTER_URI` and of any other
+/// nodes in the process, so one process can join several ROS graphs, e.g. to bridge topics between robots:
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// let robot1 = roslibrust::NodeHandle::new("http://robot1:11311", "/bridge").await?;
+/// let robot2 = roslibrust::NodeHandle::new("http://robot2:11311", "/bridge").await?;
+/// let mut status = robot1.subscribe_string("/status", 10).await?;
+/// while let Ok(msg) = status.next().await {
+///     robot2.publish_string("/robot1/status", &msg.data).await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Clone)]
 pub struct NodeHandle {
-    inner: NodeServerHandle,
+    pub(crate) inner: NodeServerHandle,
+    clock: Clock,
+    primitive_publishers: PrimitivePublishers,
 }
 
 impl NodeHandle {
-    // TODO builder, result, better error type
+    // TODO better error type
     /// Creates a new node connect and returns a handle to it
     /// It is idiomatic to call this once per process and treat the created node as singleton.
     /// The returned handle can be freely clone'd to create additional handles without creating additional connections.
+    /// Remapping arguments on the command line, e.g. those given by a launch file, are applied, see [Remappings],
+    /// and the node is placed in the namespace given by `ROS_NAMESPACE` if it is set.
     pub async fn new(
         master_uri: &str,
         name: &str,
     ) -> Result<NodeHandle, Box<dyn std::error::Error + Send + Sync>> {
-        // Follow ROS rules and determine our IP and hostname
-        let (addr, hostname) = determine_addr().await?;
+        Self::new_with_options(NodeHandleOptions::new(master_uri, name)).await
+    }
 
-        let node = Node::new(master_uri, &hostname, name, addr).await?;
-        let nh = NodeHandle { inner: node };
+    /// Creates a new node like [NodeHandle::new], using `remappings` instead of those on the command line.
+    pub async fn new_with_remappings(
+        master_uri: &str,
+        name: &str,
+        remappings: Remappings,
+    ) -> Result<NodeHandle, Box<dyn std::error::Error + Send + Sync>> {
+        Self::new_with_options(NodeHandleOptions::new(master_uri, name).remappings(remappings))
+            .await
+    }
+
+    /// Creates a new node configured by `options`, see [NodeHandleOptions].
+    pub async fn new_with_options(
+        options: NodeHandleOptions,
+    ) -> Result<NodeHandle, Box<dyn std::error::Error + Send + Sync>> {
+        let (handle, runtime) = options.runtime.start(&options.name)?;
+        match handle {
+            // Created on the chosen runtime, so every task the node spawns runs there too
+            Some(handle) => handle.spawn(Self::start(options, runtime)).await?,
+            None => Self::start(options, runtime).await,
+        }
+    }
+
+    async fn start(
+        mut options: NodeHandleOptions,
+        runtime: Option<DedicatedRuntime>,
+    ) -> Result<NodeHandle, Box<dyn std::error::Error + Send + Sync>> {
+        // Follow ROS rules and determine our IP and hostname, unless told which host to use
+        let (addr, hostname) = match &options.bind_host {
+            Some(host) => host_addr(host).await?,
+            None => determine_addr(&options.master_uri).await?,
+        };
+
+        let name = match options.anonymous {
+            true => anonymous_name(&options.name),
+            false => options.name.clone(),
+        };
+        // Like roscpp, nodes always use the global form of their name
+        let name = options
+            .remappings
+            .clone()
+            .default_namespace(options.namespace.clone())
+            .apply_to_node_name(&name);
+        let name = format!("/{}", name.trim_start_matches('/'));
+        if let Some(param) = &options.remap_table {
+            let table =
+                load_remap_table(&options.master_uri, &options.remappings, &name, param).await?;
+            options.remappings = options.remappings.clone().with_table(table);
+        }
+        let node = Node::new(&hostname, &name, addr, &options, runtime).await?;
+        let clock = Clock::new(&node).await?;
+        let nh = NodeHandle {
+            inner: node,
+            clock,
+            primitive_publishers: PrimitivePublishers::default(),
+        };
 
         Ok(nh)
     }
 
+    /// Creates a new node like [NodeHandle::new], with a random suffix appended to `name` to make it unique,
+    /// equivalent to rospy's `anonymous=True`.
+    /// Useful for tools where many instances may run at once, which would otherwise shut each other down.
+    /// The generated name can be retrieved with [NodeHandle::get_node_name].
+    pub async fn new_anonymous(
+        master_uri: &str,
+        name: &str,
+    ) -> Result<NodeHandle, Box<dyn std::error::Error + Send + Sync>> {
+        Self::new_with_options(NodeHandleOptions::new(master_uri, name).anonymous(true)).await
+    }
+
     pub fn is_ok(&self) -> bool {
-        !self.inner.node_server_sender.is_closed()
+        !self.inner.is_shut_down()
+    }
+
+    /// Shuts the node down, unregistering all of its publishers, subscribers and parameter subscriptions
+    /// from the master and closing its connections. Returns once the shutdown has finished.
+    ///
+    /// Afterwards every operation on the node fails with a [NodeShutdownError], including those still in progress,
+    /// and subscribers receive the error once they have drained any messages already received.
+    /// Dropping every handle to the node also shuts it down, but without a way to wait for it to finish,
+    /// so the master may not be told before the process exits.
+    pub async fn shutdown(&self) {
+        self.inner.shutdown_and_wait().await
+    }
+
+    /// Shuts the node down like [NodeHandle::shutdown], failing with [std::io::ErrorKind::TimedOut]
+    /// if it takes longer than `timeout`, e.g. because the master is unreachable.
+    /// The shutdown carries on in the background after a timeout, as it would once every handle is dropped.
+    pub async fn close(
+        &self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        tokio::time::timeout(timeout, self.shutdown())
+            .await
+            .map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "Timed out shutting down the node",
+                )
+                .into()
+            })
+    }
+
+    /// Resolves once the node has shut down, whether through [NodeHandle::shutdown] or externally,
+    /// returning why it shut down.
+    /// Applications should watch for [NodeShutdownError::SupersededByNewNode] and exit,
+    /// as the node can no longer communicate once another node has taken its name:
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/my_node").await?;
+    /// let reason = nh.wait_for_shutdown().await;
+    /// log::error!("Exiting: {reason}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_for_shutdown(&self) -> NodeShutdownError {
+        self.inner.wait_for_shutdown().await
+    }
+
+    /// Runs until the process receives Ctrl-C (SIGINT) or SIGTERM, or the node shuts down for another reason,
+    /// like roscpp's `ros::spin`. The node is then shut down, unregistering it from the master and cancelling
+    /// the tasks spawned with [NodeHandle::spawn], and the reason it shut down is returned.
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// # roslibrust_codegen_macro::find_and_generate_ros_messages!("assets/ros1_common_interfaces/std_msgs");
+    /// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/listener").await?;
+    /// let mut subscriber = nh.subscribe::<std_msgs::String>("/chatter", 1).await?;
+    /// nh.spawn(async move {
+    ///     while let Ok(msg) = subscriber.next().await {
+    ///         log::info!("Heard {}", msg.data);
+    ///     }
+    /// });
+    /// let reason = nh.spin().await;
+    /// log::info!("Exiting: {reason}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn spin(&self) -> NodeShutdownError {
+        self.spin_until(std::future::pending()).await
+    }
+
+    /// Spins like [NodeHandle::spin], also shutting the node down once `stop` resolves,
+    /// e.g. on a cancellation token of the application
+    pub async fn spin_until(&self, stop: impl Future<Output = ()>) -> NodeShutdownError {
+        tokio::select! {
+            _ = shutdown_signal() => log::info!("Received a shutdown signal, shutting down node"),
+            _ = stop => {}
+            _ = self.inner.shut_down() => {}
+        }
+        self.shutdown().await;
+        self.inner.shutdown_reason().error()
+    }
+
+    /// Spawns `future` as a task which is cancelled when the node shuts down,
+    /// e.g. for the loops handling the messages of a node's subscribers, see [NodeHandle::spin].
+    /// Like [tokio::spawn] it panics if not called within a runtime.
+    #[track_caller]
+    pub fn spawn<F>(&self, future: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let shut_down = self.inner.shut_down();
+        crate::tasks::spawn("node task", async move {
+            tokio::select! {
+                _ = future => {}
+                _ = shut_down => {}
+            }
+        })
     }
 
     pub async fn get_client_uri(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
         self.inner.get_client_uri().await
     }
 
+    /// The URI of the master the node was created with
+    pub async fn get_master_uri(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.get_master_uri().await
+    }
+
+    /// The name the node registered with the master under
+    pub async fn get_node_name(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.get_node_name().await
+    }
+
+    /// A [MasterClient] for the node's master, identified as the node.
+    /// Graph queries can also be made on the node directly, see [crate::GraphQuery].
+    pub async fn master_client(
+        &self,
+    ) -> Result<MasterClient, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(MasterClient::new(
+            self.get_master_uri().await?,
+            self.get_client_uri().await?,
+            self.get_node_name().await?,
+        )
+        .await?)
+    }
+
+    /// Resolves a topic, service or parameter name the same way roscpp's `resolveName` does.
+    /// Global names (`/a/b`) are kept, relative names (`a/b`) are placed in the node's namespace and
+    /// private names (`~a/b`) beneath the node's name, then the node's remappings are applied.
+    /// E.g. for the node `/ns/talker` the names `chatter` and `~rate` resolve to `/ns/chatter` and `/ns/talker/rate`.
+    ///
+    /// Every name given to the node, e.g. when advertising, subscribing or reading parameters, is resolved this way.
+    pub fn resolve_name(&self, name: &str) -> Result<String, InvalidNameError> {
+        self.inner.resolve_name(name)
+    }
+
+    /// The clock used by this node, which follows simulated time if `/use_sim_time` was set when the node was created.
+    /// Use it instead of the system time for stamping messages and scheduling work.
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Creates a [Timer] which runs `callback` every `period` according to the node's [clock](NodeHandle::clock),
+    /// so it follows simulated time when the node does. The callback first runs one period from now.
+    ///
+    /// Callbacks are run one at a time on their own task, a panicking callback is reported through
+    /// [NodeHandle::callback_panics] and the timer carries on.
+    ///
+    /// ```no_run
+    /// # async fn run(nh: roslibrust::NodeHandle) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// # roslibrust_codegen_macro::find_and_generate_ros_messages!("assets/ros1_common_interfaces/std_msgs");
+    /// let publisher = std::sync::Arc::new(nh.advertise::<std_msgs::String>("/chatter", 1).await?);
+    /// let timer = nh.create_timer(std::time::Duration::from_millis(100), move |event| {
+    ///     let publisher = publisher.clone();
+    ///     async move {
+    ///         let msg = std_msgs::String { data: format!("tick at {:?}", event.scheduled) };
+    ///         if let Err(err) = publisher.publish(&msg).await {
+    ///             log::warn!("Failed to publish: {err}");
+    ///         }
+    ///     }
+    /// });
+    /// // The timer stops when dropped
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This panics if `period` is zero.
+    pub fn create_timer<F, Fut>(&self, period: Duration, callback: F) -> Timer
+    where
+        F: FnMut(TimerEvent) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Timer::new(
+            self.clock.clone(),
+            period,
+            self.inner.callback_guard.clone(),
+            callback,
+        )
+    }
+
+    /// The registry of options applied to topics as they are advertised or subscribed to, see [TopicDefaults].
+    pub fn topic_defaults(&self) -> &TopicDefaults {
+        &self.inner.topic_defaults
+    }
+
+    /// The cache of the endpoints publishers gave this node for their topics, see [TopicEndpoints].
+    pub fn topic_endpoints(&self) -> &TopicEndpoints {
+        &self.inner.topic_endpoints
+    }
+
+    /// The accounting of the bytes held in the node's publisher queues and subscription buffers, see [MemoryBudgets].
+    pub fn memory_budgets(&self) -> &MemoryBudgets {
+        &self.inner.memory_budgets
+    }
+
+    /// The counts of messages the node's subscriptions dropped because their checksum did not match,
+    /// see [ChecksumFailures] and [TopicOptions::checksum].
+    pub fn checksum_failures(&self) -> &ChecksumFailures {
+        &self.inner.checksum_failures
+    }
+
+    /// The node's recent registrations, lookups and updates exchanged with the master, see [GraphEventLog]
+    pub fn graph_events(&self) -> &GraphEventLog {
+        &self.inner.graph_events
+    }
+
+    /// Receives the panics of the node's user callbacks, i.e. the handlers of [NodeHandle::subscribe_parallel],
+    /// the callbacks of [Publisher::on_subscriber_connect] and those of timers made with [NodeHandle::create_timer].
+    /// A panicking callback is logged and reported here while the node carries on, the message it was handling
+    /// is dropped. Set [NodeHandleOptions::abort_on_callback_panic] to abort the process instead.
+    /// Only panics after this is called are received.
+    pub fn callback_panics(&self) -> broadcast::Receiver<CallbackPanic> {
+        self.inner.callback_guard.subscribe()
+    }
+
+    /// Receives the protocol errors of the node's exchanges with other nodes, e.g. a publisher refusing a connection
+    /// through the `error` field of its handshake, a connection header the topic's [HeaderValidation](super::HeaderValidation)
+    /// rejects, a malformed header or an xmlrpc fault from a peer's `requestTopic`.
+    /// The errors are also logged, but where the node carries on they are otherwise only seen in the logs.
+    /// Only errors reported after this is called are received.
+    pub fn peer_errors(&self) -> broadcast::Receiver<PeerError> {
+        self.inner.peer_errors.subscribe()
+    }
+
+    /// Publishes the node's [peer errors](NodeHandle::peer_errors) on `topic` as `rosgraph_msgs/Log` at the ERROR level,
+    /// with the topic or service concerned in `topics`, so monitoring can alert on them.
+    /// Forwarding can be switched to another topic at any time, or stopped by passing `None`. It is off by default.
+    /// ```no_run
+    /// # async fn run(nh: roslibrust::NodeHandle) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// nh.forward_peer_errors(Some("~peer_errors")).await?;
+    /// // ...
+    /// nh.forward_peer_errors(None).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn forward_peer_errors(
+        &self,
+        topic: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(topic) = topic else {
+            self.inner.peer_errors.stop_forwarding();
+            return Ok(());
+        };
+        let publisher = self.advertise(topic, PEER_ERROR_QUEUE_SIZE).await?;
+        // Forwarded like rosout, under the node's global name
+        let node_name = format!("/{}", self.get_node_name().await?.trim_start_matches('/'));
+        self.inner
+            .peer_errors
+            .forward(publisher, node_name, self.clock.clone());
+        Ok(())
+    }
+
     pub async fn advertise<T: roslibrust_codegen::RosMessageType>(
         &self,
         topic_name: &str,
         queue_size: usize,
     ) -> Result<Publisher<T>, Box<dyn std::error::Error + Send + Sync>> {
-        let sender = self
+        let (sender, subscribers) = self
+            .inner
+            .register_publisher::<T>(topic_name, T::ROS_TYPE_NAME, queue_size, false, false)
+            .await?;
+        Ok(Publisher::new(
+            topic_name,
+            sender,
+            subscribers,
+            self.inner.shutdown_reason().clone(),
+        ))
+    }
+
+    /// Advertises `topic_name` like [NodeHandle::advertise], but for state where only the newest value matters,
+    /// e.g. poses. Each subscriber is sent the most recent message once it has taken the last,
+    /// so a subscriber the network falls behind on skips the messages superseded meanwhile
+    /// rather than receiving them late, without holding up the other subscribers.
+    /// Where the topic is already advertised by the node, the mode it was first advertised with is kept.
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/tracker").await?;
+    /// let heading = nh.advertise_keep_latest::<roslibrust::StdMsg<f64>>("/heading").await?;
+    /// for step in 0..1000 {
+    ///     heading.publish(&(step as f64 * 0.01).into()).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn advertise_keep_latest<T: roslibrust_codegen::RosMessageType>(
+        &self,
+        topic_name: &str,
+    ) -> Result<Publisher<T>, Box<dyn std::error::Error + Send + Sync>> {
+        let (sender, subscribers) = self
             .inner
-            .register_publisher::<T>(topic_name, T::ROS_TYPE_NAME, queue_size)
+            .register_publisher::<T>(topic_name, T::ROS_TYPE_NAME, 1, false, true)
             .await?;
-        Ok(Publisher::new(topic_name, sender))
+        Ok(Publisher::new(
+            topic_name,
+            sender,
+            subscribers,
+            self.inner.shutdown_reason().clone(),
+        ))
     }
 
     pub async fn subscribe<T: roslibrust_codegen::RosMessageType>(
@@ -565,57 +2207,767 @@ impl NodeHandle {
             .inner
             .register_subscriber::<T>(topic_name, queue_size)
             .await?;
-        Ok(Subscriber::new(receiver))
+        Ok(Subscriber::new(
+            receiver,
+            self.inner.shutdown_reason().clone(),
+        ))
     }
-}
 
-// TODO at the end of the day I'd like to offer a builder pattern for configuration that allow manual setting of this or "ros idiomatic" behavior - Carter
-/// Following ROS's idiomatic address rules uses ROS_HOSTNAME and ROS_IP to determine the address that server should be hosted at.
-/// Returns both the resolved IpAddress of the host (used for actually opening the socket), and the String "hostname" which should
-/// be used in the URI.
-async fn determine_addr() -> Result<(Ipv4Addr, String), RosMasterError> {
-    // If ROS_IP is set that trumps anything else
-    if let Ok(ip_str) = std::env::var("ROS_IP") {
-        let ip = ip_str.parse().map_err(|e| {
-            RosMasterError::HostIpResolutionFailure(format!(
-                "ROS_IP environment variable did not parse to a valid IpAddr::V4: {e:?}"
-            ))
-        })?;
-        return Ok((ip, ip_str));
+    /// Subscribes to `topic_name` whatever its type, receiving messages still serialized along with the
+    /// connection header of their publisher, like rospy's AnyMsg, e.g. for recording or relaying topics
+    /// without knowing their types in advance.
+    /// The subscription is registered with the master with type `*`, and asks publishers for md5sum `*`.
+    ///
+    /// When the node is already subscribed to the topic with a type its subscription is shared.
+    /// Otherwise the subscription made here asks for any type, so the topic can't then also be subscribed to
+    /// with a type until it is unsubscribed from.
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/relay").await?;
+    /// let mut subscriber = nh.subscribe_any("/chatter", 10).await?;
+    /// let msg = subscriber.next().await?;
+    /// log::info!("Received a {} of {} bytes", msg.publisher.topic_type, msg.data.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe_any(
+        &self,
+        topic_name: &str,
+        queue_size: usize,
+    ) -> Result<AnySubscriber, Box<dyn std::error::Error + Send + Sync>> {
+        let receiver = self
+            .inner
+            .register_any_subscriber(topic_name, queue_size)
+            .await?;
+        Ok(AnySubscriber::new(
+            receiver,
+            self.inner.shutdown_reason().clone(),
+        ))
+    }
+
+    /// Advertises `topic_name` latched for sharing a piece of configuration, see [ConfigTopic].
+    pub async fn advertise_config<T: RosMessageType + PartialEq>(
+        &self,
+        topic_name: &str,
+    ) -> Result<ConfigTopic<T>, Box<dyn std::error::Error + Send + Sync>> {
+        let (sender, subscribers) = self
+            .inner
+            .register_publisher::<T>(topic_name, T::ROS_TYPE_NAME, 1, true, false)
+            .await?;
+        Ok(ConfigTopic::new(Publisher::new(
+            topic_name,
+            sender,
+            subscribers,
+            self.inner.shutdown_reason().clone(),
+        )))
+    }
+
+    /// Follows the configuration published on `topic_name`, see [ConfigSubscriber].
+    pub async fn subscribe_config<T: RosMessageType + PartialEq>(
+        &self,
+        topic_name: &str,
+    ) -> Result<ConfigSubscriber<T>, Box<dyn std::error::Error + Send + Sync>> {
+        let subscriber = self.subscribe::<T>(topic_name, 1).await?;
+        Ok(ConfigSubscriber::new(
+            topic_name,
+            subscriber,
+            self.inner.shutdown_reason().clone(),
+        ))
+    }
+
+    /// Advertises `out_topic` and subscribes to `in_topic` together, for handshakes where messages sent on one
+    /// are answered on the other, see [TopicPair].
+    /// Both use a queue size of 10 unless set otherwise with [TopicOptions::queue_size].
+    pub async fn topic_pair<TOut: RosMessageType, TIn: RosMessageType>(
+        &self,
+        out_topic: &str,
+        in_topic: &str,
+    ) -> Result<TopicPair<TOut, TIn>, Box<dyn std::error::Error + Send + Sync>> {
+        let publisher = self
+            .advertise::<TOut>(out_topic, TOPIC_PAIR_QUEUE_SIZE)
+            .await?;
+        let subscriber = self
+            .subscribe::<TIn>(in_topic, TOPIC_PAIR_QUEUE_SIZE)
+            .await?;
+        Ok(TopicPair::new(in_topic, publisher, subscriber))
+    }
+
+    /// Advertises `/tf_static` latched for broadcasting static transforms, see [StaticTransformBroadcaster].
+    pub async fn advertise_static_transforms(
+        &self,
+    ) -> Result<StaticTransformBroadcaster, Box<dyn std::error::Error + Send + Sync>> {
+        let (sender, subscribers) = self
+            .inner
+            .register_publisher::<TfMessage>(
+                TF_STATIC_TOPIC,
+                TfMessage::ROS_TYPE_NAME,
+                TF_STATIC_QUEUE_SIZE,
+                true,
+                false,
+            )
+            .await?;
+        Ok(StaticTransformBroadcaster::new(Publisher::new(
+            TF_STATIC_TOPIC,
+            sender,
+            subscribers,
+            self.inner.shutdown_reason().clone(),
+        )))
+    }
+
+    /// Follows the static transforms of every publisher on `/tf_static`, see [StaticTransforms].
+    pub async fn subscribe_static_transforms(
+        &self,
+    ) -> Result<StaticTransforms, Box<dyn std::error::Error + Send + Sync>> {
+        let subscriber = self
+            .subscribe::<TfMessage>(TF_STATIC_TOPIC, TF_STATIC_QUEUE_SIZE)
+            .await?;
+        Ok(StaticTransforms::new(
+            subscriber,
+            self.inner.shutdown_reason().clone(),
+        ))
+    }
+
+    /// Publishes `data` on `topic_name` as the std_msgs message wrapping it, e.g. `std_msgs/Float64` for an f64,
+    /// without needing to generate messages or hold on to a publisher, for trivial topics such as debug values.
+    /// See [StdMsg] for the types supported.
+    ///
+    /// The topic is advertised latched on its first publish and stays advertised until the node shuts down,
+    /// so subscribers connecting later, e.g. `rostopic echo`, still receive the latest value.
+    pub async fn publish_primitive<T: Primitive>(
+        &self,
+        topic_name: &str,
+        data: T,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let publisher = self
+            .primitive_publishers
+            .get_or_advertise(&self.resolve_name(topic_name)?, async {
+                let (sender, subscribers) = self
+                    .inner
+                    .register_publisher::<StdMsg<T>>(
+                        topic_name,
+                        StdMsg::<T>::ROS_TYPE_NAME,
+                        PRIMITIVE_QUEUE_SIZE,
+                        true,
+                        false,
+                    )
+                    .await?;
+                Ok(Publisher::new(
+                    topic_name,
+                    sender,
+                    subscribers,
+                    self.inner.shutdown_reason().clone(),
+                ))
+            })
+            .await?;
+        publisher.publish(&StdMsg { data }).await
+    }
+
+    /// Publishes `data` on `topic_name` as a `std_msgs/String`, see [NodeHandle::publish_primitive].
+    pub async fn publish_string(
+        &self,
+        topic_name: &str,
+        data: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.publish_primitive(topic_name, data.to_owned()).await
+    }
+
+    /// Publishes `data` on `topic_name` as a `std_msgs/Bool`, see [NodeHandle::publish_primitive].
+    pub async fn publish_bool(
+        &self,
+        topic_name: &str,
+        data: bool,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.publish_primitive(topic_name, data).await
+    }
+
+    /// Publishes `data` on `topic_name` as a `std_msgs/Int64`, see [NodeHandle::publish_primitive].
+    pub async fn publish_i64(
+        &self,
+        topic_name: &str,
+        data: i64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.publish_primitive(topic_name, data).await
+    }
+
+    /// Publishes `data` on `topic_name` as a `std_msgs/Float64`, see [NodeHandle::publish_primitive].
+    pub async fn publish_f64(
+        &self,
+        topic_name: &str,
+        data: f64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.publish_primitive(topic_name, data).await
+    }
+
+    /// Subscribes to `topic_name` as the std_msgs message wrapping a `T`, e.g. `std_msgs/Float64` for an f64,
+    /// without needing to generate messages. See [StdMsg] for the types supported.
+    pub async fn subscribe_primitive<T: Primitive>(
+        &self,
+        topic_name: &str,
+        queue_size: usize,
+    ) -> Result<Subscriber<StdMsg<T>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.subscribe::<StdMsg<T>>(topic_name, queue_size).await
+    }
+
+    /// Subscribes to the `std_msgs/String`s on `topic_name`, see [NodeHandle::subscribe_primitive].
+    pub async fn subscribe_string(
+        &self,
+        topic_name: &str,
+        queue_size: usize,
+    ) -> Result<Subscriber<StdMsg<String>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.subscribe_primitive(topic_name, queue_size).await
+    }
+
+    /// Subscribes to the `std_msgs/Bool`s on `topic_name`, see [NodeHandle::subscribe_primitive].
+    pub async fn subscribe_bool(
+        &self,
+        topic_name: &str,
+        queue_size: usize,
+    ) -> Result<Subscriber<StdMsg<bool>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.subscribe_primitive(topic_name, queue_size).await
+    }
+
+    /// Subscribes to the `std_msgs/Int64`s on `topic_name`, see [NodeHandle::subscribe_primitive].
+    pub async fn subscribe_i64(
+        &self,
+        topic_name: &str,
+        queue_size: usize,
+    ) -> Result<Subscriber<StdMsg<i64>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.subscribe_primitive(topic_name, queue_size).await
+    }
+
+    /// Subscribes to the `std_msgs/Float64`s on `topic_name`, see [NodeHandle::subscribe_primitive].
+    pub async fn subscribe_f64(
+        &self,
+        topic_name: &str,
+        queue_size: usize,
+    ) -> Result<Subscriber<StdMsg<f64>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.subscribe_primitive(topic_name, queue_size).await
+    }
+
+    /// Subscribes to `topic_name` just long enough to receive a single message, like rospy's `wait_for_message`.
+    /// Waits indefinitely if `timeout` is None, otherwise fails with [std::io::ErrorKind::TimedOut] once it passes.
+    /// The node unsubscribes from the topic afterwards unless other subscribers of it are still alive.
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// # roslibrust_codegen_macro::find_and_generate_ros_messages!("assets/ros1_common_interfaces/std_msgs");
+    /// use std::time::Duration;
+    /// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/listener").await?;
+    /// let msg: std_msgs::String = nh
+    ///     .wait_for_message("/chatter", Some(Duration::from_secs(5)))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_for_message<T: RosMessageType>(
+        &self,
+        topic_name: &str,
+        timeout: Option<Duration>,
+    ) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+        let mut subscriber = self.subscribe::<T>(topic_name, 1).await?;
+        let received: Result<T, Box<dyn std::error::Error + Send + Sync>> = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, subscriber.next()).await {
+                Ok(received) => received.map_err(|err| err.to_string().into()),
+                Err(_) => Err(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("Timed out waiting for a message on {topic_name}"),
+                ))),
+            },
+            None => subscriber
+                .next()
+                .await
+                .map_err(|err| err.to_string().into()),
+        };
+        received
     }
-    // If ROS_HOSTNAME is set that is next highest precedent
-    if let Ok(name) = std::env::var("ROS_HOSTNAME") {
-        let ip = hostname_to_ipv4(&name).await?;
-        return Ok((ip, name));
+
+    /// Subscribes to `topic_name`, running `handler` on each message on one of `n_workers` workers,
+    /// for CPU heavy processing of messages such as inference on images.
+    /// Handlers run on tokio's blocking thread pool so are free to block.
+    ///
+    /// Messages are handled in no particular order, see [NodeHandle::subscribe_parallel_keyed] for keeping
+    /// related messages in order. While every worker is busy up to `n_workers` further messages are queued
+    /// and older messages are dropped, a longer queue can be set with [NodeHandle::topic_defaults].
+    /// A handler which panics only loses the message it was handling, see [NodeHandle::callback_panics].
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// # roslibrust_codegen_macro::find_and_generate_ros_messages!("assets/ros1_common_interfaces/std_msgs");
+    /// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/transcriber").await?;
+    /// let _subscriber = nh
+    ///     .subscribe_parallel("/speech", 4, |msg: std_msgs::String| {
+    ///         // Expensive work on the message
+    ///     })
+    ///     .await?;
+    /// nh.wait_for_shutdown().await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe_parallel<T: RosMessageType>(
+        &self,
+        topic_name: &str,
+        n_workers: usize,
+        handler: impl Fn(T) + Send + Sync + 'static,
+    ) -> Result<ParallelSubscriber, Box<dyn std::error::Error + Send + Sync>> {
+        if n_workers == 0 {
+            return Err("A parallel subscriber needs at least one worker".into());
+        }
+        let receiver = self
+            .inner
+            .register_subscriber::<T>(topic_name, n_workers)
+            .await?;
+        let handler = self
+            .inner
+            .callback_guard
+            .wrap(format!("parallel subscriber of {topic_name}"), handler);
+        Ok(ParallelSubscriber::new(
+            topic_name,
+            receiver,
+            WorkerPool::new(n_workers, handler),
+        ))
+    }
+
+    /// Like [NodeHandle::subscribe_parallel], but messages for which `key` returns the same value are always
+    /// handled by the same worker, so are handled one at a time in the order they were received,
+    /// e.g. to process the messages of each tracked object in order.
+    pub async fn subscribe_parallel_keyed<T: RosMessageType, K: std::hash::Hash>(
+        &self,
+        topic_name: &str,
+        n_workers: usize,
+        key: impl Fn(&T) -> K + Send + Sync + 'static,
+        handler: impl Fn(T) + Send + Sync + 'static,
+    ) -> Result<ParallelSubscriber, Box<dyn std::error::Error + Send + Sync>> {
+        if n_workers == 0 {
+            return Err("A parallel subscriber needs at least one worker".into());
+        }
+        let receiver = self
+            .inner
+            .register_subscriber::<T>(topic_name, n_workers)
+            .await?;
+        let handler = self
+            .inner
+            .callback_guard
+            .wrap(format!("parallel subscriber of {topic_name}"), handler);
+        Ok(ParallelSubscriber::new(
+            topic_name,
+            receiver,
+            WorkerPool::new_keyed(n_workers, key, handler),
+        ))
+    }
+
+    /// Creates a client for calling `service_name` directly over TCPROS.
+    /// If `persistent` is set the connection to the service is kept open and reused between calls.
+    pub fn service_client<T: roslibrust_codegen::RosServiceType>(
+        &self,
+        service_name: &str,
+        persistent: bool,
+    ) -> ServiceClient<T> {
+        ServiceClient::new(self.inner.clone(), service_name, persistent)
+    }
+
+    /// Creates a client for the action server whose topics are beneath `action_namespace`,
+    /// advertising the client's `goal` and `cancel` topics and subscribing to the server's `status`, `feedback` and `result`.
+    pub async fn action_client<A: roslibrust_codegen::RosActionType + 'static>(
+        &self,
+        action_namespace: &str,
+    ) -> Result<ActionClient<A>, Box<dyn std::error::Error + Send + Sync>> {
+        ActionClient::new(self, action_namespace).await
+    }
+
+    /// Creates a client for sending navigation goals to the move_base server beneath `action_namespace`,
+    /// usually `/move_base`, see [MoveBaseClient].
+    pub async fn move_base_client<A: MoveBaseAction>(
+        &self,
+        action_namespace: &str,
+    ) -> Result<MoveBaseClient<A>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(MoveBaseClient::new(
+            self.action_client(action_namespace).await?,
+        ))
+    }
+
+    /// Waits until `service_name` is registered with the master, like roscpp's `ros::service::waitForService`.
+    /// The master is polled with `lookupService`, backing off from every 50ms to every second.
+    /// Waits indefinitely if `timeout` is None, otherwise fails with [std::io::ErrorKind::TimedOut] once it passes.
+    /// Also fails if the node shuts down while waiting.
+    pub async fn wait_for_service(
+        &self,
+        service_name: &str,
+        timeout: Option<Duration>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let service_name = self.resolve_name(service_name)?;
+        let wait = async {
+            let mut delay = WAIT_FOR_SERVICE_INITIAL_DELAY;
+            loop {
+                match self.inner.lookup_service(&service_name).await {
+                    Ok(_) => return Ok(()),
+                    Err(err) if self.inner.is_shut_down() => return Err(err),
+                    Err(_) => {}
+                }
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(WAIT_FOR_SERVICE_MAX_DELAY);
+            }
+        };
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, wait).await.map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("Timed out waiting for service {service_name}"),
+                )
+            })?,
+            None => wait.await,
+        }
+    }
+
+    /// Fetches a parameter from the parameter server and converts it into `T`.
+    /// Like all names given to the node, `key` is resolved with [NodeHandle::resolve_name].
+    /// Any type serde can build from an xmlrpc value works, e.g. `bool`, `i32`, `f64`, `String`,
+    /// `Vec<T>` for lists, and `HashMap<String, T>` or a `Deserialize` struct for dictionaries.
+    /// Returns an error if the parameter is not set or does not match `T`.
+    pub async fn get_param<T: serde::de::DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+        let value = self.inner.get_param(key).await?;
+        Ok(serde_xmlrpc::from_value(value)?)
+    }
+
+    /// Watches a parameter for changes, see [ParamSubscriber].
+    /// Relative and private names are resolved against this node's name.
+    pub async fn subscribe_param<T: serde::de::DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<ParamSubscriber<T>, Box<dyn std::error::Error + Send + Sync>> {
+        let (key, value, receiver) = self.inner.subscribe_param(key).await?;
+        Ok(ParamSubscriber::new(
+            self.inner.clone(),
+            key,
+            value,
+            receiver,
+        ))
+    }
+
+    /// Advertises that this node provides the capability `tag` through `resource`, a service, action or topic,
+    /// so clients can find it with [NodeHandle::find_capability] rather than hardcoding its name.
+    /// `resource` is resolved like any name given to the node, and a node may advertise any number of capabilities.
+    /// Capabilities are advertised on the parameter server beneath [CAPABILITIES_NAMESPACE](super::CAPABILITIES_NAMESPACE),
+    /// and withdrawn when the node shuts down.
+    /// Note: a node which exits without shutting down, e.g. by crashing, leaves its capabilities advertised.
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/planner").await?;
+    /// nh.advertise_capability("grasp_planning", "~plan_grasp").await?;
+    ///
+    /// let client = roslibrust::NodeHandle::new("http://localhost:11311", "/client").await?;
+    /// for provider in client.find_capability("grasp_planning").await? {
+    ///     log::info!("{} plans grasps with {}", provider.node, provider.resource);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn advertise_capability(
+        &self,
+        tag: &str,
+        resource: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner
+            .advertise_capability(self.capability(tag, resource)?)
+            .await
+    }
+
+    /// Stops advertising a capability advertised with [NodeHandle::advertise_capability].
+    /// Withdrawing a capability which isn't advertised does nothing.
+    pub async fn withdraw_capability(
+        &self,
+        tag: &str,
+        resource: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner
+            .withdraw_capability(self.capability(tag, resource)?)
+            .await
+    }
+
+    /// Finds the providers of the capability `tag` advertised by any node, sorted by node.
+    pub async fn find_capability(
+        &self,
+        tag: &str,
+    ) -> Result<Vec<CapabilityProvider>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(capabilities::providers(
+            &self.advertisements().await?,
+            Some(tag),
+        ))
+    }
+
+    /// Every capability advertised by any node, sorted by tag and then node.
+    pub async fn all_capabilities(
+        &self,
+    ) -> Result<Vec<CapabilityProvider>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(capabilities::providers(&self.advertisements().await?, None))
+    }
+
+    /// Watches for nodes starting and stopping to provide the capability `tag`, see [CapabilityWatch].
+    pub async fn watch_capability(
+        &self,
+        tag: &str,
+    ) -> Result<CapabilityWatch, Box<dyn std::error::Error + Send + Sync>> {
+        let subscriber = self
+            .subscribe_param(capabilities::CAPABILITIES_NAMESPACE)
+            .await?;
+        Ok(CapabilityWatch::new(tag, subscriber))
+    }
+
+    fn capability(
+        &self,
+        tag: &str,
+        resource: &str,
+    ) -> Result<Capability, Box<dyn std::error::Error + Send + Sync>> {
+        if tag.is_empty() {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Capability tags must not be empty",
+            )));
+        }
+        Ok(Capability {
+            tag: tag.to_owned(),
+            resource: self.resolve_name(resource)?,
+        })
+    }
+
+    async fn advertisements(
+        &self,
+    ) -> Result<Advertisements, Box<dyn std::error::Error + Send + Sync>> {
+        // The namespace only exists once a node has advertised a capability
+        if !self.has_param(capabilities::CAPABILITIES_NAMESPACE).await? {
+            return Ok(Advertisements::default());
+        }
+        self.get_param(capabilities::CAPABILITIES_NAMESPACE).await
+    }
+
+    /// Sets a parameter on the parameter server, replacing any existing value.
+    /// Note: rosmaster does not accept 64 bit integers, use `i32` for integer parameters.
+    pub async fn set_param<T: serde::Serialize>(
+        &self,
+        key: &str,
+        value: &T,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let value = serde_xmlrpc::to_value(value)?;
+        self.inner.set_param(key, value).await
+    }
+
+    /// Removes a parameter, or a whole namespace of parameters, from the parameter server.
+    pub async fn delete_param(
+        &self,
+        key: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.delete_param(key).await
+    }
+
+    pub async fn has_param(
+        &self,
+        key: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.has_param(key).await
+    }
+
+    /// Searches for `key` starting in this node's namespace and moving up through its parents,
+    /// returning the full name of the closest match or None if nothing was found.
+    pub async fn search_param(
+        &self,
+        key: &str,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.search_param(key).await
+    }
+}
+
+/// Appends a suffix made of the process id and a random number to `name`, following rospy's anonymous names
+fn anonymous_name(name: &str) -> String {
+    // Truncated to keep names a reasonable length, uniqueness only needs to hold among the nodes running at once
+    let random = uuid::Uuid::new_v4().as_u128() as u64;
+    format!("{name}_{}_{random}", std::process::id())
+}
+
+/// Follows ROS's rules for the host a node advertises to other nodes: `ROS_HOSTNAME`, then `ROS_IP`,
+/// then the computer's hostname. A hostname which doesn't resolve can't be reached by other nodes either,
+/// so the address this computer uses to reach the master is advertised instead.
+/// Returns both the address to listen on and the host to advertise in URIs.
+async fn determine_addr(master_uri: &str) -> Result<(IpAddr, String), RosMasterError> {
+    if let Some(host) = host_override(env_var("ROS_HOSTNAME"), env_var("ROS_IP"))? {
+        return Ok((listen_addr(&host), host));
     }
-    // If neither env var is set, use the computers "hostname"
     let name = gethostname::gethostname();
     let name = name.into_string().map_err(|e| {
-            RosMasterError::HostIpResolutionFailure(format!("This host's hostname is a string that cannot be validly converted into a Rust type, and therefore we cannot convert it into an IpAddrv4: {e:?}"))
+            RosMasterError::HostIpResolutionFailure(format!("This host's hostname is a string that cannot be validly converted into a Rust type, and therefore we cannot convert it into an IpAddr: {e:?}"))
         })?;
-    let ip = hostname_to_ipv4(&name).await?;
-    return Ok((ip, name));
+    match resolve_host(&name).await {
+        Ok(_) => Ok((listen_addr(&name), name)),
+        Err(err) => {
+            let ip = local_addr_towards(master_uri).await?;
+            log::warn!("{err}, advertising {ip} instead. Set ROS_HOSTNAME or ROS_IP to choose the address other nodes reach this node at");
+            Ok((listen_addr(&ip.to_string()), ip.to_string()))
+        }
+    }
+}
+
+/// The host given by `ROS_HOSTNAME` or `ROS_IP`, which are mutually exclusive. Like roscpp and rospy,
+/// `ROS_HOSTNAME` takes precedence when both are set.
+fn host_override(
+    ros_hostname: Option<String>,
+    ros_ip: Option<String>,
+) -> Result<Option<String>, RosMasterError> {
+    if ros_hostname.is_some() {
+        return Ok(ros_hostname);
+    }
+    match ros_ip {
+        Some(ip) => match ip.parse::<IpAddr>() {
+            Ok(_) => Ok(Some(ip)),
+            Err(e) => Err(RosMasterError::HostIpResolutionFailure(format!(
+                "ROS_IP environment variable did not parse to a valid IPv4 or IPv6 address: {e:?}"
+            ))),
+        },
+        None => Ok(None),
+    }
+}
+
+// Resolves once the process receives Ctrl-C, or on unix SIGTERM as sent by roslaunch and systemd to stop a process.
+// Never resolves if the signal handlers can't be installed.
+async fn shutdown_signal() {
+    let interrupt = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            log::warn!("Failed to listen for Ctrl-C: {err}");
+            std::future::pending::<()>().await
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(err) => {
+                log::warn!("Failed to listen for SIGTERM: {err}");
+                std::future::pending::<()>().await
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => {}
+        _ = terminate => {}
+    }
+}
+
+// Empty values are treated as unset, as container setups often pass the variables through empty
+fn env_var(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|value| !value.is_empty())
+}
+
+/// Nodes listen on every interface, over both IPv4 and IPv6, so they can be reached through whichever address
+/// they advertise, including addresses mapped into a container, except nodes advertising a loopback address
+/// which stay local.
+fn listen_addr(host: &str) -> IpAddr {
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(ip)) if ip.is_loopback() => Ipv6Addr::LOCALHOST.into(),
+        Ok(ip) if ip.is_loopback() => Ipv4Addr::LOCALHOST.into(),
+        _ if host == "localhost" => Ipv4Addr::LOCALHOST.into(),
+        _ => addrs::UNSPECIFIED,
+    }
+}
+
+/// The address of this computer on the route to the master at `master_uri`.
+/// Connecting a UDP socket only picks the route, nothing is sent.
+async fn local_addr_towards(master_uri: &str) -> Result<IpAddr, RosMasterError> {
+    let failure = |e: &dyn std::fmt::Display| {
+        RosMasterError::HostIpResolutionFailure(format!(
+            "Failed to find this computer's address on the route to the master at {master_uri}: {e}"
+        ))
+    };
+    let authority = master_uri
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    let master_addr = tokio::net::lookup_host(authority)
+        .await
+        .map_err(|e| failure(&e))?
+        .next()
+        .ok_or_else(|| failure(&"the master's host has no address"))?;
+    let unspecified = match master_addr {
+        SocketAddr::V4(_) => IpAddr::from(Ipv4Addr::UNSPECIFIED),
+        SocketAddr::V6(_) => IpAddr::from(Ipv6Addr::UNSPECIFIED),
+    };
+    let socket = tokio::net::UdpSocket::bind((unspecified, 0))
+        .await
+        .map_err(|e| failure(&e))?;
+    socket.connect(master_addr).await.map_err(|e| failure(&e))?;
+    Ok(socket.local_addr().map_err(|e| failure(&e))?.ip())
+}
+
+/// Resolves a host given explicitly, e.g. by [NodeHandleOptions::bind_host], which may be an IPv4 or IPv6 address
+/// or a hostname.
+async fn host_addr(host: &str) -> Result<(IpAddr, String), RosMasterError> {
+    match host.parse() {
+        Ok(ip) => Ok((ip, host.to_owned())),
+        Err(_) => Ok((resolve_host(host).await?, host.to_owned())),
+    }
 }
 
 /// Given a the name of a host use's std::net::ToSocketAddrs to perform a DNS lookup and return the resulting IP address.
 /// This function is intended to be used to determine the correct IP host the socket for the xmlrpc server on.
-async fn hostname_to_ipv4(name: &str) -> Result<Ipv4Addr, RosMasterError> {
-    let name_with_port = &format!("{name}:0");
-    let mut i = tokio::net::lookup_host(name_with_port).await.map_err(|e| {
+async fn resolve_host(name: &str) -> Result<IpAddr, RosMasterError> {
+    let mut i = tokio::net::lookup_host((name, 0)).await.map_err(|e| {
         RosMasterError::HostIpResolutionFailure(format!(
             "Failure while attempting to lookup ROS_HOSTNAME: {e:?}"
         ))
     })?;
-    if let Some(addr) = i.next() {
-        match addr.ip() {
-                IpAddr::V4(ip) => Ok(ip),
-                IpAddr::V6(ip) => {
-                    Err(RosMasterError::HostIpResolutionFailure(format!("ROS_HOSTNAME resolved to an IPv6 address which is not support by ROS/roslibrust: {ip:?}")))
-                }
-            }
-    } else {
-        Err(RosMasterError::HostIpResolutionFailure(format!(
+    match i.next() {
+        Some(addr) => Ok(addr.ip()),
+        None => Err(RosMasterError::HostIpResolutionFailure(format!(
             "ROS_HOSTNAME did not resolve any address: {name:?}"
-        )))
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{addrs, host_override, listen_addr, local_addr_towards};
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn ros_hostname_takes_precedence_over_ros_ip() {
+        let host = |hostname: Option<&str>, ip: Option<&str>| {
+            host_override(hostname.map(str::to_owned), ip.map(str::to_owned))
+        };
+        assert_eq!(
+            host(Some("robot.local"), Some("10.0.0.2")).unwrap(),
+            Some("robot.local".to_owned())
+        );
+        assert_eq!(
+            host(None, Some("10.0.0.2")).unwrap(),
+            Some("10.0.0.2".to_owned())
+        );
+        assert_eq!(host(None, None).unwrap(), None);
+        assert!(host(None, Some("robot.local")).is_err());
+        assert_eq!(
+            host(None, Some("fd00::2")).unwrap(),
+            Some("fd00::2".to_owned())
+        );
+
+        assert_eq!(listen_addr("localhost"), IpAddr::from(Ipv4Addr::LOCALHOST));
+        assert_eq!(listen_addr("127.0.1.1"), IpAddr::from(Ipv4Addr::LOCALHOST));
+        assert_eq!(listen_addr("::1"), IpAddr::from(Ipv6Addr::LOCALHOST));
+        assert_eq!(listen_addr("robot.local"), addrs::UNSPECIFIED);
+        assert_eq!(listen_addr("10.0.0.2"), addrs::UNSPECIFIED);
+        assert_eq!(listen_addr("fd00::2"), addrs::UNSPECIFIED);
+    }
+
+    #[tokio::test]
+    async fn fallback_address_routes_to_the_master() {
+        assert_eq!(
+            local_addr_towards("http://127.0.0.1:11311/").await.unwrap(),
+            Ipv4Addr::LOCALHOST
+        );
     }
 }
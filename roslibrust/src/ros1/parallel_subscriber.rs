@@ -0,0 +1,214 @@
+//! This module contains the ParallelSubscriber which spreads the messages of a topic across a pool of workers,
+//! created with [super::NodeHandle::subscribe_parallel] and [super::NodeHandle::subscribe_parallel_keyed].
+
+use super::subscription_queue::QueueReceiver;
+use abort_on_drop::ChildTask;
+use roslibrust_codegen::RosMessageType;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+use tokio::sync::{broadcast, mpsc, Mutex};
+
+type Handler<T> = Arc<dyn Fn(T) + Send + Sync>;
+type KeyFn<T> = Box<dyn Fn(&T) -> u64 + Send + Sync>;
+
+/// A subscription whose messages are handled by a pool of workers.
+/// Messages are received for as long as this is kept, dropping it unsubscribes and stops the workers,
+/// although handlers which are already running are left to finish.
+pub struct ParallelSubscriber {
+    _dispatch_task: ChildTask<()>,
+}
+
+impl ParallelSubscriber {
+    pub(crate) fn new<T: RosMessageType>(
+        topic_name: &str,
+        receiver: QueueReceiver,
+        pool: WorkerPool<T>,
+    ) -> Self {
+        let dispatch_task = crate::tasks::spawn(
+            &format!("parallel subscriber {topic_name}"),
+            Self::dispatch(topic_name.to_owned(), receiver, pool),
+        );
+        Self {
+            _dispatch_task: dispatch_task.into(),
+        }
+    }
+
+    async fn dispatch<T: RosMessageType>(
+        topic_name: String,
+        mut receiver: QueueReceiver,
+        pool: WorkerPool<T>,
+    ) {
+        loop {
+            match receiver.recv().await {
+                Ok(message) => match message.decode::<T>() {
+                    Ok(msg) => {
+                        if !pool.dispatch(msg).await {
+                            break;
+                        }
+                    }
+                    Err(err) => log::warn!("Failed to deserialize message on {topic_name}: {err}"),
+                },
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Workers for {topic_name} fell behind, dropped {skipped} messages");
+                }
+                // The subscription is only dropped when the node shuts down
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+/// Workers running a handler on messages handed to them by [WorkerPool::dispatch]
+pub(crate) struct WorkerPool<T> {
+    // A single queue shared by every worker, unless messages are keyed in which case each worker has its own
+    queues: Vec<mpsc::Sender<T>>,
+    key: Option<KeyFn<T>>,
+    _workers: Vec<ChildTask<()>>,
+}
+
+impl<T: Send + 'static> WorkerPool<T> {
+    /// Each message is handled by whichever worker is free first
+    pub fn new(n_workers: usize, handler: impl Fn(T) + Send + Sync + 'static) -> Self {
+        let handler: Handler<T> = Arc::new(handler);
+        let (sender, receiver) = mpsc::channel(n_workers);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..n_workers)
+            .map(|index| {
+                crate::tasks::spawn(
+                    &format!("parallel subscriber worker {index}"),
+                    Self::work(receiver.clone(), handler.clone()),
+                )
+                .into()
+            })
+            .collect();
+        Self {
+            queues: vec![sender],
+            key: None,
+            _workers: workers,
+        }
+    }
+
+    /// Messages with the same key are always handled by the same worker, so are handled in the order received
+    pub fn new_keyed<K: Hash>(
+        n_workers: usize,
+        key: impl Fn(&T) -> K + Send + Sync + 'static,
+        handler: impl Fn(T) + Send + Sync + 'static,
+    ) -> Self {
+        let handler: Handler<T> = Arc::new(handler);
+        let (queues, workers) = (0..n_workers)
+            .map(|index| {
+                let (sender, receiver) = mpsc::channel(1);
+                let worker = crate::tasks::spawn(
+                    &format!("parallel subscriber worker {index}"),
+                    Self::work(Arc::new(Mutex::new(receiver)), handler.clone()),
+                );
+                (sender, ChildTask::from(worker))
+            })
+            .unzip();
+        Self {
+            queues,
+            key: Some(Box::new(move |msg| {
+                let mut hasher = DefaultHasher::new();
+                key(msg).hash(&mut hasher);
+                hasher.finish()
+            })),
+            _workers: workers,
+        }
+    }
+
+    /// Waits for room in the queue of a worker and hands it `msg`, returning false if the workers have stopped
+    pub async fn dispatch(&self, msg: T) -> bool {
+        let index = match &self.key {
+            Some(key) => (key(&msg) % self.queues.len() as u64) as usize,
+            None => 0,
+        };
+        self.queues[index].send(msg).await.is_ok()
+    }
+
+    async fn work(queue: Arc<Mutex<mpsc::Receiver<T>>>, handler: Handler<T>) {
+        loop {
+            let Some(msg) = queue.lock().await.recv().await else {
+                break;
+            };
+            let handler = handler.clone();
+            // Handlers are expected to be CPU heavy, so are run where they can block without stalling the runtime
+            if let Err(err) =
+                crate::tasks::spawn_blocking("parallel subscriber handler", move || handler(msg))
+                    .await
+            {
+                log::error!("Handler of a parallel subscriber failed: {err}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::WorkerPool;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Barrier},
+        time::Duration,
+    };
+    use tokio::{sync::mpsc, time::timeout};
+
+    #[tokio::test]
+    async fn workers_run_in_parallel() {
+        let n_workers = 3;
+        // Only opens once every worker is handling a message at the same time
+        let barrier = Arc::new(Barrier::new(n_workers));
+        let (sender, mut done) = mpsc::unbounded_channel();
+        let pool = WorkerPool::new(n_workers, move |msg: u32| {
+            barrier.wait();
+            sender.send(msg).unwrap();
+        });
+
+        for msg in 0..n_workers as u32 {
+            assert!(pool.dispatch(msg).await);
+        }
+        let mut handled = vec![];
+        for _ in 0..n_workers {
+            let msg = timeout(Duration::from_secs(5), done.recv())
+                .await
+                .expect("Messages should be handled concurrently");
+            handled.push(msg.unwrap());
+        }
+        handled.sort();
+        assert_eq!(handled, vec![0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn keyed_messages_keep_their_order() {
+        let (sender, mut done) = mpsc::unbounded_channel();
+        let pool = WorkerPool::new_keyed(
+            4,
+            |(key, _seq): &(u32, u32)| *key,
+            move |(key, seq): (u32, u32)| {
+                // Vary the time taken so unordered handling would show up
+                std::thread::sleep(Duration::from_millis(u64::from(seq % 3)));
+                sender.send((key, seq)).unwrap();
+            },
+        );
+
+        for seq in 0..30 {
+            assert!(pool.dispatch((seq % 5, seq)).await);
+        }
+        let mut last_seq = HashMap::new();
+        for _ in 0..30 {
+            let (key, seq) = timeout(Duration::from_secs(5), done.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            if let Some(last) = last_seq.insert(key, seq) {
+                assert!(
+                    last < seq,
+                    "Message {seq} of key {key} handled after {last}"
+                );
+            }
+        }
+        assert_eq!(last_seq.len(), 5);
+    }
+}
@@ -0,0 +1,146 @@
+//! This module contains the ParamSubscriber which watches a parameter on the parameter server for changes.
+
+use super::node::NodeServerHandle;
+use serde::de::DeserializeOwned;
+use serde_xmlrpc::Value;
+use std::marker::PhantomData;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Parameter changes are rare, so only a small backlog is kept for slow readers
+const PARAM_UPDATE_QUEUE_SIZE: usize = 10;
+
+/// Receives the changed key and its new value for each update of a watched parameter
+pub(crate) type ParamUpdateReceiver = broadcast::Receiver<(String, Value)>;
+
+/// Watches a single parameter, created with [super::NodeHandle::subscribe_param].
+///
+/// The first value received is the parameter's value at the time of subscribing, followed by
+/// one value each time the master reports the parameter, or anything beneath it, has changed.
+/// A value of `None` means the parameter is not set, e.g. because it was deleted.
+/// Note: rosmaster reports deleted parameters as an empty dictionary, so setting a parameter to an empty
+/// dictionary is also received as `None`.
+///
+/// The node stops watching the parameter once every subscriber to it has been dropped.
+pub struct ParamSubscriber<T> {
+    node: NodeServerHandle,
+    key: String,
+    // The value returned by the master when subscribing, handed out by the first call to next
+    initial_value: Option<Value>,
+    receiver: ParamUpdateReceiver,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> ParamSubscriber<T> {
+    pub(crate) fn new(
+        node: NodeServerHandle,
+        key: String,
+        initial_value: Value,
+        receiver: ParamUpdateReceiver,
+    ) -> Self {
+        Self {
+            node,
+            key,
+            initial_value: Some(initial_value),
+            receiver,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The fully resolved name of the parameter being watched
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Waits for the next value of the parameter.
+    /// An error is returned if the value cannot be converted to `T` or the node has shut down.
+    pub async fn next(&mut self) -> Result<Option<T>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.recv().await {
+            Some(value) => value,
+            None => Err("Node shut down while watching parameter".into()),
+        }
+    }
+
+    /// Converts the subscriber into a [futures::Stream] of parameter values which ends when the node shuts down.
+    pub fn into_stream(
+        self,
+    ) -> impl futures::Stream<Item = Result<Option<T>, Box<dyn std::error::Error + Send + Sync>>>
+    {
+        futures::stream::unfold(self, |mut subscriber| async move {
+            let value = subscriber.recv().await?;
+            Some((value, subscriber))
+        })
+    }
+
+    // Returns None once the node has shut down
+    async fn recv(
+        &mut self,
+    ) -> Option<Result<Option<T>, Box<dyn std::error::Error + Send + Sync>>> {
+        if let Some(value) = self.initial_value.take() {
+            return Some(Self::convert(value));
+        }
+        loop {
+            match self.receiver.recv().await {
+                Ok((key, value)) if key == self.key => return Some(Self::convert(value)),
+                // Only part of the parameter changed, so the update only holds that part
+                Ok(_) => {
+                    return Some(match self.node.get_param(&self.key).await {
+                        Ok(value) => Self::convert(value),
+                        Err(err) => Err(err),
+                    })
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    // Older values are superseded by the ones still queued
+                    log::debug!("Skipped {skipped} updates of parameter {}", self.key);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    fn convert(value: Value) -> Result<Option<T>, Box<dyn std::error::Error + Send + Sync>> {
+        match value {
+            Value::Struct(members) if members.is_empty() => Ok(None),
+            value => Ok(Some(serde_xmlrpc::from_value(value)?)),
+        }
+    }
+}
+
+impl<T> Drop for ParamSubscriber<T> {
+    fn drop(&mut self) {
+        if let Err(err) = self.node.unsubscribe_param(self.key.clone()) {
+            log::debug!("Unable to unsubscribe from parameter {}: {err}", self.key);
+        }
+    }
+}
+
+/// The node's record of a watched parameter, shared by all of its [ParamSubscriber]s
+pub(crate) struct ParamSubscription {
+    sender: broadcast::Sender<(String, Value)>,
+    subscriber_count: usize,
+}
+
+impl ParamSubscription {
+    pub fn new() -> Self {
+        let (sender, _receiver) = broadcast::channel(PARAM_UPDATE_QUEUE_SIZE);
+        Self {
+            sender,
+            subscriber_count: 0,
+        }
+    }
+
+    pub fn add_subscriber(&mut self) -> ParamUpdateReceiver {
+        self.subscriber_count += 1;
+        self.sender.subscribe()
+    }
+
+    /// Returns the number of subscribers remaining
+    pub fn remove_subscriber(&mut self) -> usize {
+        self.subscriber_count = self.subscriber_count.saturating_sub(1);
+        self.subscriber_count
+    }
+
+    pub fn update(&self, key: &str, value: Value) {
+        // Sending only fails if no subscribers are listening, which is fine
+        let _ = self.sender.send((key.to_owned(), value));
+    }
+}
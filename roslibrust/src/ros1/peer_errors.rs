@@ -0,0 +1,177 @@
+//! This module contains the reporting of protocol errors in the node's exchanges with other nodes,
+//! see [super::NodeHandle::peer_errors] and [super::NodeHandle::forward_peer_errors].
+
+use super::{
+    publisher::Publisher,
+    rosout::{Header, Log},
+    Clock,
+};
+use abort_on_drop::ChildTask;
+use std::{
+    fmt::Display,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+use tokio::sync::broadcast;
+
+// Only the most recent errors are kept for receivers which fall behind
+const PEER_ERROR_CHANNEL_SIZE: usize = 64;
+pub(super) const PEER_ERROR_QUEUE_SIZE: usize = 10;
+
+/// What went wrong in an exchange with another node, see [PeerError]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeerErrorKind {
+    /// The peer refused the connection, answering with the `error` field of a connection header
+    HandshakeRefused,
+    /// The connection header the peer sent was refused by the topic's [super::HeaderValidation],
+    /// or did not match the service called
+    HeaderRejected,
+    /// The peer sent a connection header or xmlrpc response which could not be parsed
+    Malformed,
+    /// The peer's xmlrpc API answered with a fault, or a failure code
+    XmlRpcFault,
+}
+
+impl Display for PeerErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PeerErrorKind::HandshakeRefused => "handshake refused",
+            PeerErrorKind::HeaderRejected => "header rejected",
+            PeerErrorKind::Malformed => "malformed data",
+            PeerErrorKind::XmlRpcFault => "xmlrpc fault",
+        })
+    }
+}
+
+/// A protocol error in an exchange with another node, received from [super::NodeHandle::peer_errors]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeerError {
+    /// When the error occurred
+    pub time: SystemTime,
+    pub kind: PeerErrorKind,
+    /// The caller id of the peer, or its address where the caller id isn't known
+    pub peer: String,
+    /// The topic or service the exchange was about
+    pub name: String,
+    /// The error, e.g. the reason the peer gave for refusing the connection
+    pub message: String,
+}
+
+impl Display for PeerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} with {} on {}: {}",
+            self.kind, self.peer, self.name, self.message
+        )
+    }
+}
+
+/// Where a node's protocol errors are reported, and forwarded to a topic when enabled.
+/// Clones report to the same receivers.
+#[derive(Clone, Debug)]
+pub(crate) struct PeerErrors {
+    errors: broadcast::Sender<PeerError>,
+    // The task publishing errors to a topic, if forwarding is enabled
+    forwarding: Arc<Mutex<Option<ChildTask<()>>>>,
+}
+
+impl Default for PeerErrors {
+    fn default() -> Self {
+        let (errors, _) = broadcast::channel(PEER_ERROR_CHANNEL_SIZE);
+        Self {
+            errors,
+            forwarding: Arc::default(),
+        }
+    }
+}
+
+impl PeerErrors {
+    pub fn subscribe(&self) -> broadcast::Receiver<PeerError> {
+        self.errors.subscribe()
+    }
+
+    /// Logs the error and sends it to receivers
+    pub fn report(&self, kind: PeerErrorKind, peer: &str, name: &str, message: impl Into<String>) {
+        let error = PeerError {
+            time: SystemTime::now(),
+            kind,
+            peer: peer.to_owned(),
+            name: name.to_owned(),
+            message: message.into(),
+        };
+        log::error!("Peer error, {error}");
+        // Nobody listening is fine, the error has been logged
+        let _ = self.errors.send(error);
+    }
+
+    /// Publishes every error reported from now on with `publisher`, replacing any previous forwarding.
+    /// Forwarding stops if the publisher fails, i.e. once the node has shut down.
+    pub(super) fn forward(&self, publisher: Publisher<Log>, node_name: String, clock: Clock) {
+        let mut errors = self.subscribe();
+        let name = format!("peer error forwarding of {node_name}");
+        let task = crate::tasks::spawn(&name, async move {
+            let mut seq = 0;
+            loop {
+                let error = match errors.recv().await {
+                    Ok(error) => error,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("Skipped forwarding {skipped} peer errors, reported faster than they could be published");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let msg = Log {
+                    header: Header {
+                        seq,
+                        stamp: clock.now(),
+                        frame_id: String::new(),
+                    },
+                    level: Log::ERROR,
+                    name: node_name.clone(),
+                    msg: error.to_string(),
+                    file: String::new(),
+                    function: error.kind.to_string(),
+                    line: 0,
+                    topics: vec![error.name],
+                };
+                seq = seq.wrapping_add(1);
+                if publisher.publish(&msg).await.is_err() {
+                    break;
+                }
+            }
+        });
+        *self.forwarding.lock().unwrap() = Some(task.into());
+    }
+
+    /// Stops any forwarding started with [PeerErrors::forward]
+    pub fn stop_forwarding(&self) {
+        self.forwarding.lock().unwrap().take();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PeerErrorKind, PeerErrors};
+
+    #[test]
+    fn reported_errors_are_received_by_clones() {
+        let peer_errors = PeerErrors::default();
+        // Reporting without receivers only logs
+        peer_errors.report(PeerErrorKind::Malformed, "/talker", "/chatter", "unheard");
+        let mut errors = peer_errors.subscribe();
+        peer_errors.clone().report(
+            PeerErrorKind::XmlRpcFault,
+            "http://talker:1234/",
+            "/chatter",
+            "requestTopic failed with code -1: unknown topic",
+        );
+        let error = errors.try_recv().unwrap();
+        assert_eq!(error.kind, PeerErrorKind::XmlRpcFault);
+        assert_eq!(
+            error.to_string(),
+            "xmlrpc fault with http://talker:1234/ on /chatter: requestTopic failed with code -1: unknown topic"
+        );
+        assert!(errors.try_recv().is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! This module contains hand written equivalents of the std_msgs messages wrapping a single primitive, used by
+//! [super::NodeHandle::publish_primitive] and [super::NodeHandle::subscribe_primitive] for trivial topics
+//! without needing to generate messages.
+
+use super::publisher::Publisher;
+use roslibrust_codegen::RosMessageType;
+use std::{any::Any, collections::HashMap, sync::Arc};
+
+/// The std_msgs message wrapping a single `T`, e.g. `StdMsg<f64>` is `std_msgs/Float64`, interchangeable with the
+/// generated message of the same type.
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/debug").await?;
+/// nh.publish_f64("/debug/error", 0.25).await?;
+/// let mut subscriber = nh.subscribe_string("/chatter", 10).await?;
+/// if let Ok(msg) = subscriber.next().await {
+///     log::info!("Heard {}", msg.data);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub struct StdMsg<T> {
+    pub data: T,
+}
+
+impl<T> From<T> for StdMsg<T> {
+    fn from(data: T) -> Self {
+        Self { data }
+    }
+}
+
+/// The primitive types which have a std_msgs message wrapping them, describing that message for [StdMsg]
+pub trait Primitive:
+    'static + serde::de::DeserializeOwned + Send + serde::Serialize + Sync + Clone + std::fmt::Debug
+{
+    /// e.g. `std_msgs/Float64`
+    const ROS_TYPE_NAME: &'static str;
+    const MD5SUM: &'static str;
+    const DEFINITION: &'static str;
+}
+
+impl<T: Primitive> RosMessageType for StdMsg<T> {
+    const ROS_TYPE_NAME: &'static str = T::ROS_TYPE_NAME;
+    const MD5SUM: &'static str = T::MD5SUM;
+    const DEFINITION: &'static str = T::DEFINITION;
+}
+
+macro_rules! std_msgs {
+    ($($type:ty => $name:literal, $md5sum:literal, $definition:literal;)*) => {
+        $(impl Primitive for $type {
+            const ROS_TYPE_NAME: &'static str = $name;
+            const MD5SUM: &'static str = $md5sum;
+            const DEFINITION: &'static str = $definition;
+        })*
+    };
+}
+
+std_msgs! {
+    String => "std_msgs/String", "992ce8a1687cec8c8bd883ec73ca41d1", "string data";
+    bool => "std_msgs/Bool", "8b94c1b53db61fb6aed406028ad6332a", "bool data";
+    f32 => "std_msgs/Float32", "73fcbf46b49191e672908e50842a83d4", "float32 data";
+    f64 => "std_msgs/Float64", "fdb28210bfa9d7c91146260178d9a584", "float64 data";
+    i8 => "std_msgs/Int8", "27ffa0c9c4b8fb8492252bcad9e5c57b", "int8 data";
+    i16 => "std_msgs/Int16", "8524586e34fbd7cb1c08c5f5f1ca0e57", "int16 data";
+    i32 => "std_msgs/Int32", "da5909fbe378aeaf85e547e830cc1bb7", "int32 data";
+    i64 => "std_msgs/Int64", "34add168574510e6e17f5d23ecc077ef", "int64 data";
+    u8 => "std_msgs/UInt8", "7c8164229e7d2c17eb95e9231617fdee", "uint8 data";
+    u16 => "std_msgs/UInt16", "1df79edf208b629fe6b81923a544552d", "uint16 data";
+    u32 => "std_msgs/UInt32", "304a39449588c7f8ce2df6e8001c5fce", "uint32 data";
+    u64 => "std_msgs/UInt64", "1b2a79973e8bf53d7b53acb71299cb57", "uint64 data";
+}
+
+/// The publishers advertised by [super::NodeHandle::publish_primitive], kept for the life of the node
+/// by the resolved name of their topic. Clones share the same publishers.
+#[derive(Clone, Default)]
+pub(crate) struct PrimitivePublishers {
+    publishers: Arc<tokio::sync::Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+}
+
+impl PrimitivePublishers {
+    /// The publisher of `topic`, advertised with `advertise` if it hasn't been yet
+    pub(crate) async fn get_or_advertise<T: Primitive, F>(
+        &self,
+        topic: &str,
+        advertise: F,
+    ) -> Result<Arc<Publisher<StdMsg<T>>>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: std::future::Future<
+            Output = Result<Publisher<StdMsg<T>>, Box<dyn std::error::Error + Send + Sync>>,
+        >,
+    {
+        // Held while advertising so concurrent first publishes advertise once
+        let mut publishers = self.publishers.lock().await;
+        let publisher = match publishers.get(topic) {
+            Some(publisher) => publisher.clone(),
+            None => {
+                let publisher: Arc<dyn Any + Send + Sync> = Arc::new(advertise.await?);
+                publishers.insert(topic.to_owned(), publisher.clone());
+                publisher
+            }
+        };
+        publisher.downcast::<Publisher<StdMsg<T>>>().map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "{topic} was already published to with a type other than {}",
+                    StdMsg::<T>::ROS_TYPE_NAME
+                ),
+            )
+            .into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StdMsg;
+    use roslibrust_codegen::RosMessageType;
+
+    fn assert_matches_definition<T: RosMessageType>() {
+        assert_eq!(
+            roslibrust_codegen::compute_md5sum_of_definition(T::ROS_TYPE_NAME, T::DEFINITION)
+                .unwrap(),
+            T::MD5SUM,
+            "{}",
+            T::ROS_TYPE_NAME
+        );
+    }
+
+    #[test]
+    fn md5sums_match_definitions() {
+        assert_matches_definition::<StdMsg<String>>();
+        assert_matches_definition::<StdMsg<bool>>();
+        assert_matches_definition::<StdMsg<f32>>();
+        assert_matches_definition::<StdMsg<f64>>();
+        assert_matches_definition::<StdMsg<i8>>();
+        assert_matches_definition::<StdMsg<i16>>();
+        assert_matches_definition::<StdMsg<i32>>();
+        assert_matches_definition::<StdMsg<i64>>();
+        assert_matches_definition::<StdMsg<u8>>();
+        assert_matches_definition::<StdMsg<u16>>();
+        assert_matches_definition::<StdMsg<u32>>();
+        assert_matches_definition::<StdMsg<u64>>();
+    }
+}
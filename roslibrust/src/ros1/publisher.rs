@@ -1,67 +1,837 @@
-use crate::RosLibRustError;
-
-use super::tcpros::ConnectionHeader;
+#[cfg(unix)]
+use super::unixros::UnixSocket;
+use super::{
+    addrs,
+    bus_info::{BusConnection, BusConnections, Direction, Transport},
+    callback_panics::CallbackGuard,
+    checksum,
+    clock::Clock,
+    header_stamper::{self, HeaderStamper},
+    intra_process::{LocalMessage, LocalSubscription},
+    memory_budgets::{Charge, MemoryBudgets, TopicAccount},
+    node::{ShutdownReason, TopicRegistration},
+    peer_errors::{PeerErrorKind, PeerErrors},
+    subscription_queue::SubscriptionQueue,
+    tcpros::{self, ConnectionHeader, HeaderValidation, SocketOptions, DEFAULT_MAX_MESSAGE_SIZE},
+    tls::{TcprosSecurity, TcprosStream},
+    udpros::{self, UdpLink, UdprosRequest, UdprosResponse},
+};
 use abort_on_drop::ChildTask;
+use bytes::Bytes;
 use roslibrust_codegen::RosMessageType;
 use std::{
+    collections::BTreeMap,
     marker::PhantomData,
-    net::{Ipv4Addr, SocketAddr},
-    sync::Arc,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
 use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    sync::{mpsc, RwLock},
+    net::UdpSocket,
+    sync::{mpsc, oneshot, watch, RwLock},
 };
 
+// How long subscribers have to complete the TLS handshake and send their connection header once connected
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A subscriber which connected to one of our topics, passed to [Publisher::on_subscriber_connect] callbacks
+#[derive(Clone, Debug)]
+pub struct SubscriberInfo {
+    /// Name of the subscribing node
+    pub caller_id: String,
+    /// Address the subscriber connected from, or receives datagrams on for UDPROS.
+    /// Subscribers connected over UNIXROS share our host and are given `127.0.0.1:0`, as is the node's own
+    /// subscription receiving the topic in-process.
+    pub addr: SocketAddr,
+    /// Custom fields the subscriber sent in its connection header, see [super::TopicOptions::header_field]
+    pub custom_fields: BTreeMap<String, String>,
+}
+
+type ConnectCallback = Arc<dyn Fn(&SubscriberInfo) + Send + Sync>;
+
+// What the node hands out for each advertise of a publication
+pub(crate) type PublicationHandle = (PublicationQueue, ConnectedSubscribers);
+
+// What publishers queue for the publication to send
+#[derive(Debug)]
+pub(crate) enum QueuedMessage {
+    // A serialized message, charged to the topic's memory budget until sent.
+    // Shared rather than copied by every subscriber it is sent to and the latched message.
+    Message { data: Bytes, charge: Charge },
+    // Answered once every message queued before it has been written to the subscribers
+    Flush(oneshot::Sender<()>),
+}
+
+// Where publishers queue messages for the publication to send
+#[derive(Clone, Debug)]
+pub(crate) struct PublicationQueue {
+    sender: mpsc::Sender<QueuedMessage>,
+    account: Arc<TopicAccount>,
+    // Counted by the publication to tell whether any publishers are left
+    _handle: Arc<()>,
+    // Shared by the clones of the queue handed out for one advertise
+    registration: Option<Arc<TopicRegistration>>,
+    // Whether the publication keeps the last message for subscribers connecting later
+    latching: bool,
+}
+
+impl PublicationQueue {
+    /// Asks the node to unregister the topic once this queue and its clones are dropped
+    pub fn unregister_on_drop(mut self, registration: TopicRegistration) -> Self {
+        self.registration = Some(Arc::new(registration));
+        self
+    }
+
+    /// Queues `data` to be sent once the topic's memory budget allows, failing if the budget refuses it
+    /// or with [std::io::ErrorKind::NotConnected] if the publication has gone
+    pub async fn send(&self, data: Bytes) -> std::io::Result<()> {
+        let charge = self.account.charge_outgoing(data.len()).await?;
+        self.sender
+            .send(QueuedMessage::Message { data, charge })
+            .await
+            .map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::NotConnected, "Publication has gone")
+            })
+    }
+
+    /// Resolves once every message queued so far has been written to the connected subscribers,
+    /// or immediately if the publication has gone
+    pub async fn flush(&self) {
+        let (flushed, done) = oneshot::channel();
+        if self
+            .sender
+            .send(QueuedMessage::Flush(flushed))
+            .await
+            .is_ok()
+        {
+            let _ = done.await;
+        }
+    }
+
+    /// Drops the queue, returning its registration for the caller to release if no clones of the queue are left
+    pub fn into_registration(mut self) -> Option<TopicRegistration> {
+        let registration = self.registration.take();
+        drop(self);
+        registration.and_then(|registration| Arc::try_unwrap(registration).ok())
+    }
+}
+
+// The subscribers connected to a publication, shared with every Publisher of it
+#[derive(Clone, Default)]
+pub struct ConnectedSubscribers {
+    // Subscribers connected over the network
+    count: Arc<AtomicUsize>,
+    // The node's own subscription of the topic, if it receives it in-process
+    local: LocalSubscription,
+    on_connect: Arc<Mutex<Vec<ConnectCallback>>>,
+    // Contains panics of the callbacks, naming the topic they were registered for
+    callback_guard: CallbackGuard,
+    topic_name: Arc<str>,
+}
+
+impl std::fmt::Debug for ConnectedSubscribers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConnectedSubscribers")
+            .field("count", &self.count())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ConnectedSubscribers {
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Relaxed) + usize::from(self.local.is_connected())
+    }
+
+    pub fn on_connect(&self, callback: impl Fn(&SubscriberInfo) + Send + Sync + 'static) {
+        self.on_connect.lock().unwrap().push(Arc::new(callback));
+    }
+
+    fn set_count(&self, count: usize) {
+        self.count.store(count, Ordering::Relaxed);
+    }
+
+    fn connected(&self, count: usize, subscriber: &SubscriberInfo) {
+        self.set_count(count);
+        self.run_callbacks(subscriber);
+    }
+
+    fn run_callbacks(&self, subscriber: &SubscriberInfo) {
+        // Callbacks are run without holding the lock so they are free to register further callbacks
+        let callbacks = self.on_connect.lock().unwrap().clone();
+        for callback in callbacks {
+            let source = format!("subscriber connect callback of {}", self.topic_name);
+            self.callback_guard.run(&source, || callback(subscriber));
+        }
+    }
+}
+
+/// Publishes messages to a topic, see [super::NodeHandle::advertise].
+/// The node unadvertises the topic once every publisher of it is dropped, without waiting for messages still queued
+/// to be sent. Use [Publisher::close] to wait for both.
 pub struct Publisher<T> {
     topic_name: String,
-    sender: mpsc::Sender<Vec<u8>>,
+    queue: PublicationQueue,
+    subscribers: ConnectedSubscribers,
+    shutdown_reason: ShutdownReason,
+    stamper: Option<HeaderStamper>,
     phantom: PhantomData<T>,
 }
 
 impl<T: RosMessageType> Publisher<T> {
-    pub(crate) fn new(topic_name: &str, sender: mpsc::Sender<Vec<u8>>) -> Self {
+    pub(crate) fn new(
+        topic_name: &str,
+        queue: PublicationQueue,
+        subscribers: ConnectedSubscribers,
+        shutdown_reason: ShutdownReason,
+    ) -> Self {
         Self {
             topic_name: topic_name.to_owned(),
-            sender,
+            queue,
+            subscribers,
+            shutdown_reason,
+            stamper: None,
             phantom: PhantomData,
         }
     }
 
+    /// Fills in the `std_msgs/Header` which begins each message as it is published, rather than leaving each caller to:
+    /// `seq` counts the messages published by this publisher from 0, and a `stamp` left at zero is set to
+    /// the current time of `clock`, usually the node's [clock](super::NodeHandle::clock) so it follows simulated time.
+    /// Stamps given by the caller, e.g. the capture time of sensor data, are kept.
+    ///
+    /// Fails with [std::io::ErrorKind::InvalidInput] if the first field of `T` is not a header.
+    /// ```no_run
+    /// # roslibrust_codegen_macro::find_and_generate_ros_messages!(
+    /// #    "assets/ros1_common_interfaces/std_msgs",
+    /// #    "assets/ros1_common_interfaces/common_msgs/geometry_msgs"
+    /// # );
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/tracker").await?;
+    /// let publisher = nh
+    ///     .advertise::<geometry_msgs::PointStamped>("/target", 1)
+    ///     .await?
+    ///     .stamp_headers(nh.clock())?;
+    /// let mut target = geometry_msgs::PointStamped::default();
+    /// target.header.frame_id = "map".to_owned();
+    /// publisher.publish(&target).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stamp_headers(mut self, clock: &Clock) -> Result<Self, std::io::Error> {
+        if !header_stamper::has_header(T::DEFINITION) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "{} does not begin with a std_msgs/Header to stamp",
+                    T::ROS_TYPE_NAME
+                ),
+            ));
+        }
+        self.stamper = Some(HeaderStamper::new(clock.clone()));
+        Ok(self)
+    }
+
+    /// The number of subscribers connected to the topic, e.g. to skip computing messages no one is listening to.
+    /// Subscribers which disconnect are only noticed when publishing to them next fails,
+    /// so are counted until then.
+    pub fn get_num_subscribers(&self) -> usize {
+        self.subscribers.count()
+    }
+
+    /// Registers `callback` to run whenever a subscriber connects to the topic, like roscpp's `SubscriberStatusCallback`.
+    /// Callbacks apply to the topic rather than this publisher, so keep running after it is dropped
+    /// until every publisher of the topic is and the node unadvertises it. Subscribers which connected before the callback was registered are not reported.
+    ///
+    /// Callbacks run on the task accepting connections, so should return quickly,
+    /// e.g. by spawning a task to publish a message for the new subscriber:
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// # roslibrust_codegen_macro::find_and_generate_ros_messages!("assets/ros1_common_interfaces/std_msgs");
+    /// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/talker").await?;
+    /// let publisher = nh.advertise::<std_msgs::String>("/chatter", 1).await?;
+    /// publisher.on_subscriber_connect(|subscriber| {
+    ///     log::info!("{} connected from {}", subscriber.caller_id, subscriber.addr);
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_subscriber_connect(
+        &self,
+        callback: impl Fn(&SubscriberInfo) + Send + Sync + 'static,
+    ) {
+        self.subscribers.on_connect(callback);
+    }
+
     pub async fn publish(&self, data: &T) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let data = serde_rosmsg::to_vec(&data)
+        let local = &self.subscribers.local;
+        // Messages are serialized for subscribers connected over the network, and to keep the last
+        // for those connecting later, but handed to the node's own subscription as they are
+        let remote = !local.is_connected()
+            || self.subscribers.count.load(Ordering::Relaxed) > 0
+            || self.queue.latching;
+        if self.stamper.is_none() && local.is_connected() {
+            local
+                .send(Bytes::new(), Some(LocalMessage::new(data.clone())), false)
+                .await;
+            if !remote {
+                log::debug!("Publishing data in-process on topic {}", self.topic_name);
+                return Ok(());
+            }
+        }
+        let mut data = serde_rosmsg::to_vec(&data)
             // Gotta do some funny error mapping here as serde_rosmsg's error type is not sync
-            .map_err(|e| RosLibRustError::Unexpected(anyhow::anyhow!("{e:?}")))?;
-        self.sender.send(data).await?;
+            .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+        if let Some(stamper) = &self.stamper {
+            stamper.stamp(&mut data);
+        }
+        let data = Bytes::from(data);
+        if self.stamper.is_some() {
+            // Headers are stamped on the serialized message, so it is handed over serialized
+            if local.is_connected() {
+                local.send(data.clone(), None, false).await;
+            }
+            if !remote {
+                return Ok(());
+            }
+        }
+        match self.queue.send(data).await {
+            Ok(()) => {}
+            // The publication is only dropped when the node shuts down
+            Err(err) if err.kind() == std::io::ErrorKind::NotConnected => {
+                return Err(self.shutdown_reason.error().into())
+            }
+            Err(err) => return Err(err.into()),
+        }
         log::debug!("Publishing data on topic {}", self.topic_name);
         Ok(())
     }
+
+    /// Drops the publisher, returning once the messages it published have been written to the connected subscribers,
+    /// and the node has unadvertised the topic from the master if no other publishers of it are left.
+    ///
+    /// Fails with [std::io::ErrorKind::TimedOut] if this takes longer than `timeout`,
+    /// in which case the rest of the teardown carries on in the background as if the publisher had been dropped.
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/talker").await?;
+    /// let publisher = nh.advertise::<roslibrust::StdMsg<bool>>("/done", 1).await?;
+    /// publisher.publish(&true.into()).await?;
+    /// publisher.close(std::time::Duration::from_secs(1)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn close(
+        self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let topic_name = self.topic_name;
+        let queue = self.queue;
+        tokio::time::timeout(timeout, async move {
+            queue.flush().await;
+            if let Some(registration) = queue.into_registration() {
+                registration.release().await;
+            }
+        })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Timed out closing the publisher of {topic_name}"),
+            )
+            .into()
+        })
+    }
+}
+
+/// How a publication treats subscribers it can't write to as fast as it publishes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SlowSubscribers {
+    /// Sends each subscriber only the latest message, see NodeHandle::advertise_keep_latest
+    pub keep_latest: bool,
+    /// Evicts subscribers a message takes longer than this to be written to, see TopicOptions::send_timeout
+    pub send_timeout: Option<Duration>,
+}
+
+// The connection to a single subscriber
+enum SubscriberLink {
+    Tcp(TcprosStream),
+    Udp(UdpLink),
+    // Another link sent only the latest message, for publications keeping the latest
+    Latest(LatestLink),
+    // Another link sent messages through a bounded queue, for publications evicting slow subscribers
+    Outbox(OutboxLink),
+}
+
+impl SubscriberLink {
+    // Sends a message framed with its length, recording what was sent to the link's bus connection.
+    // A failed send may leave part of the message written, so the link can't be used again.
+    async fn send(&mut self, msg: &Bytes, connection: &BusConnection) -> std::io::Result<()> {
+        let bytes = match self {
+            SubscriberLink::Tcp(stream) => {
+                stream.write_frame(msg).await?;
+                msg.len()
+            }
+            SubscriberLink::Udp(link) => link.send(msg).await?,
+            // Recorded by the link's task if it gets to send it
+            SubscriberLink::Latest(link) => return link.replace(msg),
+            SubscriberLink::Outbox(link) => return link.push(msg),
+        };
+        connection.record(bytes);
+        Ok(())
+    }
+
+    // Sends a message as [SubscriberLink::send], failing with TimedOut if it takes longer than `timeout`
+    async fn send_within(
+        &mut self,
+        msg: &Bytes,
+        connection: &BusConnection,
+        timeout: Option<Duration>,
+    ) -> std::io::Result<()> {
+        let Some(timeout) = timeout else {
+            return self.send(msg, connection).await;
+        };
+        match tokio::time::timeout(timeout, self.send(msg, connection)).await {
+            Ok(result) => result,
+            Err(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("writing a message took longer than {timeout:?}"),
+            )),
+        }
+    }
+}
+
+// Hands messages to a task writing them to a subscriber, each replacing the last if the task has yet to take it,
+// so a slow subscriber is only ever sent the latest message and holds up no one else
+struct LatestLink {
+    latest: watch::Sender<Bytes>,
+    _task: ChildTask<()>,
+}
+
+impl LatestLink {
+    fn new(
+        mut link: SubscriberLink,
+        connection: Arc<BusConnection>,
+        send_timeout: Option<Duration>,
+    ) -> Self {
+        let (latest, mut receiver) = watch::channel(Bytes::new());
+        let name = format!("latest message sender of {}", connection.topic());
+        let task = crate::tasks::spawn(&name, async move {
+            // Ends once the link fails, which the publish task notices when next replacing the message
+            while receiver.changed().await.is_ok() {
+                let msg = receiver.borrow_and_update().clone();
+                if let Err(err) = link.send_within(&msg, &connection, send_timeout).await {
+                    log_send_failure(&err, &connection);
+                    break;
+                }
+            }
+        });
+        Self {
+            latest,
+            _task: task.into(),
+        }
+    }
+
+    fn replace(&self, msg: &Bytes) -> std::io::Result<()> {
+        self.latest.send(msg.clone()).map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::BrokenPipe, "Subscriber link has failed")
+        })
+    }
+}
+
+// Hands messages to a task writing them to a subscriber through a queue of up to `capacity` messages,
+// so a slow subscriber holds up no one else until it falls a queue's worth behind, when it is evicted
+struct OutboxLink {
+    outbox: mpsc::Sender<Bytes>,
+    capacity: usize,
+    _task: ChildTask<()>,
+}
+
+impl OutboxLink {
+    fn new(
+        mut link: SubscriberLink,
+        connection: Arc<BusConnection>,
+        capacity: usize,
+        send_timeout: Option<Duration>,
+    ) -> Self {
+        let capacity = capacity.max(1);
+        let (outbox, mut receiver) = mpsc::channel::<Bytes>(capacity);
+        let name = format!("outbox of {}", connection.topic());
+        let task = crate::tasks::spawn(&name, async move {
+            // Ends once the link fails, which the publish task notices when next queueing a message
+            while let Some(msg) = receiver.recv().await {
+                if let Err(err) = link.send_within(&msg, &connection, send_timeout).await {
+                    log_send_failure(&err, &connection);
+                    break;
+                }
+            }
+        });
+        Self {
+            outbox,
+            capacity,
+            _task: task.into(),
+        }
+    }
+
+    fn push(&self, msg: &Bytes) -> std::io::Result<()> {
+        self.outbox.try_send(msg.clone()).map_err(|err| match err {
+            mpsc::error::TrySendError::Full(_) => std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("subscriber fell {} messages behind", self.capacity),
+            ),
+            mpsc::error::TrySendError::Closed(_) => {
+                std::io::Error::new(std::io::ErrorKind::BrokenPipe, "Subscriber link has failed")
+            }
+        })
+    }
+}
+
+// Subscribers which time out are evicted for being too slow, which is worth a warning unlike a closed connection
+fn log_send_failure(err: &std::io::Error, connection: &BusConnection) {
+    if err.kind() == std::io::ErrorKind::TimedOut {
+        log::warn!(
+            "Evicting slow subscriber {} of {}: {err}",
+            connection.peer(),
+            connection.topic()
+        );
+    } else {
+        log::debug!("Failed to send data to subscriber: {err}");
+    }
+}
+
+// Each subscriber's link, whether it asked for checksums, and the bus connection its traffic is recorded to
+type Links = Vec<(SubscriberLink, bool, Arc<BusConnection>)>;
+
+// The subscribers of a publication, shared by the task accepting TCPROS connections,
+// the publish task, and the publication itself for adding UDPROS subscribers
+#[derive(Clone)]
+struct SubscriberLinks {
+    links: Arc<RwLock<Links>>,
+    // The last message published if latching, only accessed while holding the lock on links
+    // so every subscriber either receives it on connecting or is written to by the publish task
+    latched_msg: Arc<Mutex<Option<Bytes>>>,
+    subscribers: ConnectedSubscribers,
+    slow_subscribers: SlowSubscribers,
+    // How many messages a subscriber may fall behind before it is evicted, if slow subscribers are
+    outbox_capacity: usize,
+}
+
+impl SubscriberLinks {
+    // Starts publishing to a subscriber, first sending it the latched message if there is one
+    async fn add(
+        &self,
+        mut link: SubscriberLink,
+        checksum: bool,
+        connection: Arc<BusConnection>,
+        subscriber: SubscriberInfo,
+    ) {
+        let SlowSubscribers {
+            keep_latest,
+            send_timeout,
+        } = self.slow_subscribers;
+        if keep_latest {
+            link = SubscriberLink::Latest(LatestLink::new(link, connection.clone(), send_timeout));
+        } else if send_timeout.is_some() {
+            link = SubscriberLink::Outbox(OutboxLink::new(
+                link,
+                connection.clone(),
+                self.outbox_capacity,
+                send_timeout,
+            ));
+        }
+        let mut links = self.links.write().await;
+        let latched = self.latched_msg.lock().unwrap().clone();
+        if let Some(mut latched) = latched {
+            if checksum {
+                latched = checksum::append(&latched).into();
+            }
+            if let Err(err) = link.send(&latched, &connection).await {
+                log::debug!(
+                    "Failed to send latched message to subscriber {}: {err}",
+                    subscriber.addr
+                );
+                return;
+            }
+        }
+        links.push((link, checksum, connection));
+        let count = links.len();
+        drop(links);
+        self.subscribers.connected(count, &subscriber);
+    }
+}
+
+/// Exchanges connection headers with the subscribers connecting to a topic, over TCPROS or UNIXROS,
+/// and adds those accepted to its links
+#[derive(Clone)]
+struct SubscriberAcceptor {
+    links: SubscriberLinks,
+    responding_conn_header: ConnectionHeader,
+    header_validation: HeaderValidation,
+    socket_options: SocketOptions,
+    bus_connections: BusConnections,
+    peer_errors: PeerErrors,
+}
+
+impl SubscriberAcceptor {
+    async fn accept(
+        &self,
+        mut stream: TcprosStream,
+        peer_addr: SocketAddr,
+        transport: Transport,
+        transport_info: String,
+    ) {
+        let topic_name = self.responding_conn_header.topic.as_str();
+        // Headers carry the message definition, which may span several reads
+        let read = tcpros::read_frame(&mut stream, DEFAULT_MAX_MESSAGE_SIZE);
+        let connection_header = match tokio::time::timeout(HANDSHAKE_TIMEOUT, read).await {
+            Ok(Ok(connection_header)) => connection_header,
+            Ok(Err(_)) => return,
+            Err(_) => {
+                log::warn!("Subscriber at {peer_addr} sent no connection header for {topic_name} within {HANDSHAKE_TIMEOUT:?}, closing");
+                return;
+            }
+        };
+        let bytes = connection_header.len();
+        let Ok(connection_header) = ConnectionHeader::from_bytes(&connection_header) else {
+            let header_str = connection_header[..bytes]
+                .iter()
+                .map(|ch| if *ch < 128 { *ch as char } else { '.' })
+                .collect::<String>();
+            self.peer_errors.report(
+                PeerErrorKind::Malformed,
+                &peer_addr.to_string(),
+                topic_name,
+                format!("failed to parse connection header: ({bytes} bytes) {header_str}"),
+            );
+            return;
+        };
+        if let Err(err) = self
+            .header_validation
+            .check(&self.responding_conn_header, &connection_header)
+        {
+            let peer = if connection_header.caller_id.is_empty() {
+                peer_addr.to_string()
+            } else {
+                connection_header.caller_id
+            };
+            self.peer_errors.report(
+                PeerErrorKind::HeaderRejected,
+                &peer,
+                topic_name,
+                format!("rejected subscriber at {peer_addr}: {err}"),
+            );
+            return;
+        }
+        log::debug!("Received subscribe request for {}", connection_header.topic);
+        // Either side can ask for Nagle's algorithm to be disabled
+        if let Some(tcp) = stream.tcp() {
+            let options = SocketOptions {
+                tcp_nodelay: self.socket_options.tcp_nodelay || connection_header.tcp_nodelay,
+                ..self.socket_options
+            };
+            options.apply(tcp, topic_name);
+        }
+        // Write our own connection header in response, agreeing to send checksums if asked
+        let response_header = ConnectionHeader {
+            checksum: connection_header.checksum,
+            ..self.responding_conn_header.clone()
+        };
+        let response_header_bytes = response_header
+            .to_bytes(false)
+            .expect("Couldn't serialize connection header");
+        if let Err(err) = stream.write_frame(&response_header_bytes[..]).await {
+            log::warn!("Failed to respond to subscriber at {peer_addr}: {err}");
+            return;
+        }
+        let connection = self.bus_connections.add(
+            &connection_header.caller_id,
+            Direction::Outbound,
+            transport,
+            topic_name,
+            transport_info,
+        );
+        let subscriber = SubscriberInfo {
+            caller_id: connection_header.caller_id.clone(),
+            addr: peer_addr,
+            custom_fields: connection_header.custom_fields.clone(),
+        };
+        self.links
+            .add(
+                SubscriberLink::Tcp(stream),
+                connection_header.checksum,
+                connection,
+                subscriber,
+            )
+            .await;
+        log::debug!(
+            "Added stream for topic {} to subscriber {}",
+            connection_header.topic,
+            peer_addr
+        );
+    }
+}
+
+/// The settings a [Publication] is created with beyond the topic it publishes, set with builder methods
+/// like those of [super::subscriber::Subscription]
+#[derive(Clone)]
+pub(crate) struct PublicationOptions {
+    latching: bool,
+    slow_subscribers: SlowSubscribers,
+    socket_options: SocketOptions,
+    header_validation: HeaderValidation,
+    header_fields: BTreeMap<String, String>,
+    bind_addr: IpAddr,
+    bus_connections: BusConnections,
+    callback_guard: CallbackGuard,
+    account: Option<Arc<TopicAccount>>,
+    peer_errors: PeerErrors,
+    tcpros_security: TcprosSecurity,
+}
+
+impl Default for PublicationOptions {
+    fn default() -> Self {
+        Self {
+            latching: false,
+            slow_subscribers: SlowSubscribers::default(),
+            socket_options: SocketOptions::default(),
+            header_validation: HeaderValidation::default(),
+            header_fields: BTreeMap::new(),
+            bind_addr: addrs::UNSPECIFIED,
+            bus_connections: BusConnections::default(),
+            callback_guard: CallbackGuard::default(),
+            account: None,
+            peer_errors: PeerErrors::default(),
+            tcpros_security: TcprosSecurity::default(),
+        }
+    }
+}
+
+impl PublicationOptions {
+    /// Sets whether the last message published is kept and sent to each subscriber on connecting
+    pub fn latching(mut self, latching: bool) -> Self {
+        self.latching = latching;
+        self
+    }
+
+    /// Sets how subscribers which can't be written to as fast as messages are published are treated
+    pub fn slow_subscribers(mut self, slow_subscribers: SlowSubscribers) -> Self {
+        self.slow_subscribers = slow_subscribers;
+        self
+    }
+
+    /// Sets the options of TCP connections to subscribers
+    pub fn socket_options(mut self, options: SocketOptions) -> Self {
+        self.socket_options = options;
+        self
+    }
+
+    /// Sets how strictly the headers subscribers connect with are checked
+    pub fn header_validation(mut self, validation: HeaderValidation) -> Self {
+        self.header_validation = validation;
+        self
+    }
+
+    /// Sets the custom fields sent to subscribers in the publication's connection header
+    pub fn header_fields(mut self, fields: BTreeMap<String, String>) -> Self {
+        self.header_fields = fields;
+        self
+    }
+
+    /// Sets the address subscribers are accepted on, every interface by default
+    pub fn bind_addr(mut self, addr: IpAddr) -> Self {
+        self.bind_addr = addr;
+        self
+    }
+
+    /// Sets where connections to subscribers are reported for getBusInfo
+    pub fn bus_connections(mut self, bus_connections: BusConnections) -> Self {
+        self.bus_connections = bus_connections;
+        self
+    }
+
+    /// Sets what runs the callbacks of subscribers connecting, see [Publisher::on_subscriber_connect]
+    pub fn callback_guard(mut self, callback_guard: CallbackGuard) -> Self {
+        self.callback_guard = callback_guard;
+        self
+    }
+
+    /// Sets where the bytes of queued messages are charged until they are sent,
+    /// an account without a budget by default
+    pub fn memory_account(mut self, account: Arc<TopicAccount>) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// Sets where subscribers refusing connections or connecting with headers which are rejected or malformed are reported
+    pub fn peer_errors(mut self, peer_errors: PeerErrors) -> Self {
+        self.peer_errors = peer_errors;
+        self
+    }
+
+    /// Sets whether connections from subscribers are expected to be wrapped in TLS
+    pub fn tcpros_security(mut self, security: TcprosSecurity) -> Self {
+        self.tcpros_security = security;
+        self
+    }
 }
 
 pub struct Publication {
     topic_type: String,
+    md5sum: String,
+    header_validation: HeaderValidation,
     listener_port: u16,
+    host_addr: IpAddr,
+    // Sent to subscribers in response to their own header
+    responding_conn_header: ConnectionHeader,
+    links: SubscriberLinks,
+    bus_connections: BusConnections,
+    #[cfg_attr(not(unix), allow(dead_code))]
+    acceptor: SubscriberAcceptor,
     _channel_task: ChildTask<()>,
+    // Bound once a subscriber first asks for UNIXROS, along with the task accepting on it
+    unix_socket: Option<(PathBuf, ChildTask<()>)>,
     _publish_task: ChildTask<()>,
-    publish_sender: mpsc::Sender<Vec<u8>>,
+    publish_sender: mpsc::Sender<QueuedMessage>,
+    // The bytes queued to be sent, counted against the topic's memory budget
+    account: Arc<TopicAccount>,
+    // Cloned into each queue handed out, so is only shared once every publisher is dropped
+    handles: Arc<()>,
 }
 
 impl Publication {
     pub async fn new(
         node_name: &str,
-        latching: bool,
         topic_name: &str,
-        host_addr: Ipv4Addr,
+        topic_type: &str,
         queue_size: usize,
         msg_definition: &str,
         md5sum: &str,
-        topic_type: &str,
+        options: PublicationOptions,
     ) -> Result<Self, std::io::Error> {
-        let host_addr = SocketAddr::from((host_addr, 0));
-        let tcp_listener = tokio::net::TcpListener::bind(host_addr).await?;
+        let PublicationOptions {
+            latching,
+            slow_subscribers,
+            socket_options,
+            header_validation,
+            header_fields: custom_fields,
+            bind_addr: host_addr,
+            bus_connections,
+            callback_guard,
+            account,
+            peer_errors,
+            tcpros_security,
+        } = options;
+        let account = account.unwrap_or_else(|| MemoryBudgets::default().account(topic_name, None));
+        let tcp_listener = tokio::net::TcpListener::from_std(addrs::bind_tcp(host_addr)?)?;
         let listener_port = tcp_listener.local_addr().unwrap().port();
 
-        let (sender, mut receiver) = mpsc::channel::<Vec<u8>>(queue_size);
+        let (sender, mut receiver) = mpsc::channel::<QueuedMessage>(queue_size);
 
         let responding_conn_header = ConnectionHeader {
             caller_id: node_name.to_owned(),
@@ -71,69 +841,121 @@ impl Publication {
             topic: topic_name.to_owned(),
             topic_type: topic_type.to_owned(),
             tcp_nodelay: false,
+            service: None,
+            persistent: false,
+            probe: false,
+            error: None,
+            checksum: false,
+            custom_fields,
         };
 
-        let subscriber_streams = Arc::new(RwLock::new(Vec::new()));
+        let links = SubscriberLinks {
+            links: Arc::new(RwLock::new(Vec::new())),
+            latched_msg: Arc::default(),
+            subscribers: ConnectedSubscribers {
+                callback_guard,
+                topic_name: topic_name.into(),
+                ..Default::default()
+            },
+            slow_subscribers,
+            outbox_capacity: queue_size,
+        };
 
-        let subscriber_streams_copy = subscriber_streams.clone();
-        let listener_handle = tokio::spawn(async move {
-            let subscriber_streams = subscriber_streams_copy;
+        let acceptor = SubscriberAcceptor {
+            links: links.clone(),
+            responding_conn_header: responding_conn_header.clone(),
+            header_validation,
+            socket_options,
+            bus_connections: bus_connections.clone(),
+            peer_errors: peer_errors.clone(),
+        };
+        let listener_acceptor = acceptor.clone();
+        let listener_name = format!("publication {topic_name} accepting on port {listener_port}");
+        let listener_handle = crate::tasks::spawn(&listener_name, async move {
+            let topic_name = listener_acceptor.responding_conn_header.topic.clone();
             loop {
-                if let Ok((mut stream, peer_addr)) = tcp_listener.accept().await {
-                    let topic_name = responding_conn_header.topic.as_str();
-                    log::info!(
-                        "Received connection from subscriber at {peer_addr} for topic {topic_name}"
-                    );
-                    let mut connection_header = Vec::with_capacity(16 * 1024);
-                    if let Ok(bytes) = stream.read_buf(&mut connection_header).await {
-                        if let Ok(connection_header) =
-                            ConnectionHeader::from_bytes(&connection_header[..bytes])
-                        {
-                            if connection_header.md5sum == responding_conn_header.md5sum {
-                                log::debug!(
-                                    "Received subscribe request for {}",
-                                    connection_header.topic
-                                );
-                                // Write our own connection header in response
-                                let response_header_bytes = responding_conn_header
-                                    .to_bytes(false)
-                                    .expect("Couldn't serialize connection header");
-                                stream
-                                    .write(&response_header_bytes[..])
-                                    .await
-                                    .expect("Unable to respond on tcpstream");
-                                let mut wlock = subscriber_streams.write().await;
-                                wlock.push(stream);
-                                log::debug!(
-                                    "Added stream for topic {} to subscriber {}",
-                                    connection_header.topic,
-                                    peer_addr
-                                );
-                            }
-                        } else {
-                            let header_str = connection_header[..bytes]
-                                .into_iter()
-                                .map(|ch| if *ch < 128 { *ch as char } else { '.' })
-                                .collect::<String>();
-                            log::error!(
-                                "Failed to parse connection header: ({bytes} bytes) {header_str}",
-                            )
+                let Ok((stream, peer_addr)) = tcp_listener.accept().await else {
+                    continue;
+                };
+                let peer_addr = addrs::canonical(peer_addr);
+                log::info!(
+                    "Received connection from subscriber at {peer_addr} for topic {topic_name}"
+                );
+                // Each subscriber completes its handshake in its own task, so one which stalls
+                // doesn't hold up those connecting after it
+                let acceptor = listener_acceptor.clone();
+                let tcpros_security = tcpros_security.clone();
+                let peer_errors = peer_errors.clone();
+                let topic_name = topic_name.clone();
+                let task_name = format!("subscriber at {peer_addr} connecting to {topic_name}");
+                crate::tasks::spawn(&task_name, async move {
+                    let handshake = tcpros_security.accept(stream);
+                    let stream = match tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake).await {
+                        Ok(Ok(stream)) => stream,
+                        result => {
+                            let err = match result {
+                                Ok(Err(err)) => err.to_string(),
+                                _ => format!("timed out after {HANDSHAKE_TIMEOUT:?}"),
+                            };
+                            peer_errors.report(
+                                PeerErrorKind::HandshakeRefused,
+                                &peer_addr.to_string(),
+                                &topic_name,
+                                format!(
+                                    "TLS handshake with subscriber at {peer_addr} failed: {err}"
+                                ),
+                            );
+                            return;
                         }
-                    }
-                }
+                    };
+                    acceptor
+                        .accept(
+                            stream,
+                            peer_addr,
+                            Transport::Tcpros,
+                            format!("TCPROS connection on port {listener_port} to [{peer_addr}]"),
+                        )
+                        .await;
+                });
             }
         });
 
-        let publish_task = tokio::spawn(async move {
+        let publish_links = links.clone();
+        let publish_task = crate::tasks::spawn(&format!("publication {topic_name}"), async move {
+            let links = publish_links;
             loop {
                 match receiver.recv().await {
-                    Some(msg_to_publish) => {
-                        let mut streams = subscriber_streams.write().await;
+                    Some(QueuedMessage::Message {
+                        data: msg_to_publish,
+                        charge,
+                    }) => {
+                        if charge.evict() {
+                            log::debug!(
+                                "Dropping the oldest message queued on {} to stay within its memory budget",
+                                links.subscribers.topic_name
+                            );
+                            continue;
+                        }
+                        let mut streams = links.links.write().await;
+                        if latching {
+                            *links.latched_msg.lock().unwrap() = Some(msg_to_publish.clone());
+                        }
                         let mut streams_to_remove = vec![];
-                        for (stream_idx, stream) in streams.iter_mut().enumerate() {
-                            if let Err(err) = stream.write(&msg_to_publish[..]).await {
+                        // Made once for every subscriber asking for checksums
+                        let mut checksummed = None;
+                        for (stream_idx, (stream, checksum, connection)) in
+                            streams.iter_mut().enumerate()
+                        {
+                            let msg = if *checksum {
+                                checksummed.get_or_insert_with(|| {
+                                    Bytes::from(checksum::append(&msg_to_publish))
+                                })
+                            } else {
+                                &msg_to_publish
+                            };
+                            if let Err(err) = stream.send(msg, connection).await {
                                 // TODO: A single failure between nodes that cross host boundaries is probably normal, should make this more robust perhaps
-                                log::debug!("Failed to send data to subscriber: {err}, removing");
+                                log_send_failure(&err, connection);
                                 streams_to_remove.push(stream_idx);
                             }
                         }
@@ -145,6 +967,10 @@ impl Publication {
                                 streams.remove(stream_idx - removed_cnt);
                             },
                         );
+                        links.subscribers.set_count(streams.len());
+                    }
+                    Some(QueuedMessage::Flush(flushed)) => {
+                        let _ = flushed.send(());
                     }
                     None => {
                         log::debug!("No more senders for the publisher channel, exiting...");
@@ -156,15 +982,235 @@ impl Publication {
 
         Ok(Self {
             topic_type: topic_type.to_owned(),
-            _channel_task: listener_handle.into(),
+            md5sum: md5sum.to_owned(),
+            header_validation,
             listener_port,
+            host_addr,
+            responding_conn_header,
+            links,
+            bus_connections,
+            acceptor,
+            _channel_task: listener_handle.into(),
+            unix_socket: None,
             publish_sender: sender,
+            account,
+            handles: Arc::default(),
             _publish_task: publish_task.into(),
         })
     }
 
-    pub fn get_sender(&self) -> mpsc::Sender<Vec<u8>> {
-        self.publish_sender.clone()
+    /// Closes the connections of subscribers other than `subscribers`, the nodes the master lists as subscribed to the topic,
+    /// returning how many were closed. Only connections opened before `listed_at` are closed,
+    /// as a subscriber connecting since may have registered after the master made its list.
+    pub async fn reap_subscribers(&self, subscribers: &[String], listed_at: Instant) -> usize {
+        let mut links = self.links.links.write().await;
+        let count = links.len();
+        links.retain(|(_, _, connection)| {
+            let keep = connection.opened() >= listed_at
+                || subscribers.iter().any(|name| name == connection.peer());
+            if !keep {
+                log::info!(
+                    "Closing stale connection to {} on {}, which is no longer subscribed",
+                    connection.peer(),
+                    connection.topic()
+                );
+            }
+            keep
+        });
+        let reaped = count - links.len();
+        self.links.subscribers.set_count(links.len());
+        reaped
+    }
+
+    /// Starts publishing over UDPROS to the subscriber which asked for it in its requestTopic call,
+    /// returning the parameters to answer the call with. `hostname` is the host the node advertises.
+    pub async fn add_udp_subscriber(
+        &self,
+        request: &UdprosRequest,
+        hostname: &str,
+    ) -> Result<UdprosResponse, std::io::Error> {
+        self.header_validation
+            .check(&self.responding_conn_header, &request.header)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        if request.max_datagram_size <= udpros::HEADER_SIZE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Maximum datagram size of {} is too small",
+                    request.max_datagram_size
+                ),
+            ));
+        }
+        let socket = UdpSocket::from_std(addrs::bind_udp(self.host_addr)?)?;
+        let local_addr = socket.local_addr()?;
+        let route = tokio::net::lookup_host((request.host.as_str(), request.port))
+            .await?
+            .find_map(|addr| addrs::route(local_addr, addr))
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::AddrNotAvailable,
+                    format!(
+                        "Subscriber host {} has no address reachable from {local_addr}",
+                        request.host
+                    ),
+                )
+            })?;
+        let addr = addrs::canonical(route);
+        socket.connect(route).await?;
+        let port = local_addr.port();
+
+        let topic_name = &self.responding_conn_header.topic;
+        let connection = self.bus_connections.add(
+            &request.header.caller_id,
+            Direction::Outbound,
+            Transport::Udpros,
+            topic_name,
+            format!("UDPROS connection on port {port} to [{addr}]"),
+        );
+        let connection_id = connection.id() as u32;
+        let link = UdpLink::new(socket, connection_id, request.max_datagram_size);
+        let subscriber = SubscriberInfo {
+            caller_id: request.header.caller_id.clone(),
+            addr,
+            custom_fields: request.header.custom_fields.clone(),
+        };
+        self.links
+            .add(
+                SubscriberLink::Udp(link),
+                request.header.checksum,
+                connection,
+                subscriber,
+            )
+            .await;
+        log::debug!(
+            "Added UDPROS connection {connection_id} for topic {topic_name} to subscriber {addr}"
+        );
+        Ok(UdprosResponse {
+            host: hostname.to_owned(),
+            port,
+            connection_id,
+            max_datagram_size: request.max_datagram_size,
+            header: ConnectionHeader {
+                checksum: request.header.checksum,
+                ..self.responding_conn_header.clone()
+            },
+        })
+    }
+
+    /// The path of the socket the publication accepts UNIXROS subscribers on, bound on the first call
+    #[cfg(unix)]
+    pub fn unix_socket_path(&mut self) -> Result<PathBuf, std::io::Error> {
+        if let Some((path, _)) = &self.unix_socket {
+            return Ok(path.clone());
+        }
+        let socket = UnixSocket::bind()?;
+        let path = socket.path().to_owned();
+        let acceptor = self.acceptor.clone();
+        let task_name = format!(
+            "publication {} accepting on {}",
+            self.responding_conn_header.topic,
+            path.display()
+        );
+        let task = crate::tasks::spawn(&task_name, async move {
+            loop {
+                match socket.accept().await {
+                    Ok(stream) => {
+                        let path = socket.path().display();
+                        log::info!(
+                            "Received UNIXROS connection on {path} for topic {}",
+                            acceptor.responding_conn_header.topic
+                        );
+                        // Unix domain socket peers have no address, they're on our host
+                        let peer_addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 0));
+                        let transport_info = format!("UNIXROS connection on {path}");
+                        let acceptor = acceptor.clone();
+                        let task_name = format!(
+                            "subscriber connecting to {} on {path}",
+                            acceptor.responding_conn_header.topic
+                        );
+                        // As over TCPROS, a subscriber which stalls doesn't hold up the others
+                        crate::tasks::spawn(&task_name, async move {
+                            acceptor
+                                .accept(
+                                    stream.into(),
+                                    peer_addr,
+                                    Transport::Unixros,
+                                    transport_info,
+                                )
+                                .await;
+                        });
+                    }
+                    Err(err) => {
+                        log::warn!("Failed to accept on {}: {err}", socket.path().display())
+                    }
+                }
+            }
+        });
+        self.unix_socket = Some((path.clone(), task.into()));
+        Ok(path)
+    }
+
+    #[cfg(not(unix))]
+    pub fn unix_socket_path(&mut self) -> Result<PathBuf, std::io::Error> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "UNIXROS requires Unix domain sockets",
+        ))
+    }
+
+    /// Hands the publication's messages to `queue`, the node's own subscription of the topic, in-process
+    /// rather than over a connection to the node. The last message of a latching publication is replayed to it.
+    pub async fn connect_local(&self, queue: &SubscriptionQueue, account: Arc<TopicAccount>) {
+        let header = &self.responding_conn_header;
+        let topic_name = header.topic.as_str();
+        let connections = [Direction::Outbound, Direction::Inbound].map(|direction| {
+            self.bus_connections.add(
+                &header.caller_id,
+                direction,
+                Transport::Intraprocess,
+                topic_name,
+                "INTRAPROCESS connection".to_owned(),
+            )
+        });
+        let subscribers = &self.links.subscribers;
+        subscribers.local.connect(
+            queue.link(header.latching),
+            account,
+            header.clone(),
+            connections,
+        );
+        let latched = self.links.latched_msg.lock().unwrap().clone();
+        if let Some(data) = latched {
+            subscribers.local.send(data, None, true).await;
+        }
+        log::debug!("Publishing {topic_name} in-process to the node's own subscription");
+        subscribers.run_callbacks(&SubscriberInfo {
+            caller_id: header.caller_id.clone(),
+            addr: SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+            // The node's subscription of the topic is given the same options, so sends the same fields
+            custom_fields: header.custom_fields.clone(),
+        });
+    }
+
+    /// Stops handing messages to the node's own subscription, see [Publication::connect_local]
+    pub fn disconnect_local(&self) {
+        self.links.subscribers.local.disconnect();
+    }
+
+    pub fn get_handle(&self) -> PublicationHandle {
+        let queue = PublicationQueue {
+            sender: self.publish_sender.clone(),
+            account: self.account.clone(),
+            _handle: self.handles.clone(),
+            registration: None,
+            latching: self.responding_conn_header.latching,
+        };
+        (queue, self.links.subscribers.clone())
+    }
+
+    /// Whether any queues handed out by [Publication::get_handle] are still alive
+    pub fn has_handles(&self) -> bool {
+        Arc::strong_count(&self.handles) > 1
     }
 
     pub fn port(&self) -> u16 {
@@ -174,4 +1220,672 @@ impl Publication {
     pub fn topic_type(&self) -> &str {
         &self.topic_type
     }
+
+    pub fn md5sum(&self) -> &str {
+        &self.md5sum
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Publication, PublicationOptions, Publisher, SlowSubscribers};
+    use crate::ros1::{
+        bus_info::BusConnections,
+        callback_panics::CallbackGuard,
+        checksum,
+        memory_budgets::MemoryBudgets,
+        node::ShutdownReason,
+        subscription_queue::{QueueOverflowPolicy, SubscriptionQueue},
+        tcpros::ConnectionHeader,
+        udpros::{self, Reassembler, UdprosRequest},
+    };
+    use crate::StdMsg;
+    use bytes::Bytes;
+    use std::{collections::BTreeMap, net::Ipv4Addr, time::Duration};
+    use tokio::{
+        io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+        net::{TcpSocket, TcpStream, UdpSocket},
+        sync::mpsc,
+        time::timeout,
+    };
+
+    const MD5SUM: &str = "992ce8a1687cec8c8bd883ec73ca41d1";
+    // Small enough to split every message in tests across several datagrams
+    const MAX_DATAGRAM_SIZE: usize = udpros::HEADER_SIZE + 4;
+
+    async fn chatter(latching: bool) -> Publication {
+        chatter_guarded(latching, CallbackGuard::default()).await
+    }
+
+    async fn chatter_guarded(latching: bool, callback_guard: CallbackGuard) -> Publication {
+        chatter_with(
+            latching,
+            SlowSubscribers::default(),
+            callback_guard,
+            BusConnections::default(),
+        )
+        .await
+    }
+
+    async fn chatter_with(
+        latching: bool,
+        slow_subscribers: SlowSubscribers,
+        callback_guard: CallbackGuard,
+        bus_connections: BusConnections,
+    ) -> Publication {
+        Publication::new(
+            "/talker",
+            "/chatter",
+            "std_msgs/String",
+            1,
+            "string data",
+            MD5SUM,
+            PublicationOptions::default()
+                .latching(latching)
+                .slow_subscribers(slow_subscribers)
+                .bind_addr(Ipv4Addr::LOCALHOST.into())
+                .bus_connections(bus_connections)
+                .callback_guard(callback_guard),
+        )
+        .await
+        .unwrap()
+    }
+
+    fn subscriber_header() -> ConnectionHeader {
+        ConnectionHeader {
+            caller_id: "/listener".to_owned(),
+            latching: false,
+            msg_definition: "string data".to_owned(),
+            md5sum: MD5SUM.to_owned(),
+            topic: "/chatter".to_owned(),
+            topic_type: "std_msgs/String".to_owned(),
+            tcp_nodelay: false,
+            service: None,
+            persistent: false,
+            probe: false,
+            error: None,
+            checksum: false,
+            custom_fields: BTreeMap::new(),
+        }
+    }
+
+    // Connects to the publication as a subscriber, returning once it has answered with its header
+    async fn subscribe(publication: &Publication) -> TcpStream {
+        subscribe_with(publication, subscriber_header()).await
+    }
+
+    async fn subscribe_with(publication: &Publication, header: ConnectionHeader) -> TcpStream {
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, publication.port()))
+            .await
+            .unwrap();
+        stream
+            .write_all(&header.to_bytes(true).unwrap())
+            .await
+            .unwrap();
+        read_frame(&mut stream).await;
+        stream
+    }
+
+    // Reads a single length prefixed header or message
+    async fn read_frame(stream: &mut (impl AsyncRead + Unpin)) -> Vec<u8> {
+        let len = timeout(Duration::from_secs(5), stream.read_u32_le())
+            .await
+            .expect("Publication should send a frame")
+            .unwrap();
+        let mut frame = vec![0; len as usize];
+        stream.read_exact(&mut frame).await.unwrap();
+        frame
+    }
+
+    #[tokio::test]
+    async fn silent_subscribers_do_not_block_others() {
+        let publication = chatter(false).await;
+        let (_sender, subscribers) = publication.get_handle();
+        // Connects but never sends its connection header
+        let _silent = TcpStream::connect((Ipv4Addr::LOCALHOST, publication.port()))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let _stream = subscribe(&publication).await;
+        timeout(Duration::from_secs(5), async {
+            while subscribers.count() == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Subscriber connecting after a silent one should be accepted");
+    }
+
+    #[tokio::test]
+    async fn subscriber_connections_are_reported() {
+        let publication = chatter(false).await;
+        let (_sender, subscribers) = publication.get_handle();
+        let (connected, mut connections) = mpsc::unbounded_channel();
+        subscribers.on_connect(move |subscriber| {
+            connected.send(subscriber.clone()).unwrap();
+        });
+        assert_eq!(subscribers.count(), 0);
+
+        let stream = subscribe(&publication).await;
+        let subscriber = timeout(Duration::from_secs(5), connections.recv())
+            .await
+            .expect("Connection should be reported")
+            .unwrap();
+        assert_eq!(subscriber.caller_id, "/listener");
+        assert_eq!(subscriber.addr, stream.local_addr().unwrap());
+        assert!(subscriber.custom_fields.is_empty());
+        assert_eq!(subscribers.count(), 1);
+
+        let header = ConnectionHeader {
+            custom_fields: BTreeMap::from([("tenant".to_owned(), "fleet_a".to_owned())]),
+            ..subscriber_header()
+        };
+        let _stream = subscribe_with(&publication, header).await;
+        let subscriber = timeout(Duration::from_secs(5), connections.recv())
+            .await
+            .expect("Connection should be reported")
+            .unwrap();
+        assert_eq!(subscriber.custom_fields["tenant"], "fleet_a");
+    }
+
+    #[tokio::test]
+    async fn panicking_connect_callbacks_are_contained() {
+        let callback_guard = CallbackGuard::default();
+        let mut panics = callback_guard.subscribe();
+        let publication = chatter_guarded(false, callback_guard).await;
+        let (_sender, subscribers) = publication.get_handle();
+        subscribers.on_connect(|_| panic!("callback failed"));
+
+        let _first = subscribe(&publication).await;
+        let panic = timeout(Duration::from_secs(5), panics.recv())
+            .await
+            .expect("Panic should be reported")
+            .unwrap();
+        assert_eq!(panic.source, "subscriber connect callback of /chatter");
+        assert_eq!(panic.message, "callback failed");
+
+        // The publication still accepts subscribers
+        let _second = subscribe(&publication).await;
+        timeout(Duration::from_secs(5), panics.recv())
+            .await
+            .expect("Panic should be reported")
+            .unwrap();
+        assert_eq!(subscribers.count(), 2);
+    }
+
+    #[tokio::test]
+    async fn subscribers_of_any_type_are_accepted() {
+        let publication = chatter(false).await;
+        let (sender, _subscribers) = publication.get_handle();
+        let mut header = subscriber_header();
+        header.topic_type = "*".to_owned();
+        header.md5sum = "*".to_owned();
+        let mut stream = subscribe_with(&publication, header).await;
+        sender
+            .send(Bytes::from_static(b"\x02\0\0\0hi"))
+            .await
+            .unwrap();
+        assert_eq!(read_frame(&mut stream).await, b"hi");
+    }
+
+    #[tokio::test]
+    async fn latched_message_is_sent_to_new_subscribers() {
+        let publication = chatter(true).await;
+        let (sender, _subscribers) = publication.get_handle();
+        // Messages are published already framed with their length
+        sender
+            .send(Bytes::from_static(b"\x05\0\0\0first"))
+            .await
+            .unwrap();
+        sender
+            .send(Bytes::from_static(b"\x06\0\0\0second"))
+            .await
+            .unwrap();
+        // Gives the publish task time to handle them
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Only the last message is replayed
+        let mut stream = subscribe(&publication).await;
+        assert_eq!(read_frame(&mut stream).await, b"second");
+        assert!(
+            timeout(Duration::from_millis(100), stream.read_u8())
+                .await
+                .is_err(),
+            "Only the latched message should be sent"
+        );
+
+        sender
+            .send(Bytes::from_static(b"\x05\0\0\0third"))
+            .await
+            .unwrap();
+        assert_eq!(read_frame(&mut stream).await, b"third");
+    }
+
+    #[tokio::test]
+    async fn published_messages_are_shared_rather_than_copied() {
+        let publication = chatter_with(
+            true,
+            SlowSubscribers {
+                keep_latest: true,
+                ..Default::default()
+            },
+            CallbackGuard::default(),
+            BusConnections::default(),
+        )
+        .await;
+        let (sender, _subscribers) = publication.get_handle();
+        let mut first = subscribe(&publication).await;
+        let mut second = subscribe(&publication).await;
+
+        let msg = Bytes::from(b"\x02\0\0\0hi".to_vec());
+        let allocation = msg.as_ptr();
+        sender.send(msg).await.unwrap();
+        sender.flush().await;
+        assert_eq!(read_frame(&mut first).await, b"hi");
+        assert_eq!(read_frame(&mut second).await, b"hi");
+        // The latched message is the one published, not a copy of it
+        let latched = publication.links.latched_msg.lock().unwrap().clone();
+        assert_eq!(latched.unwrap().as_ptr(), allocation);
+
+        // Nor is the latched message replayed to the node's own subscription a copy
+        let subscription = SubscriptionQueue::new(1, QueueOverflowPolicy::default());
+        let mut receiver = subscription.subscribe();
+        publication
+            .connect_local(
+                &subscription,
+                MemoryBudgets::default().account("/chatter", None),
+            )
+            .await;
+        let message = timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .expect("Latched message should be replayed")
+            .unwrap();
+        assert!(message.latched);
+        assert_eq!(message.data.as_ptr(), allocation);
+    }
+
+    #[tokio::test]
+    async fn flush_waits_for_queued_messages() {
+        let publication = chatter(true).await;
+        let (sender, _subscribers) = publication.get_handle();
+        sender
+            .send(Bytes::from_static(b"\x05\0\0\0first"))
+            .await
+            .unwrap();
+        sender
+            .send(Bytes::from_static(b"\x06\0\0\0second"))
+            .await
+            .unwrap();
+        sender.flush().await;
+
+        // Nothing is left queued, so the last message is already latched
+        let mut stream = subscribe(&publication).await;
+        assert_eq!(read_frame(&mut stream).await, b"second");
+
+        // Flushing a queue whose publication has gone does not wait
+        drop(publication);
+        timeout(Duration::from_secs(1), sender.flush())
+            .await
+            .expect("Flushing should not wait once the publication has gone");
+    }
+
+    #[tokio::test]
+    async fn checksums_are_sent_to_subscribers_asking_for_them() {
+        let publication = chatter(false).await;
+        let (sender, _subscribers) = publication.get_handle();
+        let mut plain = subscribe(&publication).await;
+
+        let mut header = subscriber_header();
+        header.checksum = true;
+        let mut checked = TcpStream::connect((Ipv4Addr::LOCALHOST, publication.port()))
+            .await
+            .unwrap();
+        checked
+            .write_all(&header.to_bytes(true).unwrap())
+            .await
+            .unwrap();
+        let response = read_frame(&mut checked).await;
+        assert!(response.windows(14).any(|field| field == b"checksum=crc32"));
+
+        sender
+            .send(Bytes::from_static(b"\x02\0\0\0hi"))
+            .await
+            .unwrap();
+        assert_eq!(read_frame(&mut plain).await, b"hi");
+        let frame = read_frame(&mut checked).await;
+        let mut message = (frame.len() as u32).to_le_bytes().to_vec();
+        message.extend(frame);
+        assert_eq!(checksum::verify(&message).unwrap(), b"\x02\0\0\0hi");
+    }
+
+    #[tokio::test]
+    async fn stalled_subscribers_are_sent_only_the_latest_message() {
+        let bus_connections = BusConnections::default();
+        let publication = chatter_with(
+            false,
+            SlowSubscribers {
+                keep_latest: true,
+                ..Default::default()
+            },
+            CallbackGuard::default(),
+            bus_connections.clone(),
+        )
+        .await;
+        let (sender, subscribers) = publication.get_handle();
+        // Its small receive buffer fills up long before every message is published
+        let socket = TcpSocket::new_v4().unwrap();
+        socket.set_recv_buffer_size(4096).unwrap();
+        let mut stalled = socket
+            .connect((Ipv4Addr::LOCALHOST, publication.port()).into())
+            .await
+            .unwrap();
+        stalled
+            .write_all(&subscriber_header().to_bytes(true).unwrap())
+            .await
+            .unwrap();
+        read_frame(&mut stalled).await;
+        let mut live = subscribe(&publication).await;
+        timeout(Duration::from_secs(1), async {
+            while subscribers.count() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Both subscribers should be added");
+
+        // Far more than the socket buffers of the stalled subscriber hold
+        const MESSAGES: u32 = 10000;
+        tokio::spawn(async move {
+            for index in 0..MESSAGES {
+                let mut frame = Vec::with_capacity(1028);
+                frame.extend_from_slice(&1024u32.to_le_bytes());
+                frame.extend_from_slice(&index.to_le_bytes());
+                frame.resize(1028, 0);
+                sender.send(frame.into()).await.unwrap();
+            }
+        });
+
+        // The live subscriber isn't held up by the stalled one
+        while u32::from_le_bytes(read_frame(&mut live).await[..4].try_into().unwrap())
+            != MESSAGES - 1
+        {}
+        // Messages superseded while the stalled subscriber wasn't reading were skipped
+        let stalled_connection = &bus_connections.current()[0];
+        assert!(stalled_connection.messages() < MESSAGES as i32);
+    }
+
+    #[tokio::test]
+    async fn slow_subscribers_are_evicted() {
+        let bus_connections = BusConnections::default();
+        let publication = chatter_with(
+            false,
+            SlowSubscribers {
+                keep_latest: false,
+                send_timeout: Some(Duration::from_millis(100)),
+            },
+            CallbackGuard::default(),
+            bus_connections.clone(),
+        )
+        .await;
+        let (sender, subscribers) = publication.get_handle();
+        let socket = TcpSocket::new_v4().unwrap();
+        socket.set_recv_buffer_size(4096).unwrap();
+        let mut stalled = socket
+            .connect((Ipv4Addr::LOCALHOST, publication.port()).into())
+            .await
+            .unwrap();
+        stalled
+            .write_all(&subscriber_header().to_bytes(true).unwrap())
+            .await
+            .unwrap();
+        read_frame(&mut stalled).await;
+        let mut live = subscribe(&publication).await;
+        timeout(Duration::from_secs(1), async {
+            while subscribers.count() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Both subscribers should be added");
+
+        // Far more than the socket buffers of the stalled subscriber hold
+        const MESSAGES: u32 = 10000;
+        tokio::spawn(async move {
+            for index in 0..MESSAGES {
+                let mut frame = Vec::with_capacity(1028);
+                frame.extend_from_slice(&1024u32.to_le_bytes());
+                frame.extend_from_slice(&index.to_le_bytes());
+                frame.resize(1028, 0);
+                sender.send(frame.into()).await.unwrap();
+            }
+        });
+
+        // The live subscriber receives every message, while the stalled one is dropped
+        for index in 0..MESSAGES {
+            let frame = read_frame(&mut live).await;
+            assert_eq!(u32::from_le_bytes(frame[..4].try_into().unwrap()), index);
+        }
+        assert_eq!(subscribers.count(), 1);
+        assert_eq!(bus_connections.current().len(), 1);
+        // Once its buffered messages are read, the stalled subscriber finds its connection closed
+        let mut rest = vec![];
+        timeout(Duration::from_secs(5), stalled.read_to_end(&mut rest))
+            .await
+            .expect("Connection should be closed")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn large_messages_are_sent_whole() {
+        let publication = chatter(false).await;
+        let (sender, subscribers) = publication.get_handle();
+        let mut stream = subscribe(&publication).await;
+        timeout(Duration::from_secs(1), async {
+            while subscribers.count() == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Subscriber should be added");
+
+        // Each far larger than the socket buffers, so only partly written by a single write
+        const MESSAGE_SIZE: usize = 8 * 1024 * 1024;
+        tokio::spawn(async move {
+            for fill in 1..=3u8 {
+                let mut frame = (MESSAGE_SIZE as u32).to_le_bytes().to_vec();
+                frame.resize(MESSAGE_SIZE + 4, fill);
+                sender.send(frame.into()).await.unwrap();
+            }
+        });
+        for fill in 1..=3u8 {
+            let frame = read_frame(&mut stream).await;
+            assert_eq!(frame.len(), MESSAGE_SIZE);
+            assert!(frame.iter().all(|byte| *byte == fill));
+        }
+    }
+
+    #[tokio::test]
+    async fn unlisted_subscribers_are_reaped() {
+        let publication = chatter(false).await;
+        let (_sender, subscribers) = publication.get_handle();
+        let mut listed = subscribe(&publication).await;
+        let mut header = subscriber_header();
+        header.caller_id = "/unregistered".to_owned();
+        let mut stale = subscribe_with(&publication, header).await;
+        while subscribers.count() < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // Connections opened since the master listed the subscribers are kept
+        let listed_before = std::time::Instant::now() - Duration::from_secs(60);
+        let names = ["/listener".to_owned()];
+        assert_eq!(publication.reap_subscribers(&names, listed_before).await, 0);
+
+        let listed_at = std::time::Instant::now();
+        assert_eq!(publication.reap_subscribers(&names, listed_at).await, 1);
+        assert_eq!(subscribers.count(), 1);
+        let mut rest = vec![];
+        timeout(Duration::from_secs(1), stale.read_to_end(&mut rest))
+            .await
+            .expect("Connection of the unlisted subscriber should be closed")
+            .unwrap();
+        assert!(timeout(Duration::from_millis(100), listed.read_u8())
+            .await
+            .is_err());
+    }
+
+    // Receives datagrams sent from `port` until they make up a message
+    async fn receive_datagrams(
+        socket: &UdpSocket,
+        reassembler: &mut Reassembler,
+        port: u16,
+    ) -> Vec<u8> {
+        let mut datagram = vec![0; udpros::MAX_DATAGRAM_SIZE];
+        loop {
+            let (bytes, addr) = timeout(Duration::from_secs(5), socket.recv_from(&mut datagram))
+                .await
+                .expect("Publication should send a datagram")
+                .unwrap();
+            assert_eq!(addr.port(), port);
+            assert!(bytes <= MAX_DATAGRAM_SIZE);
+            if let Some(msg) = reassembler.push(&datagram[..bytes]).unwrap() {
+                return msg;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn udp_subscribers_receive_datagrams() {
+        let publication = chatter(true).await;
+        let (sender, subscribers) = publication.get_handle();
+        sender
+            .send(Bytes::from_static(b"\x06\0\0\0latest"))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let mut request = UdprosRequest {
+            header: subscriber_header(),
+            host: "localhost".to_owned(),
+            port: socket.local_addr().unwrap().port(),
+            max_datagram_size: MAX_DATAGRAM_SIZE,
+        };
+        let response = publication
+            .add_udp_subscriber(&request, "talker_host")
+            .await
+            .unwrap();
+        assert_eq!(response.host, "talker_host");
+        assert_eq!(response.header.md5sum, MD5SUM);
+        assert_eq!(subscribers.count(), 1);
+
+        // The latched message is sent first, split into datagrams of at most the requested size
+        let mut reassembler = Reassembler::new(response.connection_id);
+        assert_eq!(
+            receive_datagrams(&socket, &mut reassembler, response.port).await,
+            b"\x06\0\0\0latest"
+        );
+        sender
+            .send(Bytes::from_static(b"\x04\0\0\0next"))
+            .await
+            .unwrap();
+        assert_eq!(
+            receive_datagrams(&socket, &mut reassembler, response.port).await,
+            b"\x04\0\0\0next"
+        );
+
+        // Subscribers of a different type are refused
+        request.header.md5sum = "d41d8cd98f00b204e9800998ecf8427e".to_owned();
+        let err = publication
+            .add_udp_subscriber(&request, "talker_host")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_socket_subscribers_receive_messages() {
+        let mut publication = chatter(false).await;
+        let (sender, subscribers) = publication.get_handle();
+        let (connected, mut connections) = mpsc::unbounded_channel();
+        subscribers.on_connect(move |subscriber| {
+            connected.send(subscriber.clone()).unwrap();
+        });
+
+        // The socket is bound once and shared by every subscriber
+        let path = publication.unix_socket_path().unwrap();
+        assert_eq!(publication.unix_socket_path().unwrap(), path);
+        let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        stream
+            .write_all(&subscriber_header().to_bytes(true).unwrap())
+            .await
+            .unwrap();
+        read_frame(&mut stream).await;
+        let subscriber = timeout(Duration::from_secs(5), connections.recv())
+            .await
+            .expect("Connection should be reported")
+            .unwrap();
+        assert_eq!(subscriber.caller_id, "/listener");
+        assert!(subscriber.addr.ip().is_loopback());
+
+        sender
+            .send(Bytes::from_static(b"\x02\0\0\0hi"))
+            .await
+            .unwrap();
+        assert_eq!(read_frame(&mut stream).await, b"hi");
+
+        // The socket goes with the publication, once its aborted task is dropped
+        drop(publication);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn local_subscription_is_handed_messages_unserialized() {
+        let bus_connections = BusConnections::default();
+        let publication = chatter_with(
+            false,
+            SlowSubscribers {
+                keep_latest: false,
+                ..Default::default()
+            },
+            CallbackGuard::default(),
+            bus_connections.clone(),
+        )
+        .await;
+        let (queue, subscribers) = publication.get_handle();
+        let publisher = Publisher::<StdMsg<String>>::new(
+            "/chatter",
+            queue,
+            subscribers.clone(),
+            ShutdownReason::default(),
+        );
+        let subscription = SubscriptionQueue::new(1, QueueOverflowPolicy::default());
+        let mut receiver = subscription.subscribe();
+        publication
+            .connect_local(
+                &subscription,
+                MemoryBudgets::default().account("/chatter", None),
+            )
+            .await;
+        assert_eq!(subscribers.count(), 1);
+        assert_eq!(bus_connections.current().len(), 2);
+
+        publisher.publish(&"hello".to_owned().into()).await.unwrap();
+        let message = timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .expect("Message should be handed over")
+            .unwrap();
+        assert!(message.data.is_empty());
+        let local = message
+            .local
+            .expect("Message should be handed over as published");
+        assert_eq!(local.downcast::<StdMsg<String>>().unwrap().data, "hello");
+
+        publication.disconnect_local();
+        assert_eq!(subscribers.count(), 0);
+        assert!(bus_connections.current().is_empty());
+    }
 }
@@ -0,0 +1,213 @@
+//! This module contains the RosoutLogger which publishes log records to /rosout.
+
+use super::{node::NodeServerHandle, publisher::Publisher, Clock, NodeHandle};
+use abort_on_drop::ChildTask;
+use roslibrust_codegen::{RosMessageType, Time};
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::sync::mpsc;
+
+const ROSOUT_TOPIC: &str = "/rosout";
+// Matches the queue size used by roscpp for /rosout
+const ROSOUT_QUEUE_SIZE: usize = 100;
+
+// Hand written equivalents of std_msgs/Header and rosgraph_msgs/Log, so logging does not depend on generated messages
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub(super) struct Header {
+    pub seq: u32,
+    pub stamp: Time,
+    pub frame_id: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub(super) struct Log {
+    pub header: Header,
+    pub level: u8,
+    pub name: String,
+    pub msg: String,
+    pub file: String,
+    pub function: String,
+    pub line: u32,
+    pub topics: Vec<String>,
+}
+
+impl Log {
+    pub const DEBUG: u8 = 1;
+    pub const INFO: u8 = 2;
+    pub const WARN: u8 = 4;
+    pub const ERROR: u8 = 8;
+}
+
+impl RosMessageType for Log {
+    const ROS_TYPE_NAME: &'static str = "rosgraph_msgs/Log";
+    const MD5SUM: &'static str = "acffd30cd6b6de30f120938c17c593fb";
+    const DEFINITION: &'static str = r#"##
+## Severity level constants
+##
+byte DEBUG=1 #debug level
+byte INFO=2  #general level
+byte WARN=4  #warning level
+byte ERROR=8 #error level
+byte FATAL=16 #fatal/critical level
+##
+## Fields
+##
+Header header
+byte level
+string name # name of the node
+string msg # message
+string file # file the message came from
+string function # function the message came from
+uint32 line # line the message came from
+string[] topics # topic names that the node publishes
+
+================================================================================
+MSG: std_msgs/Header
+# Standard metadata for higher-level stamped data types.
+# This is generally used to communicate timestamped data
+# in a particular coordinate frame.
+#
+# sequence ID: consecutively increasing ID
+uint32 seq
+#Two-integer timestamp that is expressed as:
+# * stamp.sec: seconds (stamp_secs) since epoch (in Python the variable is called 'secs')
+# * stamp.nsec: nanoseconds since stamp_secs (in Python the variable is called 'nsecs')
+# time-handling sugar is provided by the client library
+time stamp
+#Frame this data is associated with
+string frame_id
+"#;
+}
+
+/// A [log::Log] implementation which publishes records to `/rosout` as `rosgraph_msgs/Log`,
+/// so they show up in tools like rqt_console alongside the logs of roscpp and rospy nodes.
+///
+/// Records are tagged with the node's name, the source file, line and module of the log call,
+/// and the topics the node publishes. `trace` and `debug` records are both reported at the DEBUG level.
+/// Applications using `tracing` can forward their events to this logger with tracing's `log` feature.
+///
+/// Records logged by roslibrust itself are not published, as publishing them would log further records,
+/// but are still passed to the fallback logger.
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/my_node").await?;
+/// roslibrust::RosoutLogger::new(&nh)
+///     .await?
+///     .with_fallback(simple_logger::SimpleLogger::new())
+///     .init(log::LevelFilter::Info)?;
+/// log::info!("Hello rqt_console");
+/// # Ok(())
+/// # }
+/// ```
+pub struct RosoutLogger {
+    node_name: String,
+    clock: Clock,
+    seq: AtomicU32,
+    sender: mpsc::UnboundedSender<Log>,
+    fallback: Option<Box<dyn log::Log>>,
+    _publish_task: ChildTask<()>,
+}
+
+impl RosoutLogger {
+    /// Advertises `/rosout` on the node behind `nh`.
+    /// The logger does not keep the node alive, records logged after the node shuts down are only passed to the fallback.
+    pub async fn new(nh: &NodeHandle) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let publisher = nh.advertise::<Log>(ROSOUT_TOPIC, ROSOUT_QUEUE_SIZE).await?;
+        let node = nh.inner.without_ownership();
+        // rosout reports nodes by their global name
+        let node_name = format!("/{}", node.get_node_name().await?.trim_start_matches('/'));
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let publish_task = crate::tasks::spawn(
+            &format!("rosout logger of {node_name}"),
+            Self::publish(publisher, node, receiver),
+        );
+
+        Ok(Self {
+            node_name,
+            clock: nh.clock().clone(),
+            seq: AtomicU32::new(0),
+            sender,
+            fallback: None,
+            _publish_task: publish_task.into(),
+        })
+    }
+
+    /// Also passes every record to `logger`, e.g. to keep printing logs to the console.
+    pub fn with_fallback(mut self, logger: impl log::Log + 'static) -> Self {
+        self.fallback = Some(Box::new(logger));
+        self
+    }
+
+    /// Installs this as the global logger, see [log::set_boxed_logger].
+    pub fn init(self, level: log::LevelFilter) -> Result<(), log::SetLoggerError> {
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+
+    // Publishing is async, so records are handed off to a task
+    async fn publish(
+        publisher: Publisher<Log>,
+        node: NodeServerHandle,
+        mut receiver: mpsc::UnboundedReceiver<Log>,
+    ) {
+        while let Some(mut msg) = receiver.recv().await {
+            let Ok(publications) = node.get_publications().await else {
+                // The node has shut down
+                break;
+            };
+            msg.topics = publications.into_iter().map(|(topic, _)| topic).collect();
+            if publisher.publish(&msg).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+impl log::Log for RosoutLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        // Filtering is left to the global max level
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        if let Some(fallback) = &self.fallback {
+            if fallback.enabled(record.metadata()) {
+                fallback.log(record);
+            }
+        }
+        if record.target().starts_with("roslibrust") {
+            return;
+        }
+
+        let level = match record.level() {
+            log::Level::Error => Log::ERROR,
+            log::Level::Warn => Log::WARN,
+            log::Level::Info => Log::INFO,
+            log::Level::Debug | log::Level::Trace => Log::DEBUG,
+        };
+        let msg = Log {
+            header: Header {
+                seq: self.seq.fetch_add(1, Ordering::Relaxed),
+                stamp: self.clock.now(),
+                frame_id: String::new(),
+            },
+            level,
+            name: self.node_name.clone(),
+            msg: record.args().to_string(),
+            file: record.file().unwrap_or_default().to_owned(),
+            function: record.module_path().unwrap_or_default().to_owned(),
+            line: record.line().unwrap_or_default(),
+            // Filled in by the publishing task
+            topics: vec![],
+        };
+        // Only fails once the node has shut down
+        let _ = self.sender.send(msg);
+    }
+
+    fn flush(&self) {
+        if let Some(fallback) = &self.fallback {
+            fallback.flush();
+        }
+    }
+}
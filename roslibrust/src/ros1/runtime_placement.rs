@@ -0,0 +1,92 @@
+//! This module contains the choice of runtime a node runs its networking on, see [super::NodeHandleOptions::runtime].
+
+use tokio::{runtime::Handle, sync::oneshot};
+
+/// Where a node runs the tasks behind its networking: its xmlrpc server, its requests to the master,
+/// and the connections of its publishers, subscribers and services. Set with [super::NodeHandleOptions::runtime].
+///
+/// Tasks serving the application's side of the node, such as the workers of a [super::ParallelSubscriber]
+/// or the callbacks of a [super::Timer], run wherever they are created from.
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// use roslibrust::{NodeHandleOptions, RuntimePlacement};
+/// let options = NodeHandleOptions::new("http://localhost:11311", "/planner")
+///     .runtime(RuntimePlacement::DedicatedThread);
+/// let nh = roslibrust::NodeHandle::new_with_options(options).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub enum RuntimePlacement {
+    /// The runtime the node is created from. The default.
+    #[default]
+    Ambient,
+    /// The runtime of the given handle, e.g. one set aside for networking
+    Handle(Handle),
+    /// A current-thread runtime on a thread of the node's own, which stops once the node shuts down,
+    /// isolating the node's latency from heavy work on the application's runtime
+    DedicatedThread,
+}
+
+/// Keeps the thread of a node's dedicated runtime running until dropped, which drops any tasks left on it
+pub(crate) struct DedicatedRuntime {
+    _stop: oneshot::Sender<()>,
+}
+
+impl RuntimePlacement {
+    /// The handle of the runtime to create the node `node_name` on, None for the ambient runtime,
+    /// starting the dedicated runtime if there is one
+    pub(crate) fn start(
+        &self,
+        node_name: &str,
+    ) -> Result<(Option<Handle>, Option<DedicatedRuntime>), std::io::Error> {
+        match self {
+            RuntimePlacement::Ambient => Ok((None, None)),
+            RuntimePlacement::Handle(handle) => Ok((Some(handle.clone()), None)),
+            RuntimePlacement::DedicatedThread => {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()?;
+                let handle = runtime.handle().clone();
+                let (stop, stopped) = oneshot::channel();
+                std::thread::Builder::new()
+                    .name(format!("roslibrust {node_name}"))
+                    .spawn(move || {
+                        // A current-thread runtime only runs its tasks within block_on
+                        let _ = runtime.block_on(stopped);
+                    })?;
+                Ok((Some(handle), Some(DedicatedRuntime { _stop: stop })))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RuntimePlacement;
+    use tokio::sync::oneshot;
+
+    #[tokio::test]
+    async fn dedicated_runtimes_run_until_dropped() {
+        let (handle, runtime) = RuntimePlacement::DedicatedThread.start("/node").unwrap();
+        let handle = handle.unwrap();
+        let thread = handle
+            .spawn(async { std::thread::current().name().map(str::to_owned) })
+            .await
+            .unwrap();
+        assert_eq!(thread.as_deref(), Some("roslibrust /node"));
+
+        let (sender, receiver) = oneshot::channel::<()>();
+        handle.spawn(async move {
+            let _sender = sender;
+            std::future::pending::<()>().await
+        });
+        drop(runtime);
+        // Tasks left on the runtime are dropped along with it
+        assert!(receiver.await.is_err());
+
+        let (handle, runtime) = RuntimePlacement::Ambient.start("/node").unwrap();
+        assert!(handle.is_none() && runtime.is_none());
+    }
+}
@@ -0,0 +1,320 @@
+//! This module contains the ServiceClient which calls ROS1 services directly over TCPROS.
+
+use super::{
+    node::NodeServerHandle,
+    peer_errors::PeerErrorKind,
+    tcpros::{read_frame, ConnectionHeader, DEFAULT_MAX_MESSAGE_SIZE},
+};
+use crate::response_cache::ResponseCache;
+use roslibrust_codegen::RosServiceType;
+use std::{collections::BTreeMap, marker::PhantomData, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    sync::Mutex,
+};
+
+/// Errors describing why a service refused to talk to a [ServiceClient] during the connection handshake.
+/// These are returned boxed from [ServiceClient::call] and [ServiceClient::probe] and can be recovered with `downcast_ref`.
+#[derive(thiserror::Error, Debug)]
+pub enum ServiceHandshakeError {
+    #[error("Service {service} rejected connection: {reason}")]
+    Rejected { service: String, reason: String },
+    #[error("Tried to call {service} as {expected}, but the service reported type {received}")]
+    TypeMismatch {
+        service: String,
+        expected: String,
+        received: String,
+    },
+    #[error("Tried to call {service}, but md5sums do not match. Expected {expected}, received {received}")]
+    Md5sumMismatch {
+        service: String,
+        expected: String,
+        received: String,
+    },
+}
+
+/// A typed client for a single ROS1 service, created with [super::NodeHandle::service_client].
+///
+/// The service's provider is looked up from the master on each new connection, so calls keep
+/// working if the service is restarted elsewhere.
+/// Persistent clients hold their connection open between calls and reconnect after any error.
+/// ```no_run
+/// # roslibrust_codegen_macro::find_and_generate_ros_messages!(
+/// #    "assets/ros1_common_interfaces"
+/// # );
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "service_client_node").await?;
+/// let client = nh.service_client::<rosapi::GetTime>("/rosapi/get_time", false);
+/// let response = client.call(&rosapi::GetTimeRequest {}).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ServiceClient<T: RosServiceType> {
+    node: NodeServerHandle,
+    service_name: String,
+    persistent: bool,
+    // Skips verifying the service's type and md5sum
+    wildcard_md5sum: bool,
+    cache: Option<ResponseCache>,
+    // Only populated for persistent clients between calls
+    connection: Mutex<Option<TcpStream>>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: RosServiceType> ServiceClient<T> {
+    pub(crate) fn new(node: NodeServerHandle, service_name: &str, persistent: bool) -> Self {
+        Self {
+            // An invalid name is kept as is, so the error surfaces when looking the service up
+            service_name: node
+                .resolve_name(service_name)
+                .unwrap_or_else(|_| service_name.to_owned()),
+            node,
+            persistent,
+            wildcard_md5sum: false,
+            cache: None,
+            connection: Mutex::new(None),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// When enabled the client sends the wildcard md5sum `*` and accepts whatever type the service reports.
+    /// Useful when the service definition has changed in a compatible way, e.g. only its comments were edited.
+    pub fn wildcard_md5sum(mut self, enabled: bool) -> Self {
+        self.wildcard_md5sum = enabled;
+        self
+    }
+
+    /// Caches successful responses for `ttl`, so repeated calls with an identical request are answered
+    /// without contacting the service. Only suitable for idempotent services, e.g. map or parameter lookups.
+    pub fn cache_responses(mut self, ttl: Duration) -> Self {
+        self.cache = Some(ResponseCache::new(ttl));
+        self
+    }
+
+    /// The resolved name of the service being called, after applying the node's remappings
+    pub fn service_name(&self) -> &str {
+        &self.service_name
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.persistent
+    }
+
+    /// Calls the service with `request` and waits for its response.
+    /// An error is returned if the service cannot be reached, its type or md5sum do not match
+    /// (see [ServiceHandshakeError]), or the service itself reports a failure.
+    pub async fn call(
+        &self,
+        request: &T::Request,
+    ) -> Result<T::Response, Box<dyn std::error::Error + Send + Sync>> {
+        let Some((cache, key)) = self.cache.as_ref().zip(ResponseCache::key(request)) else {
+            return self.call_uncached(request).await;
+        };
+        if let Some(response) = cache.get(&key) {
+            return Ok(response);
+        }
+        let response = self.call_uncached(request).await?;
+        cache.insert(key, &response);
+        Ok(response)
+    }
+
+    async fn call_uncached(
+        &self,
+        request: &T::Request,
+    ) -> Result<T::Response, Box<dyn std::error::Error + Send + Sync>> {
+        let mut connection = self.connection.lock().await;
+        let mut stream = match connection.take() {
+            Some(stream) => stream,
+            None => self.connect(false).await?.0,
+        };
+
+        // A transport error leaves the stream in an unknown state so it is dropped rather than reused
+        let response = Self::exchange(&mut stream, request).await?;
+        if self.persistent {
+            *connection = Some(stream);
+        }
+        response
+    }
+
+    /// Connects to the service without making a call and returns the header the service replied with.
+    /// Useful for checking a service is available and inspecting its type.
+    pub async fn probe(
+        &self,
+    ) -> Result<ConnectionHeader, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.connect(true).await?.1)
+    }
+
+    async fn connect(
+        &self,
+        probe: bool,
+    ) -> Result<(TcpStream, ConnectionHeader), Box<dyn std::error::Error + Send + Sync>> {
+        let service_uri = self.node.lookup_service(&self.service_name).await?;
+        let address = service_uri
+            .strip_prefix("rosrpc://")
+            .ok_or_else(|| format!("Unsupported service URI {service_uri}"))?
+            .trim_end_matches('/');
+        let mut stream = TcpStream::connect(address).await?;
+
+        let conn_header = ConnectionHeader {
+            caller_id: self.node.get_node_name().await?,
+            latching: false,
+            msg_definition: String::new(),
+            md5sum: if self.wildcard_md5sum {
+                "*".to_owned()
+            } else {
+                T::MD5SUM.to_owned()
+            },
+            topic: String::new(),
+            topic_type: T::ROS_SERVICE_NAME.to_owned(),
+            tcp_nodelay: false,
+            service: Some(self.service_name.clone()),
+            persistent: self.persistent,
+            probe,
+            error: None,
+            checksum: false,
+            custom_fields: BTreeMap::new(),
+        };
+        stream.write_all(&conn_header.to_bytes(true)?).await?;
+
+        let responded_header = match ConnectionHeader::from_bytes(
+            &read_frame(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).await?,
+        ) {
+            Ok(header) => header,
+            Err(err) => {
+                self.report(PeerErrorKind::Malformed, address, err.to_string());
+                return Err(Box::new(err));
+            }
+        };
+        let peer = if responded_header.caller_id.is_empty() {
+            address
+        } else {
+            &responded_header.caller_id
+        };
+        if let Some(reason) = &responded_header.error {
+            self.report(PeerErrorKind::HandshakeRefused, peer, reason);
+            return Err(Box::new(ServiceHandshakeError::Rejected {
+                service: self.service_name.clone(),
+                reason: reason.clone(),
+            }));
+        }
+        if !self.wildcard_md5sum {
+            if let Err(err) = verify_header::<T>(&self.service_name, &responded_header) {
+                self.report(PeerErrorKind::HeaderRejected, peer, err.to_string());
+                return Err(Box::new(err));
+            }
+        }
+        log::debug!("Established connection with service {}", self.service_name);
+        Ok((stream, responded_header))
+    }
+
+    fn report(&self, kind: PeerErrorKind, peer: &str, message: impl Into<String>) {
+        self.node
+            .peer_errors()
+            .report(kind, peer, &self.service_name, message);
+    }
+
+    // The outer result covers transport failures, the inner one a failure reported by the service
+    async fn exchange(
+        stream: &mut TcpStream,
+        request: &T::Request,
+    ) -> Result<
+        Result<T::Response, Box<dyn std::error::Error + Send + Sync>>,
+        Box<dyn std::error::Error + Send + Sync>,
+    > {
+        // serde_rosmsg already prefixes the serialized request with its length
+        let request = serde_rosmsg::to_vec(request).map_err(|err| format!("{err:?}"))?;
+        stream.write_all(&request).await?;
+
+        let ok = stream.read_u8().await?;
+        let frame = read_frame(stream, DEFAULT_MAX_MESSAGE_SIZE).await?;
+        if ok != 0 {
+            Ok(serde_rosmsg::from_slice(&frame).map_err(|err| format!("{err:?}").into()))
+        } else {
+            let message = String::from_utf8_lossy(&frame[4..]).into_owned();
+            Ok(Err(message.into()))
+        }
+    }
+}
+
+// Mirrors the md5sum check subscribers make of publishers, with "*" accepted from either side
+fn verify_header<T: RosServiceType>(
+    service_name: &str,
+    header: &ConnectionHeader,
+) -> Result<(), ServiceHandshakeError> {
+    // Not every server implementation reports the service type
+    if !header.topic_type.is_empty()
+        && header.topic_type != "*"
+        && header.topic_type != T::ROS_SERVICE_NAME
+    {
+        return Err(ServiceHandshakeError::TypeMismatch {
+            service: service_name.to_owned(),
+            expected: T::ROS_SERVICE_NAME.to_owned(),
+            received: header.topic_type.clone(),
+        });
+    }
+    if header.md5sum != T::MD5SUM && header.md5sum != "*" {
+        return Err(ServiceHandshakeError::Md5sumMismatch {
+            service: service_name.to_owned(),
+            expected: T::MD5SUM.to_owned(),
+            received: header.md5sum.clone(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{verify_header, ServiceHandshakeError};
+    use crate::ConnectionHeader;
+    use roslibrust_codegen::RosServiceType;
+    use std::collections::BTreeMap;
+
+    struct AddTwoInts;
+    impl RosServiceType for AddTwoInts {
+        const ROS_SERVICE_NAME: &'static str = "rospy_tutorials/AddTwoInts";
+        const MD5SUM: &'static str = "6a2e34150c00229791cc89ff309fff21";
+        type Request = ();
+        type Response = ();
+    }
+
+    fn server_header(topic_type: &str, md5sum: &str) -> ConnectionHeader {
+        ConnectionHeader {
+            caller_id: "/server".to_owned(),
+            latching: false,
+            msg_definition: String::new(),
+            md5sum: md5sum.to_owned(),
+            topic: String::new(),
+            topic_type: topic_type.to_owned(),
+            tcp_nodelay: false,
+            service: None,
+            persistent: false,
+            probe: false,
+            error: None,
+            checksum: false,
+            custom_fields: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn verify_service_header() {
+        let md5sum = AddTwoInts::MD5SUM;
+        let name = AddTwoInts::ROS_SERVICE_NAME;
+        assert!(verify_header::<AddTwoInts>("/add", &server_header(name, md5sum)).is_ok());
+        assert!(verify_header::<AddTwoInts>("/add", &server_header("", md5sum)).is_ok());
+        assert!(verify_header::<AddTwoInts>("/add", &server_header(name, "*")).is_ok());
+
+        assert!(matches!(
+            verify_header::<AddTwoInts>("/add", &server_header("std_srvs/Empty", md5sum)),
+            Err(ServiceHandshakeError::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            verify_header::<AddTwoInts>(
+                "/add",
+                &server_header(name, "d41d8cd98f00b204e9800998ecf8427e")
+            ),
+            Err(ServiceHandshakeError::Md5sumMismatch { .. })
+        ));
+    }
+}
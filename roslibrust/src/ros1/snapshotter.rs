@@ -0,0 +1,182 @@
+//! This module contains the Snapshotter which keeps a rolling window of the most recent messages
+//! on a set of topics and dumps them to a bag file on demand.
+
+use super::{
+    bag::{BagConnection, BagWriter},
+    node::NodeHandle,
+    subscription_queue::QueueReceiver,
+    Clock,
+};
+use abort_on_drop::ChildTask;
+use roslibrust_codegen::{RosMessageType, Time};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::PathBuf,
+    sync::Arc,
+};
+use tokio::sync::{broadcast, Mutex};
+
+struct TopicBuffer {
+    connection: BagConnection,
+    depth: usize,
+    // Raw messages as received from the subscription along with the time they were received
+    messages: VecDeque<(Time, Vec<u8>)>,
+}
+
+/// Holds ring buffers of the last N raw messages received on each configured topic.
+///
+/// Messages are kept in their serialized form and are only written out when [Snapshotter::snapshot] is called.
+/// This is intended for post-incident debugging, e.g. triggering a snapshot when a fault is detected to capture
+/// the moments leading up to it.
+/// ```no_run
+/// # roslibrust_codegen_macro::find_and_generate_ros_messages!(
+/// #    "assets/ros1_common_interfaces/std_msgs"
+/// # );
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "snapshot_node").await?;
+/// let mut snapshotter = roslibrust::Snapshotter::new(&nh);
+/// snapshotter.add_topic::<std_msgs::String>("/chatter", 100).await?;
+/// // ... later, when something interesting happens
+/// snapshotter.snapshot("/tmp/incident.bag").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Snapshotter {
+    // Held to keep the underlying node alive for as long as we are buffering
+    node: NodeHandle,
+    buffers: Arc<Mutex<BTreeMap<String, TopicBuffer>>>,
+    buffer_tasks: Vec<ChildTask<()>>,
+}
+
+impl Snapshotter {
+    pub fn new(node: &NodeHandle) -> Self {
+        Self {
+            node: node.clone(),
+            buffers: Arc::new(Mutex::new(BTreeMap::new())),
+            buffer_tasks: vec![],
+        }
+    }
+
+    /// Begins buffering the last `depth` messages received on `topic`.
+    /// Subscribes to the topic via the node, if the node is already subscribed to the topic the existing
+    /// subscription is shared.
+    pub async fn add_topic<T: RosMessageType>(
+        &mut self,
+        topic: &str,
+        depth: usize,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let depth = depth.max(1);
+        let receiver = self
+            .node
+            .inner
+            .register_subscriber::<T>(topic, depth)
+            .await?;
+        {
+            let mut buffers = self.buffers.lock().await;
+            buffers.insert(
+                topic.to_owned(),
+                TopicBuffer {
+                    connection: BagConnection {
+                        topic: topic.to_owned(),
+                        topic_type: T::ROS_TYPE_NAME.to_owned(),
+                        md5sum: T::MD5SUM.to_owned(),
+                        msg_definition: T::DEFINITION.to_owned(),
+                    },
+                    depth,
+                    messages: VecDeque::with_capacity(depth),
+                },
+            );
+        }
+        let handle = crate::tasks::spawn(
+            &format!("snapshotter {topic}"),
+            Self::buffer_topic(
+                topic.to_owned(),
+                receiver,
+                self.buffers.clone(),
+                self.node.clock().clone(),
+            ),
+        );
+        self.buffer_tasks.push(handle.into());
+        Ok(())
+    }
+
+    /// Writes the currently buffered messages for all topics to a bag file at `path`.
+    /// Returns the number of messages written.
+    pub async fn snapshot(&self, path: impl Into<PathBuf>) -> std::io::Result<usize> {
+        let path = path.into();
+        let topics = {
+            let buffers = self.buffers.lock().await;
+            buffers
+                .values()
+                .map(|buffer| (buffer.connection.clone(), buffer.messages.clone()))
+                .collect::<Vec<_>>()
+        };
+        let task_name = format!("snapshot to {}", path.display());
+        crate::tasks::spawn_blocking(&task_name, move || {
+            let mut bag = BagWriter::create(&path)?;
+            let mut count = 0;
+            for (connection, messages) in topics {
+                let connection_id = bag.add_connection(connection);
+                for (time, data) in messages {
+                    // Raw messages carry their TCPROS length prefix which is not stored in the bag
+                    bag.write_message(connection_id, &time, data.get(4..).unwrap_or_default())?;
+                    count += 1;
+                }
+            }
+            bag.finish()?;
+            log::info!("Wrote snapshot of {count} messages to {}", path.display());
+            Ok(count)
+        })
+        .await
+        .map_err(std::io::Error::other)?
+    }
+
+    /// Discards all currently buffered messages
+    pub async fn clear(&self) {
+        let mut buffers = self.buffers.lock().await;
+        for buffer in buffers.values_mut() {
+            buffer.messages.clear();
+        }
+    }
+
+    async fn buffer_topic(
+        topic: String,
+        mut receiver: QueueReceiver,
+        buffers: Arc<Mutex<BTreeMap<String, TopicBuffer>>>,
+        clock: Clock,
+    ) {
+        loop {
+            match receiver.recv().await {
+                Ok(message) => {
+                    let time = clock.now();
+                    let data = match message.into_data() {
+                        Ok(data) => data,
+                        Err(err) => {
+                            log::warn!(
+                                "Snapshotter failed to serialize a message on {topic}: {err}"
+                            );
+                            continue;
+                        }
+                    };
+                    let mut buffers = buffers.lock().await;
+                    if let Some(buffer) = buffers.get_mut(&topic) {
+                        if buffer.messages.len() >= buffer.depth {
+                            buffer.messages.pop_front();
+                        }
+                        buffer.messages.push_back((time, data));
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(count)) => {
+                    log::warn!("Snapshotter fell behind on topic {topic}, {count} messages were not buffered");
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    log::debug!(
+                        "Subscription to {topic} closed, snapshotter no longer buffering it"
+                    );
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -1,38 +1,382 @@
-use super::tcpros::ConnectionHeader;
+use super::{
+    addrs,
+    bus_info::{BusConnections, Direction, Transport},
+    checksum::{self, ChecksumFailures},
+    intra_process::LocalMessage,
+    memory_budgets::{Charge, MemoryBudgets, TopicAccount},
+    node::ShutdownReason,
+    peer_errors::{PeerErrorKind, PeerErrors},
+    subscription_queue::{QueueOverflowPolicy, QueueReceiver, SubscriberStats, SubscriptionQueue},
+    tcpros::{self, ConnectionHeader, HeaderValidation, SocketOptions},
+    tls::{TcprosSecurity, TcprosStream},
+    topic_endpoints::TopicEndpoints,
+    udpros::{self, Reassembler, UdprosRequest, UdprosResponse},
+    unixros::{UnixrosRequest, UnixrosResponse},
+    xmlrpc_client::XmlRpcClient,
+};
+use crate::ReconnectPolicy;
 use abort_on_drop::ChildTask;
+use bytes::Bytes;
 use roslibrust_codegen::RosMessageType;
-use std::{marker::PhantomData, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    marker::PhantomData,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    net::TcpStream,
-    sync::{broadcast, RwLock},
+    net::{TcpStream, UdpSocket},
+    sync::broadcast,
 };
 
+/// Message data as received from a publisher, before deserialization
+#[derive(Clone, Debug)]
+pub(crate) struct RawMessage {
+    // Empty for messages published in-process, shared between the receivers of the message
+    pub data: Bytes,
+    // The message itself when published by the node in-process, see TopicOptions::intra_process
+    pub local: Option<LocalMessage>,
+    pub latched: bool,
+    // The connection header of the publisher the message came from
+    pub publisher: Arc<ConnectionHeader>,
+    // Releases the message's bytes from the memory budgets once every receiver has read it
+    pub _charge: Arc<Charge>,
+}
+
+impl RawMessage {
+    /// Deserializes the message, or clones it if it was published in-process as a `T`
+    pub fn decode<T: RosMessageType>(&self) -> Result<T, serde_rosmsg::Error> {
+        match &self.local {
+            Some(local) => match local.downcast::<T>() {
+                Some(message) => Ok(message.clone()),
+                None => serde_rosmsg::from_slice(&local.serialize()?),
+            },
+            None => serde_rosmsg::from_slice(&self.data),
+        }
+    }
+
+    /// The message as sent over TCPROS starting with its length, serializing it if it was published in-process
+    pub fn into_data(self) -> Result<Vec<u8>, serde_rosmsg::Error> {
+        match self.local {
+            Some(local) => local.serialize(),
+            None => Ok(self.data.into()),
+        }
+    }
+}
+
+/// Details of how a message was received, returned alongside it by [Subscriber::next_with_info]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MessageInfo {
+    /// Whether the message is the one a latching publisher sends each subscriber on connecting,
+    /// i.e. the first message on a connection to a publisher advertising itself as latching.
+    /// Such a message may have been published long before it was received, so holds possibly stale state
+    /// rather than a fresh update. The first message from a latching publisher which had not published yet
+    /// when the connection was made is also reported as latched.
+    /// Subscribers joining a topic the node is already subscribed to first receive the last message of each
+    /// latching publisher already connected, also reported as latched.
+    pub latched: bool,
+    /// The connection header of the publisher the message came from,
+    /// including any custom fields it sent, see [super::TopicOptions::header_field]
+    pub publisher: Arc<ConnectionHeader>,
+}
+
+/// A message a [Subscriber] failed to deserialize, sent to the dead letter stream of
+/// [DeserializeErrorPolicy::DeadLetter] for offline inspection
+#[derive(Clone, Debug)]
+pub struct DeadLetter {
+    /// The type the message was expected to be
+    pub topic_type: &'static str,
+    /// The message as received, including its TCPROS length prefix
+    pub data: Vec<u8>,
+    /// Why the message could not be deserialized
+    pub error: String,
+}
+
+/// What a [Subscriber] does with a message it fails to deserialize, e.g. because the publisher's message definition
+/// drifted from ours or the data was corrupted, set with [Subscriber::on_deserialize_error].
+#[derive(Clone, Debug, Default)]
+pub enum DeserializeErrorPolicy {
+    /// [Subscriber::next] returns the error, the default
+    #[default]
+    Error,
+    /// The message is skipped with a warning
+    Skip,
+    /// The message is sent to the dead letter stream and skipped.
+    /// Messages which don't fit in the channel, or arrive once its receiver is dropped, are skipped with a warning.
+    DeadLetter(tokio::sync::mpsc::Sender<DeadLetter>),
+}
+
+impl DeserializeErrorPolicy {
+    // Disposes of a message which failed to deserialize, returning the error if the caller should see it
+    fn handle<E: std::fmt::Display>(
+        &self,
+        topic_type: &'static str,
+        data: Vec<u8>,
+        err: E,
+    ) -> Result<(), E> {
+        match self {
+            DeserializeErrorPolicy::Error => return Err(err),
+            DeserializeErrorPolicy::Skip => {
+                log::warn!("Skipping {topic_type} which failed to deserialize: {err}")
+            }
+            DeserializeErrorPolicy::DeadLetter(sender) => {
+                let letter = DeadLetter {
+                    topic_type,
+                    data,
+                    error: err.to_string(),
+                };
+                if let Err(err) = sender.try_send(letter) {
+                    log::warn!("Dropping {topic_type} which failed to deserialize, the dead letter stream is unavailable: {err}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Receives the messages of a topic, see [super::NodeHandle::subscribe].
+/// The node unsubscribes from the topic once every subscriber of it is dropped, without waiting for the master
+/// to be told. Use [Subscriber::close] to wait for it.
 pub struct Subscriber<T> {
-    receiver: broadcast::Receiver<Vec<u8>>,
+    receiver: QueueReceiver,
+    shutdown_reason: ShutdownReason,
+    on_deserialize_error: DeserializeErrorPolicy,
     _phantom: PhantomData<T>,
 }
 
 impl<T: RosMessageType> Subscriber<T> {
-    pub(crate) fn new(receiver: broadcast::Receiver<Vec<u8>>) -> Self {
+    pub(crate) fn new(receiver: QueueReceiver, shutdown_reason: ShutdownReason) -> Self {
         Self {
             receiver,
+            shutdown_reason,
+            on_deserialize_error: DeserializeErrorPolicy::default(),
             _phantom: PhantomData,
         }
     }
 
+    /// Sets what happens to messages which fail to deserialize, see [DeserializeErrorPolicy]
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// # roslibrust_codegen_macro::find_and_generate_ros_messages!("assets/ros1_common_interfaces/std_msgs");
+    /// use roslibrust::DeserializeErrorPolicy;
+    /// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/listener").await?;
+    /// let (dead_letters, mut undeliverable) = tokio::sync::mpsc::channel(100);
+    /// let mut subscriber = nh
+    ///     .subscribe::<std_msgs::String>("/chatter", 1)
+    ///     .await?
+    ///     .on_deserialize_error(DeserializeErrorPolicy::DeadLetter(dead_letters));
+    /// tokio::spawn(async move {
+    ///     while let Some(letter) = undeliverable.recv().await {
+    ///         log::error!("Undeliverable {}: {}", letter.topic_type, letter.error);
+    ///     }
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_deserialize_error(mut self, policy: DeserializeErrorPolicy) -> Self {
+        self.on_deserialize_error = policy;
+        self
+    }
+
     pub async fn next(&mut self) -> Result<T, Box<dyn std::error::Error>> {
-        let data = self.receiver.recv().await.map_err(|err| Box::new(err))?;
-        Ok(serde_rosmsg::from_slice(&data[..]).map_err(|err| Box::new(err))?)
+        Ok(self.next_with_info().await?.0)
+    }
+
+    /// Drops the subscriber, returning once the node has unsubscribed from the topic if no other subscribers
+    /// of it are left, closing its connections to the topic's publishers and telling the master.
+    ///
+    /// Fails with [std::io::ErrorKind::TimedOut] if this takes longer than `timeout`,
+    /// in which case the rest of the teardown carries on in the background as if the subscriber had been dropped.
+    pub async fn close(
+        self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let registration = self.receiver.into_registration();
+        tokio::time::timeout(timeout, async move {
+            if let Some(registration) = registration {
+                registration.release().await;
+            }
+        })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "Timed out closing the subscriber",
+            )
+            .into()
+        })
+    }
+
+    // Skips the messages waiting to be received
+    pub(crate) fn skip_pending(&mut self) {
+        self.receiver.skip_pending();
+    }
+
+    /// How many messages the subscriber has received, and how many it missed by falling behind
+    /// as set by the topic's [QueueOverflowPolicy]
+    pub fn stats(&self) -> SubscriberStats {
+        self.receiver.stats()
+    }
+
+    /// Receives the next message like [Subscriber::next], along with details of how it was received
+    pub async fn next_with_info(&mut self) -> Result<(T, MessageInfo), Box<dyn std::error::Error>> {
+        loop {
+            let message = match self.receiver.recv().await {
+                Ok(message) => message,
+                // The subscription is only dropped when the node shuts down
+                Err(broadcast::error::RecvError::Closed) => {
+                    return Err(Box::new(self.shutdown_reason.error()))
+                }
+                Err(err) => return Err(Box::new(err)),
+            };
+            match message.decode() {
+                Ok(msg) => {
+                    let info = MessageInfo {
+                        latched: message.latched,
+                        publisher: message.publisher.clone(),
+                    };
+                    return Ok((msg, info));
+                }
+                Err(err) => {
+                    // Messages received in-process are serialized for the dead letter, left empty if that fails too
+                    let data = message.into_data().unwrap_or_default();
+                    self.on_deserialize_error
+                        .handle(T::ROS_TYPE_NAME, data, err)?
+                }
+            }
+        }
+    }
+
+    /// Turns the subscriber into a [futures::Stream] of its messages, e.g. for use with [crate::OrderedMerge].
+    /// Messages which fail to deserialize are handled by the [DeserializeErrorPolicy], skipped with a warning
+    /// rather than ending the stream if the policy is to return errors.
+    /// Messages dropped because the stream fell behind are skipped with a warning,
+    /// and the stream ends when the node shuts down.
+    pub fn into_stream(self) -> impl futures::Stream<Item = T> + Send {
+        let state = (self.receiver, self.on_deserialize_error);
+        futures::stream::unfold(state, |(mut receiver, policy)| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(message) => match message.decode() {
+                        Ok(msg) => return Some((msg, (receiver, policy))),
+                        Err(err) => {
+                            let data = message.into_data().unwrap_or_default();
+                            if let Err(err) = policy.handle(T::ROS_TYPE_NAME, data, err) {
+                                log::warn!("Failed to deserialize {}: {err}", T::ROS_TYPE_NAME)
+                            }
+                        }
+                    },
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!(
+                            "Stream of {} fell behind, dropped {skipped} messages",
+                            T::ROS_TYPE_NAME
+                        );
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+/// A message received by an [AnySubscriber], left serialized
+#[derive(Clone, Debug)]
+pub struct AnyMessage {
+    /// The serialized message, starting with its length as sent over TCPROS,
+    /// ready to be deserialized once its type is known or to be written to a bag
+    pub data: Vec<u8>,
+    /// The connection header of the publisher the message came from, giving the type, md5sum and
+    /// definition of the message
+    pub publisher: Arc<ConnectionHeader>,
+    /// See [MessageInfo::latched]
+    pub latched: bool,
+}
+
+/// Receives the messages of a topic without deserializing them, whatever their type, like rospy's AnyMsg.
+/// Created with [super::NodeHandle::subscribe_any].
+pub struct AnySubscriber {
+    receiver: QueueReceiver,
+    shutdown_reason: ShutdownReason,
+}
+
+impl AnySubscriber {
+    pub(crate) fn new(receiver: QueueReceiver, shutdown_reason: ShutdownReason) -> Self {
+        Self {
+            receiver,
+            shutdown_reason,
+        }
+    }
+
+    /// See [Subscriber::stats]
+    pub fn stats(&self) -> SubscriberStats {
+        self.receiver.stats()
+    }
+
+    pub async fn next(&mut self) -> Result<AnyMessage, Box<dyn std::error::Error>> {
+        match self.receiver.recv().await {
+            Ok(message) => Ok(AnyMessage {
+                publisher: message.publisher.clone(),
+                latched: message.latched,
+                data: message.into_data()?,
+            }),
+            // The subscription is only dropped when the node shuts down
+            Err(broadcast::error::RecvError::Closed) => Err(Box::new(self.shutdown_reason.error())),
+            Err(err) => Err(Box::new(err)),
+        }
     }
 }
 
 pub struct Subscription {
-    subscription_tasks: Vec<ChildTask<()>>,
-    _msg_receiver: broadcast::Receiver<Vec<u8>>,
-    msg_sender: broadcast::Sender<Vec<u8>>,
+    // The task reading from each publisher, keyed by the publisher's xmlrpc uri.
+    // Dropping a task aborts it, closing its connection.
+    publisher_tasks: HashMap<String, ChildTask<()>>,
+    queue: SubscriptionQueue,
     connection_header: ConnectionHeader,
-    known_publishers: Arc<RwLock<Vec<String>>>,
+    bus_connections: BusConnections,
+    reconnect_policy: ReconnectPolicy,
+    xmlrpc_client: XmlRpcClient,
+    topic_endpoints: TopicEndpoints,
+    // The hostname advertised to publishers and the address bound when asking them for UDPROS
+    udpros: Option<(String, IpAddr)>,
+    // The hostname advertised to publishers when asking them for UNIXROS
+    unix_socket: Option<String>,
+    // The xmlrpc uri of our own node, whose publication of the topic hands messages over in-process
+    intra_process: Option<String>,
+    // Where the bytes of messages waiting to be read by subscribers are charged
+    account: Arc<TopicAccount>,
+    // Where messages failing their checksum are counted, set when publishers are asked for checksums
+    checksum_failures: Option<ChecksumFailures>,
+    header_validation: HeaderValidation,
+    // Where protocol errors of publishers are reported
+    peer_errors: PeerErrors,
+    // The largest message read from a publisher
+    max_message_size: usize,
+    // Applied to TCP connections to publishers once connected
+    socket_options: SocketOptions,
+    // How long a publisher may send nothing before its connection is dropped, if at all
+    idle_timeout: Option<Duration>,
+    // Whether connections to publishers are wrapped in TLS
+    tcpros_security: TcprosSecurity,
+}
+
+// A connection to a publisher, over whichever transport it agreed to
+enum PublisherConnection {
+    Tcp(TcprosStream),
+    // The socket the publisher sends datagrams to, and the id of the connection
+    Udp(UdpSocket, u32),
+}
+
+// How a publisher answered requestTopic
+enum TopicTransport {
+    // The host:port to connect to
+    Tcpros(String),
+    // Boxed as the publisher's connection header makes it far larger than the others
+    Udpros(Box<UdprosResponse>),
+    // The path of the publisher's socket
+    Unixros(PathBuf),
 }
 
 impl Subscription {
@@ -43,8 +387,8 @@ impl Subscription {
         queue_size: usize,
         msg_definition: String,
         md5sum: String,
+        bus_connections: BusConnections,
     ) -> Self {
-        let (sender, receiver) = broadcast::channel(queue_size);
         let connection_header = ConnectionHeader {
             caller_id: node_name.to_owned(),
             latching: false,
@@ -53,172 +397,1694 @@ impl Subscription {
             topic: topic_name.to_owned(),
             topic_type: topic_type.to_owned(),
             tcp_nodelay: false,
+            service: None,
+            persistent: false,
+            probe: false,
+            error: None,
+            checksum: false,
+            custom_fields: BTreeMap::new(),
         };
 
         Self {
-            subscription_tasks: vec![],
-            _msg_receiver: receiver,
-            msg_sender: sender,
+            publisher_tasks: HashMap::new(),
+            queue: SubscriptionQueue::new(queue_size, QueueOverflowPolicy::default()),
             connection_header,
-            known_publishers: Arc::new(RwLock::new(vec![])),
+            bus_connections,
+            reconnect_policy: ReconnectPolicy::default(),
+            xmlrpc_client: XmlRpcClient::default(),
+            topic_endpoints: TopicEndpoints::default(),
+            udpros: None,
+            unix_socket: None,
+            intra_process: None,
+            account: MemoryBudgets::default().account(topic_name, None),
+            checksum_failures: None,
+            header_validation: HeaderValidation::default(),
+            peer_errors: PeerErrors::default(),
+            max_message_size: tcpros::DEFAULT_MAX_MESSAGE_SIZE,
+            socket_options: SocketOptions::default(),
+            idle_timeout: None,
+            tcpros_security: TcprosSecurity::default(),
+        }
+    }
+
+    /// Sets whether Nagle's algorithm is disabled on connections to publishers, asking the publishers to do the same
+    pub fn tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.connection_header.tcp_nodelay = tcp_nodelay;
+        self.socket_options.tcp_nodelay = tcp_nodelay;
+        self
+    }
+
+    /// Sets how connections to publishers are retried after failing or closing
+    pub fn reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = policy;
+        self
+    }
+
+    /// Sets the client used to request the topic from publishers, so connections to them can be shared
+    pub fn xmlrpc_client(mut self, client: XmlRpcClient) -> Self {
+        self.xmlrpc_client = client;
+        self
+    }
+
+    /// Sets the cache of the endpoints publishers give in answer to requestTopic, shared by the node's subscriptions
+    pub fn topic_endpoints(mut self, topic_endpoints: TopicEndpoints) -> Self {
+        self.topic_endpoints = topic_endpoints;
+        self
+    }
+
+    /// Asks publishers for UDPROS ahead of TCPROS, receiving datagrams on a socket bound to `addr`
+    /// and advertised to publishers as `hostname`.
+    /// Publishers which don't support UDPROS still answer with TCPROS.
+    pub fn udpros(mut self, hostname: &str, addr: IpAddr) -> Self {
+        self.udpros = Some((hostname.to_owned(), addr));
+        self
+    }
+
+    /// Asks publishers for UNIXROS ahead of other protocols, advertising our host as `hostname`.
+    /// Publishers on the same host then accept us on a Unix domain socket, falling back to TCPROS if it can't be connected to.
+    /// Other publishers answer with the next protocol asked for.
+    pub fn unix_socket(mut self, hostname: &str) -> Self {
+        self.unix_socket = Some(hostname.to_owned());
+        self
+    }
+
+    /// Leaves the node at `node_uri`, our own, to hand over the messages it publishes in-process
+    /// through [Subscription::intra_process_queue] rather than connecting to it
+    pub fn intra_process(mut self, node_uri: &str) -> Self {
+        self.intra_process = Some(node_uri.to_owned());
+        self
+    }
+
+    /// Sets where the bytes of received messages are charged until every subscriber has read them
+    pub fn memory_account(mut self, account: Arc<TopicAccount>) -> Self {
+        self.account = account;
+        self
+    }
+
+    /// Sets what happens when a subscriber falls behind, before any receivers are handed out
+    pub fn queue_overflow(mut self, policy: QueueOverflowPolicy) -> Self {
+        self.queue = SubscriptionQueue::new(self.queue.capacity(), policy);
+        self
+    }
+
+    /// Asks publishers to append a checksum to each message, dropping messages which fail it and counting them in `failures`.
+    /// Messages of publishers which don't support checksums are received unchecked.
+    pub fn checksum(mut self, failures: ChecksumFailures) -> Self {
+        self.connection_header.checksum = true;
+        self.checksum_failures = Some(failures);
+        self
+    }
+
+    /// Sets how strictly the headers publishers answer with are checked
+    pub fn header_validation(mut self, validation: HeaderValidation) -> Self {
+        self.header_validation = validation;
+        self
+    }
+
+    /// Sets the custom fields sent to publishers in the subscription's connection header
+    pub fn header_fields(mut self, fields: BTreeMap<String, String>) -> Self {
+        self.connection_header.custom_fields = fields;
+        self
+    }
+
+    /// Sets where publishers refusing connections, answering with headers which are rejected or malformed
+    /// and failing requestTopic are reported
+    pub fn peer_errors(mut self, peer_errors: PeerErrors) -> Self {
+        self.peer_errors = peer_errors;
+        self
+    }
+
+    /// Sets the largest message read from publishers, dropping those which announce larger messages
+    pub fn max_message_size(mut self, bytes: usize) -> Self {
+        self.max_message_size = bytes;
+        self
+    }
+
+    /// Sets the options of TCP connections to publishers, including [Subscription::tcp_nodelay]
+    pub fn socket_options(self, options: SocketOptions) -> Self {
+        Self {
+            socket_options: options,
+            ..self.tcp_nodelay(options.tcp_nodelay)
         }
     }
 
+    /// Drops connections to publishers which send nothing for `timeout`, reconnecting to them
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets whether connections to publishers are wrapped in TLS, which publishers must expect
+    pub fn tcpros_security(mut self, security: TcprosSecurity) -> Self {
+        self.tcpros_security = security;
+        self
+    }
+
     pub fn topic_type(&self) -> &str {
         self.connection_header.topic_type.as_str()
     }
 
-    pub fn get_receiver(&self) -> broadcast::Receiver<Vec<u8>> {
-        self.msg_sender.subscribe()
+    pub fn connection_header(&self) -> &ConnectionHeader {
+        &self.connection_header
+    }
+
+    /// The queue our own node's publication of the topic hands messages to in-process, and where they are charged,
+    /// if the subscription receives them in-process
+    pub fn intra_process_queue(&self) -> Option<(&SubscriptionQueue, Arc<TopicAccount>)> {
+        self.intra_process
+            .as_ref()
+            .map(|_| (&self.queue, self.account.clone()))
+    }
+
+    pub fn get_receiver(&self) -> QueueReceiver {
+        self.queue.subscribe()
+    }
+
+    /// Whether any receivers handed out by [Subscription::get_receiver] are still alive
+    pub fn has_receivers(&self) -> bool {
+        self.queue.receiver_count() > 0
+    }
+
+    /// Brings the publisher connections in line with `publishers`, the full list of publishers
+    /// the master reports for the topic in a publisherUpdate.
+    /// Connections to publishers which are no longer listed are closed, and their endpoints forgotten.
+    pub async fn set_publishers(&mut self, publishers: &[String]) -> Result<(), std::io::Error> {
+        self.reap_publishers(publishers);
+        for publisher_uri in publishers {
+            self.add_publisher_source(publisher_uri).await?;
+        }
+        Ok(())
+    }
+
+    /// Closes the connections to publishers other than `publishers`, the xmlrpc uris of the nodes the master
+    /// lists as publishing the topic, returning how many were closed.
+    pub fn reap_publishers(&mut self, publishers: &[String]) -> usize {
+        self.topic_endpoints
+            .retain_publishers(&self.connection_header.topic, publishers);
+        let count = self.publisher_tasks.len();
+        self.publisher_tasks.retain(|publisher_uri, _| {
+            let keep = publishers.contains(publisher_uri);
+            if !keep {
+                log::debug!(
+                    "Publisher {publisher_uri} left {}, closing its connection",
+                    self.connection_header.topic
+                );
+            }
+            keep
+        });
+        count - self.publisher_tasks.len()
     }
 
     pub async fn add_publisher_source(
         &mut self,
         publisher_uri: &str,
     ) -> Result<(), std::io::Error> {
-        let is_new_connection = {
-            self.known_publishers
-                .read()
-                .await
-                .iter()
-                .find(|publisher| publisher.as_str() == publisher_uri)
-                .is_none()
+        if self.intra_process.as_deref() == Some(publisher_uri) {
+            return Ok(());
+        }
+        // A finished task means the reconnect policy gave up on the publisher, so it is replaced by
+        // a new connection rather than blocking resubscription
+        let is_new_connection = match self.publisher_tasks.get(publisher_uri) {
+            Some(task) => task.is_finished(),
+            None => true,
         };
 
         if is_new_connection {
             let node_name = self.connection_header.caller_id.clone();
             let topic_name = self.connection_header.topic.clone();
             let connection_header = self.connection_header.clone();
-            let sender = self.msg_sender.clone();
-            let publisher_list = self.known_publishers.clone();
-            let publisher_uri = publisher_uri.to_owned();
-
-            let handle = tokio::spawn(async move {
-                if let Ok(mut stream) = establish_publisher_connection(
-                    &node_name,
-                    &topic_name,
-                    &publisher_uri,
-                    connection_header,
-                )
-                .await
-                {
-                    publisher_list.write().await.push(publisher_uri.to_owned());
-                    // Repeatedly read from the stream until its dry
-                    let mut read_buffer = Vec::with_capacity(4 * 1024);
-                    loop {
-                        if let Ok(bytes_read) = stream.read_buf(&mut read_buffer).await {
-                            if bytes_read == 0 {
-                                log::debug!("Got a message with 0 bytes, probably an EOF, closing connection");
-                                break;
-                            }
-                            log::debug!("Read {bytes_read} bytes from the publisher connection");
-                            if let Err(err) = sender.send(Vec::from(&read_buffer[..bytes_read])) {
-                                log::error!("Unable to send message data due to dropped channel, closing connection: {err}");
-                                break;
+            let queue = self.queue.clone();
+            let task_publisher_uri = publisher_uri.to_owned();
+            let bus_connections = self.bus_connections.clone();
+            let reconnect_policy = self.reconnect_policy.clone();
+            let xmlrpc_client = self.xmlrpc_client.clone();
+            let topic_endpoints = self.topic_endpoints.clone();
+            let udpros = self.udpros.clone();
+            let unixros = self.unix_socket.clone().map(|host| UnixrosRequest { host });
+            let account = self.account.clone();
+            let checksum_failures = self.checksum_failures.clone();
+            let header_validation = self.header_validation;
+            let peer_errors = self.peer_errors.clone();
+            let max_message_size = self.max_message_size;
+            let socket_options = self.socket_options;
+            let idle_timeout = self.idle_timeout;
+            let tcpros_security = self.tcpros_security.clone();
+
+            let task_name = format!("subscription {topic_name} from {publisher_uri}");
+            let handle = crate::tasks::spawn(&task_name, async move {
+                let publisher_uri = task_publisher_uri;
+                let mut failed_attempts = 0;
+                loop {
+                    match establish_publisher_connection(
+                        &xmlrpc_client,
+                        &topic_endpoints,
+                        &node_name,
+                        &topic_name,
+                        &publisher_uri,
+                        connection_header.clone(),
+                        header_validation,
+                        udpros.as_ref(),
+                        unixros.as_ref(),
+                        &peer_errors,
+                        max_message_size,
+                        &tcpros_security,
+                    )
+                    .await
+                    {
+                        Ok((connection, publisher_header)) => {
+                            failed_attempts = 0;
+                            let checksum_failures =
+                                verified_checksums(&checksum_failures, &publisher_header);
+                            match connection {
+                                PublisherConnection::Tcp(stream) => {
+                                    if let Some(tcp) = stream.tcp() {
+                                        socket_options.apply(tcp, &topic_name);
+                                    }
+                                    read_publisher_connection(
+                                        stream,
+                                        &publisher_uri,
+                                        &publisher_header,
+                                        &topic_name,
+                                        &queue,
+                                        &account,
+                                        checksum_failures,
+                                        &bus_connections,
+                                        &peer_errors,
+                                        max_message_size,
+                                        idle_timeout,
+                                    )
+                                    .await
+                                }
+                                PublisherConnection::Udp(socket, connection_id) => {
+                                    read_udp_publisher_connection(
+                                        socket,
+                                        connection_id,
+                                        &publisher_uri,
+                                        &publisher_header,
+                                        &topic_name,
+                                        &queue,
+                                        &account,
+                                        checksum_failures,
+                                        &bus_connections,
+                                        idle_timeout,
+                                    )
+                                    .await
+                                }
                             }
-                            read_buffer.clear();
-                        } else {
-                            log::warn!("Got an error reading from the publisher connection on topic {topic_name}, closing");
+                        }
+                        // Retrying can't fix a publisher of a different message type
+                        Err(err) if TopicMismatch::is(&err) => break,
+                        Err(err) => {
+                            failed_attempts += 1;
+                            log::debug!("Failed to connect to publisher {publisher_uri} of {topic_name}: {err}");
                         }
                     }
+                    let Some(delay) = reconnect_policy.delay(failed_attempts) else {
+                        log::warn!("Giving up on publisher {publisher_uri} of {topic_name} after {failed_attempts} failed attempts to connect");
+                        break;
+                    };
+                    tokio::time::sleep(delay).await;
                 }
             });
-            self.subscription_tasks.push(handle.into());
+            self.publisher_tasks
+                .insert(publisher_uri.to_owned(), handle.into());
         }
 
         Ok(())
     }
 }
 
+// A publisher whose type or md5sum doesn't match the subscription, carried in the error of its connection.
+// Other failures, such as a malformed header or requestTopic response, are retried like any other.
+#[derive(thiserror::Error, Debug)]
+#[error("{0}")]
+struct TopicMismatch(String);
+
+impl TopicMismatch {
+    fn error(reason: impl Into<String>) -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, Self(reason.into()))
+    }
+
+    fn is(err: &std::io::Error) -> bool {
+        err.get_ref().is_some_and(|err| err.is::<Self>())
+    }
+}
+
+// Awaits a read from a publisher, giving up on the publisher if `idle_timeout` passes first
+async fn within_idle_timeout<T>(
+    read: impl std::future::Future<Output = T>,
+    idle_timeout: Option<Duration>,
+    topic_name: &str,
+) -> Option<T> {
+    let Some(idle_timeout) = idle_timeout else {
+        return Some(read.await);
+    };
+    match tokio::time::timeout(idle_timeout, read).await {
+        Ok(read) => Some(read),
+        Err(_) => {
+            log::warn!("Publisher of {topic_name} sent nothing for {idle_timeout:?}, dropping the connection to reconnect");
+            None
+        }
+    }
+}
+
+// Where to count messages failing their checksum on a connection, if checksums were asked for and the publisher agreed
+fn verified_checksums<'a>(
+    failures: &'a Option<ChecksumFailures>,
+    publisher_header: &ConnectionHeader,
+) -> Option<&'a ChecksumFailures> {
+    let failures = failures.as_ref()?;
+    if !publisher_header.checksum {
+        log::debug!(
+            "Publisher {} of {} does not send checksums, receiving its messages unchecked",
+            publisher_header.caller_id,
+            publisher_header.topic
+        );
+        return None;
+    }
+    Some(failures)
+}
+
+// Removes the checksum of a message when one is expected, returning None if the message failed it
+fn strip_checksum(
+    data: Vec<u8>,
+    checksum_failures: Option<&ChecksumFailures>,
+    topic_name: &str,
+) -> Option<Vec<u8>> {
+    let Some(failures) = checksum_failures else {
+        return Some(data);
+    };
+    let message = checksum::verify(&data);
+    if message.is_none() {
+        log::warn!("Dropping a message on {topic_name} which failed its checksum");
+        failures.record(topic_name);
+    }
+    message
+}
+
+// Forwards messages from a publisher until the connection closes.
+// Messages are dropped while no subscriber is left, until the node drops the subscription and this task with it.
+#[allow(clippy::too_many_arguments)]
+async fn read_publisher_connection(
+    mut stream: TcprosStream,
+    publisher_uri: &str,
+    publisher_header: &ConnectionHeader,
+    topic_name: &str,
+    queue: &SubscriptionQueue,
+    account: &Arc<TopicAccount>,
+    checksum_failures: Option<&ChecksumFailures>,
+    bus_connections: &BusConnections,
+    peer_errors: &PeerErrors,
+    max_message_size: usize,
+    idle_timeout: Option<Duration>,
+) {
+    // Streams without a TCP connection are UNIXROS connections
+    let (transport, transport_info) = match stream.tcp() {
+        Some(tcp) => {
+            let peer_addr = tcp
+                .peer_addr()
+                .map(|addr| addr.to_string())
+                .unwrap_or_else(|_| publisher_uri.to_owned());
+            (
+                Transport::Tcpros,
+                format!("TCPROS connection to [{peer_addr}]"),
+            )
+        }
+        None => (
+            Transport::Unixros,
+            format!("UNIXROS connection to [{publisher_uri}]"),
+        ),
+    };
+    let connection = bus_connections.add(
+        &publisher_header.caller_id,
+        Direction::Inbound,
+        transport,
+        topic_name,
+        transport_info,
+    );
+    // A latching publisher replays its last message as the first on each connection
+    let mut latched = publisher_header.latching;
+    let link = queue.link(publisher_header.latching);
+    let publisher = Arc::new(publisher_header.clone());
+    loop {
+        let read = tcpros::read_frame(&mut stream, max_message_size);
+        let Some(read) = within_idle_timeout(read, idle_timeout, topic_name).await else {
+            return;
+        };
+        let frame = match read {
+            Ok(frame) => frame,
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                log::debug!("Publisher connection on topic {topic_name} closed");
+                return;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::InvalidData => {
+                peer_errors.report(
+                    PeerErrorKind::Malformed,
+                    &publisher_header.caller_id,
+                    topic_name,
+                    format!("{err}, closing the connection"),
+                );
+                return;
+            }
+            Err(err) => {
+                log::warn!(
+                    "Got an error reading from the publisher connection on topic {topic_name}, closing: {err}"
+                );
+                return;
+            }
+        };
+        log::debug!("Read {} bytes from the publisher connection", frame.len());
+        connection.record(frame.len());
+        let Some(data) = strip_checksum(frame, checksum_failures, topic_name) else {
+            continue;
+        };
+        if let Some(charge) = account.charge_incoming(data.len()).await {
+            let message = RawMessage {
+                data: data.into(),
+                local: None,
+                latched: std::mem::take(&mut latched),
+                publisher: publisher.clone(),
+                _charge: Arc::new(charge),
+            };
+            if !link.send(message, topic_name) {
+                log::debug!("No subscribers left on {topic_name}, dropping a message");
+            }
+        }
+    }
+}
+
+// Forwards messages reassembled from the datagrams of a UDPROS publisher until it reports an error,
+// dropped like those of a TCPROS publisher while no subscriber is left.
+// UDPROS has no notion of closing, so a publisher which goes quiet is only dropped once the master
+// reports it has left the topic.
+#[allow(clippy::too_many_arguments)]
+async fn read_udp_publisher_connection(
+    socket: UdpSocket,
+    connection_id: u32,
+    publisher_uri: &str,
+    publisher_header: &ConnectionHeader,
+    topic_name: &str,
+    queue: &SubscriptionQueue,
+    account: &Arc<TopicAccount>,
+    checksum_failures: Option<&ChecksumFailures>,
+    bus_connections: &BusConnections,
+    idle_timeout: Option<Duration>,
+) {
+    let local_port = socket
+        .local_addr()
+        .map(|addr| addr.port().to_string())
+        .unwrap_or_default();
+    let connection = bus_connections.add(
+        &publisher_header.caller_id,
+        Direction::Inbound,
+        Transport::Udpros,
+        topic_name,
+        format!("UDPROS connection on port {local_port} to [{publisher_uri}]"),
+    );
+    let mut latched = publisher_header.latching;
+    let link = queue.link(publisher_header.latching);
+    let publisher = Arc::new(publisher_header.clone());
+    let mut reassembler = Reassembler::new(connection_id);
+    let mut datagram = vec![0; udpros::MAX_DATAGRAM_SIZE];
+    loop {
+        let received = socket.recv(&mut datagram);
+        let Some(received) = within_idle_timeout(received, idle_timeout, topic_name).await else {
+            return;
+        };
+        let bytes_read = match received {
+            Ok(bytes_read) => bytes_read,
+            Err(err) => {
+                log::warn!("Got an error reading from the publisher connection on topic {topic_name}, closing: {err}");
+                return;
+            }
+        };
+        connection.record(bytes_read);
+        match reassembler.push(&datagram[..bytes_read]) {
+            Ok(Some(data)) => {
+                let Some(data) = strip_checksum(data, checksum_failures, topic_name) else {
+                    continue;
+                };
+                let Some(charge) = account.charge_incoming(data.len()).await else {
+                    continue;
+                };
+                let message = RawMessage {
+                    data: data.into(),
+                    local: None,
+                    latched: std::mem::take(&mut latched),
+                    publisher: publisher.clone(),
+                    _charge: Arc::new(charge),
+                };
+                if !link.send(message, topic_name) {
+                    log::debug!("No subscribers left on {topic_name}, dropping a message");
+                }
+            }
+            Ok(None) => {}
+            Err(err) => {
+                log::debug!(
+                    "Publisher {publisher_uri} of {topic_name} closed the connection: {err}"
+                );
+                return;
+            }
+        }
+    }
+}
+
+// Connects to the publisher through its cached endpoint if there is one, otherwise or if that fails
+// requesting the topic from the publisher.
+// When `udpros` or `unixros` is given the publisher is asked for UDPROS or UNIXROS first, and its answer
+// isn't cached since using the cached endpoint would skip asking for them again.
+// A publisher agreeing to UNIXROS whose socket can't be connected to is asked again for TCPROS alone.
+#[allow(clippy::too_many_arguments)]
 async fn establish_publisher_connection(
+    xmlrpc_client: &XmlRpcClient,
+    topic_endpoints: &TopicEndpoints,
     node_name: &str,
     topic_name: &str,
     publisher_uri: &str,
     conn_header: ConnectionHeader,
-) -> Result<TcpStream, std::io::Error> {
-    let publisher_channel_uri = send_topic_request(node_name, topic_name, publisher_uri).await?;
-    let mut stream = TcpStream::connect(publisher_channel_uri).await?;
+    validation: HeaderValidation,
+    udpros: Option<&(String, IpAddr)>,
+    unixros: Option<&UnixrosRequest>,
+    peer_errors: &PeerErrors,
+    max_message_size: usize,
+    tcpros_security: &TcprosSecurity,
+) -> Result<(PublisherConnection, ConnectionHeader), std::io::Error> {
+    let cacheable = udpros.is_none() && unixros.is_none();
+    if cacheable {
+        if let Some(endpoint) = topic_endpoints.get(publisher_uri, topic_name) {
+            match connect_to_publisher(
+                &endpoint,
+                topic_name,
+                conn_header.clone(),
+                validation,
+                peer_errors,
+                max_message_size,
+                tcpros_security,
+            )
+            .await
+            {
+                Ok((stream, header)) => return Ok((PublisherConnection::Tcp(stream), header)),
+                Err(err) => {
+                    log::debug!("Cached endpoint {endpoint} of publisher {publisher_uri} for {topic_name} failed, requesting the topic again: {err}");
+                    topic_endpoints.invalidate(publisher_uri, topic_name);
+                }
+            }
+        }
+    }
 
-    let conn_header_bytes = conn_header.to_bytes(true)?;
-    stream.write_all(&conn_header_bytes[..]).await?;
+    let udp = match udpros {
+        Some((hostname, addr)) => {
+            let socket = UdpSocket::from_std(addrs::bind_udp(*addr)?)?;
+            let request = UdprosRequest {
+                header: conn_header.clone(),
+                host: hostname.clone(),
+                port: socket.local_addr()?.port(),
+                max_datagram_size: udpros::MAX_DATAGRAM_SIZE,
+            };
+            Some((socket, request))
+        }
+        None => None,
+    };
+    let mut transport = send_topic_request(
+        xmlrpc_client,
+        node_name,
+        topic_name,
+        publisher_uri,
+        udp.as_ref().map(|(_, request)| request),
+        unixros,
+        peer_errors,
+    )
+    .await?;
 
-    let mut responded_header_bytes = Vec::with_capacity(16 * 1024);
-    let bytes = stream.read_buf(&mut responded_header_bytes).await?;
-    if let Ok(responded_header) = ConnectionHeader::from_bytes(&responded_header_bytes[..bytes]) {
-        if conn_header.md5sum == responded_header.md5sum {
+    if let TopicTransport::Unixros(path) = &transport {
+        match connect_unix_socket(path).await {
+            Ok(stream) => {
+                let (stream, header) = exchange_headers(
+                    stream,
+                    publisher_uri,
+                    topic_name,
+                    conn_header,
+                    validation,
+                    peer_errors,
+                    max_message_size,
+                )
+                .await?;
+                log::debug!("Established UNIXROS connection with publisher for {topic_name}");
+                return Ok((PublisherConnection::Tcp(stream), header));
+            }
+            Err(err) => {
+                log::debug!(
+                    "Failed to connect to the socket {} of publisher {publisher_uri} for {topic_name}, requesting TCPROS: {err}",
+                    path.display()
+                );
+                transport = send_topic_request(
+                    xmlrpc_client,
+                    node_name,
+                    topic_name,
+                    publisher_uri,
+                    None,
+                    None,
+                    peer_errors,
+                )
+                .await?;
+            }
+        }
+    }
+
+    match (transport, udp) {
+        (TopicTransport::Tcpros(endpoint), _) => {
+            let (stream, header) = connect_to_publisher(
+                &endpoint,
+                topic_name,
+                conn_header,
+                validation,
+                peer_errors,
+                max_message_size,
+                tcpros_security,
+            )
+            .await?;
+            if cacheable {
+                topic_endpoints.insert(publisher_uri, topic_name, endpoint);
+            }
+            Ok((PublisherConnection::Tcp(stream), header))
+        }
+        (TopicTransport::Udpros(response), Some((socket, _))) => {
+            if let Err(err) = validation.check(&conn_header, &response.header) {
+                peer_errors.report(
+                    PeerErrorKind::HeaderRejected,
+                    publisher_uri,
+                    topic_name,
+                    err.to_string(),
+                );
+                return Err(TopicMismatch::error(err));
+            }
             log::debug!(
-                "Established connection with publisher for {}",
-                conn_header.topic
+                "Established UDPROS connection {} with publisher for {topic_name}",
+                response.connection_id
             );
-            Ok(stream)
-        } else {
+            Ok((
+                PublisherConnection::Udp(socket, response.connection_id),
+                response.header,
+            ))
+        }
+        _ => {
             log::error!(
-                "Tried to subscribe to {}, but md5sums do not match. Expected {}, received {}",
+                "Publisher {publisher_uri} answered with a protocol which wasn't asked for"
+            );
+            Err(std::io::ErrorKind::Unsupported.into())
+        }
+    }
+}
+
+async fn connect_to_publisher(
+    endpoint: &str,
+    topic_name: &str,
+    conn_header: ConnectionHeader,
+    validation: HeaderValidation,
+    peer_errors: &PeerErrors,
+    max_message_size: usize,
+    tcpros_security: &TcprosSecurity,
+) -> Result<(TcprosStream, ConnectionHeader), std::io::Error> {
+    let stream = TcpStream::connect(endpoint).await?;
+    stream.set_nodelay(conn_header.tcp_nodelay)?;
+    let stream = match tcpros_security.connect(endpoint, stream).await {
+        Ok(stream) => stream,
+        Err(err) => {
+            peer_errors.report(
+                PeerErrorKind::HandshakeRefused,
+                endpoint,
                 topic_name,
-                conn_header.md5sum,
-                responded_header.md5sum
+                format!("TLS handshake with the publisher failed: {err}"),
             );
-            Err(std::io::ErrorKind::InvalidData)
+            return Err(err);
         }
+    };
+    exchange_headers(
+        stream,
+        endpoint,
+        topic_name,
+        conn_header,
+        validation,
+        peer_errors,
+        max_message_size,
+    )
+    .await
+}
+
+// Connects to the socket a publisher accepts UNIXROS subscribers on
+#[cfg(unix)]
+async fn connect_unix_socket(path: &Path) -> Result<TcprosStream, std::io::Error> {
+    Ok(tokio::net::UnixStream::connect(path).await?.into())
+}
+
+#[cfg(not(unix))]
+async fn connect_unix_socket(_path: &Path) -> Result<TcprosStream, std::io::Error> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+// Sends our connection header to a publisher we connected to and checks the one it answers with.
+// `peer` names the publisher in reports until it names itself in its header.
+async fn exchange_headers(
+    mut stream: TcprosStream,
+    peer: &str,
+    topic_name: &str,
+    conn_header: ConnectionHeader,
+    validation: HeaderValidation,
+    peer_errors: &PeerErrors,
+    max_message_size: usize,
+) -> Result<(TcprosStream, ConnectionHeader), std::io::Error> {
+    let conn_header_bytes = conn_header.to_bytes(true)?;
+    stream.write_frame(&conn_header_bytes[..]).await?;
+
+    // A latching publisher may send its first message along with its header
+    let responded_header_bytes = tcpros::read_frame(&mut stream, max_message_size).await?;
+    let Ok(responded_header) = ConnectionHeader::from_bytes(&responded_header_bytes) else {
+        peer_errors.report(
+            PeerErrorKind::Malformed,
+            peer,
+            topic_name,
+            "could not parse the connection header sent by the publisher",
+        );
+        return Err(std::io::ErrorKind::InvalidData.into());
+    };
+    // Publishers answer with only an error when they refuse the connection, e.g. over a mismatched md5sum
+    let peer = if responded_header.caller_id.is_empty() {
+        peer
     } else {
-        log::error!("Could not parse connection header data sent by publisher");
-        Err(std::io::ErrorKind::InvalidData)
+        &responded_header.caller_id
+    };
+    if let Some(error) = &responded_header.error {
+        peer_errors.report(PeerErrorKind::HandshakeRefused, peer, topic_name, error);
+        return Err(TopicMismatch::error(error.clone()));
+    }
+    if let Err(err) = validation.check(&conn_header, &responded_header) {
+        peer_errors.report(
+            PeerErrorKind::HeaderRejected,
+            peer,
+            topic_name,
+            err.to_string(),
+        );
+        return Err(TopicMismatch::error(err));
     }
-    .map_err(std::io::Error::from)
+    log::debug!(
+        "Established connection with publisher for {}",
+        conn_header.topic
+    );
+    Ok((stream, responded_header))
 }
 
 async fn send_topic_request(
+    xmlrpc_client: &XmlRpcClient,
     node_name: &str,
     topic_name: &str,
     publisher_uri: &str,
-) -> Result<String, std::io::Error> {
-    let xmlrpc_client = reqwest::Client::new();
+    udpros: Option<&UdprosRequest>,
+    unixros: Option<&UnixrosRequest>,
+    peer_errors: &PeerErrors,
+) -> Result<TopicTransport, std::io::Error> {
+    let mut protocols = vec![];
+    if let Some(unixros) = unixros {
+        protocols.push(unixros.to_value());
+    }
+    if let Some(udpros) = udpros {
+        protocols.push(udpros.to_value()?);
+    }
+    protocols.push(serde_xmlrpc::Value::Array(vec!["TCPROS".into()]));
     let body = serde_xmlrpc::request_to_string(
         "requestTopic",
         vec![
             node_name.into(),
             topic_name.into(),
-            serde_xmlrpc::Value::Array(vec![serde_xmlrpc::Value::Array(vec!["TCPROS".into()])]),
+            serde_xmlrpc::Value::Array(protocols),
         ],
     )
     .unwrap();
 
-    let response = xmlrpc_client
-        .post(publisher_uri)
-        .body(body)
-        .send()
+    let response_data = xmlrpc_client
+        .post(publisher_uri, body)
         .await
         .map_err(|err| {
-            log::error!("Unable to send subscribe request to publisher: {err}");
-            std::io::ErrorKind::ConnectionAborted
+            log::error!("Unable to request {topic_name} from publisher {publisher_uri}: {err}");
+            err
         })?;
-    if response.status().is_success() {
-        if let Ok(response_data) = response.text().await {
-            if let Ok((_code, _description, (protocol, hostname, port))) =
-                serde_xmlrpc::response_from_str::<(i8, String, (String, String, u16))>(
-                    &response_data,
-                )
-            {
-                if protocol == "TCPROS" {
-                    let tcpros_endpoint = format!("{hostname}:{port}");
-                    log::debug!("Got a TCPROS publisher endpoint at {tcpros_endpoint}");
-                    Ok(tcpros_endpoint)
-                } else {
-                    log::error!("Got unsupported protocol {protocol}");
-                    Err(std::io::ErrorKind::Unsupported.into())
-                }
-            } else {
-                log::error!("Failed to deserialize requestTopic response {response_data}");
-                Err(std::io::ErrorKind::InvalidData.into())
+    if let Some(response) = UdprosResponse::from_response(&response_data) {
+        log::debug!(
+            "Got a UDPROS publisher endpoint at {}:{}",
+            response.host,
+            response.port
+        );
+        return Ok(TopicTransport::Udpros(Box::new(response)));
+    }
+    if let Some(response) = UnixrosResponse::from_response(&response_data) {
+        log::debug!(
+            "Got a UNIXROS publisher endpoint at {}",
+            response.path.display()
+        );
+        return Ok(TopicTransport::Unixros(response.path));
+    }
+    // Failed calls don't carry an endpoint, so their code is read first
+    let (code, description, _) =
+        match serde_xmlrpc::response_from_str::<(i32, String, serde::de::IgnoredAny)>(
+            &response_data,
+        ) {
+            Ok(response) => response,
+            Err(serde_xmlrpc::Error::Fault(fault)) => {
+                peer_errors.report(
+                    PeerErrorKind::XmlRpcFault,
+                    publisher_uri,
+                    topic_name,
+                    fault.to_string(),
+                );
+                return Err(std::io::ErrorKind::InvalidData.into());
             }
-        } else {
-            log::error!("No data received with the response");
-            Err(std::io::ErrorKind::InvalidData.into())
-        }
+            Err(err) => {
+                peer_errors.report(
+                    PeerErrorKind::Malformed,
+                    publisher_uri,
+                    topic_name,
+                    format!("could not deserialize requestTopic response: {err}"),
+                );
+                return Err(std::io::ErrorKind::InvalidData.into());
+            }
+        };
+    if code != 1 {
+        peer_errors.report(
+            PeerErrorKind::XmlRpcFault,
+            publisher_uri,
+            topic_name,
+            format!("requestTopic failed with code {code}: {description}"),
+        );
+        return Err(std::io::ErrorKind::InvalidData.into());
+    }
+    let (_, _, (protocol, hostname, port)) =
+        serde_xmlrpc::response_from_str::<(i32, String, (String, String, u16))>(&response_data)
+            .map_err(|err| {
+                peer_errors.report(
+                    PeerErrorKind::Malformed,
+                    publisher_uri,
+                    topic_name,
+                    format!("could not deserialize the endpoint in requestTopic response: {err}"),
+                );
+                std::io::Error::from(std::io::ErrorKind::InvalidData)
+            })?;
+    if protocol == "TCPROS" {
+        let tcpros_endpoint = addrs::host_port(&hostname, port);
+        log::debug!("Got a TCPROS publisher endpoint at {tcpros_endpoint}");
+        Ok(TopicTransport::Tcpros(tcpros_endpoint))
     } else {
-        log::error!(
-            "Failed to request topic data from the publisher's XMLRPC server for {publisher_uri}: {response:#?}"
+        log::error!("Got unsupported protocol {protocol}");
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        connect_to_publisher, read_publisher_connection, verified_checksums, AnySubscriber,
+        DeserializeErrorPolicy, Subscription, TopicMismatch,
+    };
+    use crate::{
+        ros1::{
+            bus_info::BusConnections,
+            checksum::{self, ChecksumFailures},
+            peer_errors::{PeerErrorKind, PeerErrors},
+            tcpros::{HeaderValidation, SocketOptions, DEFAULT_MAX_MESSAGE_SIZE},
+            tls::TcprosSecurity,
+            topic_endpoints::TopicEndpoints,
+        },
+        ReconnectPolicy,
+    };
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Response, Server,
+    };
+    use std::{
+        convert::Infallible,
+        net::SocketAddr,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        time::timeout,
+    };
+
+    #[tokio::test]
+    async fn removed_publishers_are_disconnected() {
+        // Stands in for the xmlrpc server of a publisher which never answers requestTopic
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let publisher_uri = format!("http://{}/", listener.local_addr().unwrap());
+        let mut subscription = Subscription::new(
+            "/listener",
+            "/chatter",
+            "std_msgs/String",
+            1,
+            String::new(),
+            String::new(),
+            BusConnections::default(),
         );
-        Err(std::io::ErrorKind::ConnectionRefused.into())
+
+        let publishers = vec![publisher_uri];
+        subscription.set_publishers(&publishers).await.unwrap();
+        let (mut socket, _) = listener.accept().await.unwrap();
+        // Updates listing the same publisher keep the existing connection
+        subscription.set_publishers(&publishers).await.unwrap();
+        assert_eq!(subscription.publisher_tasks.len(), 1);
+        assert!(timeout(Duration::from_millis(100), listener.accept())
+            .await
+            .is_err());
+
+        // Once the publisher is no longer listed its connection is closed
+        subscription.set_publishers(&[]).await.unwrap();
+        assert!(subscription.publisher_tasks.is_empty());
+        let mut request = vec![];
+        let _ = timeout(Duration::from_secs(1), socket.read_to_end(&mut request))
+            .await
+            .expect("Connection to the removed publisher should be closed");
+
+        // A publisher which comes back at the same uri is connected to again
+        subscription.set_publishers(&publishers).await.unwrap();
+        timeout(Duration::from_secs(1), listener.accept())
+            .await
+            .expect("Should reconnect to the returning publisher")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn unlisted_publishers_are_reaped() {
+        let listed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let listed_uri = format!("http://{}/", listed.local_addr().unwrap());
+        let stale = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stale_uri = format!("http://{}/", stale.local_addr().unwrap());
+        let mut subscription = Subscription::new(
+            "/listener",
+            "/chatter",
+            "std_msgs/String",
+            1,
+            String::new(),
+            String::new(),
+            BusConnections::default(),
+        );
+        subscription
+            .add_publisher_source(&listed_uri)
+            .await
+            .unwrap();
+        subscription.add_publisher_source(&stale_uri).await.unwrap();
+        let (mut stale_socket, _) = stale.accept().await.unwrap();
+
+        let publishers = vec![listed_uri];
+        assert_eq!(subscription.reap_publishers(&publishers), 1);
+        assert_eq!(subscription.reap_publishers(&publishers), 0);
+        assert_eq!(subscription.publisher_tasks.len(), 1);
+        let mut request = vec![];
+        let _ = timeout(
+            Duration::from_secs(1),
+            stale_socket.read_to_end(&mut request),
+        )
+        .await
+        .expect("Connection to the unlisted publisher should be closed");
+    }
+
+    #[tokio::test]
+    async fn failed_publisher_connections_are_retried() {
+        // Stands in for the xmlrpc server of a publisher, dropping every request
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let publisher_uri = format!("http://{}/", listener.local_addr().unwrap());
+        let mut subscription = Subscription::new(
+            "/listener",
+            "/chatter",
+            "std_msgs/String",
+            1,
+            String::new(),
+            String::new(),
+            BusConnections::default(),
+        )
+        .reconnect_policy(ReconnectPolicy::fixed(Duration::from_millis(10)).max_attempts(3));
+
+        subscription
+            .add_publisher_source(&publisher_uri)
+            .await
+            .unwrap();
+        for _ in 0..3 {
+            let (socket, _) = timeout(Duration::from_secs(1), listener.accept())
+                .await
+                .expect("Should retry the publisher")
+                .unwrap();
+            drop(socket);
+        }
+        // Gives up once the policy runs out of attempts
+        assert!(timeout(Duration::from_millis(200), listener.accept())
+            .await
+            .is_err());
+        assert!(subscription.publisher_tasks[&publisher_uri].is_finished());
+    }
+
+    #[tokio::test]
+    async fn malformed_topic_responses_are_retried() {
+        // Stands in for the xmlrpc server of a publisher, answering requestTopic with something other than xmlrpc
+        let topic_requests = Arc::new(AtomicUsize::new(0));
+        let counter = topic_requests.clone();
+        let make_svc = make_service_fn(move |_| {
+            let counter = counter.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |_| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    async { Ok::<_, Infallible>(Response::new(Body::from("not xmlrpc"))) }
+                }))
+            }
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_svc);
+        let publisher_uri = format!("http://{}/", server.local_addr());
+        tokio::spawn(server);
+
+        let peer_errors = PeerErrors::default();
+        let mut errors = peer_errors.subscribe();
+        let mut subscription = Subscription::new(
+            "/listener",
+            "/chatter",
+            "std_msgs/String",
+            1,
+            String::new(),
+            String::new(),
+            BusConnections::default(),
+        )
+        .reconnect_policy(ReconnectPolicy::fixed(Duration::from_millis(10)).max_attempts(3))
+        .peer_errors(peer_errors);
+        subscription
+            .add_publisher_source(&publisher_uri)
+            .await
+            .unwrap();
+
+        timeout(Duration::from_secs(1), async {
+            while !subscription.publisher_tasks[&publisher_uri].is_finished() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Should give up on the publisher");
+        // Each malformed response is reported, and the publisher retried until the policy runs out of attempts
+        assert_eq!(topic_requests.load(Ordering::SeqCst), 3);
+        for _ in 0..3 {
+            assert_eq!(errors.try_recv().unwrap().kind, PeerErrorKind::Malformed);
+        }
+    }
+
+    #[tokio::test]
+    async fn reconnects_through_cached_endpoints() {
+        // Stands in for a publisher, which closes each connection as soon as it is made
+        let tcpros = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let tcpros_port = tcpros.local_addr().unwrap().port();
+        let tcpros_task = tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = tcpros.accept().await.unwrap();
+                let mut header = Vec::with_capacity(1024);
+                socket.read_buf(&mut header).await.unwrap();
+                // Echoing the subscriber's header back matches its md5sum
+                socket.write_all(&header).await.unwrap();
+            }
+        });
+        let topic_requests = Arc::new(AtomicUsize::new(0));
+        let counter = topic_requests.clone();
+        let make_svc = make_service_fn(move |_| {
+            let counter = counter.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |_| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    let response = serde_xmlrpc::response_to_string(
+                        vec![
+                            serde_xmlrpc::to_value((1, "", ("TCPROS", "127.0.0.1", tcpros_port)))
+                                .unwrap(),
+                        ]
+                        .into_iter(),
+                    )
+                    .unwrap();
+                    async { Ok::<_, Infallible>(Response::new(Body::from(response))) }
+                }))
+            }
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_svc);
+        let publisher_uri = format!("http://{}/", server.local_addr());
+        tokio::spawn(server);
+
+        let topic_endpoints = TopicEndpoints::default();
+        let mut subscription = Subscription::new(
+            "/listener",
+            "/chatter",
+            "std_msgs/String",
+            1,
+            String::new(),
+            String::new(),
+            BusConnections::default(),
+        )
+        .reconnect_policy(ReconnectPolicy::fixed(Duration::from_millis(10)).max_attempts(3))
+        .topic_endpoints(topic_endpoints.clone());
+        subscription
+            .add_publisher_source(&publisher_uri)
+            .await
+            .unwrap();
+
+        // Wait for a few reconnections, which all go through the endpoint given by the first request
+        let endpoint = format!("127.0.0.1:{tcpros_port}");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(topic_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            topic_endpoints.get(&publisher_uri, "/chatter"),
+            Some(endpoint.clone())
+        );
+        assert_eq!(topic_endpoints.entries()[0].endpoint, endpoint);
+
+        // Once the endpoint stops accepting connections the topic is requested again
+        tcpros_task.abort();
+        timeout(Duration::from_secs(1), async {
+            while topic_requests.load(Ordering::SeqCst) < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Should request the topic again");
+        timeout(Duration::from_secs(1), async {
+            while !subscription.publisher_tasks[&publisher_uri].is_finished() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Should give up on the publisher");
+        assert_eq!(topic_endpoints.get(&publisher_uri, "/chatter"), None);
+
+        // Publishers which leave the topic are forgotten
+        topic_endpoints.insert(&publisher_uri, "/chatter", endpoint);
+        subscription.set_publishers(&[]).await.unwrap();
+        assert!(topic_endpoints.entries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn tcp_nodelay_is_set_on_publisher_connections() {
+        // Stands in for a publisher, echoing the subscriber's header back to match its md5sum
+        let tcpros = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = tcpros.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = tcpros.accept().await.unwrap();
+                let mut header = Vec::with_capacity(1024);
+                socket.read_buf(&mut header).await.unwrap();
+                socket.write_all(&header).await.unwrap();
+            }
+        });
+        for tcp_nodelay in [true, false] {
+            let subscription = Subscription::new(
+                "/listener",
+                "/chatter",
+                "std_msgs/String",
+                1,
+                String::new(),
+                String::new(),
+                BusConnections::default(),
+            )
+            .tcp_nodelay(tcp_nodelay);
+            let (stream, publisher_header) = connect_to_publisher(
+                &endpoint,
+                "/chatter",
+                subscription.connection_header().clone(),
+                HeaderValidation::default(),
+                &PeerErrors::default(),
+                DEFAULT_MAX_MESSAGE_SIZE,
+                &TcprosSecurity::default(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(stream.tcp().unwrap().nodelay().unwrap(), tcp_nodelay);
+            assert_eq!(publisher_header.tcp_nodelay, tcp_nodelay);
+        }
+    }
+
+    #[tokio::test]
+    async fn publishers_of_any_type_are_accepted() {
+        let subscription = Subscription::new(
+            "/listener",
+            "/chatter",
+            "std_msgs/String",
+            1,
+            String::new(),
+            "992ce8a1687cec8c8bd883ec73ca41d1".to_owned(),
+            BusConnections::default(),
+        );
+        for (md5sum, accepted) in [("*", true), ("d41d8cd98f00b204e9800998ecf8427e", false)] {
+            // Stands in for a publisher answering with the given md5sum, like rostopic and AnyMsg publishers
+            let tcpros = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let endpoint = tcpros.local_addr().unwrap().to_string();
+            let mut publisher_header = subscription.connection_header().clone();
+            publisher_header.md5sum = md5sum.to_owned();
+            tokio::spawn(async move {
+                let (mut socket, _) = tcpros.accept().await.unwrap();
+                let mut header = Vec::with_capacity(1024);
+                socket.read_buf(&mut header).await.unwrap();
+                let response = publisher_header.to_bytes(false).unwrap();
+                socket.write_all(&response).await.unwrap();
+            });
+            let connected = connect_to_publisher(
+                &endpoint,
+                "/chatter",
+                subscription.connection_header().clone(),
+                HeaderValidation::default(),
+                &PeerErrors::default(),
+                DEFAULT_MAX_MESSAGE_SIZE,
+                &TcprosSecurity::default(),
+            )
+            .await;
+            assert_eq!(connected.is_ok(), accepted);
+        }
+    }
+
+    #[tokio::test]
+    async fn refused_connections_are_reported() {
+        let subscription = Subscription::new(
+            "/listener",
+            "/chatter",
+            "std_msgs/String",
+            1,
+            String::new(),
+            "992ce8a1687cec8c8bd883ec73ca41d1".to_owned(),
+            BusConnections::default(),
+        );
+        // Stands in for a publisher refusing the connection, answering with only an error as roscpp does
+        let tcpros = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = tcpros.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut socket, _) = tcpros.accept().await.unwrap();
+            let mut header = Vec::with_capacity(1024);
+            socket.read_buf(&mut header).await.unwrap();
+            let field = b"error=topic types do not match";
+            let mut response = ((field.len() + 4) as u32).to_le_bytes().to_vec();
+            response.extend((field.len() as u32).to_le_bytes());
+            response.extend(field);
+            socket.write_all(&response).await.unwrap();
+        });
+
+        let peer_errors = PeerErrors::default();
+        let mut errors = peer_errors.subscribe();
+        let connected = connect_to_publisher(
+            &endpoint,
+            "/chatter",
+            subscription.connection_header().clone(),
+            HeaderValidation::default(),
+            &peer_errors,
+            DEFAULT_MAX_MESSAGE_SIZE,
+            &TcprosSecurity::default(),
+        )
+        .await;
+        let err = connected.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        // Refused connections aren't retried
+        assert!(TopicMismatch::is(&err));
+        let error = errors.try_recv().unwrap();
+        assert_eq!(error.kind, PeerErrorKind::HandshakeRefused);
+        assert_eq!(error.peer, endpoint);
+        assert_eq!(error.name, "/chatter");
+        assert_eq!(error.message, "topic types do not match");
+    }
+
+    #[tokio::test]
+    async fn first_message_from_latching_publishers_is_flagged() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (mut publisher, _) = listener.accept().await.unwrap();
+        let subscription = Subscription::new(
+            "/listener",
+            "/chatter",
+            "std_msgs/String",
+            2,
+            String::new(),
+            String::new(),
+            BusConnections::default(),
+        );
+        let mut receiver = subscription.get_receiver();
+        let mut publisher_header = subscription.connection_header().clone();
+        publisher_header.latching = true;
+        tokio::spawn(async move {
+            read_publisher_connection(
+                stream.into(),
+                "http://127.0.0.1/",
+                &publisher_header,
+                "/chatter",
+                &subscription.queue,
+                &subscription.account,
+                None,
+                &BusConnections::default(),
+                &PeerErrors::default(),
+                DEFAULT_MAX_MESSAGE_SIZE,
+                None,
+            )
+            .await
+        });
+
+        for (data, latched) in [(1, true), (2, false)] {
+            publisher.write_all(&[1, 0, 0, 0, data]).await.unwrap();
+            let message = timeout(Duration::from_secs(1), receiver.recv())
+                .await
+                .expect("Message should be forwarded")
+                .unwrap();
+            assert_eq!(message.data, vec![1, 0, 0, 0, data]);
+            assert_eq!(message.latched, latched);
+        }
+    }
+
+    #[tokio::test]
+    async fn idle_publishers_are_dropped() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (mut publisher, _) = listener.accept().await.unwrap();
+        let options = SocketOptions {
+            keepalive: Some(Duration::from_secs(5)),
+            ..Default::default()
+        };
+        options.apply(&stream, "/chatter");
+        assert!(socket2::SockRef::from(&stream).keepalive().unwrap());
+        let subscription = Subscription::new(
+            "/listener",
+            "/chatter",
+            "std_msgs/String",
+            4,
+            String::new(),
+            String::new(),
+            BusConnections::default(),
+        );
+        let mut receiver = subscription.get_receiver();
+        let publisher_header = subscription.connection_header().clone();
+        let reader = tokio::spawn(async move {
+            read_publisher_connection(
+                stream.into(),
+                "http://127.0.0.1/",
+                &publisher_header,
+                "/chatter",
+                &subscription.queue,
+                &subscription.account,
+                None,
+                &BusConnections::default(),
+                &PeerErrors::default(),
+                DEFAULT_MAX_MESSAGE_SIZE,
+                Some(Duration::from_millis(200)),
+            )
+            .await
+        });
+
+        // Messages within the timeout keep the connection open, then the publisher goes silent without closing it
+        for _ in 0..3 {
+            publisher.write_all(b"\x02\0\0\0hi").await.unwrap();
+            timeout(Duration::from_secs(1), receiver.recv())
+                .await
+                .expect("Message should be forwarded")
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        timeout(Duration::from_secs(1), reader)
+            .await
+            .expect("Idle connection should be dropped")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn oversized_messages_close_the_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (mut publisher, _) = listener.accept().await.unwrap();
+        let subscription = Subscription::new(
+            "/listener",
+            "/chatter",
+            "std_msgs/String",
+            4,
+            String::new(),
+            String::new(),
+            BusConnections::default(),
+        );
+        let mut receiver = subscription.get_receiver();
+        let mut publisher_header = subscription.connection_header().clone();
+        publisher_header.caller_id = "/talker".to_owned();
+        let peer_errors = PeerErrors::default();
+        let mut errors = peer_errors.subscribe();
+        let reader = tokio::spawn(async move {
+            read_publisher_connection(
+                stream.into(),
+                "http://127.0.0.1/",
+                &publisher_header,
+                "/chatter",
+                &subscription.queue,
+                &subscription.account,
+                None,
+                &BusConnections::default(),
+                &peer_errors,
+                16,
+                None,
+            )
+            .await
+        });
+
+        // A message within the maximum, then a corrupt length prefix announcing far more
+        publisher.write_all(b"\x05\0\0\0first").await.unwrap();
+        publisher
+            .write_all(b"\xff\xff\xff\x7fsecond")
+            .await
+            .unwrap();
+        let message = timeout(Duration::from_secs(1), receiver.recv())
+            .await
+            .expect("Message should be forwarded")
+            .unwrap();
+        assert_eq!(message.data, &b"\x05\0\0\0first"[..]);
+        timeout(Duration::from_secs(1), reader)
+            .await
+            .expect("Connection should be closed")
+            .unwrap();
+        let error = errors.try_recv().unwrap();
+        assert_eq!(error.kind, PeerErrorKind::Malformed);
+        assert_eq!(error.peer, "/talker");
+    }
+
+    #[tokio::test]
+    async fn messages_are_split_on_their_length_prefix() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (mut publisher, _) = listener.accept().await.unwrap();
+        let subscription = Subscription::new(
+            "/listener",
+            "/chatter",
+            "std_msgs/String",
+            4,
+            String::new(),
+            String::new(),
+            BusConnections::default(),
+        );
+        let mut receiver = subscription.get_receiver();
+        let publisher_header = subscription.connection_header().clone();
+        tokio::spawn(async move {
+            read_publisher_connection(
+                stream.into(),
+                "http://127.0.0.1/",
+                &publisher_header,
+                "/chatter",
+                &subscription.queue,
+                &subscription.account,
+                None,
+                &BusConnections::default(),
+                &PeerErrors::default(),
+                DEFAULT_MAX_MESSAGE_SIZE,
+                None,
+            )
+            .await
+        });
+
+        // Messages coalesced into one segment, then one split across segments, even within its length prefix
+        publisher
+            .write_all(b"\x05\0\0\0first\x06\0\0\0second")
+            .await
+            .unwrap();
+        publisher.write_all(b"\x05\0").await.unwrap();
+        publisher.flush().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        publisher.write_all(b"\0\0thi").await.unwrap();
+        publisher.flush().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        publisher.write_all(b"rd").await.unwrap();
+
+        for expected in [
+            &b"\x05\0\0\0first"[..],
+            b"\x06\0\0\0second",
+            b"\x05\0\0\0third",
+        ] {
+            let message = timeout(Duration::from_secs(1), receiver.recv())
+                .await
+                .expect("Message should be forwarded")
+                .unwrap();
+            assert_eq!(message.data, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn messages_failing_their_checksum_are_dropped() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (mut publisher, _) = listener.accept().await.unwrap();
+        let failures = ChecksumFailures::default();
+        let subscription = Subscription::new(
+            "/listener",
+            "/chatter",
+            "std_msgs/String",
+            2,
+            String::new(),
+            String::new(),
+            BusConnections::default(),
+        )
+        .checksum(failures.clone());
+        assert!(subscription.connection_header().checksum);
+        let mut receiver = subscription.get_receiver();
+        let mut publisher_header = subscription.connection_header().clone();
+        // Publishers which don't agree to send checksums are received unchecked
+        publisher_header.checksum = false;
+        assert!(verified_checksums(&subscription.checksum_failures, &publisher_header).is_none());
+        publisher_header.checksum = true;
+        tokio::spawn(async move {
+            let checksum_failures =
+                verified_checksums(&subscription.checksum_failures, &publisher_header);
+            read_publisher_connection(
+                stream.into(),
+                "http://127.0.0.1/",
+                &publisher_header,
+                "/chatter",
+                &subscription.queue,
+                &subscription.account,
+                checksum_failures,
+                &BusConnections::default(),
+                &PeerErrors::default(),
+                DEFAULT_MAX_MESSAGE_SIZE,
+                None,
+            )
+            .await
+        });
+
+        let message = b"\x02\0\0\0hi".to_vec();
+        let checked = checksum::append(&message);
+        let mut corrupted = checked.clone();
+        corrupted[5] ^= 0x01;
+        publisher.write_all(&corrupted).await.unwrap();
+        timeout(Duration::from_secs(1), async {
+            while failures.topic_failures("/chatter") == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Corrupted message should be counted");
+
+        publisher.write_all(&checked).await.unwrap();
+        let received = timeout(Duration::from_secs(1), receiver.recv())
+            .await
+            .expect("Message should be forwarded")
+            .unwrap();
+        assert_eq!(received.data, message);
+        assert_eq!(failures.topic_failures("/chatter"), 1);
+    }
+
+    #[tokio::test]
+    async fn any_subscriptions_accept_every_publisher() {
+        let subscription = Subscription::new(
+            "/recorder",
+            "/chatter",
+            "*",
+            1,
+            String::new(),
+            "*".to_owned(),
+            BusConnections::default(),
+        );
+        let mut publisher_header = subscription.connection_header().clone();
+        publisher_header.caller_id = "/talker".to_owned();
+        publisher_header.topic_type = "std_msgs/String".to_owned();
+        publisher_header.md5sum = "992ce8a1687cec8c8bd883ec73ca41d1".to_owned();
+        // Stands in for a publisher, answering with its own header whatever the subscriber asks for
+        let tcpros = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = tcpros.local_addr().unwrap().to_string();
+        let response = publisher_header.to_bytes(false).unwrap();
+        let (publish, published) = tokio::sync::oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let (mut socket, _) = tcpros.accept().await.unwrap();
+            let mut header = Vec::with_capacity(1024);
+            socket.read_buf(&mut header).await.unwrap();
+            socket.write_all(&response).await.unwrap();
+            // Sent once the subscriber has read our header
+            published.await.unwrap();
+            socket.write_all(b"\x02\0\0\0hi").await.unwrap();
+        });
+
+        let (stream, header) = connect_to_publisher(
+            &endpoint,
+            "/chatter",
+            subscription.connection_header().clone(),
+            HeaderValidation::default(),
+            &PeerErrors::default(),
+            DEFAULT_MAX_MESSAGE_SIZE,
+            &TcprosSecurity::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(header.topic_type, "std_msgs/String");
+
+        let mut subscriber = AnySubscriber::new(subscription.get_receiver(), Default::default());
+        publish.send(()).unwrap();
+        tokio::spawn(async move {
+            read_publisher_connection(
+                stream,
+                "http://127.0.0.1/",
+                &header,
+                "/chatter",
+                &subscription.queue,
+                &subscription.account,
+                None,
+                &BusConnections::default(),
+                &PeerErrors::default(),
+                DEFAULT_MAX_MESSAGE_SIZE,
+                None,
+            )
+            .await
+        });
+        let msg = timeout(Duration::from_secs(1), subscriber.next())
+            .await
+            .expect("Message should be forwarded")
+            .unwrap();
+        assert_eq!(msg.data, b"\x02\0\0\0hi");
+        assert_eq!(msg.publisher.caller_id, "/talker");
+        assert_eq!(msg.publisher.md5sum, publisher_header.md5sum);
+    }
+
+    #[tokio::test]
+    async fn deserialize_errors_follow_the_policy() {
+        let err = || std::io::Error::from(std::io::ErrorKind::InvalidData);
+        let data = || vec![4, 0, 0, 0, 1, 2, 3];
+        assert!(DeserializeErrorPolicy::Error
+            .handle("std_msgs/String", data(), err())
+            .is_err());
+        assert!(DeserializeErrorPolicy::Skip
+            .handle("std_msgs/String", data(), err())
+            .is_ok());
+
+        let (sender, mut dead_letters) = tokio::sync::mpsc::channel(1);
+        let policy = DeserializeErrorPolicy::DeadLetter(sender);
+        assert!(policy.handle("std_msgs/String", data(), err()).is_ok());
+        // Letters which don't fit are dropped rather than blocking the subscriber
+        assert!(policy.handle("std_msgs/String", data(), err()).is_ok());
+        let letter = dead_letters.recv().await.unwrap();
+        assert_eq!(letter.topic_type, "std_msgs/String");
+        assert_eq!(letter.data, data());
+        assert_eq!(letter.error, err().to_string());
+        assert!(dead_letters.try_recv().is_err());
     }
 }
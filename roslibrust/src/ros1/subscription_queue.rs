@@ -0,0 +1,405 @@
+//! This module contains the queue a subscription hands received messages to the node's subscribers through,
+//! what happens to messages when a subscriber falls behind, and the replay of latched messages to subscribers
+//! joining a subscription after its latching publishers connected.
+
+use super::{node::TopicRegistration, subscriber::RawMessage};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::sync::broadcast::{
+    self,
+    error::{RecvError, TryRecvError},
+};
+
+/// What happens when a subscriber falls further behind its topic than the queue size of the subscription,
+/// set with [super::TopicOptions::queue_overflow]. Drops are counted in [super::Subscriber::stats].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QueueOverflowPolicy {
+    /// The oldest messages are dropped to make room, and the next receive of a subscriber which missed them
+    /// fails with how many it missed before carrying on from the oldest message left. The default.
+    #[default]
+    Error,
+    /// The oldest messages are dropped to make room, silently skipped by subscribers which missed them
+    DropOldest,
+    /// Incoming messages are dropped until the slowest subscriber of the topic makes room, so subscribers
+    /// receive every message up to the point they fell behind. Every subscriber of the topic misses the dropped messages.
+    DropNewest,
+}
+
+/// Counts of the messages a subscriber received and dropped, retrieved with [super::Subscriber::stats]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SubscriberStats {
+    /// Messages the subscriber received
+    pub received: u64,
+    /// Messages dropped since the subscriber subscribed because it, or under [QueueOverflowPolicy::DropNewest]
+    /// another subscriber of the topic, fell behind
+    pub dropped: u64,
+}
+
+#[derive(Debug)]
+struct Shared {
+    policy: QueueOverflowPolicy,
+    // Messages sent which some receiver has yet to read
+    held: AtomicUsize,
+    // Messages refused under DropNewest
+    dropped: AtomicU64,
+    latched: Mutex<Latched>,
+}
+
+// The last message of each connected latching publisher, like roscpp's latched messages of a subscription
+#[derive(Debug, Default)]
+struct Latched {
+    next_link: u64,
+    // Keyed by link, so messages are replayed in the order their publishers connected
+    messages: BTreeMap<u64, RawMessage>,
+}
+
+// Counts a message as held until every receiver has read it and the channel releases it
+#[derive(Debug)]
+struct Held(Arc<Shared>);
+
+impl Drop for Held {
+    fn drop(&mut self) {
+        self.0.held.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Queued {
+    message: RawMessage,
+    _held: Arc<Held>,
+}
+
+/// The sending side of a subscription's queue, applying its [QueueOverflowPolicy]
+#[derive(Clone, Debug)]
+pub(crate) struct SubscriptionQueue {
+    sender: broadcast::Sender<Queued>,
+    capacity: usize,
+    shared: Arc<Shared>,
+}
+
+impl SubscriptionQueue {
+    pub fn new(capacity: usize, policy: QueueOverflowPolicy) -> Self {
+        // No receiver is kept here, so a message is released as soon as every subscriber has read it
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            capacity,
+            shared: Arc::new(Shared {
+                policy,
+                held: AtomicUsize::new(0),
+                dropped: AtomicU64::new(0),
+                latched: Mutex::default(),
+            }),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// A receiver of the messages sent from now on, which first receives the last message of every
+    /// latching publisher already linked, flagged as latched
+    pub fn subscribe(&self) -> QueueReceiver {
+        // Links hold the lock while sending, so no latched message is both replayed and received
+        let latched = self.shared.latched.lock().unwrap();
+        let replay = latched
+            .messages
+            .values()
+            .map(|message| RawMessage {
+                latched: true,
+                ..message.clone()
+            })
+            .collect();
+        QueueReceiver {
+            receiver: self.sender.subscribe(),
+            replay,
+            shared: self.shared.clone(),
+            received: 0,
+            lagged: 0,
+            dropped_before: self.shared.dropped.load(Ordering::Relaxed),
+            registration: None,
+        }
+    }
+
+    pub fn receiver_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Links a publisher connection to the queue, through which it sends its messages
+    pub fn link(&self, latching: bool) -> PublisherLink {
+        let id = latching.then(|| {
+            let mut latched = self.shared.latched.lock().unwrap();
+            latched.next_link += 1;
+            latched.next_link
+        });
+        PublisherLink {
+            queue: self.clone(),
+            id,
+        }
+    }
+
+    /// Hands `message` to every receiver, returning false if there were none to receive it
+    pub fn send(&self, message: RawMessage, topic_name: &str) -> bool {
+        if self.shared.policy == QueueOverflowPolicy::DropNewest
+            && self.shared.held.load(Ordering::Relaxed) >= self.capacity
+        {
+            log::debug!("Queue of {topic_name} is full, dropping an incoming message");
+            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+            // Every receiver is still around to miss it
+            return true;
+        }
+        self.shared.held.fetch_add(1, Ordering::Relaxed);
+        let queued = Queued {
+            message,
+            _held: Arc::new(Held(self.shared.clone())),
+        };
+        self.sender.send(queued).is_ok()
+    }
+}
+
+/// The connection of a publisher to a [SubscriptionQueue], which keeps the last message of a latching publisher
+/// for replaying to receivers subscribing later, until the connection closes
+pub(crate) struct PublisherLink {
+    queue: SubscriptionQueue,
+    // Set for latching publishers
+    id: Option<u64>,
+}
+
+impl PublisherLink {
+    /// Sends `message` like [SubscriptionQueue::send]
+    pub fn send(&self, message: RawMessage, topic_name: &str) -> bool {
+        let Some(id) = self.id else {
+            return self.queue.send(message, topic_name);
+        };
+        let mut latched = self.queue.shared.latched.lock().unwrap();
+        latched.messages.insert(id, message.clone());
+        self.queue.send(message, topic_name)
+    }
+}
+
+impl Drop for PublisherLink {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.queue
+                .shared
+                .latched
+                .lock()
+                .unwrap()
+                .messages
+                .remove(&id);
+        }
+    }
+}
+
+/// Receives the messages of a subscription, applying its [QueueOverflowPolicy] when falling behind
+#[derive(Debug)]
+pub(crate) struct QueueReceiver {
+    receiver: broadcast::Receiver<Queued>,
+    // Latched messages received before those of the channel
+    replay: VecDeque<RawMessage>,
+    shared: Arc<Shared>,
+    received: u64,
+    // Messages this receiver missed by falling behind
+    lagged: u64,
+    // Messages refused under DropNewest before this receiver subscribed
+    dropped_before: u64,
+    // Unsubscribes the node from the topic once the last receiver is dropped, declared last so
+    // the channel's receiver is gone by then
+    registration: Option<TopicRegistration>,
+}
+
+impl QueueReceiver {
+    /// Asks the node to unregister the topic once this receiver is dropped
+    pub fn unregister_on_drop(mut self, registration: TopicRegistration) -> Self {
+        self.registration = Some(registration);
+        self
+    }
+
+    /// Drops the receiver, returning its registration for the caller to release
+    pub fn into_registration(mut self) -> Option<TopicRegistration> {
+        self.registration.take()
+    }
+
+    /// Receives the next message like [broadcast::Receiver::recv],
+    /// only failing with [RecvError::Lagged] under [QueueOverflowPolicy::Error]
+    pub async fn recv(&mut self) -> Result<RawMessage, RecvError> {
+        if let Some(message) = self.replay.pop_front() {
+            self.received += 1;
+            return Ok(message);
+        }
+        loop {
+            match self.receiver.recv().await {
+                Ok(Queued { message, .. }) => {
+                    self.received += 1;
+                    return Ok(message);
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    self.lagged += skipped;
+                    if self.shared.policy == QueueOverflowPolicy::Error {
+                        return Err(RecvError::Lagged(skipped));
+                    }
+                    log::debug!("Subscriber fell behind, dropped {skipped} messages");
+                }
+                Err(RecvError::Closed) => return Err(RecvError::Closed),
+            }
+        }
+    }
+
+    /// Skips the messages waiting to be received, counting them as received
+    pub fn skip_pending(&mut self) {
+        self.received += self.replay.len() as u64;
+        self.replay.clear();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(_) => self.received += 1,
+                Err(TryRecvError::Lagged(skipped)) => self.lagged += skipped,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => return,
+            }
+        }
+    }
+
+    pub fn stats(&self) -> SubscriberStats {
+        let dropped = self.shared.dropped.load(Ordering::Relaxed) - self.dropped_before;
+        SubscriberStats {
+            received: self.received,
+            dropped: self.lagged + dropped,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{QueueOverflowPolicy, SubscriptionQueue};
+    use crate::ros1::{
+        memory_budgets::MemoryBudgets, subscriber::RawMessage, tcpros::ConnectionHeader,
+    };
+    use std::{collections::BTreeMap, sync::Arc};
+    use tokio::sync::broadcast::error::RecvError;
+
+    async fn message(data: u8) -> RawMessage {
+        let account = MemoryBudgets::default().account("/chatter", None);
+        let publisher = ConnectionHeader {
+            caller_id: "/talker".to_owned(),
+            latching: false,
+            msg_definition: String::new(),
+            md5sum: String::new(),
+            topic: "/chatter".to_owned(),
+            topic_type: "std_msgs/String".to_owned(),
+            tcp_nodelay: false,
+            service: None,
+            persistent: false,
+            probe: false,
+            error: None,
+            checksum: false,
+            custom_fields: BTreeMap::new(),
+        };
+        RawMessage {
+            data: vec![data].into(),
+            local: None,
+            latched: false,
+            publisher: Arc::new(publisher),
+            _charge: Arc::new(account.charge_incoming(1).await.unwrap()),
+        }
+    }
+
+    async fn overflow(policy: QueueOverflowPolicy) -> (Vec<Result<u8, RecvError>>, u64) {
+        let queue = SubscriptionQueue::new(2, policy);
+        let mut receiver = queue.subscribe();
+        for data in 0..5 {
+            assert!(queue.send(message(data).await, "/chatter"));
+        }
+        let mut received = vec![];
+        while let Ok(result) =
+            tokio::time::timeout(std::time::Duration::from_millis(10), receiver.recv()).await
+        {
+            received.push(result.map(|message| message.data[0]));
+        }
+        (received, receiver.stats().dropped)
+    }
+
+    #[tokio::test]
+    async fn overflows_follow_the_policy() {
+        assert_eq!(
+            overflow(QueueOverflowPolicy::Error).await,
+            (vec![Err(RecvError::Lagged(3)), Ok(3), Ok(4)], 3)
+        );
+        assert_eq!(
+            overflow(QueueOverflowPolicy::DropOldest).await,
+            (vec![Ok(3), Ok(4)], 3)
+        );
+        assert_eq!(
+            overflow(QueueOverflowPolicy::DropNewest).await,
+            (vec![Ok(0), Ok(1)], 3)
+        );
+    }
+
+    #[tokio::test]
+    async fn pending_messages_are_skipped() {
+        let queue = SubscriptionQueue::new(2, QueueOverflowPolicy::DropOldest);
+        let mut receiver = queue.subscribe();
+        for data in 0..3 {
+            assert!(queue.send(message(data).await, "/chatter"));
+        }
+        receiver.skip_pending();
+        assert!(queue.send(message(3).await, "/chatter"));
+        assert_eq!(receiver.recv().await.unwrap().data, vec![3]);
+        let stats = receiver.stats();
+        assert_eq!((stats.received, stats.dropped), (3, 1));
+    }
+
+    #[tokio::test]
+    async fn read_messages_make_room() {
+        let queue = SubscriptionQueue::new(1, QueueOverflowPolicy::DropNewest);
+        let mut receiver = queue.subscribe();
+        for data in 0..3 {
+            assert!(queue.send(message(data).await, "/chatter"));
+            assert_eq!(receiver.recv().await.unwrap().data, vec![data]);
+        }
+        let stats = receiver.stats();
+        assert_eq!((stats.received, stats.dropped), (3, 0));
+
+        // Receivers only count what was dropped since they subscribed
+        assert!(queue.send(message(3).await, "/chatter"));
+        assert!(queue.send(message(4).await, "/chatter"));
+        assert_eq!(queue.subscribe().stats().dropped, 0);
+        drop(receiver);
+        assert!(!queue.send(message(5).await, "/chatter"));
+    }
+
+    #[tokio::test]
+    async fn latched_messages_are_replayed() {
+        let queue = SubscriptionQueue::new(4, QueueOverflowPolicy::default());
+        let first = queue.link(true);
+        let second = queue.link(true);
+        let unlatched = queue.link(false);
+        let mut receiver = queue.subscribe();
+        for (link, data) in [(&second, 1), (&first, 2), (&first, 3), (&unlatched, 4)] {
+            assert!(link.send(message(data).await, "/chatter"));
+        }
+        assert_eq!(receiver.recv().await.unwrap().data, vec![1]);
+
+        // Receivers subscribing later start with the last message of each latching publisher,
+        // in the order the publishers connected
+        let mut later = queue.subscribe();
+        for data in [3, 1] {
+            let message = later.recv().await.unwrap();
+            assert_eq!((message.data, message.latched), (vec![data].into(), true));
+        }
+        assert!(unlatched.send(message(5).await, "/chatter"));
+        assert_eq!(later.recv().await.unwrap().data, vec![5]);
+        assert_eq!(later.stats().received, 3);
+
+        // Publishers which disconnected are no longer replayed
+        drop(first);
+        let mut last = queue.subscribe();
+        assert_eq!(last.recv().await.unwrap().data, vec![1]);
+        last.skip_pending();
+        assert!(unlatched.send(message(6).await, "/chatter"));
+        assert_eq!(last.recv().await.unwrap().data, vec![6]);
+    }
+}
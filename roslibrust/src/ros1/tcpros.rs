@@ -1,9 +1,156 @@
+use super::checksum;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use std::io::{Cursor, Read, Write};
+use std::{
+    collections::BTreeMap,
+    io::{Cursor, Read, Write},
+    time::Duration,
+};
+use tokio::net::TcpStream;
+
+/// The md5sum and type subscribers of any type connect with, like rospy's AnyMsg
+pub(crate) const WILDCARD: &str = "*";
+
+/// Whether the md5sums of the two ends of a connection agree, where either end may send the wildcard,
+/// as rostopic, rosbag and AnyMsg publishers and subscribers do when they don't know the type in advance
+pub(crate) fn md5sums_match(ours: &str, theirs: &str) -> bool {
+    ours == theirs || ours == WILDCARD || theirs == WILDCARD
+}
+
+/// How strictly the connection headers a topic's publishers and subscribers send are checked against our own,
+/// see [super::TopicOptions::header_validation]. Whatever the mode, md5sums must agree for messages to be understood.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HeaderValidation {
+    /// Mismatched type names and missing `callerid` or `type` fields are logged as warnings, where they would reject
+    /// the connection, for peers of ROS distros and client libraries which fill in headers inconsistently
+    Lenient,
+    /// Type names must agree and headers must carry `callerid` and `type`. The default.
+    #[default]
+    Standard,
+    /// As Standard, with the message definition also required to agree, ignoring blank lines, trailing whitespace
+    /// and the definitions of nested messages peers may append
+    Strict,
+}
+
+impl HeaderValidation {
+    /// Checks the header a peer sent for a topic against our own,
+    /// returning why the connection is rejected if it is
+    pub(crate) fn check(
+        self,
+        ours: &ConnectionHeader,
+        theirs: &ConnectionHeader,
+    ) -> Result<(), String> {
+        let topic = &ours.topic;
+        if !md5sums_match(&ours.md5sum, &theirs.md5sum) {
+            return Err(format!(
+                "md5sums for {topic} do not match, expected {}, received {}",
+                ours.md5sum, theirs.md5sum
+            ));
+        }
+        let mut problems = vec![];
+        if theirs.caller_id.is_empty() {
+            problems.push("no callerid".to_owned());
+        }
+        if theirs.topic_type.is_empty() {
+            problems.push("no type".to_owned());
+        } else if ours.topic_type != theirs.topic_type
+            && ours.topic_type != WILDCARD
+            && theirs.topic_type != WILDCARD
+        {
+            problems.push(format!(
+                "type {} where {} was expected",
+                theirs.topic_type, ours.topic_type
+            ));
+        }
+        // Definitions can only be compared between peers which both know the type
+        let typed = ours.md5sum != WILDCARD && theirs.md5sum != WILDCARD;
+        if self == HeaderValidation::Strict && typed && !ours.msg_definition.is_empty() {
+            if theirs.msg_definition.is_empty() {
+                problems.push("no message_definition".to_owned());
+            } else if !definitions_match(&ours.msg_definition, &theirs.msg_definition) {
+                problems.push("a different message_definition".to_owned());
+            }
+        }
+        if problems.is_empty() {
+            return Ok(());
+        }
+        let problems = format!(
+            "Header of {} for {topic} has {}",
+            theirs.caller_id,
+            problems.join(", ")
+        );
+        if self == HeaderValidation::Lenient {
+            log::warn!("{problems}, accepting it anyway");
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+}
+
+// The line roscpp and rospy separate the definitions of nested messages from that of the message itself with
+const NESTED_DEFINITION_SEPARATOR: &str =
+    "================================================================================";
+
+// Whether two message definitions agree, comparing only the message's own definition
+fn definitions_match(ours: &str, theirs: &str) -> bool {
+    own_definition_lines(ours).eq(own_definition_lines(theirs))
+}
+
+fn own_definition_lines(definition: &str) -> impl Iterator<Item = &str> {
+    definition
+        .lines()
+        .map(str::trim_end)
+        .take_while(|line| *line != NESTED_DEFINITION_SEPARATOR)
+        .filter(|line| !line.is_empty())
+}
+
+/// Options for the TCP sockets carrying a topic, see [super::TopicOptions]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SocketOptions {
+    pub tcp_nodelay: bool,
+    pub send_buffer_size: Option<usize>,
+    pub recv_buffer_size: Option<usize>,
+    // How long a connection is idle before keepalive probes are sent, if enabled
+    pub keepalive: Option<Duration>,
+}
+
+impl SocketOptions {
+    // Applies the options to a connection of `topic_name`. Those the operating system refuses are logged,
+    // as the connection still works without them.
+    pub fn apply(&self, stream: &TcpStream, topic_name: &str) {
+        let socket = socket2::SockRef::from(stream);
+        let mut failed = vec![];
+        if let Err(err) = socket.set_nodelay(self.tcp_nodelay) {
+            failed.push(format!("TCP_NODELAY ({err})"));
+        }
+        if let Some(size) = self.send_buffer_size {
+            if let Err(err) = socket.set_send_buffer_size(size) {
+                failed.push(format!("SO_SNDBUF ({err})"));
+            }
+        }
+        if let Some(size) = self.recv_buffer_size {
+            if let Err(err) = socket.set_recv_buffer_size(size) {
+                failed.push(format!("SO_RCVBUF ({err})"));
+            }
+        }
+        if let Some(idle) = self.keepalive {
+            let keepalive = socket2::TcpKeepalive::new().with_time(idle);
+            if let Err(err) = socket.set_tcp_keepalive(&keepalive) {
+                failed.push(format!("TCP keepalive ({err})"));
+            }
+        }
+        if !failed.is_empty() {
+            log::warn!(
+                "Failed to set {} on a connection of {topic_name}",
+                failed.join(", ")
+            );
+        }
+    }
+}
 
 // Implementation of ConnectionHeader is based off of ROS documentation here:
 // wiki.ros.org/ROS/Connection%20Header
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ConnectionHeader {
     pub caller_id: String,
     pub latching: bool,
@@ -12,6 +159,43 @@ pub struct ConnectionHeader {
     pub topic: String,
     pub topic_type: String,
     pub tcp_nodelay: bool,
+    // Set instead of topic when connecting to a service
+    pub service: Option<String>,
+    // Service clients request the connection be kept open between calls
+    pub persistent: bool,
+    // Requests only the header from a service without making a call
+    pub probe: bool,
+    // Sent in place of a normal header when the other side rejects a connection
+    pub error: Option<String>,
+    // Asks for or agrees to CRC32 checksums on each message, only understood by roslibrust peers
+    pub checksum: bool,
+    // Fields outside the standard set, sent after the standard fields and collected here when received,
+    // see [super::TopicOptions::header_field]
+    pub custom_fields: BTreeMap<String, String>,
+}
+
+// The fields ROS and roslibrust give a meaning to, which can't be sent as custom fields
+const STANDARD_FIELDS: [&str; 14] = [
+    "callerid",
+    "latching",
+    "md5sum",
+    "message_definition",
+    "tcp_nodelay",
+    "topic",
+    "type",
+    "service",
+    "persistent",
+    "probe",
+    "error",
+    "checksum",
+    "request_type",
+    "response_type",
+];
+
+/// Whether `key` can be sent as a custom connection header field, i.e. it isn't a standard field
+/// and doesn't contain the `=` separating it from its value
+pub(crate) fn is_custom_field(key: &str) -> bool {
+    !key.is_empty() && !key.contains('=') && !STANDARD_FIELDS.contains(&key)
 }
 
 impl ConnectionHeader {
@@ -29,14 +213,31 @@ impl ConnectionHeader {
         let mut topic = String::new();
         let mut topic_type = String::new();
         let mut tcp_nodelay = false;
-
-        // TODO: Unhandled: error, persistent
+        let mut service = None;
+        let mut persistent = false;
+        let mut probe = false;
+        let mut error = None;
+        let mut checksum = false;
+        let mut custom_fields = BTreeMap::new();
 
         while cursor.position() < header_data.len() as u64 {
             let field_length = cursor.read_u32::<LittleEndian>()? as usize;
+            // Checked before allocating, as the length is whatever the peer sent
+            let remaining = header_data.len() - cursor.position() as usize;
+            if field_length > remaining {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("header field of {field_length} bytes overruns the {remaining} left"),
+                ));
+            }
             let mut field = vec![0u8; field_length];
             cursor.read_exact(&mut field)?;
-            let field = String::from_utf8(field).unwrap();
+            let field = String::from_utf8(field).map_err(|err| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("header field is not UTF-8: {err}"),
+                )
+            })?;
             let equals_pos = match field.find('=') {
                 Some(pos) => pos,
                 None => continue,
@@ -59,8 +260,24 @@ impl ConnectionHeader {
                 let mut tcp_nodelay_str = String::new();
                 field[equals_pos + 1..].clone_into(&mut tcp_nodelay_str);
                 tcp_nodelay = &tcp_nodelay_str != "0";
+            } else if field.starts_with("service=") {
+                service = Some(field[equals_pos + 1..].to_owned());
+            } else if field.starts_with("persistent=") {
+                persistent = &field[equals_pos + 1..] != "0";
+            } else if field.starts_with("probe=") {
+                probe = &field[equals_pos + 1..] != "0";
+            } else if field.starts_with("error=") {
+                error = Some(field[equals_pos + 1..].to_owned());
+            } else if field.starts_with("checksum=") {
+                checksum = &field[equals_pos + 1..] == checksum::CRC32;
+            } else if field.starts_with("request_type=") || field.starts_with("response_type=") {
+                // Sent by service servers, the md5sum already covers both types
             } else {
-                log::warn!("Encountered unhandled field in connection header: {field}");
+                log::debug!("Received custom field in connection header: {field}");
+                custom_fields.insert(
+                    field[..equals_pos].to_owned(),
+                    field[equals_pos + 1..].to_owned(),
+                );
             }
         }
 
@@ -72,10 +289,19 @@ impl ConnectionHeader {
             topic,
             topic_type,
             tcp_nodelay,
+            service,
+            persistent,
+            probe,
+            error,
+            checksum,
+            custom_fields,
         })
     }
 
     pub fn to_bytes(&self, to_publisher: bool) -> std::io::Result<Vec<u8>> {
+        if let Some(service) = &self.service {
+            return self.to_service_bytes(service);
+        }
         let mut header_data = Vec::with_capacity(1024);
         // Start by skipping the length header since we don't know yet
         header_data.write_u32::<LittleEndian>(0)?;
@@ -110,6 +336,22 @@ impl ConnectionHeader {
         header_data.write_u32::<LittleEndian>(topic_type.len() as u32)?;
         header_data.write(topic_type.as_bytes())?;
 
+        if self.checksum {
+            let checksum = format!("checksum={}", checksum::CRC32);
+            header_data.write_u32::<LittleEndian>(checksum.len() as u32)?;
+            header_data.write_all(checksum.as_bytes())?;
+        }
+
+        for (key, value) in &self.custom_fields {
+            if !is_custom_field(key) {
+                log::warn!("Not sending connection header field {key}, it isn't a custom field");
+                continue;
+            }
+            let field = format!("{key}={value}");
+            header_data.write_u32::<LittleEndian>(field.len() as u32)?;
+            header_data.write_all(field.as_bytes())?;
+        }
+
         let total_length = (header_data.len() - 4) as u32;
         for (idx, byte) in total_length.to_le_bytes().iter().enumerate() {
             header_data[idx] = *byte;
@@ -117,4 +359,295 @@ impl ConnectionHeader {
 
         Ok(header_data)
     }
+    // Service clients send a reduced set of fields, see http://wiki.ros.org/ROS/TCPROS
+    fn to_service_bytes(&self, service: &str) -> std::io::Result<Vec<u8>> {
+        let mut fields = vec![
+            format!("callerid={}", self.caller_id),
+            format!("md5sum={}", self.md5sum),
+            format!("service={service}"),
+        ];
+        if self.persistent {
+            fields.push("persistent=1".to_owned());
+        }
+        if self.probe {
+            fields.push("probe=1".to_owned());
+        }
+
+        let mut header_data = Vec::with_capacity(256);
+        let total_length = fields.iter().map(|field| field.len() + 4).sum::<usize>();
+        header_data.write_u32::<LittleEndian>(total_length as u32)?;
+        for field in fields {
+            header_data.write_u32::<LittleEndian>(field.len() as u32)?;
+            header_data.write_all(field.as_bytes())?;
+        }
+        Ok(header_data)
+    }
+}
+
+/// The largest message read from a peer unless configured otherwise, see [super::TopicOptions::max_message_size].
+/// Matches roscpp, which refuses messages of over a gigabyte as the sign of a corrupt length prefix.
+pub(crate) const DEFAULT_MAX_MESSAGE_SIZE: usize = 1_000_000_000;
+
+// The most allocated up front for a frame, the rest is allocated as it arrives
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Reads a single length prefixed frame from the stream, returning it with the length prefix still attached.
+/// TCP doesn't preserve the boundaries of writes, so frames must be read this way rather than one per read.
+/// Frames are accumulated as they arrive, so a corrupt length prefix under `max_size` costs no more memory
+/// than the data actually sent, and fails with InvalidData when over it.
+pub(crate) async fn read_frame(
+    stream: &mut (impl tokio::io::AsyncRead + Unpin),
+    max_size: usize,
+) -> std::io::Result<Vec<u8>> {
+    use tokio::io::AsyncReadExt;
+    let length = stream.read_u32_le().await?;
+    if length as usize > max_size {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("a message of {length} bytes was announced, over the maximum of {max_size}"),
+        ));
+    }
+    let mut frame = Vec::with_capacity((length as usize).min(READ_CHUNK_SIZE) + 4);
+    frame.extend_from_slice(&length.to_le_bytes());
+    let read = (&mut *stream)
+        .take(length as u64)
+        .read_to_end(&mut frame)
+        .await?;
+    if read < length as usize {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(frame)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{md5sums_match, read_frame, ConnectionHeader, HeaderValidation, SocketOptions};
+    use std::{collections::BTreeMap, time::Duration};
+    use tokio::{
+        io::AsyncWriteExt,
+        net::{TcpListener, TcpStream},
+    };
+
+    #[test]
+    fn wildcards_match_any_md5sum() {
+        let md5sum = "992ce8a1687cec8c8bd883ec73ca41d1";
+        assert!(md5sums_match(md5sum, md5sum));
+        assert!(md5sums_match(md5sum, "*"));
+        assert!(md5sums_match("*", md5sum));
+        assert!(md5sums_match("*", "*"));
+        assert!(!md5sums_match(md5sum, "d41d8cd98f00b204e9800998ecf8427e"));
+    }
+
+    #[test]
+    fn header_validation_modes() {
+        let ours = ConnectionHeader {
+            caller_id: "/listener".to_owned(),
+            latching: false,
+            msg_definition: "string data\n".to_owned(),
+            md5sum: "992ce8a1687cec8c8bd883ec73ca41d1".to_owned(),
+            topic: "/chatter".to_owned(),
+            topic_type: "std_msgs/String".to_owned(),
+            tcp_nodelay: false,
+            service: None,
+            persistent: false,
+            probe: false,
+            error: None,
+            checksum: false,
+            custom_fields: BTreeMap::new(),
+        };
+        let nested = format!(
+            "string data\n{}\nMSG: std_msgs/Header\nuint32 seq",
+            super::NESTED_DEFINITION_SEPARATOR
+        );
+        let theirs = ConnectionHeader {
+            caller_id: "/talker".to_owned(),
+            msg_definition: nested,
+            ..ours.clone()
+        };
+        for mode in [
+            HeaderValidation::Lenient,
+            HeaderValidation::Standard,
+            HeaderValidation::Strict,
+        ] {
+            assert!(mode.check(&ours, &theirs).is_ok());
+            let mismatched = ConnectionHeader {
+                md5sum: "d41d8cd98f00b204e9800998ecf8427e".to_owned(),
+                ..theirs.clone()
+            };
+            assert!(mode.check(&ours, &mismatched).is_err());
+        }
+
+        let renamed = ConnectionHeader {
+            topic_type: "my_msgs/String".to_owned(),
+            ..theirs.clone()
+        };
+        let anonymous = ConnectionHeader {
+            caller_id: String::new(),
+            topic_type: String::new(),
+            ..theirs.clone()
+        };
+        for header in [&renamed, &anonymous] {
+            assert!(HeaderValidation::Lenient.check(&ours, header).is_ok());
+            assert!(HeaderValidation::Standard.check(&ours, header).is_err());
+        }
+
+        let redefined = ConnectionHeader {
+            msg_definition: "string text".to_owned(),
+            ..theirs.clone()
+        };
+        assert!(HeaderValidation::Standard.check(&ours, &redefined).is_ok());
+        assert!(HeaderValidation::Strict.check(&ours, &redefined).is_err());
+        // Peers of any type can't be held to a definition
+        let wildcard = ConnectionHeader {
+            md5sum: "*".to_owned(),
+            topic_type: "*".to_owned(),
+            msg_definition: String::new(),
+            ..theirs
+        };
+        assert!(HeaderValidation::Strict.check(&ours, &wildcard).is_ok());
+    }
+
+    #[test]
+    fn service_header_round_trip() {
+        let header = ConnectionHeader {
+            caller_id: "/client".to_owned(),
+            latching: false,
+            msg_definition: String::new(),
+            md5sum: "6a2e34150c00229791cc89ff309fff21".to_owned(),
+            topic: String::new(),
+            topic_type: String::new(),
+            tcp_nodelay: false,
+            service: Some("/add_two_ints".to_owned()),
+            persistent: true,
+            probe: false,
+            error: None,
+            checksum: false,
+            custom_fields: BTreeMap::new(),
+        };
+        let bytes = header.to_bytes(true).unwrap();
+        let parsed = ConnectionHeader::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.caller_id, "/client");
+        assert_eq!(parsed.md5sum, header.md5sum);
+        assert_eq!(parsed.service.as_deref(), Some("/add_two_ints"));
+        assert!(parsed.persistent);
+        assert!(!parsed.probe);
+        assert!(parsed.topic.is_empty());
+    }
+
+    #[test]
+    fn custom_fields_round_trip() {
+        let mut header = ConnectionHeader {
+            caller_id: "/talker".to_owned(),
+            latching: false,
+            msg_definition: "string data".to_owned(),
+            md5sum: "992ce8a1687cec8c8bd883ec73ca41d1".to_owned(),
+            topic: "/chatter".to_owned(),
+            topic_type: "std_msgs/String".to_owned(),
+            tcp_nodelay: false,
+            service: None,
+            persistent: false,
+            probe: false,
+            error: None,
+            checksum: false,
+            custom_fields: BTreeMap::from([
+                ("trace_id".to_owned(), "4bf92f35=77b3".to_owned()),
+                ("tenant".to_owned(), String::new()),
+            ]),
+        };
+        let parsed = ConnectionHeader::from_bytes(&header.to_bytes(false).unwrap()).unwrap();
+        assert_eq!(parsed, header);
+
+        // Standard fields can't be overridden through custom fields
+        header
+            .custom_fields
+            .insert("md5sum".to_owned(), "*".to_owned());
+        header
+            .custom_fields
+            .insert("a=b".to_owned(), "c".to_owned());
+        let parsed = ConnectionHeader::from_bytes(&header.to_bytes(false).unwrap()).unwrap();
+        assert_eq!(parsed.md5sum, header.md5sum);
+        assert_eq!(parsed.custom_fields.len(), 2);
+        assert!(!super::is_custom_field("callerid"));
+        assert!(super::is_custom_field("trace_id"));
+    }
+
+    #[test]
+    fn malformed_fields_are_invalid_data() {
+        fn header_of(field_length: u32, field: &[u8]) -> Vec<u8> {
+            let mut header = ((4 + field.len()) as u32).to_le_bytes().to_vec();
+            header.extend(field_length.to_le_bytes());
+            header.extend(field);
+            header
+        }
+
+        let err = ConnectionHeader::from_bytes(&header_of(4, b"a=\xff\xfe")).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        // Refused before anything the size of the claimed length is allocated
+        let err = ConnectionHeader::from_bytes(&header_of(u32::MAX, b"a=b")).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let err = ConnectionHeader::from_bytes(&header_of(4, b"a=b")).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn frames_larger_than_a_read_are_accumulated() {
+        // A pipe far smaller than the frame, so it arrives over many reads
+        let (mut reader, mut writer) = tokio::io::duplex(1024);
+        let payload = vec![7u8; 200 * 1024];
+        let mut sent = (payload.len() as u32).to_le_bytes().to_vec();
+        sent.extend(&payload);
+        tokio::spawn(async move { writer.write_all(&sent).await.unwrap() });
+
+        let frame = read_frame(&mut reader, payload.len()).await.unwrap();
+        assert_eq!(&frame[..4], (payload.len() as u32).to_le_bytes());
+        assert_eq!(&frame[4..], payload);
+    }
+
+    #[tokio::test]
+    async fn frames_over_the_maximum_size_are_refused() {
+        let (mut reader, mut writer) = tokio::io::duplex(1024);
+        // A corrupt length prefix, announcing far more than will ever be sent
+        writer.write_all(&u32::MAX.to_le_bytes()).await.unwrap();
+        let err = read_frame(&mut reader, 1024).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        // Under the maximum, a truncated frame only fails once the stream ends
+        writer.write_all(&[16, 0, 0, 0, 1, 2]).await.unwrap();
+        drop(writer);
+        let err = read_frame(&mut reader, 1024).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[tokio::test]
+    async fn socket_options_are_applied() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let options = SocketOptions {
+            tcp_nodelay: true,
+            send_buffer_size: Some(256 * 1024),
+            recv_buffer_size: Some(512 * 1024),
+            keepalive: Some(Duration::from_secs(5)),
+        };
+        options.apply(&stream, "/chatter");
+
+        let socket = socket2::SockRef::from(&stream);
+        assert!(socket.nodelay().unwrap());
+        assert!(socket.keepalive().unwrap());
+        // Sizes may be rounded or capped by the operating system, but differ from the defaults
+        let defaults = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let defaults = socket2::SockRef::from(&defaults);
+        assert_ne!(
+            socket.send_buffer_size().unwrap(),
+            defaults.send_buffer_size().unwrap()
+        );
+        assert_ne!(
+            socket.recv_buffer_size().unwrap(),
+            defaults.recv_buffer_size().unwrap()
+        );
+    }
 }
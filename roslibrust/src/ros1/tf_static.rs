@@ -0,0 +1,361 @@
+//! This module contains the StaticTransformBroadcaster and StaticTransforms for sharing static transforms on `/tf_static`,
+//! created with [super::NodeHandle::advertise_static_transforms] and [super::NodeHandle::subscribe_static_transforms].
+
+use super::{
+    node::{NodeShutdownError, ShutdownReason},
+    publisher::Publisher,
+    rosout::Header,
+    subscriber::Subscriber,
+};
+use abort_on_drop::ChildTask;
+use roslibrust_codegen::{RosMessageType, Time};
+use std::collections::BTreeMap;
+use tokio::sync::{watch, Mutex};
+
+// Matches the queue sizes of tf2_ros' static broadcaster and listener
+pub(crate) const TF_STATIC_QUEUE_SIZE: usize = 100;
+
+// Hand written equivalents of tf2_msgs/TFMessage and the geometry_msgs it is made of,
+// so static transforms do not depend on generated messages
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+pub(crate) struct TfMessage {
+    transforms: Vec<TransformStamped>,
+}
+
+impl RosMessageType for TfMessage {
+    const ROS_TYPE_NAME: &'static str = "tf2_msgs/TFMessage";
+    const MD5SUM: &'static str = "94810edda583a504dfda3829e70d7eec";
+    const DEFINITION: &'static str = r#"geometry_msgs/TransformStamped[] transforms
+
+================================================================================
+MSG: geometry_msgs/TransformStamped
+# This expresses a transform from coordinate frame header.frame_id
+# to the coordinate frame child_frame_id
+#
+# This message is mostly used by the
+# <a href="http://wiki.ros.org/tf">tf</a> package.
+# See its documentation for more information.
+
+Header header
+string child_frame_id # the frame id of the child frame
+Transform transform
+
+================================================================================
+MSG: std_msgs/Header
+# Standard metadata for higher-level stamped data types.
+# This is generally used to communicate timestamped data
+# in a particular coordinate frame.
+#
+# sequence ID: consecutively increasing ID
+uint32 seq
+#Two-integer timestamp that is expressed as:
+# * stamp.sec: seconds (stamp_secs) since epoch (in Python the variable is called 'secs')
+# * stamp.nsec: nanoseconds since stamp_secs (in Python the variable is called 'nsecs')
+# time-handling sugar is provided by the client library
+time stamp
+#Frame this data is associated with
+string frame_id
+
+================================================================================
+MSG: geometry_msgs/Transform
+# This represents the transform between two coordinate frames in free space.
+
+Vector3 translation
+Quaternion rotation
+
+================================================================================
+MSG: geometry_msgs/Vector3
+# This represents a vector in free space.
+# It is only meant to represent a direction. Therefore, it does not
+# make sense to apply a translation to it (e.g., when applying a
+# generic rigid transformation to a Vector3, tf2 will only apply the
+# rotation). If you want your data to be translatable too, use the
+# geometry_msgs/Point message instead.
+
+float64 x
+float64 y
+float64 z
+================================================================================
+MSG: geometry_msgs/Quaternion
+# This represents an orientation in free space in quaternion form.
+
+float64 x
+float64 y
+float64 z
+float64 w
+"#;
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+struct TransformStamped {
+    header: Header,
+    child_frame_id: String,
+    transform: Transform,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+struct Transform {
+    translation: Vector3,
+    rotation: Quaternion,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+struct Vector3 {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, PartialEq)]
+struct Quaternion {
+    x: f64,
+    y: f64,
+    z: f64,
+    w: f64,
+}
+
+/// A transform between two coordinate frames which does not change over time, such as where a sensor is mounted,
+/// as carried by a `geometry_msgs/TransformStamped` on `/tf_static`.
+/// Frame ids are given without a leading `/`, which tf2 strips from those it receives.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StaticTransform {
+    /// The frame the transform is expressed in, the `header.frame_id` of the message
+    pub parent_frame_id: String,
+    /// The frame the transform leads to
+    pub child_frame_id: String,
+    /// When the transform was determined, usually left at zero for static transforms
+    pub stamp: Time,
+    /// The `x`, `y` and `z` of the child frame's origin in the parent frame
+    pub translation: [f64; 3],
+    /// The `x`, `y`, `z` and `w` of the quaternion rotating the parent frame onto the child frame
+    pub rotation: [f64; 4],
+}
+
+impl From<TransformStamped> for StaticTransform {
+    fn from(msg: TransformStamped) -> Self {
+        let Transform {
+            translation,
+            rotation,
+        } = msg.transform;
+        Self {
+            parent_frame_id: msg.header.frame_id.trim_start_matches('/').to_owned(),
+            child_frame_id: msg.child_frame_id.trim_start_matches('/').to_owned(),
+            stamp: msg.header.stamp,
+            translation: [translation.x, translation.y, translation.z],
+            rotation: [rotation.x, rotation.y, rotation.z, rotation.w],
+        }
+    }
+}
+
+impl From<StaticTransform> for TransformStamped {
+    fn from(transform: StaticTransform) -> Self {
+        let [x, y, z] = transform.translation;
+        let translation = Vector3 { x, y, z };
+        let [x, y, z, w] = transform.rotation;
+        let rotation = Quaternion { x, y, z, w };
+        Self {
+            header: Header {
+                seq: 0,
+                stamp: transform.stamp,
+                frame_id: transform.parent_frame_id,
+            },
+            child_frame_id: transform.child_frame_id,
+            transform: Transform {
+                translation,
+                rotation,
+            },
+        }
+    }
+}
+
+// Static transforms keyed by child frame, as a frame has only one parent
+type TransformSet = BTreeMap<String, StaticTransform>;
+
+// Adds `transforms` to `set`, replacing those with the same child frame, returning whether the set changed
+fn merge(set: &mut TransformSet, transforms: impl IntoIterator<Item = StaticTransform>) -> bool {
+    let mut changed = false;
+    for transform in transforms {
+        if set.get(&transform.child_frame_id) != Some(&transform) {
+            set.insert(transform.child_frame_id.clone(), transform);
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Broadcasts static transforms on the latched `/tf_static`, like tf2_ros' StaticTransformBroadcaster.
+///
+/// A latched topic only replays the last message of each publisher, so every message sent carries each
+/// transform sent so far, rather than only the latest ones which would replace the rest for subscribers
+/// connecting later. Transforms sent again for the same child frame replace the earlier ones.
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// use roslibrust::StaticTransform;
+/// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/lidar_mount").await?;
+/// let broadcaster = nh.advertise_static_transforms().await?;
+/// broadcaster
+///     .send([StaticTransform {
+///         parent_frame_id: "base_link".to_owned(),
+///         child_frame_id: "lidar".to_owned(),
+///         stamp: Default::default(),
+///         translation: [0.2, 0.0, 0.5],
+///         rotation: [0.0, 0.0, 0.0, 1.0],
+///     }])
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct StaticTransformBroadcaster {
+    publisher: Publisher<TfMessage>,
+    // Held while publishing so sets are published in the order transforms are sent
+    transforms: Mutex<TransformSet>,
+}
+
+impl StaticTransformBroadcaster {
+    pub(crate) fn new(publisher: Publisher<TfMessage>) -> Self {
+        Self {
+            publisher,
+            transforms: Mutex::default(),
+        }
+    }
+
+    /// Adds `transforms` to those broadcast and publishes the whole set, unless nothing changed
+    pub async fn send(
+        &self,
+        transforms: impl IntoIterator<Item = StaticTransform>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut current = self.transforms.lock().await;
+        let mut updated = current.clone();
+        if !merge(&mut updated, transforms) {
+            return Ok(());
+        }
+        let msg = TfMessage {
+            transforms: updated.values().cloned().map(Into::into).collect(),
+        };
+        self.publisher.publish(&msg).await?;
+        *current = updated;
+        Ok(())
+    }
+
+    /// The transforms sent so far, ordered by child frame
+    pub async fn get(&self) -> Vec<StaticTransform> {
+        self.transforms.lock().await.values().cloned().collect()
+    }
+}
+
+/// Follows the static transforms of every publisher on `/tf_static`, like the static half of tf2_ros' TransformListener.
+///
+/// Each latched publisher on `/tf_static` holds a part of the transform tree, so the transforms received from all
+/// of them are merged into one set rather than each message replacing the last.
+/// Transforms received again for the same child frame replace the earlier ones.
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/lidar_driver").await?;
+/// let mut static_transforms = nh.subscribe_static_transforms().await?;
+/// while static_transforms.lookup("lidar").is_none() {
+///     static_transforms.changed().await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct StaticTransforms {
+    receiver: watch::Receiver<TransformSet>,
+    shutdown_reason: ShutdownReason,
+    _update_task: Option<ChildTask<()>>,
+}
+
+impl StaticTransforms {
+    pub(crate) fn new(
+        mut subscriber: Subscriber<TfMessage>,
+        shutdown_reason: ShutdownReason,
+    ) -> Self {
+        let (sender, receiver) = watch::channel(TransformSet::new());
+        let update_task = crate::tasks::spawn("static transforms of /tf_static", async move {
+            loop {
+                match subscriber.next().await {
+                    Ok(msg) => {
+                        let transforms = msg.transforms.into_iter().map(StaticTransform::from);
+                        sender.send_if_modified(|set| merge(set, transforms));
+                    }
+                    Err(err) if err.is::<NodeShutdownError>() => break,
+                    Err(err) => log::warn!("Skipping static transforms: {err}"),
+                }
+            }
+        });
+        Self {
+            receiver,
+            shutdown_reason,
+            _update_task: Some(update_task.into()),
+        }
+    }
+
+    /// Every static transform received so far, one per child frame, ordered by child frame
+    pub fn get(&self) -> Vec<StaticTransform> {
+        self.receiver.borrow().values().cloned().collect()
+    }
+
+    /// The static transform to `child_frame_id` from its parent, if one has been received
+    pub fn lookup(&self, child_frame_id: &str) -> Option<StaticTransform> {
+        let child_frame_id = child_frame_id.trim_start_matches('/');
+        self.receiver.borrow().get(child_frame_id).cloned()
+    }
+
+    /// Waits for transforms to be added or replaced since this was last called.
+    /// Fails once the node shuts down.
+    pub async fn changed(&mut self) -> Result<(), NodeShutdownError> {
+        // The update task only stops when the node shuts down
+        self.receiver
+            .changed()
+            .await
+            .map_err(|_| self.shutdown_reason.error())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{merge, StaticTransform, TransformSet, TransformStamped};
+
+    fn transform(parent: &str, child: &str, x: f64) -> StaticTransform {
+        StaticTransform {
+            parent_frame_id: parent.to_owned(),
+            child_frame_id: child.to_owned(),
+            stamp: Default::default(),
+            translation: [x, 0.0, 0.0],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+
+    #[test]
+    fn transforms_are_merged_by_child_frame() {
+        let mut set = TransformSet::new();
+        // The latched messages of two publishers, each holding their part of the tree
+        assert!(merge(
+            &mut set,
+            [
+                transform("base_link", "lidar", 1.0),
+                transform("base_link", "camera", 2.0)
+            ]
+        ));
+        assert!(merge(&mut set, [transform("map", "odom", 3.0)]));
+        assert!(!merge(&mut set, [transform("map", "odom", 3.0)]));
+        assert!(merge(&mut set, [transform("base_link", "lidar", 4.0)]));
+
+        let children: Vec<_> = set.values().map(|t| t.child_frame_id.as_str()).collect();
+        assert_eq!(children, vec!["camera", "lidar", "odom"]);
+        assert_eq!(set["lidar"].translation[0], 4.0);
+    }
+
+    #[test]
+    fn leading_slashes_are_stripped() {
+        let mut msg = TransformStamped::from(transform("base_link", "lidar", 1.0));
+        assert_eq!(msg.header.frame_id, "base_link");
+        msg.header.frame_id = "/base_link".to_owned();
+        msg.child_frame_id = "/lidar".to_owned();
+        assert_eq!(
+            StaticTransform::from(msg),
+            transform("base_link", "lidar", 1.0)
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! This module contains the Timer which runs a callback periodically according to the node's clock,
+//! created with [super::NodeHandle::create_timer].
+
+use super::{callback_panics::CallbackGuard, clock::Clock};
+use abort_on_drop::ChildTask;
+use roslibrust_codegen::Time;
+use std::{future::Future, time::Duration};
+use tokio::sync::watch;
+
+/// Passed to the callback of a [Timer] each time it fires, like roscpp's `TimerEvent`
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimerEvent {
+    /// The time the callback was scheduled to run at
+    pub scheduled: Time,
+    /// The time the callback actually ran at, later than scheduled if the node was busy or the timer fell behind
+    pub actual: Time,
+}
+
+// How the handle controls the timer's task
+#[derive(Clone, Debug, Default)]
+struct TimerControl {
+    paused: bool,
+    // Incremented by each reset, so the task notices every one of them
+    resets: u64,
+    cancelled: bool,
+}
+
+/// Runs a callback every period according to the node's [Clock], created with [super::NodeHandle::create_timer].
+///
+/// Following roscpp, the callback first runs one period after the timer is created, and if the callback
+/// takes longer than a period the runs which were missed are skipped rather than run back to back.
+/// With simulated time the timer only advances as `/clock` does.
+///
+/// The timer stops when this handle is dropped or [Timer::cancel] is called.
+pub struct Timer {
+    period: Duration,
+    control: watch::Sender<TimerControl>,
+    _task: ChildTask<()>,
+}
+
+impl Timer {
+    pub(crate) fn new<F, Fut>(
+        clock: Clock,
+        period: Duration,
+        callback_guard: CallbackGuard,
+        callback: F,
+    ) -> Self
+    where
+        F: FnMut(TimerEvent) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        assert!(!period.is_zero(), "Timer period must be non-zero");
+        let (control, receiver) = watch::channel(TimerControl::default());
+        let task = crate::tasks::spawn(
+            &format!("timer every {period:?}"),
+            Self::run(clock, period, callback_guard, callback, receiver),
+        );
+        Self {
+            period,
+            control,
+            _task: task.into(),
+        }
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Stops running the callback until [Timer::resume] is called
+    pub fn pause(&self) {
+        self.control.send_modify(|control| control.paused = true);
+    }
+
+    /// Resumes a paused timer, which next runs the callback one period from now.
+    /// Resuming a timer which isn't paused does nothing.
+    pub fn resume(&self) {
+        self.control.send_if_modified(|control| {
+            let resumed = control.paused;
+            control.paused = false;
+            resumed
+        });
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.control.borrow().paused
+    }
+
+    /// Restarts the timer's schedule, so it next runs the callback one period from now
+    pub fn reset(&self) {
+        self.control.send_modify(|control| control.resets += 1);
+    }
+
+    /// Stops the timer for good. A callback which is already running is left to finish.
+    pub fn cancel(&self) {
+        self.control.send_modify(|control| control.cancelled = true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.control.borrow().cancelled
+    }
+
+    async fn run<F, Fut>(
+        clock: Clock,
+        period: Duration,
+        callback_guard: CallbackGuard,
+        mut callback: F,
+        mut control: watch::Receiver<TimerControl>,
+    ) where
+        F: FnMut(TimerEvent) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut interval = clock.interval(period);
+        // The first tick of an interval is immediate, while timers wait a period before first running
+        interval.tick().await;
+        loop {
+            let state = control.borrow_and_update().clone();
+            if state.cancelled {
+                break;
+            }
+            if state.paused {
+                if control.changed().await.is_err() {
+                    break;
+                }
+                interval = clock.interval(period);
+                interval.tick().await;
+                continue;
+            }
+            tokio::select! {
+                // Changes are handled first, so nothing runs once the timer is paused or cancelled
+                biased;
+                changed = control.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    if control.borrow().resets != state.resets {
+                        interval = clock.interval(period);
+                        interval.tick().await;
+                    }
+                }
+                scheduled = interval.tick() => {
+                    let event = TimerEvent {
+                        scheduled,
+                        actual: clock.now(),
+                    };
+                    callback_guard
+                        .run_async("timer callback", async { callback(event).await })
+                        .await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Timer, TimerEvent};
+    use crate::ros1::{callback_panics::CallbackGuard, clock::Clock};
+    use roslibrust_codegen::Time;
+    use std::time::Duration;
+    use tokio::sync::{mpsc, watch};
+
+    fn time(secs: u32, millis: u32) -> Time {
+        Time {
+            secs,
+            nsecs: millis * 1_000_000,
+        }
+    }
+
+    // Gives the timer's task a chance to run, returning the time it fired for if it did
+    async fn fired(events: &mut mpsc::UnboundedReceiver<Time>) -> Option<Time> {
+        tokio::time::timeout(Duration::from_millis(50), events.recv())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    #[tokio::test]
+    async fn timers_follow_the_clock() {
+        let (sender, receiver) = watch::channel(Time::default());
+        let clock = Clock::simulated(receiver, None);
+        let (events_sender, mut events) = mpsc::unbounded_channel();
+        let timer = Timer::new(
+            clock,
+            Duration::from_secs(1),
+            CallbackGuard::default(),
+            move |event: TimerEvent| {
+                let events_sender = events_sender.clone();
+                async move {
+                    events_sender.send(event.scheduled).unwrap();
+                }
+            },
+        );
+
+        // Nothing runs until a period has passed on the clock, however much wall time passes
+        assert_eq!(fired(&mut events).await, None);
+        sender.send(time(1, 0)).unwrap();
+        assert_eq!(fired(&mut events).await, Some(time(1, 0)));
+
+        timer.pause();
+        assert!(timer.is_paused());
+        sender.send(time(3, 0)).unwrap();
+        assert_eq!(fired(&mut events).await, None);
+
+        // Resuming restarts the schedule from the current time
+        timer.resume();
+        assert_eq!(fired(&mut events).await, None);
+        sender.send(time(4, 0)).unwrap();
+        assert_eq!(fired(&mut events).await, Some(time(4, 0)));
+
+        sender.send(time(4, 500)).unwrap();
+        assert_eq!(fired(&mut events).await, None);
+        timer.reset();
+        assert_eq!(fired(&mut events).await, None);
+        sender.send(time(5, 0)).unwrap();
+        assert_eq!(fired(&mut events).await, None);
+        sender.send(time(5, 500)).unwrap();
+        assert_eq!(fired(&mut events).await, Some(time(5, 500)));
+
+        // Cancelling ends the timer's task, dropping its clock
+        timer.cancel();
+        assert!(timer.is_cancelled());
+        assert_eq!(fired(&mut events).await, None);
+        assert!(sender.send(time(10, 0)).is_err());
+    }
+}
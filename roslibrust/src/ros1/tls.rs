@@ -0,0 +1,314 @@
+//! This module wraps a node's TCPROS connections in TLS, when configured with
+//! `NodeHandleOptions::tcpros_tls` of the `ros1_tls` feature. Otherwise connections are plain TCP.
+
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf},
+    net::TcpStream,
+};
+
+#[cfg(feature = "ros1_tls")]
+use std::{path::Path, sync::Arc};
+#[cfg(feature = "ros1_tls")]
+use tokio_rustls::{
+    rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig, ServerName},
+    TlsAcceptor, TlsConnector,
+};
+
+/// The certificates a node presents to and expects from its peers on TCPROS connections,
+/// see [super::NodeHandleOptions::tcpros_tls].
+///
+/// TLS and plain TCPROS peers can't talk to each other, so every node on a topic must be configured with TLS,
+/// which limits it to traffic between roslibrust nodes, e.g. tunnelled across an untrusted network.
+/// UDPROS, services and xmlrpc are not wrapped.
+#[cfg(feature = "ros1_tls")]
+#[derive(Clone)]
+pub struct TcprosTls {
+    acceptor: TlsAcceptor,
+    connector: TlsConnector,
+}
+
+#[cfg(feature = "ros1_tls")]
+impl std::fmt::Debug for TcprosTls {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TcprosTls").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "ros1_tls")]
+impl TcprosTls {
+    /// TLS configured with rustls directly, e.g. to require subscribers to present certificates of their own.
+    /// `server` is used when publishing, to accept subscribers, and `client` when subscribing, to connect to publishers.
+    pub fn new(server: Arc<ServerConfig>, client: Arc<ClientConfig>) -> Self {
+        Self {
+            acceptor: TlsAcceptor::from(server),
+            connector: TlsConnector::from(client),
+        }
+    }
+
+    /// TLS with the node's certificate chain and private key, presented to subscribers, and the certificates of
+    /// the authorities publishers' certificates are verified against, each read from a PEM file.
+    /// A publisher's certificate must be valid for the host it is reached at, i.e. its `ROS_HOSTNAME` or `ROS_IP`.
+    pub fn from_pem_files(
+        cert_chain: impl AsRef<Path>,
+        private_key: impl AsRef<Path>,
+        ca_certs: impl AsRef<Path>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let cert_chain = read_certs(cert_chain.as_ref())?;
+        let private_key = read_private_key(private_key.as_ref())?;
+        let mut roots = RootCertStore::empty();
+        for cert in read_certs(ca_certs.as_ref())? {
+            roots.add(&cert)?;
+        }
+        let server = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(cert_chain, private_key)?;
+        let client = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Ok(Self::new(Arc::new(server), Arc::new(client)))
+    }
+}
+
+#[cfg(feature = "ros1_tls")]
+fn read_certs(path: &Path) -> Result<Vec<Certificate>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut reader = io::BufReader::new(std::fs::File::open(path)?);
+    let certs = rustls_pemfile::certs(&mut reader)?;
+    if certs.is_empty() {
+        return Err(format!("No certificates found in {}", path.display()).into());
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+#[cfg(feature = "ros1_tls")]
+fn read_private_key(path: &Path) -> Result<PrivateKey, Box<dyn std::error::Error + Send + Sync>> {
+    let mut reader = io::BufReader::new(std::fs::File::open(path)?);
+    for item in rustls_pemfile::read_all(&mut reader)? {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => return Ok(PrivateKey(key)),
+            _ => {}
+        }
+    }
+    Err(format!("No private key found in {}", path.display()).into())
+}
+
+/// How a node's TCPROS connections are made, wrapped in TLS if configured
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TcprosSecurity {
+    #[cfg(feature = "ros1_tls")]
+    tls: Option<TcprosTls>,
+}
+
+impl TcprosSecurity {
+    #[cfg(feature = "ros1_tls")]
+    pub fn new(tls: Option<TcprosTls>) -> Self {
+        Self { tls }
+    }
+
+    /// Takes over a connection accepted from a subscriber
+    pub async fn accept(&self, stream: TcpStream) -> io::Result<TcprosStream> {
+        #[cfg(feature = "ros1_tls")]
+        if let Some(tls) = &self.tls {
+            let stream = tls.acceptor.accept(stream).await?;
+            return Ok(TcprosStream::Tls(Box::new(stream.into())));
+        }
+        Ok(TcprosStream::Plain(stream))
+    }
+
+    /// Takes over a connection made to a publisher at `endpoint`, i.e. host:port
+    pub async fn connect(&self, endpoint: &str, stream: TcpStream) -> io::Result<TcprosStream> {
+        #[cfg(feature = "ros1_tls")]
+        if let Some(tls) = &self.tls {
+            let host = endpoint.rsplit_once(':').map_or(endpoint, |(host, _)| host);
+            // IPv6 addresses are bracketed in endpoints
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            let name = ServerName::try_from(host).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("The certificate of publisher {host} can't be verified: {err}"),
+                )
+            })?;
+            let stream = tls.connector.connect(name, stream).await?;
+            return Ok(TcprosStream::Tls(Box::new(stream.into())));
+        }
+        // Only needed to verify the publisher's certificate
+        #[cfg(not(feature = "ros1_tls"))]
+        let _ = endpoint;
+        Ok(TcprosStream::Plain(stream))
+    }
+}
+
+/// A TCPROS connection, over TLS if the node is configured with it, or over a Unix domain socket for UNIXROS.
+/// Writes over TLS may be buffered until flushed.
+#[derive(Debug)]
+pub(crate) enum TcprosStream {
+    Plain(TcpStream),
+    #[cfg(feature = "ros1_tls")]
+    Tls(Box<tokio_rustls::TlsStream<TcpStream>>),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixStream),
+}
+
+impl TcprosStream {
+    /// The underlying TCP connection, e.g. for setting socket options, None for a Unix domain socket
+    pub fn tcp(&self) -> Option<&TcpStream> {
+        match self {
+            TcprosStream::Plain(stream) => Some(stream),
+            #[cfg(feature = "ros1_tls")]
+            TcprosStream::Tls(stream) => Some(stream.get_ref().0),
+            #[cfg(unix)]
+            TcprosStream::Unix(_) => None,
+        }
+    }
+
+    /// Writes all of `bytes`, flushed so they are sent rather than held by TLS
+    pub async fn write_frame(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.write_all(bytes).await?;
+        self.flush().await
+    }
+}
+
+impl From<TcpStream> for TcprosStream {
+    fn from(stream: TcpStream) -> Self {
+        TcprosStream::Plain(stream)
+    }
+}
+
+#[cfg(unix)]
+impl From<tokio::net::UnixStream> for TcprosStream {
+    fn from(stream: tokio::net::UnixStream) -> Self {
+        TcprosStream::Unix(stream)
+    }
+}
+
+impl AsyncRead for TcprosStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TcprosStream::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            #[cfg(feature = "ros1_tls")]
+            TcprosStream::Tls(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+            #[cfg(unix)]
+            TcprosStream::Unix(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for TcprosStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            TcprosStream::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            #[cfg(feature = "ros1_tls")]
+            TcprosStream::Tls(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+            #[cfg(unix)]
+            TcprosStream::Unix(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TcprosStream::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            #[cfg(feature = "ros1_tls")]
+            TcprosStream::Tls(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+            #[cfg(unix)]
+            TcprosStream::Unix(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TcprosStream::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            #[cfg(feature = "ros1_tls")]
+            TcprosStream::Tls(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+            #[cfg(unix)]
+            TcprosStream::Unix(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(feature = "ros1_tls")]
+#[cfg(test)]
+mod test {
+    use super::{TcprosSecurity, TcprosTls};
+    use std::net::Ipv4Addr;
+    use tokio::{
+        io::AsyncReadExt,
+        net::{TcpListener, TcpStream},
+    };
+
+    // TLS with a certificate for localhost, trusted as its own authority
+    fn localhost_tls(dir_name: &str) -> TcprosTls {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+        let dir = std::env::temp_dir().join(dir_name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("cert.pem"), cert.serialize_pem().unwrap()).unwrap();
+        std::fs::write(dir.join("key.pem"), cert.serialize_private_key_pem()).unwrap();
+        TcprosTls::from_pem_files(
+            dir.join("cert.pem"),
+            dir.join("key.pem"),
+            dir.join("cert.pem"),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn frames_round_trip_over_tls() {
+        let security = TcprosSecurity::new(Some(localhost_tls("roslibrust_tls_round_trip")));
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let accepting = security.clone();
+        let publisher = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = accepting.accept(stream).await.unwrap();
+            stream.write_frame(b"\x02\0\0\0hi").await.unwrap();
+        });
+        let endpoint = format!("localhost:{port}");
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+            .await
+            .unwrap();
+        let mut stream = security.connect(&endpoint, stream).await.unwrap();
+        let mut frame = [0; 6];
+        stream.read_exact(&mut frame).await.unwrap();
+        assert_eq!(&frame, b"\x02\0\0\0hi");
+        publisher.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn untrusted_publishers_are_refused() {
+        let publisher_security =
+            TcprosSecurity::new(Some(localhost_tls("roslibrust_tls_untrusted_publisher")));
+        let subscriber_security =
+            TcprosSecurity::new(Some(localhost_tls("roslibrust_tls_untrusted_subscriber")));
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let publisher = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            publisher_security.accept(stream).await.is_err()
+        });
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+            .await
+            .unwrap();
+        assert!(subscriber_security
+            .connect(&format!("localhost:{port}"), stream)
+            .await
+            .is_err());
+        assert!(publisher.await.unwrap());
+    }
+}
@@ -0,0 +1,381 @@
+//! This module contains the per topic options a node applies when topics are advertised or subscribed to,
+//! and the TopicDefaults registry applications use to set them by pattern at runtime.
+
+use super::{
+    memory_budgets::MemoryBudget,
+    names::InvalidNameError,
+    subscription_queue::QueueOverflowPolicy,
+    tcpros::{self, HeaderValidation, SocketOptions},
+};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+/// Options for a single topic, applied by the node whenever the topic is advertised or subscribed to,
+/// see [super::NodeHandleOptions::topic] and [TopicDefaults::set].
+/// Options which are not set leave the node's behaviour unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TopicOptions {
+    pub(crate) queue_size: Option<usize>,
+    pub(crate) tcp_nodelay: Option<bool>,
+    pub(crate) latched: Option<bool>,
+    pub(crate) keep_latest: Option<bool>,
+    pub(crate) udp: Option<bool>,
+    pub(crate) unix_socket: Option<bool>,
+    pub(crate) intra_process: Option<bool>,
+    pub(crate) memory_budget: Option<MemoryBudget>,
+    pub(crate) checksum: Option<bool>,
+    pub(crate) queue_overflow: Option<QueueOverflowPolicy>,
+    pub(crate) header_validation: Option<HeaderValidation>,
+    pub(crate) max_message_size: Option<usize>,
+    pub(crate) keepalive: Option<Duration>,
+    pub(crate) idle_timeout: Option<Duration>,
+    pub(crate) send_buffer_size: Option<usize>,
+    pub(crate) recv_buffer_size: Option<usize>,
+    pub(crate) send_timeout: Option<Duration>,
+    pub(crate) header_fields: BTreeMap<String, String>,
+}
+
+impl TopicOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Used in place of the queue size given when advertising or subscribing to the topic
+    pub fn queue_size(mut self, queue_size: usize) -> Self {
+        self.queue_size = Some(queue_size);
+        self
+    }
+
+    /// Disables Nagle's algorithm on the topic's connections, trading bandwidth for latency.
+    /// Subscribers also ask the topic's publishers to disable it, like roscpp's `TransportHints().tcpNoDelay()`,
+    /// while publishers disable it for every subscriber, not only those asking.
+    pub fn tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.tcp_nodelay = Some(tcp_nodelay);
+        self
+    }
+
+    /// Advertises the topic as latching in the connection header sent to its subscribers
+    pub fn latched(mut self, latched: bool) -> Self {
+        self.latched = Some(latched);
+        self
+    }
+
+    /// Publishes only the most recent message to each subscriber the network falls behind on, dropping the
+    /// messages it hasn't been sent yet in favour of the newest, like [super::NodeHandle::advertise_keep_latest].
+    /// Suits topics of poses and other state where intermediate values are worthless once superseded.
+    pub fn keep_latest(mut self, keep_latest: bool) -> Self {
+        self.keep_latest = Some(keep_latest);
+        self
+    }
+
+    /// Subscribes to the topic over UDPROS where publishers support it, falling back to TCPROS for those which don't,
+    /// like roscpp's `TransportHints().udp().tcp()`. Messages may be lost or dropped when datagrams are,
+    /// and a publisher which stops sending is only noticed once the master reports it has left the topic,
+    /// or once it has been quiet for the [TopicOptions::idle_timeout].
+    /// Publishers always accept UDPROS subscribers.
+    pub fn udp(mut self, udp: bool) -> Self {
+        self.udp = Some(udp);
+        self
+    }
+
+    /// Subscribes to the topic over a Unix domain socket where publishers are roslibrust nodes advertising the same
+    /// host as ours, skipping the TCP stack, and over UDPROS or TCPROS elsewhere. Connections over which the
+    /// publisher's socket can't be reached, e.g. from another container sharing the hostname, fall back to TCPROS.
+    /// TCP options such as [TopicOptions::keepalive] don't apply to these connections, nor does
+    /// TLS set up with `NodeHandleOptions::tcpros_tls` encrypt them. Has no effect on platforms without Unix domain sockets.
+    /// Publishers always accept UNIXROS subscribers.
+    pub fn unix_socket(mut self, unix_socket: bool) -> Self {
+        self.unix_socket = Some(unix_socket);
+        self
+    }
+
+    /// Subscribes to the messages the node publishes on the topic itself in-process, handing each subscriber
+    /// a clone of the published message without serializing it or passing it through a socket, like roscpp's nodelets.
+    /// Messages from other nodes are received as usual. Publishers only serialize messages while subscribers of
+    /// other nodes are connected or the topic is latched, and hand those whose headers they stamp over serialized.
+    /// The node's own subscription shows in getBusInfo as an INTRAPROCESS connection.
+    /// Messages handed over as published are not serialized to be measured, so they count as 0 bytes against the
+    /// topic's [TopicOptions::memory_budget] and in the INTRAPROCESS connection's byte count, though a subscription
+    /// whose budget is already exceeded still refuses them. Those handed over serialized are counted in full.
+    pub fn intra_process(mut self, intra_process: bool) -> Self {
+        self.intra_process = Some(intra_process);
+        self
+    }
+
+    /// Bounds the bytes held in the topic's publisher queue and subscription buffer, see [super::MemoryBudgets]
+    pub fn memory_budget(mut self, budget: MemoryBudget) -> Self {
+        self.memory_budget = Some(budget);
+        self
+    }
+
+    /// Asks the topic's publishers to append a CRC32 checksum to each message, for links which may corrupt data
+    /// such as UDPROS or flaky bridges. Messages failing their checksum are dropped before deserialization
+    /// and counted in [super::NodeHandle::checksum_failures].
+    /// Only roslibrust publishers support checksums, messages of other publishers are received unchecked.
+    /// Publishers always send checksums to subscribers asking for them.
+    pub fn checksum(mut self, checksum: bool) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Sets what happens when a subscriber of the topic falls further behind than the queue size,
+    /// see [QueueOverflowPolicy]. Subscribers report what they dropped in [super::Subscriber::stats].
+    pub fn queue_overflow(mut self, policy: QueueOverflowPolicy) -> Self {
+        self.queue_overflow = Some(policy);
+        self
+    }
+
+    /// Sets how strictly the connection headers of the topic's publishers and subscribers are checked,
+    /// see [HeaderValidation]. Peers whose headers fail the check are refused a connection.
+    pub fn header_validation(mut self, validation: HeaderValidation) -> Self {
+        self.header_validation = Some(validation);
+        self
+    }
+
+    /// The largest message in bytes the topic's subscribers read from publishers, for topics of large messages
+    /// such as images and point clouds, or to guard against corrupt length prefixes on smaller ones.
+    /// A publisher announcing a larger message is dropped and reported in [super::NodeHandle::peer_errors].
+    /// Defaults to 1GB, like roscpp.
+    pub fn max_message_size(mut self, bytes: usize) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+
+    /// Enables TCP keepalive on the topic's connections, probing a connection once it has been idle for `idle`,
+    /// so a peer which vanished without closing its connection, e.g. through a power loss or network partition,
+    /// is noticed. Subscribers reconnect to such publishers, publishers drop such subscribers.
+    /// How often probes are repeated, and how many may fail before the connection is dropped,
+    /// are left to the operating system.
+    pub fn keepalive(mut self, idle: Duration) -> Self {
+        self.keepalive = Some(idle);
+        self
+    }
+
+    /// Drops the topic's connections to publishers which send nothing for `timeout`, then reconnects to them
+    /// following [super::NodeHandleOptions::publisher_reconnect]. Detects vanished publishers faster than
+    /// [TopicOptions::keepalive] and also covers UDPROS, but suits only topics published at a steady rate,
+    /// as a publisher quieter than `timeout` is reconnected to, replaying its last message if it latches.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the size of the kernel's send buffer (SO_SNDBUF) on the topic's TCP connections, for publishers of
+    /// high-throughput topics such as camera streams to queue large messages without blocking on slow links.
+    /// The operating system may round or cap the size, e.g. Linux doubles it and caps it at `net.core.wmem_max`.
+    pub fn send_buffer_size(mut self, bytes: usize) -> Self {
+        self.send_buffer_size = Some(bytes);
+        self
+    }
+
+    /// Sets the size of the kernel's receive buffer (SO_RCVBUF) on the topic's TCP connections, for subscribers of
+    /// high-throughput topics to keep receiving while busy handling earlier messages. Capped by the operating system
+    /// like [TopicOptions::send_buffer_size], at `net.core.rmem_max` on Linux.
+    pub fn recv_buffer_size(mut self, bytes: usize) -> Self {
+        self.recv_buffer_size = Some(bytes);
+        self
+    }
+
+    /// Evicts subscribers of the topic which can't keep up, closing their connections while the rest keep receiving.
+    /// Rather than writing to each subscriber in turn, where one stalled subscriber holds up every other, each is
+    /// written to on its own with up to the topic's queue size of messages waiting for it. A subscriber is evicted
+    /// once that many are waiting, or once writing it a single message takes longer than `timeout`, e.g. while
+    /// it is hung or its network is down. Evicted subscribers are logged and may reconnect.
+    /// With [TopicOptions::keep_latest], only subscribers a message takes longer than `timeout` to write to are evicted.
+    pub fn send_timeout(mut self, timeout: Duration) -> Self {
+        self.send_timeout = Some(timeout);
+        self
+    }
+
+    /// Adds the custom field `key=value` to the connection headers the topic's publishers and subscribers send,
+    /// for middleware to pass metadata such as trace or tenant ids to the other end of each connection.
+    /// Publishers read the fields their subscribers send from [super::SubscriberInfo::custom_fields],
+    /// subscribers those of each message's publisher from [super::MessageInfo::publisher].
+    /// Peers which don't know a field ignore it, roscpp and rospy included.
+    /// Keys of standard fields such as `callerid` or `md5sum`, or containing `=`, are logged and ignored.
+    pub fn header_field(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let key = key.into();
+        if tcpros::is_custom_field(&key) {
+            self.header_fields.insert(key, value.into());
+        } else {
+            log::warn!("Ignoring connection header field {key:?}, it isn't a custom field");
+        }
+        self
+    }
+
+    // The options applying to the topic's TCP connections
+    pub(crate) fn socket_options(&self) -> SocketOptions {
+        SocketOptions {
+            tcp_nodelay: self.tcp_nodelay.unwrap_or(false),
+            send_buffer_size: self.send_buffer_size,
+            recv_buffer_size: self.recv_buffer_size,
+            keepalive: self.keepalive,
+        }
+    }
+
+    // Takes every option set in `other`, keeping ours where it is unset
+    fn apply(&mut self, other: &TopicOptions) {
+        self.queue_size = other.queue_size.or(self.queue_size);
+        self.tcp_nodelay = other.tcp_nodelay.or(self.tcp_nodelay);
+        self.latched = other.latched.or(self.latched);
+        self.keep_latest = other.keep_latest.or(self.keep_latest);
+        self.udp = other.udp.or(self.udp);
+        self.unix_socket = other.unix_socket.or(self.unix_socket);
+        self.intra_process = other.intra_process.or(self.intra_process);
+        self.memory_budget = other.memory_budget.or(self.memory_budget);
+        self.checksum = other.checksum.or(self.checksum);
+        self.queue_overflow = other.queue_overflow.or(self.queue_overflow);
+        self.header_validation = other.header_validation.or(self.header_validation);
+        self.max_message_size = other.max_message_size.or(self.max_message_size);
+        self.keepalive = other.keepalive.or(self.keepalive);
+        self.idle_timeout = other.idle_timeout.or(self.idle_timeout);
+        self.send_buffer_size = other.send_buffer_size.or(self.send_buffer_size);
+        self.recv_buffer_size = other.recv_buffer_size.or(self.recv_buffer_size);
+        self.send_timeout = other.send_timeout.or(self.send_timeout);
+        self.header_fields.extend(other.header_fields.clone());
+    }
+}
+
+/// The registry of per topic options of a node, retrieved with [super::NodeHandle::topic_defaults].
+///
+/// Options are set for patterns of resolved topic names, where `*` matches any part of a single name segment
+/// and `**` matches anything including further segments, e.g. `/camera/*/image_raw` or `/sensors/**`.
+/// They apply to topics advertised or subscribed to after they are set, existing publications and
+/// subscriptions are left as they are.
+/// Where several patterns match a topic, options of the most recently set pattern take precedence.
+/// The registry starts out with the topics given in [super::NodeHandleOptions::topic].
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// use roslibrust::TopicOptions;
+/// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/my_node").await?;
+/// nh.topic_defaults().set("/camera/**", TopicOptions::new().queue_size(1))?;
+/// nh.topic_defaults().set("/cmd_*", TopicOptions::new().tcp_nodelay(true))?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Clones share the same registry.
+#[derive(Clone, Debug, Default)]
+pub struct TopicDefaults(Arc<RwLock<Vec<(String, TopicOptions)>>>);
+
+impl TopicDefaults {
+    /// Applies `options` to topics matching `pattern`, replacing any options previously set for the same pattern.
+    /// Patterns are matched against resolved names, so must be global, i.e. start with `/`.
+    pub fn set(&self, pattern: &str, options: TopicOptions) -> Result<(), InvalidNameError> {
+        if !pattern.starts_with('/') {
+            return Err(InvalidNameError(pattern.to_owned()));
+        }
+        let mut patterns = self.0.write().unwrap();
+        patterns.retain(|(existing, _)| existing != pattern);
+        patterns.push((pattern.to_owned(), options));
+        Ok(())
+    }
+
+    /// Removes the options set for `pattern`, returning them
+    pub fn remove(&self, pattern: &str) -> Option<TopicOptions> {
+        let mut patterns = self.0.write().unwrap();
+        let index = patterns
+            .iter()
+            .position(|(existing, _)| existing == pattern)?;
+        Some(patterns.remove(index).1)
+    }
+
+    /// The options which apply to the resolved name `topic`, combined from every matching pattern
+    pub fn get(&self, topic: &str) -> TopicOptions {
+        let patterns = self.0.read().unwrap();
+        let mut options = TopicOptions::default();
+        for (pattern, pattern_options) in patterns.iter() {
+            if matches(pattern, topic) {
+                options.apply(pattern_options);
+            }
+        }
+        options
+    }
+}
+
+// Whether `topic` matches `pattern`, where `*` matches within a name segment and `**` across segments
+fn matches(pattern: &str, topic: &str) -> bool {
+    let Some(star) = pattern.find('*') else {
+        return pattern == topic;
+    };
+    let Some(topic_rest) = topic.strip_prefix(&pattern[..star]) else {
+        return false;
+    };
+    // Names are ascii, so every index is a char boundary
+    match pattern[star..].strip_prefix("**") {
+        Some(pattern_rest) => {
+            (0..=topic_rest.len()).any(|skip| matches(pattern_rest, &topic_rest[skip..]))
+        }
+        None => {
+            let segment_end = topic_rest.find('/').unwrap_or(topic_rest.len());
+            (0..=segment_end).any(|skip| matches(&pattern[star + 1..], &topic_rest[skip..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{matches, TopicDefaults, TopicOptions};
+
+    #[test]
+    fn patterns_match_resolved_names() {
+        assert!(matches("/chatter", "/chatter"));
+        assert!(!matches("/chatter", "/chatter2"));
+        assert!(matches("/cmd_*", "/cmd_vel"));
+        assert!(matches("/camera/*/image_raw", "/camera/left/image_raw"));
+        assert!(!matches(
+            "/camera/*/image_raw",
+            "/camera/left/rect/image_raw"
+        ));
+        assert!(!matches("/camera/*", "/camera/left/image_raw"));
+        assert!(matches("/camera/**", "/camera/left/image_raw"));
+        assert!(matches("/**/image_raw", "/camera/left/image_raw"));
+        assert!(!matches("/**/image_raw", "/camera/left/image_rect"));
+    }
+
+    #[test]
+    fn later_patterns_take_precedence() {
+        let defaults = TopicDefaults::default();
+        defaults
+            .set(
+                "/camera/**",
+                TopicOptions::new().queue_size(1).tcp_nodelay(true),
+            )
+            .unwrap();
+        defaults
+            .set("/camera/left/*", TopicOptions::new().queue_size(5))
+            .unwrap();
+        assert!(defaults.set("camera", TopicOptions::new()).is_err());
+
+        assert_eq!(
+            defaults.get("/camera/left/image_raw"),
+            TopicOptions::new().queue_size(5).tcp_nodelay(true)
+        );
+        assert_eq!(
+            defaults.get("/camera/right/image_raw"),
+            TopicOptions::new().queue_size(1).tcp_nodelay(true)
+        );
+        assert_eq!(defaults.get("/chatter"), TopicOptions::new());
+
+        // Setting a pattern again moves it to the end
+        defaults
+            .set("/camera/**", TopicOptions::new().queue_size(2))
+            .unwrap();
+        assert_eq!(
+            defaults.get("/camera/left/image_raw"),
+            TopicOptions::new().queue_size(2)
+        );
+        assert_eq!(
+            defaults.remove("/camera/**"),
+            Some(TopicOptions::new().queue_size(2))
+        );
+        assert_eq!(
+            defaults.get("/camera/left/image_raw"),
+            TopicOptions::new().queue_size(5)
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! This module contains the cache of TCPROS endpoints publishers gave in answer to requestTopic,
+//! letting subscribers reconnect to a publisher without asking it for the topic again.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// A TCPROS endpoint given by a publisher for one of its topics
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TopicEndpoint {
+    /// The xmlrpc uri of the publishing node
+    pub publisher_uri: String,
+    pub topic: String,
+    /// The `host:port` the publisher accepts connections for the topic on
+    pub endpoint: String,
+}
+
+/// The node's cache of the endpoints publishers gave in answer to requestTopic, retrieved with
+/// [super::NodeHandle::topic_endpoints].
+///
+/// Subscribers reconnecting to a publisher after losing their connection try its cached endpoint first,
+/// only requesting the topic again if connecting to it fails.
+/// An endpoint is forgotten when connecting to it fails, and once the master reports the publisher
+/// has stopped publishing the topic.
+///
+/// Clones share the same cache.
+#[derive(Clone, Debug, Default)]
+pub struct TopicEndpoints(Arc<RwLock<HashMap<(String, String), String>>>);
+
+impl TopicEndpoints {
+    /// The endpoint cached for `topic` of the publisher at `publisher_uri`
+    pub fn get(&self, publisher_uri: &str, topic: &str) -> Option<String> {
+        self.0
+            .read()
+            .unwrap()
+            .get(&(publisher_uri.to_owned(), topic.to_owned()))
+            .cloned()
+    }
+
+    /// Every cached endpoint, sorted by topic and then publisher
+    pub fn entries(&self) -> Vec<TopicEndpoint> {
+        let mut entries: Vec<_> = self
+            .0
+            .read()
+            .unwrap()
+            .iter()
+            .map(|((publisher_uri, topic), endpoint)| TopicEndpoint {
+                publisher_uri: publisher_uri.clone(),
+                topic: topic.clone(),
+                endpoint: endpoint.clone(),
+            })
+            .collect();
+        entries.sort_by(|a, b| (&a.topic, &a.publisher_uri).cmp(&(&b.topic, &b.publisher_uri)));
+        entries
+    }
+
+    /// Forgets every cached endpoint, so subscribers request the topic again when they next connect
+    pub fn clear(&self) {
+        self.0.write().unwrap().clear();
+    }
+
+    pub(crate) fn insert(&self, publisher_uri: &str, topic: &str, endpoint: String) {
+        self.0
+            .write()
+            .unwrap()
+            .insert((publisher_uri.to_owned(), topic.to_owned()), endpoint);
+    }
+
+    pub(crate) fn invalidate(&self, publisher_uri: &str, topic: &str) {
+        self.0
+            .write()
+            .unwrap()
+            .remove(&(publisher_uri.to_owned(), topic.to_owned()));
+    }
+
+    /// Forgets the endpoints of `topic` for publishers other than `publishers`
+    pub(crate) fn retain_publishers(&self, topic: &str, publishers: &[String]) {
+        self.0
+            .write()
+            .unwrap()
+            .retain(|(publisher_uri, cached_topic), _| {
+                cached_topic != topic || publishers.contains(publisher_uri)
+            });
+    }
+}
@@ -0,0 +1,98 @@
+//! This module contains the TopicPair for request and response style exchanges over a pair of topics,
+//! created with [super::NodeHandle::topic_pair].
+
+use super::{publisher::Publisher, subscriber::Subscriber};
+use roslibrust_codegen::RosMessageType;
+use std::time::Duration;
+
+/// A publisher and subscriber used together for handshakes over topics, such as drivers which answer commands
+/// on one topic with acknowledgements on another, or devices echoing back what they are sent.
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// # roslibrust_codegen_macro::find_and_generate_ros_messages!("assets/ros1_common_interfaces/std_msgs");
+/// use std::time::Duration;
+/// let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/gripper_client").await?;
+/// let mut gripper = nh
+///     .topic_pair::<std_msgs::String, std_msgs::String>("/gripper/command", "/gripper/ack")
+///     .await?;
+/// let command = std_msgs::String { data: "open".to_owned() };
+/// let ack = gripper
+///     .send_and_await_match(&command, |ack| ack.data == "open", Duration::from_secs(1))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TopicPair<TOut, TIn> {
+    // The topic answers are awaited on, for errors
+    in_topic: String,
+    publisher: Publisher<TOut>,
+    subscriber: Subscriber<TIn>,
+}
+
+impl<TOut: RosMessageType, TIn: RosMessageType> TopicPair<TOut, TIn> {
+    pub(crate) fn new(
+        in_topic: &str,
+        publisher: Publisher<TOut>,
+        subscriber: Subscriber<TIn>,
+    ) -> Self {
+        Self {
+            in_topic: in_topic.to_owned(),
+            publisher,
+            subscriber,
+        }
+    }
+
+    /// Publishes `msg`, then waits for the first message for which `predicate` holds, skipping any others.
+    /// Messages received before `msg` was published are skipped too, so an answer to an earlier message isn't
+    /// taken for the answer to this one.
+    /// Fails with [std::io::ErrorKind::TimedOut] once `timeout` passes without a match.
+    ///
+    /// Like any message, `msg` only reaches subscribers already connected to the publisher,
+    /// and the answer only reaches this pair once its subscriber is connected to the answering publisher.
+    pub async fn send_and_await_match(
+        &mut self,
+        msg: &TOut,
+        mut predicate: impl FnMut(&TIn) -> bool,
+        timeout: Duration,
+    ) -> Result<TIn, Box<dyn std::error::Error + Send + Sync>> {
+        self.subscriber.skip_pending();
+        self.publisher.publish(msg).await?;
+        let matched = tokio::time::timeout(timeout, async {
+            loop {
+                let received = self.subscriber.next().await?;
+                if predicate(&received) {
+                    return Ok(received);
+                }
+            }
+        })
+        .await;
+        match matched {
+            Ok(received) => {
+                received.map_err(|err: Box<dyn std::error::Error>| err.to_string().into())
+            }
+            Err(_) => Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!(
+                    "Timed out waiting for a matching message on {}",
+                    self.in_topic
+                ),
+            ))),
+        }
+    }
+
+    /// The publisher of the outgoing topic
+    pub fn publisher(&self) -> &Publisher<TOut> {
+        &self.publisher
+    }
+
+    /// The subscriber of the incoming topic, e.g. for receiving messages sent unprompted
+    pub fn subscriber(&mut self) -> &mut Subscriber<TIn> {
+        &mut self.subscriber
+    }
+
+    /// Splits the pair back into its publisher and subscriber
+    pub fn into_parts(self) -> (Publisher<TOut>, Subscriber<TIn>) {
+        (self.publisher, self.subscriber)
+    }
+}
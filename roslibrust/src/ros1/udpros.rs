@@ -0,0 +1,417 @@
+//! This module contains the framing of [UDPROS](http://wiki.ros.org/ROS/UDPROS), which carries topics over UDP
+//! for subscribers asking for it with [super::TopicOptions::udp].
+//!
+//! Each message, including its length prefix, is split across datagrams of at most the maximum datagram size
+//! the subscriber asked for, each starting with an 8 byte header:
+//! the connection id, an op code, a message id and a block number.
+
+use super::tcpros::ConnectionHeader;
+use serde_xmlrpc::Value;
+use tokio::net::UdpSocket;
+
+/// The largest datagram subscribers ask for, fitting within the MTU of an ethernet link like roscpp's default
+pub(crate) const MAX_DATAGRAM_SIZE: usize = 1500;
+
+pub(crate) const HEADER_SIZE: usize = 8;
+
+// The first datagram of a message, whose block number is the number of datagrams in the message
+const OP_DATA0: u8 = 0;
+// A further datagram of a message, whose block number is its index within the message
+const OP_DATAN: u8 = 1;
+const OP_PING: u8 = 2;
+const OP_ERR: u8 = 3;
+
+/// Splits `msg` into the datagrams of message `msg_id` on connection `connection_id`.
+/// `max_datagram_size` must be larger than the header.
+/// Fails with InvalidInput if the message needs more datagrams than a block number can count.
+pub(crate) fn fragment(
+    connection_id: u32,
+    msg_id: u8,
+    msg: &[u8],
+    max_datagram_size: usize,
+) -> std::io::Result<Vec<Vec<u8>>> {
+    let chunks: Vec<&[u8]> = msg.chunks(max_datagram_size - HEADER_SIZE).collect();
+    if chunks.len() > u16::MAX as usize {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "a message of {} bytes needs {} datagrams, more than the {} UDPROS allows",
+                msg.len(),
+                chunks.len(),
+                u16::MAX
+            ),
+        ));
+    }
+    Ok(chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let (op_code, block) = match index {
+                0 => (OP_DATA0, chunks.len()),
+                index => (OP_DATAN, index),
+            };
+            let mut datagram = Vec::with_capacity(HEADER_SIZE + chunk.len());
+            datagram.extend_from_slice(&connection_id.to_le_bytes());
+            datagram.push(op_code);
+            datagram.push(msg_id);
+            datagram.extend_from_slice(&(block as u16).to_le_bytes());
+            datagram.extend_from_slice(chunk);
+            datagram
+        })
+        .collect())
+}
+
+/// Sends messages to a single subscriber over UDPROS
+pub(crate) struct UdpLink {
+    // Connected to the subscriber
+    socket: UdpSocket,
+    connection_id: u32,
+    max_datagram_size: usize,
+    msg_id: u8,
+}
+
+impl UdpLink {
+    pub fn new(socket: UdpSocket, connection_id: u32, max_datagram_size: usize) -> Self {
+        Self {
+            socket,
+            connection_id,
+            max_datagram_size,
+            msg_id: 0,
+        }
+    }
+
+    /// Sends `msg`, returning the number of bytes of it sent.
+    /// A message too large for UDPROS is dropped, leaving the link usable for the next.
+    pub async fn send(&mut self, msg: &[u8]) -> std::io::Result<usize> {
+        let datagrams = match fragment(self.connection_id, self.msg_id, msg, self.max_datagram_size)
+        {
+            Ok(datagrams) => datagrams,
+            Err(err) => {
+                log::warn!(
+                    "Dropping a message for UDPROS connection {}: {err}",
+                    self.connection_id
+                );
+                return Ok(0);
+            }
+        };
+        for datagram in datagrams {
+            self.socket.send(&datagram).await?;
+        }
+        self.msg_id = self.msg_id.wrapping_add(1);
+        Ok(msg.len())
+    }
+}
+
+/// Rebuilds the messages of a single connection from its datagrams.
+/// Like roscpp, datagrams are expected in order: a message missing a datagram, or receiving one out of order, is dropped.
+pub(crate) struct Reassembler {
+    connection_id: u32,
+    // The message being received, its id and the number of datagrams it is made of
+    partial: Option<(u8, usize, Vec<u8>)>,
+    received_blocks: usize,
+}
+
+impl Reassembler {
+    pub fn new(connection_id: u32) -> Self {
+        Self {
+            connection_id,
+            partial: None,
+            received_blocks: 0,
+        }
+    }
+
+    /// Adds a received datagram, returning the message it completes if any.
+    /// Fails if the publisher reports an error, closing the connection.
+    pub fn push(&mut self, datagram: &[u8]) -> std::io::Result<Option<Vec<u8>>> {
+        if datagram.len() < HEADER_SIZE {
+            log::debug!("Ignoring UDPROS datagram of {} bytes", datagram.len());
+            return Ok(None);
+        }
+        let connection_id = u32::from_le_bytes(datagram[..4].try_into().unwrap());
+        if connection_id != self.connection_id {
+            log::debug!("Ignoring UDPROS datagram for connection {connection_id}");
+            return Ok(None);
+        }
+        let (op_code, msg_id) = (datagram[4], datagram[5]);
+        let block = u16::from_le_bytes(datagram[6..8].try_into().unwrap()) as usize;
+        let payload = &datagram[HEADER_SIZE..];
+        match op_code {
+            OP_DATA0 => {
+                if self.partial.is_some() {
+                    log::debug!("Dropping incomplete UDPROS message");
+                }
+                self.partial = Some((msg_id, block, payload.to_vec()));
+                self.received_blocks = 1;
+            }
+            OP_DATAN => match &mut self.partial {
+                Some((partial_id, _, data))
+                    if *partial_id == msg_id && block == self.received_blocks =>
+                {
+                    data.extend_from_slice(payload);
+                    self.received_blocks += 1;
+                }
+                _ => {
+                    log::debug!("Dropping UDPROS message {msg_id} which arrived out of order");
+                    self.partial = None;
+                    return Ok(None);
+                }
+            },
+            OP_PING => return Ok(None),
+            OP_ERR => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionAborted,
+                    "Publisher closed the UDPROS connection",
+                ))
+            }
+            op_code => {
+                log::debug!("Ignoring UDPROS datagram with unknown op code {op_code}");
+                return Ok(None);
+            }
+        }
+        match &self.partial {
+            Some((_, blocks, _)) if self.received_blocks >= *blocks => {
+                Ok(self.partial.take().map(|(_, _, data)| data))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The UDPROS entry of the protocols a subscriber lists in its requestTopic call
+#[derive(Debug)]
+pub struct UdprosRequest {
+    /// The connection header of the subscriber
+    pub header: ConnectionHeader,
+    /// Where the subscriber receives datagrams
+    pub host: String,
+    pub port: u16,
+    pub max_datagram_size: usize,
+}
+
+impl UdprosRequest {
+    pub fn to_value(&self) -> std::io::Result<Value> {
+        Ok(Value::Array(vec![
+            "UDPROS".into(),
+            Value::Base64(header_to_bytes(&self.header)?),
+            self.host.as_str().into(),
+            Value::Int(self.port.into()),
+            Value::Int(self.max_datagram_size as i32),
+        ]))
+    }
+
+    /// Parses an entry of the protocols in a requestTopic call, None unless it is a valid UDPROS entry
+    pub fn from_values(values: &[Value]) -> Option<Self> {
+        match values {
+            [protocol, header, host, port, max_datagram_size]
+                if protocol.as_str() == Some("UDPROS") =>
+            {
+                Some(Self {
+                    header: header_from_bytes(header.as_bytes()?).ok()?,
+                    host: host.as_str()?.to_owned(),
+                    port: port.as_i32()?.try_into().ok()?,
+                    max_datagram_size: max_datagram_size.as_i32()?.try_into().ok()?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The parameters a publisher answers a requestTopic call with when agreeing to UDPROS
+#[derive(Debug)]
+pub struct UdprosResponse {
+    /// Where the publisher sends datagrams from
+    pub host: String,
+    pub port: u16,
+    /// The id the publisher puts in the header of every datagram of the connection
+    pub connection_id: u32,
+    pub max_datagram_size: usize,
+    /// The connection header of the publisher
+    pub header: ConnectionHeader,
+}
+
+// The Base64 connection header in a requestTopic response
+struct HeaderBytes(Vec<u8>);
+
+impl<'de> serde::Deserialize<'de> for HeaderBytes {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = HeaderBytes;
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("base64 encoded bytes")
+            }
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<HeaderBytes, E> {
+                Ok(HeaderBytes(bytes.to_vec()))
+            }
+            fn visit_byte_buf<E: serde::de::Error>(self, bytes: Vec<u8>) -> Result<HeaderBytes, E> {
+                Ok(HeaderBytes(bytes))
+            }
+        }
+        deserializer.deserialize_bytes(Visitor)
+    }
+}
+
+impl UdprosResponse {
+    pub fn to_value(&self) -> std::io::Result<Value> {
+        Ok(Value::Array(vec![
+            "UDPROS".into(),
+            self.host.as_str().into(),
+            Value::Int(self.port.into()),
+            Value::Int(self.connection_id as i32),
+            Value::Int(self.max_datagram_size as i32),
+            Value::Base64(header_to_bytes(&self.header)?),
+        ]))
+    }
+
+    /// Parses a requestTopic response, None unless the publisher agreed to UDPROS
+    pub fn from_response(response: &str) -> Option<Self> {
+        let (_code, _description, (protocol, host, port, connection_id, max_datagram_size, header)) =
+            serde_xmlrpc::response_from_str::<(
+                i32,
+                String,
+                (String, String, u16, u32, usize, HeaderBytes),
+            )>(response)
+            .ok()?;
+        if protocol != "UDPROS" {
+            return None;
+        }
+        Some(Self {
+            host,
+            port,
+            connection_id,
+            max_datagram_size,
+            header: header_from_bytes(&header.0).ok()?,
+        })
+    }
+}
+
+/// Connection headers are exchanged within the requestTopic call, without their length prefix
+pub(crate) fn header_to_bytes(header: &ConnectionHeader) -> std::io::Result<Vec<u8>> {
+    let mut bytes = header.to_bytes(false)?;
+    bytes.drain(..4);
+    Ok(bytes)
+}
+
+pub(crate) fn header_from_bytes(bytes: &[u8]) -> std::io::Result<ConnectionHeader> {
+    let mut framed = (bytes.len() as u32).to_le_bytes().to_vec();
+    framed.extend_from_slice(bytes);
+    ConnectionHeader::from_bytes(&framed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{fragment, Reassembler, UdprosRequest, UdprosResponse, HEADER_SIZE};
+    use crate::ros1::tcpros::ConnectionHeader;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn messages_are_reassembled_from_their_datagrams() {
+        let msg: Vec<u8> = (0..=255).collect();
+        let datagrams = fragment(7, 3, &msg, HEADER_SIZE + 100).unwrap();
+        assert_eq!(datagrams.len(), 3);
+        assert_eq!(&datagrams[0][..HEADER_SIZE], &[7, 0, 0, 0, 0, 3, 3, 0]);
+        assert_eq!(&datagrams[2][..HEADER_SIZE], &[7, 0, 0, 0, 1, 3, 2, 0]);
+
+        let mut reassembler = Reassembler::new(7);
+        assert_eq!(reassembler.push(&datagrams[0]).unwrap(), None);
+        assert_eq!(reassembler.push(&datagrams[1]).unwrap(), None);
+        assert_eq!(reassembler.push(&datagrams[2]).unwrap(), Some(msg.clone()));
+
+        // A message missing a datagram is dropped, the next one still gets through
+        let next = fragment(7, 4, &msg, HEADER_SIZE + 100).unwrap();
+        assert_eq!(reassembler.push(&datagrams[0]).unwrap(), None);
+        assert_eq!(reassembler.push(&datagrams[2]).unwrap(), None);
+        assert_eq!(reassembler.push(&next[0]).unwrap(), None);
+        assert_eq!(reassembler.push(&next[1]).unwrap(), None);
+        assert_eq!(reassembler.push(&next[2]).unwrap(), Some(msg.clone()));
+
+        // Datagrams of other connections are ignored
+        let small = fragment(8, 0, b"small", HEADER_SIZE + 100).unwrap();
+        assert_eq!(reassembler.push(&small[0]).unwrap(), None);
+        let small = fragment(7, 5, b"small", HEADER_SIZE + 100).unwrap();
+        assert_eq!(
+            reassembler.push(&small[0]).unwrap(),
+            Some(b"small".to_vec())
+        );
+    }
+
+    #[test]
+    fn block_numbers_limit_the_datagrams_of_a_message() {
+        // A single byte per datagram keeps the message at the limit small
+        let msg = vec![0u8; u16::MAX as usize];
+        let datagrams = fragment(7, 0, &msg, HEADER_SIZE + 1).unwrap();
+        assert_eq!(datagrams.len(), u16::MAX as usize);
+        assert_eq!(&datagrams[0][..HEADER_SIZE], &[7, 0, 0, 0, 0, 0, 255, 255]);
+
+        let msg = vec![0u8; u16::MAX as usize + 1];
+        let err = fragment(7, 0, &msg, HEADER_SIZE + 1).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn protocol_parameters_round_trip() {
+        let header = ConnectionHeader {
+            caller_id: "/talker".to_owned(),
+            latching: true,
+            msg_definition: "string data".to_owned(),
+            md5sum: "992ce8a1687cec8c8bd883ec73ca41d1".to_owned(),
+            topic: "/chatter".to_owned(),
+            topic_type: "std_msgs/String".to_owned(),
+            tcp_nodelay: false,
+            service: None,
+            persistent: false,
+            probe: false,
+            error: None,
+            checksum: false,
+            custom_fields: BTreeMap::new(),
+        };
+
+        let request = UdprosRequest {
+            header: header.clone(),
+            host: "listener_host".to_owned(),
+            port: 1234,
+            max_datagram_size: 1500,
+        };
+        let serde_xmlrpc::Value::Array(values) = request.to_value().unwrap() else {
+            panic!("Protocol should be an array");
+        };
+        let parsed = UdprosRequest::from_values(&values).unwrap();
+        assert_eq!(parsed.host, "listener_host");
+        assert_eq!(parsed.port, 1234);
+        assert_eq!(parsed.max_datagram_size, 1500);
+        assert_eq!(parsed.header.md5sum, header.md5sum);
+        assert!(UdprosRequest::from_values(&[serde_xmlrpc::Value::from("TCPROS")]).is_none());
+
+        let response = UdprosResponse {
+            host: "talker_host".to_owned(),
+            port: 4321,
+            connection_id: 7,
+            max_datagram_size: 1500,
+            header,
+        };
+        let body = serde_xmlrpc::response_to_string(
+            vec![serde_xmlrpc::Value::Array(vec![
+                1.into(),
+                "ready".into(),
+                response.to_value().unwrap(),
+            ])]
+            .into_iter(),
+        )
+        .unwrap();
+        let parsed = UdprosResponse::from_response(&body).unwrap();
+        assert_eq!(parsed.host, "talker_host");
+        assert_eq!(parsed.port, 4321);
+        assert_eq!(parsed.connection_id, 7);
+        assert!(parsed.header.latching);
+        assert_eq!(parsed.header.topic, "/chatter");
+
+        // Publishers answering with TCPROS aren't mistaken for UDPROS
+        let body = serde_xmlrpc::response_to_string(
+            vec![serde_xmlrpc::to_value((1, "ready", ("TCPROS", "talker_host", 4321))).unwrap()]
+                .into_iter(),
+        )
+        .unwrap();
+        assert!(UdprosResponse::from_response(&body).is_none());
+    }
+}
@@ -0,0 +1,168 @@
+//! This module contains UNIXROS, a roslibrust extension carrying TCPROS over a Unix domain socket between nodes
+//! on the same host, for subscribers asking for it with [super::TopicOptions::unix_socket].
+//!
+//! Subscribers list `["UNIXROS", host]` ahead of their other protocols in their requestTopic call, naming the host
+//! they advertise. A publisher advertising the same host answers `["UNIXROS", path]` with the path of a socket
+//! it accepts subscribers of the topic on, over which connection headers and messages follow as over TCPROS.
+//! Nodes which don't know UNIXROS, including roscpp and rospy, skip the entry.
+
+use serde_xmlrpc::Value;
+use std::path::PathBuf;
+
+const PROTOCOL: &str = "UNIXROS";
+
+/// The UNIXROS entry of the protocols a subscriber lists in its requestTopic call
+#[derive(Debug)]
+pub struct UnixrosRequest {
+    /// The host the subscriber advertises, which the publisher must share
+    pub host: String,
+}
+
+impl UnixrosRequest {
+    pub fn to_value(&self) -> Value {
+        Value::Array(vec![PROTOCOL.into(), self.host.as_str().into()])
+    }
+
+    /// Parses an entry of the protocols in a requestTopic call, None unless it is a valid UNIXROS entry
+    pub fn from_values(values: &[Value]) -> Option<Self> {
+        match values {
+            [protocol, host] if protocol.as_str() == Some(PROTOCOL) => Some(Self {
+                host: host.as_str()?.to_owned(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// The parameters a publisher answers a requestTopic call with when agreeing to UNIXROS
+#[derive(Debug)]
+pub struct UnixrosResponse {
+    /// Where the publisher accepts subscribers of the topic
+    pub path: PathBuf,
+}
+
+impl UnixrosResponse {
+    pub fn to_value(&self) -> Value {
+        Value::Array(vec![
+            PROTOCOL.into(),
+            self.path.to_string_lossy().as_ref().into(),
+        ])
+    }
+
+    /// Parses a requestTopic response, None unless the publisher agreed to UNIXROS
+    pub fn from_response(response: &str) -> Option<Self> {
+        let (_code, _description, (protocol, path)) =
+            serde_xmlrpc::response_from_str::<(i32, String, (String, String))>(response).ok()?;
+        if protocol != PROTOCOL {
+            return None;
+        }
+        Some(Self { path: path.into() })
+    }
+}
+
+#[cfg(unix)]
+pub(crate) use socket::UnixSocket;
+
+#[cfg(unix)]
+mod socket {
+    use std::{
+        path::{Path, PathBuf},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use tokio::net::{UnixListener, UnixStream};
+
+    // Numbers the sockets of the process, so each has a path of its own
+    static NEXT_SOCKET: AtomicUsize = AtomicUsize::new(0);
+
+    /// A socket a publication accepts UNIXROS subscribers on, removed from the filesystem once dropped
+    pub(crate) struct UnixSocket {
+        path: PathBuf,
+        listener: UnixListener,
+    }
+
+    impl UnixSocket {
+        /// Binds a socket in the temporary directory, at a path unique to the process and socket
+        pub fn bind() -> std::io::Result<Self> {
+            let path = std::env::temp_dir().join(format!(
+                "roslibrust_{}_{}.sock",
+                std::process::id(),
+                NEXT_SOCKET.fetch_add(1, Ordering::Relaxed)
+            ));
+            // Left behind by an earlier process which had the same id and didn't exit cleanly
+            if path.exists() {
+                std::fs::remove_file(&path)?;
+            }
+            let listener = UnixListener::bind(&path)?;
+            Ok(Self { path, listener })
+        }
+
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        pub async fn accept(&self) -> std::io::Result<UnixStream> {
+            let (stream, _) = self.listener.accept().await?;
+            Ok(stream)
+        }
+    }
+
+    impl Drop for UnixSocket {
+        fn drop(&mut self) {
+            if let Err(err) = std::fs::remove_file(&self.path) {
+                log::debug!("Failed to remove socket {}: {err}", self.path.display());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{UnixrosRequest, UnixrosResponse};
+
+    #[test]
+    fn protocol_entries_round_trip() {
+        let request = UnixrosRequest {
+            host: "robot".to_owned(),
+        };
+        let serde_xmlrpc::Value::Array(values) = request.to_value() else {
+            panic!("UNIXROS entry should be an array");
+        };
+        assert_eq!(UnixrosRequest::from_values(&values).unwrap().host, "robot");
+        assert!(UnixrosRequest::from_values(&values[..1]).is_none());
+
+        let response = UnixrosResponse {
+            path: "/tmp/roslibrust_1_0.sock".into(),
+        };
+        let body = serde_xmlrpc::response_to_string(
+            vec![serde_xmlrpc::Value::Array(vec![
+                1.into(),
+                "ready".into(),
+                response.to_value(),
+            ])]
+            .into_iter(),
+        )
+        .unwrap();
+        assert_eq!(
+            UnixrosResponse::from_response(&body).unwrap().path,
+            response.path
+        );
+
+        // Publishers answering with TCPROS aren't mistaken for UNIXROS
+        let body = serde_xmlrpc::response_to_string(
+            vec![serde_xmlrpc::to_value((1, "ready", ("TCPROS", "talker_host", 4321))).unwrap()]
+                .into_iter(),
+        )
+        .unwrap();
+        assert!(UnixrosResponse::from_response(&body).is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn sockets_are_removed_once_dropped() {
+        let socket = super::UnixSocket::bind().unwrap();
+        let path = socket.path().to_owned();
+        assert!(path.exists());
+        drop(socket);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,126 @@
+//! This module contains the client a node uses to call the xmlrpc APIs of other nodes, e.g. requestTopic.
+
+use hyper::{client::HttpConnector, header::CONTENT_TYPE, Body, Client, Request};
+use std::{
+    io::{Error, ErrorKind},
+    time::Duration,
+};
+
+// Node APIs answer from memory, so anything slower than this is treated as a dead node
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A plain HTTP/1.1 client shared by everything in a node which calls other nodes,
+/// keeping connections to them open between requests.
+/// Cloning shares the underlying connection pool.
+#[derive(Clone, Debug)]
+pub(crate) struct XmlRpcClient {
+    client: Client<HttpConnector>,
+    timeout: Duration,
+}
+
+impl Default for XmlRpcClient {
+    fn default() -> Self {
+        let mut connector = HttpConnector::new();
+        connector.set_connect_timeout(Some(CONNECT_TIMEOUT));
+        connector.set_nodelay(true);
+        Self {
+            client: Client::builder().build(connector),
+            timeout: REQUEST_TIMEOUT,
+        }
+    }
+}
+
+impl XmlRpcClient {
+    /// Posts the xmlrpc request `body` to `uri`, returning the body of the response.
+    /// Failing to reach the server or a server which doesn't answer is reported as [ErrorKind::ConnectionAborted]
+    /// or [ErrorKind::TimedOut], and a server which answers with an HTTP error as [ErrorKind::ConnectionRefused].
+    pub async fn post(&self, uri: &str, body: String) -> Result<String, Error> {
+        let request = Request::post(uri)
+            .header(CONTENT_TYPE, "text/xml")
+            .body(Body::from(body))
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+        let response = async {
+            let response = self
+                .client
+                .request(request)
+                .await
+                .map_err(|err| Error::new(ErrorKind::ConnectionAborted, err))?;
+            if !response.status().is_success() {
+                return Err(Error::new(
+                    ErrorKind::ConnectionRefused,
+                    format!("{uri} responded with {}", response.status()),
+                ));
+            }
+            hyper::body::to_bytes(response.into_body())
+                .await
+                .map_err(|err| Error::new(ErrorKind::ConnectionAborted, err))
+        };
+        let body = tokio::time::timeout(self.timeout, response)
+            .await
+            .map_err(|_| {
+                Error::new(
+                    ErrorKind::TimedOut,
+                    format!("{uri} did not respond within {:?}", self.timeout),
+                )
+            })??;
+        String::from_utf8(body.to_vec()).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::XmlRpcClient;
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Response, Server,
+    };
+    use std::{
+        convert::Infallible,
+        net::SocketAddr,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    #[tokio::test]
+    async fn connections_are_reused() {
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+        let make_svc = make_service_fn(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async {
+                Ok::<_, Infallible>(service_fn(|request| async {
+                    let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                    Ok::<_, Infallible>(Response::new(Body::from(body)))
+                }))
+            }
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_svc);
+        let uri = format!("http://{}/", server.local_addr());
+        tokio::spawn(server);
+
+        let client = XmlRpcClient::default();
+        assert_eq!(
+            client.post(&uri, "first".to_owned()).await.unwrap(),
+            "first"
+        );
+        assert_eq!(
+            client
+                .clone()
+                .post(&uri, "second".to_owned())
+                .await
+                .unwrap(),
+            "second"
+        );
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+        // Nothing listens on port 1, so the connection is refused
+        let err = XmlRpcClient::default()
+            .post("http://127.0.0.1:1/", String::new())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionAborted);
+    }
+}
@@ -1,11 +1,17 @@
-use super::node::NodeServerHandle;
+use super::{
+    addrs,
+    bus_info::{bus_info, bus_stats},
+    graph_events::listed,
+    master_client::ParamValue,
+    node::{NodeServerHandle, TopicProtocol, NAME_CONFLICT_REASON},
+    udpros::UdprosRequest,
+    unixros::UnixrosRequest,
+    NodeShutdownError,
+};
 use abort_on_drop::ChildTask;
 use hyper::{Body, Response, StatusCode};
 use log::*;
-use std::{
-    convert::Infallible,
-    net::{Ipv4Addr, SocketAddr},
-};
+use std::{convert::Infallible, net::IpAddr};
 
 #[allow(unused)]
 enum RosXmlStatusCode {
@@ -45,7 +51,7 @@ impl XmlRpcServerHandle {
 
 impl XmlRpcServer {
     pub fn new(
-        host_addr: Ipv4Addr,
+        host_addr: IpAddr,
         node_server: NodeServerHandle,
     ) -> Result<XmlRpcServerHandle, Box<dyn std::error::Error + Send + Sync>> {
         let make_svc = hyper::service::make_service_fn(move |connection| {
@@ -57,12 +63,11 @@ impl XmlRpcServer {
                 }))
             }
         });
-        let host_addr = SocketAddr::from((host_addr, 0));
-        let server = hyper::server::Server::try_bind(&host_addr.into())?;
+        let server = hyper::server::Server::from_tcp(addrs::bind_tcp(host_addr)?)?;
         let server = server.serve(make_svc);
         let addr = server.local_addr();
 
-        let handle = tokio::spawn(async {
+        let handle = crate::tasks::spawn(&format!("xmlrpc server on {addr}"), async {
             if let Err(err) = server.await {
                 log::error!("xmlrpc server encountered error: {err:?}");
             }
@@ -156,10 +161,58 @@ impl XmlRpcServer {
                     Err(e) => Err(Self::make_response_from_boxed_error(e, "Unable to get publications", StatusCode::INTERNAL_SERVER_ERROR))
                 }
             }
+            "getBusStats" => {
+                debug!("getBusStats called by {args:?}");
+                let stats = async {
+                    let publications = node_server.get_publications().await?;
+                    let subscriptions = node_server.get_subscriptions().await?;
+                    let connections = node_server.get_bus_connections().await?;
+                    Ok::<_, Box<dyn std::error::Error>>(bus_stats(
+                        &publications,
+                        &subscriptions,
+                        &connections,
+                    ))
+                };
+                match stats.await {
+                    Ok(stats) => Self::to_response(stats),
+                    Err(e) => Err(Self::make_response_from_boxed_error(
+                        e,
+                        "Unable to get bus stats",
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                    )),
+                }
+            }
+            "getBusInfo" => {
+                debug!("getBusInfo called by {args:?}");
+                match node_server.get_bus_connections().await {
+                    Ok(connections) => Self::to_response(bus_info(&connections)),
+                    Err(e) => Err(Self::make_response_from_boxed_error(
+                        e,
+                        "Unable to get bus info",
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                    )),
+                }
+            }
             "paramUpdate" => {
-                // Not supporting params for first cut
                 debug!("paramUpdate called by {args:?}");
-                unimplemented!()
+                let (_caller_id, key, ParamValue(value)): (String, String, ParamValue) =
+                    serde_xmlrpc::from_values(args).map_err(|e| {
+                        Self::make_error_response(
+                            e,
+                            "Failed to parse arguments to paramUpdate",
+                            StatusCode::BAD_REQUEST,
+                        )
+                    })?;
+                node_server.param_update(key, value).map_err(|e| {
+                    Self::make_response_from_boxed_error(
+                        e,
+                        "Unable to deliver parameter update",
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                    )
+                })?;
+
+                // Like publisherUpdate the returned int is ignored
+                Self::to_response(0)
             }
             "publisherUpdate" => {
                 debug!("publisherUpdate called by {args:?}");
@@ -171,33 +224,68 @@ impl XmlRpcServer {
                             StatusCode::BAD_REQUEST,
                         )
                     })?;
-                node_server
-                    .set_peer_publishers(topic, publishers)
-                    .map_err(|e| {
-                        Self::make_response_from_boxed_error(
-                            e,
-                            "Unable to set peer publishers",
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                        )
-                    })?;
+                let outcome = listed("publishers", &publishers);
+                let updated = node_server.set_peer_publishers(topic.clone(), publishers);
+                node_server.graph_events().record(
+                    "publisherUpdate",
+                    &topic,
+                    match &updated {
+                        Ok(()) => Ok(outcome),
+                        Err(err) => Err(err.to_string()),
+                    },
+                );
+                updated.map_err(|e| {
+                    Self::make_response_from_boxed_error(
+                        e,
+                        "Unable to set peer publishers",
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                    )
+                })?;
 
                 // ROS's API is for us to still return an int, but the value is literally named "ignore"...
                 Self::to_response(0)
             }
             "requestTopic" => {
                 debug!("requestTopic called by {args:?}");
-                let (caller_id, topic, protocols): (String, String, Vec<Vec<String>>) =
-                    serde_xmlrpc::from_values(args).map_err(|e| {
+                // Protocols mix types, e.g. UDPROS carries a base64 header, so are parsed by hand
+                let mut args = args;
+                let protocols = if args.len() == 3 { args.pop() } else { None };
+                let (caller_id, topic): (String, String) = serde_xmlrpc::from_values(args)
+                    .map_err(|e| {
                         Self::make_error_response(
                             e,
                             "Failed to parse arguments to requestTopic",
                             StatusCode::BAD_REQUEST,
                         )
                     })?;
-                let protocols = protocols.iter().flatten().cloned().collect::<Vec<_>>();
+                let protocols = protocols
+                    .as_ref()
+                    .and_then(serde_xmlrpc::Value::as_array)
+                    .ok_or_else(|| {
+                        Self::make_response_from_boxed_error(
+                            "Expected a list of protocols".into(),
+                            "Failed to parse arguments to requestTopic",
+                            StatusCode::BAD_REQUEST,
+                        )
+                    })?;
+                let protocols = protocols
+                    .iter()
+                    .filter_map(|protocol| {
+                        let protocol = protocol.as_array()?;
+                        match protocol.first()?.as_str()? {
+                            "TCPROS" => Some(TopicProtocol::Tcpros),
+                            "UDPROS" => UdprosRequest::from_values(protocol)
+                                .map(|request| TopicProtocol::Udpros(Box::new(request))),
+                            "UNIXROS" => {
+                                UnixrosRequest::from_values(protocol).map(TopicProtocol::Unixros)
+                            }
+                            _ => None,
+                        }
+                    })
+                    .collect::<Vec<_>>();
                 debug!("Request for topic {topic} from {caller_id} via protocols {protocols:?}");
                 let params = node_server
-                    .request_topic(&caller_id, &topic, &protocols)
+                    .request_topic(&caller_id, &topic, protocols)
                     .await
                     .map_err(|e| {
                         Self::make_response_from_boxed_error(
@@ -207,38 +295,75 @@ impl XmlRpcServer {
                         )
                     })?;
 
-                let response = Self::make_success_response(
-                    RosXmlStatusCode::Success,
-                    format!("ready on {}:{}", params.hostname.clone(), params.port).as_str(),
-                    serde_xmlrpc::to_value((params.protocol, params.hostname, params.port))
-                        .unwrap(),
-                );
+                let protocol = match (&params.udpros, &params.unixros) {
+                    (Some(udpros), _) => udpros.to_value().map_err(|e| {
+                        Self::make_error_response(
+                            e,
+                            "Unable to serialize connection header",
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                        )
+                    })?,
+                    (None, Some(unixros)) => unixros.to_value(),
+                    (None, None) => serde_xmlrpc::to_value((
+                        params.protocol.clone(),
+                        params.hostname.clone(),
+                        params.port,
+                    ))
+                    .unwrap(),
+                };
+                let status = match &params.unixros {
+                    Some(unixros) => format!("ready on {}", unixros.path.display()),
+                    None => format!(
+                        "ready on {}",
+                        addrs::host_port(&params.hostname, params.port)
+                    ),
+                };
+                let response =
+                    Self::make_success_response(RosXmlStatusCode::Success, &status, protocol);
 
                 log::debug!("Sending response for requested topic {response:?}");
                 Ok(response)
             }
             "shutdown" => {
                 debug!("shutdown called by {args:?}");
-                let (caller_id, msg): (String, String) =
-                    serde_xmlrpc::from_values(args).map_err(|e| {
-                        Self::make_error_response(
-                            e,
-                            "Failed to parse arguments",
-                            StatusCode::BAD_REQUEST,
-                        )
-                    })?;
-                debug!("Received request for shutdown from {caller_id}: {msg}");
-                node_server.shutdown().map_err(|e| {
-                    Self::make_response_from_boxed_error(
+                let args: Vec<String> = serde_xmlrpc::from_values(args).map_err(|e| {
+                    Self::make_error_response(
                         e,
-                        "Unable to shutdown",
-                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Failed to parse arguments",
+                        StatusCode::BAD_REQUEST,
                     )
                 })?;
+                // The message is optional in rospy's implementation, so some tools only send the caller_id
+                let (caller_id, msg) = match args.as_slice() {
+                    [caller_id] => (caller_id.as_str(), ""),
+                    [caller_id, msg] => (caller_id.as_str(), msg.as_str()),
+                    _ => {
+                        return Err(Self::make_response_from_boxed_error(
+                            format!("Expected a caller_id and message, got {args:?}").into(),
+                            "Failed to parse arguments",
+                            StatusCode::BAD_REQUEST,
+                        ))
+                    }
+                };
+                info!("Received request for shutdown from {caller_id}: {msg}");
+                node_server
+                    .graph_events()
+                    .record("shutdown", caller_id, Ok(msg.to_owned()));
+                let reason = if msg.contains(NAME_CONFLICT_REASON) {
+                    NodeShutdownError::SupersededByNewNode {
+                        name: node_server.get_node_name().await.unwrap_or_default(),
+                    }
+                } else {
+                    NodeShutdownError::ShutDown
+                };
+                // Only fails if the node is already shutting down, e.g. when asked twice by rosnode kill,
+                // in which case the request is already satisfied
+                if node_server.shutdown(reason).is_err() {
+                    debug!("Node was already shutting down");
+                }
 
                 Self::to_response(0)
             }
-            // getBusStats, getBusInfo <= have decided not to impl these
             _ => {
                 let error_str = format!("Client attempted call function {method_name} which is not implemented by the Node's xmlrpc server.");
                 warn!("{error_str}");
@@ -3,7 +3,7 @@
 //!
 //! Ensure rosapi is running on your target system before attempting to utilize these features!
 
-use crate::{ClientHandle, RosLibRustResult};
+use crate::{ClientHandle, GraphQuery, NodeDetails, RosLibRustResult};
 use async_trait::async_trait;
 
 // TODO major issue here for folks who actually try to use rosapi in their project
@@ -373,6 +373,70 @@ impl RosApi for ClientHandle {
     */
 }
 
+type GraphQueryResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+// rosapi answers with an empty string where a topic or service is unknown
+fn known(name: String) -> Option<String> {
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+#[async_trait]
+impl GraphQuery for ClientHandle {
+    async fn topics(&self) -> GraphQueryResult<Vec<(String, String)>> {
+        let response = RosApi::topics(self).await?;
+        Ok(response.topics.into_iter().zip(response.types).collect())
+    }
+
+    async fn topic_type(&self, topic: &str) -> GraphQueryResult<Option<String>> {
+        Ok(known(self.get_topic_type(topic).await?.r#type))
+    }
+
+    async fn topics_for_type(&self, topic_type: &str) -> GraphQueryResult<Vec<String>> {
+        Ok(self.get_topics_for_type(topic_type).await?.topics)
+    }
+
+    async fn nodes(&self) -> GraphQueryResult<Vec<String>> {
+        Ok(self.get_nodes().await?.nodes)
+    }
+
+    async fn node_details(&self, node: &str) -> GraphQueryResult<NodeDetails> {
+        let response = self.get_node_details(node).await?;
+        Ok(NodeDetails {
+            publishing: response.publishing,
+            subscribing: response.subscribing,
+            services: response.services,
+        })
+    }
+
+    async fn publishers(&self, topic: &str) -> GraphQueryResult<Vec<String>> {
+        Ok(RosApi::publishers(self, topic).await?.publishers)
+    }
+
+    async fn subscribers(&self, topic: &str) -> GraphQueryResult<Vec<String>> {
+        let response: rosapi::SubscribersResponse = self
+            .call_service(
+                "/rosapi/subscribers",
+                rosapi::SubscribersRequest {
+                    topic: topic.to_owned(),
+                },
+            )
+            .await?;
+        Ok(response.subscribers)
+    }
+
+    async fn services(&self) -> GraphQueryResult<Vec<String>> {
+        Ok(self.get_services().await?.services)
+    }
+
+    async fn service_node(&self, service: &str) -> GraphQueryResult<Option<String>> {
+        Ok(known(self.get_node_for_service(service).await?.node))
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "running_bridge")]
 // TODO currently rosapi only supports ros1, we should try to figure out a way to fix that
@@ -542,4 +606,29 @@ mod test {
         let response = api.get_services().await.unwrap();
         assert!(!response.services.is_empty());
     }
+
+    #[test_log::test(tokio::test)]
+    async fn rosapi_graph_query() {
+        use crate::GraphQuery;
+        let api = fixture_client().await;
+        assert_eq!(
+            GraphQuery::topic_type(&api, "/rosout").await.unwrap(),
+            Some("rosgraph_msgs/Log".to_owned())
+        );
+        assert_eq!(
+            GraphQuery::topic_type(&api, "/not_a_topic").await.unwrap(),
+            None
+        );
+        assert_eq!(
+            GraphQuery::service_node(&api, "/rosapi/service_node")
+                .await
+                .unwrap(),
+            Some("/rosapi".to_owned())
+        );
+        assert!(GraphQuery::subscribers(&api, "/rosout")
+            .await
+            .unwrap()
+            .iter()
+            .any(|node| node == "/rosout"));
+    }
 }
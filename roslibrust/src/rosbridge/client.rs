@@ -1,6 +1,6 @@
 use crate::rosbridge::comm;
-use crate::{rosbridge::comm::RosBridgeComm, RosLibRustError};
-use crate::{Publisher, ServiceHandle, Subscriber};
+use crate::{response_cache::ResponseCache, rosbridge::comm::RosBridgeComm, RosLibRustError};
+use crate::{Publisher, ReconnectPolicy, ServiceCallback, ServiceHandle, Subscriber};
 use anyhow::anyhow;
 use dashmap::DashMap;
 use futures::StreamExt;
@@ -12,11 +12,12 @@ use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tokio::time::Duration;
 use tokio_tungstenite::tungstenite::Message;
 
 use super::{
-    MessageQueue, PublisherHandle, Reader, RosLibRustResult, ServiceCallback, Socket, Subscription,
+    Encoding, MessageQueue, PublisherHandle, Reader, RosLibRustResult, Socket, Subscription,
     Writer, QUEUE_SIZE,
 };
 
@@ -25,6 +26,9 @@ use super::{
 pub struct ClientHandleOptions {
     url: String,
     timeout: Option<Duration>,
+    encoding: Encoding,
+    cached_services: HashMap<String, Duration>,
+    reconnect_policy: ReconnectPolicy,
 }
 
 impl ClientHandleOptions {
@@ -33,6 +37,9 @@ impl ClientHandleOptions {
         ClientHandleOptions {
             url: url.into(),
             timeout: None,
+            encoding: Encoding::Json,
+            cached_services: HashMap::new(),
+            reconnect_policy: ReconnectPolicy::default(),
         }
     }
 
@@ -43,6 +50,31 @@ impl ClientHandleOptions {
         self.timeout = Some(duration.into());
         self
     }
+
+    /// Configures the encoding used for operations sent to rosbridge, defaults to [Encoding::Json].
+    /// Rosbridge servers run with `bson_only_mode` require `Encoding::Bson`, which is provided by the `bson` feature.
+    /// Incoming frames are decoded based on their frame type regardless of this setting.
+    pub fn encoding(mut self, encoding: Encoding) -> ClientHandleOptions {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Caches successful responses from `service` for `ttl`, so repeated calls with an identical request
+    /// are answered without contacting rosbridge. Only suitable for idempotent services such as `/rosapi/topic_type`.
+    pub fn cache_service_responses<S: Into<String>>(
+        mut self,
+        service: S,
+        ttl: Duration,
+    ) -> ClientHandleOptions {
+        self.cached_services.insert(service.into(), ttl);
+        self
+    }
+
+    /// Configures how the client retries connecting to rosbridge, see [ReconnectPolicy].
+    pub fn reconnect_policy(mut self, policy: ReconnectPolicy) -> ClientHandleOptions {
+        self.reconnect_policy = policy;
+        self
+    }
 }
 
 /// The ClientHandle is the fundamental object through which users of this library are expected to interact with it.
@@ -86,6 +118,7 @@ impl ClientHandle {
     /// This function respects the [ClientHandleOptions] timeout and will return with an error if a connection is not
     /// established within the timeout.
     pub async fn new_with_options(opts: ClientHandleOptions) -> RosLibRustResult<Self> {
+        let spin_name = format!("rosbridge client of {}", opts.url);
         let inner = Arc::new(RwLock::new(timeout(opts.timeout, Client::new(opts)).await?));
         let inner_weak = Arc::downgrade(&inner);
 
@@ -94,7 +127,10 @@ impl ClientHandle {
 
         // Spawn the spin task
         // The internal stubborn spin task continues to try to reconnect on failure
-        let _ = tokio::task::spawn(stubborn_spin(inner_weak, is_disconnected.clone()));
+        crate::tasks::spawn(
+            &spin_name,
+            stubborn_spin(inner_weak, is_disconnected.clone()),
+        );
 
         Ok(ClientHandle {
             inner,
@@ -305,6 +341,26 @@ impl ClientHandle {
     /// # }
     /// ```
     pub async fn advertise<T>(&self, topic: &str) -> RosLibRustResult<Publisher<T>>
+    where
+        T: RosMessageType,
+    {
+        self.advertise_with_latching(topic, false).await
+    }
+
+    /// Identical to [ClientHandle::advertise], but asks rosbridge_server to latch the topic,
+    /// so subscribers which connect later are sent the last message published on it.
+    pub async fn advertise_latched<T>(&self, topic: &str) -> RosLibRustResult<Publisher<T>>
+    where
+        T: RosMessageType,
+    {
+        self.advertise_with_latching(topic, true).await
+    }
+
+    async fn advertise_with_latching<T>(
+        &self,
+        topic: &str,
+        latch: bool,
+    ) -> RosLibRustResult<Publisher<T>>
     where
         T: RosMessageType,
     {
@@ -327,7 +383,7 @@ impl ClientHandle {
         {
             let mut stream = client.writer.write().await;
             debug!("Advertise got lock on comm");
-            stream.advertise::<T>(topic).await?;
+            stream.advertise::<T>(topic, latch).await?;
         }
         Ok(Publisher::new(topic.to_string(), self.clone()))
     }
@@ -361,6 +417,31 @@ impl ClientHandle {
         &self,
         service: &str,
         req: Req,
+    ) -> RosLibRustResult<Res> {
+        // Only services configured with ClientHandleOptions::cache_service_responses have a cache
+        let cache = self
+            .inner
+            .read()
+            .await
+            .response_caches
+            .get(service)
+            .cloned();
+        let Some((cache, key)) = cache.zip(ResponseCache::key(&req)) else {
+            return self._call_service(service, req).await;
+        };
+        if let Some(response) = cache.get(&key) {
+            return Ok(response);
+        }
+        let response = self._call_service(service, req).await?;
+        cache.insert(key, &response);
+        Ok(response)
+    }
+
+    // Internal implementation of call_service
+    async fn _call_service<Req: RosMessageType, Res: RosMessageType>(
+        &self,
+        service: &str,
+        req: Req,
     ) -> RosLibRustResult<Res> {
         self.check_for_disconnect()?;
         let (tx, rx) = tokio::sync::oneshot::channel();
@@ -473,16 +554,23 @@ impl ClientHandle {
         Ok(ServiceHandle {
             client: self.clone(),
             topic: topic.to_string(),
+            closed: false,
         })
     }
 
     // Internal method for removing a service, this is expected to be automatically called
-    // by dropping the relevant service handle. Intentionally not async as a result.
-    pub(crate) fn unadvertise_service(&self, topic: &str) {
+    // by dropping the relevant service handle. Intentionally not async as a result,
+    // the returned task is only awaited by ServiceHandle::close.
+    pub(crate) fn unadvertise_service(&self, topic: &str) -> JoinHandle<RosLibRustResult<()>> {
         let copy = self.inner.clone();
         let topic = topic.to_string();
-        tokio::spawn(async move {
+        let task_name = format!("rosbridge unadvertise_service {topic}");
+        crate::tasks::spawn(&task_name, async move {
             let client = copy.read().await;
+            // Closing the client already unadvertised everything
+            if client.closed.load(Ordering::Relaxed) {
+                return Ok(());
+            }
             let entry = client.services.remove(&topic);
             // Since this is called by drop we can't really propagate and error and instead simply have to log
             if entry.is_none() {
@@ -495,74 +583,137 @@ impl ClientHandle {
             // Regardless of whether we found an entry we should still send he unadvertise_service message to rosbridge
             let mut writer = client.writer.write().await;
             let res = writer.unadvertise_service(&topic).await;
-            if let Err(e) = res {
+            if let Err(e) = &res {
                 error!("Failed to send unadvertise_service message when service handle was dropped for `{topic}`: {e}");
             }
-        });
+            res
+        })
     }
 
     // This function is not async specifically so it can be called from drop
-    // same reason why it doesn't return anything
+    // same reason why its task is only awaited by Publisher::close
     // Called automatically when Publisher is dropped
-    pub(crate) fn unadvertise(&self, topic_name: &str) {
+    pub(crate) fn unadvertise(&self, topic_name: &str) -> JoinHandle<RosLibRustResult<()>> {
         let copy = self.clone();
         let topic_name_copy = topic_name.to_string();
-        tokio::spawn(async move {
+        crate::tasks::spawn(&format!("rosbridge unadvertise {topic_name}"), async move {
             // Remove publisher from our records
             let client = copy.inner.read().await;
+            if client.closed.load(Ordering::Relaxed) {
+                return Ok(());
+            }
             client.publishers.remove(&topic_name_copy);
 
             // Send unadvertise message
-            {
-                debug!("Unadvertise waiting for comm lock");
-                let mut comm = client.writer.write().await;
-                debug!("Unadvertise got comm lock");
-                if let Err(e) = comm.unadvertise(&topic_name_copy).await {
-                    error!("Failed to send unadvertise in comm layer: {:?}", e);
-                }
+            debug!("Unadvertise waiting for comm lock");
+            let mut comm = client.writer.write().await;
+            debug!("Unadvertise got comm lock");
+            let res = comm.unadvertise(&topic_name_copy).await;
+            if let Err(e) = &res {
+                error!("Failed to send unadvertise in comm layer: {:?}", e);
             }
-        });
+            res
+        })
     }
 
     // This function removes the entry for a subscriber in from the client, and if it is the last
     // subscriber for a given topic then dispatches an unsubscribe message to the master/bridge
-    pub(crate) fn unsubscribe(&self, topic_name: &str, id: &uuid::Uuid) -> RosLibRustResult<()> {
+    pub(crate) fn unsubscribe(
+        &self,
+        topic_name: &str,
+        id: &uuid::Uuid,
+    ) -> JoinHandle<RosLibRustResult<()>> {
         // Copy so we can move into closure
         let client = self.clone();
         let topic_name = topic_name.to_string();
         let id = *id;
         // Actually send the unsubscribe message in a task so subscriber::Drop can call this function
-        tokio::spawn(async move {
+        crate::tasks::spawn(&format!("rosbridge unsubscribe {topic_name}"), async move {
             // Identify the subscription entry for the subscriber
             let client = client.inner.read().await;
+            if client.closed.load(Ordering::Relaxed) {
+                return Ok(());
+            }
             let mut subscription = match client.subscriptions.get_mut(&topic_name) {
                 Some(subscription) => subscription,
                 None => {
                     error!("Topic not found in subscriptions upon dropping. This should be impossible and indicates a bug in the roslibrust crate. Topic: {topic_name} UUID: {id:?}");
-                    return;
+                    return Ok(());
                 }
             };
             if subscription.value_mut().handles.remove(&id).is_none() {
                 error!("Subscriber id {id:?} was not found in handles list for topic {topic_name:?} while unsubscribing");
-                return;
+                return Ok(());
             }
 
             if subscription.handles.is_empty() {
                 // This is the last subscriber for that topic and we need to unsubscribe now
                 let mut stream = client.writer.write().await;
-                match stream.unsubscribe(&topic_name).await {
-                    Ok(_) => {}
-                    Err(e) => error!(
+                let res = stream.unsubscribe(&topic_name).await;
+                if let Err(e) = &res {
+                    error!(
                         "Failed to send unsubscribe while dropping subscriber: {:?}",
                         e
-                    ),
+                    );
                 }
+                return res;
             }
-        });
-        Ok(())
+            Ok(())
+        })
+    }
+
+    /// Closes the connection to rosbridge, first unsubscribing from every topic and unadvertising every topic
+    /// and service the client has, failing with [RosLibRustError::Timeout] if that takes longer than `timeout`.
+    ///
+    /// Afterwards every handle of the client reports [RosLibRustError::Disconnected] and the client no longer reconnects.
+    /// Without closing, the connection stays open until every handle of the client and every publisher,
+    /// subscriber and service handle made from it is dropped, and is then closed without unadvertising first.
+    pub async fn close(&self, timeout: Duration) -> RosLibRustResult<()> {
+        tokio::time::timeout(timeout, async {
+            let client = self.inner.read().await;
+            if client.closed.swap(true, Ordering::Relaxed) {
+                return Ok(());
+            }
+            self.is_disconnected.store(true, Ordering::Relaxed);
+            let subscriptions = keys(&client.subscriptions);
+            let publishers = keys(&client.publishers);
+            let services = keys(&client.services);
+            client.subscriptions.clear();
+            client.publishers.clear();
+            client.services.clear();
+
+            let mut writer = client.writer.write().await;
+            for topic in &subscriptions {
+                writer.unsubscribe(topic).await?;
+            }
+            for topic in &publishers {
+                writer.unadvertise(topic).await?;
+            }
+            for service in &services {
+                writer.unadvertise_service(service).await?;
+            }
+            writer.close().await
+        })
+        .await?
     }
 }
 
+// The keys of `map`, so they can be used across an await without holding its locks
+fn keys<V>(map: &DashMap<String, V>) -> Vec<String> {
+    map.iter().map(|entry| entry.key().clone()).collect()
+}
+
+/// Waits up to `timeout` for a handle's teardown `task` to finish, which carries on in the background
+/// as if the handle had been dropped if it doesn't
+pub(crate) async fn finish_teardown(
+    task: JoinHandle<RosLibRustResult<()>>,
+    timeout: Duration,
+) -> RosLibRustResult<()> {
+    tokio::time::timeout(timeout, task)
+        .await?
+        .map_err(|e| anyhow!("Teardown task failed: {e}"))?
+}
+
 /// A client connection to the rosbridge_server that allows for publishing and subscribing to topics
 pub(crate) struct Client {
     reader: RwLock<Reader>,
@@ -571,16 +722,21 @@ pub(crate) struct Client {
     publishers: DashMap<String, PublisherHandle>,
     subscriptions: DashMap<String, Subscription>,
     services: DashMap<String, ServiceCallback>,
+    // Set once the client is closed, after which it no longer reconnects
+    closed: AtomicBool,
     // Contains any outstanding service calls we're waiting for a response on
     // Map key will be a uniquely generated id for each call
     service_calls: DashMap<String, tokio::sync::oneshot::Sender<Value>>,
+    // Response caches for the services configured with ClientHandleOptions::cache_service_responses
+    response_caches: HashMap<String, ResponseCache>,
     opts: ClientHandleOptions,
 }
 
 impl Client {
     // internal implementation of new
     async fn new(opts: ClientHandleOptions) -> RosLibRustResult<Self> {
-        let (writer, reader) = stubborn_connect(&opts.url).await;
+        let (writer, reader) =
+            stubborn_connect(&opts.url, opts.encoding, &opts.reconnect_policy).await?;
         let client = Self {
             reader: RwLock::new(reader),
             writer: RwLock::new(writer),
@@ -588,6 +744,12 @@ impl Client {
             services: DashMap::new(),
             subscriptions: DashMap::new(),
             service_calls: DashMap::new(),
+            closed: AtomicBool::new(false),
+            response_caches: opts
+                .cached_services
+                .iter()
+                .map(|(service, ttl)| (service.clone(), ResponseCache::new(*ttl)))
+                .collect(),
             opts,
         };
 
@@ -596,10 +758,19 @@ impl Client {
 
     async fn handle_message(&self, msg: Message) -> RosLibRustResult<()> {
         match msg {
-            Message::Text(text) => {
-                debug!("got message: {}", text);
-                // TODO better error handling here serde_json::Error not send
-                let parsed: serde_json::Value = serde_json::from_str(text.as_str()).unwrap();
+            Message::Text(_) | Message::Binary(_) => {
+                let parsed: serde_json::Value = match msg {
+                    Message::Text(text) => {
+                        debug!("got message: {}", text);
+                        // TODO better error handling here serde_json::Error not send
+                        serde_json::from_str(text.as_str()).unwrap()
+                    }
+                    Message::Binary(data) => {
+                        debug!("got binary message of {} bytes", data.len());
+                        comm::decode_bson(&data)?
+                    }
+                    _ => unreachable!(),
+                };
                 let parsed_object = parsed
                     .as_object()
                     .expect("Recieved non-object json response");
@@ -627,6 +798,8 @@ impl Client {
                     }
                 }
             }
+            // The server acknowledging ClientHandle::close
+            Message::Close(_) if self.closed.load(Ordering::Relaxed) => {}
             Message::Close(close) => {
                 // TODO how should we respond to this?
                 // How do we represent connection status via our API well?
@@ -639,7 +812,7 @@ impl Client {
                 debug!("Pong received {:?}", pong);
             }
             _ => {
-                panic!("Non-text response received");
+                panic!("Non-data response received");
             }
         }
 
@@ -731,7 +904,12 @@ impl Client {
 
     async fn reconnect(&mut self) -> RosLibRustResult<()> {
         // Reconnect stream
-        let (writer, reader) = stubborn_connect(&self.opts.url).await;
+        let (writer, reader) = stubborn_connect(
+            &self.opts.url,
+            self.opts.encoding,
+            &self.opts.reconnect_policy,
+        )
+        .await?;
         self.reader = RwLock::new(reader);
         self.writer = RwLock::new(writer);
 
@@ -764,6 +942,8 @@ async fn stubborn_spin(
 
         match tokio::time::timeout(SPIN_DURATION, client.read().await.spin_once()).await {
             Ok(Ok(())) => {}
+            // Closed on purpose, see ClientHandle::close
+            Ok(Err(_)) if client.read().await.closed.load(Ordering::Relaxed) => break,
             Ok(Err(err)) => {
                 is_disconnected.store(true, Ordering::Relaxed);
                 warn!("Spin failed with error: {err}, attempting to reconnect");
@@ -793,19 +973,27 @@ where
     }
 }
 
-// Connects to websocket at specified URL, retries indefinitely
-async fn stubborn_connect(url: &str) -> (Writer, Reader) {
+// Connects to websocket at specified URL, retrying according to the policy
+async fn stubborn_connect(
+    url: &str,
+    encoding: Encoding,
+    policy: &ReconnectPolicy,
+) -> RosLibRustResult<(Writer, Reader)> {
+    let mut failed_attempts = 0;
     loop {
         match connect(url).await {
             Err(e) => {
-                warn!("Failed to reconnect: {:?}", e);
-                // TODO configurable rate?
-                tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
-                continue;
+                failed_attempts += 1;
+                let Some(delay) = policy.delay(failed_attempts) else {
+                    warn!("Giving up connecting to {url} after {failed_attempts} attempts: {e:?}");
+                    return Err(e);
+                };
+                warn!("Failed to reconnect: {e:?}, retrying in {delay:?}");
+                tokio::time::sleep(delay).await;
             }
             Ok(stream) => {
                 let (writer, reader) = stream.split();
-                return (writer, reader);
+                return Ok((Writer::new(writer, encoding), reader));
             }
         }
     }
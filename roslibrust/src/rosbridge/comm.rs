@@ -7,8 +7,10 @@ use log::debug;
 use serde_json::json;
 use tokio_tungstenite::tungstenite::Message;
 
-use crate::{rosbridge::RosLibRustResult, rosbridge::Writer};
+use crate::rosbridge::{RosLibRustResult, Socket};
+use futures_util::stream::SplitSink;
 use roslibrust_codegen::RosMessageType;
+use serde_json::Value;
 
 /// Describes all documented rosbridge server operations
 pub(crate) enum Ops {
@@ -85,17 +87,91 @@ impl FromStr for Ops {
     }
 }
 
+/// Wire encoding used for the operations sent to rosbridge
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// Operations are sent as JSON text frames, this is rosbridge's default
+    #[default]
+    Json,
+    /// Operations are sent as BSON binary frames, required when rosbridge is run with `bson_only_mode`
+    #[cfg(feature = "bson")]
+    Bson,
+}
+
+/// The write half of our websocket, encodes outgoing operations with the configured [Encoding]
+pub(crate) struct Writer {
+    sink: SplitSink<Socket, Message>,
+    encoding: Encoding,
+}
+
+impl Writer {
+    pub(crate) fn new(sink: SplitSink<Socket, Message>, encoding: Encoding) -> Self {
+        Self { sink, encoding }
+    }
+
+    async fn send(&mut self, msg: Value) -> RosLibRustResult<()> {
+        let msg = match self.encoding {
+            Encoding::Json => Message::Text(msg.to_string()),
+            #[cfg(feature = "bson")]
+            Encoding::Bson => Message::Binary(
+                bson::to_vec(&msg).map_err(|e| anyhow::anyhow!("Failed to encode BSON: {e}"))?,
+            ),
+        };
+        self.sink.send(msg).await?;
+        Ok(())
+    }
+
+    /// Sends a close frame, ending the connection
+    pub(crate) async fn close(&mut self) -> RosLibRustResult<()> {
+        self.sink.close().await?;
+        Ok(())
+    }
+}
+
+/// Decodes a BSON binary frame received from rosbridge into the same JSON representation
+/// that is produced for text frames, so the rest of the client can stay encoding agnostic.
+#[cfg(feature = "bson")]
+pub(crate) fn decode_bson(data: &[u8]) -> RosLibRustResult<Value> {
+    let document = bson::Document::from_reader(data)
+        .map_err(|e| anyhow::anyhow!("Failed to decode BSON: {e}"))?;
+    Ok(bson_to_json(bson::Bson::Document(document)))
+}
+
+#[cfg(feature = "bson")]
+fn bson_to_json(value: bson::Bson) -> Value {
+    match value {
+        bson::Bson::Document(document) => Value::Object(
+            document
+                .into_iter()
+                .map(|(key, value)| (key, bson_to_json(value)))
+                .collect(),
+        ),
+        bson::Bson::Array(array) => Value::Array(array.into_iter().map(bson_to_json).collect()),
+        // rosbridge sends uint8[] fields as binary, generated types expect them as an array of numbers
+        bson::Bson::Binary(binary) => {
+            Value::Array(binary.bytes.into_iter().map(Value::from).collect())
+        }
+        other => other.into_relaxed_extjson(),
+    }
+}
+
+#[cfg(not(feature = "bson"))]
+pub(crate) fn decode_bson(_data: &[u8]) -> RosLibRustResult<Value> {
+    Err(anyhow::anyhow!("Received a BSON frame, decoding BSON requires the bson feature").into())
+}
+
 /// Describes the low level comm capabilities of talking to a rosbridge server
-/// This trait exists because we haven't wrapped Writer in our own type
-/// So we're defining this trait on a foregin type, since we didn't end up
-/// using this trait for mocking. I'm inclined to replace it, and move the
-/// impls directly into some wrapper around [Writer]
 #[async_trait]
 pub(crate) trait RosBridgeComm {
     async fn subscribe(&mut self, topic: &str, msg_type: &str) -> RosLibRustResult<()>;
     async fn unsubscribe(&mut self, topic: &str) -> RosLibRustResult<()>;
     async fn publish<T: RosMessageType>(&mut self, topic: &str, msg: T) -> RosLibRustResult<()>;
-    async fn advertise<T: RosMessageType>(&mut self, topic: &str) -> RosLibRustResult<()>;
+    async fn advertise<T: RosMessageType>(
+        &mut self,
+        topic: &str,
+        latch: bool,
+    ) -> RosLibRustResult<()>;
     async fn call_service<Req: RosMessageType>(
         &mut self,
         service: &str,
@@ -124,7 +200,6 @@ impl RosBridgeComm for Writer {
         "type": msg_type,
         }
         );
-        let msg = Message::Text(msg.to_string());
         debug!("Sending subscribe: {:?}", &msg);
         self.send(msg).await?;
         Ok(())
@@ -137,7 +212,6 @@ impl RosBridgeComm for Writer {
         "topic": topic,
         }
         );
-        let msg = Message::Text(msg.to_string());
         debug!("Sending unsubscribe: {:?}", &msg);
         self.send(msg).await?;
         Ok(())
@@ -152,21 +226,27 @@ impl RosBridgeComm for Writer {
                 "msg": &msg,
             }
         );
-        let msg = Message::Text(msg.to_string());
         debug!("Sending publish: {:?}", &msg);
         self.send(msg).await?;
         Ok(())
     }
 
-    async fn advertise<T: RosMessageType>(&mut self, topic: &str) -> RosLibRustResult<()> {
-        let msg = json!(
+    async fn advertise<T: RosMessageType>(
+        &mut self,
+        topic: &str,
+        latch: bool,
+    ) -> RosLibRustResult<()> {
+        let mut msg = json!(
             {
                 "op": Ops::Advertise.to_string(),
                 "topic": topic.to_string(),
                 "type": T::ROS_TYPE_NAME,
             }
         );
-        let msg = Message::Text(msg.to_string());
+        // Only sent when set, as an extension of the protocol implemented by rosbridge_library
+        if latch {
+            msg["latch"] = json!(true);
+        }
         debug!("Sending advertise: {:?}", &msg);
         self.send(msg).await?;
         Ok(())
@@ -186,7 +266,6 @@ impl RosBridgeComm for Writer {
                 "args": req,
             }
         );
-        let msg = Message::Text(msg.to_string());
         debug!("Sending call_service: {:?}", &msg);
         self.send(msg).await?;
         Ok(())
@@ -200,7 +279,6 @@ impl RosBridgeComm for Writer {
                 "topic": topic
             }
         };
-        let msg = Message::Text(msg.to_string());
         debug!("Sending unadvertise: {:?}", &msg);
         self.send(msg).await?;
         Ok(())
@@ -215,7 +293,6 @@ impl RosBridgeComm for Writer {
                 "service": srv_name
             }
         };
-        let msg = Message::Text(msg.to_string());
         self.send(msg).await?;
         Ok(())
     }
@@ -228,7 +305,6 @@ impl RosBridgeComm for Writer {
                 "service": &topic
             }
         };
-        let msg = Message::Text(msg.to_string());
         self.send(msg).await?;
         Ok(())
     }
@@ -253,9 +329,40 @@ impl RosBridgeComm for Writer {
                 "values": response,
             }
         };
-        let msg = Message::Text(msg.to_string());
         debug!("Sending service_response: {:?}", &msg);
         self.send(msg).await?;
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "bson"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bson_decodes_like_json() {
+        let msg = json!({
+            "op": Ops::Publish.to_string(),
+            "topic": "/chatter",
+            "msg": { "data": "hello", "count": 3, "nested": [1.5, true] },
+        });
+        let encoded = bson::to_vec(&msg).unwrap();
+        assert_eq!(decode_bson(&encoded).unwrap(), msg);
+    }
+
+    #[test]
+    fn bson_binary_decodes_as_byte_array() {
+        let document = bson::doc! {
+            "op": "publish",
+            "msg": {
+                "data": bson::Binary { subtype: bson::spec::BinarySubtype::Generic, bytes: vec![1, 2, 255] },
+            },
+        };
+        let mut encoded = vec![];
+        document.to_writer(&mut encoded).unwrap();
+        assert_eq!(
+            decode_bson(&encoded).unwrap(),
+            json!({ "op": "publish", "msg": { "data": [1, 2, 255] } })
+        );
+    }
+}
@@ -322,6 +322,33 @@ mod integration_tests {
         Ok(())
     }
 
+    #[test_log::test(tokio::test)]
+    async fn close_tears_down_before_returning() -> TestResult {
+        let client =
+            ClientHandle::new_with_options(ClientHandleOptions::new(LOCAL_WS).timeout(TIMEOUT))
+                .await?;
+        let publisher = client.advertise::<Header>("/close_tears_down").await?;
+        let subscriber = client.subscribe::<Header>("/close_tears_down").await?;
+        publisher.close(TIMEOUT).await?;
+        subscriber.close(TIMEOUT).await?;
+        // Only one publisher of a topic is allowed at a time, so this relies on the first having been removed
+        let publisher = client.advertise::<Header>("/close_tears_down").await?;
+        publisher.close(TIMEOUT).await?;
+
+        // Handles still open are torn down along with the client, dropping them afterwards does nothing
+        let publisher = client
+            .advertise::<Header>("/close_tears_down/dropped")
+            .await?;
+        client.close(TIMEOUT).await?;
+        std::mem::drop(publisher);
+        let res = client.advertise::<Header>("/close_tears_down").await;
+        assert!(matches!(res, Err(RosLibRustError::Disconnected)));
+        // Closing again does nothing
+        client.close(TIMEOUT).await?;
+
+        Ok(())
+    }
+
     #[test_log::test(tokio::test)]
     async fn working_with_char() -> TestResult {
         let client =
@@ -372,4 +399,64 @@ mod integration_tests {
             }
         }
     }
+
+    #[cfg(all(feature = "ros1_test", feature = "dynamic_reconfigure"))]
+    #[test_log::test(tokio::test)]
+    async fn dynamic_reconfigure_round_trip() -> TestResult {
+        use crate::dynamic_reconfigure::{msgs, ConfigDescriptor, Parameter, ReconfigureServer};
+
+        #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+        struct RateConfig {
+            rate: i32,
+        }
+
+        let client =
+            ClientHandle::new_with_options(ClientHandleOptions::new(LOCAL_WS).timeout(TIMEOUT))
+                .await?;
+        let (levels, mut received_levels) = tokio::sync::mpsc::unbounded_channel();
+        let descriptor =
+            ConfigDescriptor::new().parameter(Parameter::int("rate", 10, 1, 100).level(2));
+        let server = ReconfigureServer::new(
+            &client,
+            "/dynamic_reconfigure_round_trip",
+            descriptor,
+            move |_config: &mut RateConfig, level| levels.send(level).unwrap(),
+        )
+        .await?;
+        assert_eq!(received_levels.recv().await, Some(!0));
+        tokio::time::sleep(TIMEOUT).await;
+
+        let request = msgs::ReconfigureRequest {
+            config: msgs::Config {
+                ints: vec![msgs::IntParameter {
+                    name: "rate".to_owned(),
+                    value: 1000,
+                }],
+                ..Default::default()
+            },
+        };
+        let response: msgs::ReconfigureResponse = client
+            .call_service("/dynamic_reconfigure_round_trip/set_parameters", request)
+            .await?;
+        // Clamped to the parameter's bounds
+        assert_eq!(response.config.ints[0].value, 100);
+        assert_eq!(server.config().rate, 100);
+        assert_eq!(received_levels.recv().await, Some(2));
+
+        // Both topics are latched, so the latest of each is received on subscribing
+        let updates = client
+            .subscribe::<msgs::Config>("/dynamic_reconfigure_round_trip/parameter_updates")
+            .await?;
+        let update = timeout(TIMEOUT, updates.next()).await?;
+        assert_eq!(update.ints[0].value, 100);
+        let descriptions = client
+            .subscribe::<msgs::ConfigDescription>(
+                "/dynamic_reconfigure_round_trip/parameter_descriptions",
+            )
+            .await?;
+        let description = timeout(TIMEOUT, descriptions.next()).await?;
+        assert_eq!(description.groups[0].parameters[0].name, "rate");
+
+        Ok(())
+    }
 }
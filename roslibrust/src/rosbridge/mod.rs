@@ -28,13 +28,13 @@ mod topic_provider;
 
 /// Communication primitives for the rosbridge_suite protocol
 mod comm;
+pub use comm::Encoding;
+use comm::Writer;
 
-use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::stream::SplitStream;
 use log::*;
 use std::collections::HashMap;
 use tokio::net::TcpStream;
-use tokio_tungstenite::*;
-use tungstenite::Message;
 
 /// For now starting with a central error type, may break this up more in future
 #[derive(thiserror::Error, Debug)]
@@ -74,21 +74,6 @@ pub type RosLibRustResult<T> = Result<T, RosLibRustError>;
 /// Used for type erasure of message type so that we can store arbitrary handles
 type Callback = Box<dyn Fn(&str) + Send + Sync>;
 
-/// Type erasure of callback for a service
-/// Internally this will covert the input string to the Request type
-/// Send that converted type into the user's callback
-/// Get the result of the user's callback and then serialize that so it can be transmitted
-// TODO reconsider use of serde_json::Value here vs. tungstenite::Message vs. &str
-// Not quite sure what type we want to erase to?
-// I can make a good argument for &str because that should be generic even if we switch
-// backends - Carter 2022-10-6
-// TODO move out of rosbridge and into "common"
-pub(crate) type ServiceCallback = Box<
-    dyn Fn(&str) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>
-        + Send
-        + Sync,
->;
-
 /// The handle returned to the caller of advertise_service this struct represents the lifetime
 /// of the service, and dropping this struct automatically unadvertises and removes the service.
 /// No interaction with this struct is expected beyond managing its lifetime.
@@ -98,12 +83,26 @@ pub struct ServiceHandle {
     /// Topic that the service is served on, this is used to uniquely identify it, only one service
     /// may exist for a given topic (per client, we can't control it on the ROS side)
     topic: String,
+    /// Set by close, which unadvertises the service in place of drop
+    closed: bool,
+}
+
+impl ServiceHandle {
+    /// Unadvertises the service, returning once rosbridge has been sent the unadvertise,
+    /// or failing with [RosLibRustError::Timeout] if that takes longer than `timeout`,
+    /// in which case it carries on in the background as it would have if the handle were dropped.
+    pub async fn close(mut self, timeout: tokio::time::Duration) -> RosLibRustResult<()> {
+        self.closed = true;
+        client::finish_teardown(self.client.unadvertise_service(&self.topic), timeout).await
+    }
 }
 
-/// Service handles automatically unadvertise their service when dropped.
+/// Service handles automatically unadvertise their service when dropped, in the background as drop can't wait for it.
 impl Drop for ServiceHandle {
     fn drop(&mut self) {
-        self.client.unadvertise_service(&self.topic);
+        if !self.closed {
+            self.client.unadvertise_service(&self.topic);
+        }
     }
 }
 
@@ -114,10 +113,6 @@ type Socket = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStre
 /// This is the read half.
 type Reader = SplitStream<Socket>;
 
-/// We split our underlying socket into two halves with separate locks on read and write.
-/// This is the write half.
-type Writer = SplitSink<Socket, Message>;
-
 /// Topics have a fundamental queue *per subscriber* this is te queue type used for each subscriber.
 type MessageQueue<T> = deadqueue::limited::Queue<T>;
 
@@ -1,5 +1,9 @@
-use crate::{rosbridge::RosLibRustResult, ClientHandle};
+use crate::{
+    rosbridge::{client::finish_teardown, RosLibRustResult},
+    ClientHandle,
+};
 use roslibrust_codegen::RosMessageType;
+use tokio::time::Duration;
 
 /// A handle given to the caller when they advertise a topic
 ///
@@ -24,13 +28,17 @@ pub struct Publisher<T: RosMessageType> {
     // seq: usize,
     // Stores a copy of the client so that we can de-register ourselves
     client: ClientHandle,
+    // Set by close, which unadvertises in place of drop
+    closed: bool,
     _marker: std::marker::PhantomData<T>,
 }
 
-/// Publisher will un-advertise its topic automatically on drop
+/// Publisher will un-advertise its topic automatically on drop, in the background as drop can't wait for it
 impl<T: RosMessageType> Drop for Publisher<T> {
     fn drop(&mut self) {
-        self.client.unadvertise(&self.topic);
+        if !self.closed {
+            self.client.unadvertise(&self.topic);
+        }
     }
 }
 
@@ -39,6 +47,7 @@ impl<T: RosMessageType> Publisher<T> {
         Publisher {
             topic,
             client,
+            closed: false,
             _marker: Default::default(),
         }
     }
@@ -51,4 +60,12 @@ impl<T: RosMessageType> Publisher<T> {
     pub async fn publish(&self, msg: T) -> RosLibRustResult<()> {
         self.client.publish(&self.topic, msg).await
     }
+
+    /// Un-advertises the topic, returning once rosbridge has been sent the unadvertise,
+    /// or failing with [crate::RosLibRustError::Timeout] if that takes longer than `timeout`,
+    /// in which case it carries on in the background as it would have if the publisher were dropped.
+    pub async fn close(mut self, timeout: Duration) -> RosLibRustResult<()> {
+        self.closed = true;
+        finish_teardown(self.client.unadvertise(&self.topic), timeout).await
+    }
 }
@@ -2,11 +2,14 @@
 // The subscriber manages the lifetime of the subscription and provides an
 // API which allows us to hide our underlying queue/channel type
 
-use log::error;
 use std::sync::Arc;
 
-use crate::{rosbridge::MessageQueue, ClientHandle};
+use crate::{
+    rosbridge::{client::finish_teardown, MessageQueue, RosLibRustResult},
+    ClientHandle,
+};
 use roslibrust_codegen::RosMessageType;
+use tokio::time::Duration;
 
 /// Represents a single instance of listening to a topic, and provides the ability to extract messages
 ///
@@ -29,6 +32,8 @@ pub struct Subscriber<T: RosMessageType> {
     // Holds an internal copy of client to reference back to when being drop'ed
     client: ClientHandle,
     queue: Arc<MessageQueue<T>>,
+    // Set by close, which unsubscribes in place of drop
+    closed: bool,
 }
 
 impl<T: RosMessageType> Subscriber<T> {
@@ -40,6 +45,7 @@ impl<T: RosMessageType> Subscriber<T> {
             topic,
             client,
             queue,
+            closed: false,
         }
     }
 
@@ -73,6 +79,23 @@ impl<T: RosMessageType> Subscriber<T> {
         self.queue.pop().await
     }
 
+    /// Turns the subscriber into a [futures::Stream] of its messages, e.g. for use with [crate::OrderedMerge].
+    /// The stream never ends, dropping it unsubscribes.
+    pub fn into_stream(self) -> impl futures::Stream<Item = T> + Send {
+        futures::stream::unfold(self, |subscriber| async move {
+            let msg = subscriber.next().await;
+            Some((msg, subscriber))
+        })
+    }
+
+    /// Unsubscribes, returning once rosbridge has been sent the unsubscribe if this was the last subscriber of the topic,
+    /// or failing with [crate::RosLibRustError::Timeout] if that takes longer than `timeout`,
+    /// in which case it carries on in the background as it would have if the subscriber were dropped.
+    pub async fn close(mut self, timeout: Duration) -> RosLibRustResult<()> {
+        self.closed = true;
+        finish_teardown(self.client.unsubscribe(&self.topic, &self.id), timeout).await
+    }
+
     // Used internally to track subscribers within the ClientHandle
     pub(crate) fn get_id(&self) -> &uuid::Uuid {
         &self.id
@@ -80,14 +103,12 @@ impl<T: RosMessageType> Subscriber<T> {
 }
 
 /// Informs the client that the subscriber is being dropped so that
-/// the client can track when the last subscriber for a topic is dropped
+/// the client can track when the last subscriber for a topic is dropped.
+/// The client unsubscribes in the background as drop can't wait for it, logging any failure.
 impl<T: RosMessageType> Drop for Subscriber<T> {
     fn drop(&mut self) {
-        match self.client.unsubscribe(&self.topic, &self.id) {
-            Ok(_) => {}
-            Err(e) => {
-                error!("Failed to unsubscribe while dropping subscriber: topic={:?}, id={:?}, err={:?}", &self.topic, &self.id, e);
-            }
+        if !self.closed {
+            self.client.unsubscribe(&self.topic, &self.id);
         }
     }
 }
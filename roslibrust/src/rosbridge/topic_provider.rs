@@ -79,7 +79,7 @@ impl TopicProvider for crate::ClientHandle {
         )
             -> Result<T::Response, Box<dyn std::error::Error + 'static + Send + Sync>>,
     ) -> RosLibRustResult<Self::ServiceHandle> {
-        self.advertise_service::<T>(topic, server).await
+        self.advertise_service::<T, _>(topic, server).await
     }
 }
 
@@ -0,0 +1,49 @@
+//! Spawning of the tasks roslibrust runs in the background, each named after the topic, peer or node it serves
+//! so a node with hundreds of tasks can be made sense of in [tokio-console](https://github.com/tokio-rs/console).
+//!
+//! Tokio only names tasks when built with `RUSTFLAGS="--cfg tokio_unstable"`, so names are attached with the
+//! `tokio-console` feature enabled under that flag and are otherwise ignored.
+
+use std::future::Future;
+use tokio::task::JoinHandle;
+
+/// Spawns `future` as a task called `name`, panicking like [tokio::spawn] if not called within a runtime.
+#[track_caller]
+pub(crate) fn spawn<F>(name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(all(tokio_unstable, feature = "tokio-console"))]
+    return tokio::task::Builder::new()
+        .name(name)
+        .spawn(future)
+        .unwrap_or_else(|err| panic!("Failed to spawn task {name}: {err}"));
+
+    #[cfg(not(all(tokio_unstable, feature = "tokio-console")))]
+    {
+        let _ = name;
+        tokio::spawn(future)
+    }
+}
+
+/// Runs `function` on the blocking thread pool as a task called `name`, see [tokio::task::spawn_blocking].
+#[cfg(feature = "ros1")]
+#[track_caller]
+pub(crate) fn spawn_blocking<F, R>(name: &str, function: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    #[cfg(all(tokio_unstable, feature = "tokio-console"))]
+    return tokio::task::Builder::new()
+        .name(name)
+        .spawn_blocking(function)
+        .unwrap_or_else(|err| panic!("Failed to spawn task {name}: {err}"));
+
+    #[cfg(not(all(tokio_unstable, feature = "tokio-console")))]
+    {
+        let _ = name;
+        tokio::task::spawn_blocking(function)
+    }
+}
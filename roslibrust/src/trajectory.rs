@@ -0,0 +1,434 @@
+//! This module contains helpers for commanding joint trajectories: validating `trajectory_msgs/JointTrajectory`
+//! messages before sending them, matching the joint order of controllers, and tracking a commanded trajectory
+//! against `sensor_msgs/JointState` feedback.
+//!
+//! As with [crate::Stamped], the helpers work with generated messages through traits implemented for them:
+//! ```ignore
+//! impl roslibrust::trajectory::TrajectoryPoint for trajectory_msgs::JointTrajectoryPoint {
+//!     fn positions(&self) -> &[f64] { &self.positions }
+//!     fn velocities(&self) -> &[f64] { &self.velocities }
+//!     fn accelerations(&self) -> &[f64] { &self.accelerations }
+//!     fn effort(&self) -> &[f64] { &self.effort }
+//!     fn time_from_start(&self) -> std::time::Duration {
+//!         std::time::Duration::new(self.time_from_start.sec as u64, self.time_from_start.nsec as u32)
+//!     }
+//! }
+//! impl roslibrust::trajectory::JointTrajectory for trajectory_msgs::JointTrajectory {
+//!     type Point = trajectory_msgs::JointTrajectoryPoint;
+//!     fn joint_names(&self) -> &[String] { &self.joint_names }
+//!     fn points(&self) -> &[Self::Point] { &self.points }
+//! }
+//! impl roslibrust::trajectory::JointState for sensor_msgs::JointState {
+//!     fn names(&self) -> &[String] { &self.name }
+//!     fn positions(&self) -> &[f64] { &self.position }
+//! }
+//! impl roslibrust::Stamped for sensor_msgs::JointState {
+//!     fn stamp(&self) -> roslibrust_codegen::Time { self.header.stamp.clone() }
+//! }
+//! ```
+
+use crate::{ordered_merge::to_duration, Stamped};
+use roslibrust_codegen::Time;
+use std::time::Duration;
+
+/// A `trajectory_msgs/JointTrajectoryPoint`, see the [module docs](self) for implementing it
+pub trait TrajectoryPoint {
+    fn positions(&self) -> &[f64];
+    fn velocities(&self) -> &[f64];
+    fn accelerations(&self) -> &[f64];
+    fn effort(&self) -> &[f64];
+    fn time_from_start(&self) -> Duration;
+}
+
+/// A `trajectory_msgs/JointTrajectory`, see the [module docs](self) for implementing it
+pub trait JointTrajectory {
+    type Point: TrajectoryPoint;
+    fn joint_names(&self) -> &[String];
+    fn points(&self) -> &[Self::Point];
+}
+
+/// A `sensor_msgs/JointState`, see the [module docs](self) for implementing it
+pub trait JointState: Stamped {
+    fn names(&self) -> &[String];
+    fn positions(&self) -> &[f64];
+}
+
+/// Reasons a trajectory is rejected, matching the checks controllers make before executing one
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum TrajectoryError {
+    #[error("Trajectory has no joints")]
+    NoJoints,
+    #[error("Trajectory has no points")]
+    NoPoints,
+    #[error("Joint {0} appears more than once")]
+    DuplicateJoint(String),
+    #[error("Joint {0} is missing")]
+    MissingJoint(String),
+    #[error("Point {point} has {actual} {field} for {expected} joints")]
+    WrongLength {
+        point: usize,
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("Point {point} is not later than the point before it")]
+    NotIncreasing { point: usize },
+}
+
+/// Checks `trajectory` is well formed: it has joints, each named once, and points which give a position for
+/// every joint, give velocities, accelerations and efforts either for every joint or not at all,
+/// and whose `time_from_start` strictly increases.
+pub fn validate(trajectory: &impl JointTrajectory) -> Result<(), TrajectoryError> {
+    let joint_names = trajectory.joint_names();
+    if joint_names.is_empty() {
+        return Err(TrajectoryError::NoJoints);
+    }
+    for (index, name) in joint_names.iter().enumerate() {
+        if joint_names[..index].contains(name) {
+            return Err(TrajectoryError::DuplicateJoint(name.clone()));
+        }
+    }
+    let mut previous_time = None;
+    for (index, point) in trajectory.points().iter().enumerate() {
+        let fields = [
+            ("positions", point.positions(), true),
+            ("velocities", point.velocities(), false),
+            ("accelerations", point.accelerations(), false),
+            ("efforts", point.effort(), false),
+        ];
+        for (field, values, required) in fields {
+            if values.len() != joint_names.len() && (required || !values.is_empty()) {
+                return Err(TrajectoryError::WrongLength {
+                    point: index,
+                    field,
+                    expected: joint_names.len(),
+                    actual: values.len(),
+                });
+            }
+        }
+        let time = point.time_from_start();
+        if previous_time.is_some_and(|previous| time <= previous) {
+            return Err(TrajectoryError::NotIncreasing { point: index });
+        }
+        previous_time = Some(time);
+    }
+    Ok(())
+}
+
+/// Reorders `values` given for joints `from` into the order of joints `to`,
+/// e.g. to build the positions of a trajectory point in the order a controller expects.
+/// Joints of `from` which are not in `to` are left out, and every joint of `to` must be in `from`.
+pub fn reorder<T: Clone>(
+    values: &[T],
+    from: &[String],
+    to: &[String],
+) -> Result<Vec<T>, TrajectoryError> {
+    to.iter()
+        .map(|name| {
+            from.iter()
+                .position(|from_name| from_name == name)
+                .and_then(|index| values.get(index).cloned())
+                .ok_or_else(|| TrajectoryError::MissingJoint(name.clone()))
+        })
+        .collect()
+}
+
+/// Compares joint state feedback against a commanded trajectory, by interpolating the position each joint
+/// should be at when the feedback was stamped.
+/// ```ignore
+/// let tracker = TrajectoryTracker::new(&trajectory, start)?;
+/// while let Ok(state) = joint_states.next().await {
+///     let sample = tracker.compare(&state)?;
+///     if sample.max_error() > 0.1 {
+///         log::warn!("Arm is lagging behind its trajectory: {:?}", sample.error);
+///     }
+///     if sample.finished {
+///         break;
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TrajectoryTracker {
+    joint_names: Vec<String>,
+    start: Duration,
+    times: Vec<Duration>,
+    positions: Vec<Vec<f64>>,
+}
+
+/// How far joint state feedback is from the commanded trajectory, in the trajectory's joint order
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackingSample {
+    /// Time since the trajectory started when the feedback was stamped, zero if stamped before it started
+    pub time_from_start: Duration,
+    /// Where the joints should be
+    pub desired: Vec<f64>,
+    /// Where the joints are
+    pub actual: Vec<f64>,
+    /// `actual - desired` for each joint
+    pub error: Vec<f64>,
+    /// Whether the time of the last point of the trajectory has passed
+    pub finished: bool,
+}
+
+impl TrackingSample {
+    /// The largest absolute error of any joint
+    pub fn max_error(&self) -> f64 {
+        self.error
+            .iter()
+            .fold(0.0, |max, error| error.abs().max(max))
+    }
+}
+
+impl TrajectoryTracker {
+    /// Tracks `trajectory` started at `start`, the stamp of its header or the time it was sent if that is zero,
+    /// failing if it does not pass [validate] or has no points
+    pub fn new(trajectory: &impl JointTrajectory, start: Time) -> Result<Self, TrajectoryError> {
+        validate(trajectory)?;
+        if trajectory.points().is_empty() {
+            return Err(TrajectoryError::NoPoints);
+        }
+        Ok(Self {
+            joint_names: trajectory.joint_names().to_vec(),
+            start: to_duration(&start),
+            times: trajectory
+                .points()
+                .iter()
+                .map(|point| point.time_from_start())
+                .collect(),
+            positions: trajectory
+                .points()
+                .iter()
+                .map(|point| point.positions().to_vec())
+                .collect(),
+        })
+    }
+
+    pub fn joint_names(&self) -> &[String] {
+        &self.joint_names
+    }
+
+    /// The time from the start of the trajectory to its last point
+    pub fn duration(&self) -> Duration {
+        self.times[self.times.len() - 1]
+    }
+
+    /// The positions the joints should be at `time_from_start`, interpolated linearly between points.
+    /// The first point is held before its time and the last point after its time.
+    pub fn desired(&self, time_from_start: Duration) -> Vec<f64> {
+        let next = self.times.partition_point(|time| *time <= time_from_start);
+        if next == 0 {
+            return self.positions[0].clone();
+        }
+        if next == self.times.len() {
+            return self.positions[next - 1].clone();
+        }
+        let (from, to) = (&self.positions[next - 1], &self.positions[next]);
+        let segment = (self.times[next] - self.times[next - 1]).as_secs_f64();
+        let fraction = (time_from_start - self.times[next - 1]).as_secs_f64() / segment;
+        from.iter()
+            .zip(to)
+            .map(|(from, to)| from + (to - from) * fraction)
+            .collect()
+    }
+
+    /// Compares `state` against where the joints should be when it was stamped.
+    /// Fails if `state` does not report the position of every joint of the trajectory.
+    pub fn compare(&self, state: &impl JointState) -> Result<TrackingSample, TrajectoryError> {
+        let actual = reorder(state.positions(), state.names(), &self.joint_names)?;
+        let time_from_start = to_duration(&state.stamp()).saturating_sub(self.start);
+        let desired = self.desired(time_from_start);
+        let error = actual
+            .iter()
+            .zip(&desired)
+            .map(|(actual, desired)| actual - desired)
+            .collect();
+        Ok(TrackingSample {
+            time_from_start,
+            desired,
+            actual,
+            error,
+            finished: time_from_start >= self.duration(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        reorder, validate, JointState, JointTrajectory, TrajectoryError, TrajectoryPoint,
+        TrajectoryTracker,
+    };
+    use crate::Stamped;
+    use roslibrust_codegen::Time;
+    use std::time::Duration;
+
+    struct Point {
+        positions: Vec<f64>,
+        velocities: Vec<f64>,
+        secs: u64,
+    }
+
+    impl TrajectoryPoint for Point {
+        fn positions(&self) -> &[f64] {
+            &self.positions
+        }
+        fn velocities(&self) -> &[f64] {
+            &self.velocities
+        }
+        fn accelerations(&self) -> &[f64] {
+            &[]
+        }
+        fn effort(&self) -> &[f64] {
+            &[]
+        }
+        fn time_from_start(&self) -> Duration {
+            Duration::from_secs(self.secs)
+        }
+    }
+
+    struct Trajectory {
+        joint_names: Vec<String>,
+        points: Vec<Point>,
+    }
+
+    impl JointTrajectory for Trajectory {
+        type Point = Point;
+        fn joint_names(&self) -> &[String] {
+            &self.joint_names
+        }
+        fn points(&self) -> &[Point] {
+            &self.points
+        }
+    }
+
+    struct State {
+        names: Vec<String>,
+        positions: Vec<f64>,
+        secs: u32,
+    }
+
+    impl Stamped for State {
+        fn stamp(&self) -> Time {
+            Time {
+                secs: self.secs,
+                nsecs: 0,
+            }
+        }
+    }
+
+    impl JointState for State {
+        fn names(&self) -> &[String] {
+            &self.names
+        }
+        fn positions(&self) -> &[f64] {
+            &self.positions
+        }
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn point(positions: &[f64], secs: u64) -> Point {
+        Point {
+            positions: positions.to_vec(),
+            velocities: vec![],
+            secs,
+        }
+    }
+
+    #[test]
+    fn malformed_trajectories_are_rejected() {
+        let valid = Trajectory {
+            joint_names: names(&["shoulder", "elbow"]),
+            points: vec![point(&[0.0, 0.0], 1), point(&[1.0, 2.0], 2)],
+        };
+        assert_eq!(validate(&valid), Ok(()));
+
+        let duplicate = Trajectory {
+            joint_names: names(&["shoulder", "shoulder"]),
+            points: vec![],
+        };
+        assert_eq!(
+            validate(&duplicate),
+            Err(TrajectoryError::DuplicateJoint("shoulder".to_owned()))
+        );
+
+        let mut short = Trajectory {
+            joint_names: names(&["shoulder", "elbow"]),
+            points: vec![point(&[0.0, 0.0], 1), point(&[1.0, 2.0], 2)],
+        };
+        short.points[1].velocities = vec![0.5];
+        assert_eq!(
+            validate(&short),
+            Err(TrajectoryError::WrongLength {
+                point: 1,
+                field: "velocities",
+                expected: 2,
+                actual: 1
+            })
+        );
+
+        let unordered = Trajectory {
+            joint_names: names(&["shoulder", "elbow"]),
+            points: vec![point(&[0.0, 0.0], 2), point(&[1.0, 2.0], 2)],
+        };
+        assert_eq!(
+            validate(&unordered),
+            Err(TrajectoryError::NotIncreasing { point: 1 })
+        );
+    }
+
+    #[test]
+    fn values_are_reordered_by_joint_name() {
+        let from = names(&["wrist", "shoulder", "elbow"]);
+        let to = names(&["shoulder", "elbow"]);
+        assert_eq!(reorder(&[3.0, 1.0, 2.0], &from, &to), Ok(vec![1.0, 2.0]));
+        assert_eq!(
+            reorder(&[1.0, 2.0], &to, &from),
+            Err(TrajectoryError::MissingJoint("wrist".to_owned()))
+        );
+    }
+
+    #[test]
+    fn feedback_is_compared_against_interpolated_positions() {
+        let trajectory = Trajectory {
+            joint_names: names(&["shoulder", "elbow"]),
+            points: vec![point(&[0.0, 0.0], 2), point(&[1.0, -2.0], 4)],
+        };
+        let tracker = TrajectoryTracker::new(
+            &trajectory,
+            Time {
+                secs: 100,
+                nsecs: 0,
+            },
+        )
+        .unwrap();
+
+        // Feedback reports joints in its own order
+        let sample = tracker
+            .compare(&State {
+                names: names(&["elbow", "shoulder"]),
+                positions: vec![-1.0, 0.25],
+                secs: 103,
+            })
+            .unwrap();
+        assert_eq!(sample.time_from_start, Duration::from_secs(3));
+        assert_eq!(sample.desired, vec![0.5, -1.0]);
+        assert_eq!(sample.error, vec![-0.25, 0.0]);
+        assert_eq!(sample.max_error(), 0.25);
+        assert!(!sample.finished);
+
+        // Before the start the first point is held, after the end the last point
+        assert_eq!(tracker.desired(Duration::ZERO), vec![0.0, 0.0]);
+        let sample = tracker
+            .compare(&State {
+                names: names(&["shoulder", "elbow"]),
+                positions: vec![1.0, -2.0],
+                secs: 110,
+            })
+            .unwrap();
+        assert_eq!(sample.desired, vec![1.0, -2.0]);
+        assert!(sample.finished);
+    }
+}
@@ -0,0 +1,249 @@
+//! Fetching and querying the robot description, the URDF model of a robot, as used by controllers and visualizers.
+//! Parsing is done by [urdf_rs], whose types are re-exported for working with the model.
+//!
+//! ROS1 keeps the robot description as XML in the `robot_description` parameter,
+//! while ROS2's robot_state_publisher publishes it latched on the `/robot_description` topic:
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! use roslibrust::urdf::RobotModel;
+//! let client = roslibrust::ClientHandle::new("ws://localhost:9090").await?;
+//! let model = RobotModel::from_topic(&client, "/robot_description").await?;
+//! for joint in model.movable_joints() {
+//!     println!("{} moves {}", joint.name, joint.child.link);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "rosbridge")]
+use crate::{ClientHandle, RosLibRustError, RosLibRustResult};
+#[cfg(feature = "rosbridge")]
+use roslibrust_codegen::RosMessageType;
+#[cfg(feature = "rosbridge")]
+use serde::{Deserialize, Serialize};
+
+pub use urdf_rs::{Joint, JointType, Link, Robot};
+
+/// Errors building a [RobotModel] from a robot description
+#[derive(thiserror::Error, Debug)]
+pub enum RobotModelError {
+    #[error("Failed to parse robot description: {0}")]
+    Parse(#[from] urdf_rs::UrdfError),
+    #[error("Joint {joint} refers to link {link} which the robot description does not contain")]
+    UnknownLink { joint: String, link: String },
+}
+
+/// A robot description parsed from URDF, with queries for walking its tree of links and joints
+#[derive(Clone, Debug)]
+pub struct RobotModel {
+    robot: Robot,
+}
+
+impl RobotModel {
+    /// Parses the URDF XML of a robot description.
+    /// Fails if the XML is not valid URDF or a joint refers to a link which is not part of the description.
+    pub fn parse(xml: &str) -> Result<Self, RobotModelError> {
+        let robot = urdf_rs::read_from_string(xml)?;
+        for joint in &robot.joints {
+            for link in [&joint.parent.link, &joint.child.link] {
+                if !robot.links.iter().any(|existing| &existing.name == link) {
+                    return Err(RobotModelError::UnknownLink {
+                        joint: joint.name.clone(),
+                        link: link.clone(),
+                    });
+                }
+            }
+        }
+        Ok(Self { robot })
+    }
+
+    /// Fetches and parses the robot description held in the parameter `key` of the parameter server,
+    /// usually `robot_description`.
+    /// Like all names given to the node, `key` is resolved with [crate::NodeHandle::resolve_name].
+    #[cfg(feature = "ros1")]
+    pub async fn from_param(
+        nh: &crate::NodeHandle,
+        key: &str,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let xml: String = nh.get_param(key).await?;
+        Ok(Self::parse(&xml)?)
+    }
+
+    /// Waits for the robot description to be published on `topic` through rosbridge, usually `/robot_description`,
+    /// and parses it.
+    #[cfg(feature = "rosbridge")]
+    pub async fn from_topic(client: &ClientHandle, topic: &str) -> RosLibRustResult<Self> {
+        let subscriber = client.subscribe::<RobotDescription>(topic).await?;
+        let description = subscriber.next().await;
+        Self::parse(&description.data).map_err(|err| RosLibRustError::Unexpected(err.into()))
+    }
+
+    /// The model as parsed by [urdf_rs]
+    pub fn robot(&self) -> &Robot {
+        &self.robot
+    }
+
+    pub fn name(&self) -> &str {
+        &self.robot.name
+    }
+
+    pub fn links(&self) -> &[Link] {
+        &self.robot.links
+    }
+
+    pub fn joints(&self) -> &[Joint] {
+        &self.robot.joints
+    }
+
+    pub fn link(&self, name: &str) -> Option<&Link> {
+        self.robot.links.iter().find(|link| link.name == name)
+    }
+
+    pub fn joint(&self, name: &str) -> Option<&Joint> {
+        self.robot.joints.iter().find(|joint| joint.name == name)
+    }
+
+    /// The link at the base of the tree, which is not the child of any joint
+    pub fn root_link(&self) -> Option<&Link> {
+        self.robot
+            .links
+            .iter()
+            .find(|link| self.parent_joint(&link.name).is_none())
+    }
+
+    /// The joint connecting `link` to its parent, None for the root link
+    pub fn parent_joint(&self, link: &str) -> Option<&Joint> {
+        self.robot
+            .joints
+            .iter()
+            .find(|joint| joint.child.link == link)
+    }
+
+    /// The joints connecting `link` to its children
+    pub fn child_joints<'a>(&'a self, link: &'a str) -> impl Iterator<Item = &'a Joint> + 'a {
+        self.robot
+            .joints
+            .iter()
+            .filter(move |joint| joint.parent.link == link)
+    }
+
+    /// Every joint which is not fixed, i.e. the joints a controller commands
+    pub fn movable_joints(&self) -> impl Iterator<Item = &Joint> {
+        self.robot
+            .joints
+            .iter()
+            .filter(|joint| !matches!(joint.joint_type, JointType::Fixed))
+    }
+
+    /// The joints connecting link `root` down to link `tip`, in order from `root`,
+    /// e.g. the joints of an arm from its base to its end effector.
+    /// Returns None if `tip` does not descend from `root`.
+    pub fn chain(&self, root: &str, tip: &str) -> Option<Vec<&Joint>> {
+        let mut chain = vec![];
+        let mut link = tip;
+        while link != root {
+            // A malformed description could contain a loop, which no chain can be longer than
+            if chain.len() == self.robot.joints.len() {
+                return None;
+            }
+            let joint = self.parent_joint(link)?;
+            chain.push(joint);
+            link = joint.parent.link.as_str();
+        }
+        chain.reverse();
+        Some(chain)
+    }
+}
+
+// The std_msgs/String the robot description is published as, defined here to not depend on generated messages
+#[cfg(feature = "rosbridge")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct RobotDescription {
+    data: String,
+}
+
+#[cfg(feature = "rosbridge")]
+impl RosMessageType for RobotDescription {
+    const ROS_TYPE_NAME: &'static str = "std_msgs/String";
+    const MD5SUM: &'static str = "992ce8a1687cec8c8bd883ec73ca41d1";
+    const DEFINITION: &'static str = "string data";
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RobotModel, RobotModelError};
+
+    const ARM: &str = r#"<?xml version="1.0"?>
+<robot name="arm">
+  <link name="base_link"/>
+  <link name="shoulder"/>
+  <link name="forearm"/>
+  <link name="gripper"/>
+  <link name="camera"/>
+  <joint name="shoulder_pan" type="revolute">
+    <parent link="base_link"/>
+    <child link="shoulder"/>
+    <axis xyz="0 0 1"/>
+    <limit lower="-3.14" upper="3.14" effort="10" velocity="1"/>
+  </joint>
+  <joint name="elbow" type="revolute">
+    <parent link="shoulder"/>
+    <child link="forearm"/>
+    <axis xyz="0 1 0"/>
+    <limit lower="-2.0" upper="2.0" effort="10" velocity="1"/>
+  </joint>
+  <joint name="wrist" type="continuous">
+    <parent link="forearm"/>
+    <child link="gripper"/>
+  </joint>
+  <joint name="camera_mount" type="fixed">
+    <parent link="base_link"/>
+    <child link="camera"/>
+  </joint>
+</robot>
+"#;
+
+    #[test]
+    fn queries_walk_the_link_tree() {
+        let model = RobotModel::parse(ARM).unwrap();
+        assert_eq!(model.name(), "arm");
+        assert_eq!(model.root_link().unwrap().name, "base_link");
+        assert_eq!(model.parent_joint("forearm").unwrap().name, "elbow");
+        assert!(model.parent_joint("base_link").is_none());
+
+        let children: Vec<_> = model
+            .child_joints("base_link")
+            .map(|joint| joint.name.as_str())
+            .collect();
+        assert_eq!(children, vec!["shoulder_pan", "camera_mount"]);
+        let movable: Vec<_> = model
+            .movable_joints()
+            .map(|joint| joint.name.as_str())
+            .collect();
+        assert_eq!(movable, vec!["shoulder_pan", "elbow", "wrist"]);
+        assert_eq!(model.joint("elbow").unwrap().limit.upper, 2.0);
+
+        let chain: Vec<_> = model
+            .chain("base_link", "gripper")
+            .unwrap()
+            .into_iter()
+            .map(|joint| joint.name.as_str())
+            .collect();
+        assert_eq!(chain, vec!["shoulder_pan", "elbow", "wrist"]);
+        assert!(model.chain("base_link", "base_link").unwrap().is_empty());
+        assert!(model.chain("shoulder", "camera").is_none());
+    }
+
+    #[test]
+    fn joints_must_refer_to_known_links() {
+        let broken = ARM.replace(r#"<link name="camera"/>"#, "");
+        match RobotModel::parse(&broken) {
+            Err(RobotModelError::UnknownLink { joint, link }) => {
+                assert_eq!(joint, "camera_mount");
+                assert_eq!(link, "camera");
+            }
+            other => panic!("Expected an unknown link error, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Conformance tests checking roslibrust's native ros1 client interoperates with roscpp and rospy.
+//!
+//! Each implementation runs a counterpart node from `docker/conformance` under `/conformance/<implementation>`,
+//! which echoes `echo_in` back out on `echo_out`, latches a message on `latched` and serves `set_bool`.
+//! Every case is run against every implementation, and the results are written out as a markdown report
+//! to `CONFORMANCE_REPORT`, by default `target/conformance_report.md`.
+//! `CONFORMANCE_IMPLEMENTATIONS` limits the run to a comma separated list of implementations.
+
+#[cfg(feature = "conformance")]
+mod conformance {
+    use roslibrust::NodeHandle;
+    use roslibrust_codegen::{RosMessageType, RosServiceType};
+    use std::time::Duration;
+
+    roslibrust_codegen_macro::find_and_generate_ros_messages!("assets/ros1_common_interfaces");
+
+    type CaseResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    const MASTER_URI: &str = "http://localhost:11311";
+    const IMPLEMENTATIONS: &[&str] = &["roscpp", "rospy"];
+    // Counterparts may take a while to connect back to us, so cases get generous time before being failed
+    const CASE_TIMEOUT: Duration = Duration::from_secs(15);
+    // Comfortably larger than any of the buffers messages pass through on either side
+    const LARGE_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+    #[derive(Clone, Copy, Debug)]
+    enum Case {
+        PubSub,
+        LargeMessage,
+        Latched,
+        Md5sum,
+        PersistentService,
+    }
+
+    impl Case {
+        const ALL: &'static [Case] = &[
+            Case::PubSub,
+            Case::LargeMessage,
+            Case::Latched,
+            Case::Md5sum,
+            Case::PersistentService,
+        ];
+
+        fn name(&self) -> &'static str {
+            match self {
+                Case::PubSub => "pub/sub round trip",
+                Case::LargeMessage => "large message round trip",
+                Case::Latched => "latched message on late subscription",
+                Case::Md5sum => "md5sum and type negotiation",
+                Case::PersistentService => "persistent service calls",
+            }
+        }
+
+        async fn run(&self, implementation: &str) -> CaseResult {
+            // Each case gets its own node, so nothing a case leaves connected affects the next
+            let node_name = format!("/conformance_{implementation}_{self:?}").to_lowercase();
+            let nh = NodeHandle::new(MASTER_URI, &node_name).await?;
+            let namespace = format!("/conformance/{implementation}");
+            match self {
+                Case::PubSub => {
+                    let data = format!("hello {implementation}");
+                    expect_eq(&round_trip(&nh, &namespace, data.clone()).await?, &data)
+                }
+                Case::LargeMessage => {
+                    let data = "x".repeat(LARGE_MESSAGE_SIZE);
+                    let echoed = round_trip(&nh, &namespace, data.clone()).await?;
+                    expect_eq(&echoed.len(), &data.len())?;
+                    expect_eq(&echoed, &data)
+                }
+                Case::Latched => {
+                    let mut subscriber = nh
+                        .subscribe::<std_msgs::String>(&format!("{namespace}/latched"), 1)
+                        .await?;
+                    let (msg, info) = subscriber.next_with_info().await.map_err(stringify)?;
+                    expect_eq(&msg.data, &format!("latched by {implementation}"))?;
+                    expect_eq(&info.latched, &true)
+                }
+                Case::Md5sum => {
+                    let topic = format!("{namespace}/latched");
+                    let mut subscriber = nh.subscribe_any(&topic, 1).await?;
+                    let msg = subscriber.next().await.map_err(stringify)?;
+                    expect_eq(
+                        &msg.publisher.topic_type.as_str(),
+                        &std_msgs::String::ROS_TYPE_NAME,
+                    )?;
+                    expect_eq(&msg.publisher.md5sum.as_str(), &std_msgs::String::MD5SUM)?;
+                    expect_eq(&msg.publisher.latching, &true)?;
+
+                    // A subscriber of a different type must be refused by the counterpart
+                    let mismatched =
+                        NodeHandle::new(MASTER_URI, &format!("{node_name}_mismatched")).await?;
+                    let mut subscriber = mismatched.subscribe::<std_msgs::Int32>(&topic, 1).await?;
+                    match tokio::time::timeout(Duration::from_secs(2), subscriber.next()).await {
+                        Err(_) => Ok(()),
+                        Ok(msg) => Err(format!(
+                            "Subscriber with a mismatched md5sum received {msg:?}"
+                        )
+                        .into()),
+                    }
+                }
+                Case::PersistentService => {
+                    let service = format!("{namespace}/set_bool");
+                    nh.wait_for_service(&service, Some(CASE_TIMEOUT)).await?;
+                    expect_eq(
+                        &nh.service_client::<std_srvs::SetBool>(&service, true)
+                            .probe()
+                            .await?
+                            .md5sum
+                            .as_str(),
+                        &std_srvs::SetBool::MD5SUM,
+                    )?;
+                    // Every call after the first reuses the connection the first call opened
+                    let client = nh.service_client::<std_srvs::SetBool>(&service, true);
+                    for data in [true, false, true] {
+                        let response = client.call(&std_srvs::SetBoolRequest { data }).await?;
+                        expect_eq(&response.success, &data)?;
+                        expect_eq(&response.message, &implementation.to_owned())?;
+                    }
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Publishes `data` to the counterpart's `echo_in` until it is received back on `echo_out`.
+    /// Publishing is repeated as the counterpart's subscription and its connection back to us are made
+    /// independently, so either may miss the first few messages.
+    async fn round_trip(
+        nh: &NodeHandle,
+        namespace: &str,
+        data: String,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let publisher = nh
+            .advertise::<std_msgs::String>(&format!("{namespace}/echo_in"), 1)
+            .await?;
+        let mut subscriber = nh
+            .subscribe::<std_msgs::String>(&format!("{namespace}/echo_out"), 1)
+            .await?;
+        let msg = std_msgs::String { data };
+        let mut interval = tokio::time::interval(Duration::from_millis(250));
+        loop {
+            tokio::select! {
+                echoed = subscriber.next() => return Ok(echoed.map_err(stringify)?.data),
+                _ = interval.tick() => publisher.publish(&msg).await?,
+            }
+        }
+    }
+
+    fn expect_eq<T: PartialEq + std::fmt::Debug>(
+        actual: &T,
+        expected: &T,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if actual == expected {
+            return Ok(());
+        }
+        let describe = |value: &T| {
+            let mut description = format!("{value:?}");
+            // Keeps the report readable when a large message doesn't match
+            if description.len() > 80 {
+                description.truncate(80);
+                description.push_str("...");
+            }
+            description
+        };
+        Err(format!("expected {}, got {}", describe(expected), describe(actual)).into())
+    }
+
+    // Subscriber errors aren't Send, so are carried over as their description
+    fn stringify(err: Box<dyn std::error::Error>) -> Box<dyn std::error::Error + Send + Sync> {
+        err.to_string().into()
+    }
+
+    /// Renders the results as a markdown table with a row per case and a column per implementation
+    fn report(implementations: &[String], results: &[(Case, Vec<CaseResult>)]) -> String {
+        let mut report = String::from("# roslibrust conformance report\n\n| Case |");
+        for implementation in implementations {
+            report.push_str(&format!(" {implementation} |"));
+        }
+        report.push_str("\n|---|");
+        report.push_str(&"---|".repeat(implementations.len()));
+        for (case, outcomes) in results {
+            report.push_str(&format!("\n| {} |", case.name()));
+            for outcome in outcomes {
+                match outcome {
+                    Ok(()) => report.push_str(" pass |"),
+                    Err(err) => report
+                        .push_str(&format!(" FAIL: {} |", err.to_string().replace('|', "\\|"))),
+                }
+            }
+        }
+        report.push('\n');
+        report
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn conformance_matrix() {
+        let implementations: Vec<String> = match std::env::var("CONFORMANCE_IMPLEMENTATIONS") {
+            Ok(implementations) => implementations
+                .split(',')
+                .map(|implementation| implementation.trim().to_owned())
+                .filter(|implementation| !implementation.is_empty())
+                .collect(),
+            Err(_) => IMPLEMENTATIONS
+                .iter()
+                .map(|implementation| implementation.to_string())
+                .collect(),
+        };
+
+        let mut results = vec![];
+        for case in Case::ALL {
+            let mut outcomes = vec![];
+            for implementation in &implementations {
+                log::info!("Running {} against {implementation}", case.name());
+                let outcome = tokio::time::timeout(CASE_TIMEOUT, case.run(implementation))
+                    .await
+                    .unwrap_or_else(|_| Err(format!("timed out after {CASE_TIMEOUT:?}").into()));
+                if let Err(err) = &outcome {
+                    log::error!("{} against {implementation} failed: {err}", case.name());
+                }
+                outcomes.push(outcome);
+            }
+            results.push((*case, outcomes));
+        }
+
+        let report = report(&implementations, &results);
+        let path = std::env::var("CONFORMANCE_REPORT").unwrap_or_else(|_| {
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../target/conformance_report.md"
+            )
+            .to_owned()
+        });
+        std::fs::write(&path, &report).unwrap();
+        log::info!("Wrote conformance report to {path}:\n{report}");
+
+        let failures = results
+            .iter()
+            .flat_map(|(_, outcomes)| outcomes)
+            .filter(|outcome| outcome.is_err())
+            .count();
+        assert_eq!(
+            failures, 0,
+            "{failures} conformance cases failed, see {path}"
+        );
+    }
+}
@@ -0,0 +1,365 @@
+//! Snapshot of the public surface of [roslibrust::prelude], guarding it against accidental breaking changes.
+//!
+//! For every item the prelude re-exports, its definition, public methods and trait implementations are read from
+//! the source and compared against `tests/public_api.txt`. The snapshot covers every backend regardless of the
+//! features the test is built with.
+//! After a deliberate change to the prelude's surface, regenerate the snapshot with
+//! `UPDATE_PUBLIC_API=1 cargo test -p roslibrust --test public_api` and commit it along with the change.
+
+use quote::ToTokens;
+use std::path::{Path, PathBuf};
+
+const SNAPSHOT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/public_api.txt");
+const PRELUDE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/prelude.rs");
+
+/// An item re-exported by the prelude, e.g. `crate::ros1::NodeHandle`
+struct Reexport {
+    path: Vec<String>,
+    name: String,
+    cfg: Option<String>,
+}
+
+fn collect_reexports(
+    tree: &syn::UseTree,
+    path: &mut Vec<String>,
+    cfg: &Option<String>,
+    out: &mut Vec<Reexport>,
+) {
+    match tree {
+        syn::UseTree::Path(use_path) => {
+            path.push(use_path.ident.to_string());
+            collect_reexports(&use_path.tree, path, cfg, out);
+            path.pop();
+        }
+        syn::UseTree::Name(name) => out.push(Reexport {
+            path: path.clone(),
+            name: name.ident.to_string(),
+            cfg: cfg.clone(),
+        }),
+        syn::UseTree::Group(group) => {
+            for tree in &group.items {
+                collect_reexports(tree, path, cfg, out);
+            }
+        }
+        syn::UseTree::Rename(_) | syn::UseTree::Glob(_) => {
+            panic!(
+                "The prelude re-exports items by name, found {}",
+                render(tree)
+            )
+        }
+    }
+}
+
+fn prelude_reexports() -> Vec<Reexport> {
+    let prelude = syn::parse_file(&std::fs::read_to_string(PRELUDE_PATH).unwrap()).unwrap();
+    let mut reexports = vec![];
+    for item in &prelude.items {
+        let syn::Item::Use(item) = item else {
+            continue;
+        };
+        if !is_public(&item.vis) {
+            continue;
+        }
+        let cfg = item
+            .attrs
+            .iter()
+            .find(|attr| attr.path.is_ident("cfg"))
+            .map(render);
+        collect_reexports(&item.tree, &mut vec![], &cfg, &mut reexports);
+    }
+    reexports
+}
+
+/// The source files an item re-exported from `path` may be defined and implemented in
+fn source_files(path: &[String]) -> Vec<PathBuf> {
+    let (mut root, modules) = match path.split_first() {
+        Some((krate, modules)) if krate == "crate" => (
+            PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/src")),
+            modules,
+        ),
+        Some((krate, modules)) => (
+            PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/.."))
+                .join(krate)
+                .join("src"),
+            modules,
+        ),
+        None => panic!("The prelude re-exports items by path"),
+    };
+    for module in modules {
+        if root.join(module).is_dir() {
+            root = root.join(module);
+        } else {
+            return vec![root.join(format!("{module}.rs"))];
+        }
+    }
+    let mut files = vec![];
+    collect_files(&root, &mut files);
+    files.sort();
+    files
+}
+
+fn collect_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_files(&path, out);
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            out.push(path);
+        }
+    }
+}
+
+fn is_public(vis: &syn::Visibility) -> bool {
+    matches!(vis, syn::Visibility::Public(_))
+}
+
+fn is_hidden_or_test(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        let tokens = attr.tokens.to_string();
+        (attr.path.is_ident("doc") && tokens.contains("hidden"))
+            || (attr.path.is_ident("cfg") && tokens.contains("test"))
+    })
+}
+
+fn derives(attrs: &[syn::Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("derive"))
+        .map(|attr| format!("{} ", render(attr)))
+        .collect()
+}
+
+/// Renders tokens on one line, spaced the way rustfmt would for the signatures found in the snapshot
+fn render(tokens: &impl ToTokens) -> String {
+    let mut rendered = tokens.to_token_stream().to_string();
+    for (from, to) in [
+        (" :: ", "::"),
+        (":: ", "::"),
+        (" < ", "<"),
+        (" <", "<"),
+        ("< ", "<"),
+        (" >", ">"),
+        ("& ", "&"),
+        (" ,", ","),
+        (" (", "("),
+        ("( ", "("),
+        (" )", ")"),
+        (" :", ":"),
+        ("# [", "#["),
+        ("[ ", "["),
+        (" ]", "]"),
+        (" ;", ";"),
+        (",)", ")"),
+        (", }", " }"),
+        ("->(", "-> ("),
+        ("=(", "= ("),
+        // Restores the space before paths from the crate root, e.g. `Clone, ::serde::Serialize`
+        (",::", ", ::"),
+    ] {
+        rendered = rendered.replace(from, to);
+    }
+    rendered.trim_end_matches(',').to_owned()
+}
+
+/// Renders the signature of a method, leaving out whether its arguments are bound mutably
+/// as that is private to the method
+fn render_signature(sig: &syn::Signature) -> String {
+    let mut sig = sig.clone();
+    for input in sig.inputs.iter_mut() {
+        match input {
+            // `&mut self` is part of the signature, only `mut self` binds mutably
+            syn::FnArg::Receiver(receiver) if receiver.reference.is_none() => {
+                receiver.mutability = None
+            }
+            syn::FnArg::Receiver(_) => {}
+            syn::FnArg::Typed(typed) => {
+                if let syn::Pat::Ident(pat) = typed.pat.as_mut() {
+                    pat.mutability = None;
+                }
+            }
+        }
+    }
+    render(&sig)
+}
+
+fn self_type_name(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// The surface of `name` found in `items`: its definition, its public inherent methods and constants,
+/// and the traits implemented for it
+fn describe(
+    name: &str,
+    items: &[syn::Item],
+    definitions: &mut Vec<String>,
+    members: &mut Vec<String>,
+) {
+    for item in items {
+        match item {
+            syn::Item::Struct(item) if item.ident == name && is_public(&item.vis) => {
+                definitions.push(format!(
+                    "{}pub struct {name}{} {}",
+                    derives(&item.attrs),
+                    render(&item.generics),
+                    render(&item.generics.where_clause)
+                ));
+                for field in item.fields.iter().filter(|field| is_public(&field.vis)) {
+                    let field_name = field
+                        .ident
+                        .as_ref()
+                        .map(|ident| format!("{ident}: "))
+                        .unwrap_or_default();
+                    members.push(format!("pub {field_name}{}", render(&field.ty)));
+                }
+            }
+            syn::Item::Enum(item) if item.ident == name && is_public(&item.vis) => {
+                definitions.push(format!(
+                    "{}pub enum {name}{} {}",
+                    derives(&item.attrs),
+                    render(&item.generics),
+                    render(&item.generics.where_clause)
+                ));
+                for variant in &item.variants {
+                    let mut variant = variant.clone();
+                    variant.attrs.clear();
+                    for field in variant.fields.iter_mut() {
+                        field.attrs.clear();
+                    }
+                    members.push(format!("variant {}", render(&variant)));
+                }
+            }
+            syn::Item::Trait(item) if item.ident == name && is_public(&item.vis) => {
+                let supertraits = match item.supertraits.is_empty() {
+                    true => String::new(),
+                    false => format!(": {}", render(&item.supertraits)),
+                };
+                definitions.push(format!(
+                    "pub trait {name}{}{supertraits} {}",
+                    render(&item.generics),
+                    render(&item.generics.where_clause)
+                ));
+                for trait_item in &item.items {
+                    let member = match trait_item {
+                        syn::TraitItem::Const(item) => {
+                            format!("const {}: {}", item.ident, render(&item.ty))
+                        }
+                        syn::TraitItem::Method(item) => render_signature(&item.sig),
+                        syn::TraitItem::Type(item) => {
+                            format!("type {}: {}", item.ident, render(&item.bounds))
+                        }
+                        _ => continue,
+                    };
+                    members.push(member);
+                }
+            }
+            syn::Item::Type(item) if item.ident == name && is_public(&item.vis) => {
+                definitions.push(format!(
+                    "pub type {name}{} = {}",
+                    render(&item.generics),
+                    render(&item.ty)
+                ));
+            }
+            syn::Item::Impl(item)
+                if self_type_name(&item.self_ty).as_deref() == Some(name)
+                    && !is_hidden_or_test(&item.attrs) =>
+            {
+                if let Some((_, trait_path, _)) = &item.trait_ {
+                    members.push(format!(
+                        "impl{} {} for {} {}",
+                        render(&item.generics),
+                        render(trait_path),
+                        render(&item.self_ty),
+                        render(&item.generics.where_clause)
+                    ));
+                    continue;
+                }
+                for impl_item in &item.items {
+                    match impl_item {
+                        syn::ImplItem::Method(method)
+                            if is_public(&method.vis) && !is_hidden_or_test(&method.attrs) =>
+                        {
+                            members.push(format!("pub {}", render_signature(&method.sig)))
+                        }
+                        syn::ImplItem::Const(constant)
+                            if is_public(&constant.vis) && !is_hidden_or_test(&constant.attrs) =>
+                        {
+                            members.push(format!(
+                                "pub const {}: {}",
+                                constant.ident,
+                                render(&constant.ty)
+                            ))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            syn::Item::Mod(item) if !is_hidden_or_test(&item.attrs) => {
+                if let Some((_, items)) = &item.content {
+                    describe(name, items, definitions, members);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn snapshot() -> String {
+    let mut snapshot = String::new();
+    for reexport in prelude_reexports() {
+        let mut definitions = vec![];
+        let mut members = vec![];
+        for file in source_files(&reexport.path) {
+            let source = syn::parse_file(&std::fs::read_to_string(&file).unwrap()).unwrap();
+            describe(
+                &reexport.name,
+                &source.items,
+                &mut definitions,
+                &mut members,
+            );
+        }
+        assert_eq!(
+            definitions.len(),
+            1,
+            "Expected one public definition of {} under {}, found {definitions:?}",
+            reexport.name,
+            reexport.path.join("::")
+        );
+        // Sorted so moving items around the source doesn't change the snapshot
+        members.sort();
+        if let Some(cfg) = &reexport.cfg {
+            snapshot.push_str(&format!("{cfg}\n"));
+        }
+        snapshot.push_str(&format!("roslibrust::prelude::{}\n", reexport.name));
+        snapshot.push_str(&format!("    {}\n", definitions[0].trim_end()));
+        for member in members {
+            snapshot.push_str(&format!("        {}\n", member.trim_end()));
+        }
+        snapshot.push('\n');
+    }
+    snapshot
+}
+
+#[test]
+fn prelude_surface_is_unchanged() {
+    let current = snapshot();
+    if std::env::var("UPDATE_PUBLIC_API").is_ok() {
+        std::fs::write(SNAPSHOT_PATH, &current).unwrap();
+        return;
+    }
+    let recorded = std::fs::read_to_string(SNAPSHOT_PATH).unwrap_or_default();
+
+    // Creating a diff so if there are changes output in CI is sane
+    let diff = diffy::create_patch(&recorded, &current);
+    println!("Diff is \n{}", diff);
+
+    if recorded != current {
+        panic!("The surface of roslibrust::prelude changed, see diff! If the change is intended, regenerate tests/public_api.txt with UPDATE_PUBLIC_API=1");
+    }
+}
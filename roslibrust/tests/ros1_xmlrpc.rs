@@ -25,6 +25,16 @@ mod tests {
         value
     }
 
+    /// Fetches the publishers, subscribers and services currently registered with the master
+    async fn system_state() -> roslibrust::SystemState {
+        roslibrust::MasterClient::new("http://localhost:11311", "", "/registration_checker")
+            .await
+            .unwrap()
+            .get_system_state()
+            .await
+            .unwrap()
+    }
+
     #[test_log::test(tokio::test)]
     async fn verify_get_master_uri() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let node =
@@ -84,6 +94,69 @@ mod tests {
         Ok(())
     }
 
+    #[test_log::test(tokio::test)]
+    async fn verify_get_bus_info() {
+        let node_name = "/verify_get_bus_info";
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", node_name)
+            .await
+            .unwrap();
+        let node_uri = node.get_client_uri().await.unwrap();
+        let publisher = node
+            .advertise::<std_msgs::String>("/bus_info", 1)
+            .await
+            .unwrap();
+        let mut subscriber = node
+            .subscribe::<std_msgs::String>("/bus_info", 1)
+            .await
+            .unwrap();
+
+        // The subscriber connects in the background, so publish until a message makes it through
+        let timeout = tokio::time::Duration::from_secs(1);
+        let msg = std_msgs::String {
+            data: "hello".to_owned(),
+        };
+        loop {
+            publisher.publish(&msg).await.unwrap();
+            if tokio::time::timeout(timeout, subscriber.next())
+                .await
+                .is_ok()
+            {
+                break;
+            }
+        }
+
+        // [connectionId, destinationId, direction, transport, topic, connected, transportInfo]
+        let bus_info = call_node_api::<Vec<(i32, String, String, String, String, bool, String)>>(
+            &node_uri,
+            "getBusInfo",
+            vec![node_name.into()],
+        )
+        .await;
+        assert_eq!(bus_info.len(), 2);
+        assert!(bus_info.iter().all(|(_, peer, _, transport, topic, _, _)| {
+            peer == node_name && transport == "TCPROS" && topic == "/bus_info"
+        }));
+        assert!(bus_info
+            .iter()
+            .any(|(_, _, direction, ..)| direction == "o"));
+        assert!(bus_info
+            .iter()
+            .any(|(_, _, direction, ..)| direction == "i"));
+
+        // [publishStats, subscribeStats, serviceStats]
+        type PublishStats = Vec<(String, i32, Vec<(i32, i32, i32, bool)>)>;
+        type SubscribeStats = Vec<(String, Vec<(i32, i32, i32, i32, bool)>)>;
+        let (publish_stats, subscribe_stats, _): (PublishStats, SubscribeStats, Vec<i32>) =
+            call_node_api(&node_uri, "getBusStats", vec![node_name.into()]).await;
+        let (topic, bytes_sent, connections) = &publish_stats[0];
+        assert_eq!(topic, "/bus_info");
+        assert!(*bytes_sent > 0);
+        assert!(connections[0].2 > 0);
+        let (topic, connections) = &subscribe_stats[0];
+        assert_eq!(topic, "/bus_info");
+        assert!(connections[0].1 > 0);
+    }
+
     #[test_log::test(tokio::test)]
     async fn verify_shutdown() {
         let node = roslibrust::NodeHandle::new("http://localhost:11311", "verify_shutdown")
@@ -143,4 +216,1764 @@ mod tests {
         assert!(!host.is_empty());
         assert!(port != 0);
     }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_param_update() {
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", "verify_param_update")
+            .await
+            .unwrap();
+        log::info!("Got handle");
+
+        let mut gain = node.subscribe_param::<f64>("~gain").await.unwrap();
+        assert_eq!(gain.key(), "/verify_param_update/gain");
+        let timeout = tokio::time::Duration::from_secs(1);
+        let next = tokio::time::timeout(timeout, gain.next()).await.unwrap();
+        assert_eq!(next.unwrap(), None);
+
+        node.set_param(gain.key(), &2.5).await.unwrap();
+        let next = tokio::time::timeout(timeout, gain.next()).await.unwrap();
+        assert_eq!(next.unwrap(), Some(2.5));
+        log::info!("Got update");
+
+        node.delete_param(gain.key()).await.unwrap();
+        let next = tokio::time::timeout(timeout, gain.next()).await.unwrap();
+        assert_eq!(next.unwrap(), None);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_graceful_shutdown() {
+        let node_name = "/verify_graceful_shutdown";
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", node_name)
+            .await
+            .unwrap();
+        let publisher = node
+            .advertise::<std_msgs::String>("/graceful_shutdown", 1)
+            .await
+            .unwrap();
+        let mut subscriber = node
+            .subscribe::<std_msgs::String>("/graceful_shutdown", 1)
+            .await
+            .unwrap();
+
+        let state = system_state().await;
+        assert!(state.is_publishing("/graceful_shutdown", node_name));
+        assert!(state.is_subscribed("/graceful_shutdown", node_name));
+
+        node.shutdown().await;
+        assert!(!node.is_ok());
+        let state = system_state().await;
+        assert!(!state.is_publishing("/graceful_shutdown", node_name));
+        assert!(!state.is_subscribed("/graceful_shutdown", node_name));
+
+        // Everything still holding on to the node learns it has shut down
+        let err = publisher
+            .publish(&std_msgs::String::default())
+            .await
+            .unwrap_err();
+        assert!(err.is::<roslibrust::NodeShutdownError>());
+        let err = subscriber.next().await.unwrap_err();
+        assert!(err.is::<roslibrust::NodeShutdownError>());
+        let err = node.get_client_uri().await.unwrap_err();
+        assert!(err.is::<roslibrust::NodeShutdownError>());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_name_conflict() {
+        // The master only learns of a node once it registers something
+        let original =
+            roslibrust::NodeHandle::new("http://localhost:11311", "verify_name_conflict")
+                .await
+                .unwrap();
+        let _original_publisher = original
+            .advertise::<std_msgs::String>("/name_conflict", 1)
+            .await
+            .unwrap();
+        let replacement =
+            roslibrust::NodeHandle::new("http://localhost:11311", "verify_name_conflict")
+                .await
+                .unwrap();
+        let _replacement_publisher = replacement
+            .advertise::<std_msgs::String>("/name_conflict", 1)
+            .await
+            .unwrap();
+
+        // The master asks the original node to shut down once the replacement registers
+        let reason = tokio::time::timeout(
+            tokio::time::Duration::from_secs(1),
+            original.wait_for_shutdown(),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            reason,
+            roslibrust::NodeShutdownError::SupersededByNewNode { .. }
+        ));
+        assert!(!original.is_ok());
+        assert!(replacement.is_ok());
+        let err = original.get_client_uri().await.unwrap_err();
+        match err.downcast_ref::<roslibrust::NodeShutdownError>() {
+            Some(roslibrust::NodeShutdownError::SupersededByNewNode { name }) => {
+                assert_eq!(name, "/verify_name_conflict")
+            }
+            other => panic!("Expected the node to be superseded, got {other:?}"),
+        }
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_remapping() {
+        let remappings =
+            roslibrust::Remappings::from_args(["chatter:=/remapped_chatter", "__ns:=/remap_ns"]);
+        let node = roslibrust::NodeHandle::new_with_remappings(
+            "http://localhost:11311",
+            "verify_remapping",
+            remappings,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            node.get_node_name().await.unwrap(),
+            "/remap_ns/verify_remapping"
+        );
+        let _publisher = node
+            .advertise::<std_msgs::String>("chatter", 1)
+            .await
+            .unwrap();
+
+        assert!(system_state()
+            .await
+            .is_publishing("/remapped_chatter", "/remap_ns/verify_remapping"));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_remap_table() {
+        let master = roslibrust::MasterClient::new("http://localhost:11311", "", "/rosparam")
+            .await
+            .unwrap();
+        master
+            .set_param(
+                "/verify_remap_table/remappings",
+                Value::Array(vec![
+                    "chatter:=/table_chatter".into(),
+                    "image:=/table_image".into(),
+                ]),
+            )
+            .await
+            .unwrap();
+
+        let options =
+            roslibrust::NodeHandleOptions::new("http://localhost:11311", "/verify_remap_table")
+                .remappings(roslibrust::Remappings::new().remap("image", "/argument_image"))
+                .remap_table("~remappings");
+        let node = roslibrust::NodeHandle::new_with_options(options)
+            .await
+            .unwrap();
+        assert_eq!(node.resolve_name("chatter").unwrap(), "/table_chatter");
+        // Remappings given to the node take precedence over the table
+        assert_eq!(node.resolve_name("image").unwrap(), "/argument_image");
+        let _publisher = node
+            .advertise::<std_msgs::String>("chatter", 1)
+            .await
+            .unwrap();
+        assert!(system_state()
+            .await
+            .is_publishing("/table_chatter", "/verify_remap_table"));
+        master
+            .delete_param("/verify_remap_table/remappings")
+            .await
+            .unwrap();
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_name_resolution() {
+        let node = roslibrust::NodeHandle::new_with_remappings(
+            "http://localhost:11311",
+            "verify_name_resolution",
+            roslibrust::Remappings::new().namespace("/resolution_ns"),
+        )
+        .await
+        .unwrap();
+        let _relative = node
+            .advertise::<std_msgs::String>("relative", 1)
+            .await
+            .unwrap();
+        let _private = node
+            .advertise::<std_msgs::String>("~private", 1)
+            .await
+            .unwrap();
+
+        let node_name = "/resolution_ns/verify_name_resolution";
+        let state = system_state().await;
+        assert!(state.is_publishing("/resolution_ns/relative", node_name));
+        assert!(state.is_publishing("/resolution_ns/verify_name_resolution/private", node_name));
+
+        // Parameters resolve the same way as topics
+        node.set_param("~gain", &2.5).await.unwrap();
+        let gain: f64 = node
+            .get_param("/resolution_ns/verify_name_resolution/gain")
+            .await
+            .unwrap();
+        assert_eq!(gain, 2.5);
+        node.delete_param("~gain").await.unwrap();
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_anonymous_names() {
+        let first = roslibrust::NodeHandle::new_anonymous("http://localhost:11311", "anonymous")
+            .await
+            .unwrap();
+        let second = roslibrust::NodeHandle::new_anonymous("http://localhost:11311", "anonymous")
+            .await
+            .unwrap();
+        let first_name = first.get_node_name().await.unwrap();
+        let second_name = second.get_node_name().await.unwrap();
+        assert!(first_name.starts_with("/anonymous_"));
+        assert_ne!(first_name, second_name);
+
+        // Neither replaces the other
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        assert!(first.is_ok());
+        assert!(second.is_ok());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_wait_for_service() {
+        let node =
+            roslibrust::NodeHandle::new("http://localhost:11311", "/verify_wait_for_service")
+                .await
+                .unwrap();
+        let err = node
+            .wait_for_service(
+                "/late_service",
+                Some(tokio::time::Duration::from_millis(200)),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::TimedOut
+        );
+
+        let waiter =
+            tokio::spawn(async move { node.wait_for_service("/late_service", None).await });
+        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+        assert!(!waiter.is_finished());
+
+        // Stands in for a node providing the service
+        let provider = roslibrust::MasterClient::new(
+            "http://localhost:11311",
+            "http://localhost:11313",
+            "/late_provider",
+        )
+        .await
+        .unwrap();
+        provider
+            .register_service("/late_service", "rosrpc://localhost:11314")
+            .await
+            .unwrap();
+        tokio::time::timeout(tokio::time::Duration::from_secs(5), waiter)
+            .await
+            .expect("Should stop waiting once the service is registered")
+            .unwrap()
+            .unwrap();
+        provider
+            .unregister_service("/late_service", "rosrpc://localhost:11314")
+            .await
+            .unwrap();
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_wait_for_message() {
+        let node_name = "/verify_wait_for_message";
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", node_name)
+            .await
+            .unwrap();
+        let err = node
+            .wait_for_message::<std_msgs::String>(
+                "/wait_for_message",
+                Some(tokio::time::Duration::from_millis(200)),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::TimedOut
+        );
+
+        let publisher = node
+            .advertise::<std_msgs::String>("/wait_for_message", 1)
+            .await
+            .unwrap();
+        let waiter = {
+            let node = node.clone();
+            tokio::spawn(async move {
+                node.wait_for_message::<std_msgs::String>("/wait_for_message", None)
+                    .await
+            })
+        };
+        // Publishes until the waiter has connected and received a message
+        let msg = tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+            loop {
+                publisher
+                    .publish(&std_msgs::String {
+                        data: "hello".to_owned(),
+                    })
+                    .await
+                    .unwrap();
+                if waiter.is_finished() {
+                    break waiter.await.unwrap().unwrap();
+                }
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("Should receive a published message");
+        assert_eq!(msg.data, "hello");
+
+        // The subscription is torn down once the message arrives
+        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+        assert!(!system_state()
+            .await
+            .is_subscribed("/wait_for_message", node_name));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_duplicate_registrations() {
+        let node_name = "/verify_duplicate_registrations";
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", node_name)
+            .await
+            .unwrap();
+        let first = node
+            .advertise::<std_msgs::String>("/duplicated", 1)
+            .await
+            .unwrap();
+        let second = node
+            .advertise::<std_msgs::String>("/duplicated", 1)
+            .await
+            .unwrap();
+        let mut subscriber_a = node
+            .subscribe::<std_msgs::String>("/duplicated", 10)
+            .await
+            .unwrap();
+        let mut subscriber_b = node
+            .subscribe::<std_msgs::String>("/duplicated", 10)
+            .await
+            .unwrap();
+
+        // Both publishers share one publication and both subscribers one subscription
+        let publications: Vec<(String, String)> = call_node_api(
+            &node.get_client_uri().await.unwrap(),
+            "getPublications",
+            vec![node_name.into()],
+        )
+        .await;
+        assert_eq!(
+            publications
+                .iter()
+                .filter(|(topic, _)| topic == "/duplicated")
+                .count(),
+            1
+        );
+        let state = system_state().await;
+        assert!(state.is_publishing("/duplicated", node_name));
+        assert!(state.is_subscribed("/duplicated", node_name));
+
+        // Messages from either publisher reach both subscribers, each only once
+        tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+            loop {
+                first
+                    .publish(&std_msgs::String {
+                        data: "first".to_owned(),
+                    })
+                    .await
+                    .unwrap();
+                let received = tokio::time::timeout(
+                    tokio::time::Duration::from_millis(50),
+                    subscriber_a.next(),
+                )
+                .await;
+                if let Ok(msg) = received {
+                    assert_eq!(msg.unwrap().data, "first");
+                    break;
+                }
+            }
+        })
+        .await
+        .expect("Subscriber should connect to the publication");
+        second
+            .publish(&std_msgs::String {
+                data: "second".to_owned(),
+            })
+            .await
+            .unwrap();
+        let mut received = vec![];
+        while let Ok(msg) =
+            tokio::time::timeout(tokio::time::Duration::from_millis(200), subscriber_b.next()).await
+        {
+            received.push(msg.unwrap().data);
+        }
+        assert_eq!(received.last().unwrap(), "second");
+        assert_eq!(received.iter().filter(|data| *data == "second").count(), 1);
+
+        // Other message types can't share the topic
+        let err = node
+            .advertise::<std_msgs::Header>("/duplicated", 1)
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("std_msgs/String"));
+        assert!(node
+            .subscribe::<std_msgs::Header>("/duplicated", 1)
+            .await
+            .is_err());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_config_topic() {
+        let publisher_node =
+            roslibrust::NodeHandle::new("http://localhost:11311", "verify_config_topic_pub")
+                .await
+                .unwrap();
+        let subscriber_node =
+            roslibrust::NodeHandle::new("http://localhost:11311", "verify_config_topic_sub")
+                .await
+                .unwrap();
+        let config = publisher_node
+            .advertise_config::<std_msgs::String>("/config_topic")
+            .await
+            .unwrap();
+        let mode = |data: &str| std_msgs::String {
+            data: data.to_owned(),
+        };
+        assert!(config.set(mode("idle")).await.unwrap());
+        assert!(!config.set(mode("idle")).await.unwrap());
+
+        // Subscribers joining late still receive the current value
+        let mut subscriber = subscriber_node
+            .subscribe_config::<std_msgs::String>("/config_topic")
+            .await
+            .unwrap();
+        let current =
+            tokio::time::timeout(tokio::time::Duration::from_secs(1), subscriber.current())
+                .await
+                .expect("Latched value should be received")
+                .unwrap();
+        assert_eq!(current.data, "idle");
+
+        assert!(config.set(mode("active")).await.unwrap());
+        let changed =
+            tokio::time::timeout(tokio::time::Duration::from_secs(1), subscriber.changed())
+                .await
+                .expect("Change should be received")
+                .unwrap();
+        assert_eq!(changed.data, "active");
+        assert_eq!(subscriber.get().unwrap().data, "active");
+        assert_eq!(config.get().await.unwrap().data, "active");
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_ping_and_kill() {
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", "verify_ping_and_kill")
+            .await
+            .unwrap();
+        let _publisher = node
+            .advertise::<std_msgs::String>("/verify_ping_and_kill", 1)
+            .await
+            .unwrap();
+        let node_uri = node.get_client_uri().await.unwrap();
+        let master = roslibrust::MasterClient::new("http://localhost:11311", "", "/rosnode")
+            .await
+            .unwrap();
+        assert_eq!(
+            master.lookup_node("/verify_ping_and_kill").await.unwrap(),
+            node_uri
+        );
+
+        // rosnode ping
+        let pid = call_node_api::<i32>(&node_uri, "getPid", vec!["/rosnode".into()]).await;
+        assert_eq!(pid as u32, std::process::id());
+
+        // rosnode kill, with the message left out as rospy allows
+        call_node_api::<i32>(&node_uri, "shutdown", vec!["/rosnode".into()]).await;
+        tokio::time::timeout(
+            tokio::time::Duration::from_secs(1),
+            node.wait_for_shutdown(),
+        )
+        .await
+        .expect("Node should shut down");
+        assert!(master.lookup_node("/verify_ping_and_kill").await.is_err());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_namespace() {
+        let options =
+            roslibrust::NodeHandleOptions::new("http://localhost:11311", "/verify_namespace")
+                .remappings(roslibrust::Remappings::new())
+                .namespace("/options_ns");
+        let node = roslibrust::NodeHandle::new_with_options(options.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            node.get_node_name().await.unwrap(),
+            "/options_ns/verify_namespace"
+        );
+        assert_eq!(node.resolve_name("chatter").unwrap(), "/options_ns/chatter");
+        let _publisher = node
+            .advertise::<std_msgs::String>("chatter", 1)
+            .await
+            .unwrap();
+        assert!(system_state()
+            .await
+            .is_publishing("/options_ns/chatter", "/options_ns/verify_namespace"));
+
+        // An __ns remapping takes precedence over the namespace
+        let node = roslibrust::NodeHandle::new_with_options(
+            options.remappings(roslibrust::Remappings::new().namespace("/remapped_ns")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            node.get_node_name().await.unwrap(),
+            "/remapped_ns/verify_namespace"
+        );
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_latched_replay_is_flagged() {
+        let nh = roslibrust::NodeHandle::new("http://localhost:11311", "verify_latched_replay")
+            .await
+            .unwrap();
+        let config = nh
+            .advertise_config::<std_msgs::String>("/latched_replay")
+            .await
+            .unwrap();
+        let value = |data: &str| std_msgs::String {
+            data: data.to_owned(),
+        };
+        config.set(value("stale")).await.unwrap();
+
+        let mut subscriber = nh
+            .subscribe::<std_msgs::String>("/latched_replay", 1)
+            .await
+            .unwrap();
+        let (msg, info) = tokio::time::timeout(
+            tokio::time::Duration::from_secs(1),
+            subscriber.next_with_info(),
+        )
+        .await
+        .expect("Latched message should be received")
+        .unwrap();
+        assert_eq!(msg.data, "stale");
+        assert!(info.latched);
+
+        config.set(value("fresh")).await.unwrap();
+        let (msg, info) = tokio::time::timeout(
+            tokio::time::Duration::from_secs(1),
+            subscriber.next_with_info(),
+        )
+        .await
+        .expect("Update should be received")
+        .unwrap();
+        assert_eq!(msg.data, "fresh");
+        assert!(!info.latched);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_udpros_subscription() {
+        let node_name = "/verify_udpros_subscription";
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", node_name)
+            .await
+            .unwrap();
+        node.topic_defaults()
+            .set("/udpros", roslibrust::TopicOptions::new().udp(true))
+            .unwrap();
+        let publisher = node
+            .advertise::<std_msgs::String>("/udpros", 1)
+            .await
+            .unwrap();
+        let mut subscriber = node
+            .subscribe::<std_msgs::String>("/udpros", 1)
+            .await
+            .unwrap();
+
+        // The subscriber connects in the background, so publish until a message makes it through
+        let timeout = tokio::time::Duration::from_secs(1);
+        let msg = std_msgs::String {
+            data: "x".repeat(4000),
+        };
+        loop {
+            publisher.publish(&msg).await.unwrap();
+            if let Ok(received) = tokio::time::timeout(timeout, subscriber.next()).await {
+                // Larger than a single datagram, so reassembled from several
+                assert_eq!(received.unwrap(), msg);
+                break;
+            }
+        }
+
+        let node_uri = node.get_client_uri().await.unwrap();
+        let bus_info = call_node_api::<Vec<(i32, String, String, String, String, bool, String)>>(
+            &node_uri,
+            "getBusInfo",
+            vec![node_name.into()],
+        )
+        .await;
+        assert_eq!(bus_info.len(), 2);
+        assert!(bus_info
+            .iter()
+            .all(|(_, _, _, transport, ..)| transport == "UDPROS"));
+    }
+
+    #[cfg(unix)]
+    #[test_log::test(tokio::test)]
+    async fn verify_unix_socket_subscription() {
+        let node_name = "/verify_unix_socket_subscription";
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", node_name)
+            .await
+            .unwrap();
+        node.topic_defaults()
+            .set(
+                "/unixros",
+                roslibrust::TopicOptions::new().unix_socket(true),
+            )
+            .unwrap();
+        let publisher = node
+            .advertise::<std_msgs::String>("/unixros", 1)
+            .await
+            .unwrap();
+        let mut subscriber = node
+            .subscribe::<std_msgs::String>("/unixros", 1)
+            .await
+            .unwrap();
+
+        // The subscriber connects in the background, so publish until a message makes it through
+        let timeout = tokio::time::Duration::from_secs(1);
+        let msg = std_msgs::String {
+            data: "over a socket".to_owned(),
+        };
+        loop {
+            publisher.publish(&msg).await.unwrap();
+            if let Ok(received) = tokio::time::timeout(timeout, subscriber.next()).await {
+                assert_eq!(received.unwrap(), msg);
+                break;
+            }
+        }
+
+        let node_uri = node.get_client_uri().await.unwrap();
+        let bus_info = call_node_api::<Vec<(i32, String, String, String, String, bool, String)>>(
+            &node_uri,
+            "getBusInfo",
+            vec![node_name.into()],
+        )
+        .await;
+        assert_eq!(bus_info.len(), 2);
+        assert!(bus_info
+            .iter()
+            .all(|(_, _, _, transport, ..)| transport == "UNIXROS"));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_intra_process_subscription() {
+        let node_name = "/verify_intra_process_subscription";
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", node_name)
+            .await
+            .unwrap();
+        node.topic_defaults()
+            .set(
+                "/intra_process",
+                roslibrust::TopicOptions::new().intra_process(true),
+            )
+            .unwrap();
+        let mut subscriber = node
+            .subscribe::<std_msgs::String>("/intra_process", 1)
+            .await
+            .unwrap();
+        let publisher = node
+            .advertise::<std_msgs::String>("/intra_process", 1)
+            .await
+            .unwrap();
+
+        // Linked as the publication is advertised, so the first message is already handed over
+        let msg = std_msgs::String {
+            data: "in-process".to_owned(),
+        };
+        publisher.publish(&msg).await.unwrap();
+        let received = tokio::time::timeout(tokio::time::Duration::from_secs(1), subscriber.next())
+            .await
+            .expect("Message should be handed over in-process");
+        assert_eq!(received.unwrap(), msg);
+
+        let node_uri = node.get_client_uri().await.unwrap();
+        let bus_info = call_node_api::<Vec<(i32, String, String, String, String, bool, String)>>(
+            &node_uri,
+            "getBusInfo",
+            vec![node_name.into()],
+        )
+        .await;
+        assert_eq!(bus_info.len(), 2);
+        assert!(bus_info
+            .iter()
+            .all(|(_, _, _, transport, ..)| transport == "INTRAPROCESS"));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_ipv6_nodes() {
+        let node = |name: &'static str| async move {
+            let options =
+                roslibrust::NodeHandleOptions::new("http://localhost:11311", name).bind_host("::1");
+            roslibrust::NodeHandle::new_with_options(options)
+                .await
+                .unwrap()
+        };
+        let talker = node("/ipv6_talker").await;
+        let listener = node("/ipv6_listener").await;
+        let client_uri = talker.get_client_uri().await.unwrap();
+        assert!(client_uri.starts_with("http://[::1]:"), "{client_uri}");
+
+        let publisher = talker
+            .advertise::<std_msgs::String>("/ipv6", 1)
+            .await
+            .unwrap();
+        let mut subscriber = listener
+            .subscribe::<std_msgs::String>("/ipv6", 1)
+            .await
+            .unwrap();
+
+        // The subscriber connects in the background, so publish until a message makes it through
+        let timeout = tokio::time::Duration::from_secs(1);
+        let msg = std_msgs::String {
+            data: "over IPv6".to_owned(),
+        };
+        loop {
+            publisher.publish(&msg).await.unwrap();
+            if let Ok(received) = tokio::time::timeout(timeout, subscriber.next()).await {
+                assert_eq!(received.unwrap(), msg);
+                break;
+            }
+        }
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_custom_header_fields() {
+        let talker = roslibrust::NodeHandle::new("http://localhost:11311", "/custom_fields_talker")
+            .await
+            .unwrap();
+        talker
+            .topic_defaults()
+            .set(
+                "/custom_fields",
+                roslibrust::TopicOptions::new().header_field("tenant", "fleet_a"),
+            )
+            .unwrap();
+        let listener =
+            roslibrust::NodeHandle::new("http://localhost:11311", "/custom_fields_listener")
+                .await
+                .unwrap();
+        listener
+            .topic_defaults()
+            .set(
+                "/custom_fields",
+                roslibrust::TopicOptions::new().header_field("trace_id", "4bf92f35"),
+            )
+            .unwrap();
+
+        let publisher = talker
+            .advertise::<std_msgs::String>("/custom_fields", 1)
+            .await
+            .unwrap();
+        let (connected, mut connections) = tokio::sync::mpsc::unbounded_channel();
+        publisher.on_subscriber_connect(move |subscriber| {
+            let _ = connected.send(subscriber.custom_fields.clone());
+        });
+        let mut subscriber = listener
+            .subscribe::<std_msgs::String>("/custom_fields", 1)
+            .await
+            .unwrap();
+
+        let timeout = tokio::time::Duration::from_secs(1);
+        let msg = std_msgs::String {
+            data: "with metadata".to_owned(),
+        };
+        let info = loop {
+            publisher.publish(&msg).await.unwrap();
+            if let Ok(received) = tokio::time::timeout(timeout, subscriber.next_with_info()).await {
+                break received.unwrap().1;
+            }
+        };
+        assert_eq!(info.publisher.custom_fields["tenant"], "fleet_a");
+        let fields = connections.recv().await.unwrap();
+        assert_eq!(fields["trace_id"], "4bf92f35");
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_header_stamping() {
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", "/verify_header_stamping")
+            .await
+            .unwrap();
+        let unstampable = node
+            .advertise::<std_msgs::String>("/unstampable", 1)
+            .await
+            .unwrap();
+        assert!(unstampable.stamp_headers(node.clock()).is_err());
+
+        let publisher = node
+            .advertise::<actionlib_msgs::GoalStatusArray>("/stamped", 1)
+            .await
+            .unwrap()
+            .stamp_headers(node.clock())
+            .unwrap();
+        let mut subscriber = node
+            .subscribe::<actionlib_msgs::GoalStatusArray>("/stamped", 1)
+            .await
+            .unwrap();
+
+        // The subscriber connects in the background, so publish until a message makes it through
+        let timeout = tokio::time::Duration::from_secs(1);
+        let msg = actionlib_msgs::GoalStatusArray::default();
+        let first = loop {
+            publisher.publish(&msg).await.unwrap();
+            if let Ok(received) = tokio::time::timeout(timeout, subscriber.next()).await {
+                break received.unwrap();
+            }
+        };
+        assert_ne!(first.header.stamp, roslibrust_codegen::Time::default());
+
+        // Stamps given by the caller are kept, while the sequence number keeps counting
+        let mut msg = msg;
+        msg.header.stamp = roslibrust_codegen::Time { secs: 1, nsecs: 2 };
+        publisher.publish(&msg).await.unwrap();
+        let second = subscriber.next().await.unwrap();
+        assert_eq!(second.header.seq, first.header.seq + 1);
+        assert_eq!(second.header.stamp, msg.header.stamp);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_stale_connection_reaping() {
+        let options = roslibrust::NodeHandleOptions::new(
+            "http://localhost:11311",
+            "/verify_stale_connection_reaping",
+        )
+        .reap_stale_connections(tokio::time::Duration::from_millis(200));
+        let node = roslibrust::NodeHandle::new_with_options(options)
+            .await
+            .unwrap();
+        let publisher = node
+            .advertise::<std_msgs::String>("/reaped", 1)
+            .await
+            .unwrap();
+        let subscriber_node =
+            roslibrust::NodeHandle::new("http://localhost:11311", "/stale_subscriber")
+                .await
+                .unwrap();
+        let _subscriber = subscriber_node
+            .subscribe::<std_msgs::String>("/reaped", 1)
+            .await
+            .unwrap();
+        let timeout = tokio::time::Duration::from_secs(5);
+        tokio::time::timeout(timeout, async {
+            while publisher.get_num_subscribers() == 0 {
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("Subscriber should connect");
+
+        // Unregister the subscriber behind its node's back, leaving its connection open
+        let subscriber_uri = subscriber_node.get_client_uri().await.unwrap();
+        let master = roslibrust::MasterClient::new(
+            "http://localhost:11311",
+            subscriber_uri,
+            "/stale_subscriber",
+        )
+        .await
+        .unwrap();
+        assert!(master.unregister_subscriber("/reaped").await.unwrap());
+        tokio::time::timeout(timeout, async {
+            while publisher.get_num_subscribers() > 0 {
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("Connection of the unregistered subscriber should be reaped");
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_connections_do_not_accumulate() {
+        let node_name = "/verify_connections_do_not_accumulate";
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", node_name)
+            .await
+            .unwrap();
+        let mut subscriber = node
+            .subscribe::<std_msgs::String>("/churned", 1)
+            .await
+            .unwrap();
+        let node_uri = node.get_client_uri().await.unwrap();
+
+        // Publishers come and go, each leaving the topic properly
+        for _ in 0..5 {
+            let publisher_node =
+                roslibrust::NodeHandle::new("http://localhost:11311", "/churning_publisher")
+                    .await
+                    .unwrap();
+            let publisher = publisher_node
+                .advertise::<std_msgs::String>("/churned", 1)
+                .await
+                .unwrap();
+            let msg = std_msgs::String::default();
+            loop {
+                publisher.publish(&msg).await.unwrap();
+                let wait = tokio::time::Duration::from_millis(200);
+                if tokio::time::timeout(wait, subscriber.next()).await.is_ok() {
+                    break;
+                }
+            }
+            publisher_node.shutdown().await;
+        }
+
+        // Every connection to the departed publishers is closed
+        let timeout = tokio::time::Duration::from_secs(5);
+        tokio::time::timeout(timeout, async {
+            loop {
+                let bus_info = call_node_api::<
+                    Vec<(i32, String, String, String, String, bool, String)>,
+                >(&node_uri, "getBusInfo", vec![node_name.into()])
+                .await;
+                if bus_info.is_empty() {
+                    break;
+                }
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("Connections to departed publishers should be closed");
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_queue_overflow_policy() {
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", "/verify_queue_overflow")
+            .await
+            .unwrap();
+        node.topic_defaults()
+            .set(
+                "/overflowing",
+                roslibrust::TopicOptions::new()
+                    .queue_overflow(roslibrust::QueueOverflowPolicy::DropNewest),
+            )
+            .unwrap();
+        let publisher = node
+            .advertise::<std_msgs::String>("/overflowing", 1)
+            .await
+            .unwrap();
+        let mut subscriber = node
+            .subscribe::<std_msgs::String>("/overflowing", 1)
+            .await
+            .unwrap();
+
+        // The subscriber connects in the background, so publish until a message makes it through
+        let wait = tokio::time::Duration::from_millis(200);
+        let msg = std_msgs::String::default();
+        loop {
+            publisher.publish(&msg).await.unwrap();
+            if tokio::time::timeout(wait, subscriber.next()).await.is_ok() {
+                break;
+            }
+        }
+        while tokio::time::timeout(wait, subscriber.next()).await.is_ok() {}
+        let before = subscriber.stats();
+
+        // Only the first message fits in the queue while the subscriber isn't reading
+        for data in ["first", "second", "third"] {
+            publisher
+                .publish(&std_msgs::String {
+                    data: data.to_owned(),
+                })
+                .await
+                .unwrap();
+            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        }
+        assert_eq!(subscriber.next().await.unwrap().data, "first");
+        let after = subscriber.stats();
+        assert_eq!(after.received, before.received + 1);
+        assert_eq!(after.dropped, before.dropped + 2);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_topic_pair() {
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", "/verify_topic_pair")
+            .await
+            .unwrap();
+        // Echoes every command back as its acknowledgement
+        let mut commands = node
+            .subscribe::<std_msgs::String>("/pair/command", 10)
+            .await
+            .unwrap();
+        let acks = node
+            .advertise::<std_msgs::String>("/pair/ack", 10)
+            .await
+            .unwrap();
+        tokio::spawn(async move {
+            loop {
+                let Ok(command) = commands.next().await else {
+                    break;
+                };
+                acks.publish(&command).await.unwrap();
+            }
+        });
+
+        let mut pair = node
+            .topic_pair::<std_msgs::String, std_msgs::String>("/pair/command", "/pair/ack")
+            .await
+            .unwrap();
+        let wait = tokio::time::Duration::from_millis(200);
+        let command = |data: &str| std_msgs::String {
+            data: data.to_owned(),
+        };
+
+        // The subscribers connect in the background, so send until an acknowledgement makes it through
+        while pair
+            .send_and_await_match(&command("hello"), |ack| ack.data == "hello", wait)
+            .await
+            .is_err()
+        {}
+
+        let ack = pair
+            .send_and_await_match(&command("open"), |ack| ack.data == "open", wait)
+            .await
+            .unwrap();
+        assert_eq!(ack.data, "open");
+
+        let err = pair
+            .send_and_await_match(&command("close"), |ack| ack.data == "open", wait)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::TimedOut
+        );
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_dedicated_runtime() {
+        let options = roslibrust::NodeHandleOptions::new(
+            "http://localhost:11311",
+            "/verify_dedicated_runtime",
+        )
+        .runtime(roslibrust::RuntimePlacement::DedicatedThread);
+        let node = roslibrust::NodeHandle::new_with_options(options)
+            .await
+            .unwrap();
+        let publisher = node
+            .advertise::<std_msgs::String>("/dedicated", 1)
+            .await
+            .unwrap();
+        let mut subscriber = node
+            .subscribe::<std_msgs::String>("/dedicated", 1)
+            .await
+            .unwrap();
+
+        // The subscriber connects in the background, so publish until a message makes it through
+        let wait = tokio::time::Duration::from_millis(200);
+        let msg = std_msgs::String {
+            data: "dedicated".to_owned(),
+        };
+        let received = loop {
+            publisher.publish(&msg).await.unwrap();
+            if let Ok(received) = tokio::time::timeout(wait, subscriber.next()).await {
+                break received.unwrap();
+            }
+        };
+        assert_eq!(received, msg);
+
+        node.shutdown().await;
+        assert!(subscriber.next().await.is_err());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_spin_until() {
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", "/verify_spin_until")
+            .await
+            .unwrap();
+        let (sender, cancelled) = tokio::sync::oneshot::channel::<()>();
+        node.spawn(async move {
+            let _sender = sender;
+            std::future::pending::<()>().await
+        });
+
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let spinning = tokio::spawn({
+            let node = node.clone();
+            async move {
+                node.spin_until(async {
+                    let _ = stopped.await;
+                })
+                .await
+            }
+        });
+        stop.send(()).unwrap();
+        let reason = spinning.await.unwrap();
+        assert!(matches!(reason, roslibrust::NodeShutdownError::ShutDown));
+        assert!(!node.is_ok());
+        // Tasks spawned through the node are cancelled along with it
+        assert!(cancelled.await.is_err());
+
+        // Spinning a node which has already shut down returns straight away
+        let reason = node.spin().await;
+        assert!(matches!(reason, roslibrust::NodeShutdownError::ShutDown));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_static_transforms_are_merged() {
+        let transform = |parent: &str, child: &str| roslibrust::StaticTransform {
+            parent_frame_id: parent.to_owned(),
+            child_frame_id: child.to_owned(),
+            stamp: Default::default(),
+            translation: [1.0, 0.0, 0.0],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+        };
+        // Two nodes each latch their own part of the tree
+        let mount =
+            roslibrust::NodeHandle::new("http://localhost:11311", "/verify_tf_static_mount")
+                .await
+                .unwrap();
+        let mount_broadcaster = mount.advertise_static_transforms().await.unwrap();
+        mount_broadcaster
+            .send([transform("base_link", "lidar")])
+            .await
+            .unwrap();
+        // Transforms sent separately are all kept in the latched message
+        mount_broadcaster
+            .send([transform("base_link", "camera")])
+            .await
+            .unwrap();
+        let map = roslibrust::NodeHandle::new("http://localhost:11311", "/verify_tf_static_map")
+            .await
+            .unwrap();
+        let map_broadcaster = map.advertise_static_transforms().await.unwrap();
+        map_broadcaster
+            .send([transform("map", "odom")])
+            .await
+            .unwrap();
+
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", "/verify_tf_static")
+            .await
+            .unwrap();
+        let children = |transforms: &roslibrust::StaticTransforms| {
+            transforms
+                .get()
+                .into_iter()
+                .map(|t| t.child_frame_id)
+                .collect::<Vec<_>>()
+        };
+        let timeout = tokio::time::Duration::from_secs(5);
+        let mut first = node.subscribe_static_transforms().await.unwrap();
+        tokio::time::timeout(timeout, async {
+            while children(&first).len() < 3 {
+                first.changed().await.unwrap();
+            }
+        })
+        .await
+        .expect("Every latched publisher's transforms should be received");
+        assert_eq!(children(&first), vec!["camera", "lidar", "odom"]);
+
+        // Later subscribers share the node's subscription, which replays each publisher's latched message
+        let mut later = node.subscribe_static_transforms().await.unwrap();
+        tokio::time::timeout(timeout, async {
+            while children(&later).len() < 3 {
+                later.changed().await.unwrap();
+            }
+        })
+        .await
+        .expect("Latched messages should be replayed to later subscribers");
+        assert_eq!(later.lookup("/lidar").unwrap().parent_frame_id, "base_link");
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_graph_events() {
+        let dump = std::env::temp_dir().join("verify_graph_events.log");
+        let options =
+            roslibrust::NodeHandleOptions::new("http://localhost:11311", "/verify_graph_events")
+                .graph_event_dump(&dump);
+        let node = roslibrust::NodeHandle::new_with_options(options)
+            .await
+            .unwrap();
+        let _subscriber = node
+            .subscribe::<std_msgs::String>("/graph_events", 1)
+            .await
+            .unwrap();
+        let _publisher = node
+            .advertise::<std_msgs::String>("/graph_events", 1)
+            .await
+            .unwrap();
+
+        // The master tells the subscriber about the publisher once it has registered
+        tokio::time::timeout(tokio::time::Duration::from_secs(2), async {
+            while !node
+                .graph_events()
+                .events()
+                .iter()
+                .any(|event| event.method == "publisherUpdate")
+            {
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let events = node.graph_events().events();
+        let methods: Vec<&str> = events.iter().map(|event| event.method.as_str()).collect();
+        assert_eq!(
+            methods,
+            ["registerSubscriber", "registerPublisher", "publisherUpdate"]
+        );
+        assert!(events.iter().all(|event| event.name == "/graph_events"));
+        assert_eq!(events[0].outcome.as_deref(), Ok("no publishers"));
+
+        node.shutdown().await;
+        let dumped = std::fs::read_to_string(&dump).unwrap();
+        assert!(dumped.contains("] publisherUpdate /graph_events: publishers http://"));
+        assert!(dumped.contains("] unregisterPublisher /graph_events: unregistered"));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_dropped_topics_are_unregistered() {
+        let node_name = "/verify_dropped_topics_are_unregistered";
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", node_name)
+            .await
+            .unwrap();
+        let node_uri = node.get_client_uri().await.unwrap();
+        let topics = |api: &'static str| {
+            let node_uri = node_uri.clone();
+            async move {
+                let topics: Vec<(String, String)> =
+                    call_node_api(&node_uri, api, vec![node_name.into()]).await;
+                topics.into_iter().any(|(topic, topic_type)| {
+                    topic == "/dropped" && topic_type == "std_msgs/String"
+                })
+            }
+        };
+
+        let publisher = node
+            .advertise::<std_msgs::String>("/dropped", 1)
+            .await
+            .unwrap();
+        let second_publisher = node
+            .advertise::<std_msgs::String>("/dropped", 1)
+            .await
+            .unwrap();
+        let subscriber = node
+            .subscribe::<std_msgs::String>("/dropped", 1)
+            .await
+            .unwrap();
+        assert!(topics("getPublications").await);
+        assert!(topics("getSubscriptions").await);
+
+        // The topic stays advertised while any of its publishers are alive
+        drop(publisher);
+        drop(subscriber);
+        tokio::time::timeout(tokio::time::Duration::from_secs(1), async {
+            while topics("getSubscriptions").await {
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Subscription should be dropped along with its subscriber");
+        assert!(topics("getPublications").await);
+        let state = system_state().await;
+        assert!(state.is_publishing("/dropped", node_name));
+        assert!(!state.is_subscribed("/dropped", node_name));
+
+        drop(second_publisher);
+        tokio::time::timeout(tokio::time::Duration::from_secs(1), async {
+            while topics("getPublications").await {
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Publication should be dropped along with its last publisher");
+        assert!(!system_state().await.is_publishing("/dropped", node_name));
+
+        let pid = call_node_api::<i32>(&node_uri, "getPid", vec!["/rosnode".into()]).await;
+        assert_eq!(pid as u32, std::process::id());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_closed_topics_are_unregistered() {
+        let node_name = "/verify_closed_topics_are_unregistered";
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", node_name)
+            .await
+            .unwrap();
+        let timeout = tokio::time::Duration::from_secs(1);
+
+        let publisher = node
+            .advertise::<std_msgs::String>("/closed", 1)
+            .await
+            .unwrap();
+        let subscriber = node
+            .subscribe::<std_msgs::String>("/closed", 1)
+            .await
+            .unwrap();
+        let state = system_state().await;
+        assert!(state.is_publishing("/closed", node_name));
+        assert!(state.is_subscribed("/closed", node_name));
+
+        // The master has been told by the time close returns
+        publisher.close(timeout).await.unwrap();
+        subscriber.close(timeout).await.unwrap();
+        let state = system_state().await;
+        assert!(!state.is_publishing("/closed", node_name));
+        assert!(!state.is_subscribed("/closed", node_name));
+
+        // Closes which run out of time finish in the background like a drop
+        let publisher = node
+            .advertise::<std_msgs::String>("/closed", 1)
+            .await
+            .unwrap();
+        let err = publisher
+            .close(tokio::time::Duration::ZERO)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::TimedOut
+        );
+        tokio::time::timeout(timeout, async {
+            while system_state().await.is_publishing("/closed", node_name) {
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Publication should be dropped after the close timed out");
+
+        node.close(timeout).await.unwrap();
+        assert!(!node.is_ok());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_keep_latest_publisher() {
+        let node =
+            roslibrust::NodeHandle::new("http://localhost:11311", "/verify_keep_latest_publisher")
+                .await
+                .unwrap();
+        let publisher = node
+            .advertise_keep_latest::<std_msgs::Float64>("/keep_latest")
+            .await
+            .unwrap();
+        let mut subscriber = node
+            .subscribe::<std_msgs::Float64>("/keep_latest", 100)
+            .await
+            .unwrap();
+        tokio::time::timeout(tokio::time::Duration::from_secs(1), async {
+            while publisher.get_num_subscribers() == 0 {
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Subscriber should connect");
+
+        for step in 0..100 {
+            publisher
+                .publish(&std_msgs::Float64 { data: step as f64 })
+                .await
+                .unwrap();
+        }
+        // Whatever is skipped along the way, the latest message always arrives
+        loop {
+            let msg = tokio::time::timeout(tokio::time::Duration::from_secs(1), subscriber.next())
+                .await
+                .unwrap()
+                .unwrap();
+            if msg.data == 99.0 {
+                break;
+            }
+        }
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_peer_errors_are_reported_and_forwarded() {
+        let publisher_node =
+            roslibrust::NodeHandle::new("http://localhost:11311", "/verify_peer_errors_publisher")
+                .await
+                .unwrap();
+        let _publisher = publisher_node
+            .advertise::<std_msgs::String>("/peer_errors/chatter", 1)
+            .await
+            .unwrap();
+        let mut peer_errors = publisher_node.peer_errors();
+        publisher_node
+            .forward_peer_errors(Some("/peer_errors/forwarded"))
+            .await
+            .unwrap();
+
+        let other_node =
+            roslibrust::NodeHandle::new("http://localhost:11311", "/verify_peer_errors_subscriber")
+                .await
+                .unwrap();
+        let mut forwarded = other_node
+            .subscribe::<rosgraph_msgs::Log>("/peer_errors/forwarded", 10)
+            .await
+            .unwrap();
+        // Gives the forwarded topic's connection time to be established
+        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+        // The publisher rejects a subscriber of the wrong type
+        let _subscriber = other_node
+            .subscribe::<std_msgs::Float64>("/peer_errors/chatter", 1)
+            .await
+            .unwrap();
+
+        let error = tokio::time::timeout(tokio::time::Duration::from_secs(1), peer_errors.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(error.kind, roslibrust::PeerErrorKind::HeaderRejected);
+        assert_eq!(error.peer, "/verify_peer_errors_subscriber");
+        assert_eq!(error.name, "/peer_errors/chatter");
+
+        let msg = tokio::time::timeout(tokio::time::Duration::from_secs(1), forwarded.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(msg.level, rosgraph_msgs::Log::ERROR);
+        assert_eq!(msg.name, "/verify_peer_errors_publisher");
+        assert_eq!(msg.topics, vec!["/peer_errors/chatter".to_owned()]);
+
+        // The subscriber keeps retrying, and is rejected each time. Once forwarding is stopped
+        // those rejections are still reported, but no longer published.
+        publisher_node.forward_peer_errors(None).await.unwrap();
+        while tokio::time::timeout(tokio::time::Duration::from_millis(300), forwarded.next())
+            .await
+            .is_ok()
+        {}
+        // Retries back off, so the next may take a few seconds
+        tokio::time::timeout(tokio::time::Duration::from_secs(10), peer_errors.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            tokio::time::timeout(tokio::time::Duration::from_millis(200), forwarded.next())
+                .await
+                .is_err()
+        );
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_large_messages_and_max_message_size() {
+        let node = roslibrust::NodeHandle::new(
+            "http://localhost:11311",
+            "/verify_large_messages_and_max_message_size",
+        )
+        .await
+        .unwrap();
+        node.topic_defaults()
+            .set(
+                "/large_messages/limited",
+                roslibrust::TopicOptions::new().max_message_size(1024),
+            )
+            .unwrap();
+        let mut peer_errors = node.peer_errors();
+        let large = std_msgs::String {
+            data: "x".repeat(8 * 1024 * 1024),
+        };
+
+        // Far larger than a single read, the message arrives whole
+        let publisher = node
+            .advertise::<std_msgs::String>("/large_messages/unlimited", 1)
+            .await
+            .unwrap();
+        let mut subscriber = node
+            .subscribe::<std_msgs::String>("/large_messages/unlimited", 1)
+            .await
+            .unwrap();
+        tokio::time::timeout(tokio::time::Duration::from_secs(1), async {
+            while publisher.get_num_subscribers() == 0 {
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Subscriber should connect");
+        publisher.publish(&large).await.unwrap();
+        let msg = tokio::time::timeout(tokio::time::Duration::from_secs(5), subscriber.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(msg.data.len(), large.data.len());
+
+        // Over the topic's maximum, the connection is dropped and reported
+        let publisher = node
+            .advertise::<std_msgs::String>("/large_messages/limited", 1)
+            .await
+            .unwrap();
+        let _subscriber = node
+            .subscribe::<std_msgs::String>("/large_messages/limited", 1)
+            .await
+            .unwrap();
+        tokio::time::timeout(tokio::time::Duration::from_secs(1), async {
+            while publisher.get_num_subscribers() == 0 {
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Subscriber should connect");
+        publisher.publish(&large).await.unwrap();
+        let error = tokio::time::timeout(tokio::time::Duration::from_secs(1), peer_errors.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(error.kind, roslibrust::PeerErrorKind::Malformed);
+        assert_eq!(error.name, "/large_messages/limited");
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_primitive_topics() {
+        let node =
+            roslibrust::NodeHandle::new("http://localhost:11311", "/verify_primitive_topics")
+                .await
+                .unwrap();
+        node.publish_string("/primitive/string", "hello")
+            .await
+            .unwrap();
+        node.publish_f64("/primitive/f64", 0.5).await.unwrap();
+        node.publish_f64("/primitive/f64", 1.5).await.unwrap();
+
+        // Primitive topics are latched, and interchangeable with generated std_msgs
+        let mut subscriber = node
+            .subscribe::<std_msgs::String>("/primitive/string", 1)
+            .await
+            .unwrap();
+        let msg = tokio::time::timeout(tokio::time::Duration::from_secs(1), subscriber.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(msg.data, "hello");
+        let mut subscriber = node.subscribe_f64("/primitive/f64", 1).await.unwrap();
+        let msg = tokio::time::timeout(tokio::time::Duration::from_secs(1), subscriber.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(msg.data, 1.5);
+
+        // A topic keeps the type it was first published with
+        assert!(node.publish_bool("/primitive/f64", true).await.is_err());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_nodes_keep_their_own_master() {
+        let node = roslibrust::NodeHandle::new("http://localhost:11311", "/verify_own_master")
+            .await
+            .unwrap();
+        // Nothing listens on port 1, standing in for a robot which isn't up
+        let unreachable =
+            roslibrust::NodeHandle::new("http://127.0.0.1:1", "/verify_own_master").await;
+        assert!(unreachable.is_err());
+
+        assert_eq!(
+            node.get_master_uri().await.unwrap(),
+            "http://localhost:11311"
+        );
+        let node_uri = node.get_client_uri().await.unwrap();
+        let master_uri: String =
+            call_node_api(&node_uri, "getMasterUri", vec!["/rosnode".into()]).await;
+        assert_eq!(master_uri, "http://localhost:11311");
+        assert!(node.is_ok());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_checksummed_udpros_subscription() {
+        let node = roslibrust::NodeHandle::new(
+            "http://localhost:11311",
+            "/verify_checksummed_udpros_subscription",
+        )
+        .await
+        .unwrap();
+        node.topic_defaults()
+            .set(
+                "/checksummed",
+                roslibrust::TopicOptions::new().udp(true).checksum(true),
+            )
+            .unwrap();
+        let publisher = node
+            .advertise::<std_msgs::String>("/checksummed", 1)
+            .await
+            .unwrap();
+        let mut subscriber = node
+            .subscribe::<std_msgs::String>("/checksummed", 1)
+            .await
+            .unwrap();
+
+        // The subscriber connects in the background, so publish until a message makes it through
+        let timeout = tokio::time::Duration::from_secs(1);
+        let msg = std_msgs::String {
+            data: "x".repeat(4000),
+        };
+        loop {
+            publisher.publish(&msg).await.unwrap();
+            if let Ok(received) = tokio::time::timeout(timeout, subscriber.next()).await {
+                // The checksum is removed before deserializing
+                assert_eq!(received.unwrap(), msg);
+                break;
+            }
+        }
+        assert_eq!(node.checksum_failures().topic_failures("/checksummed"), 0);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_subscribe_any() {
+        let nh = roslibrust::NodeHandle::new("http://localhost:11311", "verify_subscribe_any")
+            .await
+            .unwrap();
+        let publisher = nh
+            .advertise::<std_msgs::String>("/any_msg", 1)
+            .await
+            .unwrap();
+        let mut subscriber = nh.subscribe_any("/any_msg", 1).await.unwrap();
+
+        // The subscriber connects in the background, so publish until a message makes it through
+        let msg = std_msgs::String {
+            data: "hello".to_owned(),
+        };
+        let received = loop {
+            publisher.publish(&msg).await.unwrap();
+            if let Ok(received) =
+                tokio::time::timeout(tokio::time::Duration::from_secs(1), subscriber.next()).await
+            {
+                break received.unwrap();
+            }
+        };
+        assert_eq!(received.publisher.topic_type, "std_msgs/String");
+        assert_eq!(received.data, b"\x09\0\0\0\x05\0\0\0hello");
+        assert!(!received.latched);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_capability_discovery() {
+        let timeout = tokio::time::Duration::from_secs(2);
+        let client = roslibrust::NodeHandle::new("http://localhost:11311", "/capability_client")
+            .await
+            .unwrap();
+        let mut watch = client.watch_capability("grasp_planning").await.unwrap();
+        assert!(watch.next().await.unwrap().is_empty());
+
+        let planner = roslibrust::NodeHandle::new("http://localhost:11311", "/ns/grasp_planner")
+            .await
+            .unwrap();
+        planner
+            .advertise_capability("grasp_planning", "~plan_grasp")
+            .await
+            .unwrap();
+        let expected = vec![roslibrust::CapabilityProvider {
+            node: "/ns/grasp_planner".to_owned(),
+            tag: "grasp_planning".to_owned(),
+            resource: "/ns/grasp_planner/plan_grasp".to_owned(),
+        }];
+        assert_eq!(
+            tokio::time::timeout(timeout, watch.next())
+                .await
+                .unwrap()
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            client.find_capability("grasp_planning").await.unwrap(),
+            expected
+        );
+        assert!(client
+            .find_capability("navigation")
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Shutting down withdraws every capability of the node
+        planner.shutdown().await;
+        assert!(tokio::time::timeout(timeout, watch.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .is_empty());
+        assert!(client
+            .find_capability("grasp_planning")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn verify_action_client() {
+        let timeout = tokio::time::Duration::from_secs(2);
+        // Stands in for an action server, following goals through the action's topics by hand
+        let server = roslibrust::NodeHandle::new("http://localhost:11311", "/map_server")
+            .await
+            .unwrap();
+        let status = server
+            .advertise::<actionlib_msgs::GoalStatusArray>("/static_map/status", 1)
+            .await
+            .unwrap();
+        let result = server
+            .advertise::<nav_msgs::GetMapActionResult>("/static_map/result", 1)
+            .await
+            .unwrap();
+        let mut goals = server
+            .subscribe::<nav_msgs::GetMapActionGoal>("/static_map/goal", 1)
+            .await
+            .unwrap();
+        let _cancels = server
+            .subscribe::<actionlib_msgs::GoalID>("/static_map/cancel", 1)
+            .await
+            .unwrap();
+
+        let nh = roslibrust::NodeHandle::new("http://localhost:11311", "/map_client")
+            .await
+            .unwrap();
+        let client = nh
+            .action_client::<nav_msgs::GetMapAction>("/static_map")
+            .await
+            .unwrap();
+        // Action servers publish their status periodically, the client sees the server once one arrives
+        tokio::time::timeout(timeout, async {
+            while client
+                .wait_for_server(Some(tokio::time::Duration::from_millis(100)))
+                .await
+                .is_err()
+            {
+                status.publish(&Default::default()).await.unwrap();
+            }
+        })
+        .await
+        .unwrap();
+
+        let mut goal = client.send_goal(nav_msgs::GetMapGoal {}).await.unwrap();
+        let received = tokio::time::timeout(timeout, goals.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(received.goal_id.id, goal.goal_id());
+
+        let mut goal_status = actionlib_msgs::GoalStatus {
+            goal_id: received.goal_id,
+            status: actionlib_msgs::GoalStatus::ACTIVE,
+            text: String::new(),
+        };
+        status
+            .publish(&actionlib_msgs::GoalStatusArray {
+                header: Default::default(),
+                status_list: vec![goal_status.clone()],
+            })
+            .await
+            .unwrap();
+        goal_status.status = actionlib_msgs::GoalStatus::SUCCEEDED;
+        goal_status.text = "Map ready".to_owned();
+        result
+            .publish(&nav_msgs::GetMapActionResult {
+                header: Default::default(),
+                status: goal_status,
+                result: Default::default(),
+            })
+            .await
+            .unwrap();
+
+        let outcome = tokio::time::timeout(timeout, goal.result())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(outcome.succeeded());
+        assert_eq!(outcome.text, "Map ready");
+        assert_eq!(goal.comm_state(), roslibrust::CommState::Done);
+        // Feedback ends along with the goal
+        assert!(goal.feedback().await.is_none());
+    }
 }
@@ -0,0 +1,245 @@
+//! Evaluation of the `condition` attributes package.xml format 3 allows on dependency tags, see REP 149,
+//! e.g. `<depend condition="$ROS_VERSION == 2">rclcpp</depend>`
+
+use crate::utils::RosVersion;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Comparison(Comparison),
+    Variable(String),
+    Literal(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    // Values are compared as strings, as catkin_pkg does
+    fn holds(self, left: &str, right: &str) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+/// Evaluates `condition` with `$ROS_VERSION` set to `ros_version`.
+/// Returns None when the result depends on a variable without a value, i.e. `$ROS_VERSION` when no version
+/// is given or any other variable such as `$ROS_PYTHON_VERSION`, and an error if `condition` is malformed.
+pub(crate) fn evaluate(
+    condition: &str,
+    ros_version: Option<RosVersion>,
+) -> Result<Option<bool>, String> {
+    let tokens = tokenize(condition)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        ros_version,
+    };
+    let value = parser.or_expression()?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(token) => Err(format!("unexpected {token:?} after a complete condition")),
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '$')
+}
+
+fn tokenize(condition: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = condition.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '=' | '!' | '<' | '>' => {
+                let or_equal = chars.next_if_eq(&'=').is_some();
+                Token::Comparison(match (c, or_equal) {
+                    ('=', true) => Comparison::Equal,
+                    ('!', true) => Comparison::NotEqual,
+                    ('<', false) => Comparison::Less,
+                    ('<', true) => Comparison::LessOrEqual,
+                    ('>', false) => Comparison::Greater,
+                    ('>', true) => Comparison::GreaterOrEqual,
+                    _ => return Err(format!("expected {c}= but found {c} alone")),
+                })
+            }
+            '"' | '\'' => {
+                let mut literal = String::new();
+                loop {
+                    match chars.next() {
+                        Some(end) if end == c => break,
+                        Some(other) => literal.push(other),
+                        None => return Err(format!("unterminated string {c}{literal}")),
+                    }
+                }
+                Token::Literal(literal)
+            }
+            c if is_word_char(c) => {
+                let mut word = String::from(c);
+                while let Some(next) = chars.next_if(|next| is_word_char(*next)) {
+                    word.push(next);
+                }
+                match word.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    _ => match word.strip_prefix('$') {
+                        Some(name) => Token::Variable(name.to_owned()),
+                        None => Token::Literal(word),
+                    },
+                }
+            }
+            c => return Err(format!("unexpected character '{c}'")),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+// Undecided values follow three-valued logic, so e.g. `$ROS_DISTRO == noetic and $ROS_VERSION == 2`
+// is false for ROS1 whatever the distro
+fn and(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+fn or(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None,
+    }
+}
+
+// A recursive descent parser evaluating the condition as it goes, with `and` binding tighter than `or`
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    ros_version: Option<RosVersion>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn or_expression(&mut self) -> Result<Option<bool>, String> {
+        let mut value = self.and_expression()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            value = or(value, self.and_expression()?);
+        }
+        Ok(value)
+    }
+
+    fn and_expression(&mut self) -> Result<Option<bool>, String> {
+        let mut value = self.comparison()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            value = and(value, self.comparison()?);
+        }
+        Ok(value)
+    }
+
+    fn comparison(&mut self) -> Result<Option<bool>, String> {
+        if self.peek() == Some(&Token::Open) {
+            self.position += 1;
+            let value = self.or_expression()?;
+            return match self.next() {
+                Some(Token::Close) => Ok(value),
+                _ => Err("expected )".to_owned()),
+            };
+        }
+        let left = self.operand()?;
+        let comparison = match self.next() {
+            Some(Token::Comparison(comparison)) => *comparison,
+            token => return Err(format!("expected a comparison, found {token:?}")),
+        };
+        let right = self.operand()?;
+        Ok(left
+            .zip(right)
+            .map(|(left, right)| comparison.holds(&left, &right)))
+    }
+
+    fn operand(&mut self) -> Result<Option<String>, String> {
+        match self.next() {
+            Some(Token::Literal(literal)) => Ok(Some(literal.clone())),
+            Some(Token::Variable(name)) if name == "ROS_VERSION" => {
+                Ok(self.ros_version.map(|version| {
+                    match version {
+                        RosVersion::ROS1 => "1",
+                        RosVersion::ROS2 => "2",
+                    }
+                    .to_owned()
+                }))
+            }
+            Some(Token::Variable(_)) => Ok(None),
+            token => Err(format!("expected a value, found {token:?}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::evaluate;
+    use crate::utils::RosVersion;
+
+    #[test]
+    fn conditions_are_evaluated_against_the_ros_version() {
+        let ros1 = Some(RosVersion::ROS1);
+        let ros2 = Some(RosVersion::ROS2);
+        assert_eq!(evaluate("$ROS_VERSION == 2", ros2), Ok(Some(true)));
+        assert_eq!(evaluate("$ROS_VERSION == 2", ros1), Ok(Some(false)));
+        assert_eq!(evaluate("$ROS_VERSION != 2", ros1), Ok(Some(true)));
+        assert_eq!(evaluate("$ROS_VERSION == 2", None), Ok(None));
+        assert_eq!(
+            evaluate("$ROS_VERSION == 1 or ($ROS_VERSION >= '2' and 1 < 2)", ros2),
+            Ok(Some(true))
+        );
+        // Other variables are unknown, leaving the condition undecided unless the ROS version settles it
+        assert_eq!(
+            evaluate("$ROS_PYTHON_VERSION == 3 and $ROS_VERSION == 2", ros1),
+            Ok(Some(false))
+        );
+        assert_eq!(
+            evaluate("$ROS_PYTHON_VERSION == 3 and $ROS_VERSION == 2", ros2),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn malformed_conditions_are_errors() {
+        assert!(evaluate("$ROS_VERSION = 2", None).is_err());
+        assert!(evaluate("$ROS_VERSION == 2 and", None).is_err());
+        assert!(evaluate("($ROS_VERSION == 2", None).is_err());
+        assert!(evaluate("$ROS_VERSION == '2", None).is_err());
+        assert!(evaluate("$ROS_VERSION", None).is_err());
+    }
+}
@@ -1,13 +1,16 @@
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote, ToTokens};
 use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, BTreeSet};
 use std::str::FromStr;
 use syn::parse_quote;
 
-use crate::parse::convert_ros_type_to_rust_type;
+use crate::parse::{convert_ros_type_to_rust_type, ParsedActionFile, ParsedMessageFile};
 use crate::utils::RosVersion;
 use crate::{bail, Error};
-use crate::{ConstantInfo, FieldInfo, MessageFile, RosLiteral, ServiceFile};
+use crate::{
+    CodegenOptions, ConstantInfo, FieldInfo, FieldNaming, MessageFile, RosLiteral, ServiceFile,
+};
 
 fn derive_attrs() -> Vec<syn::Attribute> {
     // TODO we should look into using $crate here...
@@ -27,15 +30,18 @@ fn derive_attrs() -> Vec<syn::Attribute> {
 /// Generates the service for a given service file
 /// The service definition defines a struct representing the service an an implementation
 /// of the RosServiceType trait for that struct
-pub fn generate_service(service: ServiceFile) -> Result<TokenStream, Error> {
+pub fn generate_service(
+    service: ServiceFile,
+    options: &CodegenOptions,
+) -> Result<TokenStream, Error> {
     let service_type_name = service.get_full_name();
     let service_md5sum = service.md5sum;
     let struct_name = format_ident!("{}", service.parsed.name);
     let request_name = format_ident!("{}", service.parsed.request_type.name);
     let response_name = format_ident!("{}", service.parsed.response_type.name);
 
-    let request_msg = generate_struct(service.request)?;
-    let response_msg = generate_struct(service.response)?;
+    let request_msg = generate_struct(service.request, options)?;
+    let response_msg = generate_struct(service.response, options)?;
     Ok(quote! {
 
         #request_msg
@@ -53,9 +59,56 @@ pub fn generate_service(service: ServiceFile) -> Result<TokenStream, Error> {
     })
 }
 
-pub fn generate_struct(msg: MessageFile) -> Result<TokenStream, Error> {
+/// Generates the action for a given action file
+/// The action's `Action` message, e.g. `FibonacciAction`, represents the action with an implementation
+/// of the RosActionType trait naming the messages the action expands into.
+/// A struct named after the action itself would clash with a service of the same name, e.g. nav_msgs/GetMap
+pub fn generate_action(action: &ParsedActionFile) -> TokenStream {
+    let action_type_name = format!("{}/{}", action.package, action.name);
+    let struct_name = format_ident!("{}", action.action_type.name);
+    let goal_name = format_ident!("{}", action.goal_type.name);
+    let result_name = format_ident!("{}", action.result_type.name);
+    let feedback_name = format_ident!("{}", action.feedback_type.name);
+    let action_goal_name = format_ident!("{}", action.action_goal_type.name);
+    let action_result_name = format_ident!("{}", action.action_result_type.name);
+    let action_feedback_name = format_ident!("{}", action.action_feedback_type.name);
+    quote! {
+        impl ::roslibrust_codegen::RosActionType for #struct_name {
+            const ROS_ACTION_NAME: &'static str = #action_type_name;
+            type Goal = #goal_name;
+            type Result = #result_name;
+            type Feedback = #feedback_name;
+            type ActionGoal = #action_goal_name;
+            type ActionResult = #action_result_name;
+            type ActionFeedback = #action_feedback_name;
+        }
+    }
+}
+
+pub fn generate_struct(msg: MessageFile, options: &CodegenOptions) -> Result<TokenStream, Error> {
     let ros_type_name = msg.get_full_name();
     let attrs = derive_attrs();
+    let version = msg.parsed.version.unwrap_or(RosVersion::ROS1);
+    let constant_enums = if options.generate_constant_enums {
+        find_constant_enums(&msg.parsed.name, &msg.parsed.constants, version)
+            .into_iter()
+            .map(|group| generate_constant_enum(&msg.parsed.name, group, version))
+            .collect::<Result<Vec<TokenStream>, _>>()?
+    } else {
+        vec![]
+    };
+    check_field_names(&msg.parsed, options.field_naming)?;
+    let builder = if options.generate_builders {
+        generate_builder(
+            &msg.parsed.name,
+            &msg.parsed.fields,
+            &msg.parsed.package,
+            version,
+            options.field_naming,
+        )?
+    } else {
+        TokenStream::new()
+    };
     let fields = msg
         .parsed
         .fields
@@ -65,6 +118,7 @@ pub fn generate_struct(msg: MessageFile) -> Result<TokenStream, Error> {
                 field,
                 &msg.parsed.package,
                 msg.parsed.version.unwrap_or(RosVersion::ROS1),
+                options.field_naming,
             )
         })
         .collect::<Result<Vec<TokenStream>, _>>()?;
@@ -107,11 +161,274 @@ pub fn generate_struct(msg: MessageFile) -> Result<TokenStream, Error> {
             }
         });
     }
+    base.extend(constant_enums);
+    base.extend(builder);
     Ok(base)
 }
 
-fn generate_field_definition(
-    field: FieldInfo,
+/// Generates a `{Message}Builder` type along with a `builder()` constructor on the message
+fn generate_builder(
+    msg_name: &str,
+    fields: &[FieldInfo],
+    msg_pkg: &str,
+    version: RosVersion,
+    naming: FieldNaming,
+) -> Result<TokenStream, Error> {
+    let struct_name = format_ident!("{}", msg_name);
+    let builder_name = format_ident!("{}Builder", msg_name);
+    let setters = fields
+        .iter()
+        .map(|field| {
+            let field_name = format_ident!("r#{}", rust_field_name(&field.field_name, naming));
+            let field_type = rust_field_type(field, msg_pkg, version)?;
+            Ok(quote! {
+                pub fn #field_name(mut self, value: impl ::std::convert::Into<#field_type>) -> Self {
+                    self.inner.#field_name = value.into();
+                    self
+                }
+            })
+        })
+        .collect::<Result<Vec<TokenStream>, Error>>()?;
+    let builder_doc =
+        format!("Builder for [{msg_name}], any field which is not set keeps its default value");
+
+    Ok(quote! {
+        impl #struct_name {
+            pub fn builder() -> #builder_name {
+                #builder_name::default()
+            }
+        }
+
+        #[doc = #builder_doc]
+        #[derive(Default, Debug, Clone)]
+        pub struct #builder_name {
+            inner: #struct_name,
+        }
+
+        impl #builder_name {
+            #(#setters )*
+
+            pub fn build(self) -> #struct_name {
+                self.inner
+            }
+        }
+    })
+}
+
+/// A set of integer constants within a message which together form an enumeration
+#[derive(Debug, PartialEq)]
+struct ConstantEnum {
+    name: String,
+    // Shared name prefix of the constants (e.g. STATUS for STATUS_FIX, STATUS_NO_FIX), if any
+    prefix: Option<String>,
+    ros_type: String,
+    // Pairs of (constant name, variant name)
+    variants: Vec<(String, String)>,
+}
+
+/// Groups the integer constants of a message into enumerations.
+/// Constants are grouped by type, and within a type by the first word of their name when several constants
+/// share it. Groups with fewer than two constants, duplicate values, or values that look like bit flags are
+/// not considered enumerations.
+/// Enums are named after the message and the constants' shared prefix, e.g. `NavSatStatusStatus`,
+/// or `{Message}Enum` when the constants share no prefix, e.g. `GoalStatusEnum`.
+fn find_constant_enums(
+    msg_name: &str,
+    constants: &[ConstantInfo],
+    version: RosVersion,
+) -> Vec<ConstantEnum> {
+    let mut by_type: Vec<(String, Vec<&ConstantInfo>)> = vec![];
+    for constant in constants {
+        let is_integer = matches!(
+            convert_ros_type_to_rust_type(version, &constant.constant_type),
+            Some("i8" | "u8" | "i16" | "u16" | "i32" | "u32" | "i64" | "u64")
+        );
+        if !is_integer {
+            continue;
+        }
+        match by_type
+            .iter_mut()
+            .find(|(ros_type, _)| *ros_type == constant.constant_type)
+        {
+            Some((_, group)) => group.push(constant),
+            None => by_type.push((constant.constant_type.clone(), vec![constant])),
+        }
+    }
+
+    let mut enums = vec![];
+    for (ros_type, group) in by_type {
+        // Split on shared prefixes, anything not sharing a prefix with another constant stays together
+        let mut prefixed: Vec<(String, Vec<&ConstantInfo>)> = vec![];
+        let mut unprefixed = vec![];
+        for constant in &group {
+            let prefix = constant
+                .constant_name
+                .split_once('_')
+                .map(|(prefix, _)| prefix.to_owned());
+            let shared = prefix.filter(|prefix| {
+                group
+                    .iter()
+                    .filter(|other| other.constant_name.starts_with(&format!("{prefix}_")))
+                    .count()
+                    > 1
+            });
+            match shared {
+                Some(prefix) => match prefixed.iter_mut().find(|(p, _)| *p == prefix) {
+                    Some((_, members)) => members.push(*constant),
+                    None => prefixed.push((prefix, vec![*constant])),
+                },
+                None => unprefixed.push(*constant),
+            }
+        }
+        let candidates = prefixed
+            .into_iter()
+            .map(|(prefix, members)| (Some(prefix), members))
+            .chain(std::iter::once((None, unprefixed)));
+        for (prefix, members) in candidates {
+            if let Some(group) = constant_enum_from_group(msg_name, prefix, &ros_type, &members) {
+                enums.push(group);
+            }
+        }
+    }
+    // Groups of different types could end up with the same name, skip those rather than guess
+    let names = enums.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+    enums.retain(|e| names.iter().filter(|name| **name == e.name).count() == 1);
+    enums
+}
+
+fn constant_enum_from_group(
+    msg_name: &str,
+    prefix: Option<String>,
+    ros_type: &str,
+    members: &[&ConstantInfo],
+) -> Option<ConstantEnum> {
+    if members.len() < 2 {
+        return None;
+    }
+    let values = members
+        .iter()
+        .map(|constant| constant.constant_value.inner.trim().parse::<i128>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let mut sorted = values.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+    if sorted.len() != values.len() {
+        // Multiple names for the same value, likely aliases rather than an enumeration
+        return None;
+    }
+    let is_contiguous = sorted.windows(2).all(|pair| pair[1] == pair[0] + 1);
+    let is_power_of_two = |value: &i128| *value > 0 && (*value & (*value - 1)) == 0;
+    if !is_contiguous && values.len() > 2 && values.iter().all(is_power_of_two) {
+        // Looks like a set of bit flags which may be combined, an enum would reject valid values
+        return None;
+    }
+
+    let variants = members
+        .iter()
+        .map(|constant| {
+            let name = constant.constant_name.as_str();
+            let stripped = prefix
+                .as_ref()
+                .and_then(|prefix| name.strip_prefix(&format!("{prefix}_")))
+                // Variant names must not start with a digit
+                .filter(|stripped| !stripped.starts_with(|c: char| c.is_ascii_digit()))
+                .unwrap_or(name);
+            (name.to_owned(), to_upper_camel_case(stripped))
+        })
+        .collect::<Vec<_>>();
+    let mut variant_names = variants.iter().map(|(_, v)| v).collect::<Vec<_>>();
+    variant_names.sort();
+    variant_names.dedup();
+    if variant_names.len() != variants.len() {
+        return None;
+    }
+    let name = match &prefix {
+        Some(prefix) => format!("{msg_name}{}", to_upper_camel_case(prefix)),
+        None => format!("{msg_name}Enum"),
+    };
+    Some(ConstantEnum {
+        name,
+        prefix,
+        ros_type: ros_type.to_owned(),
+        variants,
+    })
+}
+
+fn to_upper_camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => {
+                    first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+                }
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Generates an enum for a group of constants along with conversions to and from the raw integer type.
+fn generate_constant_enum(
+    msg_name: &str,
+    group: ConstantEnum,
+    version: RosVersion,
+) -> Result<TokenStream, Error> {
+    let struct_name = format_ident!("{}", msg_name);
+    let enum_name = format_ident!("{}", group.name);
+    let raw_type = convert_ros_type_to_rust_type(version, &group.ros_type).ok_or(Error::new(
+        format!("No Rust type for constant type {}", group.ros_type),
+    ))?;
+    let raw_type = format_ident!("{}", raw_type);
+    let constant_names = group
+        .variants
+        .iter()
+        .map(|(constant, _)| format_ident!("r#{}", constant))
+        .collect::<Vec<_>>();
+    let variant_names = group
+        .variants
+        .iter()
+        .map(|(_, variant)| format_ident!("{}", variant))
+        .collect::<Vec<_>>();
+    let doc = format!(
+        "Enumeration of the {} constants defined on [{msg_name}]",
+        group
+            .prefix
+            .as_ref()
+            .map(|prefix| format!("`{prefix}_*`"))
+            .unwrap_or_else(|| format!("`{}`", group.ros_type))
+    );
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[repr(#raw_type)]
+        pub enum #enum_name {
+            #(#variant_names = #struct_name::#constant_names, )*
+        }
+
+        impl ::std::convert::TryFrom<#raw_type> for #enum_name {
+            type Error = #raw_type;
+            fn try_from(value: #raw_type) -> Result<Self, Self::Error> {
+                match value {
+                    #(#struct_name::#constant_names => Ok(Self::#variant_names), )*
+                    _ => Err(value),
+                }
+            }
+        }
+
+        impl ::std::convert::From<#enum_name> for #raw_type {
+            fn from(value: #enum_name) -> Self {
+                value as #raw_type
+            }
+        }
+    })
+}
+
+/// Determines the Rust type used to represent a field within a generated struct
+fn rust_field_type(
+    field: &FieldInfo,
     msg_pkg: &str,
     version: RosVersion,
 ) -> Result<TokenStream, Error> {
@@ -131,11 +448,71 @@ fn generate_field_definition(
         Some(_) => format!("::std::vec::Vec<{rust_field_type}>"),
         None => rust_field_type,
     };
-    let rust_field_type = TokenStream::from_str(rust_field_type.as_str()).expect(
-        "Somehow we generate a rust type that isn't valid rust syntax. This should not happen!",
-    );
+    TokenStream::from_str(rust_field_type.as_str()).map_err(|err| {
+        Error::with(
+            format!("Failed to parse {rust_field_type} into valid rust syntax").as_str(),
+            err,
+        )
+    })
+}
+
+/// Determines the name of a field within a generated struct
+fn rust_field_name(field_name: &str, naming: FieldNaming) -> String {
+    match naming {
+        FieldNaming::AsIs => field_name.to_owned(),
+        FieldNaming::SnakeCase => to_snake_case(field_name),
+    }
+}
+
+/// Converts a name to snake_case, e.g. `frameId` -> `frame_id`, `D` -> `d`, `HTTPServer` -> `http_server`
+fn to_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars
+                .get(i + 1)
+                .is_some_and(|next| next.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_is_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
 
-    let field_name = format_ident!("r#{}", field.field_name);
+/// Confirms no two fields of a message end up with the same Rust name under `naming`
+fn check_field_names(msg: &ParsedMessageFile, naming: FieldNaming) -> Result<(), Error> {
+    let mut seen: BTreeMap<String, &str> = BTreeMap::new();
+    for field in &msg.fields {
+        let rust_name = rust_field_name(&field.field_name, naming);
+        if let Some(other) = seen.insert(rust_name.clone(), &field.field_name) {
+            bail!(
+                "Fields {other} and {} of {} both have the Rust name {rust_name} with {naming:?} field naming",
+                field.field_name,
+                msg.get_full_name()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn generate_field_definition(
+    field: FieldInfo,
+    msg_pkg: &str,
+    version: RosVersion,
+    naming: FieldNaming,
+) -> Result<TokenStream, Error> {
+    let rust_field_type = rust_field_type(&field, msg_pkg, version)?;
+    let field_name = format_ident!("r#{}", rust_field_name(&field.field_name, naming));
+    // Always name the field on the wire explicitly so Rust side naming can never change it
+    let wire_name = &field.field_name;
+    let rename = quote! { #[serde(rename = #wire_name)] };
     if let Some(ref default_val) = field.default {
         let default_val = ros_literal_to_rust_literal(
             &field.field_type.field_type,
@@ -146,18 +523,23 @@ fn generate_field_definition(
         if field.field_type.array_info.is_some() {
             // For vectors use smart_defaults "dynamic" style
             Ok(quote! {
+                #rename
                 #[default(_code = #default_val)]
                 pub #field_name: #rust_field_type,
             })
         } else {
             // For non vectors use smart_default's constant style
             Ok(quote! {
+              #rename
               #[default(#default_val)]
               pub #field_name: #rust_field_type,
             })
         }
     } else {
-        Ok(quote! { pub #field_name: #rust_field_type, })
+        Ok(quote! {
+            #rename
+            pub #field_name: #rust_field_type,
+        })
     }
 }
 
@@ -191,6 +573,169 @@ fn generate_constant_field_definition(
     Ok(quote! { pub const #constant_name: #constant_rust_type = #constant_value; })
 }
 
+/// Key used to decide whether a field has the same representation in ROS1 and ROS2.
+/// Builtin types compare by their Rust type, so ROS1 `time` matches ROS2 `builtin_interfaces/Time`.
+/// Message types compare by full name and are converted recursively.
+fn conversion_type_key(field: &FieldInfo, version: RosVersion) -> Option<(String, bool)> {
+    let field_type = &field.field_type;
+    let key = match field_type.package_name {
+        Some(ref pkg) => (format!("{pkg}/{}", field_type.field_type), true),
+        None => (
+            convert_ros_type_to_rust_type(version, &field_type.field_type)?.to_owned(),
+            false,
+        ),
+    };
+    Some(key)
+}
+
+/// Whether `ros1` and `ros2` can be converted between: the field names of one version must be a subset of
+/// the other's (e.g. ROS2 dropped `seq` from `std_msgs/Header`), and all shared fields must have the same
+/// type, with nested messages themselves in `convertible`.
+fn conversion_compatible(
+    ros1: &ParsedMessageFile,
+    ros2: &ParsedMessageFile,
+    convertible: &BTreeSet<String>,
+) -> bool {
+    let find = |msg: &ParsedMessageFile, name: &str| {
+        msg.fields
+            .iter()
+            .find(|field| field.field_name == name)
+            .cloned()
+    };
+    let ros1_in_ros2 = ros1
+        .fields
+        .iter()
+        .all(|field| find(ros2, &field.field_name).is_some());
+    let ros2_in_ros1 = ros2
+        .fields
+        .iter()
+        .all(|field| find(ros1, &field.field_name).is_some());
+    if !ros1_in_ros2 && !ros2_in_ros1 {
+        return false;
+    }
+
+    ros1.fields.iter().all(|ros1_field| {
+        let Some(ros2_field) = find(ros2, &ros1_field.field_name) else {
+            return true;
+        };
+        let ros1_key = conversion_type_key(ros1_field, RosVersion::ROS1);
+        let ros2_key = conversion_type_key(&ros2_field, RosVersion::ROS2);
+        match (ros1_key, ros2_key) {
+            (Some((ros1_key, is_msg)), Some((ros2_key, _))) => {
+                ros1_key == ros2_key
+                    && ros1_field.field_type.array_info.is_some()
+                        == ros2_field.field_type.array_info.is_some()
+                    && (!is_msg || convertible.contains(&ros1_key))
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Determines the full names of all messages present in both `ros1` and `ros2` which can be converted
+/// between. Messages are only convertible if every message they contain is also convertible.
+pub fn find_convertible_messages(
+    ros1: &[&ParsedMessageFile],
+    ros2: &[&ParsedMessageFile],
+) -> BTreeSet<String> {
+    let ros2_by_name = ros2
+        .iter()
+        .map(|msg| (msg.get_full_name(), *msg))
+        .collect::<BTreeMap<_, _>>();
+    let pairs = ros1
+        .iter()
+        .filter_map(|msg| {
+            ros2_by_name
+                .get(&msg.get_full_name())
+                .map(|ros2_msg| (*msg, *ros2_msg))
+        })
+        .collect::<Vec<_>>();
+
+    // Start optimistic and remove pairs until nothing changes, so nested messages resolve in any order
+    let mut convertible = pairs
+        .iter()
+        .map(|(msg, _)| msg.get_full_name())
+        .collect::<BTreeSet<_>>();
+    loop {
+        let incompatible = pairs
+            .iter()
+            .filter(|(ros1_msg, ros2_msg)| {
+                convertible.contains(&ros1_msg.get_full_name())
+                    && !conversion_compatible(ros1_msg, ros2_msg, &convertible)
+            })
+            .map(|(msg, _)| msg.get_full_name())
+            .collect::<Vec<_>>();
+        if incompatible.is_empty() {
+            return convertible;
+        }
+        for name in incompatible {
+            log::debug!("{name} differs between ROS1 and ROS2, no conversion will be generated");
+            convertible.remove(&name);
+        }
+    }
+}
+
+/// Generates `From` impls in both directions between the ROS1 and ROS2 versions of a message.
+/// `ros1_module` and `ros2_module` are the paths the two sets of generated messages were placed at.
+/// The pair must have been found convertible by [find_convertible_messages].
+pub fn generate_conversions(
+    ros1: &ParsedMessageFile,
+    ros2: &ParsedMessageFile,
+    ros1_module: &TokenStream,
+    ros2_module: &TokenStream,
+    naming: FieldNaming,
+) -> TokenStream {
+    let pkg = format_ident!("{}", ros1.package);
+    let msg = format_ident!("{}", ros1.name);
+    let ros1_type = quote! { #ros1_module::#pkg::#msg };
+    let ros2_type = quote! { #ros2_module::#pkg::#msg };
+    let ros1_to_ros2 = generate_conversion(ros1, ros2, &ros1_type, &ros2_type, naming);
+    let ros2_to_ros1 = generate_conversion(ros2, ros1, &ros2_type, &ros1_type, naming);
+    quote! {
+        #ros1_to_ros2
+        #ros2_to_ros1
+    }
+}
+
+fn generate_conversion(
+    from: &ParsedMessageFile,
+    to: &ParsedMessageFile,
+    from_type: &TokenStream,
+    to_type: &TokenStream,
+    naming: FieldNaming,
+) -> TokenStream {
+    let in_from = |field: &FieldInfo| from.fields.iter().any(|f| f.field_name == field.field_name);
+    let fields = to.fields.iter().map(|field| {
+        let field_name = format_ident!("r#{}", rust_field_name(&field.field_name, naming));
+        let value = if !in_from(field) {
+            // Only present in this version, e.g. the seq of a ROS1 header
+            quote! { ::std::default::Default::default() }
+        } else if field.field_type.package_name.is_none() {
+            quote! { value.#field_name }
+        } else if field.field_type.array_info.is_some() {
+            quote! { value.#field_name.into_iter().map(::std::convert::Into::into).collect() }
+        } else {
+            quote! { value.#field_name.into() }
+        };
+        quote! { #field_name: #value, }
+    });
+    // Messages without any shared fields would otherwise leave the argument unused
+    let value = if !to.fields.iter().any(in_from) {
+        format_ident!("_value")
+    } else {
+        format_ident!("value")
+    };
+    quote! {
+        impl ::std::convert::From<#from_type> for #to_type {
+            fn from(#value: #from_type) -> Self {
+                Self {
+                    #(#fields )*
+                }
+            }
+        }
+    }
+}
+
 pub fn generate_mod(
     pkg_name: String,
     struct_definitions: Vec<TokenStream>,
@@ -316,3 +861,197 @@ fn parse_ros_value(
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn constant(constant_type: &str, constant_name: &str, value: &str) -> ConstantInfo {
+        ConstantInfo {
+            constant_type: constant_type.to_owned(),
+            constant_name: constant_name.to_owned(),
+            constant_value: value.to_owned().into(),
+        }
+    }
+
+    #[test_log::test]
+    fn constant_enums_grouped_by_prefix() {
+        // Mirrors sensor_msgs/NavSatStatus
+        let constants = vec![
+            constant("int8", "STATUS_NO_FIX", "-1"),
+            constant("int8", "STATUS_FIX", "0"),
+            constant("int8", "STATUS_SBAS_FIX", "1"),
+            constant("int8", "STATUS_GBAS_FIX", "2"),
+            constant("uint16", "SERVICE_GPS", "1"),
+            constant("uint16", "SERVICE_GLONASS", "2"),
+            constant("uint16", "SERVICE_COMPASS", "4"),
+            constant("uint16", "SERVICE_GALILEO", "8"),
+        ];
+        let enums = find_constant_enums("NavSatStatus", &constants, RosVersion::ROS1);
+        // SERVICE_* are bit flags and must not become an enum
+        assert_eq!(enums.len(), 1);
+        assert_eq!(enums[0].name, "NavSatStatusStatus");
+        assert_eq!(
+            enums[0].variants,
+            vec![
+                ("STATUS_NO_FIX".to_owned(), "NoFix".to_owned()),
+                ("STATUS_FIX".to_owned(), "Fix".to_owned()),
+                ("STATUS_SBAS_FIX".to_owned(), "SbasFix".to_owned()),
+                ("STATUS_GBAS_FIX".to_owned(), "GbasFix".to_owned()),
+            ]
+        );
+    }
+
+    #[test_log::test]
+    fn constant_enums_without_prefix() {
+        // Subset of actionlib_msgs/GoalStatus
+        let constants = vec![
+            constant("uint8", "PENDING", "0"),
+            constant("uint8", "ACTIVE", "1"),
+            constant("uint8", "PREEMPTED", "2"),
+            constant("string", "NAME", "status"),
+        ];
+        let enums = find_constant_enums("GoalStatus", &constants, RosVersion::ROS1);
+        assert_eq!(enums.len(), 1);
+        assert_eq!(enums[0].name, "GoalStatusEnum");
+        assert_eq!(enums[0].variants.len(), 3);
+
+        // Aliased values are not an enumeration
+        let constants = vec![
+            constant("int32", "ADD", "0"),
+            constant("int32", "MODIFY", "0"),
+            constant("int32", "DELETE", "2"),
+        ];
+        assert!(find_constant_enums("Marker", &constants, RosVersion::ROS1).is_empty());
+    }
+
+    fn parse_msg(source: &str, pkg: &str, name: &str, version: RosVersion) -> ParsedMessageFile {
+        let package = crate::utils::Package {
+            name: pkg.to_owned(),
+            path: std::path::PathBuf::new(),
+            version: Some(version),
+            ..Default::default()
+        };
+        crate::parse::parse_ros_message_file(source, name, &package, std::path::Path::new(name))
+            .unwrap()
+    }
+
+    #[test_log::test]
+    fn ros1_ros2_conversions() {
+        let ros1_header = parse_msg(
+            "uint32 seq\ntime stamp\nstring frame_id\n",
+            "std_msgs",
+            "Header",
+            RosVersion::ROS1,
+        );
+        let ros2_header = parse_msg(
+            "builtin_interfaces/Time stamp\nstring frame_id\n",
+            "std_msgs",
+            "Header",
+            RosVersion::ROS2,
+        );
+        let ros1_stamped = parse_msg(
+            "Header header\nfloat64[] data\n",
+            "test_msgs",
+            "Stamped",
+            RosVersion::ROS1,
+        );
+        let ros2_stamped = parse_msg(
+            "std_msgs/Header header\nfloat64[] data\n",
+            "test_msgs",
+            "Stamped",
+            RosVersion::ROS2,
+        );
+        // Same field names with different types are not convertible
+        let ros1_changed = parse_msg("int32 value\n", "test_msgs", "Changed", RosVersion::ROS1);
+        let ros2_changed = parse_msg("int64 value\n", "test_msgs", "Changed", RosVersion::ROS2);
+
+        let convertible = find_convertible_messages(
+            &[&ros1_header, &ros1_stamped, &ros1_changed],
+            &[&ros2_header, &ros2_stamped, &ros2_changed],
+        );
+        assert_eq!(
+            convertible.into_iter().collect::<Vec<_>>(),
+            vec!["std_msgs/Header", "test_msgs/Stamped"]
+        );
+
+        // A message is only convertible if the messages it contains are
+        let convertible = find_convertible_messages(&[&ros1_stamped], &[&ros2_stamped]);
+        assert!(convertible.is_empty());
+
+        let source = generate_conversions(
+            &ros1_header,
+            &ros2_header,
+            &quote! { crate::ros1 },
+            &quote! { crate::ros2 },
+            FieldNaming::AsIs,
+        )
+        .to_string();
+        assert!(source.contains(
+            "From < crate :: ros1 :: std_msgs :: Header > for crate :: ros2 :: std_msgs :: Header"
+        ));
+        assert!(source.contains("r#seq : :: std :: default :: Default :: default ()"));
+    }
+
+    #[test_log::test]
+    fn snake_case_field_names() {
+        assert_eq!(to_snake_case("frame_id"), "frame_id");
+        assert_eq!(to_snake_case("D"), "d");
+        assert_eq!(to_snake_case("frameId"), "frame_id");
+        assert_eq!(to_snake_case("HTTPServer"), "http_server");
+        assert_eq!(to_snake_case("point2D"), "point2_d");
+
+        // The wire name is kept regardless of the Rust name
+        let msg = parse_msg(
+            "float64[] D\n",
+            "sensor_msgs",
+            "CameraInfo",
+            RosVersion::ROS1,
+        );
+        let field = msg.fields[0].clone();
+        let source = generate_field_definition(
+            field,
+            "sensor_msgs",
+            RosVersion::ROS1,
+            FieldNaming::SnakeCase,
+        )
+        .unwrap()
+        .to_string();
+        assert_eq!(
+            source,
+            "# [serde (rename = \"D\")] pub r#d : :: std :: vec :: Vec < f64 > ,"
+        );
+
+        let msg = parse_msg(
+            "float64[] D\nfloat64 d\n",
+            "test_msgs",
+            "Clash",
+            RosVersion::ROS1,
+        );
+        assert!(check_field_names(&msg, FieldNaming::AsIs).is_ok());
+        assert!(check_field_names(&msg, FieldNaming::SnakeCase).is_err());
+    }
+
+    #[test_log::test]
+    fn actions_name_their_messages() {
+        let package = crate::utils::Package {
+            name: "actionlib_tutorials".to_owned(),
+            path: std::path::PathBuf::new(),
+            version: Some(RosVersion::ROS1),
+            ..Default::default()
+        };
+        let action = crate::parse::parse_ros_action_file(
+            "int32 order\n---\nint32[] sequence\n---\nint32[] sequence\n",
+            "Fibonacci",
+            &package,
+            std::path::Path::new("Fibonacci.action"),
+        )
+        .unwrap();
+        let source = generate_action(&action).to_string();
+        assert!(source.contains("RosActionType for FibonacciAction"));
+        assert!(source.contains("\"actionlib_tutorials/Fibonacci\""));
+        assert!(source.contains("type Goal = FibonacciGoal ;"));
+        assert!(source.contains("type ActionGoal = FibonacciActionGoal ;"));
+        assert!(source.contains("type ActionFeedback = FibonacciActionFeedback ;"));
+    }
+}
@@ -4,17 +4,29 @@ use quote::quote;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use simple_error::{bail, SimpleError as Error};
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::fmt::{Debug, Display};
 use std::path::PathBuf;
+use std::time::Instant;
 use utils::Package;
 
 mod gen;
 use gen::*;
 mod parse;
 use parse::*;
+mod condition;
+mod report;
+pub use report::CodegenReport;
+mod message_crate;
+pub use message_crate::{MessageCrate, MessageCrateOptions};
 pub mod utils;
 use utils::RosVersion;
+#[cfg(feature = "watch")]
+mod watch;
+#[cfg(feature = "watch")]
+pub use watch::{
+    watch_and_generate, watch_and_generate_with_options, watch_for_changes, GENERATED_FILE,
+};
 
 pub mod integral_types;
 pub use integral_types::*;
@@ -57,6 +69,73 @@ pub trait RosServiceType {
     type Response: RosMessageType;
 }
 
+/// Fundamental traits for action types this crate works with
+/// This trait will be satisfied for any action definitions generated with this crate's message_gen functionality,
+/// tying together the messages each action file expands into.
+/// It is implemented by the `Action` message of each action, e.g. `actionlib_tutorials::FibonacciAction`
+pub trait RosActionType {
+    /// Name of the ros action e.g. `actionlib_tutorials/Fibonacci`
+    const ROS_ACTION_NAME: &'static str;
+    type Goal: RosMessageType;
+    type Result: RosMessageType;
+    type Feedback: RosMessageType;
+    /// The goal along with its id, as sent on the action's `goal` topic
+    type ActionGoal: RosMessageType;
+    /// The result along with the final status of its goal, as sent on the action's `result` topic
+    type ActionResult: RosMessageType;
+    /// The feedback along with the current status of its goal, as sent on the action's `feedback` topic
+    type ActionFeedback: RosMessageType;
+}
+
+/// Optional behaviors for code generation.
+/// The default options produce the same output as the top level generation functions.
+#[derive(Clone, Debug, Default)]
+pub struct CodegenOptions {
+    pub(crate) generate_constant_enums: bool,
+    pub(crate) generate_builders: bool,
+    pub(crate) field_naming: FieldNaming,
+}
+
+/// Naming convention used for the Rust fields of generated messages.
+/// Regardless of the convention, fields are always serialized under their original ROS name.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FieldNaming {
+    /// Fields keep the exact name from the message definition, e.g. `sensor_msgs/CameraInfo` has a field `D`
+    #[default]
+    AsIs,
+    /// Fields are converted to snake_case, e.g. `D` becomes `d` and `frameId` becomes `frame_id`
+    SnakeCase,
+}
+
+impl CodegenOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// When enabled, integer constants within a message that form an enumeration (e.g. the states of
+    /// `actionlib_msgs/GoalStatus`) additionally generate a Rust enum with `TryFrom` / `Into` conversions
+    /// to the raw integer type. Message fields keep their raw integer types so wire compatibility is unaffected.
+    pub fn generate_constant_enums(mut self, enabled: bool) -> Self {
+        self.generate_constant_enums = enabled;
+        self
+    }
+
+    /// When enabled, each message additionally gets a `::builder()` constructor returning a `{Message}Builder`
+    /// which starts from the message's default value and has a setter per field.
+    /// Constructing messages through the builder keeps code compiling when fields are added to a message.
+    pub fn generate_builders(mut self, enabled: bool) -> Self {
+        self.generate_builders = enabled;
+        self
+    }
+
+    /// Selects the naming convention of the generated Rust fields, see [FieldNaming].
+    /// Generation fails if converting the names of a message's fields would make two of them collide.
+    pub fn field_naming(mut self, naming: FieldNaming) -> Self {
+        self.field_naming = naming;
+        self
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct MessageFile {
     pub(crate) parsed: ParsedMessageFile,
@@ -137,13 +216,7 @@ impl MessageFile {
             if is_intrinsic_type(parsed.version.unwrap_or(RosVersion::ROS1), field_type) {
                 md5sum_content.push_str(&format!("{} {}\n", field.field_type, field.field_name));
             } else {
-                let field_package = field
-                    .field_type
-                    .package_name
-                    .as_ref()
-                    .expect(&format!("Expected package name for field {field:#?}"));
-                let field_full_name = format!("{field_package}/{field_type}");
-                let sub_message = graph.get(field_full_name.as_str())?;
+                let sub_message = graph.get(field.get_full_name()?.as_str())?;
                 let sub_md5sum = Self::compute_md5sum(&sub_message.parsed, graph)?;
                 md5sum_content.push_str(&format!("{} {}\n", sub_md5sum, field.field_name));
             }
@@ -167,7 +240,7 @@ impl MessageFile {
                     return Some(false);
                 }
             } else {
-                let field_msg = graph.get(field.get_full_name().as_str())?;
+                let field_msg = graph.get(field.get_full_name()?.as_str())?;
                 let field_is_fixed_length =
                     Self::determine_if_fixed_length(&field_msg.parsed, graph)?;
                 if !field_is_fixed_length {
@@ -309,13 +382,10 @@ impl PartialEq for FieldInfo {
 }
 
 impl FieldInfo {
-    pub fn get_full_name(&self) -> String {
-        let field_package = self
-            .field_type
-            .package_name
-            .as_ref()
-            .expect(&format!("Expected package name for field {self:#?}"));
-        format!("{field_package}/{}", self.field_type.field_type)
+    /// Returns the full name of the message type of this field, or None if the field is of a builtin type
+    pub fn get_full_name(&self) -> Option<String> {
+        let field_package = self.field_type.package_name.as_ref()?;
+        Some(format!("{field_package}/{}", self.field_type.field_type))
     }
 }
 
@@ -346,9 +416,28 @@ impl PartialEq for ConstantInfo {
 pub fn find_and_generate_ros_messages(
     additional_search_paths: Vec<PathBuf>,
 ) -> Result<(TokenStream, Vec<PathBuf>), Error> {
+    find_and_generate_ros_messages_with_options(additional_search_paths, &CodegenOptions::default())
+}
+
+/// Identical to [find_and_generate_ros_messages], but generates code according to `options`.
+pub fn find_and_generate_ros_messages_with_options(
+    additional_search_paths: Vec<PathBuf>,
+    options: &CodegenOptions,
+) -> Result<(TokenStream, Vec<PathBuf>), Error> {
+    let (source, dependent_paths, _report) =
+        find_and_generate_ros_messages_with_report(additional_search_paths, options)?;
+    Ok((source, dependent_paths))
+}
+
+/// Identical to [find_and_generate_ros_messages_with_options], but additionally returns a [CodegenReport]
+/// describing what was found and how long each stage of generation took.
+pub fn find_and_generate_ros_messages_with_report(
+    additional_search_paths: Vec<PathBuf>,
+    options: &CodegenOptions,
+) -> Result<(TokenStream, Vec<PathBuf>, CodegenReport), Error> {
     let mut ros_package_paths = utils::get_search_paths();
     ros_package_paths.extend(additional_search_paths);
-    find_and_generate_ros_messages_without_ros_package_path(ros_package_paths)
+    find_and_generate_ros_messages_without_ros_package_path_with_report(ros_package_paths, options)
 }
 
 /// Searches a list of paths for ROS packages and generates struct definitions
@@ -361,20 +450,218 @@ pub fn find_and_generate_ros_messages(
 pub fn find_and_generate_ros_messages_without_ros_package_path(
     search_paths: Vec<PathBuf>,
 ) -> Result<(TokenStream, Vec<PathBuf>), Error> {
-    let (messages, services, actions) = find_and_parse_ros_messages(&search_paths)?;
+    find_and_generate_ros_messages_without_ros_package_path_with_options(
+        search_paths,
+        &CodegenOptions::default(),
+    )
+}
+
+/// Identical to [find_and_generate_ros_messages_without_ros_package_path], but generates code according to `options`.
+pub fn find_and_generate_ros_messages_without_ros_package_path_with_options(
+    search_paths: Vec<PathBuf>,
+    options: &CodegenOptions,
+) -> Result<(TokenStream, Vec<PathBuf>), Error> {
+    let (source, dependent_paths, _report) =
+        find_and_generate_ros_messages_without_ros_package_path_with_report(search_paths, options)?;
+    Ok((source, dependent_paths))
+}
+
+/// Identical to [find_and_generate_ros_messages_without_ros_package_path_with_options], but additionally
+/// returns a [CodegenReport] describing what was found and how long each stage of generation took.
+pub fn find_and_generate_ros_messages_without_ros_package_path_with_report(
+    search_paths: Vec<PathBuf>,
+    options: &CodegenOptions,
+) -> Result<(TokenStream, Vec<PathBuf>, CodegenReport), Error> {
+    let mut report = CodegenReport::default();
+    let (messages, services, actions) = search_and_parse(&search_paths, &mut report)?;
 
     if messages.is_empty() && services.is_empty() {
         // I'm considering this an error for now, but I could see this one being debateable
         // As it stands there is not good way for us to manually produce a warning, so I'd rather fail loud
         bail!("Failed to find any services or messages while generating ROS message definitions, paths searched: {search_paths:?}");
     }
+    let start = Instant::now();
     let (messages, services) = resolve_dependency_graph(messages, services)?;
+    report.resolve_time = start.elapsed();
+    report.generated_messages = messages.len();
+    report.generated_services = services.len();
+    let dependent_paths = dependent_paths(&messages, &services, &actions);
+
+    let start = Instant::now();
+    let source = generate_definitions(messages, services, &actions, options)?;
+    report.generate_time = start.elapsed();
+    debug!("{report}");
+    Ok((source, dependent_paths, report))
+}
+
+/// Searches a list of paths for ROS packages and generates a complete crate holding their messages and services,
+/// ready to be versioned and published, instead of source to be included into an existing crate.
+/// Each ROS package becomes a module in its own file, behind a cargo feature of the same name which enables
+/// the features of the packages it depends on, see [MessageCrateOptions] for the crate's metadata.
+/// Like [find_and_generate_ros_messages_without_ros_package_path] only `search_paths` are searched,
+/// so the generated crate doesn't depend on the environment it was generated in.
+/// Returns a tuple of the generated crate and list of file system paths that if
+/// modified would trigger re-generation of the crate.
+/// ```no_run
+/// let options = roslibrust_codegen::MessageCrateOptions::new("my_msgs", "0.1.0")
+///     .description("Messages of my robots");
+/// let (message_crate, _dependent_paths) = roslibrust_codegen::find_and_generate_ros_message_crate(
+///     vec!["assets/ros1_common_interfaces/std_msgs".into()],
+///     &options,
+/// )
+/// .unwrap();
+/// message_crate.write_to("my_msgs").unwrap();
+/// ```
+pub fn find_and_generate_ros_message_crate(
+    search_paths: Vec<PathBuf>,
+    options: &MessageCrateOptions,
+) -> Result<(MessageCrate, Vec<PathBuf>), Error> {
+    let (messages, services, actions) =
+        search_and_parse(&search_paths, &mut CodegenReport::default())?;
+    if messages.is_empty() && services.is_empty() {
+        bail!("Failed to find any services or messages while generating a ROS message crate, paths searched: {search_paths:?}");
+    }
+    let (messages, services) = resolve_dependency_graph(messages, services)?;
+    let dependent_paths = dependent_paths(&messages, &services, &actions);
+    let dependencies = package_dependencies(&messages, &services);
+    let packages = generate_package_definitions(messages, services, &actions, &options.codegen)?;
+    Ok((
+        message_crate::generate_crate(packages, &dependencies, options),
+        dependent_paths,
+    ))
+}
+
+// The files generation depends on
+fn dependent_paths(
+    messages: &[MessageFile],
+    services: &[ServiceFile],
+    actions: &[ParsedActionFile],
+) -> Vec<PathBuf> {
     let msg_iter = messages.iter().map(|m| m.parsed.path.clone());
     let srv_iter = services.iter().map(|s| s.parsed.path.clone());
     let action_iter = actions.iter().map(|a| a.path.clone());
-    let dependent_paths = msg_iter.chain(srv_iter).chain(action_iter).collect();
-    let source = generate_rust_ros_message_definitions(messages, services)?;
-    Ok((source, dependent_paths))
+    msg_iter.chain(srv_iter).chain(action_iter).collect()
+}
+
+// The other packages whose messages each package's messages and services contain
+fn package_dependencies(
+    messages: &[MessageFile],
+    services: &[ServiceFile],
+) -> BTreeMap<String, BTreeSet<String>> {
+    let mut dependencies: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let service_messages = services
+        .iter()
+        .flat_map(|srv| [&srv.request.parsed, &srv.response.parsed]);
+    for msg in messages
+        .iter()
+        .map(|msg| &msg.parsed)
+        .chain(service_messages)
+    {
+        let entry = dependencies.entry(msg.package.clone()).or_default();
+        entry.extend(
+            msg.fields
+                .iter()
+                .filter_map(|field| field.field_type.package_name.clone())
+                .filter(|pkg| *pkg != msg.package),
+        );
+    }
+    dependencies
+}
+
+/// Searches two sets of paths holding the ROS1 and ROS2 versions of the same packages and generates
+/// `From` conversions in both directions for every message present in both whose fields are compatible.
+/// Fields only present in one version are dropped or defaulted, e.g. the `seq` of `std_msgs/Header`.
+/// Returns a tuple of the generated source code and list of file system paths that if
+/// modified would trigger re-generation of the source.
+///
+/// The messages themselves are not generated, they are expected to already be generated separately
+/// for each version, e.g. with [find_and_generate_ros_messages_without_ros_package_path].
+/// * `ros1_search_paths` - Paths to search for the ROS1 packages.
+/// * `ros1_module` - Rust path the ROS1 messages were generated into, e.g. `crate::ros1`.
+/// * `ros2_search_paths` - Paths to search for the ROS2 packages.
+/// * `ros2_module` - Rust path the ROS2 messages were generated into, e.g. `crate::ros2`.
+pub fn find_and_generate_ros1_ros2_conversions(
+    ros1_search_paths: Vec<PathBuf>,
+    ros1_module: &str,
+    ros2_search_paths: Vec<PathBuf>,
+    ros2_module: &str,
+) -> Result<(TokenStream, Vec<PathBuf>), Error> {
+    find_and_generate_ros1_ros2_conversions_with_options(
+        ros1_search_paths,
+        ros1_module,
+        ros2_search_paths,
+        ros2_module,
+        &CodegenOptions::default(),
+    )
+}
+
+/// Identical to [find_and_generate_ros1_ros2_conversions], for messages which were generated with `options`.
+pub fn find_and_generate_ros1_ros2_conversions_with_options(
+    ros1_search_paths: Vec<PathBuf>,
+    ros1_module: &str,
+    ros2_search_paths: Vec<PathBuf>,
+    ros2_module: &str,
+    options: &CodegenOptions,
+) -> Result<(TokenStream, Vec<PathBuf>), Error> {
+    let parse_module = |module: &str| {
+        module
+            .parse::<TokenStream>()
+            .map_err(|err| Error::with(format!("{module} is not a valid Rust path").as_str(), err))
+    };
+    let ros1_module = parse_module(ros1_module)?;
+    let ros2_module = parse_module(ros2_module)?;
+
+    let (ros1_messages, ros1_services, _) = find_and_parse_ros_messages(&ros1_search_paths)?;
+    let (ros2_messages, ros2_services, _) = find_and_parse_ros_messages(&ros2_search_paths)?;
+    let dependent_paths = ros1_messages
+        .iter()
+        .chain(ros2_messages.iter())
+        .map(|msg| msg.path.clone())
+        .chain(
+            ros1_services
+                .iter()
+                .chain(ros2_services.iter())
+                .map(|srv| srv.path.clone()),
+        )
+        .collect();
+
+    let ros1 = messages_and_service_types(&ros1_messages, &ros1_services);
+    let ros2 = messages_and_service_types(&ros2_messages, &ros2_services);
+
+    let convertible = find_convertible_messages(&ros1, &ros2);
+    let conversions = ros1
+        .iter()
+        .filter(|msg| convertible.contains(&msg.get_full_name()))
+        .filter_map(|ros1_msg| {
+            let ros2_msg = ros2
+                .iter()
+                .find(|msg| msg.get_full_name() == ros1_msg.get_full_name())?;
+            Some(generate_conversions(
+                ros1_msg,
+                ros2_msg,
+                &ros1_module,
+                &ros2_module,
+                options.field_naming,
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    Ok((quote! { #(#conversions)* }, dependent_paths))
+}
+
+// Service requests and responses are generated as plain messages so can be converted the same way
+fn messages_and_service_types<'a>(
+    messages: &'a [ParsedMessageFile],
+    services: &'a [ParsedServiceFile],
+) -> Vec<&'a ParsedMessageFile> {
+    messages
+        .iter()
+        .chain(
+            services
+                .iter()
+                .flat_map(|srv| [&srv.request_type, &srv.response_type]),
+        )
+        .collect()
 }
 
 /// Searches a list of paths for ROS packages to find their associated message
@@ -394,12 +681,27 @@ pub fn find_and_parse_ros_messages(
     ),
     Error,
 > {
+    search_and_parse(search_paths, &mut CodegenReport::default())
+}
+
+type ParsedFiles = (
+    Vec<ParsedMessageFile>,
+    Vec<ParsedServiceFile>,
+    Vec<ParsedActionFile>,
+);
+
+// Implementation of find_and_parse_ros_messages which records its progress in `report`
+fn search_and_parse(
+    search_paths: &[PathBuf],
+    report: &mut CodegenReport,
+) -> Result<ParsedFiles, Error> {
+    let start = Instant::now();
     let search_paths  = search_paths
-        .into_iter()
+        .iter()
         .map(|path| {
             path.canonicalize().map_err(
             |e| {
-                    Error::with(format!("Codegen was instructed to search a path that could not be canonicalized relative to {:?}: {path:?}", std::env::current_dir().unwrap()).as_str(), e)
+                    Error::with(format!("Codegen was instructed to search a path that could not be canonicalized relative to {:?}: {path:?}", std::env::current_dir().unwrap_or_default()).as_str(), e)
         })
         })
         .collect::<Result<Vec<_>, Error>>()?;
@@ -407,13 +709,18 @@ pub fn find_and_parse_ros_messages(
         "Codegen is looking in following paths for files: {:?}",
         &search_paths
     );
-    let packages = utils::crawl(&search_paths);
+    report.search_paths = search_paths.clone();
+    let packages = utils::crawl(&search_paths)
+        .map_err(|err| Error::with("Codegen failed while searching for ROS packages:", err))?;
     // Check for duplicate package names
+    let found_count = packages.len();
     let packages = utils::deduplicate_packages(packages);
+    report.duplicate_packages = found_count - packages.len();
+    report.packages = packages.iter().map(|pkg| pkg.name.clone()).collect();
     if packages.is_empty() {
         bail!(
             "No ROS packages found while searching in: {search_paths:?}, relative to {:?}",
-            std::env::current_dir().unwrap()
+            std::env::current_dir().unwrap_or_default()
         );
     }
 
@@ -436,8 +743,16 @@ pub fn find_and_parse_ros_messages(
             }
         })
         .collect::<Result<Vec<(Package, PathBuf)>, Error>>()?;
-
-    parse_ros_files(message_files)
+    report.search_time = start.elapsed();
+
+    let start = Instant::now();
+    let parsed = parse_ros_files(message_files)?;
+    report.parse_time = start.elapsed();
+    report.service_files = parsed.1.len();
+    report.action_files = parsed.2.len();
+    // Each action expands into seven messages
+    report.message_files = parsed.0.len() - 7 * report.action_files;
+    Ok(parsed)
 }
 
 /// Takes in collections of ROS message and ROS service data and generates Rust
@@ -452,6 +767,54 @@ pub fn generate_rust_ros_message_definitions(
     messages: Vec<MessageFile>,
     services: Vec<ServiceFile>,
 ) -> Result<TokenStream, Error> {
+    generate_rust_ros_message_definitions_with_options(
+        messages,
+        services,
+        &CodegenOptions::default(),
+    )
+}
+
+/// Identical to [generate_rust_ros_message_definitions], but generates code according to `options`.
+pub fn generate_rust_ros_message_definitions_with_options(
+    messages: Vec<MessageFile>,
+    services: Vec<ServiceFile>,
+    options: &CodegenOptions,
+) -> Result<TokenStream, Error> {
+    generate_definitions(messages, services, &[], options)
+}
+
+// Generates the messages and services, along with the actions tying together messages among them
+fn generate_definitions(
+    messages: Vec<MessageFile>,
+    services: Vec<ServiceFile>,
+    actions: &[ParsedActionFile],
+    options: &CodegenOptions,
+) -> Result<TokenStream, Error> {
+    let modules_to_struct_definitions =
+        generate_package_definitions(messages, services, actions, options)?;
+    // Now generate modules to wrap all of the TokenStreams in a module for each package
+    let all_pkgs = modules_to_struct_definitions
+        .keys()
+        .cloned()
+        .collect::<Vec<String>>();
+    let module_definitions = modules_to_struct_definitions
+        .into_iter()
+        .map(|(pkg, struct_defs)| generate_mod(pkg, struct_defs, &all_pkgs[..]))
+        .collect::<Vec<_>>();
+
+    Ok(quote! {
+        #(#module_definitions)*
+
+    })
+}
+
+// Generates the definitions of the messages, services and actions of each package, keyed by package
+fn generate_package_definitions(
+    messages: Vec<MessageFile>,
+    services: Vec<ServiceFile>,
+    actions: &[ParsedActionFile],
+    options: &CodegenOptions,
+) -> Result<BTreeMap<String, Vec<TokenStream>>, Error> {
     let mut modules_to_struct_definitions: BTreeMap<String, Vec<TokenStream>> = BTreeMap::new();
 
     // Convert messages files into rust token streams and insert them into BTree organized by package
@@ -459,7 +822,7 @@ pub fn generate_rust_ros_message_definitions(
         .into_iter()
         .map(|message| {
             let pkg_name = message.parsed.package.clone();
-            let definition = generate_struct(message)?;
+            let definition = generate_struct(message, options)?;
             if let Some(entry) = modules_to_struct_definitions.get_mut(&pkg_name) {
                 entry.push(definition);
             } else {
@@ -473,7 +836,7 @@ pub fn generate_rust_ros_message_definitions(
         .into_iter()
         .map(|service| {
             let pkg_name = service.parsed.package.clone();
-            let definition = generate_service(service)?;
+            let definition = generate_service(service, options)?;
             if let Some(entry) = modules_to_struct_definitions.get_mut(&pkg_name) {
                 entry.push(definition);
             } else {
@@ -482,20 +845,47 @@ pub fn generate_rust_ros_message_definitions(
             Ok(())
         })
         .collect::<Result<_, Error>>()?;
-    // Now generate modules to wrap all of the TokenStreams in a module for each package
-    let all_pkgs = modules_to_struct_definitions
-        .keys()
-        .cloned()
-        .collect::<Vec<String>>();
-    let module_definitions = modules_to_struct_definitions
-        .into_iter()
-        .map(|(pkg, struct_defs)| generate_mod(pkg, struct_defs, &all_pkgs[..]))
-        .collect::<Vec<_>>();
-
-    Ok(quote! {
-        #(#module_definitions)*
+    // Actions only need their trait implementation, the messages they expand into were generated above
+    for action in actions {
+        modules_to_struct_definitions
+            .entry(action.package.clone())
+            .or_default()
+            .push(generate_action(action));
+    }
+    Ok(modules_to_struct_definitions)
+}
 
-    })
+/// Computes the md5sum ROS would give the message `type_name`, e.g. `my_pkg/Reading`, defined by `definition`,
+/// as used by `#[derive(RosMessageType)]` for message types written by hand rather than generated.
+/// Only definitions whose fields are all builtin types can be summed on their own, as the md5sum of a message
+/// covers the md5sums of the messages it contains; returns an error naming the first other field.
+pub fn compute_md5sum_of_definition(type_name: &str, definition: &str) -> Result<String, Error> {
+    let (package, name) = type_name.split_once('/').ok_or(Error::new(format!(
+        "Message type name {type_name} should be of the form package/Name"
+    )))?;
+    let package = Package {
+        name: package.to_owned(),
+        path: PathBuf::new(),
+        version: Some(RosVersion::ROS1),
+        ..Default::default()
+    };
+    let parsed =
+        parse_ros_message_file(definition, name, &package, std::path::Path::new(type_name))?;
+    if let Some(field) = parsed
+        .fields
+        .iter()
+        .find(|field| !is_intrinsic_type(RosVersion::ROS1, &field.field_type.field_type))
+    {
+        bail!(
+            "Can't compute the md5sum of {type_name}, its field {} is of message type {}, supply the md5sum instead",
+            field.field_name,
+            field.field_type
+        );
+    }
+    // With only builtin fields there are no other messages to look up
+    MessageFile::compute_md5sum(&parsed, &BTreeMap::new()).ok_or(Error::new(format!(
+        "Failed to compute the md5sum of {type_name}"
+    )))
 }
 
 struct MessageMetadata {
@@ -524,8 +914,9 @@ pub fn resolve_dependency_graph(
                 ROS_2_TYPE_TO_RUST_TYPE_MAP.contains_key(field.field_type.field_type.as_str());
             let is_primitive = is_ros1_primitive || is_ros2_primitive;
             if !is_primitive {
-                let is_resolved = resolved_messages.contains_key(field.get_full_name().as_str());
-                is_resolved
+                field
+                    .get_full_name()
+                    .is_some_and(|name| resolved_messages.contains_key(name.as_str()))
             } else {
                 true
             }
@@ -600,7 +991,11 @@ fn parse_ros_files(
             .ok_or(Error::new(format!(
                 "File stem for file at path {path:?} was not valid unicode?"
             )))?;
-        match path.extension().unwrap().to_str().unwrap() {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+        {
             "srv" => {
                 let srv_file = parse_ros_service_file(&contents, name, &pkg, &path)?;
                 parsed_services.push(srv_file);
@@ -631,7 +1026,12 @@ fn parse_ros_files(
 
 #[cfg(test)]
 mod test {
-    use crate::find_and_generate_ros_messages;
+    use crate::{
+        compute_md5sum_of_definition, find_and_generate_ros_message_crate,
+        find_and_generate_ros_messages,
+        find_and_generate_ros_messages_without_ros_package_path_with_report, CodegenOptions,
+        MessageCrateOptions,
+    };
 
     /// Confirms we don't panic on ros1 parsing
     #[test_log::test]
@@ -681,6 +1081,50 @@ mod test {
         assert!(!paths.is_empty());
     }
 
+    /// Confirms a generated crate has a feature per package, enabling the packages it depends on
+    #[test_log::test]
+    #[cfg_attr(not(feature = "ros1_test"), ignore)]
+    fn generate_message_crate() {
+        let assets_path = concat!(env!("CARGO_MANIFEST_DIR"), "/../assets/ros1_test_msgs");
+        let std_msgs = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../assets/ros1_common_interfaces/std_msgs"
+        );
+        let (message_crate, paths) = find_and_generate_ros_message_crate(
+            vec![assets_path.into(), std_msgs.into()],
+            &MessageCrateOptions::new("test_msgs_crate", "0.1.0"),
+        )
+        .unwrap();
+        assert!(!paths.is_empty());
+        let manifest = &message_crate.files[std::path::Path::new("Cargo.toml")];
+        assert!(manifest.contains("test_msgs = [\"std_msgs\"]\n"));
+        assert!(message_crate
+            .files
+            .contains_key(std::path::Path::new("src/test_msgs.rs")));
+    }
+
+    /// Confirms the report counts what was found, including packages found via multiple search paths
+    #[test_log::test]
+    fn report_counts_packages_and_messages() {
+        let assets_path = concat!(env!("CARGO_MANIFEST_DIR"), "/../assets/ros2_test_msgs");
+
+        let (_source, _paths, report) =
+            find_and_generate_ros_messages_without_ros_package_path_with_report(
+                vec![assets_path.into(), assets_path.into()],
+                &CodegenOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(report.search_paths.len(), 2);
+        assert_eq!(report.packages, vec!["test_msgs".to_owned()]);
+        assert_eq!(report.duplicate_packages, 1);
+        assert_eq!(report.message_files, 1);
+        assert_eq!(report.service_files, 0);
+        assert_eq!(report.generated_messages, 1);
+        assert!(report
+            .to_string()
+            .contains("found 1 packages (1 duplicates ignored)"));
+    }
+
     /// Confirms we don't panic on ros2_test_msgs parsing
     #[test_log::test]
     #[cfg_attr(not(feature = "ros2_test"), ignore)]
@@ -691,4 +1135,23 @@ mod test {
         assert!(!source.is_empty());
         assert!(!paths.is_empty());
     }
+
+    /// Confirms hand written definitions sum the same as their message files
+    #[test_log::test]
+    fn md5sum_of_definition() {
+        assert_eq!(
+            compute_md5sum_of_definition("std_msgs/String", "string data").unwrap(),
+            "992ce8a1687cec8c8bd883ec73ca41d1"
+        );
+        assert_eq!(
+            compute_md5sum_of_definition(
+                "std_msgs/Header",
+                "# Sequence ID\nuint32 seq\ntime stamp\nstring frame_id\n"
+            )
+            .unwrap(),
+            "2176decaecbce78abc3b96ef049fabed"
+        );
+        assert!(compute_md5sum_of_definition("my_pkg/Stamped", "Header header").is_err());
+        assert!(compute_md5sum_of_definition("Stamped", "uint8 value").is_err());
+    }
 }
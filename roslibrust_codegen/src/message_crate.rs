@@ -0,0 +1,231 @@
+//! Generation of a standalone crate holding generated messages, see [crate::find_and_generate_ros_message_crate]
+
+use crate::CodegenOptions;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Describes the crate generated by [crate::find_and_generate_ros_message_crate]
+#[derive(Clone, Debug)]
+pub struct MessageCrateOptions {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    pub(crate) description: Option<String>,
+    pub(crate) license: Option<String>,
+    pub(crate) codegen_dependency: String,
+    pub(crate) codegen: CodegenOptions,
+}
+
+impl MessageCrateOptions {
+    /// Options for a crate named `name` at `version`, e.g. "0.1.0"
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            description: None,
+            license: None,
+            codegen_dependency: format!("\"{}\"", env!("CARGO_PKG_VERSION")),
+            codegen: CodegenOptions::default(),
+        }
+    }
+
+    /// The description written to the crate's Cargo.toml, required to publish the crate
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// The license written to the crate's Cargo.toml, e.g. "MIT", required to publish the crate
+    pub fn license(mut self, license: impl Into<String>) -> Self {
+        self.license = Some(license.into());
+        self
+    }
+
+    /// The crate's dependency on roslibrust_codegen, whose traits the generated messages implement,
+    /// as written in Cargo.toml e.g. `{ path = "../roslibrust_codegen" }`.
+    /// Defaults to the version of roslibrust_codegen generating the crate.
+    pub fn codegen_dependency(mut self, dependency: impl Into<String>) -> Self {
+        self.codegen_dependency = dependency.into();
+        self
+    }
+
+    /// Options for generating the messages themselves, see [CodegenOptions]
+    pub fn codegen_options(mut self, options: CodegenOptions) -> Self {
+        self.codegen = options;
+        self
+    }
+}
+
+/// A generated message crate, returned by [crate::find_and_generate_ros_message_crate]
+#[derive(Clone, Debug)]
+pub struct MessageCrate {
+    /// The contents of each of the crate's files, by path relative to the crate's root e.g. `src/std_msgs.rs`
+    pub files: BTreeMap<PathBuf, String>,
+}
+
+impl MessageCrate {
+    /// Writes the crate's files into `dir`, creating it and its `src` directory if needed.
+    /// Other files within `dir`, e.g. those of packages which are no longer generated, are left in place.
+    pub fn write_to(&self, dir: impl AsRef<Path>) -> std::io::Result<()> {
+        for (path, contents) in &self.files {
+            let path = dir.as_ref().join(path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, contents)?;
+        }
+        Ok(())
+    }
+}
+
+/// Lays out the definitions generated for each package as a crate, with a module and feature per package
+pub(crate) fn generate_crate(
+    packages: BTreeMap<String, Vec<TokenStream>>,
+    dependencies: &BTreeMap<String, BTreeSet<String>>,
+    options: &MessageCrateOptions,
+) -> MessageCrate {
+    let no_dependencies = BTreeSet::new();
+    let dependencies_of = |pkg: &str| dependencies.get(pkg).unwrap_or(&no_dependencies);
+
+    let mut files = BTreeMap::new();
+    files.insert(
+        PathBuf::from("Cargo.toml"),
+        generate_manifest(packages.keys(), dependencies_of, options),
+    );
+    files.insert(PathBuf::from("src/lib.rs"), generate_lib(packages.keys()));
+    for (pkg, definitions) in packages {
+        let imports = dependencies_of(&pkg)
+            .iter()
+            .map(|dependency| format_ident!("{}", dependency));
+        let source = quote! {
+            #(use super::#imports;)*
+
+            #(#definitions)*
+        };
+        files.insert(
+            PathBuf::from(format!("src/{pkg}.rs")),
+            format!("//! Messages and services of the ROS package {pkg}\n\n{source}\n"),
+        );
+    }
+    MessageCrate { files }
+}
+
+fn generate_manifest<'p, 'd>(
+    packages: impl Iterator<Item = &'p String> + Clone,
+    dependencies_of: impl Fn(&str) -> &'d BTreeSet<String>,
+    options: &MessageCrateOptions,
+) -> String {
+    let mut package = format!(
+        "[package]\nname = {}\nversion = {}\nedition = \"2021\"\n",
+        toml_string(&options.name),
+        toml_string(&options.version)
+    );
+    if let Some(description) = &options.description {
+        package += &format!("description = {}\n", toml_string(description));
+    }
+    if let Some(license) = &options.license {
+        package += &format!("license = {}\n", toml_string(license));
+    }
+    let features = packages
+        .clone()
+        .map(|pkg| format!("{pkg} = [{}]\n", toml_list(dependencies_of(pkg).iter())))
+        .collect::<String>();
+    format!(
+        "{package}
+# The crates the generated code refers to
+[dependencies]
+roslibrust_codegen = {}
+serde = {{ version = \"1.0\", features = [\"derive\"] }}
+smart-default = \"0.6\"
+
+[features]
+default = [{}]
+{features}",
+        options.codegen_dependency,
+        toml_list(packages)
+    )
+}
+
+fn generate_lib<'a>(packages: impl Iterator<Item = &'a String>) -> String {
+    let modules = packages
+        .map(|pkg| format!("\n#[cfg(feature = \"{pkg}\")]\npub mod {pkg};\n"))
+        .collect::<String>();
+    format!(
+        "//! ROS messages and services generated by roslibrust_codegen.
+//! Each ROS package is a module behind a feature of the same name, all of which are enabled by default.
+{modules}"
+    )
+}
+
+// Quotes a TOML basic string
+fn toml_string(value: &str) -> String {
+    let escaped = value
+        .chars()
+        .map(|c| match c {
+            '"' => "\\\"".to_owned(),
+            '\\' => "\\\\".to_owned(),
+            '\n' => "\\n".to_owned(),
+            '\t' => "\\t".to_owned(),
+            c if c.is_control() => format!("\\u{:04X}", c as u32),
+            c => c.to_string(),
+        })
+        .collect::<String>();
+    format!("\"{escaped}\"")
+}
+
+fn toml_list<'a>(values: impl Iterator<Item = &'a String>) -> String {
+    values
+        .map(|value| toml_string(value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{generate_crate, toml_string, MessageCrateOptions};
+    use quote::quote;
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::path::Path;
+
+    #[test]
+    fn packages_become_modules_behind_features() {
+        let packages = BTreeMap::from([
+            (
+                "geometry_msgs".to_owned(),
+                vec![quote! { pub struct Point; }],
+            ),
+            ("std_msgs".to_owned(), vec![quote! { pub struct Header; }]),
+        ]);
+        let dependencies = BTreeMap::from([(
+            "geometry_msgs".to_owned(),
+            BTreeSet::from(["std_msgs".to_owned()]),
+        )]);
+        let options = MessageCrateOptions::new("my_msgs", "0.1.0")
+            .description("Messages \"of\" my robots")
+            .codegen_dependency("{ path = \"../roslibrust_codegen\" }");
+        let generated = generate_crate(packages, &dependencies, &options);
+
+        let manifest = &generated.files[Path::new("Cargo.toml")];
+        assert!(manifest.contains("name = \"my_msgs\"\n"));
+        assert!(manifest.contains("description = \"Messages \\\"of\\\" my robots\"\n"));
+        assert!(manifest.contains("roslibrust_codegen = { path = \"../roslibrust_codegen\" }\n"));
+        assert!(manifest.contains("default = [\"geometry_msgs\", \"std_msgs\"]\n"));
+        assert!(manifest.contains("geometry_msgs = [\"std_msgs\"]\n"));
+        assert!(manifest.contains("std_msgs = []\n"));
+        assert!(!manifest.contains("license"));
+
+        let lib = &generated.files[Path::new("src/lib.rs")];
+        assert!(lib.contains("#[cfg(feature = \"std_msgs\")]\npub mod std_msgs;\n"));
+        assert!(
+            generated.files[Path::new("src/geometry_msgs.rs")].contains("use super :: std_msgs ;")
+        );
+        assert!(!generated.files[Path::new("src/std_msgs.rs")].contains("use super"));
+    }
+
+    #[test]
+    fn toml_strings_are_escaped() {
+        assert_eq!(toml_string("C:\\msgs\n"), "\"C:\\\\msgs\\n\"");
+        assert_eq!(toml_string("\u{7}"), "\"\\u0007\"");
+    }
+}
@@ -76,7 +76,9 @@ fn parse_field(line: &str, pkg: &Package, msg_name: &str) -> Result<FieldInfo, E
         "Did not find field_name on line: {line} while parsing {pkg_name}/{msg_name}"
     )))?;
 
-    let sep = line.find(' ').unwrap();
+    let sep = line.find(|c: char| c.is_ascii_whitespace()).ok_or(Error::new(format!(
+        "Did not find whitespace after field_type on line: {line} while parsing {pkg_name}/{msg_name}"
+    )))?;
     // Determine if there is a default value for this field
     let default = if matches!(pkg.version, Some(RosVersion::ROS2)) {
         // For ros2 packages only, check if there is a default value
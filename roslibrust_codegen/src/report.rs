@@ -0,0 +1,89 @@
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Summary of a code generation run, useful for diagnosing slow builds and misconfigured search paths.
+/// Returned by the `_with_report` variants of the generation functions.
+///
+/// Within a build.rs the report can be surfaced in cargo's output with [CodegenReport::emit_cargo_warnings]:
+/// ```no_run
+/// let (source, dependent_paths, report) =
+///     roslibrust_codegen::find_and_generate_ros_messages_without_ros_package_path_with_report(
+///         vec!["assets/ros1_common_interfaces/std_msgs".into()],
+///         &roslibrust_codegen::CodegenOptions::default(),
+///     )
+///     .unwrap();
+/// report.emit_cargo_warnings();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CodegenReport {
+    /// The canonicalized paths which were searched for packages
+    pub search_paths: Vec<PathBuf>,
+    /// Names of the packages found, after removing duplicates
+    pub packages: Vec<String>,
+    /// Number of packages found more than once within the search paths, only the first copy is used
+    pub duplicate_packages: usize,
+    /// Number of .msg files parsed
+    pub message_files: usize,
+    /// Number of .srv files parsed
+    pub service_files: usize,
+    /// Number of .action files parsed
+    pub action_files: usize,
+    /// Number of message structs generated, including those expanded from actions
+    pub generated_messages: usize,
+    /// Number of services generated
+    pub generated_services: usize,
+    /// Time spent finding packages and their message files
+    pub search_time: Duration,
+    /// Time spent reading and parsing message files
+    pub parse_time: Duration,
+    /// Time spent resolving dependencies between messages and calculating md5sums
+    pub resolve_time: Duration,
+    /// Time spent generating Rust source
+    pub generate_time: Duration,
+}
+
+impl CodegenReport {
+    pub fn total_time(&self) -> Duration {
+        self.search_time + self.parse_time + self.resolve_time + self.generate_time
+    }
+
+    /// Prints the report as `cargo:warning=` lines, which cargo displays when run from a build.rs.
+    pub fn emit_cargo_warnings(&self) {
+        for line in self.to_string().lines() {
+            println!("cargo:warning={line}");
+        }
+    }
+}
+
+impl Display for CodegenReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "roslibrust_codegen found {} packages ({} duplicates ignored) in {} search paths: {}",
+            self.packages.len(),
+            self.duplicate_packages,
+            self.search_paths.len(),
+            self.packages.join(", ")
+        )?;
+        writeln!(
+            f,
+            "roslibrust_codegen parsed {} messages, {} services and {} actions",
+            self.message_files, self.service_files, self.action_files
+        )?;
+        writeln!(
+            f,
+            "roslibrust_codegen generated {} messages and {} services",
+            self.generated_messages, self.generated_services
+        )?;
+        write!(
+            f,
+            "roslibrust_codegen took {:?} (search {:?}, parse {:?}, resolve {:?}, generate {:?})",
+            self.total_time(),
+            self.search_time,
+            self.parse_time,
+            self.resolve_time,
+            self.generate_time
+        )
+    }
+}
@@ -2,12 +2,30 @@ use std::collections::HashMap;
 use std::io;
 use std::path::{Path, PathBuf};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Package {
     pub name: String,
     pub path: PathBuf,
     /// For now RosVersion is being left as an option, because our ability to detect the correct version is in question
     pub version: Option<RosVersion>,
+    /// The packages this package depends on, from the `depend`, `build_depend`, `build_export_depend`,
+    /// `exec_depend` and `run_depend` tags of its package.xml, leaving out those whose condition doesn't hold
+    pub dependencies: Vec<String>,
+    /// The version of the package itself from the `version` tag of its package.xml, e.g. "1.13.1"
+    pub package_version: Option<String>,
+    pub maintainers: Vec<Maintainer>,
+    /// The dependencies needed to build the package, from its `depend` and `build_depend` tags
+    pub build_dependencies: Vec<String>,
+    /// The dependencies needed to run the package, from its `depend`, `exec_depend` and format 1 `run_depend` tags
+    pub exec_dependencies: Vec<String>,
+}
+
+/// A maintainer of a package, from a `maintainer` tag of its package.xml
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Maintainer {
+    pub name: String,
+    /// Required by every package.xml format, though not every package gives one
+    pub email: Option<String>,
 }
 
 impl PartialEq for Package {
@@ -24,6 +42,15 @@ pub enum RosVersion {
 
 const CATKIN_IGNORE: &str = "CATKIN_IGNORE";
 const PACKAGE_FILE_NAME: &str = "package.xml";
+const DEPEND_TAGS: [&str; 5] = [
+    "depend",
+    "build_depend",
+    "build_export_depend",
+    "exec_depend",
+    "run_depend",
+];
+const BUILD_DEPEND_TAGS: [&str; 2] = ["depend", "build_depend"];
+const EXEC_DEPEND_TAGS: [&str; 3] = ["depend", "exec_depend", "run_depend"];
 const ROS_PACKAGE_PATH_ENV_VAR: &str = "ROS_PACKAGE_PATH";
 
 pub fn get_search_paths() -> Vec<PathBuf> {
@@ -45,79 +72,84 @@ pub fn get_search_paths() -> Vec<PathBuf> {
 
 /// Finds ROS packages within a list of search paths.
 ///
-/// This function may panic if it reaches a maximum search depth. If this function
-/// panics while you're using it, you may have some infinite loop in your paths
-/// due to symlinking.
-pub fn crawl<P: AsRef<Path>>(search_paths: &[P]) -> Vec<Package> {
+/// Returns an error if any directory within the search paths cannot be read, if a package.xml is malformed,
+/// or if a maximum search depth is reached. Reaching the maximum depth likely means there is
+/// an infinite loop in your paths due to symlinking.
+/// The `condition` attributes of package.xml format 3 are evaluated without knowing `$ROS_VERSION`,
+/// see [crawl_for_ros_version].
+pub fn crawl<P: AsRef<Path>>(search_paths: &[P]) -> io::Result<Vec<Package>> {
+    crawl_for_ros_version(search_paths, None)
+}
+
+/// Like [crawl], evaluating the `condition` attributes of package.xml format 3, e.g. `condition="$ROS_VERSION == 2"`,
+/// with `$ROS_VERSION` set to `ros_version`.
+///
+/// A package building with catkin for ROS1 and ament for ROS2 is detected as the version given,
+/// or as neither if no version is given. Dependencies whose condition can't be decided are kept.
+pub fn crawl_for_ros_version<P: AsRef<Path>>(
+    search_paths: &[P],
+    ros_version: Option<RosVersion>,
+) -> io::Result<Vec<Package>> {
     let mut packages = vec![];
 
     for path in search_paths {
         const MAX_RECURSION_DEPTH: u16 = 1000;
-        if let Ok(found_packages) =
-            packages_from_path(path.as_ref().to_owned(), MAX_RECURSION_DEPTH)
-        {
-            packages = [packages, found_packages].concat();
-        }
+        packages.extend(packages_from_path(
+            path.as_ref().to_owned(),
+            MAX_RECURSION_DEPTH,
+            ros_version,
+        )?);
     }
 
-    packages
+    Ok(packages)
 }
 
-fn packages_from_path(mut path: PathBuf, depth: u16) -> io::Result<Vec<Package>> {
-    let mut found_packages = vec![];
+/// Adds the path an io operation failed on to its error, so failures within build scripts point at the culprit
+fn with_path_context(err: io::Error, action: &str, path: &Path) -> io::Error {
+    io::Error::new(err.kind(), format!("{action} {}: {err}", path.display()))
+}
 
+fn packages_from_path(
+    path: PathBuf,
+    depth: u16,
+    ros_version: Option<RosVersion>,
+) -> io::Result<Vec<Package>> {
     if depth == 0 {
-        log::error!(
+        return Err(io::Error::other(format!(
             "Reached depth limit in: {}. Possible symlink loop detected.",
-            path.as_os_str().to_string_lossy()
-        );
-        return Err(io::ErrorKind::Other.into());
+            path.display()
+        )));
     }
 
-    if path.as_path().is_dir() {
-        // We have a valid path
-        path.push(CATKIN_IGNORE);
-        // We'll only check this directory if no CATKIN_IGNORE file is present
-        // TODO: support for ament ignore and colcon ignore
-        if !path.as_path().is_file() {
-            assert!(path.pop());
-
-            path.push(PACKAGE_FILE_NAME);
-            if path.as_path().is_file() {
-                // And there's a package.xml here!
-                if let Ok((version, name)) = parse_ros_package_info(&path) {
-                    // Remove package.xml from our path
-                    assert!(path.pop());
-
-                    log::debug!("Found package {name} at {}", path.display());
-
-                    found_packages.push(Package {
-                        name,
-                        path,
-                        version,
-                    });
-                }
-            } else {
-                // No file here, we'll have to go deeper
-                assert!(path.pop());
-                for subdir in std::fs::read_dir(path)
-                    .unwrap()
-                    .filter(|entry| match entry {
-                        Ok(entry) => entry.path().as_path().is_dir(),
-                        Err(_err) => false,
-                    })
-                    .map(|entry| entry.unwrap())
-                {
-                    found_packages = [
-                        found_packages,
-                        packages_from_path(subdir.path(), depth - 1)?,
-                    ]
-                    .concat()
-                }
-            }
+    if !path.is_dir() {
+        log::error!("{} is not a directory", path.display());
+        return Ok(vec![]);
+    }
+
+    // We'll only check this directory if no CATKIN_IGNORE file is present
+    // TODO: support for ament ignore and colcon ignore
+    if path.join(CATKIN_IGNORE).is_file() {
+        return Ok(vec![]);
+    }
+
+    let package_file = path.join(PACKAGE_FILE_NAME);
+    if package_file.is_file() {
+        // And there's a package.xml here!
+        let package = parse_ros_package_info(&package_file, ros_version)?;
+        log::debug!("Found package {} at {}", package.name, path.display());
+        return Ok(vec![Package { path, ..package }]);
+    }
+
+    // No file here, we'll have to go deeper
+    let mut found_packages = vec![];
+    let entries = std::fs::read_dir(&path)
+        .map_err(|err| with_path_context(err, "Failed to read directory", &path))?;
+    for entry in entries {
+        let entry = entry
+            .map_err(|err| with_path_context(err, "Failed to read an entry of directory", &path))?;
+        if entry.path().is_dir() {
+            found_packages.extend(packages_from_path(entry.path(), depth - 1, ros_version)?);
         }
-    } else {
-        log::error!("{} is not a directory", path.to_string_lossy())
     }
 
     Ok(found_packages)
@@ -133,7 +165,9 @@ pub fn get_message_files(pkg: &Package) -> io::Result<Vec<PathBuf>> {
 
 fn message_files_from_path(path: &Path, ext: &str) -> io::Result<Vec<PathBuf>> {
     let mut msg_files = vec![];
-    for entry in (std::fs::read_dir(path)?).flatten() {
+    let entries = std::fs::read_dir(path)
+        .map_err(|err| with_path_context(err, "Failed to read directory", path))?;
+    for entry in entries.flatten() {
         if entry.path().as_path().is_dir() {
             msg_files = [
                 msg_files,
@@ -142,7 +176,7 @@ fn message_files_from_path(path: &Path, ext: &str) -> io::Result<Vec<PathBuf>> {
             .concat()
         } else if entry.path().as_path().is_file() {
             if let Some(extension) = entry.path().extension() {
-                if extension.to_str().unwrap() == ext {
+                if extension == ext {
                     msg_files.push(entry.path())
                 }
             }
@@ -190,23 +224,165 @@ pub fn deduplicate_packages(packages: Vec<Package>) -> Vec<Package> {
     package_map.into_values().collect()
 }
 
+/// The dependencies between crawled packages, following the [Package::dependencies] of each.
+///
+/// Packages are looked up by name, so should be of a single ROS version, e.g. as found by [crawl_for_ros_version].
+/// Of several packages sharing a name the first is used.
+pub struct DependencyGraph<'a> {
+    packages: HashMap<&'a str, &'a Package>,
+}
+
+impl<'a> DependencyGraph<'a> {
+    pub fn new(packages: &'a [Package]) -> Self {
+        let mut graph = HashMap::new();
+        for package in packages {
+            graph.entry(package.name.as_str()).or_insert(package);
+        }
+        Self { packages: graph }
+    }
+
+    pub fn package(&self, name: &str) -> Option<&'a Package> {
+        self.packages.get(name).copied()
+    }
+
+    /// The packages `name` depends on directly, leaving out dependencies which weren't crawled
+    pub fn dependencies(&self, name: &str) -> Vec<&'a Package> {
+        self.package(name)
+            .map(|package| {
+                package
+                    .dependencies
+                    .iter()
+                    .filter_map(|dependency| self.package(dependency))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The packages which depend directly on `name`, sorted by name
+    pub fn dependents(&self, name: &str) -> Vec<&'a Package> {
+        let mut dependents = self
+            .packages
+            .values()
+            .filter(|package| {
+                package
+                    .dependencies
+                    .iter()
+                    .any(|dependency| dependency == name)
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        dependents.sort_by(|a, b| a.name.cmp(&b.name));
+        dependents
+    }
+
+    /// Every package `name` depends on directly or through other packages, each after its own dependencies.
+    /// Dependencies which weren't crawled are left out, along with what they depend on.
+    pub fn transitive_dependencies(&self, name: &str) -> Vec<&'a Package> {
+        let Some(package) = self.package(name) else {
+            return vec![];
+        };
+        // Holds the package itself so a cycle back to it doesn't list it as its own dependency
+        let mut visited = vec![package.name.as_str()];
+        let mut ordered = vec![];
+        self.visit_dependencies(name, &mut visited, &mut ordered);
+        ordered
+    }
+
+    // Depth first, so each dependency is pushed once everything it depends on has been
+    fn visit_dependencies(
+        &self,
+        name: &str,
+        visited: &mut Vec<&'a str>,
+        ordered: &mut Vec<&'a Package>,
+    ) {
+        for dependency in self.dependencies(name) {
+            if !visited.contains(&dependency.name.as_str()) {
+                visited.push(&dependency.name);
+                self.visit_dependencies(&dependency.name, visited, ordered);
+                ordered.push(dependency);
+            }
+        }
+    }
+
+    /// The dependencies of crawled packages which weren't crawled themselves, as pairs of
+    /// the depending package's name and the missing dependency, sorted
+    pub fn missing_dependencies(&self) -> Vec<(&'a str, &'a str)> {
+        let mut missing = self
+            .packages
+            .values()
+            .flat_map(|package| {
+                package
+                    .dependencies
+                    .iter()
+                    .filter(|dependency| !self.packages.contains_key(dependency.as_str()))
+                    .map(|dependency| (package.name.as_str(), dependency.as_str()))
+            })
+            .collect::<Vec<_>>();
+        missing.sort();
+        missing
+    }
+
+    /// Every package, each after the packages it depends on, such as to build a workspace in.
+    /// Returns the names of packages forming a cycle if there is one, in which case no order exists.
+    pub fn build_order(&self) -> Result<Vec<&'a Package>, Vec<String>> {
+        let mut names = self.packages.keys().copied().collect::<Vec<_>>();
+        // Sorted so the order is the same on every call
+        names.sort();
+        let mut ordered = vec![];
+        let mut path = vec![];
+        for name in names {
+            self.visit_in_order(name, &mut path, &mut ordered)?;
+        }
+        Ok(ordered)
+    }
+
+    // Depth first like visit_dependencies, following `path` back to the package being ordered to spot cycles
+    fn visit_in_order(
+        &self,
+        name: &'a str,
+        path: &mut Vec<&'a str>,
+        ordered: &mut Vec<&'a Package>,
+    ) -> Result<(), Vec<String>> {
+        if ordered.iter().any(|package| package.name == name) {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|visiting| *visiting == name) {
+            return Err(path[start..].iter().map(|name| name.to_string()).collect());
+        }
+        path.push(name);
+        for dependency in self.dependencies(name) {
+            self.visit_in_order(&dependency.name, path, ordered)?;
+        }
+        path.pop();
+        ordered.push(self.packages[name]);
+        Ok(())
+    }
+}
+
 /// Parses a ROS package.xml file, which may be in any of the 3 supported formats,
-/// and returns a tuple of (RosVersion, Package Name)
+/// and returns the package it describes, leaving its path for the caller to fill in
 /// Note: the name of the folder the package resides in is NOT the name of the package,
 /// although that is the convention.
-/// Finding the name is considered infallible and panics if name cannot be determined
+/// Returns an error if the file cannot be read or has no name
 /// ROS version determination is heuristic only, and returns None if failed.
 /// See: https://answers.ros.org/question/410017/how-to-determine-if-a-package-is-ros1-or-ros2/
+/// Format 3 condition attributes are evaluated against `ros_version`, see [crawl_for_ros_version].
 fn parse_ros_package_info(
     path: impl AsRef<Path> + std::fmt::Debug,
-) -> io::Result<(Option<RosVersion>, String)> {
+    ros_version: Option<RosVersion>,
+) -> io::Result<Package> {
     use std::fs::File;
     use std::io::BufReader;
     use xml::reader::{EventReader, ParserConfig, XmlEvent};
     const BUILD_TOOL_TAG: &str = "buildtool_depend";
     const NAME_TAG: &str = "name";
+    const VERSION_TAG: &str = "version";
+    const MAINTAINER_TAG: &str = "maintainer";
+    const CONDITION_ATTRIBUTE: &str = "condition";
+    const EMAIL_ATTRIBUTE: &str = "email";
 
-    let file = File::open(&path)?;
+    let file = File::open(path.as_ref())
+        .map_err(|err| with_path_context(err, "Failed to open", path.as_ref()))?;
     let reader = BufReader::new(file);
     let parser = EventReader::new_with_config(
         reader,
@@ -217,55 +393,106 @@ fn parse_ros_package_info(
         },
     );
 
-    let mut in_build = false;
-    let mut in_name = false;
+    // The tag being read, empty between tags
+    let mut tag = String::new();
+    // Whether the condition of the tag being read holds, None if it can't be decided
+    let mut condition = Some(true);
+    // The email of the maintainer being read
+    let mut email = None;
     let mut version = None;
+    // Versions built for under conditions which can't be decided
+    let mut undecided_versions = vec![];
     let mut name = None;
+    let mut package = Package::default();
     for e in parser {
         match e {
-            Ok(XmlEvent::StartElement { name, .. }) => {
-                if name.local_name == BUILD_TOOL_TAG {
-                    in_build = true;
-                } else if name.local_name == NAME_TAG {
-                    in_name = true;
-                }
-            }
-            Ok(XmlEvent::EndElement { name, .. }) => {
-                if name.local_name == BUILD_TOOL_TAG {
-                    in_build = false;
-                } else if name.local_name == NAME_TAG {
-                    in_name = false;
-                }
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => {
+                tag = name.local_name;
+                let attribute = |attribute_name: &str| {
+                    attributes
+                        .iter()
+                        .find(|attribute| attribute.name.local_name == attribute_name)
+                };
+                condition = match attribute(CONDITION_ATTRIBUTE) {
+                    Some(attribute) => crate::condition::evaluate(&attribute.value, ros_version)
+                        .map_err(|err| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!(
+                                    "Invalid condition \"{}\" in {}: {err}",
+                                    attribute.value,
+                                    path.as_ref().display()
+                                ),
+                            )
+                        })?,
+                    None => Some(true),
+                };
+                email = attribute(EMAIL_ATTRIBUTE).map(|attribute| attribute.value.clone());
             }
-            Ok(XmlEvent::Characters(data)) => {
-                if in_build {
+            Ok(XmlEvent::EndElement { .. }) => tag.clear(),
+            Ok(XmlEvent::Characters(data)) => match tag.as_str() {
+                BUILD_TOOL_TAG => {
                     log::trace!("Got data inside of {BUILD_TOOL_TAG}: {data}");
-                    match data.as_str() {
-                        "catkin" => {
-                            version = Some(RosVersion::ROS1);
-                        }
-                        "ament_cmake" => {
-                            version = Some(RosVersion::ROS2);
-                        }
-                        _ => {}
+                    let build_version = match data.as_str() {
+                        "catkin" => Some(RosVersion::ROS1),
+                        "ament_cmake" => Some(RosVersion::ROS2),
+                        _ => None,
                     };
-                } else if in_name {
+                    match (build_version, condition) {
+                        (Some(build_version), Some(true)) => version = Some(build_version),
+                        (Some(build_version), None) => undecided_versions.push(build_version),
+                        _ => {}
+                    }
+                }
+                NAME_TAG => {
                     log::trace!("Got data inside of {NAME_TAG}: {data}");
                     name = Some(data);
                 }
-            }
+                VERSION_TAG => package.package_version = Some(data),
+                MAINTAINER_TAG => package.maintainers.push(Maintainer {
+                    name: data,
+                    email: email.take(),
+                }),
+                depend_tag if DEPEND_TAGS.contains(&depend_tag) && condition != Some(false) => {
+                    for (tags, dependencies) in [
+                        (&BUILD_DEPEND_TAGS[..], &mut package.build_dependencies),
+                        (&EXEC_DEPEND_TAGS[..], &mut package.exec_dependencies),
+                    ] {
+                        if tags.contains(&depend_tag) && !dependencies.contains(&data) {
+                            dependencies.push(data.clone());
+                        }
+                    }
+                    if !package.dependencies.contains(&data) {
+                        package.dependencies.push(data);
+                    }
+                }
+                _ => {}
+            },
             _ => {}
         }
     }
-
-    if let Some(name) = name {
-        Ok((version, name))
-    } else {
-        log::error!(
-            "Failed to find the <name> tag within package.xml, which is a required tag: {path:?}"
-        );
-        Err(io::ErrorKind::Other.into())
+    // A package building for both versions depending on $ROS_VERSION is neither until told which applies
+    undecided_versions.dedup();
+    if let (None, [undecided_version]) = (version, &undecided_versions[..]) {
+        version = Some(*undecided_version);
     }
+
+    let name = name.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Failed to find the <name> tag within package.xml, which is a required tag: {}",
+                path.as_ref().display()
+            ),
+        )
+    })?;
+    Ok(Package {
+        name,
+        version,
+        ..package
+    })
 }
 
 #[cfg(test)]
@@ -281,27 +508,205 @@ mod test {
                 name: "diagnostic_msgs".into(),
                 path: "/opt/ros/noetic/share/diagnostic_msgs".into(),
                 version: Some(utils::RosVersion::ROS1),
+                ..Default::default()
             },
             utils::Package {
                 name: "std_msgs".into(),
                 path: "/tmp/std_msgs".into(),
                 version: Some(utils::RosVersion::ROS1),
+                ..Default::default()
             },
             // This duplicate below should be removed
             utils::Package {
                 name: "diagnostic_msgs".into(),
                 path: "/code/assets/ros1_common_interfaces/common_msgs/diagnostic_msgs".into(),
                 version: Some(utils::RosVersion::ROS1),
+                ..Default::default()
             },
             // This will be kept because the ROS Version is different
             utils::Package {
                 name: "std_msgs".into(),
                 path: "/ros2/std_msgs".into(),
                 version: Some(utils::RosVersion::ROS2),
+                ..Default::default()
             },
         ];
 
         let deduplicated = utils::deduplicate_packages(packages);
         assert_eq!(deduplicated.len(), 3);
     }
+
+    #[test]
+    fn crawl_reports_path_of_invalid_package() {
+        let dir =
+            std::env::temp_dir().join(format!("roslibrust_crawl_test_{}", std::process::id()));
+        let pkg_dir = dir.join("nameless_pkg");
+        std::fs::create_dir_all(&pkg_dir).unwrap();
+        std::fs::write(
+            pkg_dir.join("package.xml"),
+            "<package format=\"2\"></package>",
+        )
+        .unwrap();
+
+        let err = utils::crawl(&[&dir]).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(err.to_string().contains("nameless_pkg"));
+    }
+
+    #[test]
+    fn crawl_evaluates_conditions_against_ros_version() {
+        let dir =
+            std::env::temp_dir().join(format!("roslibrust_condition_test_{}", std::process::id()));
+        let pkg_dir = dir.join("mixed_msgs");
+        std::fs::create_dir_all(&pkg_dir).unwrap();
+        std::fs::write(
+            pkg_dir.join("package.xml"),
+            r#"<package format="3">
+  <name>mixed_msgs</name>
+  <buildtool_depend condition="$ROS_VERSION == 1">catkin</buildtool_depend>
+  <buildtool_depend condition="$ROS_VERSION == 2">ament_cmake</buildtool_depend>
+  <depend>std_msgs</depend>
+  <build_depend condition="$ROS_VERSION == 1">message_generation</build_depend>
+  <build_depend condition="$ROS_VERSION == 2">rosidl_default_generators</build_depend>
+  <exec_depend>std_msgs</exec_depend>
+</package>"#,
+        )
+        .unwrap();
+
+        let ros1 = utils::crawl_for_ros_version(&[&dir], Some(utils::RosVersion::ROS1)).unwrap();
+        let ros2 = utils::crawl_for_ros_version(&[&dir], Some(utils::RosVersion::ROS2)).unwrap();
+        let unknown = utils::crawl(&[&dir]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(ros1[0].version, Some(utils::RosVersion::ROS1));
+        assert_eq!(ros1[0].dependencies, ["std_msgs", "message_generation"]);
+        assert_eq!(ros2[0].version, Some(utils::RosVersion::ROS2));
+        assert_eq!(
+            ros2[0].dependencies,
+            ["std_msgs", "rosidl_default_generators"]
+        );
+        assert_eq!(unknown[0].version, None);
+        assert_eq!(
+            unknown[0].dependencies,
+            [
+                "std_msgs",
+                "message_generation",
+                "rosidl_default_generators"
+            ]
+        );
+    }
+
+    #[test]
+    fn crawl_reads_manifest_metadata() {
+        let dir =
+            std::env::temp_dir().join(format!("roslibrust_manifest_test_{}", std::process::id()));
+        let pkg_dir = dir.join("nav_msgs");
+        std::fs::create_dir_all(&pkg_dir).unwrap();
+        std::fs::write(
+            pkg_dir.join("package.xml"),
+            r#"<package format="2">
+  <name>nav_msgs</name>
+  <version>1.13.1</version>
+  <maintainer email="jane@example.com">Jane Doe</maintainer>
+  <maintainer>Nobody</maintainer>
+  <buildtool_depend>catkin</buildtool_depend>
+  <depend>geometry_msgs</depend>
+  <build_depend>message_generation</build_depend>
+  <build_export_depend>std_msgs</build_export_depend>
+  <exec_depend>message_runtime</exec_depend>
+</package>"#,
+        )
+        .unwrap();
+
+        let packages = utils::crawl(&[&dir]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let package = &packages[0];
+        assert_eq!(package.path, pkg_dir);
+        assert_eq!(package.package_version.as_deref(), Some("1.13.1"));
+        assert_eq!(
+            package.maintainers,
+            [
+                utils::Maintainer {
+                    name: "Jane Doe".into(),
+                    email: Some("jane@example.com".into()),
+                },
+                utils::Maintainer {
+                    name: "Nobody".into(),
+                    email: None,
+                },
+            ]
+        );
+        assert_eq!(
+            package.build_dependencies,
+            ["geometry_msgs", "message_generation"]
+        );
+        assert_eq!(
+            package.exec_dependencies,
+            ["geometry_msgs", "message_runtime"]
+        );
+        assert_eq!(
+            package.dependencies,
+            [
+                "geometry_msgs",
+                "message_generation",
+                "std_msgs",
+                "message_runtime"
+            ]
+        );
+    }
+
+    #[test]
+    fn dependency_graph_orders_packages() {
+        let package = |name: &str, dependencies: &[&str]| utils::Package {
+            name: name.into(),
+            dependencies: dependencies.iter().map(|name| name.to_string()).collect(),
+            ..Default::default()
+        };
+        let names = |packages: Vec<&utils::Package>| {
+            packages
+                .into_iter()
+                .map(|package| package.name.clone())
+                .collect::<Vec<_>>()
+        };
+        let packages = vec![
+            package(
+                "nav_msgs",
+                &["geometry_msgs", "std_msgs", "message_runtime"],
+            ),
+            package("geometry_msgs", &["std_msgs"]),
+            package("std_msgs", &[]),
+            package("sensor_msgs", &["geometry_msgs"]),
+        ];
+        let graph = utils::DependencyGraph::new(&packages);
+
+        assert_eq!(
+            names(graph.dependencies("nav_msgs")),
+            ["geometry_msgs", "std_msgs"]
+        );
+        assert_eq!(
+            names(graph.dependents("geometry_msgs")),
+            ["nav_msgs", "sensor_msgs"]
+        );
+        assert_eq!(
+            names(graph.transitive_dependencies("sensor_msgs")),
+            ["std_msgs", "geometry_msgs"]
+        );
+        assert_eq!(
+            graph.missing_dependencies(),
+            [("nav_msgs", "message_runtime")]
+        );
+        assert_eq!(
+            names(graph.build_order().unwrap()),
+            ["std_msgs", "geometry_msgs", "nav_msgs", "sensor_msgs"]
+        );
+
+        let cyclic = vec![
+            package("a_msgs", &["b_msgs"]),
+            package("b_msgs", &["a_msgs"]),
+        ];
+        let graph = utils::DependencyGraph::new(&cyclic);
+        assert_eq!(graph.build_order().unwrap_err(), ["a_msgs", "b_msgs"]);
+        assert_eq!(names(graph.transitive_dependencies("a_msgs")), ["b_msgs"]);
+    }
 }
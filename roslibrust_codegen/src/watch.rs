@@ -0,0 +1,208 @@
+//! Regeneration of messages as their definitions are edited, see [crate::watch_and_generate]
+
+use crate::{CodegenOptions, Error};
+use log::*;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// The file [crate::watch_and_generate] writes the generated source to within its output directory
+pub const GENERATED_FILE: &str = "messages.rs";
+
+// Editors and version control touch several files at once, so changes are collected for this long
+// after the first before regenerating once for all of them
+const SETTLE_TIME: Duration = Duration::from_millis(100);
+
+/// Searches `search_paths` for ROS packages and generates their messages into [GENERATED_FILE] within `out_dir`,
+/// as [crate::find_and_generate_ros_messages_without_ros_package_path] would, then regenerates them whenever
+/// a .msg, .srv, .action or package.xml file within `search_paths` is created, modified or removed.
+/// Intended to run alongside e.g. `cargo watch` while iterating on custom message definitions,
+/// with the generated file included by the crate using the messages.
+///
+/// Runs until watching `search_paths` fails. Failures to generate, e.g. while a definition is half written,
+/// are logged and leave the last generated file in place until the next change.
+/// The file is only rewritten when its contents change, so unrelated edits don't trigger rebuilds.
+/// ```no_run
+/// roslibrust_codegen::watch_and_generate(vec!["my_msgs".into()], "src/generated").unwrap();
+/// ```
+pub fn watch_and_generate(
+    search_paths: Vec<PathBuf>,
+    out_dir: impl AsRef<Path>,
+) -> Result<(), Error> {
+    watch_and_generate_with_options(search_paths, out_dir, &CodegenOptions::default())
+}
+
+/// Identical to [watch_and_generate], but generates code according to `options`.
+pub fn watch_and_generate_with_options(
+    search_paths: Vec<PathBuf>,
+    out_dir: impl AsRef<Path>,
+    options: &CodegenOptions,
+) -> Result<(), Error> {
+    let out_file = out_dir.as_ref().join(GENERATED_FILE);
+    watch_for_changes(&search_paths, || {
+        match generate_to_file(search_paths.clone(), &out_file, options) {
+            Ok(true) => info!("Regenerated {}", out_file.display()),
+            Ok(false) => debug!("{} is unchanged", out_file.display()),
+            Err(err) => error!("Failed to regenerate {}: {err}", out_file.display()),
+        }
+    })
+}
+
+/// Calls `regenerate` once, then again whenever a .msg, .srv, .action or package.xml file within `paths`
+/// is created, modified or removed, e.g. for tools generating something other than Rust source from the definitions.
+/// Runs until watching `paths` fails.
+pub fn watch_for_changes(paths: &[PathBuf], mut regenerate: impl FnMut()) -> Result<(), Error> {
+    let changes = DefinitionChanges::watch(paths)?;
+    loop {
+        regenerate();
+        let changed = changes.wait()?;
+        debug!("Regenerating after changes to {changed:?}");
+    }
+}
+
+// Generates into `out_file`, returning whether its contents changed
+fn generate_to_file(
+    search_paths: Vec<PathBuf>,
+    out_file: &Path,
+    options: &CodegenOptions,
+) -> Result<bool, Error> {
+    let (source, _dependent_paths) =
+        crate::find_and_generate_ros_messages_without_ros_package_path_with_options(
+            search_paths,
+            options,
+        )?;
+    let source = source.to_string();
+    if std::fs::read_to_string(out_file).is_ok_and(|current| current == source) {
+        return Ok(false);
+    }
+    if let Some(parent) = out_file.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| Error::with("Failed to create the output directory:", err))?;
+    }
+    std::fs::write(out_file, source)
+        .map_err(|err| Error::with("Failed to write the generated source:", err))?;
+    Ok(true)
+}
+
+// Whether a change to `path` can change what is generated
+fn is_definition(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "package.xml")
+        || path
+            .extension()
+            .is_some_and(|ext| ext == "msg" || ext == "srv" || ext == "action")
+}
+
+// Filesystem notifications of changes to message definitions within the watched paths
+struct DefinitionChanges {
+    // Dropping the watcher stops the notifications
+    _watcher: notify::RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+}
+
+impl DefinitionChanges {
+    fn watch(paths: &[PathBuf]) -> Result<Self, Error> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)
+            .map_err(|err| Error::with("Failed to watch for changes:", err))?;
+        for path in paths {
+            watcher
+                .watch(path, RecursiveMode::Recursive)
+                .map_err(|err| {
+                    Error::with(
+                        format!("Failed to watch {path:?} for changes:").as_str(),
+                        err,
+                    )
+                })?;
+        }
+        Ok(Self {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    // Waits for changes to definitions, returning the paths changed once they settle
+    fn wait(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut changed = vec![];
+        while changed.is_empty() {
+            let event = self.events.recv().map_err(|_| stopped())?;
+            changed.extend(changed_definitions(event)?);
+        }
+        loop {
+            match self.events.recv_timeout(SETTLE_TIME) {
+                Ok(event) => changed.extend(changed_definitions(event)?),
+                Err(RecvTimeoutError::Timeout) => return Ok(changed),
+                Err(RecvTimeoutError::Disconnected) => return Err(stopped()),
+            }
+        }
+    }
+}
+
+fn stopped() -> Error {
+    Error::new("Stopped receiving changes from the watcher")
+}
+
+fn changed_definitions(event: notify::Result<notify::Event>) -> Result<Vec<PathBuf>, Error> {
+    let event = event.map_err(|err| Error::with("Failed while watching for changes:", err))?;
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) {
+        return Ok(vec![]);
+    }
+    Ok(event
+        .paths
+        .into_iter()
+        .filter(|path| is_definition(path))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{generate_to_file, is_definition, DefinitionChanges, GENERATED_FILE};
+    use crate::CodegenOptions;
+    use std::path::Path;
+
+    #[test]
+    fn only_definitions_trigger_regeneration() {
+        assert!(is_definition(Path::new("my_msgs/msg/Foo.msg")));
+        assert!(is_definition(Path::new("my_msgs/srv/Bar.srv")));
+        assert!(is_definition(Path::new("my_msgs/action/Baz.action")));
+        assert!(is_definition(Path::new("my_msgs/package.xml")));
+        assert!(!is_definition(Path::new("my_msgs/CMakeLists.txt")));
+        assert!(!is_definition(Path::new("my_msgs/msg/.Foo.msg.swp")));
+    }
+
+    #[test]
+    fn edited_definitions_are_regenerated() {
+        let root =
+            std::env::temp_dir().join(format!("roslibrust_watch_test_{}", std::process::id()));
+        let package = root.join("my_msgs");
+        std::fs::create_dir_all(package.join("msg")).unwrap();
+        std::fs::write(
+            package.join("package.xml"),
+            "<package><name>my_msgs</name></package>",
+        )
+        .unwrap();
+        std::fs::write(package.join("msg/Foo.msg"), "int32 first\n").unwrap();
+        let search_paths = vec![package.clone()];
+        let out_file = root.join("out").join(GENERATED_FILE);
+        let options = CodegenOptions::default();
+
+        assert!(generate_to_file(search_paths.clone(), &out_file, &options).unwrap());
+        let generated = std::fs::read_to_string(&out_file).unwrap();
+        assert!(generated.contains("first"));
+        // Nothing changed, so the file is left alone
+        assert!(!generate_to_file(search_paths.clone(), &out_file, &options).unwrap());
+
+        let changes = DefinitionChanges::watch(&search_paths).unwrap();
+        std::fs::write(package.join("msg/Foo.msg"), "int32 second\n").unwrap();
+        let changed = changes.wait().unwrap();
+        assert!(changed.iter().any(|path| path.ends_with("msg/Foo.msg")));
+
+        assert!(generate_to_file(search_paths.clone(), &out_file, &options).unwrap());
+        let generated = std::fs::read_to_string(&out_file).unwrap();
+        assert!(generated.contains("second") && !generated.contains("first"));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,5 +1,5 @@
 #[test]
 fn test_crawl() {
-    let packages = roslibrust_codegen::utils::crawl(&[env!("CARGO_MANIFEST_DIR")]);
+    let packages = roslibrust_codegen::utils::crawl(&[env!("CARGO_MANIFEST_DIR")]).unwrap();
     assert_eq!(packages.len(), 1);
 }
@@ -1,4 +1,5 @@
 use proc_macro::TokenStream;
+use quote::ToTokens;
 use syn::parse::{Parse, ParseStream};
 use syn::{parse_macro_input, Token};
 
@@ -22,6 +23,36 @@ impl Parse for RosLibRustMessagePaths {
     }
 }
 
+struct RosVersionPaths {
+    module: syn::Path,
+    paths: Vec<std::path::PathBuf>,
+}
+
+/// Parses `module::path: "path", "path";` describing where one ROS version's messages were generated from and to.
+impl Parse for RosVersionPaths {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let module = input.parse::<syn::Path>()?;
+        input.parse::<Token![:]>()?;
+        let RosLibRustMessagePaths { paths } = input.parse()?;
+        input.parse::<Token![;]>()?;
+        Ok(Self { module, paths })
+    }
+}
+
+struct RosConversionPaths {
+    ros1: RosVersionPaths,
+    ros2: RosVersionPaths,
+}
+
+impl Parse for RosConversionPaths {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            ros1: input.parse()?,
+            ros2: input.parse()?,
+        })
+    }
+}
+
 /// Given a list of paths, generates struct definitions and trait impls for any
 /// ros messages found within those paths.
 /// Paths are relative to where rustc is being invoked from your mileage may vary.
@@ -61,3 +92,155 @@ pub fn find_and_generate_ros_messages_without_ros_package_path(
         }
     }
 }
+
+/// Generates `From` conversions between the ROS1 and ROS2 versions of messages which have been
+/// generated into two separate modules. The ROS1 module and its paths come first, then the ROS2 ones:
+/// ```ignore
+/// roslibrust_codegen_macro::generate_ros1_ros2_conversions! {
+///     crate::ros1: "assets/ros1_common_interfaces/std_msgs";
+///     crate::ros2: "assets/ros2_common_interfaces/std_msgs";
+/// }
+/// ```
+/// Like `find_and_generate_ros_messages_without_ros_package_path`, only the given paths are searched.
+#[proc_macro]
+pub fn generate_ros1_ros2_conversions(input_stream: TokenStream) -> TokenStream {
+    let RosConversionPaths { ros1, ros2 } = parse_macro_input!(input_stream as RosConversionPaths);
+    let ros1_module = ros1.module.to_token_stream().to_string();
+    let ros2_module = ros2.module.to_token_stream().to_string();
+    match roslibrust_codegen::find_and_generate_ros1_ros2_conversions(
+        ros1.paths,
+        &ros1_module,
+        ros2.paths,
+        &ros2_module,
+    ) {
+        // Note: there is not currently a way for proc_macros to indicate that they need to be re-generated
+        // We discard the "dependent_paths" part of the response here...
+        Ok((source, _dependent_paths)) => source.into(),
+        Err(e) => {
+            let error_msg = e.to_string();
+            quote::quote!( compile_error!(#error_msg); ).into()
+        }
+    }
+}
+
+/// The `#[ros(...)]` attributes given to a type deriving `RosMessageType`
+#[derive(Default)]
+struct RosMessageAttributes {
+    type_name: Option<syn::LitStr>,
+    md5sum: Option<syn::LitStr>,
+    definition: Option<syn::LitStr>,
+}
+
+impl RosMessageAttributes {
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("ros")) {
+            let syn::Meta::List(list) = attr.parse_meta()? else {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "expected #[ros(type_name = \"...\", ...)]",
+                ));
+            };
+            for nested in list.nested {
+                let syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(value),
+                    ..
+                })) = nested
+                else {
+                    return Err(syn::Error::new_spanned(
+                        nested,
+                        "expected `type_name`, `md5sum` or `definition` set to a string",
+                    ));
+                };
+                let field = if path.is_ident("type_name") {
+                    &mut parsed.type_name
+                } else if path.is_ident("md5sum") {
+                    &mut parsed.md5sum
+                } else if path.is_ident("definition") {
+                    &mut parsed.definition
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        path,
+                        "expected `type_name`, `md5sum` or `definition`",
+                    ));
+                };
+                if field.replace(value).is_some() {
+                    return Err(syn::Error::new_spanned(path, "given more than once"));
+                }
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Implements `RosMessageType` for a hand written type, so it can be published and subscribed to like a
+/// generated message. Its serde representation has to match how ROS serializes the message,
+/// so fields are declared in the order of the definition with the Rust types codegen would give them.
+///
+/// The message is described by a `#[ros(...)]` attribute:
+/// * `type_name` -- The ROS type of the message, e.g. `my_pkg/Reading`, required
+/// * `definition` -- The message definition as it would appear in a .msg file, sent to ROS1 peers
+/// * `md5sum` -- The md5sum of the message, computed from `definition` when left out.
+///   Needed when the definition contains other messages, as their md5sums feed into it.
+///
+/// Without either, the message can only be used where md5sums aren't checked, e.g. with rosbridge.
+/// ```ignore
+/// #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, roslibrust_codegen_macro::RosMessageType)]
+/// #[ros(type_name = "my_pkg/Reading", definition = "float64 value\nstring label")]
+/// pub struct Reading {
+///     pub value: f64,
+///     pub label: String,
+/// }
+/// ```
+#[proc_macro_derive(RosMessageType, attributes(ros))]
+pub fn derive_ros_message_type(input_stream: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input_stream as syn::DeriveInput);
+    match impl_ros_message_type(&input) {
+        Ok(source) => source.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn impl_ros_message_type(input: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let attrs = RosMessageAttributes::parse(&input.attrs)?;
+    let type_name = attrs.type_name.ok_or_else(|| {
+        syn::Error::new_spanned(
+            &input.ident,
+            "deriving RosMessageType needs #[ros(type_name = \"package/Name\")]",
+        )
+    })?;
+    let definition = attrs
+        .definition
+        .as_ref()
+        .map(|definition| definition.value().trim().to_owned())
+        .unwrap_or_default();
+    let md5sum = match (&attrs.md5sum, &attrs.definition) {
+        (Some(md5sum), _) => {
+            let value = md5sum.value();
+            let is_md5sum = value.len() == 32 && value.chars().all(|c| c.is_ascii_hexdigit());
+            if !is_md5sum && value != "*" {
+                return Err(syn::Error::new_spanned(
+                    md5sum,
+                    "expected 32 hexadecimal digits, or * to match any md5sum",
+                ));
+            }
+            value
+        }
+        (None, Some(lit)) => {
+            roslibrust_codegen::compute_md5sum_of_definition(&type_name.value(), &definition)
+                .map_err(|e| syn::Error::new_spanned(lit, e))?
+        }
+        (None, None) => String::new(),
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote::quote! {
+        impl #impl_generics ::roslibrust_codegen::RosMessageType for #ident #ty_generics #where_clause {
+            const ROS_TYPE_NAME: &'static str = #type_name;
+            const MD5SUM: &'static str = #md5sum;
+            const DEFINITION: &'static str = #definition;
+        }
+    })
+}
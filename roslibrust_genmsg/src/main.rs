@@ -19,6 +19,9 @@ struct Args {
     /// Include namespaces for message dependencies
     #[arg(long, short = 'I', value_parser = include_namespace_parse)]
     include: Option<Vec<IncludedNamespace>>,
+    /// Keep running, regenerating whenever a definition within the include paths changes
+    #[arg(long, short)]
+    watch: bool,
 }
 
 fn include_namespace_parse(s: &str) -> Result<IncludedNamespace, String> {
@@ -46,25 +49,42 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         std::process::exit(1);
     }
 
-    let short_name = args.msg_path.file_stem().unwrap().to_str().unwrap();
-    let extension = args.msg_path.extension().unwrap().to_str().unwrap();
     let msg_paths = args
         .include
-        .unwrap_or_default()
-        .into_iter()
-        .map(|inc| inc.path)
+        .iter()
+        .flatten()
+        .map(|inc| inc.path.clone())
         .collect::<Vec<_>>();
-    let generator = roslibrust_genmsg::make_cpp_generator(&msg_paths).unwrap();
+    if args.watch {
+        // The input file's package is among the include paths, so they hold every definition it depends on
+        roslibrust_codegen::watch_for_changes(&msg_paths, || match generate(&args, &msg_paths) {
+            Ok(()) => log::info!("Generated headers for {}", args.msg_path.display()),
+            Err(err) => log::error!(
+                "Failed to generate headers for {}: {err}",
+                args.msg_path.display()
+            ),
+        })?;
+    } else if let Err(err) = generate(&args, &msg_paths) {
+        log::error!("{err}");
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn generate(args: &Args, msg_paths: &[PathBuf]) -> Result<(), Box<dyn std::error::Error>> {
+    let short_name = args.msg_path.file_stem().unwrap().to_str().unwrap();
+    let extension = args.msg_path.extension().unwrap().to_str().unwrap();
+    let generator = roslibrust_genmsg::make_cpp_generator(msg_paths)?;
 
     match extension {
         "msg" => {
-            let generated_source = generator.generate_messages().unwrap();
+            let generated_source = generator.generate_messages()?;
             let msg_source = generated_source
                 .iter()
                 .find(|msg| msg.message_name == short_name && msg.package_name == args.package)
-                .expect(
-                    format!("Could not find message: {}/{}", args.package, short_name).as_str(),
-                );
+                .ok_or_else(|| {
+                    format!("Could not find message: {}/{}", args.package, short_name)
+                })?;
             write_source_file(
                 &args.output,
                 &format!("{short_name}.h"),
@@ -72,11 +92,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             )?;
         }
         "srv" => {
-            let generated_source = generator.generate_services().unwrap();
+            let generated_source = generator.generate_services()?;
             let srv_source = generated_source
                 .iter()
                 .find(|srv| srv.service_name == short_name && srv.package_name == args.package)
-                .unwrap();
+                .ok_or_else(|| {
+                    format!("Could not find service: {}/{}", args.package, short_name)
+                })?;
             write_source_file(
                 &args.output,
                 &format!("{short_name}.h"),
@@ -104,7 +126,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 format!("{short_name}ActionFeedback"),
             ];
 
-            let generated_source = generator.generate_messages().unwrap();
+            let generated_source = generator.generate_messages()?;
             let action_sources = generated_source
                 .into_iter()
                 .filter(|msg| {
@@ -121,22 +143,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     )
                 })?;
             } else {
-                log::error!(
+                return Err(format!(
                     "Improperly generated action messages, generated: {:?}",
                     action_sources
                         .into_iter()
                         .map(|src| src.message_name)
                         .collect::<Vec<_>>()
-                );
-                std::process::exit(1);
+                )
+                .into());
             }
         }
         _ => {
-            log::error!(
+            return Err(format!(
                 "Unrecognized extension: {extension} in provided input file: {}",
                 args.msg_path.display()
-            );
-            std::process::exit(1);
+            )
+            .into());
         }
     }
     Ok(())
@@ -23,7 +23,9 @@ pub mod actionlib_msgs {
         PartialEq,
     )]
     pub struct GoalID {
+        #[serde(rename = "stamp")]
         pub r#stamp: ::roslibrust_codegen::integral_types::Time,
+        #[serde(rename = "id")]
         pub r#id: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for GoalID {
@@ -41,8 +43,11 @@ pub mod actionlib_msgs {
         PartialEq,
     )]
     pub struct GoalStatus {
+        #[serde(rename = "goal_id")]
         pub r#goal_id: self::GoalID,
+        #[serde(rename = "status")]
         pub r#status: u8,
+        #[serde(rename = "text")]
         pub r#text: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for GoalStatus {
@@ -72,7 +77,9 @@ pub mod actionlib_msgs {
         PartialEq,
     )]
     pub struct GoalStatusArray {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "status_list")]
         pub r#status_list: ::std::vec::Vec<self::GoalStatus>,
     }
     impl ::roslibrust_codegen::RosMessageType for GoalStatusArray {
@@ -106,7 +113,9 @@ pub mod diagnostic_msgs {
         PartialEq,
     )]
     pub struct DiagnosticArray {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "status")]
         pub r#status: ::std::vec::Vec<self::DiagnosticStatus>,
     }
     impl ::roslibrust_codegen::RosMessageType for DiagnosticArray {
@@ -124,10 +133,15 @@ pub mod diagnostic_msgs {
         PartialEq,
     )]
     pub struct DiagnosticStatus {
+        #[serde(rename = "level")]
         pub r#level: u8,
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "message")]
         pub r#message: ::std::string::String,
+        #[serde(rename = "hardware_id")]
         pub r#hardware_id: ::std::string::String,
+        #[serde(rename = "values")]
         pub r#values: ::std::vec::Vec<self::KeyValue>,
     }
     impl ::roslibrust_codegen::RosMessageType for DiagnosticStatus {
@@ -151,7 +165,9 @@ pub mod diagnostic_msgs {
         PartialEq,
     )]
     pub struct KeyValue {
+        #[serde(rename = "key")]
         pub r#key: ::std::string::String,
+        #[serde(rename = "value")]
         pub r#value: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for KeyValue {
@@ -169,6 +185,7 @@ pub mod diagnostic_msgs {
         PartialEq,
     )]
     pub struct AddDiagnosticsRequest {
+        #[serde(rename = "load_namespace")]
         pub r#load_namespace: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for AddDiagnosticsRequest {
@@ -186,7 +203,9 @@ pub mod diagnostic_msgs {
         PartialEq,
     )]
     pub struct AddDiagnosticsResponse {
+        #[serde(rename = "success")]
         pub r#success: bool,
+        #[serde(rename = "message")]
         pub r#message: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for AddDiagnosticsResponse {
@@ -226,8 +245,11 @@ pub mod diagnostic_msgs {
         PartialEq,
     )]
     pub struct SelfTestResponse {
+        #[serde(rename = "id")]
         pub r#id: ::std::string::String,
+        #[serde(rename = "passed")]
         pub r#passed: u8,
+        #[serde(rename = "status")]
         pub r#status: ::std::vec::Vec<self::DiagnosticStatus>,
     }
     impl ::roslibrust_codegen::RosMessageType for SelfTestResponse {
@@ -268,7 +290,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Accel {
+        #[serde(rename = "linear")]
         pub r#linear: self::Vector3,
+        #[serde(rename = "angular")]
         pub r#angular: self::Vector3,
     }
     impl ::roslibrust_codegen::RosMessageType for Accel {
@@ -286,7 +310,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct AccelStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "accel")]
         pub r#accel: self::Accel,
     }
     impl ::roslibrust_codegen::RosMessageType for AccelStamped {
@@ -305,7 +331,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct AccelWithCovariance {
+        #[serde(rename = "accel")]
         pub r#accel: self::Accel,
+        #[serde(rename = "covariance")]
         pub r#covariance: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for AccelWithCovariance {
@@ -323,7 +351,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct AccelWithCovarianceStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "accel")]
         pub r#accel: self::AccelWithCovariance,
     }
     impl ::roslibrust_codegen::RosMessageType for AccelWithCovarianceStamped {
@@ -341,13 +371,21 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Inertia {
+        #[serde(rename = "m")]
         pub r#m: f64,
+        #[serde(rename = "com")]
         pub r#com: self::Vector3,
+        #[serde(rename = "ixx")]
         pub r#ixx: f64,
+        #[serde(rename = "ixy")]
         pub r#ixy: f64,
+        #[serde(rename = "ixz")]
         pub r#ixz: f64,
+        #[serde(rename = "iyy")]
         pub r#iyy: f64,
+        #[serde(rename = "iyz")]
         pub r#iyz: f64,
+        #[serde(rename = "izz")]
         pub r#izz: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Inertia {
@@ -365,7 +403,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct InertiaStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "inertia")]
         pub r#inertia: self::Inertia,
     }
     impl ::roslibrust_codegen::RosMessageType for InertiaStamped {
@@ -383,8 +423,11 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Point {
+        #[serde(rename = "x")]
         pub r#x: f64,
+        #[serde(rename = "y")]
         pub r#y: f64,
+        #[serde(rename = "z")]
         pub r#z: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Point {
@@ -402,8 +445,11 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Point32 {
+        #[serde(rename = "x")]
         pub r#x: f32,
+        #[serde(rename = "y")]
         pub r#y: f32,
+        #[serde(rename = "z")]
         pub r#z: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for Point32 {
@@ -421,7 +467,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct PointStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "point")]
         pub r#point: self::Point,
     }
     impl ::roslibrust_codegen::RosMessageType for PointStamped {
@@ -439,6 +487,7 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Polygon {
+        #[serde(rename = "points")]
         pub r#points: ::std::vec::Vec<self::Point32>,
     }
     impl ::roslibrust_codegen::RosMessageType for Polygon {
@@ -456,7 +505,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct PolygonStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "polygon")]
         pub r#polygon: self::Polygon,
     }
     impl ::roslibrust_codegen::RosMessageType for PolygonStamped {
@@ -474,7 +525,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Pose {
+        #[serde(rename = "position")]
         pub r#position: self::Point,
+        #[serde(rename = "orientation")]
         pub r#orientation: self::Quaternion,
     }
     impl ::roslibrust_codegen::RosMessageType for Pose {
@@ -492,8 +545,11 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Pose2D {
+        #[serde(rename = "x")]
         pub r#x: f64,
+        #[serde(rename = "y")]
         pub r#y: f64,
+        #[serde(rename = "theta")]
         pub r#theta: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Pose2D {
@@ -511,7 +567,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct PoseArray {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "poses")]
         pub r#poses: ::std::vec::Vec<self::Pose>,
     }
     impl ::roslibrust_codegen::RosMessageType for PoseArray {
@@ -529,7 +587,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct PoseStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "pose")]
         pub r#pose: self::Pose,
     }
     impl ::roslibrust_codegen::RosMessageType for PoseStamped {
@@ -548,7 +608,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct PoseWithCovariance {
+        #[serde(rename = "pose")]
         pub r#pose: self::Pose,
+        #[serde(rename = "covariance")]
         pub r#covariance: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for PoseWithCovariance {
@@ -566,7 +628,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct PoseWithCovarianceStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "pose")]
         pub r#pose: self::PoseWithCovariance,
     }
     impl ::roslibrust_codegen::RosMessageType for PoseWithCovarianceStamped {
@@ -584,9 +648,13 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Quaternion {
+        #[serde(rename = "x")]
         pub r#x: f64,
+        #[serde(rename = "y")]
         pub r#y: f64,
+        #[serde(rename = "z")]
         pub r#z: f64,
+        #[serde(rename = "w")]
         pub r#w: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Quaternion {
@@ -604,7 +672,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct QuaternionStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "quaternion")]
         pub r#quaternion: self::Quaternion,
     }
     impl ::roslibrust_codegen::RosMessageType for QuaternionStamped {
@@ -622,7 +692,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Transform {
+        #[serde(rename = "translation")]
         pub r#translation: self::Vector3,
+        #[serde(rename = "rotation")]
         pub r#rotation: self::Quaternion,
     }
     impl ::roslibrust_codegen::RosMessageType for Transform {
@@ -640,8 +712,11 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct TransformStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "child_frame_id")]
         pub r#child_frame_id: ::std::string::String,
+        #[serde(rename = "transform")]
         pub r#transform: self::Transform,
     }
     impl ::roslibrust_codegen::RosMessageType for TransformStamped {
@@ -659,7 +734,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Twist {
+        #[serde(rename = "linear")]
         pub r#linear: self::Vector3,
+        #[serde(rename = "angular")]
         pub r#angular: self::Vector3,
     }
     impl ::roslibrust_codegen::RosMessageType for Twist {
@@ -677,7 +754,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct TwistStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "twist")]
         pub r#twist: self::Twist,
     }
     impl ::roslibrust_codegen::RosMessageType for TwistStamped {
@@ -696,7 +775,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct TwistWithCovariance {
+        #[serde(rename = "twist")]
         pub r#twist: self::Twist,
+        #[serde(rename = "covariance")]
         pub r#covariance: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for TwistWithCovariance {
@@ -714,7 +795,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct TwistWithCovarianceStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "twist")]
         pub r#twist: self::TwistWithCovariance,
     }
     impl ::roslibrust_codegen::RosMessageType for TwistWithCovarianceStamped {
@@ -732,8 +815,11 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Vector3 {
+        #[serde(rename = "x")]
         pub r#x: f64,
+        #[serde(rename = "y")]
         pub r#y: f64,
+        #[serde(rename = "z")]
         pub r#z: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Vector3 {
@@ -751,7 +837,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Vector3Stamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "vector")]
         pub r#vector: self::Vector3,
     }
     impl ::roslibrust_codegen::RosMessageType for Vector3Stamped {
@@ -769,7 +857,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Wrench {
+        #[serde(rename = "force")]
         pub r#force: self::Vector3,
+        #[serde(rename = "torque")]
         pub r#torque: self::Vector3,
     }
     impl ::roslibrust_codegen::RosMessageType for Wrench {
@@ -787,7 +877,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct WrenchStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "wrench")]
         pub r#wrench: self::Wrench,
     }
     impl ::roslibrust_codegen::RosMessageType for WrenchStamped {
@@ -821,8 +913,11 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct GetMapAction {
+        #[serde(rename = "action_goal")]
         pub r#action_goal: self::GetMapActionGoal,
+        #[serde(rename = "action_result")]
         pub r#action_result: self::GetMapActionResult,
+        #[serde(rename = "action_feedback")]
         pub r#action_feedback: self::GetMapActionFeedback,
     }
     impl ::roslibrust_codegen::RosMessageType for GetMapAction {
@@ -840,8 +935,11 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct GetMapActionFeedback {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "status")]
         pub r#status: actionlib_msgs::GoalStatus,
+        #[serde(rename = "feedback")]
         pub r#feedback: self::GetMapFeedback,
     }
     impl ::roslibrust_codegen::RosMessageType for GetMapActionFeedback {
@@ -860,8 +958,11 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct GetMapActionGoal {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "goal_id")]
         pub r#goal_id: actionlib_msgs::GoalID,
+        #[serde(rename = "goal")]
         pub r#goal: self::GetMapGoal,
     }
     impl ::roslibrust_codegen::RosMessageType for GetMapActionGoal {
@@ -880,8 +981,11 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct GetMapActionResult {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "status")]
         pub r#status: actionlib_msgs::GoalStatus,
+        #[serde(rename = "result")]
         pub r#result: self::GetMapResult,
     }
     impl ::roslibrust_codegen::RosMessageType for GetMapActionResult {
@@ -930,6 +1034,7 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct GetMapResult {
+        #[serde(rename = "map")]
         pub r#map: self::OccupancyGrid,
     }
     impl ::roslibrust_codegen::RosMessageType for GetMapResult {
@@ -947,9 +1052,13 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct GridCells {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "cell_width")]
         pub r#cell_width: f32,
+        #[serde(rename = "cell_height")]
         pub r#cell_height: f32,
+        #[serde(rename = "cells")]
         pub r#cells: ::std::vec::Vec<geometry_msgs::Point>,
     }
     impl ::roslibrust_codegen::RosMessageType for GridCells {
@@ -967,10 +1076,15 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct MapMetaData {
+        #[serde(rename = "map_load_time")]
         pub r#map_load_time: ::roslibrust_codegen::integral_types::Time,
+        #[serde(rename = "resolution")]
         pub r#resolution: f32,
+        #[serde(rename = "width")]
         pub r#width: u32,
+        #[serde(rename = "height")]
         pub r#height: u32,
+        #[serde(rename = "origin")]
         pub r#origin: geometry_msgs::Pose,
     }
     impl ::roslibrust_codegen::RosMessageType for MapMetaData {
@@ -988,8 +1102,11 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct OccupancyGrid {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "info")]
         pub r#info: self::MapMetaData,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<i8>,
     }
     impl ::roslibrust_codegen::RosMessageType for OccupancyGrid {
@@ -1007,9 +1124,13 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct Odometry {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "child_frame_id")]
         pub r#child_frame_id: ::std::string::String,
+        #[serde(rename = "pose")]
         pub r#pose: geometry_msgs::PoseWithCovariance,
+        #[serde(rename = "twist")]
         pub r#twist: geometry_msgs::TwistWithCovariance,
     }
     impl ::roslibrust_codegen::RosMessageType for Odometry {
@@ -1027,7 +1148,9 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct Path {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "poses")]
         pub r#poses: ::std::vec::Vec<geometry_msgs::PoseStamped>,
     }
     impl ::roslibrust_codegen::RosMessageType for Path {
@@ -1060,6 +1183,7 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct GetMapResponse {
+        #[serde(rename = "map")]
         pub r#map: self::OccupancyGrid,
     }
     impl ::roslibrust_codegen::RosMessageType for GetMapResponse {
@@ -1084,8 +1208,11 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct GetPlanRequest {
+        #[serde(rename = "start")]
         pub r#start: geometry_msgs::PoseStamped,
+        #[serde(rename = "goal")]
         pub r#goal: geometry_msgs::PoseStamped,
+        #[serde(rename = "tolerance")]
         pub r#tolerance: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for GetPlanRequest {
@@ -1103,6 +1230,7 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct GetPlanResponse {
+        #[serde(rename = "plan")]
         pub r#plan: self::Path,
     }
     impl ::roslibrust_codegen::RosMessageType for GetPlanResponse {
@@ -1127,6 +1255,7 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct LoadMapRequest {
+        #[serde(rename = "map_url")]
         pub r#map_url: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for LoadMapRequest {
@@ -1144,7 +1273,9 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct LoadMapResponse {
+        #[serde(rename = "map")]
         pub r#map: self::OccupancyGrid,
+        #[serde(rename = "result")]
         pub r#result: u8,
     }
     impl ::roslibrust_codegen::RosMessageType for LoadMapResponse {
@@ -1176,7 +1307,9 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct SetMapRequest {
+        #[serde(rename = "map")]
         pub r#map: self::OccupancyGrid,
+        #[serde(rename = "initial_pose")]
         pub r#initial_pose: geometry_msgs::PoseWithCovarianceStamped,
     }
     impl ::roslibrust_codegen::RosMessageType for SetMapRequest {
@@ -1194,6 +1327,7 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct SetMapResponse {
+        #[serde(rename = "success")]
         pub r#success: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for SetMapResponse {
@@ -1208,6 +1342,15 @@ pub mod nav_msgs {
         type Request = SetMapRequest;
         type Response = SetMapResponse;
     }
+    impl ::roslibrust_codegen::RosActionType for GetMapAction {
+        const ROS_ACTION_NAME: &'static str = "nav_msgs/GetMap";
+        type Goal = GetMapGoal;
+        type Result = GetMapResult;
+        type Feedback = GetMapFeedback;
+        type ActionGoal = GetMapActionGoal;
+        type ActionResult = GetMapActionResult;
+        type ActionFeedback = GetMapActionFeedback;
+    }
 }
 #[allow(unused_imports)]
 pub mod rosapi {
@@ -1234,12 +1377,19 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct TypeDef {
+        #[serde(rename = "type")]
         pub r#type: ::std::string::String,
+        #[serde(rename = "fieldnames")]
         pub r#fieldnames: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "fieldtypes")]
         pub r#fieldtypes: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "fieldarraylen")]
         pub r#fieldarraylen: ::std::vec::Vec<i32>,
+        #[serde(rename = "examples")]
         pub r#examples: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "constnames")]
         pub r#constnames: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "constvalues")]
         pub r#constvalues: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for TypeDef {
@@ -1257,6 +1407,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct DeleteParamRequest {
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for DeleteParamRequest {
@@ -1311,6 +1462,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct GetActionServersResponse {
+        #[serde(rename = "action_servers")]
         pub r#action_servers: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for GetActionServersResponse {
@@ -1335,7 +1487,9 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct GetParamRequest {
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "default")]
         pub r#default: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for GetParamRequest {
@@ -1353,6 +1507,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct GetParamResponse {
+        #[serde(rename = "value")]
         pub r#value: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for GetParamResponse {
@@ -1392,6 +1547,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct GetParamNamesResponse {
+        #[serde(rename = "names")]
         pub r#names: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for GetParamNamesResponse {
@@ -1431,6 +1587,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct GetTimeResponse {
+        #[serde(rename = "time")]
         pub r#time: ::roslibrust_codegen::integral_types::Time,
     }
     impl ::roslibrust_codegen::RosMessageType for GetTimeResponse {
@@ -1455,6 +1612,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct HasParamRequest {
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for HasParamRequest {
@@ -1472,6 +1630,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct HasParamResponse {
+        #[serde(rename = "exists")]
         pub r#exists: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for HasParamResponse {
@@ -1496,6 +1655,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct MessageDetailsRequest {
+        #[serde(rename = "type")]
         pub r#type: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for MessageDetailsRequest {
@@ -1513,6 +1673,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct MessageDetailsResponse {
+        #[serde(rename = "typedefs")]
         pub r#typedefs: ::std::vec::Vec<self::TypeDef>,
     }
     impl ::roslibrust_codegen::RosMessageType for MessageDetailsResponse {
@@ -1537,6 +1698,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct NodeDetailsRequest {
+        #[serde(rename = "node")]
         pub r#node: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for NodeDetailsRequest {
@@ -1554,8 +1716,11 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct NodeDetailsResponse {
+        #[serde(rename = "subscribing")]
         pub r#subscribing: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "publishing")]
         pub r#publishing: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "services")]
         pub r#services: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for NodeDetailsResponse {
@@ -1596,6 +1761,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct NodesResponse {
+        #[serde(rename = "nodes")]
         pub r#nodes: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for NodesResponse {
@@ -1620,6 +1786,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct PublishersRequest {
+        #[serde(rename = "topic")]
         pub r#topic: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for PublishersRequest {
@@ -1637,6 +1804,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct PublishersResponse {
+        #[serde(rename = "publishers")]
         pub r#publishers: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for PublishersResponse {
@@ -1661,6 +1829,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct SearchParamRequest {
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for SearchParamRequest {
@@ -1678,6 +1847,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct SearchParamResponse {
+        #[serde(rename = "global_name")]
         pub r#global_name: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for SearchParamResponse {
@@ -1702,6 +1872,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServiceHostRequest {
+        #[serde(rename = "service")]
         pub r#service: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for ServiceHostRequest {
@@ -1719,6 +1890,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServiceHostResponse {
+        #[serde(rename = "host")]
         pub r#host: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for ServiceHostResponse {
@@ -1743,6 +1915,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServiceNodeRequest {
+        #[serde(rename = "service")]
         pub r#service: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for ServiceNodeRequest {
@@ -1760,6 +1933,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServiceNodeResponse {
+        #[serde(rename = "node")]
         pub r#node: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for ServiceNodeResponse {
@@ -1784,6 +1958,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServiceProvidersRequest {
+        #[serde(rename = "service")]
         pub r#service: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for ServiceProvidersRequest {
@@ -1801,6 +1976,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServiceProvidersResponse {
+        #[serde(rename = "providers")]
         pub r#providers: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for ServiceProvidersResponse {
@@ -1825,6 +2001,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServiceRequestDetailsRequest {
+        #[serde(rename = "type")]
         pub r#type: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for ServiceRequestDetailsRequest {
@@ -1842,6 +2019,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServiceRequestDetailsResponse {
+        #[serde(rename = "typedefs")]
         pub r#typedefs: ::std::vec::Vec<self::TypeDef>,
     }
     impl ::roslibrust_codegen::RosMessageType for ServiceRequestDetailsResponse {
@@ -1866,6 +2044,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServiceResponseDetailsRequest {
+        #[serde(rename = "type")]
         pub r#type: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for ServiceResponseDetailsRequest {
@@ -1883,6 +2062,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServiceResponseDetailsResponse {
+        #[serde(rename = "typedefs")]
         pub r#typedefs: ::std::vec::Vec<self::TypeDef>,
     }
     impl ::roslibrust_codegen::RosMessageType for ServiceResponseDetailsResponse {
@@ -1907,6 +2087,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServiceTypeRequest {
+        #[serde(rename = "service")]
         pub r#service: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for ServiceTypeRequest {
@@ -1924,6 +2105,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServiceTypeResponse {
+        #[serde(rename = "type")]
         pub r#type: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for ServiceTypeResponse {
@@ -1963,6 +2145,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServicesResponse {
+        #[serde(rename = "services")]
         pub r#services: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for ServicesResponse {
@@ -1987,6 +2170,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServicesForTypeRequest {
+        #[serde(rename = "type")]
         pub r#type: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for ServicesForTypeRequest {
@@ -2004,6 +2188,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct ServicesForTypeResponse {
+        #[serde(rename = "services")]
         pub r#services: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for ServicesForTypeResponse {
@@ -2028,7 +2213,9 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct SetParamRequest {
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "value")]
         pub r#value: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for SetParamRequest {
@@ -2068,6 +2255,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct SubscribersRequest {
+        #[serde(rename = "topic")]
         pub r#topic: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for SubscribersRequest {
@@ -2085,6 +2273,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct SubscribersResponse {
+        #[serde(rename = "subscribers")]
         pub r#subscribers: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for SubscribersResponse {
@@ -2109,6 +2298,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct TopicTypeRequest {
+        #[serde(rename = "topic")]
         pub r#topic: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for TopicTypeRequest {
@@ -2126,6 +2316,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct TopicTypeResponse {
+        #[serde(rename = "type")]
         pub r#type: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for TopicTypeResponse {
@@ -2165,7 +2356,9 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct TopicsResponse {
+        #[serde(rename = "topics")]
         pub r#topics: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "types")]
         pub r#types: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for TopicsResponse {
@@ -2205,8 +2398,11 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct TopicsAndRawTypesResponse {
+        #[serde(rename = "topics")]
         pub r#topics: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "types")]
         pub r#types: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "typedefs_full_text")]
         pub r#typedefs_full_text: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for TopicsAndRawTypesResponse {
@@ -2232,6 +2428,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct TopicsForTypeRequest {
+        #[serde(rename = "type")]
         pub r#type: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for TopicsForTypeRequest {
@@ -2249,6 +2446,7 @@ pub mod rosapi {
         PartialEq,
     )]
     pub struct TopicsForTypeResponse {
+        #[serde(rename = "topics")]
         pub r#topics: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for TopicsForTypeResponse {
@@ -2289,6 +2487,7 @@ pub mod rosgraph_msgs {
         PartialEq,
     )]
     pub struct Clock {
+        #[serde(rename = "clock")]
         pub r#clock: ::roslibrust_codegen::integral_types::Time,
     }
     impl ::roslibrust_codegen::RosMessageType for Clock {
@@ -2306,13 +2505,21 @@ pub mod rosgraph_msgs {
         PartialEq,
     )]
     pub struct Log {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "level")]
         pub r#level: u8,
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "msg")]
         pub r#msg: ::std::string::String,
+        #[serde(rename = "file")]
         pub r#file: ::std::string::String,
+        #[serde(rename = "function")]
         pub r#function: ::std::string::String,
+        #[serde(rename = "line")]
         pub r#line: u32,
+        #[serde(rename = "topics")]
         pub r#topics: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for Log {
@@ -2337,19 +2544,33 @@ pub mod rosgraph_msgs {
         PartialEq,
     )]
     pub struct TopicStatistics {
+        #[serde(rename = "topic")]
         pub r#topic: ::std::string::String,
+        #[serde(rename = "node_pub")]
         pub r#node_pub: ::std::string::String,
+        #[serde(rename = "node_sub")]
         pub r#node_sub: ::std::string::String,
+        #[serde(rename = "window_start")]
         pub r#window_start: ::roslibrust_codegen::integral_types::Time,
+        #[serde(rename = "window_stop")]
         pub r#window_stop: ::roslibrust_codegen::integral_types::Time,
+        #[serde(rename = "delivered_msgs")]
         pub r#delivered_msgs: i32,
+        #[serde(rename = "dropped_msgs")]
         pub r#dropped_msgs: i32,
+        #[serde(rename = "traffic")]
         pub r#traffic: i32,
+        #[serde(rename = "period_mean")]
         pub r#period_mean: ::roslibrust_codegen::integral_types::Duration,
+        #[serde(rename = "period_stddev")]
         pub r#period_stddev: ::roslibrust_codegen::integral_types::Duration,
+        #[serde(rename = "period_max")]
         pub r#period_max: ::roslibrust_codegen::integral_types::Duration,
+        #[serde(rename = "stamp_age_mean")]
         pub r#stamp_age_mean: ::roslibrust_codegen::integral_types::Duration,
+        #[serde(rename = "stamp_age_stddev")]
         pub r#stamp_age_stddev: ::roslibrust_codegen::integral_types::Duration,
+        #[serde(rename = "stamp_age_max")]
         pub r#stamp_age_max: ::roslibrust_codegen::integral_types::Duration,
     }
     impl ::roslibrust_codegen::RosMessageType for TopicStatistics {
@@ -2383,21 +2604,37 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct BatteryState {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "voltage")]
         pub r#voltage: f32,
+        #[serde(rename = "temperature")]
         pub r#temperature: f32,
+        #[serde(rename = "current")]
         pub r#current: f32,
+        #[serde(rename = "charge")]
         pub r#charge: f32,
+        #[serde(rename = "capacity")]
         pub r#capacity: f32,
+        #[serde(rename = "design_capacity")]
         pub r#design_capacity: f32,
+        #[serde(rename = "percentage")]
         pub r#percentage: f32,
+        #[serde(rename = "power_supply_status")]
         pub r#power_supply_status: u8,
+        #[serde(rename = "power_supply_health")]
         pub r#power_supply_health: u8,
+        #[serde(rename = "power_supply_technology")]
         pub r#power_supply_technology: u8,
+        #[serde(rename = "present")]
         pub r#present: bool,
+        #[serde(rename = "cell_voltage")]
         pub r#cell_voltage: ::std::vec::Vec<f32>,
+        #[serde(rename = "cell_temperature")]
         pub r#cell_temperature: ::std::vec::Vec<f32>,
+        #[serde(rename = "location")]
         pub r#location: ::std::string::String,
+        #[serde(rename = "serial_number")]
         pub r#serial_number: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for BatteryState {
@@ -2438,16 +2675,27 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct CameraInfo {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "height")]
         pub r#height: u32,
+        #[serde(rename = "width")]
         pub r#width: u32,
+        #[serde(rename = "distortion_model")]
         pub r#distortion_model: ::std::string::String,
+        #[serde(rename = "D")]
         pub r#D: ::std::vec::Vec<f64>,
+        #[serde(rename = "K")]
         pub r#K: ::std::vec::Vec<f64>,
+        #[serde(rename = "R")]
         pub r#R: ::std::vec::Vec<f64>,
+        #[serde(rename = "P")]
         pub r#P: ::std::vec::Vec<f64>,
+        #[serde(rename = "binning_x")]
         pub r#binning_x: u32,
+        #[serde(rename = "binning_y")]
         pub r#binning_y: u32,
+        #[serde(rename = "roi")]
         pub r#roi: self::RegionOfInterest,
     }
     impl ::roslibrust_codegen::RosMessageType for CameraInfo {
@@ -2465,7 +2713,9 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct ChannelFloat32 {
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "values")]
         pub r#values: ::std::vec::Vec<f32>,
     }
     impl ::roslibrust_codegen::RosMessageType for ChannelFloat32 {
@@ -2483,8 +2733,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct CompressedImage {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "format")]
         pub r#format: ::std::string::String,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u8>,
     }
     impl ::roslibrust_codegen::RosMessageType for CompressedImage {
@@ -2502,8 +2755,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct FluidPressure {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "fluid_pressure")]
         pub r#fluid_pressure: f64,
+        #[serde(rename = "variance")]
         pub r#variance: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for FluidPressure {
@@ -2521,8 +2777,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct Illuminance {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "illuminance")]
         pub r#illuminance: f64,
+        #[serde(rename = "variance")]
         pub r#variance: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Illuminance {
@@ -2540,12 +2799,19 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct Image {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "height")]
         pub r#height: u32,
+        #[serde(rename = "width")]
         pub r#width: u32,
+        #[serde(rename = "encoding")]
         pub r#encoding: ::std::string::String,
+        #[serde(rename = "is_bigendian")]
         pub r#is_bigendian: u8,
+        #[serde(rename = "step")]
         pub r#step: u32,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u8>,
     }
     impl ::roslibrust_codegen::RosMessageType for Image {
@@ -2563,12 +2829,19 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct Imu {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "orientation")]
         pub r#orientation: geometry_msgs::Quaternion,
+        #[serde(rename = "orientation_covariance")]
         pub r#orientation_covariance: ::std::vec::Vec<f64>,
+        #[serde(rename = "angular_velocity")]
         pub r#angular_velocity: geometry_msgs::Vector3,
+        #[serde(rename = "angular_velocity_covariance")]
         pub r#angular_velocity_covariance: ::std::vec::Vec<f64>,
+        #[serde(rename = "linear_acceleration")]
         pub r#linear_acceleration: geometry_msgs::Vector3,
+        #[serde(rename = "linear_acceleration_covariance")]
         pub r#linear_acceleration_covariance: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for Imu {
@@ -2586,10 +2859,15 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct JointState {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "name")]
         pub r#name: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "position")]
         pub r#position: ::std::vec::Vec<f64>,
+        #[serde(rename = "velocity")]
         pub r#velocity: ::std::vec::Vec<f64>,
+        #[serde(rename = "effort")]
         pub r#effort: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for JointState {
@@ -2607,8 +2885,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct Joy {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "axes")]
         pub r#axes: ::std::vec::Vec<f32>,
+        #[serde(rename = "buttons")]
         pub r#buttons: ::std::vec::Vec<i32>,
     }
     impl ::roslibrust_codegen::RosMessageType for Joy {
@@ -2626,8 +2907,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct JoyFeedback {
+        #[serde(rename = "type")]
         pub r#type: u8,
+        #[serde(rename = "id")]
         pub r#id: u8,
+        #[serde(rename = "intensity")]
         pub r#intensity: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for JoyFeedback {
@@ -2650,6 +2934,7 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct JoyFeedbackArray {
+        #[serde(rename = "array")]
         pub r#array: ::std::vec::Vec<self::JoyFeedback>,
     }
     impl ::roslibrust_codegen::RosMessageType for JoyFeedbackArray {
@@ -2668,6 +2953,7 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct LaserEcho {
+        #[serde(rename = "echoes")]
         pub r#echoes: ::std::vec::Vec<f32>,
     }
     impl ::roslibrust_codegen::RosMessageType for LaserEcho {
@@ -2685,15 +2971,25 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct LaserScan {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "angle_min")]
         pub r#angle_min: f32,
+        #[serde(rename = "angle_max")]
         pub r#angle_max: f32,
+        #[serde(rename = "angle_increment")]
         pub r#angle_increment: f32,
+        #[serde(rename = "time_increment")]
         pub r#time_increment: f32,
+        #[serde(rename = "scan_time")]
         pub r#scan_time: f32,
+        #[serde(rename = "range_min")]
         pub r#range_min: f32,
+        #[serde(rename = "range_max")]
         pub r#range_max: f32,
+        #[serde(rename = "ranges")]
         pub r#ranges: ::std::vec::Vec<f32>,
+        #[serde(rename = "intensities")]
         pub r#intensities: ::std::vec::Vec<f32>,
     }
     impl ::roslibrust_codegen::RosMessageType for LaserScan {
@@ -2711,8 +3007,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct MagneticField {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "magnetic_field")]
         pub r#magnetic_field: geometry_msgs::Vector3,
+        #[serde(rename = "magnetic_field_covariance")]
         pub r#magnetic_field_covariance: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for MagneticField {
@@ -2730,10 +3029,15 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct MultiDOFJointState {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "joint_names")]
         pub r#joint_names: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "transforms")]
         pub r#transforms: ::std::vec::Vec<geometry_msgs::Transform>,
+        #[serde(rename = "twist")]
         pub r#twist: ::std::vec::Vec<geometry_msgs::Twist>,
+        #[serde(rename = "wrench")]
         pub r#wrench: ::std::vec::Vec<geometry_msgs::Wrench>,
     }
     impl ::roslibrust_codegen::RosMessageType for MultiDOFJointState {
@@ -2751,15 +3055,25 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct MultiEchoLaserScan {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "angle_min")]
         pub r#angle_min: f32,
+        #[serde(rename = "angle_max")]
         pub r#angle_max: f32,
+        #[serde(rename = "angle_increment")]
         pub r#angle_increment: f32,
+        #[serde(rename = "time_increment")]
         pub r#time_increment: f32,
+        #[serde(rename = "scan_time")]
         pub r#scan_time: f32,
+        #[serde(rename = "range_min")]
         pub r#range_min: f32,
+        #[serde(rename = "range_max")]
         pub r#range_max: f32,
+        #[serde(rename = "ranges")]
         pub r#ranges: ::std::vec::Vec<self::LaserEcho>,
+        #[serde(rename = "intensities")]
         pub r#intensities: ::std::vec::Vec<self::LaserEcho>,
     }
     impl ::roslibrust_codegen::RosMessageType for MultiEchoLaserScan {
@@ -2777,12 +3091,19 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct NavSatFix {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "status")]
         pub r#status: self::NavSatStatus,
+        #[serde(rename = "latitude")]
         pub r#latitude: f64,
+        #[serde(rename = "longitude")]
         pub r#longitude: f64,
+        #[serde(rename = "altitude")]
         pub r#altitude: f64,
+        #[serde(rename = "position_covariance")]
         pub r#position_covariance: ::std::vec::Vec<f64>,
+        #[serde(rename = "position_covariance_type")]
         pub r#position_covariance_type: u8,
     }
     impl ::roslibrust_codegen::RosMessageType for NavSatFix {
@@ -2806,7 +3127,9 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct NavSatStatus {
+        #[serde(rename = "status")]
         pub r#status: i8,
+        #[serde(rename = "service")]
         pub r#service: u16,
     }
     impl ::roslibrust_codegen::RosMessageType for NavSatStatus {
@@ -2834,8 +3157,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct PointCloud {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "points")]
         pub r#points: ::std::vec::Vec<geometry_msgs::Point32>,
+        #[serde(rename = "channels")]
         pub r#channels: ::std::vec::Vec<self::ChannelFloat32>,
     }
     impl ::roslibrust_codegen::RosMessageType for PointCloud {
@@ -2853,14 +3179,23 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct PointCloud2 {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "height")]
         pub r#height: u32,
+        #[serde(rename = "width")]
         pub r#width: u32,
+        #[serde(rename = "fields")]
         pub r#fields: ::std::vec::Vec<self::PointField>,
+        #[serde(rename = "is_bigendian")]
         pub r#is_bigendian: bool,
+        #[serde(rename = "point_step")]
         pub r#point_step: u32,
+        #[serde(rename = "row_step")]
         pub r#row_step: u32,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u8>,
+        #[serde(rename = "is_dense")]
         pub r#is_dense: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for PointCloud2 {
@@ -2878,9 +3213,13 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct PointField {
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "offset")]
         pub r#offset: u32,
+        #[serde(rename = "datatype")]
         pub r#datatype: u8,
+        #[serde(rename = "count")]
         pub r#count: u32,
     }
     impl ::roslibrust_codegen::RosMessageType for PointField {
@@ -2908,11 +3247,17 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct Range {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "radiation_type")]
         pub r#radiation_type: u8,
+        #[serde(rename = "field_of_view")]
         pub r#field_of_view: f32,
+        #[serde(rename = "min_range")]
         pub r#min_range: f32,
+        #[serde(rename = "max_range")]
         pub r#max_range: f32,
+        #[serde(rename = "range")]
         pub r#range: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for Range {
@@ -2934,10 +3279,15 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct RegionOfInterest {
+        #[serde(rename = "x_offset")]
         pub r#x_offset: u32,
+        #[serde(rename = "y_offset")]
         pub r#y_offset: u32,
+        #[serde(rename = "height")]
         pub r#height: u32,
+        #[serde(rename = "width")]
         pub r#width: u32,
+        #[serde(rename = "do_rectify")]
         pub r#do_rectify: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for RegionOfInterest {
@@ -2955,8 +3305,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct RelativeHumidity {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "relative_humidity")]
         pub r#relative_humidity: f64,
+        #[serde(rename = "variance")]
         pub r#variance: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for RelativeHumidity {
@@ -2974,8 +3327,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct Temperature {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "temperature")]
         pub r#temperature: f64,
+        #[serde(rename = "variance")]
         pub r#variance: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Temperature {
@@ -2993,8 +3349,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct TimeReference {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "time_ref")]
         pub r#time_ref: ::roslibrust_codegen::integral_types::Time,
+        #[serde(rename = "source")]
         pub r#source: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for TimeReference {
@@ -3012,6 +3371,7 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct SetCameraInfoRequest {
+        #[serde(rename = "camera_info")]
         pub r#camera_info: self::CameraInfo,
     }
     impl ::roslibrust_codegen::RosMessageType for SetCameraInfoRequest {
@@ -3029,7 +3389,9 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct SetCameraInfoResponse {
+        #[serde(rename = "success")]
         pub r#success: bool,
+        #[serde(rename = "status_message")]
         pub r#status_message: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for SetCameraInfoResponse {
@@ -3070,7 +3432,9 @@ pub mod shape_msgs {
         PartialEq,
     )]
     pub struct Mesh {
+        #[serde(rename = "triangles")]
         pub r#triangles: ::std::vec::Vec<self::MeshTriangle>,
+        #[serde(rename = "vertices")]
         pub r#vertices: ::std::vec::Vec<geometry_msgs::Point>,
     }
     impl ::roslibrust_codegen::RosMessageType for Mesh {
@@ -3088,6 +3452,7 @@ pub mod shape_msgs {
         PartialEq,
     )]
     pub struct MeshTriangle {
+        #[serde(rename = "vertex_indices")]
         pub r#vertex_indices: ::std::vec::Vec<u32>,
     }
     impl ::roslibrust_codegen::RosMessageType for MeshTriangle {
@@ -3106,6 +3471,7 @@ pub mod shape_msgs {
         PartialEq,
     )]
     pub struct Plane {
+        #[serde(rename = "coef")]
         pub r#coef: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for Plane {
@@ -3123,7 +3489,9 @@ pub mod shape_msgs {
         PartialEq,
     )]
     pub struct SolidPrimitive {
+        #[serde(rename = "type")]
         pub r#type: u8,
+        #[serde(rename = "dimensions")]
         pub r#dimensions: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for SolidPrimitive {
@@ -3171,6 +3539,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Bool {
+        #[serde(rename = "data")]
         pub r#data: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for Bool {
@@ -3188,6 +3557,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Byte {
+        #[serde(rename = "data")]
         pub r#data: u8,
     }
     impl ::roslibrust_codegen::RosMessageType for Byte {
@@ -3205,7 +3575,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct ByteMultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u8>,
     }
     impl ::roslibrust_codegen::RosMessageType for ByteMultiArray {
@@ -3223,6 +3595,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Char {
+        #[serde(rename = "data")]
         pub r#data: u8,
     }
     impl ::roslibrust_codegen::RosMessageType for Char {
@@ -3240,9 +3613,13 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct ColorRGBA {
+        #[serde(rename = "r")]
         pub r#r: f32,
+        #[serde(rename = "g")]
         pub r#g: f32,
+        #[serde(rename = "b")]
         pub r#b: f32,
+        #[serde(rename = "a")]
         pub r#a: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for ColorRGBA {
@@ -3260,6 +3637,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Duration {
+        #[serde(rename = "data")]
         pub r#data: ::roslibrust_codegen::integral_types::Duration,
     }
     impl ::roslibrust_codegen::RosMessageType for Duration {
@@ -3292,6 +3670,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Float32 {
+        #[serde(rename = "data")]
         pub r#data: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for Float32 {
@@ -3309,7 +3688,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Float32MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<f32>,
     }
     impl ::roslibrust_codegen::RosMessageType for Float32MultiArray {
@@ -3327,6 +3708,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Float64 {
+        #[serde(rename = "data")]
         pub r#data: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Float64 {
@@ -3344,7 +3726,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Float64MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for Float64MultiArray {
@@ -3362,8 +3746,11 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Header {
+        #[serde(rename = "seq")]
         pub r#seq: u32,
+        #[serde(rename = "stamp")]
         pub r#stamp: ::roslibrust_codegen::integral_types::Time,
+        #[serde(rename = "frame_id")]
         pub r#frame_id: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for Header {
@@ -3381,6 +3768,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int16 {
+        #[serde(rename = "data")]
         pub r#data: i16,
     }
     impl ::roslibrust_codegen::RosMessageType for Int16 {
@@ -3398,7 +3786,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int16MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<i16>,
     }
     impl ::roslibrust_codegen::RosMessageType for Int16MultiArray {
@@ -3416,6 +3806,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int32 {
+        #[serde(rename = "data")]
         pub r#data: i32,
     }
     impl ::roslibrust_codegen::RosMessageType for Int32 {
@@ -3433,7 +3824,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int32MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<i32>,
     }
     impl ::roslibrust_codegen::RosMessageType for Int32MultiArray {
@@ -3451,6 +3844,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int64 {
+        #[serde(rename = "data")]
         pub r#data: i64,
     }
     impl ::roslibrust_codegen::RosMessageType for Int64 {
@@ -3468,7 +3862,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int64MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<i64>,
     }
     impl ::roslibrust_codegen::RosMessageType for Int64MultiArray {
@@ -3486,6 +3882,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int8 {
+        #[serde(rename = "data")]
         pub r#data: i8,
     }
     impl ::roslibrust_codegen::RosMessageType for Int8 {
@@ -3503,7 +3900,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int8MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<i8>,
     }
     impl ::roslibrust_codegen::RosMessageType for Int8MultiArray {
@@ -3521,8 +3920,11 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct MultiArrayDimension {
+        #[serde(rename = "label")]
         pub r#label: ::std::string::String,
+        #[serde(rename = "size")]
         pub r#size: u32,
+        #[serde(rename = "stride")]
         pub r#stride: u32,
     }
     impl ::roslibrust_codegen::RosMessageType for MultiArrayDimension {
@@ -3540,7 +3942,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct MultiArrayLayout {
+        #[serde(rename = "dim")]
         pub r#dim: ::std::vec::Vec<self::MultiArrayDimension>,
+        #[serde(rename = "data_offset")]
         pub r#data_offset: u32,
     }
     impl ::roslibrust_codegen::RosMessageType for MultiArrayLayout {
@@ -3558,6 +3962,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct String {
+        #[serde(rename = "data")]
         pub r#data: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for String {
@@ -3575,6 +3980,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Time {
+        #[serde(rename = "data")]
         pub r#data: ::roslibrust_codegen::integral_types::Time,
     }
     impl ::roslibrust_codegen::RosMessageType for Time {
@@ -3592,6 +3998,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt16 {
+        #[serde(rename = "data")]
         pub r#data: u16,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt16 {
@@ -3609,7 +4016,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt16MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u16>,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt16MultiArray {
@@ -3627,6 +4036,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt32 {
+        #[serde(rename = "data")]
         pub r#data: u32,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt32 {
@@ -3644,7 +4054,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt32MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u32>,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt32MultiArray {
@@ -3662,6 +4074,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt64 {
+        #[serde(rename = "data")]
         pub r#data: u64,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt64 {
@@ -3679,7 +4092,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt64MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u64>,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt64MultiArray {
@@ -3697,6 +4112,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt8 {
+        #[serde(rename = "data")]
         pub r#data: u8,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt8 {
@@ -3714,7 +4130,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt8MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u8>,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt8MultiArray {
@@ -3785,6 +4203,7 @@ pub mod std_srvs {
         PartialEq,
     )]
     pub struct SetBoolRequest {
+        #[serde(rename = "data")]
         pub r#data: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for SetBoolRequest {
@@ -3802,7 +4221,9 @@ pub mod std_srvs {
         PartialEq,
     )]
     pub struct SetBoolResponse {
+        #[serde(rename = "success")]
         pub r#success: bool,
+        #[serde(rename = "message")]
         pub r#message: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for SetBoolResponse {
@@ -3842,7 +4263,9 @@ pub mod std_srvs {
         PartialEq,
     )]
     pub struct TriggerResponse {
+        #[serde(rename = "success")]
         pub r#success: bool,
+        #[serde(rename = "message")]
         pub r#message: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for TriggerResponse {
@@ -3883,13 +4306,21 @@ pub mod stereo_msgs {
         PartialEq,
     )]
     pub struct DisparityImage {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "image")]
         pub r#image: sensor_msgs::Image,
+        #[serde(rename = "f")]
         pub r#f: f32,
+        #[serde(rename = "T")]
         pub r#T: f32,
+        #[serde(rename = "valid_window")]
         pub r#valid_window: sensor_msgs::RegionOfInterest,
+        #[serde(rename = "min_disparity")]
         pub r#min_disparity: f32,
+        #[serde(rename = "max_disparity")]
         pub r#max_disparity: f32,
+        #[serde(rename = "delta_d")]
         pub r#delta_d: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for DisparityImage {
@@ -3945,7 +4376,9 @@ pub mod test_msgs {
         PartialEq,
     )]
     pub struct Float64Stamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "value")]
         pub r#value: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Float64Stamped {
@@ -3963,6 +4396,7 @@ pub mod test_msgs {
         PartialEq,
     )]
     pub struct LoggerLevel {
+        #[serde(rename = "level")]
         pub r#level: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for LoggerLevel {
@@ -3980,8 +4414,11 @@ pub mod test_msgs {
         PartialEq,
     )]
     pub struct Metric {
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "time")]
         pub r#time: f64,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<self::MetricPair>,
     }
     impl ::roslibrust_codegen::RosMessageType for Metric {
@@ -3999,7 +4436,9 @@ pub mod test_msgs {
         PartialEq,
     )]
     pub struct MetricPair {
+        #[serde(rename = "key")]
         pub r#key: ::std::string::String,
+        #[serde(rename = "value")]
         pub r#value: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for MetricPair {
@@ -4017,8 +4456,11 @@ pub mod test_msgs {
         PartialEq,
     )]
     pub struct NodeInfo {
+        #[serde(rename = "node_name")]
         pub r#node_name: ::std::string::String,
+        #[serde(rename = "pid")]
         pub r#pid: i64,
+        #[serde(rename = "status")]
         pub r#status: u8,
     }
     impl ::roslibrust_codegen::RosMessageType for NodeInfo {
@@ -4044,7 +4486,9 @@ pub mod test_msgs {
         PartialEq,
     )]
     pub struct AddTwoIntsRequest {
+        #[serde(rename = "a")]
         pub r#a: i64,
+        #[serde(rename = "b")]
         pub r#b: i64,
     }
     impl ::roslibrust_codegen::RosMessageType for AddTwoIntsRequest {
@@ -4062,6 +4506,7 @@ pub mod test_msgs {
         PartialEq,
     )]
     pub struct AddTwoIntsResponse {
+        #[serde(rename = "sum")]
         pub r#sum: i64,
     }
     impl ::roslibrust_codegen::RosMessageType for AddTwoIntsResponse {
@@ -4102,8 +4547,11 @@ pub mod trajectory_msgs {
         PartialEq,
     )]
     pub struct JointTrajectory {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "joint_names")]
         pub r#joint_names: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "points")]
         pub r#points: ::std::vec::Vec<self::JointTrajectoryPoint>,
     }
     impl ::roslibrust_codegen::RosMessageType for JointTrajectory {
@@ -4122,10 +4570,15 @@ pub mod trajectory_msgs {
         PartialEq,
     )]
     pub struct JointTrajectoryPoint {
+        #[serde(rename = "positions")]
         pub r#positions: ::std::vec::Vec<f64>,
+        #[serde(rename = "velocities")]
         pub r#velocities: ::std::vec::Vec<f64>,
+        #[serde(rename = "accelerations")]
         pub r#accelerations: ::std::vec::Vec<f64>,
+        #[serde(rename = "effort")]
         pub r#effort: ::std::vec::Vec<f64>,
+        #[serde(rename = "time_from_start")]
         pub r#time_from_start: ::roslibrust_codegen::integral_types::Duration,
     }
     impl ::roslibrust_codegen::RosMessageType for JointTrajectoryPoint {
@@ -4143,8 +4596,11 @@ pub mod trajectory_msgs {
         PartialEq,
     )]
     pub struct MultiDOFJointTrajectory {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "joint_names")]
         pub r#joint_names: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "points")]
         pub r#points: ::std::vec::Vec<self::MultiDOFJointTrajectoryPoint>,
     }
     impl ::roslibrust_codegen::RosMessageType for MultiDOFJointTrajectory {
@@ -4162,9 +4618,13 @@ pub mod trajectory_msgs {
         PartialEq,
     )]
     pub struct MultiDOFJointTrajectoryPoint {
+        #[serde(rename = "transforms")]
         pub r#transforms: ::std::vec::Vec<geometry_msgs::Transform>,
+        #[serde(rename = "velocities")]
         pub r#velocities: ::std::vec::Vec<geometry_msgs::Twist>,
+        #[serde(rename = "accelerations")]
         pub r#accelerations: ::std::vec::Vec<geometry_msgs::Twist>,
+        #[serde(rename = "time_from_start")]
         pub r#time_from_start: ::roslibrust_codegen::integral_types::Duration,
     }
     impl ::roslibrust_codegen::RosMessageType for MultiDOFJointTrajectoryPoint {
@@ -4198,18 +4658,31 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct ImageMarker {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "ns")]
         pub r#ns: ::std::string::String,
+        #[serde(rename = "id")]
         pub r#id: i32,
+        #[serde(rename = "type")]
         pub r#type: i32,
+        #[serde(rename = "action")]
         pub r#action: i32,
+        #[serde(rename = "position")]
         pub r#position: geometry_msgs::Point,
+        #[serde(rename = "scale")]
         pub r#scale: f32,
+        #[serde(rename = "outline_color")]
         pub r#outline_color: std_msgs::ColorRGBA,
+        #[serde(rename = "filled")]
         pub r#filled: u8,
+        #[serde(rename = "fill_color")]
         pub r#fill_color: std_msgs::ColorRGBA,
+        #[serde(rename = "lifetime")]
         pub r#lifetime: ::roslibrust_codegen::integral_types::Duration,
+        #[serde(rename = "points")]
         pub r#points: ::std::vec::Vec<geometry_msgs::Point>,
+        #[serde(rename = "outline_colors")]
         pub r#outline_colors: ::std::vec::Vec<std_msgs::ColorRGBA>,
     }
     impl ::roslibrust_codegen::RosMessageType for ImageMarker {
@@ -4236,12 +4709,19 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct InteractiveMarker {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "pose")]
         pub r#pose: geometry_msgs::Pose,
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "description")]
         pub r#description: ::std::string::String,
+        #[serde(rename = "scale")]
         pub r#scale: f32,
+        #[serde(rename = "menu_entries")]
         pub r#menu_entries: ::std::vec::Vec<self::MenuEntry>,
+        #[serde(rename = "controls")]
         pub r#controls: ::std::vec::Vec<self::InteractiveMarkerControl>,
     }
     impl ::roslibrust_codegen::RosMessageType for InteractiveMarker {
@@ -4259,13 +4739,21 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct InteractiveMarkerControl {
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "orientation")]
         pub r#orientation: geometry_msgs::Quaternion,
+        #[serde(rename = "orientation_mode")]
         pub r#orientation_mode: u8,
+        #[serde(rename = "interaction_mode")]
         pub r#interaction_mode: u8,
+        #[serde(rename = "always_visible")]
         pub r#always_visible: bool,
+        #[serde(rename = "markers")]
         pub r#markers: ::std::vec::Vec<self::Marker>,
+        #[serde(rename = "independent_marker_orientation")]
         pub r#independent_marker_orientation: bool,
+        #[serde(rename = "description")]
         pub r#description: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for InteractiveMarkerControl {
@@ -4298,14 +4786,23 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct InteractiveMarkerFeedback {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "client_id")]
         pub r#client_id: ::std::string::String,
+        #[serde(rename = "marker_name")]
         pub r#marker_name: ::std::string::String,
+        #[serde(rename = "control_name")]
         pub r#control_name: ::std::string::String,
+        #[serde(rename = "event_type")]
         pub r#event_type: u8,
+        #[serde(rename = "pose")]
         pub r#pose: geometry_msgs::Pose,
+        #[serde(rename = "menu_entry_id")]
         pub r#menu_entry_id: u32,
+        #[serde(rename = "mouse_point")]
         pub r#mouse_point: geometry_msgs::Point,
+        #[serde(rename = "mouse_point_valid")]
         pub r#mouse_point_valid: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for InteractiveMarkerFeedback {
@@ -4331,8 +4828,11 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct InteractiveMarkerInit {
+        #[serde(rename = "server_id")]
         pub r#server_id: ::std::string::String,
+        #[serde(rename = "seq_num")]
         pub r#seq_num: u64,
+        #[serde(rename = "markers")]
         pub r#markers: ::std::vec::Vec<self::InteractiveMarker>,
     }
     impl ::roslibrust_codegen::RosMessageType for InteractiveMarkerInit {
@@ -4350,8 +4850,11 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct InteractiveMarkerPose {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "pose")]
         pub r#pose: geometry_msgs::Pose,
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for InteractiveMarkerPose {
@@ -4369,11 +4872,17 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct InteractiveMarkerUpdate {
+        #[serde(rename = "server_id")]
         pub r#server_id: ::std::string::String,
+        #[serde(rename = "seq_num")]
         pub r#seq_num: u64,
+        #[serde(rename = "type")]
         pub r#type: u8,
+        #[serde(rename = "markers")]
         pub r#markers: ::std::vec::Vec<self::InteractiveMarker>,
+        #[serde(rename = "poses")]
         pub r#poses: ::std::vec::Vec<self::InteractiveMarkerPose>,
+        #[serde(rename = "erases")]
         pub r#erases: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for InteractiveMarkerUpdate {
@@ -4395,20 +4904,35 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct Marker {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "ns")]
         pub r#ns: ::std::string::String,
+        #[serde(rename = "id")]
         pub r#id: i32,
+        #[serde(rename = "type")]
         pub r#type: i32,
+        #[serde(rename = "action")]
         pub r#action: i32,
+        #[serde(rename = "pose")]
         pub r#pose: geometry_msgs::Pose,
+        #[serde(rename = "scale")]
         pub r#scale: geometry_msgs::Vector3,
+        #[serde(rename = "color")]
         pub r#color: std_msgs::ColorRGBA,
+        #[serde(rename = "lifetime")]
         pub r#lifetime: ::roslibrust_codegen::integral_types::Duration,
+        #[serde(rename = "frame_locked")]
         pub r#frame_locked: bool,
+        #[serde(rename = "points")]
         pub r#points: ::std::vec::Vec<geometry_msgs::Point>,
+        #[serde(rename = "colors")]
         pub r#colors: ::std::vec::Vec<std_msgs::ColorRGBA>,
+        #[serde(rename = "text")]
         pub r#text: ::std::string::String,
+        #[serde(rename = "mesh_resource")]
         pub r#mesh_resource: ::std::string::String,
+        #[serde(rename = "mesh_use_embedded_materials")]
         pub r#mesh_use_embedded_materials: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for Marker {
@@ -4444,6 +4968,7 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct MarkerArray {
+        #[serde(rename = "markers")]
         pub r#markers: ::std::vec::Vec<self::Marker>,
     }
     impl ::roslibrust_codegen::RosMessageType for MarkerArray {
@@ -4461,10 +4986,15 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct MenuEntry {
+        #[serde(rename = "id")]
         pub r#id: u32,
+        #[serde(rename = "parent_id")]
         pub r#parent_id: u32,
+        #[serde(rename = "title")]
         pub r#title: ::std::string::String,
+        #[serde(rename = "command")]
         pub r#command: ::std::string::String,
+        #[serde(rename = "command_type")]
         pub r#command_type: u8,
     }
     impl ::roslibrust_codegen::RosMessageType for MenuEntry {
@@ -21,7 +21,9 @@ pub mod actionlib_msgs {
         PartialEq,
     )]
     pub struct GoalID {
+        #[serde(rename = "stamp")]
         pub r#stamp: ::roslibrust_codegen::integral_types::Time,
+        #[serde(rename = "id")]
         pub r#id: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for GoalID {
@@ -39,8 +41,11 @@ pub mod actionlib_msgs {
         PartialEq,
     )]
     pub struct GoalStatus {
+        #[serde(rename = "goal_id")]
         pub r#goal_id: self::GoalID,
+        #[serde(rename = "status")]
         pub r#status: u8,
+        #[serde(rename = "text")]
         pub r#text: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for GoalStatus {
@@ -70,7 +75,9 @@ pub mod actionlib_msgs {
         PartialEq,
     )]
     pub struct GoalStatusArray {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "status_list")]
         pub r#status_list: ::std::vec::Vec<self::GoalStatus>,
     }
     impl ::roslibrust_codegen::RosMessageType for GoalStatusArray {
@@ -102,7 +109,9 @@ pub mod diagnostic_msgs {
         PartialEq,
     )]
     pub struct DiagnosticArray {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "status")]
         pub r#status: ::std::vec::Vec<self::DiagnosticStatus>,
     }
     impl ::roslibrust_codegen::RosMessageType for DiagnosticArray {
@@ -120,10 +129,15 @@ pub mod diagnostic_msgs {
         PartialEq,
     )]
     pub struct DiagnosticStatus {
+        #[serde(rename = "level")]
         pub r#level: u8,
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "message")]
         pub r#message: ::std::string::String,
+        #[serde(rename = "hardware_id")]
         pub r#hardware_id: ::std::string::String,
+        #[serde(rename = "values")]
         pub r#values: ::std::vec::Vec<self::KeyValue>,
     }
     impl ::roslibrust_codegen::RosMessageType for DiagnosticStatus {
@@ -147,7 +161,9 @@ pub mod diagnostic_msgs {
         PartialEq,
     )]
     pub struct KeyValue {
+        #[serde(rename = "key")]
         pub r#key: ::std::string::String,
+        #[serde(rename = "value")]
         pub r#value: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for KeyValue {
@@ -165,6 +181,7 @@ pub mod diagnostic_msgs {
         PartialEq,
     )]
     pub struct AddDiagnosticsRequest {
+        #[serde(rename = "load_namespace")]
         pub r#load_namespace: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for AddDiagnosticsRequest {
@@ -182,7 +199,9 @@ pub mod diagnostic_msgs {
         PartialEq,
     )]
     pub struct AddDiagnosticsResponse {
+        #[serde(rename = "success")]
         pub r#success: bool,
+        #[serde(rename = "message")]
         pub r#message: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for AddDiagnosticsResponse {
@@ -222,8 +241,11 @@ pub mod diagnostic_msgs {
         PartialEq,
     )]
     pub struct SelfTestResponse {
+        #[serde(rename = "id")]
         pub r#id: ::std::string::String,
+        #[serde(rename = "passed")]
         pub r#passed: u8,
+        #[serde(rename = "status")]
         pub r#status: ::std::vec::Vec<self::DiagnosticStatus>,
     }
     impl ::roslibrust_codegen::RosMessageType for SelfTestResponse {
@@ -262,7 +284,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Accel {
+        #[serde(rename = "linear")]
         pub r#linear: self::Vector3,
+        #[serde(rename = "angular")]
         pub r#angular: self::Vector3,
     }
     impl ::roslibrust_codegen::RosMessageType for Accel {
@@ -280,7 +304,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct AccelStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "accel")]
         pub r#accel: self::Accel,
     }
     impl ::roslibrust_codegen::RosMessageType for AccelStamped {
@@ -298,7 +324,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct AccelWithCovariance {
+        #[serde(rename = "accel")]
         pub r#accel: self::Accel,
+        #[serde(rename = "covariance")]
         pub r#covariance: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for AccelWithCovariance {
@@ -316,7 +344,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct AccelWithCovarianceStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "accel")]
         pub r#accel: self::AccelWithCovariance,
     }
     impl ::roslibrust_codegen::RosMessageType for AccelWithCovarianceStamped {
@@ -334,13 +364,21 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Inertia {
+        #[serde(rename = "m")]
         pub r#m: f64,
+        #[serde(rename = "com")]
         pub r#com: self::Vector3,
+        #[serde(rename = "ixx")]
         pub r#ixx: f64,
+        #[serde(rename = "ixy")]
         pub r#ixy: f64,
+        #[serde(rename = "ixz")]
         pub r#ixz: f64,
+        #[serde(rename = "iyy")]
         pub r#iyy: f64,
+        #[serde(rename = "iyz")]
         pub r#iyz: f64,
+        #[serde(rename = "izz")]
         pub r#izz: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Inertia {
@@ -358,7 +396,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct InertiaStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "inertia")]
         pub r#inertia: self::Inertia,
     }
     impl ::roslibrust_codegen::RosMessageType for InertiaStamped {
@@ -376,8 +416,11 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Point {
+        #[serde(rename = "x")]
         pub r#x: f64,
+        #[serde(rename = "y")]
         pub r#y: f64,
+        #[serde(rename = "z")]
         pub r#z: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Point {
@@ -395,8 +438,11 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Point32 {
+        #[serde(rename = "x")]
         pub r#x: f32,
+        #[serde(rename = "y")]
         pub r#y: f32,
+        #[serde(rename = "z")]
         pub r#z: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for Point32 {
@@ -414,7 +460,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct PointStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "point")]
         pub r#point: self::Point,
     }
     impl ::roslibrust_codegen::RosMessageType for PointStamped {
@@ -432,6 +480,7 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Polygon {
+        #[serde(rename = "points")]
         pub r#points: ::std::vec::Vec<self::Point32>,
     }
     impl ::roslibrust_codegen::RosMessageType for Polygon {
@@ -449,7 +498,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct PolygonStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "polygon")]
         pub r#polygon: self::Polygon,
     }
     impl ::roslibrust_codegen::RosMessageType for PolygonStamped {
@@ -467,7 +518,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Pose {
+        #[serde(rename = "position")]
         pub r#position: self::Point,
+        #[serde(rename = "orientation")]
         pub r#orientation: self::Quaternion,
     }
     impl ::roslibrust_codegen::RosMessageType for Pose {
@@ -485,8 +538,11 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Pose2D {
+        #[serde(rename = "x")]
         pub r#x: f64,
+        #[serde(rename = "y")]
         pub r#y: f64,
+        #[serde(rename = "theta")]
         pub r#theta: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Pose2D {
@@ -504,7 +560,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct PoseArray {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "poses")]
         pub r#poses: ::std::vec::Vec<self::Pose>,
     }
     impl ::roslibrust_codegen::RosMessageType for PoseArray {
@@ -522,7 +580,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct PoseStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "pose")]
         pub r#pose: self::Pose,
     }
     impl ::roslibrust_codegen::RosMessageType for PoseStamped {
@@ -540,7 +600,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct PoseWithCovariance {
+        #[serde(rename = "pose")]
         pub r#pose: self::Pose,
+        #[serde(rename = "covariance")]
         pub r#covariance: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for PoseWithCovariance {
@@ -558,7 +620,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct PoseWithCovarianceStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "pose")]
         pub r#pose: self::PoseWithCovariance,
     }
     impl ::roslibrust_codegen::RosMessageType for PoseWithCovarianceStamped {
@@ -576,12 +640,16 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Quaternion {
+        #[serde(rename = "x")]
         #[default(0f64)]
         pub r#x: f64,
+        #[serde(rename = "y")]
         #[default(0f64)]
         pub r#y: f64,
+        #[serde(rename = "z")]
         #[default(0f64)]
         pub r#z: f64,
+        #[serde(rename = "w")]
         #[default(1f64)]
         pub r#w: f64,
     }
@@ -600,7 +668,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct QuaternionStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "quaternion")]
         pub r#quaternion: self::Quaternion,
     }
     impl ::roslibrust_codegen::RosMessageType for QuaternionStamped {
@@ -618,7 +688,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Transform {
+        #[serde(rename = "translation")]
         pub r#translation: self::Vector3,
+        #[serde(rename = "rotation")]
         pub r#rotation: self::Quaternion,
     }
     impl ::roslibrust_codegen::RosMessageType for Transform {
@@ -636,8 +708,11 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct TransformStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "child_frame_id")]
         pub r#child_frame_id: ::std::string::String,
+        #[serde(rename = "transform")]
         pub r#transform: self::Transform,
     }
     impl ::roslibrust_codegen::RosMessageType for TransformStamped {
@@ -655,7 +730,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Twist {
+        #[serde(rename = "linear")]
         pub r#linear: self::Vector3,
+        #[serde(rename = "angular")]
         pub r#angular: self::Vector3,
     }
     impl ::roslibrust_codegen::RosMessageType for Twist {
@@ -673,7 +750,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct TwistStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "twist")]
         pub r#twist: self::Twist,
     }
     impl ::roslibrust_codegen::RosMessageType for TwistStamped {
@@ -691,7 +770,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct TwistWithCovariance {
+        #[serde(rename = "twist")]
         pub r#twist: self::Twist,
+        #[serde(rename = "covariance")]
         pub r#covariance: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for TwistWithCovariance {
@@ -709,7 +790,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct TwistWithCovarianceStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "twist")]
         pub r#twist: self::TwistWithCovariance,
     }
     impl ::roslibrust_codegen::RosMessageType for TwistWithCovarianceStamped {
@@ -727,8 +810,11 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Vector3 {
+        #[serde(rename = "x")]
         pub r#x: f64,
+        #[serde(rename = "y")]
         pub r#y: f64,
+        #[serde(rename = "z")]
         pub r#z: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Vector3 {
@@ -746,7 +832,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Vector3Stamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "vector")]
         pub r#vector: self::Vector3,
     }
     impl ::roslibrust_codegen::RosMessageType for Vector3Stamped {
@@ -764,7 +852,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct Wrench {
+        #[serde(rename = "force")]
         pub r#force: self::Vector3,
+        #[serde(rename = "torque")]
         pub r#torque: self::Vector3,
     }
     impl ::roslibrust_codegen::RosMessageType for Wrench {
@@ -782,7 +872,9 @@ pub mod geometry_msgs {
         PartialEq,
     )]
     pub struct WrenchStamped {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "wrench")]
         pub r#wrench: self::Wrench,
     }
     impl ::roslibrust_codegen::RosMessageType for WrenchStamped {
@@ -814,9 +906,13 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct GridCells {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "cell_width")]
         pub r#cell_width: f32,
+        #[serde(rename = "cell_height")]
         pub r#cell_height: f32,
+        #[serde(rename = "cells")]
         pub r#cells: ::std::vec::Vec<geometry_msgs::Point>,
     }
     impl ::roslibrust_codegen::RosMessageType for GridCells {
@@ -834,10 +930,15 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct MapMetaData {
+        #[serde(rename = "map_load_time")]
         pub r#map_load_time: ::roslibrust_codegen::integral_types::Time,
+        #[serde(rename = "resolution")]
         pub r#resolution: f32,
+        #[serde(rename = "width")]
         pub r#width: u32,
+        #[serde(rename = "height")]
         pub r#height: u32,
+        #[serde(rename = "origin")]
         pub r#origin: geometry_msgs::Pose,
     }
     impl ::roslibrust_codegen::RosMessageType for MapMetaData {
@@ -855,8 +956,11 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct OccupancyGrid {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "info")]
         pub r#info: self::MapMetaData,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<i8>,
     }
     impl ::roslibrust_codegen::RosMessageType for OccupancyGrid {
@@ -874,9 +978,13 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct Odometry {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "child_frame_id")]
         pub r#child_frame_id: ::std::string::String,
+        #[serde(rename = "pose")]
         pub r#pose: geometry_msgs::PoseWithCovariance,
+        #[serde(rename = "twist")]
         pub r#twist: geometry_msgs::TwistWithCovariance,
     }
     impl ::roslibrust_codegen::RosMessageType for Odometry {
@@ -894,7 +1002,9 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct Path {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "poses")]
         pub r#poses: ::std::vec::Vec<geometry_msgs::PoseStamped>,
     }
     impl ::roslibrust_codegen::RosMessageType for Path {
@@ -927,6 +1037,7 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct GetMapResponse {
+        #[serde(rename = "map")]
         pub r#map: self::OccupancyGrid,
     }
     impl ::roslibrust_codegen::RosMessageType for GetMapResponse {
@@ -952,8 +1063,11 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct GetPlanRequest {
+        #[serde(rename = "start")]
         pub r#start: geometry_msgs::PoseStamped,
+        #[serde(rename = "goal")]
         pub r#goal: geometry_msgs::PoseStamped,
+        #[serde(rename = "tolerance")]
         pub r#tolerance: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for GetPlanRequest {
@@ -971,6 +1085,7 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct GetPlanResponse {
+        #[serde(rename = "plan")]
         pub r#plan: self::Path,
     }
     impl ::roslibrust_codegen::RosMessageType for GetPlanResponse {
@@ -996,6 +1111,7 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct LoadMapRequest {
+        #[serde(rename = "map_url")]
         pub r#map_url: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for LoadMapRequest {
@@ -1013,7 +1129,9 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct LoadMapResponse {
+        #[serde(rename = "map")]
         pub r#map: self::OccupancyGrid,
+        #[serde(rename = "result")]
         pub r#result: u8,
     }
     impl ::roslibrust_codegen::RosMessageType for LoadMapResponse {
@@ -1045,7 +1163,9 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct SetMapRequest {
+        #[serde(rename = "map")]
         pub r#map: self::OccupancyGrid,
+        #[serde(rename = "initial_pose")]
         pub r#initial_pose: geometry_msgs::PoseWithCovarianceStamped,
     }
     impl ::roslibrust_codegen::RosMessageType for SetMapRequest {
@@ -1063,6 +1183,7 @@ pub mod nav_msgs {
         PartialEq,
     )]
     pub struct SetMapResponse {
+        #[serde(rename = "success")]
         pub r#success: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for SetMapResponse {
@@ -1102,21 +1223,37 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct BatteryState {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "voltage")]
         pub r#voltage: f32,
+        #[serde(rename = "temperature")]
         pub r#temperature: f32,
+        #[serde(rename = "current")]
         pub r#current: f32,
+        #[serde(rename = "charge")]
         pub r#charge: f32,
+        #[serde(rename = "capacity")]
         pub r#capacity: f32,
+        #[serde(rename = "design_capacity")]
         pub r#design_capacity: f32,
+        #[serde(rename = "percentage")]
         pub r#percentage: f32,
+        #[serde(rename = "power_supply_status")]
         pub r#power_supply_status: u8,
+        #[serde(rename = "power_supply_health")]
         pub r#power_supply_health: u8,
+        #[serde(rename = "power_supply_technology")]
         pub r#power_supply_technology: u8,
+        #[serde(rename = "present")]
         pub r#present: bool,
+        #[serde(rename = "cell_voltage")]
         pub r#cell_voltage: ::std::vec::Vec<f32>,
+        #[serde(rename = "cell_temperature")]
         pub r#cell_temperature: ::std::vec::Vec<f32>,
+        #[serde(rename = "location")]
         pub r#location: ::std::string::String,
+        #[serde(rename = "serial_number")]
         pub r#serial_number: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for BatteryState {
@@ -1157,16 +1294,27 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct CameraInfo {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "height")]
         pub r#height: u32,
+        #[serde(rename = "width")]
         pub r#width: u32,
+        #[serde(rename = "distortion_model")]
         pub r#distortion_model: ::std::string::String,
+        #[serde(rename = "d")]
         pub r#d: ::std::vec::Vec<f64>,
+        #[serde(rename = "k")]
         pub r#k: ::std::vec::Vec<f64>,
+        #[serde(rename = "r")]
         pub r#r: ::std::vec::Vec<f64>,
+        #[serde(rename = "p")]
         pub r#p: ::std::vec::Vec<f64>,
+        #[serde(rename = "binning_x")]
         pub r#binning_x: u32,
+        #[serde(rename = "binning_y")]
         pub r#binning_y: u32,
+        #[serde(rename = "roi")]
         pub r#roi: self::RegionOfInterest,
     }
     impl ::roslibrust_codegen::RosMessageType for CameraInfo {
@@ -1184,7 +1332,9 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct ChannelFloat32 {
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "values")]
         pub r#values: ::std::vec::Vec<f32>,
     }
     impl ::roslibrust_codegen::RosMessageType for ChannelFloat32 {
@@ -1202,8 +1352,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct CompressedImage {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "format")]
         pub r#format: ::std::string::String,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u8>,
     }
     impl ::roslibrust_codegen::RosMessageType for CompressedImage {
@@ -1221,8 +1374,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct FluidPressure {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "fluid_pressure")]
         pub r#fluid_pressure: f64,
+        #[serde(rename = "variance")]
         pub r#variance: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for FluidPressure {
@@ -1240,8 +1396,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct Illuminance {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "illuminance")]
         pub r#illuminance: f64,
+        #[serde(rename = "variance")]
         pub r#variance: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Illuminance {
@@ -1259,12 +1418,19 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct Image {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "height")]
         pub r#height: u32,
+        #[serde(rename = "width")]
         pub r#width: u32,
+        #[serde(rename = "encoding")]
         pub r#encoding: ::std::string::String,
+        #[serde(rename = "is_bigendian")]
         pub r#is_bigendian: u8,
+        #[serde(rename = "step")]
         pub r#step: u32,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u8>,
     }
     impl ::roslibrust_codegen::RosMessageType for Image {
@@ -1282,12 +1448,19 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct Imu {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "orientation")]
         pub r#orientation: geometry_msgs::Quaternion,
+        #[serde(rename = "orientation_covariance")]
         pub r#orientation_covariance: ::std::vec::Vec<f64>,
+        #[serde(rename = "angular_velocity")]
         pub r#angular_velocity: geometry_msgs::Vector3,
+        #[serde(rename = "angular_velocity_covariance")]
         pub r#angular_velocity_covariance: ::std::vec::Vec<f64>,
+        #[serde(rename = "linear_acceleration")]
         pub r#linear_acceleration: geometry_msgs::Vector3,
+        #[serde(rename = "linear_acceleration_covariance")]
         pub r#linear_acceleration_covariance: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for Imu {
@@ -1305,10 +1478,15 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct JointState {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "name")]
         pub r#name: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "position")]
         pub r#position: ::std::vec::Vec<f64>,
+        #[serde(rename = "velocity")]
         pub r#velocity: ::std::vec::Vec<f64>,
+        #[serde(rename = "effort")]
         pub r#effort: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for JointState {
@@ -1326,8 +1504,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct Joy {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "axes")]
         pub r#axes: ::std::vec::Vec<f32>,
+        #[serde(rename = "buttons")]
         pub r#buttons: ::std::vec::Vec<i32>,
     }
     impl ::roslibrust_codegen::RosMessageType for Joy {
@@ -1345,8 +1526,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct JoyFeedback {
+        #[serde(rename = "type")]
         pub r#type: u8,
+        #[serde(rename = "id")]
         pub r#id: u8,
+        #[serde(rename = "intensity")]
         pub r#intensity: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for JoyFeedback {
@@ -1369,6 +1553,7 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct JoyFeedbackArray {
+        #[serde(rename = "array")]
         pub r#array: ::std::vec::Vec<self::JoyFeedback>,
     }
     impl ::roslibrust_codegen::RosMessageType for JoyFeedbackArray {
@@ -1387,6 +1572,7 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct LaserEcho {
+        #[serde(rename = "echoes")]
         pub r#echoes: ::std::vec::Vec<f32>,
     }
     impl ::roslibrust_codegen::RosMessageType for LaserEcho {
@@ -1404,15 +1590,25 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct LaserScan {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "angle_min")]
         pub r#angle_min: f32,
+        #[serde(rename = "angle_max")]
         pub r#angle_max: f32,
+        #[serde(rename = "angle_increment")]
         pub r#angle_increment: f32,
+        #[serde(rename = "time_increment")]
         pub r#time_increment: f32,
+        #[serde(rename = "scan_time")]
         pub r#scan_time: f32,
+        #[serde(rename = "range_min")]
         pub r#range_min: f32,
+        #[serde(rename = "range_max")]
         pub r#range_max: f32,
+        #[serde(rename = "ranges")]
         pub r#ranges: ::std::vec::Vec<f32>,
+        #[serde(rename = "intensities")]
         pub r#intensities: ::std::vec::Vec<f32>,
     }
     impl ::roslibrust_codegen::RosMessageType for LaserScan {
@@ -1430,8 +1626,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct MagneticField {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "magnetic_field")]
         pub r#magnetic_field: geometry_msgs::Vector3,
+        #[serde(rename = "magnetic_field_covariance")]
         pub r#magnetic_field_covariance: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for MagneticField {
@@ -1449,10 +1648,15 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct MultiDOFJointState {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "joint_names")]
         pub r#joint_names: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "transforms")]
         pub r#transforms: ::std::vec::Vec<geometry_msgs::Transform>,
+        #[serde(rename = "twist")]
         pub r#twist: ::std::vec::Vec<geometry_msgs::Twist>,
+        #[serde(rename = "wrench")]
         pub r#wrench: ::std::vec::Vec<geometry_msgs::Wrench>,
     }
     impl ::roslibrust_codegen::RosMessageType for MultiDOFJointState {
@@ -1470,15 +1674,25 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct MultiEchoLaserScan {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "angle_min")]
         pub r#angle_min: f32,
+        #[serde(rename = "angle_max")]
         pub r#angle_max: f32,
+        #[serde(rename = "angle_increment")]
         pub r#angle_increment: f32,
+        #[serde(rename = "time_increment")]
         pub r#time_increment: f32,
+        #[serde(rename = "scan_time")]
         pub r#scan_time: f32,
+        #[serde(rename = "range_min")]
         pub r#range_min: f32,
+        #[serde(rename = "range_max")]
         pub r#range_max: f32,
+        #[serde(rename = "ranges")]
         pub r#ranges: ::std::vec::Vec<self::LaserEcho>,
+        #[serde(rename = "intensities")]
         pub r#intensities: ::std::vec::Vec<self::LaserEcho>,
     }
     impl ::roslibrust_codegen::RosMessageType for MultiEchoLaserScan {
@@ -1496,12 +1710,19 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct NavSatFix {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "status")]
         pub r#status: self::NavSatStatus,
+        #[serde(rename = "latitude")]
         pub r#latitude: f64,
+        #[serde(rename = "longitude")]
         pub r#longitude: f64,
+        #[serde(rename = "altitude")]
         pub r#altitude: f64,
+        #[serde(rename = "position_covariance")]
         pub r#position_covariance: ::std::vec::Vec<f64>,
+        #[serde(rename = "position_covariance_type")]
         pub r#position_covariance_type: u8,
     }
     impl ::roslibrust_codegen::RosMessageType for NavSatFix {
@@ -1525,7 +1746,9 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct NavSatStatus {
+        #[serde(rename = "status")]
         pub r#status: i8,
+        #[serde(rename = "service")]
         pub r#service: u16,
     }
     impl ::roslibrust_codegen::RosMessageType for NavSatStatus {
@@ -1553,8 +1776,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct PointCloud {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "points")]
         pub r#points: ::std::vec::Vec<geometry_msgs::Point32>,
+        #[serde(rename = "channels")]
         pub r#channels: ::std::vec::Vec<self::ChannelFloat32>,
     }
     impl ::roslibrust_codegen::RosMessageType for PointCloud {
@@ -1572,14 +1798,23 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct PointCloud2 {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "height")]
         pub r#height: u32,
+        #[serde(rename = "width")]
         pub r#width: u32,
+        #[serde(rename = "fields")]
         pub r#fields: ::std::vec::Vec<self::PointField>,
+        #[serde(rename = "is_bigendian")]
         pub r#is_bigendian: bool,
+        #[serde(rename = "point_step")]
         pub r#point_step: u32,
+        #[serde(rename = "row_step")]
         pub r#row_step: u32,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u8>,
+        #[serde(rename = "is_dense")]
         pub r#is_dense: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for PointCloud2 {
@@ -1597,9 +1832,13 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct PointField {
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "offset")]
         pub r#offset: u32,
+        #[serde(rename = "datatype")]
         pub r#datatype: u8,
+        #[serde(rename = "count")]
         pub r#count: u32,
     }
     impl ::roslibrust_codegen::RosMessageType for PointField {
@@ -1627,11 +1866,17 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct Range {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "radiation_type")]
         pub r#radiation_type: u8,
+        #[serde(rename = "field_of_view")]
         pub r#field_of_view: f32,
+        #[serde(rename = "min_range")]
         pub r#min_range: f32,
+        #[serde(rename = "max_range")]
         pub r#max_range: f32,
+        #[serde(rename = "range")]
         pub r#range: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for Range {
@@ -1653,10 +1898,15 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct RegionOfInterest {
+        #[serde(rename = "x_offset")]
         pub r#x_offset: u32,
+        #[serde(rename = "y_offset")]
         pub r#y_offset: u32,
+        #[serde(rename = "height")]
         pub r#height: u32,
+        #[serde(rename = "width")]
         pub r#width: u32,
+        #[serde(rename = "do_rectify")]
         pub r#do_rectify: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for RegionOfInterest {
@@ -1674,8 +1924,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct RelativeHumidity {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "relative_humidity")]
         pub r#relative_humidity: f64,
+        #[serde(rename = "variance")]
         pub r#variance: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for RelativeHumidity {
@@ -1693,8 +1946,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct Temperature {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "temperature")]
         pub r#temperature: f64,
+        #[serde(rename = "variance")]
         pub r#variance: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Temperature {
@@ -1712,8 +1968,11 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct TimeReference {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "time_ref")]
         pub r#time_ref: ::roslibrust_codegen::integral_types::Time,
+        #[serde(rename = "source")]
         pub r#source: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for TimeReference {
@@ -1731,6 +1990,7 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct SetCameraInfoRequest {
+        #[serde(rename = "camera_info")]
         pub r#camera_info: self::CameraInfo,
     }
     impl ::roslibrust_codegen::RosMessageType for SetCameraInfoRequest {
@@ -1748,7 +2008,9 @@ pub mod sensor_msgs {
         PartialEq,
     )]
     pub struct SetCameraInfoResponse {
+        #[serde(rename = "success")]
         pub r#success: bool,
+        #[serde(rename = "status_message")]
         pub r#status_message: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for SetCameraInfoResponse {
@@ -1787,7 +2049,9 @@ pub mod shape_msgs {
         PartialEq,
     )]
     pub struct Mesh {
+        #[serde(rename = "triangles")]
         pub r#triangles: ::std::vec::Vec<self::MeshTriangle>,
+        #[serde(rename = "vertices")]
         pub r#vertices: ::std::vec::Vec<geometry_msgs::Point>,
     }
     impl ::roslibrust_codegen::RosMessageType for Mesh {
@@ -1805,6 +2069,7 @@ pub mod shape_msgs {
         PartialEq,
     )]
     pub struct MeshTriangle {
+        #[serde(rename = "vertex_indices")]
         pub r#vertex_indices: ::std::vec::Vec<u32>,
     }
     impl ::roslibrust_codegen::RosMessageType for MeshTriangle {
@@ -1823,6 +2088,7 @@ pub mod shape_msgs {
         PartialEq,
     )]
     pub struct Plane {
+        #[serde(rename = "coef")]
         pub r#coef: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for Plane {
@@ -1840,8 +2106,11 @@ pub mod shape_msgs {
         PartialEq,
     )]
     pub struct SolidPrimitive {
+        #[serde(rename = "type")]
         pub r#type: u8,
+        #[serde(rename = "dimensions")]
         pub r#dimensions: ::std::vec::Vec<f64>,
+        #[serde(rename = "polygon")]
         pub r#polygon: geometry_msgs::Polygon,
     }
     impl ::roslibrust_codegen::RosMessageType for SolidPrimitive {
@@ -1889,6 +2158,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Bool {
+        #[serde(rename = "data")]
         pub r#data: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for Bool {
@@ -1906,6 +2176,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Byte {
+        #[serde(rename = "data")]
         pub r#data: u8,
     }
     impl ::roslibrust_codegen::RosMessageType for Byte {
@@ -1923,7 +2194,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct ByteMultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u8>,
     }
     impl ::roslibrust_codegen::RosMessageType for ByteMultiArray {
@@ -1941,6 +2214,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Char {
+        #[serde(rename = "data")]
         pub r#data: u8,
     }
     impl ::roslibrust_codegen::RosMessageType for Char {
@@ -1958,9 +2232,13 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct ColorRGBA {
+        #[serde(rename = "r")]
         pub r#r: f32,
+        #[serde(rename = "g")]
         pub r#g: f32,
+        #[serde(rename = "b")]
         pub r#b: f32,
+        #[serde(rename = "a")]
         pub r#a: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for ColorRGBA {
@@ -1993,6 +2271,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Float32 {
+        #[serde(rename = "data")]
         pub r#data: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for Float32 {
@@ -2010,7 +2289,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Float32MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<f32>,
     }
     impl ::roslibrust_codegen::RosMessageType for Float32MultiArray {
@@ -2028,6 +2309,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Float64 {
+        #[serde(rename = "data")]
         pub r#data: f64,
     }
     impl ::roslibrust_codegen::RosMessageType for Float64 {
@@ -2045,7 +2327,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Float64MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<f64>,
     }
     impl ::roslibrust_codegen::RosMessageType for Float64MultiArray {
@@ -2063,7 +2347,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Header {
+        #[serde(rename = "stamp")]
         pub r#stamp: ::roslibrust_codegen::integral_types::Time,
+        #[serde(rename = "frame_id")]
         pub r#frame_id: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for Header {
@@ -2081,6 +2367,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int16 {
+        #[serde(rename = "data")]
         pub r#data: i16,
     }
     impl ::roslibrust_codegen::RosMessageType for Int16 {
@@ -2098,7 +2385,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int16MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<i16>,
     }
     impl ::roslibrust_codegen::RosMessageType for Int16MultiArray {
@@ -2116,6 +2405,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int32 {
+        #[serde(rename = "data")]
         pub r#data: i32,
     }
     impl ::roslibrust_codegen::RosMessageType for Int32 {
@@ -2133,7 +2423,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int32MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<i32>,
     }
     impl ::roslibrust_codegen::RosMessageType for Int32MultiArray {
@@ -2151,6 +2443,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int64 {
+        #[serde(rename = "data")]
         pub r#data: i64,
     }
     impl ::roslibrust_codegen::RosMessageType for Int64 {
@@ -2168,7 +2461,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int64MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<i64>,
     }
     impl ::roslibrust_codegen::RosMessageType for Int64MultiArray {
@@ -2186,6 +2481,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int8 {
+        #[serde(rename = "data")]
         pub r#data: i8,
     }
     impl ::roslibrust_codegen::RosMessageType for Int8 {
@@ -2203,7 +2499,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct Int8MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<i8>,
     }
     impl ::roslibrust_codegen::RosMessageType for Int8MultiArray {
@@ -2221,8 +2519,11 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct MultiArrayDimension {
+        #[serde(rename = "label")]
         pub r#label: ::std::string::String,
+        #[serde(rename = "size")]
         pub r#size: u32,
+        #[serde(rename = "stride")]
         pub r#stride: u32,
     }
     impl ::roslibrust_codegen::RosMessageType for MultiArrayDimension {
@@ -2240,7 +2541,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct MultiArrayLayout {
+        #[serde(rename = "dim")]
         pub r#dim: ::std::vec::Vec<self::MultiArrayDimension>,
+        #[serde(rename = "data_offset")]
         pub r#data_offset: u32,
     }
     impl ::roslibrust_codegen::RosMessageType for MultiArrayLayout {
@@ -2258,6 +2561,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct String {
+        #[serde(rename = "data")]
         pub r#data: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for String {
@@ -2275,6 +2579,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt16 {
+        #[serde(rename = "data")]
         pub r#data: u16,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt16 {
@@ -2292,7 +2597,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt16MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u16>,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt16MultiArray {
@@ -2310,6 +2617,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt32 {
+        #[serde(rename = "data")]
         pub r#data: u32,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt32 {
@@ -2327,7 +2635,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt32MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u32>,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt32MultiArray {
@@ -2345,6 +2655,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt64 {
+        #[serde(rename = "data")]
         pub r#data: u64,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt64 {
@@ -2362,7 +2673,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt64MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u64>,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt64MultiArray {
@@ -2380,6 +2693,7 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt8 {
+        #[serde(rename = "data")]
         pub r#data: u8,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt8 {
@@ -2397,7 +2711,9 @@ pub mod std_msgs {
         PartialEq,
     )]
     pub struct UInt8MultiArray {
+        #[serde(rename = "layout")]
         pub r#layout: self::MultiArrayLayout,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u8>,
     }
     impl ::roslibrust_codegen::RosMessageType for UInt8MultiArray {
@@ -2466,6 +2782,7 @@ pub mod std_srvs {
         PartialEq,
     )]
     pub struct SetBoolRequest {
+        #[serde(rename = "data")]
         pub r#data: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for SetBoolRequest {
@@ -2483,7 +2800,9 @@ pub mod std_srvs {
         PartialEq,
     )]
     pub struct SetBoolResponse {
+        #[serde(rename = "success")]
         pub r#success: bool,
+        #[serde(rename = "message")]
         pub r#message: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for SetBoolResponse {
@@ -2523,7 +2842,9 @@ pub mod std_srvs {
         PartialEq,
     )]
     pub struct TriggerResponse {
+        #[serde(rename = "success")]
         pub r#success: bool,
+        #[serde(rename = "message")]
         pub r#message: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for TriggerResponse {
@@ -2562,13 +2883,21 @@ pub mod stereo_msgs {
         PartialEq,
     )]
     pub struct DisparityImage {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "image")]
         pub r#image: sensor_msgs::Image,
+        #[serde(rename = "f")]
         pub r#f: f32,
+        #[serde(rename = "t")]
         pub r#t: f32,
+        #[serde(rename = "valid_window")]
         pub r#valid_window: sensor_msgs::RegionOfInterest,
+        #[serde(rename = "min_disparity")]
         pub r#min_disparity: f32,
+        #[serde(rename = "max_disparity")]
         pub r#max_disparity: f32,
+        #[serde(rename = "delta_d")]
         pub r#delta_d: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for DisparityImage {
@@ -2600,16 +2929,22 @@ pub mod test_msgs {
         PartialEq,
     )]
     pub struct Defaults {
+        #[serde(rename = "x")]
         #[default(42u8)]
         pub r#x: u8,
+        #[serde(rename = "y")]
         # [default (- 2000i16)]
         pub r#y: i16,
+        #[serde(rename = "full_name")]
         #[default("John Doe")]
         pub r#full_name: ::std::string::String,
+        #[serde(rename = "samples")]
         #[default(_code = "vec![-200, -100, 0, 100, 200]")]
         pub r#samples: ::std::vec::Vec<i32>,
+        #[serde(rename = "f_samples")]
         #[default(_code = "vec![-200.0, -1.0, 0.0]")]
         pub r#f_samples: ::std::vec::Vec<f32>,
+        #[serde(rename = "s_vec")]
         #[default(_code = "[\"hello\", \"world\"].iter().map(|x| x.to_string()).collect()")]
         pub r#s_vec: ::std::vec::Vec<::std::string::String>,
     }
@@ -2642,8 +2977,11 @@ pub mod trajectory_msgs {
         PartialEq,
     )]
     pub struct JointTrajectory {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "joint_names")]
         pub r#joint_names: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "points")]
         pub r#points: ::std::vec::Vec<self::JointTrajectoryPoint>,
     }
     impl ::roslibrust_codegen::RosMessageType for JointTrajectory {
@@ -2661,10 +2999,15 @@ pub mod trajectory_msgs {
         PartialEq,
     )]
     pub struct JointTrajectoryPoint {
+        #[serde(rename = "positions")]
         pub r#positions: ::std::vec::Vec<f64>,
+        #[serde(rename = "velocities")]
         pub r#velocities: ::std::vec::Vec<f64>,
+        #[serde(rename = "accelerations")]
         pub r#accelerations: ::std::vec::Vec<f64>,
+        #[serde(rename = "effort")]
         pub r#effort: ::std::vec::Vec<f64>,
+        #[serde(rename = "time_from_start")]
         pub r#time_from_start: ::roslibrust_codegen::integral_types::Duration,
     }
     impl ::roslibrust_codegen::RosMessageType for JointTrajectoryPoint {
@@ -2682,8 +3025,11 @@ pub mod trajectory_msgs {
         PartialEq,
     )]
     pub struct MultiDOFJointTrajectory {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "joint_names")]
         pub r#joint_names: ::std::vec::Vec<::std::string::String>,
+        #[serde(rename = "points")]
         pub r#points: ::std::vec::Vec<self::MultiDOFJointTrajectoryPoint>,
     }
     impl ::roslibrust_codegen::RosMessageType for MultiDOFJointTrajectory {
@@ -2701,9 +3047,13 @@ pub mod trajectory_msgs {
         PartialEq,
     )]
     pub struct MultiDOFJointTrajectoryPoint {
+        #[serde(rename = "transforms")]
         pub r#transforms: ::std::vec::Vec<geometry_msgs::Transform>,
+        #[serde(rename = "velocities")]
         pub r#velocities: ::std::vec::Vec<geometry_msgs::Twist>,
+        #[serde(rename = "accelerations")]
         pub r#accelerations: ::std::vec::Vec<geometry_msgs::Twist>,
+        #[serde(rename = "time_from_start")]
         pub r#time_from_start: ::roslibrust_codegen::integral_types::Duration,
     }
     impl ::roslibrust_codegen::RosMessageType for MultiDOFJointTrajectoryPoint {
@@ -2735,18 +3085,31 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct ImageMarker {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "ns")]
         pub r#ns: ::std::string::String,
+        #[serde(rename = "id")]
         pub r#id: i32,
+        #[serde(rename = "type")]
         pub r#type: i32,
+        #[serde(rename = "action")]
         pub r#action: i32,
+        #[serde(rename = "position")]
         pub r#position: geometry_msgs::Point,
+        #[serde(rename = "scale")]
         pub r#scale: f32,
+        #[serde(rename = "outline_color")]
         pub r#outline_color: std_msgs::ColorRGBA,
+        #[serde(rename = "filled")]
         pub r#filled: u8,
+        #[serde(rename = "fill_color")]
         pub r#fill_color: std_msgs::ColorRGBA,
+        #[serde(rename = "lifetime")]
         pub r#lifetime: ::roslibrust_codegen::integral_types::Duration,
+        #[serde(rename = "points")]
         pub r#points: ::std::vec::Vec<geometry_msgs::Point>,
+        #[serde(rename = "outline_colors")]
         pub r#outline_colors: ::std::vec::Vec<std_msgs::ColorRGBA>,
     }
     impl ::roslibrust_codegen::RosMessageType for ImageMarker {
@@ -2773,12 +3136,19 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct InteractiveMarker {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "pose")]
         pub r#pose: geometry_msgs::Pose,
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "description")]
         pub r#description: ::std::string::String,
+        #[serde(rename = "scale")]
         pub r#scale: f32,
+        #[serde(rename = "menu_entries")]
         pub r#menu_entries: ::std::vec::Vec<self::MenuEntry>,
+        #[serde(rename = "controls")]
         pub r#controls: ::std::vec::Vec<self::InteractiveMarkerControl>,
     }
     impl ::roslibrust_codegen::RosMessageType for InteractiveMarker {
@@ -2796,13 +3166,21 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct InteractiveMarkerControl {
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
+        #[serde(rename = "orientation")]
         pub r#orientation: geometry_msgs::Quaternion,
+        #[serde(rename = "orientation_mode")]
         pub r#orientation_mode: u8,
+        #[serde(rename = "interaction_mode")]
         pub r#interaction_mode: u8,
+        #[serde(rename = "always_visible")]
         pub r#always_visible: bool,
+        #[serde(rename = "markers")]
         pub r#markers: ::std::vec::Vec<self::Marker>,
+        #[serde(rename = "independent_marker_orientation")]
         pub r#independent_marker_orientation: bool,
+        #[serde(rename = "description")]
         pub r#description: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for InteractiveMarkerControl {
@@ -2835,14 +3213,23 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct InteractiveMarkerFeedback {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "client_id")]
         pub r#client_id: ::std::string::String,
+        #[serde(rename = "marker_name")]
         pub r#marker_name: ::std::string::String,
+        #[serde(rename = "control_name")]
         pub r#control_name: ::std::string::String,
+        #[serde(rename = "event_type")]
         pub r#event_type: u8,
+        #[serde(rename = "pose")]
         pub r#pose: geometry_msgs::Pose,
+        #[serde(rename = "menu_entry_id")]
         pub r#menu_entry_id: u32,
+        #[serde(rename = "mouse_point")]
         pub r#mouse_point: geometry_msgs::Point,
+        #[serde(rename = "mouse_point_valid")]
         pub r#mouse_point_valid: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for InteractiveMarkerFeedback {
@@ -2868,8 +3255,11 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct InteractiveMarkerInit {
+        #[serde(rename = "server_id")]
         pub r#server_id: ::std::string::String,
+        #[serde(rename = "seq_num")]
         pub r#seq_num: u64,
+        #[serde(rename = "markers")]
         pub r#markers: ::std::vec::Vec<self::InteractiveMarker>,
     }
     impl ::roslibrust_codegen::RosMessageType for InteractiveMarkerInit {
@@ -2887,8 +3277,11 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct InteractiveMarkerPose {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "pose")]
         pub r#pose: geometry_msgs::Pose,
+        #[serde(rename = "name")]
         pub r#name: ::std::string::String,
     }
     impl ::roslibrust_codegen::RosMessageType for InteractiveMarkerPose {
@@ -2906,11 +3299,17 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct InteractiveMarkerUpdate {
+        #[serde(rename = "server_id")]
         pub r#server_id: ::std::string::String,
+        #[serde(rename = "seq_num")]
         pub r#seq_num: u64,
+        #[serde(rename = "type")]
         pub r#type: u8,
+        #[serde(rename = "markers")]
         pub r#markers: ::std::vec::Vec<self::InteractiveMarker>,
+        #[serde(rename = "poses")]
         pub r#poses: ::std::vec::Vec<self::InteractiveMarkerPose>,
+        #[serde(rename = "erases")]
         pub r#erases: ::std::vec::Vec<::std::string::String>,
     }
     impl ::roslibrust_codegen::RosMessageType for InteractiveMarkerUpdate {
@@ -2932,24 +3331,43 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct Marker {
+        #[serde(rename = "header")]
         pub r#header: std_msgs::Header,
+        #[serde(rename = "ns")]
         pub r#ns: ::std::string::String,
+        #[serde(rename = "id")]
         pub r#id: i32,
+        #[serde(rename = "type")]
         pub r#type: i32,
+        #[serde(rename = "action")]
         pub r#action: i32,
+        #[serde(rename = "pose")]
         pub r#pose: geometry_msgs::Pose,
+        #[serde(rename = "scale")]
         pub r#scale: geometry_msgs::Vector3,
+        #[serde(rename = "color")]
         pub r#color: std_msgs::ColorRGBA,
+        #[serde(rename = "lifetime")]
         pub r#lifetime: ::roslibrust_codegen::integral_types::Duration,
+        #[serde(rename = "frame_locked")]
         pub r#frame_locked: bool,
+        #[serde(rename = "points")]
         pub r#points: ::std::vec::Vec<geometry_msgs::Point>,
+        #[serde(rename = "colors")]
         pub r#colors: ::std::vec::Vec<std_msgs::ColorRGBA>,
+        #[serde(rename = "texture_resource")]
         pub r#texture_resource: ::std::string::String,
+        #[serde(rename = "texture")]
         pub r#texture: sensor_msgs::CompressedImage,
+        #[serde(rename = "uv_coordinates")]
         pub r#uv_coordinates: ::std::vec::Vec<self::UVCoordinate>,
+        #[serde(rename = "text")]
         pub r#text: ::std::string::String,
+        #[serde(rename = "mesh_resource")]
         pub r#mesh_resource: ::std::string::String,
+        #[serde(rename = "mesh_file")]
         pub r#mesh_file: self::MeshFile,
+        #[serde(rename = "mesh_use_embedded_materials")]
         pub r#mesh_use_embedded_materials: bool,
     }
     impl ::roslibrust_codegen::RosMessageType for Marker {
@@ -2985,6 +3403,7 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct MarkerArray {
+        #[serde(rename = "markers")]
         pub r#markers: ::std::vec::Vec<self::Marker>,
     }
     impl ::roslibrust_codegen::RosMessageType for MarkerArray {
@@ -3002,10 +3421,15 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct MenuEntry {
+        #[serde(rename = "id")]
         pub r#id: u32,
+        #[serde(rename = "parent_id")]
         pub r#parent_id: u32,
+        #[serde(rename = "title")]
         pub r#title: ::std::string::String,
+        #[serde(rename = "command")]
         pub r#command: ::std::string::String,
+        #[serde(rename = "command_type")]
         pub r#command_type: u8,
     }
     impl ::roslibrust_codegen::RosMessageType for MenuEntry {
@@ -3028,7 +3452,9 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct MeshFile {
+        #[serde(rename = "filename")]
         pub r#filename: ::std::string::String,
+        #[serde(rename = "data")]
         pub r#data: ::std::vec::Vec<u8>,
     }
     impl ::roslibrust_codegen::RosMessageType for MeshFile {
@@ -3046,7 +3472,9 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct UVCoordinate {
+        #[serde(rename = "u")]
         pub r#u: f32,
+        #[serde(rename = "v")]
         pub r#v: f32,
     }
     impl ::roslibrust_codegen::RosMessageType for UVCoordinate {
@@ -3079,7 +3507,9 @@ pub mod visualization_msgs {
         PartialEq,
     )]
     pub struct GetInteractiveMarkersResponse {
+        #[serde(rename = "sequence_number")]
         pub r#sequence_number: u64,
+        #[serde(rename = "markers")]
         pub r#markers: ::std::vec::Vec<self::InteractiveMarker>,
     }
     impl ::roslibrust_codegen::RosMessageType for GetInteractiveMarkersResponse {
@@ -1,4 +1,4 @@
-use roslibrust_codegen::{RosMessageType, RosServiceType};
+use roslibrust_codegen::{RosActionType, RosMessageType, RosServiceType};
 use roslibrust_test::ros1::*;
 
 /// Ensures that associate constants are generated on the test_msgs correctly
@@ -9,6 +9,17 @@ fn test_associated_constants() {
     let _ = actionlib_msgs::GoalStatus::REJECTED;
 }
 
+/// Actions are represented by their Action message, which can't clash with a service of the same name
+#[test]
+fn test_action_types() {
+    assert_eq!(nav_msgs::GetMapAction::ROS_ACTION_NAME, "nav_msgs/GetMap");
+    assert_eq!(nav_msgs::GetMap::ROS_SERVICE_NAME, "nav_msgs/GetMap");
+    assert_eq!(
+        <nav_msgs::GetMapAction as RosActionType>::ActionGoal::MD5SUM,
+        nav_msgs::GetMapActionGoal::MD5SUM
+    );
+}
+
 #[test]
 fn test_md5sum_generation() {
     assert_eq!(std_msgs::Header::MD5SUM, "2176decaecbce78abc3b96ef049fabed");
@@ -25,3 +36,40 @@ fn test_md5sum_generation() {
         "060021388200f6f0f447d0fcd9c64743"
     );
 }
+
+/// A hand written message, whose md5sum is computed from its definition
+#[derive(
+    serde::Serialize, serde::Deserialize, Debug, Clone, roslibrust_codegen_macro::RosMessageType,
+)]
+#[ros(type_name = "std_msgs/String", definition = "string data")]
+struct HandWrittenString {
+    data: String,
+}
+
+/// A hand written message containing another message, which needs its md5sum given
+#[derive(
+    serde::Serialize, serde::Deserialize, Debug, Clone, roslibrust_codegen_macro::RosMessageType,
+)]
+#[ros(
+    type_name = "geometry_msgs/PointStamped",
+    md5sum = "c63aecb41bfdfd6b7e1fac37c7cbe7bf",
+    definition = "Header header\nPoint point"
+)]
+struct HandWrittenPointStamped {
+    header: std_msgs::Header,
+    point: geometry_msgs::Point,
+}
+
+#[test]
+fn test_derived_message_types() {
+    assert_eq!(
+        HandWrittenString::ROS_TYPE_NAME,
+        std_msgs::String::ROS_TYPE_NAME
+    );
+    assert_eq!(HandWrittenString::MD5SUM, std_msgs::String::MD5SUM);
+    assert_eq!(HandWrittenString::DEFINITION, "string data");
+    assert_eq!(
+        HandWrittenPointStamped::MD5SUM,
+        geometry_msgs::PointStamped::MD5SUM
+    );
+}